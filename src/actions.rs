@@ -0,0 +1,401 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+use serde::Deserialize;
+
+/// Capability profile chosen with `profile` in `config.toml`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Profile {
+    /// Everything the user's privileges allow
+    #[default]
+    Admin,
+    /// For kiosks and shared accounts: browsing, searching and previewing
+    /// only, without root tools, shells, custom commands or launching
+    /// other programs
+    Basic,
+}
+
+/// When quitting asks for a second `q`, chosen with `confirm_quit` in
+/// `config.toml`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuitConfirm {
+    /// Quit at once
+    #[default]
+    Never,
+    /// Always press `q` twice
+    Twice,
+    /// Only while background work is running or items are selected
+    Busy,
+}
+
+impl QuitConfirm {
+    /// What to show before quitting, or `None` to quit now
+    pub fn prompt(self, jobs: usize, selected: usize) -> Option<String> {
+        let mut busy = Vec::new();
+        if jobs > 0 {
+            busy.push(format!(
+                "{} background job{} running",
+                jobs,
+                if jobs == 1 { "" } else { "s" }
+            ));
+        }
+        if selected > 0 {
+            busy.push(format!(
+                "{} item{} selected",
+                selected,
+                if selected == 1 { "" } else { "s" }
+            ));
+        }
+
+        match self {
+            QuitConfirm::Never => None,
+            QuitConfirm::Busy if busy.is_empty() => None,
+            QuitConfirm::Twice if busy.is_empty() => Some("Press q again to quit".to_string()),
+            _ => Some(format!("⚠️  {}: press q again to quit", busy.join(", "))),
+        }
+    }
+}
+
+/// Every user-facing command of the browse view
+///
+/// Keybindings and the command palette both resolve to an `Action`, which
+/// `Navigator::perform` dispatches in a single place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    OpenSelected,
+    QuickView,
+    Pager,
+    Diff,
+    OpenExternally,
+    OpenWith,
+    Search,
+    FuzzyFind,
+    Filter,
+    FilterByExtension,
+    DirectoryStats,
+    TogglePreview,
+    ToggleHidden,
+    GoToRealPath,
+    JumpToHotFile,
+    VerifyChecksums,
+    ExtractArchive,
+    ToggleRelativeDates,
+    CycleSort,
+    SortByName,
+    SortBySize,
+    SortByModified,
+    CycleGrouping,
+    OpenBookmarks,
+    AddBookmark,
+    TogglePin,
+    SplitPane,
+    OpenInSplit,
+    TakeSnapshot,
+    CompareSnapshot,
+    Monitor,
+    HexEditor,
+    UnitStatus,
+    ContainerVolumes,
+    SelectMode,
+    PatternSelect,
+    OpenChmod,
+    OpenChown,
+    RecordWorkflow,
+    Workflows,
+    Sessions,
+    SpawnShell,
+    CommandPalette,
+    /// Index into the configured custom commands
+    Custom(usize),
+    Quit,
+}
+
+impl Action {
+    /// All actions in the order they are listed in the command palette
+    pub const ALL: &'static [Action] = &[
+        Action::OpenSelected,
+        Action::QuickView,
+        Action::Pager,
+        Action::Diff,
+        Action::OpenExternally,
+        Action::OpenWith,
+        Action::Search,
+        Action::FuzzyFind,
+        Action::Filter,
+        Action::FilterByExtension,
+        Action::DirectoryStats,
+        Action::TogglePreview,
+        Action::ToggleHidden,
+        Action::GoToRealPath,
+        Action::JumpToHotFile,
+        Action::VerifyChecksums,
+        Action::ExtractArchive,
+        Action::ToggleRelativeDates,
+        Action::CycleSort,
+        Action::SortByName,
+        Action::SortBySize,
+        Action::SortByModified,
+        Action::CycleGrouping,
+        Action::OpenBookmarks,
+        Action::AddBookmark,
+        Action::TogglePin,
+        Action::SplitPane,
+        Action::OpenInSplit,
+        Action::TakeSnapshot,
+        Action::CompareSnapshot,
+        Action::Monitor,
+        Action::HexEditor,
+        Action::UnitStatus,
+        Action::ContainerVolumes,
+        Action::SelectMode,
+        Action::PatternSelect,
+        Action::OpenChmod,
+        Action::OpenChown,
+        Action::RecordWorkflow,
+        Action::Workflows,
+        Action::Sessions,
+        Action::SpawnShell,
+        Action::Quit,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::OpenSelected => "Open selected entry",
+            Action::QuickView => "Quick view",
+            Action::Pager => "View file in full-screen pager",
+            Action::Diff => "Compare the two selected files",
+            Action::OpenExternally => "Open with default application",
+            Action::OpenWith => "Open with…",
+            Action::Search => "Search files",
+            Action::FuzzyFind => "Fuzzy find (jump to file)",
+            Action::Filter => "Filter listing as you type",
+            Action::FilterByExtension => "Filter listing to this extension",
+            Action::DirectoryStats => "Directory statistics by extension",
+            Action::TogglePreview => "Toggle preview panel",
+            Action::ToggleHidden => "Toggle hidden files",
+            Action::GoToRealPath => "Go to the real path, resolving symbolic links",
+            Action::JumpToHotFile => "Jump to the newest file in a hot directory",
+            Action::VerifyChecksums => "Verify checksum files (.sha256, .md5) in this directory",
+            Action::ExtractArchive => "Extract the highlighted archive here",
+            Action::ToggleRelativeDates => "Toggle relative dates",
+            Action::CycleSort => "Cycle sort order",
+            Action::SortByName => "Sort by name",
+            Action::SortBySize => "Sort by size",
+            Action::SortByModified => "Sort by modification time",
+            Action::CycleGrouping => "Cycle grouping (dirs first, mixed, files first)",
+            Action::OpenBookmarks => "Open bookmarks",
+            Action::AddBookmark => "Bookmark current directory",
+            Action::TogglePin => "Pin/unpin entry at top of directory",
+            Action::SplitPane => "Split-pane view",
+            Action::OpenInSplit => "Open directory in the right split pane",
+            Action::TakeSnapshot => "Snapshot directory tree",
+            Action::CompareSnapshot => "Compare directory tree to snapshot",
+            Action::Monitor => "Monitor directory for changes",
+            Action::HexEditor => "Hex view / editor",
+            Action::UnitStatus => "Show systemd unit status",
+            Action::ContainerVolumes => "Container volumes (Docker/Podman)",
+            Action::SelectMode => "Selection mode",
+            Action::PatternSelect => "Select by pattern",
+            Action::OpenChmod => "Change permissions (chmod)",
+            Action::OpenChown => "Change ownership (chown)",
+            Action::RecordWorkflow => "Start or stop recording a workflow",
+            Action::Workflows => "Replay a saved workflow",
+            Action::Sessions => "Save or restore a named session",
+            Action::SpawnShell => "Spawn shell here",
+            Action::CommandPalette => "Command palette",
+            Action::Custom(_) => "Custom command",
+            Action::Quit => "Quit",
+        }
+    }
+
+    pub fn key_hint(self) -> &'static str {
+        match self {
+            Action::OpenSelected => "Enter",
+            Action::QuickView => "Space",
+            Action::Pager => "i / F3",
+            Action::Diff => "D",
+            Action::OpenExternally => "x",
+            Action::OpenWith => "X",
+            Action::Search => "Ctrl+F",
+            Action::FuzzyFind => "Ctrl+T",
+            Action::Filter => "/",
+            Action::FilterByExtension => "E",
+            Action::DirectoryStats => "I",
+            Action::TogglePreview => "Ctrl+P",
+            Action::ToggleHidden => ".",
+            Action::GoToRealPath => "R",
+            Action::JumpToHotFile => "N",
+            Action::VerifyChecksums => "V",
+            Action::ExtractArchive => "Z",
+            Action::ToggleRelativeDates => "t",
+            Action::CycleSort => "Ctrl+S",
+            Action::SortByName | Action::SortBySize | Action::SortByModified => "",
+            Action::CycleGrouping => "Ctrl+O",
+            Action::OpenBookmarks => "Ctrl+B",
+            Action::AddBookmark => "",
+            Action::TogglePin => "P",
+            Action::SplitPane => "F2",
+            Action::OpenInSplit => "O",
+            Action::TakeSnapshot | Action::CompareSnapshot => "",
+            Action::Monitor => "M",
+            Action::HexEditor => "H",
+            Action::UnitStatus => "U",
+            Action::ContainerVolumes => "",
+            Action::SelectMode => "s",
+            Action::PatternSelect => "p",
+            Action::OpenChmod => "c",
+            Action::OpenChown => "o",
+            Action::RecordWorkflow => "w",
+            Action::Workflows => "W",
+            Action::Sessions => "F4",
+            Action::SpawnShell => "S",
+            Action::CommandPalette => ":",
+            Action::Custom(_) => "",
+            Action::Quit => "q",
+        }
+    }
+
+    /// Chmod and chown are left out: other users get chmod for the files
+    /// they own and chown as a simulation
+    pub fn requires_root(self) -> bool {
+        matches!(self, Action::SelectMode | Action::PatternSelect)
+    }
+
+    /// Actions that change files or hand control to another program,
+    /// withheld from the basic profile along with the root-only ones
+    pub fn is_privileged(self) -> bool {
+        self.requires_root()
+            || matches!(
+                self,
+                Action::SpawnShell
+                    | Action::OpenChmod
+                    | Action::OpenChown
+                    | Action::ExtractArchive
+                    | Action::RecordWorkflow
+                    | Action::Workflows
+                    | Action::OpenExternally
+                    | Action::OpenWith
+                    | Action::UnitStatus
+                    | Action::ContainerVolumes
+                    | Action::Custom(_)
+            )
+    }
+
+    /// Whether the action is offered at all to this user and profile
+    pub fn is_available(self, is_root: bool, profile: Profile) -> bool {
+        (is_root || !self.requires_root()) && (profile == Profile::Admin || !self.is_privileged())
+    }
+
+    /// Resolve a key press in browse mode
+    pub fn from_browse_key(code: KeyCode, modifiers: KeyModifiers) -> Option<Action> {
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        let action = match code {
+            KeyCode::Enter => Action::OpenSelected,
+            KeyCode::Char(' ') => Action::QuickView,
+            KeyCode::Char('f') if ctrl => Action::Search,
+            KeyCode::Char('t') if ctrl => Action::FuzzyFind,
+            KeyCode::Char('b') if ctrl => Action::OpenBookmarks,
+            KeyCode::Char('s') if ctrl => Action::CycleSort,
+            KeyCode::Char('o') if ctrl => Action::CycleGrouping,
+            KeyCode::Char('d') if ctrl => Action::SpawnShell,
+            // Ctrl+Shift+P, as reported by terminals that distinguish it
+            KeyCode::Char('P') if ctrl => Action::CommandPalette,
+            KeyCode::Char('p') if ctrl && modifiers.contains(KeyModifiers::SHIFT) => {
+                Action::CommandPalette
+            }
+            KeyCode::Char('p') if ctrl => Action::TogglePreview,
+            KeyCode::F(2) => Action::SplitPane,
+            KeyCode::F(3) => Action::Pager,
+            KeyCode::F(4) => Action::Sessions,
+            KeyCode::Char(':') => Action::CommandPalette,
+            KeyCode::Char('/') => Action::Filter,
+            KeyCode::Char('.') => Action::ToggleHidden,
+            KeyCode::Char('R') => Action::GoToRealPath,
+            KeyCode::Char('N') => Action::JumpToHotFile,
+            KeyCode::Char('V') => Action::VerifyChecksums,
+            KeyCode::Char('Z') => Action::ExtractArchive,
+            KeyCode::Char('t') => Action::ToggleRelativeDates,
+            KeyCode::Char('x') => Action::OpenExternally,
+            KeyCode::Char('X') => Action::OpenWith,
+            KeyCode::Char('s') => Action::SelectMode,
+            KeyCode::Char('p') => Action::PatternSelect,
+            KeyCode::Char('i') => Action::Pager,
+            KeyCode::Char('c') => Action::OpenChmod,
+            KeyCode::Char('o') => Action::OpenChown,
+            KeyCode::Char('w') => Action::RecordWorkflow,
+            KeyCode::Char('W') => Action::Workflows,
+            KeyCode::Char('S') => Action::SpawnShell,
+            KeyCode::Char('P') => Action::TogglePin,
+            KeyCode::Char('M') => Action::Monitor,
+            KeyCode::Char('H') => Action::HexEditor,
+            KeyCode::Char('D') => Action::Diff,
+            KeyCode::Char('E') => Action::FilterByExtension,
+            KeyCode::Char('I') => Action::DirectoryStats,
+            KeyCode::Char('U') => Action::UnitStatus,
+            KeyCode::Char('O') => Action::OpenInSplit,
+            KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
+            _ => return None,
+        };
+        Some(action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_browse_bindings() {
+        assert_eq!(
+            Action::from_browse_key(KeyCode::Char('p'), KeyModifiers::CONTROL),
+            Some(Action::TogglePreview)
+        );
+        assert_eq!(
+            Action::from_browse_key(
+                KeyCode::Char('p'),
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT
+            ),
+            Some(Action::CommandPalette)
+        );
+        assert_eq!(
+            Action::from_browse_key(KeyCode::Char('p'), KeyModifiers::NONE),
+            Some(Action::PatternSelect)
+        );
+        assert_eq!(
+            Action::from_browse_key(KeyCode::Char('z'), KeyModifiers::NONE),
+            None
+        );
+    }
+
+    #[test]
+    fn test_permission_managers_offered_to_other_users() {
+        assert!(Action::OpenChown.is_available(false, Profile::Admin));
+        assert!(!Action::OpenChown.is_available(false, Profile::Basic));
+        assert!(Action::OpenChmod.is_available(false, Profile::Admin));
+        assert!(!Action::OpenChmod.is_available(false, Profile::Basic));
+        assert!(!Action::SelectMode.is_available(false, Profile::Admin));
+    }
+
+    #[test]
+    fn test_palette_excludes_itself() {
+        assert!(!Action::ALL.contains(&Action::CommandPalette));
+    }
+
+    #[test]
+    fn test_quit_prompt() {
+        assert_eq!(QuitConfirm::Never.prompt(2, 3), None);
+        assert_eq!(QuitConfirm::Busy.prompt(0, 0), None);
+        assert_eq!(
+            QuitConfirm::Twice.prompt(0, 0).as_deref(),
+            Some("Press q again to quit")
+        );
+        assert_eq!(
+            QuitConfirm::Busy.prompt(1, 0).as_deref(),
+            Some("⚠️  1 background job running: press q again to quit")
+        );
+        assert_eq!(
+            QuitConfirm::Twice.prompt(2, 3).as_deref(),
+            Some("⚠️  2 background jobs running, 3 items selected: press q again to quit")
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Separates an archive from the path of an entry inside it, as in
+/// `logs.tar.gz!/var/log/syslog`
+pub const ENTRY_SEPARATOR: &str = "!/";
+
+/// Archives larger than this on disk are not opened
+const MAX_ARCHIVE_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Archives opened along one path: the file itself and archives inside it
+const MAX_ARCHIVE_DEPTH: usize = 2;
+
+/// Entries read from one archive before giving up on the rest
+const MAX_ARCHIVE_ENTRIES: usize = 10_000;
+
+/// Larger entries are skipped
+const MAX_ENTRY_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Whether `name` is a tarball fsnav can read: `.tar`, `.tar.gz` or `.tgz`
+pub fn is_tar_archive(name: &str) -> bool {
+    let name = name.to_lowercase();
+    [".tar", ".tar.gz", ".tgz"]
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+}
+
+/// `name` without its `.tar`, `.tar.gz` or `.tgz` suffix, for the
+/// directory an archive is extracted into
+pub fn archive_stem(name: &str) -> &str {
+    [".tar.gz", ".tgz", ".tar"]
+        .iter()
+        .find_map(|suffix| {
+            let stem = name.len().checked_sub(suffix.len())?;
+            let found = name.get(stem..)?.eq_ignore_ascii_case(suffix);
+            found.then(|| &name[..stem])
+        })
+        .filter(|stem| !stem.is_empty())
+        .unwrap_or(name)
+}
+
+/// `reader` gunzipped when `name` says it is compressed
+pub fn decoder<'a>(reader: impl Read + 'a, name: &str) -> Box<dyn Read + 'a> {
+    let name = name.to_lowercase();
+    if name.ends_with(".gz") || name.ends_with(".tgz") {
+        Box::new(GzDecoder::new(reader))
+    } else {
+        Box::new(reader)
+    }
+}
+
+/// Hand every text file of the archive at `path` to `visit` with its path
+/// inside the archive; archives within it are opened too, their entries
+/// named like `inner.tar!/file`. Stops early when `visit` returns false.
+pub fn for_each_text_entry(
+    path: &Path,
+    cancel: &AtomicBool,
+    visit: &mut dyn FnMut(&str, &str) -> bool,
+) -> io::Result<()> {
+    let file = File::open(path)?;
+    if file.metadata()?.len() > MAX_ARCHIVE_BYTES {
+        return Ok(());
+    }
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    walk(decoder(file, &name), "", 0, cancel, visit)?;
+    Ok(())
+}
+
+/// Text of the file at `inner` in the archive at `path`
+pub fn read_entry(path: &Path, inner: &str) -> io::Result<Option<String>> {
+    let mut found = None;
+    for_each_text_entry(path, &AtomicBool::new(false), &mut |name, text| {
+        if name == inner {
+            found = Some(text.to_string());
+        }
+        found.is_none()
+    })?;
+    Ok(found)
+}
+
+/// Returns false once `visit` or `cancel` asked to stop
+fn walk(
+    reader: Box<dyn Read + '_>,
+    prefix: &str,
+    depth: usize,
+    cancel: &AtomicBool,
+    visit: &mut dyn FnMut(&str, &str) -> bool,
+) -> io::Result<bool> {
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries()?.take(MAX_ARCHIVE_ENTRIES) {
+        if cancel.load(Ordering::Relaxed) {
+            return Ok(false);
+        }
+        let mut entry = entry?;
+        if entry.header().entry_type() != tar::EntryType::Regular || entry.size() > MAX_ENTRY_BYTES
+        {
+            continue;
+        }
+        let name = format!("{}{}", prefix, entry.path()?.to_string_lossy());
+
+        if is_tar_archive(&name) {
+            if depth + 1 < MAX_ARCHIVE_DEPTH {
+                let prefix = format!("{}{}", name, ENTRY_SEPARATOR);
+                if !walk(
+                    decoder(&mut entry, &name),
+                    &prefix,
+                    depth + 1,
+                    cancel,
+                    visit,
+                )? {
+                    return Ok(false);
+                }
+            }
+            continue;
+        }
+
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes)?;
+        // NUL bytes near the start mark a binary file
+        if bytes.iter().take(512).any(|&b| b == 0) {
+            continue;
+        }
+        if !visit(&name, &String::from_utf8_lossy(&bytes)) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    /// A tarball holding `files`, gzipped when `gzip` is set
+    pub(crate) fn tarball(files: &[(&str, &[u8])], gzip: bool) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, *content).unwrap();
+        }
+        let tar = builder.into_inner().unwrap();
+        if !gzip {
+            return tar;
+        }
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&tar).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_walk_nested_archive() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let nested = tarball(&[("deep.txt", b"deep")], false);
+        let path = temp_dir.path().join("logs.tar.gz");
+        std::fs::write(
+            &path,
+            tarball(
+                &[
+                    ("etc/app.conf", b"port = 80\n"),
+                    ("bin/app", b"\x7fELF\0\0"),
+                    ("old.tar", &nested),
+                ],
+                true,
+            ),
+        )
+        .unwrap();
+
+        let mut seen = Vec::new();
+        for_each_text_entry(&path, &AtomicBool::new(false), &mut |name, text| {
+            seen.push((name.to_string(), text.to_string()));
+            true
+        })
+        .unwrap();
+        assert_eq!(
+            seen,
+            vec![
+                ("etc/app.conf".to_string(), "port = 80\n".to_string()),
+                ("old.tar!/deep.txt".to_string(), "deep".to_string()),
+            ]
+        );
+
+        assert_eq!(
+            read_entry(&path, "old.tar!/deep.txt").unwrap().as_deref(),
+            Some("deep")
+        );
+        assert_eq!(read_entry(&path, "missing").unwrap(), None);
+        assert!(is_tar_archive("backup.TGZ"));
+        assert!(!is_tar_archive("notes.gz"));
+        assert_eq!(archive_stem("Backup.TGZ"), "Backup");
+        assert_eq!(archive_stem("src-1.2.tar.gz"), "src-1.2");
+        assert_eq!(archive_stem(".tar"), ".tar");
+    }
+}
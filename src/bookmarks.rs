@@ -1,8 +1,38 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::search::fuzzy_score;
+use crate::tasks::Task;
+use crate::utils::{config_dir, home_dir, lock_file};
+
+/// Format of `bookmarks.json`; version 1 files, from before tags, are
+/// rewritten on load
+const BOOKMARKS_VERSION: u32 = 2;
+
+/// Read-only bookmarks shipped to every user of the machine, listed after
+/// their own
+pub const SHARED_BOOKMARKS_PATH: &str = "/etc/fsnav/bookmarks.toml";
+
+/// Visits after which a directory is suggested as a bookmark
+const SUGGEST_AFTER: usize = 5;
+
+/// Suggestions shown at once, most visited first
+const SUGGESTIONS_SHOWN: usize = 3;
+
+/// Directories `visits.json` keeps counts for; the least visited go first
+const VISITS_KEPT: usize = 500;
+
+/// How long a bookmark's target may take to answer before it is shown as
+/// unreachable
+const UNREACHABLE_AFTER: Duration = Duration::from_secs(3);
+
+/// How often targets are checked again while the bookmarks screen is open
+const RECHECK_EVERY: Duration = Duration::from_secs(30);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Bookmark {
@@ -12,24 +42,62 @@ pub struct Bookmark {
     pub created_at: std::time::SystemTime,
     pub last_accessed: Option<std::time::SystemTime>,
     pub access_count: usize,
+    /// Lowercase labels such as `work` or `servers`; the first one is the
+    /// category the bookmarks screen groups it under
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Watched in the background for new files
+    #[serde(default)]
+    pub hot: bool,
+    /// Comes from [`SHARED_BOOKMARKS_PATH`]: never saved, renamed, retagged
+    /// or deleted
+    #[serde(skip)]
+    pub shared: bool,
+}
+
+impl Bookmark {
+    pub fn category(&self) -> Option<&str> {
+        self.tags.first().map(String::as_str)
+    }
+}
+
+/// Tags typed as `work, servers` or `#work #servers`: lowercased, without
+/// duplicates, in the order given
+pub fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in text.split(|c: char| c == ',' || c.is_whitespace()) {
+        let tag = tag.trim_start_matches('#').to_lowercase();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BookmarksManager {
+    /// The user's bookmarks, then the shared ones
     bookmarks: Vec<Bookmark>,
     shortcuts: HashMap<char, usize>, // Maps shortcut to bookmark index
     config_path: PathBuf,
+    /// Why the shared bookmarks file could not be read
+    shared_error: Option<String>,
+    /// The user's bookmarks as last read from or written to disk, to tell
+    /// changes made here from the ones another instance saved
+    baseline: Vec<Bookmark>,
 }
 
 impl BookmarksManager {
     pub fn new() -> Result<Self> {
-        let config_dir = Self::get_config_dir()?;
+        let config_dir = config_dir()?;
         let config_path = config_dir.join("bookmarks.json");
 
         let mut manager = Self {
             bookmarks: Vec::new(),
             shortcuts: HashMap::new(),
             config_path,
+            shared_error: None,
+            baseline: Vec::new(),
         };
 
         // Load existing bookmarks if file exists
@@ -41,24 +109,68 @@ impl BookmarksManager {
             manager.save()?;
         }
 
+        manager.load_shared(Path::new(SHARED_BOOKMARKS_PATH));
         Ok(manager)
     }
 
-    fn get_config_dir() -> Result<PathBuf> {
-        let home = dirs::home_dir().context("Failed to get home directory")?;
-        let config_dir = home.join(".config").join("fsnav");
+    /// Append the bookmarks of a shared file, skipping paths the user has
+    /// bookmarked already; a shortcut the user has taken stays theirs
+    fn load_shared(&mut self, path: &Path) {
+        if !path.exists() {
+            return;
+        }
+        let shared = match read_shared_bookmarks(path) {
+            Ok(shared) => shared,
+            Err(e) => {
+                self.shared_error = Some(format!("{}: {:#}", path.display(), e));
+                return;
+            }
+        };
+
+        for bookmark in shared {
+            if !self.bookmarks.iter().any(|b| b.path == bookmark.path) {
+                self.bookmarks.push(bookmark);
+            }
+        }
+        self.rebuild_shortcuts();
+    }
+
+    /// Why the shared bookmarks could not be loaded, if they could not
+    pub fn shared_error(&self) -> Option<&str> {
+        self.shared_error.as_deref()
+    }
 
-        // Create directory if it doesn't exist
-        if !config_dir.exists() {
-            fs::create_dir_all(&config_dir)?;
+    fn user_count(&self) -> usize {
+        self.bookmarks.iter().take_while(|b| !b.shared).count()
+    }
+
+    /// The index of one of the user's own bookmarks, which may be changed
+    fn editable(&self, index: usize) -> Result<usize> {
+        match self.bookmarks.get(index) {
+            None => Err(anyhow::anyhow!("Invalid bookmark index")),
+            Some(bookmark) if bookmark.shared => Err(anyhow::anyhow!(
+                "'{}' is a shared bookmark from {} and cannot be changed",
+                bookmark.name,
+                SHARED_BOOKMARKS_PATH
+            )),
+            Some(_) => Ok(index),
         }
+    }
 
-        Ok(config_dir)
+    /// Shortcuts go to the first bookmark claiming them, so the user's own
+    /// win over shared ones
+    fn rebuild_shortcuts(&mut self) {
+        self.shortcuts.clear();
+        for (index, bookmark) in self.bookmarks.iter().enumerate() {
+            if let Some(key) = bookmark.shortcut {
+                self.shortcuts.entry(key).or_insert(index);
+            }
+        }
     }
 
     fn create_default_bookmarks(&mut self) {
         // Add common directories as default bookmarks
-        if let Some(home) = dirs::home_dir() {
+        if let Some(home) = home_dir() {
             self.add_bookmark_internal("Home".to_string(), home.clone(), Some('h'));
 
             let downloads = home.join("Downloads");
@@ -102,14 +214,15 @@ impl BookmarksManager {
             created_at: std::time::SystemTime::now(),
             last_accessed: None,
             access_count: 0,
+            tags: Vec::new(),
+            hot: false,
+            shared: false,
         };
 
-        let index = self.bookmarks.len();
-        self.bookmarks.push(bookmark);
-
-        if let Some(key) = shortcut {
-            self.shortcuts.insert(key, index);
-        }
+        // Before the shared bookmarks
+        let index = self.user_count();
+        self.bookmarks.insert(index, bookmark);
+        self.rebuild_shortcuts();
     }
 
     pub fn add_bookmark(
@@ -128,9 +241,13 @@ impl BookmarksManager {
             return Err(anyhow::anyhow!("Bookmark already exists for this path"));
         }
 
-        // Check if shortcut is already taken
+        // Check if shortcut is already taken; a shared bookmark gives it up
         if let Some(key) = shortcut {
-            if self.shortcuts.contains_key(&key) {
+            if self
+                .shortcuts
+                .get(&key)
+                .is_some_and(|&index| !self.bookmarks[index].shared)
+            {
                 return Err(anyhow::anyhow!("Shortcut '{}' is already in use", key));
             }
         }
@@ -141,88 +258,223 @@ impl BookmarksManager {
     }
 
     pub fn remove_bookmark(&mut self, index: usize) -> Result<()> {
-        if index >= self.bookmarks.len() {
-            return Err(anyhow::anyhow!("Invalid bookmark index"));
-        }
-
-        let bookmark = self.bookmarks.remove(index);
+        let index = self.editable(index)?;
 
-        // Remove shortcut if it exists
-        if let Some(key) = bookmark.shortcut {
-            self.shortcuts.remove(&key);
-        }
+        self.bookmarks.remove(index);
 
-        // Update shortcut indices for remaining bookmarks
-        self.shortcuts = self
-            .shortcuts
-            .iter()
-            .map(|(&k, &v)| if v > index { (k, v - 1) } else { (k, v) })
-            .collect();
+        // A shared bookmark may get back a shortcut freed by this one
+        self.rebuild_shortcuts();
 
         self.save()?;
         Ok(())
     }
 
-    pub fn rename_bookmark(&mut self, index: usize, new_name: String) -> Result<()> {
-        if index >= self.bookmarks.len() {
-            return Err(anyhow::anyhow!("Invalid bookmark index"));
+    /// The user's bookmarks whose target `health` found missing or not a
+    /// directory; shared ones are left to whoever ships them
+    pub fn dead_bookmarks(&self, health: &BookmarkHealth) -> Vec<&Bookmark> {
+        self.bookmarks
+            .iter()
+            .filter(|b| !b.shared && health.is_dead(&b.path))
+            .collect()
+    }
+
+    /// Remove every bookmark [`dead_bookmarks`](Self::dead_bookmarks)
+    /// lists, saving once; returns how many went
+    pub fn prune_dead(&mut self, health: &BookmarkHealth) -> Result<usize> {
+        let before = self.bookmarks.len();
+        self.bookmarks
+            .retain(|b| b.shared || !health.is_dead(&b.path));
+        let removed = before - self.bookmarks.len();
+        if removed > 0 {
+            self.rebuild_shortcuts();
+            self.save()?;
         }
+        Ok(removed)
+    }
+
+    pub fn rename_bookmark(&mut self, index: usize, new_name: String) -> Result<()> {
+        let index = self.editable(index)?;
 
         self.bookmarks[index].name = new_name;
         self.save()?;
         Ok(())
     }
 
-    #[allow(dead_code)]
-    pub fn update_shortcut(&mut self, index: usize, new_shortcut: Option<char>) -> Result<()> {
-        if index >= self.bookmarks.len() {
-            return Err(anyhow::anyhow!("Invalid bookmark index"));
-        }
+    /// Point bookmark `index` at another directory
+    pub fn set_path(&mut self, index: usize, path: PathBuf) -> Result<()> {
+        let index = self.editable(index)?;
 
-        // Remove old shortcut
-        if let Some(old_key) = self.bookmarks[index].shortcut {
-            self.shortcuts.remove(&old_key);
+        if !path.is_absolute() {
+            return Err(anyhow::anyhow!("Path must be absolute: {}", path.display()));
+        }
+        if !path.is_dir() {
+            return Err(anyhow::anyhow!("Not a directory: {}", path.display()));
+        }
+        if self
+            .bookmarks
+            .iter()
+            .enumerate()
+            .any(|(i, b)| i != index && b.path == path)
+        {
+            return Err(anyhow::anyhow!("Bookmark already exists for this path"));
         }
 
+        self.bookmarks[index].path = path;
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn set_tags(&mut self, index: usize, tags: Vec<String>) -> Result<()> {
+        let index = self.editable(index)?;
+
+        self.bookmarks[index].tags = tags;
+        self.save()?;
+        Ok(())
+    }
+
+    /// Mark bookmark `index` hot, or no longer; returns the new state
+    pub fn toggle_hot(&mut self, index: usize) -> Result<bool> {
+        let index = self.editable(index)?;
+
+        let bookmark = &mut self.bookmarks[index];
+        bookmark.hot = !bookmark.hot;
+        let hot = bookmark.hot;
+        self.save()?;
+        Ok(hot)
+    }
+
+    /// Directories of the hot bookmarks
+    pub fn hot_dirs(&self) -> Vec<PathBuf> {
+        self.bookmarks
+            .iter()
+            .filter(|bookmark| bookmark.hot)
+            .map(|bookmark| bookmark.path.clone())
+            .collect()
+    }
+
+    /// Every tag in use, sorted
+    pub fn all_tags(&self) -> Vec<&str> {
+        let mut tags: Vec<&str> = self
+            .bookmarks
+            .iter()
+            .flat_map(|bookmark| bookmark.tags.iter().map(String::as_str))
+            .collect();
+        tags.sort_unstable();
+        tags.dedup();
+        tags
+    }
+
+    /// Bind bookmark `index` to `new_shortcut`, or unbind it; a shortcut
+    /// another of the user's bookmarks holds is refused, one a shared
+    /// bookmark holds is taken over
+    pub fn update_shortcut(&mut self, index: usize, new_shortcut: Option<char>) -> Result<()> {
+        let index = self.editable(index)?;
+
         // Check if new shortcut is already taken
         if let Some(key) = new_shortcut {
-            if self.shortcuts.contains_key(&key) {
-                return Err(anyhow::anyhow!("Shortcut '{}' is already in use", key));
+            if !is_valid_shortcut(key) {
+                return Err(anyhow::anyhow!(
+                    "Shortcuts are lowercase letters and digits, not '{}'",
+                    key
+                ));
+            }
+            if let Some(owner) = self.shortcut_owner(key) {
+                if owner != index && !self.bookmarks[owner].shared {
+                    return Err(anyhow::anyhow!(
+                        "Shortcut '{}' is already used by '{}'",
+                        key,
+                        self.bookmarks[owner].name
+                    ));
+                }
             }
-            self.shortcuts.insert(key, index);
         }
 
         self.bookmarks[index].shortcut = new_shortcut;
+        self.rebuild_shortcuts();
         self.save()?;
         Ok(())
     }
 
-    pub fn get_bookmark_by_shortcut(&mut self, shortcut: char) -> Option<&Bookmark> {
-        if let Some(&index) = self.shortcuts.get(&shortcut) {
-            if let Some(bookmark) = self.bookmarks.get_mut(index) {
-                bookmark.last_accessed = Some(std::time::SystemTime::now());
-                bookmark.access_count += 1;
-                let _ = self.save(); // Ignore save errors for access updates
-                return self.bookmarks.get(index);
-            }
-        }
-        None
+    /// Index of the bookmark `shortcut` jumps to
+    pub fn shortcut_owner(&self, shortcut: char) -> Option<usize> {
+        self.shortcuts.get(&shortcut).copied()
+    }
+
+    /// The bookmark bound to `shortcut`, counted as accessed unless
+    /// `health` knows its target to be unavailable
+    pub fn get_bookmark_by_shortcut(
+        &mut self,
+        shortcut: char,
+        health: &BookmarkHealth,
+    ) -> Option<&Bookmark> {
+        let index = *self.shortcuts.get(&shortcut)?;
+        self.get_bookmark_by_index(index, health)
     }
 
-    pub fn get_bookmark_by_index(&mut self, index: usize) -> Option<&Bookmark> {
-        if let Some(bookmark) = self.bookmarks.get_mut(index) {
-            bookmark.last_accessed = Some(std::time::SystemTime::now());
-            bookmark.access_count += 1;
-            let _ = self.save(); // Ignore save errors for access updates
+    pub fn get_bookmark_by_index(
+        &mut self,
+        index: usize,
+        health: &BookmarkHealth,
+    ) -> Option<&Bookmark> {
+        let bookmark = self.bookmarks.get_mut(index)?;
+        // An unreachable mount would only stall the write or count a visit
+        // that never happened
+        if !health.is_usable(&bookmark.path) {
             return self.bookmarks.get(index);
         }
-        None
+        bookmark.last_accessed = Some(std::time::SystemTime::now());
+        bookmark.access_count += 1;
+        let path = bookmark.path.clone();
+        // Ignore save errors for access updates
+        let _ = self.save();
+        // Merging may have moved it
+        self.bookmarks.iter().find(|b| b.path == path)
     }
 
     pub fn list_bookmarks(&self) -> &[Bookmark] {
         &self.bookmarks
     }
 
+    /// Indices of the bookmarks matching `query`
+    ///
+    /// Words starting with `#` keep bookmarks with a tag starting that way;
+    /// the rest of the query fuzzy-matches names and paths, best match
+    /// first. Without such text the bookmarks are grouped by category
+    /// (see [`groups_by_category`]), untagged ones last.
+    pub fn filter(&self, query: &str) -> Vec<usize> {
+        let (tags, text) = split_query(query);
+        let tagged = |bookmark: &Bookmark| {
+            tags.iter()
+                .all(|wanted| bookmark.tags.iter().any(|tag| tag.starts_with(wanted)))
+        };
+
+        if text.is_empty() {
+            let mut matches: Vec<usize> = (0..self.bookmarks.len())
+                .filter(|&i| tagged(&self.bookmarks[i]))
+                .collect();
+            // Stable: bookmarks keep their order within a category
+            matches.sort_by_key(|&i| match self.bookmarks[i].category() {
+                Some(category) => (false, category),
+                None => (true, ""),
+            });
+            return matches;
+        }
+
+        let mut scored: Vec<(i64, usize)> = self
+            .bookmarks
+            .iter()
+            .enumerate()
+            .filter(|(_, bookmark)| tagged(bookmark))
+            .filter_map(|(i, bookmark)| {
+                let name = fuzzy_score(&text, &bookmark.name);
+                let path = fuzzy_score(&text, &bookmark.path.to_string_lossy());
+                name.max(path).map(|score| (score, i))
+            })
+            .collect();
+        scored.sort_by_key(|&(score, i)| (std::cmp::Reverse(score), i));
+        scored.into_iter().map(|(_, i)| i).collect()
+    }
+
     #[allow(dead_code)]
     pub fn find_bookmark_by_path(&self, path: &Path) -> Option<usize> {
         self.bookmarks.iter().position(|b| b.path == path)
@@ -231,30 +483,30 @@ impl BookmarksManager {
     #[allow(dead_code)]
     pub fn sort_by_frequency(&mut self) {
         self.bookmarks
-            .sort_by(|a, b| b.access_count.cmp(&a.access_count));
+            .sort_by_key(|b| (b.shared, std::cmp::Reverse(b.access_count)));
 
-        // Rebuild shortcuts map
-        self.shortcuts.clear();
-        for (index, bookmark) in self.bookmarks.iter().enumerate() {
-            if let Some(key) = bookmark.shortcut {
-                self.shortcuts.insert(key, index);
-            }
-        }
+        self.rebuild_shortcuts();
 
         let _ = self.save();
     }
 
+    /// Swap two of the user's bookmarks, to order them by hand
+    pub fn swap(&mut self, a: usize, b: usize) -> Result<()> {
+        let a = self.editable(a)?;
+        let b = self.editable(b)?;
+
+        self.bookmarks.swap(a, b);
+        self.rebuild_shortcuts();
+        self.save()?;
+        Ok(())
+    }
+
     #[allow(dead_code)]
     pub fn sort_by_name(&mut self) {
-        self.bookmarks.sort_by(|a, b| a.name.cmp(&b.name));
+        self.bookmarks
+            .sort_by(|a, b| a.shared.cmp(&b.shared).then_with(|| a.name.cmp(&b.name)));
 
-        // Rebuild shortcuts map
-        self.shortcuts.clear();
-        for (index, bookmark) in self.bookmarks.iter().enumerate() {
-            if let Some(key) = bookmark.shortcut {
-                self.shortcuts.insert(key, index);
-            }
-        }
+        self.rebuild_shortcuts();
 
         let _ = self.save();
     }
@@ -278,96 +530,516 @@ impl BookmarksManager {
         let content = fs::read_to_string(&self.config_path)?;
         let data: SavedBookmarks = serde_json::from_str(&content)?;
 
+        let outdated = data.version < BOOKMARKS_VERSION;
         self.bookmarks = data.bookmarks;
+        self.baseline = self.bookmarks.clone();
+        self.rebuild_shortcuts();
 
-        // Rebuild shortcuts map
-        self.shortcuts.clear();
-        for (index, bookmark) in self.bookmarks.iter().enumerate() {
-            if let Some(key) = bookmark.shortcut {
-                self.shortcuts.insert(key, index);
-            }
+        // Version 1 had no tags, which default to none; write them out so
+        // the file records the format it is in
+        if outdated {
+            self.save()?;
         }
 
         Ok(())
     }
 
-    fn save(&self) -> Result<()> {
+    /// Write the user's bookmarks, merged first with what other fsnav
+    /// instances saved since they were read; the lock keeps two instances
+    /// from merging at the same time
+    fn save(&mut self) -> Result<()> {
+        let lock = self.config_path.with_extension("json.lock");
+        let _lock =
+            lock_file(&lock).with_context(|| format!("Failed to lock {}", lock.display()))?;
+        self.merge_saved()?;
+
         let data = SavedBookmarks {
-            version: 1,
-            bookmarks: self.bookmarks.clone(),
+            version: BOOKMARKS_VERSION,
+            bookmarks: self.baseline.clone(),
         };
-
         let json = serde_json::to_string_pretty(&data)?;
-        fs::write(&self.config_path, json)?;
+        // Renamed into place, so a reader never sees half a file
+        let temp = self.config_path.with_extension("json.tmp");
+        fs::write(&temp, json)?;
+        fs::rename(&temp, &self.config_path)?;
         Ok(())
     }
 
-    #[allow(dead_code)]
-    pub fn export_to_file(&self, path: &Path) -> Result<()> {
-        let data = SavedBookmarks {
-            version: 1,
-            bookmarks: self.bookmarks.clone(),
+    /// Pick up the bookmarks other instances added, changed or removed
+    pub fn sync(&mut self) -> Result<()> {
+        self.merge_saved()
+    }
+
+    /// Merge `bookmarks.json` as it is now into the user's bookmarks, and
+    /// take the result as the new baseline
+    fn merge_saved(&mut self) -> Result<()> {
+        let theirs = match fs::read_to_string(&self.config_path) {
+            Ok(content) => serde_json::from_str::<SavedBookmarks>(&content)
+                .map(|data| data.bookmarks)
+                // Unreadable: ours replace it, as before merging existed
+                .unwrap_or_else(|_| self.baseline.clone()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
         };
 
-        let json = serde_json::to_string_pretty(&data)?;
-        fs::write(path, json)?;
+        let user_count = self.user_count();
+        let shared = self.bookmarks.split_off(user_count);
+        self.bookmarks = merge(&self.baseline, &self.bookmarks, &theirs);
+        self.baseline = self.bookmarks.clone();
+        self.bookmarks.extend(shared);
+        self.rebuild_shortcuts();
         Ok(())
     }
 
-    #[allow(dead_code)]
-    pub fn import_from_file(&mut self, path: &Path) -> Result<()> {
+    pub fn export_to_file(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.export_json()?)?;
+        Ok(())
+    }
+
+    /// The user's own bookmarks in the format of `bookmarks.json`
+    pub fn export_json(&self) -> Result<String> {
+        let data = SavedBookmarks {
+            version: BOOKMARKS_VERSION,
+            bookmarks: self.bookmarks[..self.user_count()].to_vec(),
+        };
+        Ok(serde_json::to_string_pretty(&data)?)
+    }
+
+    pub fn import_from_file(&mut self, path: &Path) -> Result<usize> {
         let content = fs::read_to_string(path)?;
-        let data: SavedBookmarks = serde_json::from_str(&content)?;
+        self.import_json(&content)
+    }
+
+    /// Merge bookmarks exported by [`Self::export_json`]; paths already
+    /// bookmarked are skipped and shortcuts another user bookmark holds are
+    /// dropped. Returns how many were added
+    pub fn import_json(&mut self, content: &str) -> Result<usize> {
+        let data: SavedBookmarks = serde_json::from_str(content)?;
 
-        // Merge with existing bookmarks
+        let mut added = 0;
         for bookmark in data.bookmarks {
             // Skip if path already bookmarked
-            if !self.bookmarks.iter().any(|b| b.path == bookmark.path) {
-                let index = self.bookmarks.len();
-
-                // Find new shortcut if current one is taken
-                let shortcut = if let Some(key) = bookmark.shortcut {
-                    if self.shortcuts.contains_key(&key) {
-                        None // Will need to assign manually
-                    } else {
-                        Some(key)
-                    }
-                } else {
-                    None
-                };
-
-                self.bookmarks.push(Bookmark {
+            if self.bookmarks.iter().any(|b| b.path == bookmark.path) {
+                continue;
+            }
+            let index = self.user_count();
+            // Shared bookmarks give their shortcut up, as on the bookmarks screen
+            let shortcut = bookmark.shortcut.filter(|&key| {
+                is_valid_shortcut(key)
+                    && self
+                        .shortcut_owner(key)
+                        .is_none_or(|owner| self.bookmarks[owner].shared)
+            });
+
+            self.bookmarks.insert(
+                index,
+                Bookmark {
                     shortcut,
+                    shared: false,
                     ..bookmark
-                });
+                },
+            );
+            self.rebuild_shortcuts();
+            added += 1;
+        }
+
+        self.save()?;
+        Ok(added)
+    }
+
+    /// One tab-separated line per bookmark, for scripts: shortcut (`-` for
+    /// none), name, path, comma-separated tags and `user` or `shared`
+    pub fn listing(&self) -> String {
+        self.bookmarks
+            .iter()
+            .map(|b| {
+                format!(
+                    "{}\t{}\t{}\t{}\t{}\n",
+                    b.shortcut.unwrap_or('-'),
+                    b.name,
+                    b.path.display(),
+                    b.tags.join(","),
+                    if b.shared { "shared" } else { "user" }
+                )
+            })
+            .collect()
+    }
+}
+
+/// `fsnav --list-bookmarks`, `--export-bookmarks FILE` and
+/// `--import-bookmarks FILE`, where `-` is standard output or input;
+/// returns the exit status
+pub fn run_cli(flag: &str, file: Option<&str>) -> i32 {
+    match cli(flag, file) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            1
+        }
+    }
+}
+
+fn cli(flag: &str, file: Option<&str>) -> Result<()> {
+    let mut manager = BookmarksManager::new()?;
+    match (flag, file) {
+        ("--list-bookmarks", _) => print!("{}", manager.listing()),
+        (_, None) => bail!("{} needs a file, or - for the terminal", flag),
+        ("--export-bookmarks", Some("-")) => println!("{}", manager.export_json()?),
+        ("--export-bookmarks", Some(file)) => {
+            manager
+                .export_to_file(Path::new(file))
+                .with_context(|| format!("Failed to write {}", file))?;
+            eprintln!("Exported {} bookmarks to {}", manager.user_count(), file);
+        }
+        (_, Some(file)) => {
+            let added = if file == "-" {
+                manager.import_json(&io::read_to_string(io::stdin())?)
+            } else {
+                manager.import_from_file(Path::new(file))
+            }
+            .with_context(|| format!("Failed to import bookmarks from {}", file))?;
+            eprintln!(
+                "Imported {} new bookmark{}",
+                added,
+                if added == 1 { "" } else { "s" }
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Characters [`BookmarksManager::get_available_shortcuts`] offers
+pub fn is_valid_shortcut(c: char) -> bool {
+    c.is_ascii_lowercase() || c.is_ascii_digit()
+}
+
+/// Whether [`BookmarksManager::filter`] groups its matches by category:
+/// the query holds nothing but `#tag` words
+pub fn groups_by_category(query: &str) -> bool {
+    split_query(query).1.is_empty()
+}
 
-                if let Some(key) = shortcut {
-                    self.shortcuts.insert(key, index);
+/// `#tag` words of a bookmark query, lowercased without the `#`, and the
+/// rest of it
+fn split_query(query: &str) -> (Vec<String>, String) {
+    let mut tags = Vec::new();
+    let mut text = Vec::new();
+    for word in query.split_whitespace() {
+        match word.strip_prefix('#') {
+            Some(tag) => tags.push(tag.to_lowercase()),
+            None => text.push(word),
+        }
+    }
+    (tags, text.join(" "))
+}
+
+/// Whether a bookmark's target can be opened
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Availability {
+    Available,
+    PermissionDenied,
+    Missing,
+    NotADirectory,
+    /// A network mount that timed out, went away or failed to answer
+    Unreachable,
+}
+
+impl Availability {
+    /// Blocks for as long as the filesystem takes; run it on a worker
+    pub fn check(path: &Path) -> Self {
+        match fs::read_dir(path) {
+            Ok(_) => Availability::Available,
+            Err(e) => Self::from_error(&e),
+        }
+    }
+
+    fn from_error(error: &io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::NotFound => Availability::Missing,
+            io::ErrorKind::PermissionDenied => Availability::PermissionDenied,
+            _ if error.raw_os_error() == Some(libc::ENOTDIR) => Availability::NotADirectory,
+            _ => Availability::Unreachable,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Availability::Available => "ok",
+            Availability::PermissionDenied => "denied",
+            Availability::Missing => "missing",
+            Availability::NotADirectory => "not a dir",
+            Availability::Unreachable => "unreachable",
+        }
+    }
+
+    /// The target is gone for good, unlike a denied or slow one that may
+    /// come back
+    pub fn is_dead(self) -> bool {
+        matches!(self, Availability::Missing | Availability::NotADirectory)
+    }
+}
+
+/// Availability of bookmark targets, checked on worker threads so a hung
+/// network mount never stalls the bookmarks screen
+#[derive(Debug, Default)]
+pub struct BookmarkHealth {
+    states: HashMap<PathBuf, Availability>,
+    /// Checks under way, with when they started
+    checks: Vec<(PathBuf, Instant, Task<Availability>)>,
+    last_check: Option<Instant>,
+}
+
+impl BookmarkHealth {
+    /// Start checking every target not being checked already
+    pub fn check(&mut self, bookmarks: &[Bookmark]) {
+        self.last_check = Some(Instant::now());
+        for bookmark in bookmarks {
+            if self.checks.iter().any(|(path, ..)| *path == bookmark.path) {
+                continue;
+            }
+            let path = bookmark.path.clone();
+            let task = Task::spawn(move |_| Availability::check(&path));
+            self.checks
+                .push((bookmark.path.clone(), Instant::now(), task));
+        }
+    }
+
+    /// Pick up finished checks, mark slow ones unreachable, and check
+    /// again every [`RECHECK_EVERY`]
+    pub fn poll(&mut self, bookmarks: &[Bookmark]) {
+        let now = Instant::now();
+        let states = &mut self.states;
+        self.checks
+            .retain_mut(|(path, started, task)| match task.poll() {
+                Some(availability) => {
+                    states.insert(path.clone(), availability);
+                    false
                 }
+                // Kept going: a mount that comes back updates the badge
+                None => {
+                    if now.duration_since(*started) >= UNREACHABLE_AFTER {
+                        states.insert(path.clone(), Availability::Unreachable);
+                    }
+                    true
+                }
+            });
+
+        if self
+            .last_check
+            .is_some_and(|last| now.duration_since(last) >= RECHECK_EVERY)
+        {
+            self.check(bookmarks);
+        }
+    }
+
+    /// `None` until the first check of `path` answers or times out
+    pub fn get(&self, path: &Path) -> Option<Availability> {
+        self.states.get(path).copied()
+    }
+
+    /// Some targets have not answered yet
+    pub fn is_checking(&self) -> bool {
+        !self.checks.is_empty()
+    }
+
+    /// Known to be missing or not a directory
+    pub fn is_dead(&self, path: &Path) -> bool {
+        self.get(path).is_some_and(Availability::is_dead)
+    }
+
+    /// Not known to be unavailable
+    pub fn is_usable(&self, path: &Path) -> bool {
+        self.get(path)
+            .is_none_or(|availability| availability == Availability::Available)
+    }
+}
+
+/// How often each directory was opened, kept in `visits.json` to suggest
+/// bookmarks for the ones visited most
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DirVisits {
+    counts: HashMap<PathBuf, usize>,
+    /// Suggestions turned down, never offered again
+    #[serde(default)]
+    dismissed: Vec<PathBuf>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl DirVisits {
+    pub fn load() -> Self {
+        let Ok(dir) = config_dir() else {
+            return Self::default();
+        };
+        Self::load_from(dir.join("visits.json"))
+    }
+
+    fn load_from(path: PathBuf) -> Self {
+        let mut visits: Self = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        visits.path = path;
+        visits
+    }
+
+    pub fn record(&mut self, dir: &Path) -> Result<()> {
+        *self.counts.entry(dir.to_path_buf()).or_default() += 1;
+        if self.counts.len() > VISITS_KEPT {
+            if let Some(rarest) = self
+                .counts
+                .iter()
+                .min_by_key(|(_, &count)| count)
+                .map(|(path, _)| path.clone())
+            {
+                self.counts.remove(&rarest);
             }
         }
+        self.save()
+    }
 
-        self.save()?;
+    /// Stop suggesting `dir`
+    pub fn dismiss(&mut self, dir: &Path) -> Result<()> {
+        self.dismissed.push(dir.to_path_buf());
+        self.save()
+    }
+
+    /// Directories visited often and not bookmarked yet, most visited
+    /// first, with their visit counts; the home and root directories are
+    /// never suggested
+    pub fn suggestions(&self, bookmarks: &[Bookmark]) -> Vec<(PathBuf, usize)> {
+        let home = home_dir();
+        let mut hot: Vec<(PathBuf, usize)> = self
+            .counts
+            .iter()
+            .filter(|&(path, &count)| {
+                count >= SUGGEST_AFTER
+                    && path.parent().is_some()
+                    && home.as_deref() != Some(path.as_path())
+                    && !self.dismissed.contains(path)
+                    && !bookmarks.iter().any(|b| b.path == *path)
+            })
+            .map(|(path, &count)| (path.clone(), count))
+            .collect();
+        hot.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        hot.truncate(SUGGESTIONS_SHOWN);
+        hot
+    }
+
+    fn save(&self) -> Result<()> {
+        if self.path.as_os_str().is_empty() {
+            return Ok(());
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&self.path, json)?;
         Ok(())
     }
 }
 
+/// What a bookmark is set to, as opposed to its visit statistics
+fn settings(bookmark: &Bookmark) -> (&str, Option<char>, &[String], bool) {
+    (
+        &bookmark.name,
+        bookmark.shortcut,
+        &bookmark.tags,
+        bookmark.hot,
+    )
+}
+
+/// Three-way merge of the user's bookmarks, matched by path: `base` as
+/// read from disk, `ours` as changed since, `theirs` as on disk now
+///
+/// Removals on either side win. Settings changed here win over the ones
+/// changed there, visits made on both sides add up, and bookmarks added
+/// there go last, giving up a shortcut already taken here.
+fn merge(base: &[Bookmark], ours: &[Bookmark], theirs: &[Bookmark]) -> Vec<Bookmark> {
+    let find = |list: &[Bookmark], path: &Path| list.iter().position(|b| b.path == path);
+    let mut merged = Vec::new();
+    for bookmark in ours {
+        let (old, other) = match (find(base, &bookmark.path), find(theirs, &bookmark.path)) {
+            (None, _) => {
+                merged.push(bookmark.clone());
+                continue;
+            }
+            (Some(_), None) => continue,
+            (Some(old), Some(other)) => (&base[old], &theirs[other]),
+        };
+        let mut kept = if settings(bookmark) == settings(old) {
+            other.clone()
+        } else {
+            bookmark.clone()
+        };
+        kept.access_count =
+            other.access_count + bookmark.access_count.saturating_sub(old.access_count);
+        kept.last_accessed = other.last_accessed.max(bookmark.last_accessed);
+        merged.push(kept);
+    }
+
+    for bookmark in theirs {
+        if find(base, &bookmark.path).is_some() || find(ours, &bookmark.path).is_some() {
+            continue;
+        }
+        let taken = bookmark
+            .shortcut
+            .is_some_and(|key| merged.iter().any(|b: &Bookmark| b.shortcut == Some(key)));
+        merged.push(Bookmark {
+            shortcut: bookmark.shortcut.filter(|_| !taken),
+            ..bookmark.clone()
+        });
+    }
+    merged
+}
+
 #[derive(Serialize, Deserialize)]
 struct SavedBookmarks {
     version: u32,
     bookmarks: Vec<Bookmark>,
 }
 
-// Directory for home_dir fallback
-mod dirs {
-    use std::path::PathBuf;
+/// `bookmarks.toml` as an administrator writes it:
+///
+/// ```toml
+/// [[bookmark]]
+/// name = "App logs"
+/// path = "/var/log/app"
+/// shortcut = "l"
+/// tags = ["servers"]
+/// ```
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SharedBookmarks {
+    #[serde(default)]
+    bookmark: Vec<SharedBookmark>,
+}
 
-    pub fn home_dir() -> Option<PathBuf> {
-        std::env::var("HOME")
-            .or_else(|_| std::env::var("USERPROFILE"))
-            .ok()
-            .map(PathBuf::from)
-    }
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SharedBookmark {
+    name: String,
+    path: PathBuf,
+    shortcut: Option<char>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+pub fn read_shared_bookmarks(path: &Path) -> Result<Vec<Bookmark>> {
+    let content = fs::read_to_string(path)?;
+    let file: SharedBookmarks = toml::from_str(&content)?;
+    Ok(file
+        .bookmark
+        .into_iter()
+        .map(|shared| Bookmark {
+            name: shared.name,
+            path: shared.path,
+            shortcut: shared.shortcut,
+            created_at: std::time::UNIX_EPOCH,
+            last_accessed: None,
+            access_count: 0,
+            tags: parse_tags(&shared.tags.join(" ")),
+            hot: false,
+            shared: true,
+        })
+        .collect())
 }
 
 #[cfg(test)]
@@ -391,7 +1063,8 @@ mod tests {
             .unwrap();
 
         // Test finding by shortcut
-        assert!(manager.get_bookmark_by_shortcut('x').is_some());
+        let health = BookmarkHealth::default();
+        assert!(manager.get_bookmark_by_shortcut('x', &health).is_some());
 
         // Test finding by path
         let index = manager.find_bookmark_by_path(&test_path);
@@ -399,7 +1072,7 @@ mod tests {
 
         // Test removing bookmark
         manager.remove_bookmark(index.unwrap()).unwrap();
-        assert!(manager.get_bookmark_by_shortcut('x').is_none());
+        assert!(manager.get_bookmark_by_shortcut('x', &health).is_none());
     }
 
     #[test]
@@ -422,4 +1095,446 @@ mod tests {
         let result = manager.add_bookmark("Test2".to_string(), path2, Some('x'));
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_bookmark_health() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("file");
+        fs::write(&file, "").unwrap();
+        let bookmark = |path: PathBuf| Bookmark {
+            name: "b".to_string(),
+            path,
+            shortcut: None,
+            created_at: std::time::SystemTime::now(),
+            last_accessed: None,
+            access_count: 0,
+            tags: Vec::new(),
+            hot: false,
+            shared: false,
+        };
+        let bookmarks = vec![
+            bookmark(temp_dir.path().to_path_buf()),
+            bookmark(temp_dir.path().join("gone")),
+            bookmark(file.clone()),
+        ];
+
+        let mut health = BookmarkHealth::default();
+        assert!(health.is_usable(&bookmarks[1].path));
+        health.check(&bookmarks);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !health.checks.is_empty() && Instant::now() < deadline {
+            health.poll(&bookmarks);
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        assert_eq!(health.get(temp_dir.path()), Some(Availability::Available));
+        assert_eq!(health.get(&bookmarks[1].path), Some(Availability::Missing));
+        assert_eq!(health.get(&file), Some(Availability::NotADirectory));
+        assert!(!health.is_usable(&file));
+
+        // Pruning drops the dead targets and keeps shared ones
+        let mut manager = open_empty(&temp_dir.path().join("bookmarks.json"));
+        manager.bookmarks = bookmarks.clone();
+        manager.bookmarks[2].shared = true;
+        assert_eq!(manager.dead_bookmarks(&health).len(), 1);
+        assert_eq!(manager.prune_dead(&health).unwrap(), 1);
+        let left: Vec<&Path> = manager
+            .list_bookmarks()
+            .iter()
+            .map(|b| b.path.as_path())
+            .collect();
+        assert_eq!(left, [temp_dir.path(), file.as_path()]);
+
+        let timed_out = io::Error::from_raw_os_error(libc::ETIMEDOUT);
+        assert_eq!(
+            Availability::from_error(&timed_out),
+            Availability::Unreachable
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_filter() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager = open_empty(&temp_dir.path().join("bookmarks.json"));
+        // Fixed paths: letters of a random temporary directory would match
+        for (name, path) in [
+            ("Projects", "/home/user/work/projects"),
+            ("Downloads", "/home/user/Downloads"),
+            ("Config", "/home/user/.config"),
+            ("Logs", "/srv/app/logs"),
+        ] {
+            manager.add_bookmark_internal(name.to_string(), PathBuf::from(path), None);
+        }
+
+        assert_eq!(manager.filter(""), vec![0, 1, 2, 3]);
+        assert_eq!(manager.filter("dl")[0], 1);
+        assert_eq!(manager.filter("cfg"), vec![2]);
+        // Paths match as well as names
+        assert_eq!(manager.filter("app/lo"), vec![3]);
+        assert!(manager.filter("zzz").is_empty());
+    }
+
+    #[test]
+    fn test_tags_and_categories() {
+        assert_eq!(
+            parse_tags("Work, #ssh  work,,servers"),
+            ["work", "ssh", "servers"]
+        );
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager = open_empty(&temp_dir.path().join("bookmarks.json"));
+        for (name, tags) in [
+            ("notes", ""),
+            ("web01", "servers prod"),
+            ("fsnav", "projects work"),
+            ("db01", "servers"),
+        ] {
+            let path = PathBuf::from("/srv").join(name);
+            manager.add_bookmark_internal(name.to_string(), path, None);
+            let index = manager.list_bookmarks().len() - 1;
+            manager.set_tags(index, parse_tags(tags)).unwrap();
+        }
+
+        // Grouped by category, untagged last, in order within a group
+        assert!(groups_by_category(""));
+        assert_eq!(manager.filter(""), vec![2, 1, 3, 0]);
+        assert_eq!(manager.filter("#serv"), vec![1, 3]);
+        assert_eq!(manager.filter("#servers #prod"), vec![1]);
+        assert!(!groups_by_category("#servers db"));
+        assert_eq!(manager.filter("#servers db"), vec![3]);
+        assert_eq!(manager.all_tags(), ["prod", "projects", "servers", "work"]);
+
+        assert!(manager.toggle_hot(3).unwrap());
+        assert_eq!(manager.hot_dirs(), [PathBuf::from("/srv/db01")]);
+        assert!(!manager.toggle_hot(3).unwrap());
+        assert!(manager.hot_dirs().is_empty());
+    }
+
+    #[test]
+    fn test_manual_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("bookmarks.json");
+        let mut source = open_empty(&config_path);
+        for name in ["home", "logs", "www"] {
+            source.add_bookmark_internal(name.to_string(), PathBuf::from("/").join(name), None);
+        }
+        source.bookmarks.push(Bookmark {
+            shared: true,
+            ..source.bookmarks[0].clone()
+        });
+
+        source.swap(2, 0).unwrap();
+        assert!(source.swap(2, 3).is_err());
+
+        let mut reloaded = open_empty(&config_path);
+        reloaded.load().unwrap();
+        let names: Vec<&str> = reloaded
+            .list_bookmarks()
+            .iter()
+            .map(|bookmark| bookmark.name.as_str())
+            .collect();
+        assert_eq!(names, ["www", "logs", "home"]);
+    }
+
+    #[test]
+    fn test_concurrent_instances() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("bookmarks.json");
+        let open = || {
+            let mut manager = open_empty(&config_path);
+            manager.load().unwrap();
+            manager
+        };
+        let mut setup = open_empty(&config_path);
+        for (name, shortcut) in [("home", 'h'), ("logs", 'l'), ("www", 'w')] {
+            let path = temp_dir.path().join(name);
+            fs::create_dir(&path).unwrap();
+            setup
+                .add_bookmark(name.to_string(), path, Some(shortcut))
+                .unwrap();
+        }
+
+        // Two terminals open fsnav, then each changes something
+        let mut first = open();
+        let mut second = open();
+        let health = BookmarkHealth::default();
+        first.get_bookmark_by_index(0, &health);
+        first.rename_bookmark(1, "Logs".to_string()).unwrap();
+        let tmp = temp_dir.path().join("tmp");
+        fs::create_dir(&tmp).unwrap();
+        first
+            .add_bookmark("tmp".to_string(), tmp, Some('t'))
+            .unwrap();
+        // Added before it saw the other one's 't': the one added there
+        // gives the shortcut up
+        let srv = temp_dir.path().join("srv");
+        fs::create_dir(&srv).unwrap();
+        second
+            .add_bookmark("srv".to_string(), srv, Some('t'))
+            .unwrap();
+        second.get_bookmark_by_index(0, &health);
+        second.remove_bookmark(2).unwrap();
+
+        // Nothing either of them did is lost, and visits add up
+        let merged = open();
+        let summary: Vec<(&str, Option<char>)> = merged
+            .list_bookmarks()
+            .iter()
+            .map(|b| (b.name.as_str(), b.shortcut))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("home", Some('h')),
+                ("Logs", Some('l')),
+                ("srv", Some('t')),
+                ("tmp", None)
+            ]
+        );
+        assert_eq!(merged.list_bookmarks()[0].access_count, 2);
+
+        // The first instance catches up when the bookmarks view opens
+        first.sync().unwrap();
+        assert_eq!(first.list_bookmarks().len(), 4);
+        assert_eq!(first.list_bookmarks()[0].access_count, 2);
+    }
+
+    fn open_empty(config_path: &Path) -> BookmarksManager {
+        BookmarksManager {
+            bookmarks: Vec::new(),
+            shortcuts: HashMap::new(),
+            config_path: config_path.to_path_buf(),
+            shared_error: None,
+            baseline: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_version_1_file_migrates() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("bookmarks.json");
+        fs::write(
+            &config_path,
+            r#"{"version": 1, "bookmarks": [{"name": "Root", "path": "/", "shortcut": "r",
+                "created_at": {"secs_since_epoch": 0, "nanos_since_epoch": 0},
+                "last_accessed": null, "access_count": 3}]}"#,
+        )
+        .unwrap();
+
+        let mut manager = open_empty(&config_path);
+        manager.load().unwrap();
+        assert_eq!(manager.list_bookmarks()[0].access_count, 3);
+        assert!(manager.list_bookmarks()[0].tags.is_empty());
+        assert!(!manager.list_bookmarks()[0].hot);
+
+        let saved: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(saved["version"], BOOKMARKS_VERSION);
+        assert_eq!(saved["bookmarks"][0]["tags"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_shared_bookmarks() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager = open_empty(&temp_dir.path().join("bookmarks.json"));
+        let home = temp_dir.path().join("home");
+        fs::create_dir(&home).unwrap();
+        manager
+            .add_bookmark("Home".to_string(), home.clone(), Some('h'))
+            .unwrap();
+
+        let shared_path = temp_dir.path().join("shared.toml");
+        fs::write(
+            &shared_path,
+            format!(
+                r#"
+[[bookmark]]
+name = "App logs"
+path = "/var/log/app"
+shortcut = "h"
+tags = ["Servers"]
+
+[[bookmark]]
+name = "Duplicate"
+path = "{}"
+"#,
+                home.display()
+            ),
+        )
+        .unwrap();
+        manager.load_shared(&shared_path);
+        assert!(manager.shared_error().is_none());
+
+        // Listed after the user's own; a path they have is not repeated
+        let names: Vec<&str> = manager
+            .list_bookmarks()
+            .iter()
+            .map(|b| b.name.as_str())
+            .collect();
+        assert_eq!(names, ["Home", "App logs"]);
+        assert!(manager.list_bookmarks()[1].shared);
+        assert_eq!(manager.list_bookmarks()[1].tags, ["servers"]);
+
+        // The user's shortcut wins, and shared entries cannot be changed
+        let health = BookmarkHealth::default();
+        assert_eq!(
+            manager.get_bookmark_by_shortcut('h', &health).unwrap().name,
+            "Home"
+        );
+        assert!(manager.rename_bookmark(1, "Logs".to_string()).is_err());
+        assert!(manager.remove_bookmark(1).is_err());
+
+        // New bookmarks go before the shared ones, which are never saved
+        let work = temp_dir.path().join("work");
+        fs::create_dir(&work).unwrap();
+        manager
+            .add_bookmark("Work".to_string(), work, None)
+            .unwrap();
+        assert_eq!(manager.list_bookmarks()[2].name, "App logs");
+        let saved = fs::read_to_string(temp_dir.path().join("bookmarks.json")).unwrap();
+        assert!(!saved.contains("App logs"));
+
+        // Removing the user's bookmark hands its shortcut to the shared one
+        manager.remove_bookmark(0).unwrap();
+        assert_eq!(
+            manager.get_bookmark_by_shortcut('h', &health).unwrap().name,
+            "App logs"
+        );
+
+        fs::write(&shared_path, "[[bookmark]]\nname = \"No path\"\n").unwrap();
+        manager.load_shared(&shared_path);
+        assert!(manager.shared_error().unwrap().contains("path"));
+    }
+
+    #[test]
+    fn test_export_import() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut source = open_empty(&temp_dir.path().join("source.json"));
+        source.add_bookmark_internal("Logs".to_string(), PathBuf::from("/var/log"), Some('l'));
+        source.add_bookmark_internal("Web".to_string(), PathBuf::from("/srv/www"), Some('w'));
+        source.set_tags(1, vec!["servers".to_string()]).unwrap();
+        let export = temp_dir.path().join("export.json");
+        source.export_to_file(&export).unwrap();
+
+        let mut target = open_empty(&temp_dir.path().join("target.json"));
+        target.add_bookmark_internal("Home".to_string(), PathBuf::from("/home/user"), Some('w'));
+        target.add_bookmark_internal("Logs".to_string(), PathBuf::from("/var/log"), None);
+        assert_eq!(target.import_from_file(&export).unwrap(), 1);
+
+        // The known path is skipped and the taken shortcut dropped
+        assert_eq!(
+            target.listing(),
+            "w\tHome\t/home/user\t\tuser\n\
+             -\tLogs\t/var/log\t\tuser\n\
+             -\tWeb\t/srv/www\tservers\tuser\n"
+        );
+        assert_eq!(
+            target.import_json(&source.export_json().unwrap()).unwrap(),
+            0
+        );
+        assert!(target.import_json("{}").is_err());
+    }
+
+    #[test]
+    fn test_set_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager = open_empty(&temp_dir.path().join("bookmarks.json"));
+        let (old, new) = (temp_dir.path().join("old"), temp_dir.path().join("new"));
+        fs::create_dir(&old).unwrap();
+        fs::create_dir(&new).unwrap();
+        manager
+            .add_bookmark("Old".to_string(), old.clone(), None)
+            .unwrap();
+        manager
+            .add_bookmark("New".to_string(), new.clone(), None)
+            .unwrap();
+
+        assert!(manager.set_path(0, PathBuf::from("relative")).is_err());
+        assert!(manager
+            .set_path(0, temp_dir.path().join("missing"))
+            .is_err());
+        assert!(manager.set_path(0, new.clone()).is_err());
+
+        let moved = temp_dir.path().join("moved");
+        fs::create_dir(&moved).unwrap();
+        manager.set_path(0, moved.clone()).unwrap();
+        assert_eq!(manager.find_bookmark_by_path(&moved), Some(0));
+        assert!(fs::read_to_string(temp_dir.path().join("bookmarks.json"))
+            .unwrap()
+            .contains("moved"));
+    }
+
+    #[test]
+    fn test_update_shortcut() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager = open_empty(&temp_dir.path().join("bookmarks.json"));
+        manager.add_bookmark_internal("Home".to_string(), PathBuf::from("/home"), Some('h'));
+        manager.add_bookmark_internal("Logs".to_string(), PathBuf::from("/var/log"), Some('l'));
+        manager.bookmarks.push(Bookmark {
+            shortcut: Some('s'),
+            shared: true,
+            ..manager.bookmarks[0].clone()
+        });
+        manager.rebuild_shortcuts();
+
+        // Taken by another of the user's bookmarks: refused, nothing lost
+        let error = manager.update_shortcut(1, Some('h')).unwrap_err();
+        assert!(error.to_string().contains("'Home'"), "{}", error);
+        assert_eq!(manager.shortcut_owner('l'), Some(1));
+        assert!(manager.update_shortcut(1, Some('L')).is_err());
+
+        manager.update_shortcut(1, Some('g')).unwrap();
+        assert_eq!(manager.shortcut_owner('g'), Some(1));
+        assert_eq!(manager.shortcut_owner('l'), None);
+
+        // A shared bookmark's shortcut is taken over, and handed back
+        manager.update_shortcut(0, Some('s')).unwrap();
+        assert_eq!(manager.shortcut_owner('s'), Some(0));
+        manager.update_shortcut(0, None).unwrap();
+        assert_eq!(manager.shortcut_owner('s'), Some(2));
+        assert_eq!(manager.shortcut_owner('h'), None);
+    }
+
+    #[test]
+    fn test_visit_suggestions() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("visits.json");
+        let mut visits = DirVisits::load_from(path.clone());
+        let (logs, src, tmp) = (
+            PathBuf::from("/var/log/app"),
+            PathBuf::from("/srv/app/src"),
+            PathBuf::from("/tmp"),
+        );
+        for _ in 0..SUGGEST_AFTER {
+            visits.record(&logs).unwrap();
+            visits.record(&src).unwrap();
+            visits.record(Path::new("/")).unwrap();
+        }
+        visits.record(&src).unwrap();
+        for _ in 1..SUGGEST_AFTER {
+            visits.record(&tmp).unwrap();
+        }
+
+        // Most visited first; the root and rarely visited directories are not
+        // suggested, and bookmarked ones no longer are
+        let mut visits = DirVisits::load_from(path.clone());
+        assert_eq!(
+            visits.suggestions(&[]),
+            vec![
+                (src.clone(), SUGGEST_AFTER + 1),
+                (logs.clone(), SUGGEST_AFTER)
+            ]
+        );
+        let mut manager = open_empty(&temp_dir.path().join("bookmarks.json"));
+        manager.add_bookmark_internal("Source".to_string(), src, None);
+        assert_eq!(
+            visits.suggestions(manager.list_bookmarks()),
+            vec![(logs.clone(), SUGGEST_AFTER)]
+        );
+
+        visits.dismiss(&logs).unwrap();
+        assert!(DirVisits::load_from(path)
+            .suggestions(manager.list_bookmarks())
+            .is_empty());
+    }
 }
@@ -0,0 +1,572 @@
+use anyhow::Result;
+use crossterm::{
+    cursor::MoveTo,
+    event::KeyCode,
+    execute,
+    style::{Color, Print, ResetColor},
+    terminal,
+};
+use md5::Md5;
+use sha2::{Digest, Sha256, Sha512};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::tasks::Task;
+use crate::ui::{SetBackgroundColor, SetForegroundColor};
+use crate::utils::truncate_chars;
+
+/// Files hashed at the same time; more would only compete for the disk
+const JOBS: usize = 2;
+
+/// Checksum files larger than this are not lists of digests
+const MAX_CHECKSUM_FILE: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    Md5,
+    Sha256,
+    Sha512,
+}
+
+impl Algorithm {
+    /// The algorithm a checksum file is for, going by its name:
+    /// `image.iso.sha256`, `image.iso.md5sum`, `SHA256SUMS`, `md5sums.txt`
+    pub fn of_checksum_file(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        let name = name.strip_suffix(".txt").unwrap_or(&name);
+        [Algorithm::Md5, Algorithm::Sha256, Algorithm::Sha512]
+            .into_iter()
+            .find(|algorithm| {
+                let tag = algorithm.tag();
+                name.ends_with(&format!(".{}", tag))
+                    || name.ends_with(&format!(".{}sum", tag))
+                    || name == format!("{}sums", tag)
+                    || name == format!("{}sum", tag)
+            })
+    }
+
+    /// Name of the algorithm in checksum file names
+    fn tag(self) -> &'static str {
+        match self {
+            Algorithm::Md5 => "md5",
+            Algorithm::Sha256 => "sha256",
+            Algorithm::Sha512 => "sha512",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Algorithm::Md5 => "MD5",
+            Algorithm::Sha256 => "SHA-256",
+            Algorithm::Sha512 => "SHA-512",
+        }
+    }
+
+    /// Length of a digest in hexadecimal
+    fn hex_len(self) -> usize {
+        match self {
+            Algorithm::Md5 => 32,
+            Algorithm::Sha256 => 64,
+            Algorithm::Sha512 => 128,
+        }
+    }
+
+    fn is_digest(self, text: &str) -> bool {
+        text.len() == self.hex_len() && text.chars().all(|c| c.is_ascii_hexdigit())
+    }
+}
+
+/// Hex digest of the file at `path`; `None` when `cancel` is set first
+pub fn hash_file(
+    path: &Path,
+    algorithm: Algorithm,
+    cancel: &AtomicBool,
+) -> io::Result<Option<String>> {
+    let mut file = File::open(path)?;
+    match algorithm {
+        Algorithm::Md5 => hash_with::<Md5>(&mut file, cancel),
+        Algorithm::Sha256 => hash_with::<Sha256>(&mut file, cancel),
+        Algorithm::Sha512 => hash_with::<Sha512>(&mut file, cancel),
+    }
+}
+
+fn hash_with<D: Digest>(file: &mut File, cancel: &AtomicBool) -> io::Result<Option<String>> {
+    let mut hasher = D::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(Some(
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect(),
+    ))
+}
+
+/// Named after the one file it is for, as `image.iso.sha256` is
+fn is_per_file(file: &Path, algorithm: Algorithm) -> bool {
+    file.extension().is_some_and(|extension| {
+        extension
+            .to_string_lossy()
+            .to_lowercase()
+            .starts_with(algorithm.tag())
+    })
+}
+
+/// The files the checksum file `file` vouches for, with their lowercase
+/// digests
+///
+/// Lines are read in the GNU format (`digest  name`, `digest *name`) or
+/// the BSD one (`SHA256 (name) = digest`); a file holding a lone digest
+/// vouches for the file it is named after, `image.iso` for
+/// `image.iso.sha256`.
+pub fn parse(content: &str, file: &Path, algorithm: Algorithm) -> Vec<(PathBuf, String)> {
+    let dir = file.parent().unwrap_or(Path::new(""));
+    let mut expected = Vec::new();
+    for line in content.lines().map(str::trim_end) {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let bsd = line.rsplit_once(" = ").and_then(|(left, digest)| {
+            let name = left.split_once(" (")?.1.strip_suffix(')')?;
+            Some((name, digest.trim()))
+        });
+        let gnu = || {
+            let digest = line.get(..algorithm.hex_len())?;
+            let rest = &line[digest.len()..];
+            let name = rest
+                .strip_prefix(" *")
+                .or_else(|| rest.strip_prefix("  "))
+                .or_else(|| rest.strip_prefix(' '))?;
+            Some((name, digest))
+        };
+        let (target, digest) = match bsd.or_else(gnu) {
+            Some((name, digest)) => (dir.join(name), digest),
+            None => match file.file_stem() {
+                Some(stem) if is_per_file(file, algorithm) => (dir.join(stem), line.trim()),
+                _ => continue,
+            },
+        };
+        if algorithm.is_digest(digest) {
+            expected.push((target, digest.to_lowercase()));
+        }
+    }
+    expected
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Pending,
+    Pass,
+    /// The digest the file actually has
+    Mismatch(String),
+    Missing,
+    Unreadable(String),
+}
+
+impl Verdict {
+    /// Shown after the entry's name in the listing
+    pub fn marker(&self) -> &'static str {
+        match self {
+            Verdict::Pending => " …",
+            Verdict::Pass => " ✔",
+            Verdict::Mismatch(_) => " ✘",
+            Verdict::Missing | Verdict::Unreadable(_) => " ?",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Verdict::Pending => Color::DarkGrey,
+            Verdict::Pass => Color::Green,
+            Verdict::Mismatch(_) => Color::Red,
+            Verdict::Missing | Verdict::Unreadable(_) => Color::Yellow,
+        }
+    }
+
+    fn is_failure(&self) -> bool {
+        !matches!(self, Verdict::Pending | Verdict::Pass)
+    }
+}
+
+/// One digest a checksum file gives for a file
+struct Check {
+    /// Name of the checksum file
+    source: String,
+    target: PathBuf,
+    algorithm: Algorithm,
+    expected: String,
+    verdict: Verdict,
+}
+
+impl Check {
+    fn run(&self) -> Task<Option<Verdict>> {
+        let (target, algorithm, expected) =
+            (self.target.clone(), self.algorithm, self.expected.clone());
+        Task::spawn(move |cancel| match hash_file(&target, algorithm, cancel) {
+            Ok(Some(digest)) if digest == expected => Some(Verdict::Pass),
+            Ok(Some(digest)) => Some(Verdict::Mismatch(digest)),
+            Ok(None) => None,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Some(Verdict::Missing),
+            Err(e) => Some(Verdict::Unreadable(e.to_string())),
+        })
+    }
+}
+
+pub enum ChecksumOutcome {
+    Continue,
+    Close,
+    /// Read the checksum files again and verify everything anew
+    Rerun,
+}
+
+/// Every digest listed by the checksum files of a directory, verified on
+/// background jobs, with the report of how they fared
+pub struct ChecksumRun {
+    pub dir: PathBuf,
+    checks: Vec<Check>,
+    /// Indices into `checks` being hashed
+    jobs: Vec<(usize, Task<Option<Verdict>>)>,
+    /// Checks not started yet begin here
+    next: usize,
+    selected: usize,
+}
+
+impl ChecksumRun {
+    /// Read `files`, the checksum files of `dir`, and start verifying what
+    /// they list
+    pub fn start(dir: &Path, files: &[PathBuf]) -> Self {
+        let mut checks = Vec::new();
+        for file in files {
+            let name = file
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let Some(algorithm) = Algorithm::of_checksum_file(&name) else {
+                continue;
+            };
+            let readable = fs::metadata(file).is_ok_and(|m| m.len() <= MAX_CHECKSUM_FILE);
+            let Some(content) = readable.then(|| fs::read_to_string(file).ok()).flatten() else {
+                continue;
+            };
+            for (target, expected) in parse(&content, file, algorithm) {
+                checks.push(Check {
+                    source: name.clone(),
+                    target,
+                    algorithm,
+                    expected,
+                    verdict: Verdict::Pending,
+                });
+            }
+        }
+
+        let mut run = Self {
+            dir: dir.to_path_buf(),
+            checks,
+            jobs: Vec::new(),
+            next: 0,
+            selected: 0,
+        };
+        run.start_jobs();
+        run
+    }
+
+    fn start_jobs(&mut self) {
+        while self.jobs.len() < JOBS && self.next < self.checks.len() {
+            self.jobs.push((self.next, self.checks[self.next].run()));
+            self.next += 1;
+        }
+    }
+
+    /// Pick up finished checks and start the next ones; whether any finished
+    pub fn poll(&mut self) -> bool {
+        let checks = &mut self.checks;
+        let before = self.jobs.len();
+        self.jobs.retain_mut(|(index, task)| match task.poll() {
+            Some(verdict) => {
+                checks[*index].verdict = verdict.unwrap_or(Verdict::Pending);
+                false
+            }
+            None => true,
+        });
+        let finished = self.jobs.len() < before;
+        self.start_jobs();
+        finished
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.checks.is_empty()
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.jobs.is_empty()
+    }
+
+    /// How `path` fared, a failure from any checksum file taking precedence
+    pub fn verdict(&self, path: &Path) -> Option<&Verdict> {
+        let mut verdicts = self
+            .checks
+            .iter()
+            .filter(|check| check.target == path)
+            .map(|check| &check.verdict);
+        let first = verdicts.next()?;
+        Some(
+            verdicts
+                .chain(std::iter::once(first))
+                .find(|verdict| verdict.is_failure())
+                .unwrap_or(first),
+        )
+    }
+
+    /// `7 passed, 1 failed, 1 missing, 2 to go`
+    pub fn summary(&self) -> String {
+        let count = |wanted: fn(&Verdict) -> bool| {
+            self.checks
+                .iter()
+                .filter(|check| wanted(&check.verdict))
+                .count()
+        };
+        let mut parts = vec![format!("{} passed", count(|v| *v == Verdict::Pass))];
+        for (label, n) in [
+            ("failed", count(|v| matches!(v, Verdict::Mismatch(_)))),
+            ("missing", count(|v| *v == Verdict::Missing)),
+            ("unreadable", count(|v| matches!(v, Verdict::Unreadable(_)))),
+            ("to go", count(|v| *v == Verdict::Pending)),
+        ] {
+            if n > 0 {
+                parts.push(format!("{} {}", n, label));
+            }
+        }
+        parts.join(", ")
+    }
+
+    pub fn handle_input(&mut self, code: KeyCode) -> ChecksumOutcome {
+        match code {
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down if self.selected + 1 < self.checks.len() => self.selected += 1,
+            KeyCode::Home => self.selected = 0,
+            KeyCode::End => self.selected = self.checks.len().saturating_sub(1),
+            // Next failure, wrapping around
+            KeyCode::Char('f') => {
+                let len = self.checks.len();
+                if let Some(next) = (1..=len)
+                    .map(|step| (self.selected + step) % len)
+                    .find(|&i| self.checks[i].verdict.is_failure())
+                {
+                    self.selected = next;
+                }
+            }
+            KeyCode::Char('r') => return ChecksumOutcome::Rerun,
+            KeyCode::Esc | KeyCode::Char('q') => return ChecksumOutcome::Close,
+            _ => {}
+        }
+        ChecksumOutcome::Continue
+    }
+
+    pub fn render(&self) -> Result<()> {
+        let mut stdout = io::stdout();
+        let (terminal_width, terminal_height) = terminal::size()?;
+        let width = terminal_width as usize;
+
+        execute!(stdout, terminal::Clear(terminal::ClearType::All))?;
+
+        let header = format!(" 🔐 CHECKSUMS {}", self.dir.display());
+        execute!(
+            stdout,
+            MoveTo(0, 0),
+            SetBackgroundColor(Color::DarkBlue),
+            SetForegroundColor(Color::White),
+            Print(format!(
+                "{:<width$}",
+                truncate_chars(&header, width),
+                width = width
+            )),
+            ResetColor
+        )?;
+        execute!(
+            stdout,
+            MoveTo(1, 1),
+            SetForegroundColor(Color::Yellow),
+            Print(truncate_chars(&self.summary(), width.saturating_sub(2))),
+            ResetColor
+        )?;
+
+        let list_height = (terminal_height as usize).saturating_sub(4);
+        let start = self.selected.saturating_sub(list_height.saturating_sub(1));
+        for (row, (i, check)) in self
+            .checks
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(list_height)
+            .enumerate()
+        {
+            let name = check
+                .target
+                .strip_prefix(&self.dir)
+                .unwrap_or(&check.target)
+                .display()
+                .to_string();
+            let detail = match check.verdict {
+                Verdict::Pending => "checking…".to_string(),
+                Verdict::Pass => "ok".to_string(),
+                Verdict::Mismatch(ref actual) => format!(
+                    "MISMATCH: expected {}…, got {}…",
+                    &check.expected[..12],
+                    &actual[..12]
+                ),
+                Verdict::Missing => "missing".to_string(),
+                Verdict::Unreadable(ref error) => format!("unreadable: {}", error),
+            };
+            let line = format!(
+                "{} {:<32} {:<8} {:<20} {}",
+                check.verdict.marker().trim_start(),
+                name,
+                check.algorithm.label(),
+                check.source,
+                detail
+            );
+            let is_selected = i == self.selected;
+            execute!(
+                stdout,
+                MoveTo(1, 3 + row as u16),
+                SetBackgroundColor(if is_selected {
+                    Color::DarkGreen
+                } else {
+                    Color::Reset
+                }),
+                SetForegroundColor(if is_selected {
+                    Color::White
+                } else {
+                    check.verdict.color()
+                }),
+                Print(format!(
+                    "{:<w$}",
+                    truncate_chars(&line, width.saturating_sub(2)),
+                    w = width.saturating_sub(2)
+                )),
+                ResetColor
+            )?;
+        }
+
+        let footer =
+            " ↑↓: Select | f: Next failure | r: Verify again | Esc/q: Close (markers stay)";
+        execute!(
+            stdout,
+            MoveTo(0, terminal_height.saturating_sub(1)),
+            SetBackgroundColor(Color::DarkGrey),
+            SetForegroundColor(Color::White),
+            Print(format!(
+                "{:<width$}",
+                truncate_chars(footer, width),
+                width = width
+            )),
+            ResetColor
+        )?;
+
+        stdout.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    const HELLO_SHA256: &str = "5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03";
+    const HELLO_MD5: &str = "b1946ac92492d2347c6235b4d2611184";
+
+    #[test]
+    fn test_parse_formats() {
+        assert_eq!(
+            Algorithm::of_checksum_file("ubuntu.iso.sha256"),
+            Some(Algorithm::Sha256)
+        );
+        assert_eq!(
+            Algorithm::of_checksum_file("SHA512SUMS"),
+            Some(Algorithm::Sha512)
+        );
+        assert_eq!(
+            Algorithm::of_checksum_file("md5sums.txt"),
+            Some(Algorithm::Md5)
+        );
+        assert_eq!(Algorithm::of_checksum_file("notes.txt"), None);
+
+        let sums = Path::new("/dl/SHA256SUMS");
+        let content = format!(
+            "# release\n{d}  a.iso\n{d} *b.iso\nSHA256 (c d.iso) = {upper}\nnot a digest  e.iso\n",
+            d = HELLO_SHA256,
+            upper = HELLO_SHA256.to_uppercase()
+        );
+        let parsed = parse(&content, sums, Algorithm::Sha256);
+        let targets: Vec<&Path> = parsed.iter().map(|(path, _)| path.as_path()).collect();
+        assert_eq!(
+            targets,
+            [
+                Path::new("/dl/a.iso"),
+                Path::new("/dl/b.iso"),
+                Path::new("/dl/c d.iso")
+            ]
+        );
+        assert!(parsed.iter().all(|(_, digest)| digest == HELLO_SHA256));
+
+        // A lone digest is for the file the checksum file is named after
+        let lone = parse(
+            &format!("{}\n", HELLO_MD5),
+            Path::new("/dl/a.iso.md5"),
+            Algorithm::Md5,
+        );
+        assert_eq!(lone, [(PathBuf::from("/dl/a.iso"), HELLO_MD5.to_string())]);
+    }
+
+    #[test]
+    fn test_verify_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name);
+        fs::write(path("good.iso"), "hello\n").unwrap();
+        fs::write(path("bad.iso"), "tampered\n").unwrap();
+        fs::write(
+            path("SHA256SUMS"),
+            format!(
+                "{d}  good.iso\n{d}  bad.iso\n{d}  gone.iso\n",
+                d = HELLO_SHA256
+            ),
+        )
+        .unwrap();
+        fs::write(path("good.iso.md5"), HELLO_MD5).unwrap();
+
+        let mut run = ChecksumRun::start(dir.path(), &[path("SHA256SUMS"), path("good.iso.md5")]);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while run.is_running() && Instant::now() < deadline {
+            run.poll();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(run.verdict(&path("good.iso")), Some(&Verdict::Pass));
+        assert!(matches!(
+            run.verdict(&path("bad.iso")),
+            Some(Verdict::Mismatch(_))
+        ));
+        assert_eq!(run.verdict(&path("gone.iso")), Some(&Verdict::Missing));
+        assert_eq!(run.verdict(&path("SHA256SUMS")), None);
+        assert_eq!(run.summary(), "2 passed, 1 failed, 1 missing");
+
+        // f walks the failures
+        run.handle_input(KeyCode::Char('f'));
+        assert_eq!(run.selected, 1);
+        run.handle_input(KeyCode::Char('f'));
+        assert_eq!(run.selected, 2);
+    }
+}
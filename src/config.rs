@@ -0,0 +1,378 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::actions::{Profile, QuitConfirm};
+use crate::custom_commands::{CommandSpec, CustomCommand};
+use crate::graphics::GraphicsProtocol;
+use crate::models::{Grouping, NameOrder};
+use crate::ui::ColorLevel;
+use crate::utils::{config_dir, expand_home, home_dir, TimeFormat, DEFAULT_DATE_FORMAT};
+
+/// User settings read from `~/.config/fsnav/config.toml`
+///
+/// Every section is optional; missing keys fall back to the defaults below.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// `admin` (default) or `basic`, which withholds root tools, shells,
+    /// custom commands and launching other programs
+    pub profile: Profile,
+    /// `full`, `ansi16`, `ansi8` or `monochrome`; detected from `TERM`,
+    /// `COLORTERM` and `NO_COLOR` when unset
+    pub colors: Option<ColorLevel>,
+    /// `never` (default), `twice` or `busy`: whether `q` must be pressed
+    /// again before quitting
+    pub confirm_quit: QuitConfirm,
+    /// Applications offered in the "Open with…" menu, keyed by lowercase extension
+    pub open_with: HashMap<String, Vec<String>>,
+    pub dates: DateConfig,
+    pub sort: SortConfig,
+    pub snapshot: SnapshotConfig,
+    pub search: SearchConfig,
+    pub preview: PreviewConfig,
+    pub extract: ExtractConfig,
+    /// User-defined shell commands, keyed by the name shown in the palette
+    pub commands: BTreeMap<String, CommandSpec>,
+    /// Templates the chmod interface lists after its built-in ones
+    pub chmod_templates: Vec<PermissionTemplate>,
+}
+
+/// A `[[chmod_templates]]` entry
+#[derive(Debug, Clone, Deserialize)]
+pub struct PermissionTemplate {
+    pub name: String,
+    /// Octal mode such as "750"
+    pub mode: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// The `[sort]` section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SortConfig {
+    /// `natural` (default), `lexicographic` or `locale`
+    pub name_order: NameOrder,
+    /// `dirs_first` (default), `mixed` or `files_first`
+    pub grouping: Grouping,
+}
+
+/// The `[snapshot]` section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SnapshotConfig {
+    /// Record a content hash of every file, catching same-size edits
+    pub hash_contents: bool,
+}
+
+/// The `[search]` section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SearchConfig {
+    /// Globs left out of searches, e.g. `target/` or `*.min.js`
+    pub exclude: Vec<String>,
+    /// Also leave out what `.gitignore` files ignore
+    pub gitignore: bool,
+    /// Search file contents with `rg` when it is installed
+    pub ripgrep: bool,
+    /// Content search starts out reading inside `.tar`, `.tar.gz` and
+    /// `.tgz` archives
+    pub archives: bool,
+    /// Directory walked by the "root" search scope; `~` is the home
+    /// directory, which is also the default
+    pub root: Option<String>,
+}
+
+impl SearchConfig {
+    /// The "root" scope directory with `~` expanded
+    pub fn root_dir(&self) -> Option<PathBuf> {
+        match self.root.as_deref() {
+            None => home_dir(),
+            Some(root) => Some(expand_home(root)),
+        }
+    }
+}
+
+/// The `[preview]` section
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PreviewConfig {
+    /// `kitty`, `iterm2`, `sixel` or `blocks`; detected from the terminal
+    /// when unset
+    pub graphics: Option<GraphicsProtocol>,
+    /// Start with a line-number gutter beside text previews
+    pub line_numbers: bool,
+    /// Start with long lines wrapped instead of cut at the panel edge
+    pub wrap: bool,
+    /// Opening lines of a directory's README shown under the file list;
+    /// 0 turns the strip off
+    pub readme_lines: usize,
+}
+
+impl Default for PreviewConfig {
+    fn default() -> Self {
+        Self {
+            graphics: None,
+            line_numbers: true,
+            wrap: false,
+            readme_lines: 4,
+        }
+    }
+}
+
+impl Config {
+    pub fn color_level(&self) -> ColorLevel {
+        self.colors.unwrap_or_else(ColorLevel::detect)
+    }
+}
+
+impl PreviewConfig {
+    pub fn graphics_protocol(&self) -> GraphicsProtocol {
+        self.graphics.unwrap_or_else(GraphicsProtocol::detect)
+    }
+}
+
+/// The `[extract]` section
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ExtractConfig {
+    /// Always extract into a new directory named after the archive; when
+    /// off, an archive holding a single top-level entry is extracted as is
+    /// (others still get a directory, so they never spill over the listing)
+    pub into_subdirectory: bool,
+    /// Where archives are unpacked before moving into place; `~` is the
+    /// home directory. Defaults to `$TMPDIR`, or `/tmp`
+    pub staging_dir: Option<String>,
+}
+
+impl Default for ExtractConfig {
+    fn default() -> Self {
+        Self {
+            into_subdirectory: true,
+            staging_dir: None,
+        }
+    }
+}
+
+impl ExtractConfig {
+    /// The staging directory with `~` expanded
+    pub fn staging_root(&self) -> PathBuf {
+        match self.staging_dir.as_deref() {
+            None => std::env::temp_dir(),
+            Some(dir) => expand_home(dir),
+        }
+    }
+}
+
+/// The `[dates]` section: how modification times are shown
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DateConfig {
+    /// `strftime` pattern for absolute dates; `%b`/`%a` follow the locale
+    pub format: String,
+    /// Start with relative ages ("3h ago") instead of absolute dates
+    pub relative: bool,
+}
+
+impl Default for DateConfig {
+    fn default() -> Self {
+        Self {
+            format: DEFAULT_DATE_FORMAT.to_string(),
+            relative: false,
+        }
+    }
+}
+
+impl Config {
+    pub fn load() -> Result<Self> {
+        let path = config_dir()?.join("config.toml");
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Self::load_from(&path)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Invalid config {}", path.display()))
+    }
+
+    pub fn custom_commands(&self) -> Vec<CustomCommand> {
+        self.commands
+            .iter()
+            .map(|(name, spec)| CustomCommand::new(name, spec))
+            .collect()
+    }
+
+    pub fn time_format(&self) -> TimeFormat {
+        TimeFormat {
+            pattern: self.dates.format.clone(),
+            relative: self.dates.relative,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_open_with_table() {
+        let config: Config = toml::from_str(
+            r#"
+            [open_with]
+            pdf = ["zathura", "evince --fullscreen"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.open_with.get("pdf").unwrap(),
+            &vec!["zathura".to_string(), "evince --fullscreen".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_empty_config_uses_defaults() {
+        let config: Config = toml::from_str("").unwrap();
+        assert!(config.open_with.is_empty());
+        assert_eq!(config.time_format(), TimeFormat::default());
+        assert_eq!(config.confirm_quit, QuitConfirm::Never);
+    }
+
+    #[test]
+    fn test_parse_dates_section() {
+        let config: Config = toml::from_str(
+            r#"
+            [dates]
+            relative = true
+            "#,
+        )
+        .unwrap();
+
+        let format = config.time_format();
+        assert!(format.relative);
+        assert_eq!(format.pattern, DEFAULT_DATE_FORMAT);
+    }
+
+    #[test]
+    fn test_parse_sort_section() {
+        let config: Config = toml::from_str(
+            r#"
+            [sort]
+            name_order = "lexicographic"
+            grouping = "files_first"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.sort.name_order, NameOrder::Lexicographic);
+        assert_eq!(config.sort.grouping, Grouping::FilesFirst);
+
+        assert!(toml::from_str::<Config>("[sort]\nname_order = \"random\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_profile() {
+        assert_eq!(Config::default().profile, Profile::Admin);
+        let config: Config = toml::from_str("profile = \"basic\"").unwrap();
+        assert_eq!(config.profile, Profile::Basic);
+        assert!(toml::from_str::<Config>("profile = \"guest\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_search_section() {
+        let config: Config = toml::from_str(
+            r#"
+            [search]
+            exclude = ["target/", "*.min.js"]
+            gitignore = true
+            ripgrep = true
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.search.exclude, ["target/", "*.min.js"]);
+        assert!(config.search.gitignore);
+        assert!(config.search.ripgrep);
+        assert_eq!(config.search.root_dir(), home_dir());
+
+        let config: Config = toml::from_str("[search]\nroot = \"/srv\"").unwrap();
+        assert_eq!(config.search.root_dir(), Some(PathBuf::from("/srv")));
+    }
+
+    #[test]
+    fn test_parse_chmod_templates() {
+        assert!(Config::default().chmod_templates.is_empty());
+        let config: Config = toml::from_str(
+            r#"
+            [[chmod_templates]]
+            name = "Web root"
+            mode = "750"
+            description = "Deployed sites, read by the web server group"
+
+            [[chmod_templates]]
+            name = "Drop box"
+            mode = "733"
+            "#,
+        )
+        .unwrap();
+        let templates: Vec<(&str, &str, &str)> = config
+            .chmod_templates
+            .iter()
+            .map(|t| (t.name.as_str(), t.mode.as_str(), t.description.as_str()))
+            .collect();
+        assert_eq!(
+            templates,
+            [
+                (
+                    "Web root",
+                    "750",
+                    "Deployed sites, read by the web server group"
+                ),
+                ("Drop box", "733", ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_colors() {
+        assert_eq!(Config::default().colors, None);
+        let config: Config = toml::from_str("colors = \"ansi8\"").unwrap();
+        assert_eq!(config.color_level(), ColorLevel::Ansi8);
+        assert!(toml::from_str::<Config>("colors = \"sepia\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_preview_section() {
+        assert_eq!(Config::default().preview.graphics, None);
+        let config: Config = toml::from_str("[preview]\ngraphics = \"sixel\"").unwrap();
+        assert_eq!(config.preview.graphics_protocol(), GraphicsProtocol::Sixel);
+        assert!(toml::from_str::<Config>("[preview]\ngraphics = \"ascii\"").is_err());
+
+        let config: Config = toml::from_str("[preview]\nwrap = true").unwrap();
+        assert!(config.preview.line_numbers);
+        assert!(config.preview.wrap);
+    }
+
+    #[test]
+    fn test_parse_extract_section() {
+        assert!(Config::default().extract.into_subdirectory);
+        assert_eq!(
+            Config::default().extract.staging_root(),
+            std::env::temp_dir()
+        );
+        let config: Config = toml::from_str(
+            r#"
+            [extract]
+            into_subdirectory = false
+            staging_dir = "/var/tmp"
+            "#,
+        )
+        .unwrap();
+        assert!(!config.extract.into_subdirectory);
+        assert_eq!(config.extract.staging_root(), PathBuf::from("/var/tmp"));
+    }
+}
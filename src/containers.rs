@@ -0,0 +1,423 @@
+use anyhow::{bail, Context, Result};
+use crossterm::{
+    cursor::MoveTo,
+    event::KeyCode,
+    execute,
+    style::{Color, Print, ResetColor},
+    terminal,
+};
+use serde::Deserialize;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::ui::{SetBackgroundColor, SetForegroundColor};
+use crate::utils::find_program;
+
+/// Container engines tried in order; both accept the same `ps`/`inspect` flags
+const RUNTIMES: &[&str] = &["docker", "podman"];
+
+/// The installed container engine, if any
+pub fn container_runtime() -> Option<PathBuf> {
+    RUNTIMES.iter().find_map(|program| find_program(program))
+}
+
+/// A mount as reported by `docker inspect`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Mount {
+    /// `bind`, `volume` or `tmpfs`
+    #[serde(rename = "Type", default)]
+    pub kind: String,
+    /// Volume name, for named volumes
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Host-side path
+    #[serde(default)]
+    pub source: String,
+    /// Path inside the container
+    #[serde(default)]
+    pub destination: String,
+    #[serde(rename = "RW", default)]
+    pub read_write: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct InspectConfig {
+    #[serde(default)]
+    image: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct InspectState {
+    #[serde(default)]
+    status: String,
+}
+
+/// One element of the `docker inspect` output array
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Inspect {
+    name: String,
+    config: Option<InspectConfig>,
+    state: Option<InspectState>,
+    #[serde(default)]
+    mounts: Vec<Mount>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Container {
+    pub name: String,
+    pub image: String,
+    pub status: String,
+    pub mounts: Vec<Mount>,
+}
+
+/// Parse `docker inspect` (or `podman inspect`) JSON
+fn parse_inspect(json: &str) -> Result<Vec<Container>> {
+    let inspected: Vec<Inspect> =
+        serde_json::from_str(json).context("Unexpected inspect output")?;
+    Ok(inspected
+        .into_iter()
+        .map(|inspect| Container {
+            // Docker prefixes names with a slash, Podman does not
+            name: inspect.name.trim_start_matches('/').to_string(),
+            image: inspect.config.map(|c| c.image).unwrap_or_default(),
+            status: inspect.state.map(|s| s.status).unwrap_or_default(),
+            mounts: inspect.mounts,
+        })
+        .collect())
+}
+
+fn run(program: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .with_context(|| format!("Failed to run {}", program.display()))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "{}",
+            stderr.lines().next().unwrap_or("command failed").trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Every container known to `program`, running or not, with its mounts
+pub fn list_containers(program: &Path) -> Result<Vec<Container>> {
+    let ids = run(program, &["ps", "--all", "--quiet"])?;
+    let ids: Vec<&str> = ids.split_whitespace().collect();
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut args = vec!["inspect"];
+    args.extend(ids);
+    let mut containers = parse_inspect(&run(program, &args)?)?;
+    containers.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(containers)
+}
+
+pub enum ContainersOutcome {
+    Continue,
+    Close,
+    /// Show the host side of a mount in the listing
+    Jump(PathBuf),
+}
+
+/// Containers and their mounts, each mount leading to its host path
+pub struct ContainerVolumes {
+    program: PathBuf,
+    containers: Vec<Container>,
+    /// (container, mount) index of every selectable row
+    rows: Vec<(usize, usize)>,
+    selected: usize,
+    status: Option<String>,
+}
+
+impl ContainerVolumes {
+    pub fn new(program: &Path) -> Result<Self> {
+        let mut view = Self {
+            program: program.to_path_buf(),
+            containers: Vec::new(),
+            rows: Vec::new(),
+            selected: 0,
+            status: None,
+        };
+        view.set_containers(list_containers(program)?);
+        Ok(view)
+    }
+
+    fn set_containers(&mut self, containers: Vec<Container>) {
+        self.rows = containers
+            .iter()
+            .enumerate()
+            .flat_map(|(c, container)| (0..container.mounts.len()).map(move |m| (c, m)))
+            .collect();
+        self.containers = containers;
+        self.selected = self.selected.min(self.rows.len().saturating_sub(1));
+    }
+
+    fn refresh(&mut self) {
+        match list_containers(&self.program) {
+            Ok(containers) => {
+                self.set_containers(containers);
+                self.status = None;
+            }
+            Err(e) => self.status = Some(format!("Refresh failed: {:#}", e)),
+        }
+    }
+
+    fn selected_mount(&self) -> Option<&Mount> {
+        let &(c, m) = self.rows.get(self.selected)?;
+        self.containers.get(c)?.mounts.get(m)
+    }
+
+    /// Display lines: a header per container, then one line per mount; the
+    /// second value is the row index of mount lines
+    fn lines(&self) -> Vec<(String, Option<usize>)> {
+        let mut lines = Vec::new();
+        let mut row = 0;
+        for container in &self.containers {
+            lines.push((
+                format!(
+                    "{}  {}  ({})",
+                    container.name, container.image, container.status
+                ),
+                None,
+            ));
+            if container.mounts.is_empty() {
+                lines.push(("    (no mounts)".to_string(), None));
+            }
+            for mount in &container.mounts {
+                let source = match (&mount.name, mount.kind.as_str()) {
+                    (Some(name), "volume") => format!("{} → {}", name, mount.source),
+                    _ => mount.source.clone(),
+                };
+                lines.push((
+                    format!(
+                        "    {:<6} {} {}  ⇢ {}",
+                        mount.kind,
+                        if mount.read_write { "rw" } else { "ro" },
+                        source,
+                        mount.destination
+                    ),
+                    Some(row),
+                ));
+                row += 1;
+            }
+        }
+        lines
+    }
+
+    pub fn render(&self) -> Result<()> {
+        let mut stdout = io::stdout();
+        let (terminal_width, terminal_height) = terminal::size()?;
+        let width = terminal_width as usize;
+
+        execute!(stdout, terminal::Clear(terminal::ClearType::All))?;
+
+        let header: String = format!(
+            " 🐳 CONTAINER VOLUMES ({})",
+            self.program
+                .file_name()
+                .map(|n| n.to_string_lossy())
+                .unwrap_or_default()
+        )
+        .chars()
+        .take(width)
+        .collect();
+        execute!(
+            stdout,
+            MoveTo(0, 0),
+            SetBackgroundColor(Color::DarkBlue),
+            SetForegroundColor(Color::White),
+            Print(format!("{:<width$}", header, width = width)),
+            ResetColor
+        )?;
+
+        let info = self.status.clone().unwrap_or_else(|| {
+            format!(
+                "{} containers, {} mounts",
+                self.containers.len(),
+                self.rows.len()
+            )
+        });
+        execute!(
+            stdout,
+            MoveTo(1, 1),
+            SetForegroundColor(Color::Yellow),
+            Print(
+                info.chars()
+                    .take(width.saturating_sub(2))
+                    .collect::<String>()
+            ),
+            ResetColor
+        )?;
+
+        if self.containers.is_empty() {
+            execute!(
+                stdout,
+                MoveTo(1, 3),
+                SetForegroundColor(Color::DarkGrey),
+                Print("No containers"),
+                ResetColor
+            )?;
+        }
+
+        let lines = self.lines();
+        let list_height = (terminal_height as usize).saturating_sub(4);
+        let selected_line = lines
+            .iter()
+            .position(|(_, row)| *row == Some(self.selected))
+            .unwrap_or(0);
+        let start = selected_line.saturating_sub(list_height.saturating_sub(1));
+
+        for (i, (line, row)) in lines.iter().skip(start).take(list_height).enumerate() {
+            let is_selected = *row == Some(self.selected);
+            let color = match row {
+                _ if is_selected => Color::White,
+                None => Color::Cyan,
+                Some(_) => Color::Reset,
+            };
+            execute!(
+                stdout,
+                MoveTo(1, 3 + i as u16),
+                SetBackgroundColor(if is_selected {
+                    Color::DarkGreen
+                } else {
+                    Color::Reset
+                }),
+                SetForegroundColor(color),
+                Print(format!(
+                    "{:<w$}",
+                    line.chars()
+                        .take(width.saturating_sub(2))
+                        .collect::<String>(),
+                    w = width.saturating_sub(2)
+                )),
+                ResetColor
+            )?;
+        }
+
+        let footer = " ↑↓: Select mount | Enter: Go to host path | r: Refresh | Esc/q: Close";
+        execute!(
+            stdout,
+            MoveTo(0, terminal_height.saturating_sub(1)),
+            SetBackgroundColor(Color::DarkGrey),
+            SetForegroundColor(Color::White),
+            Print(format!(
+                "{:<width$}",
+                footer.chars().take(width).collect::<String>(),
+                width = width
+            )),
+            ResetColor
+        )?;
+
+        stdout.flush()?;
+        Ok(())
+    }
+
+    pub fn handle_input(&mut self, key: KeyCode) -> ContainersOutcome {
+        match key {
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down if self.selected + 1 < self.rows.len() => self.selected += 1,
+            KeyCode::Home => self.selected = 0,
+            KeyCode::End => self.selected = self.rows.len().saturating_sub(1),
+            KeyCode::Char('r') => self.refresh(),
+            KeyCode::Enter => {
+                if let Some(mount) = self.selected_mount() {
+                    let source = PathBuf::from(&mount.source);
+                    if mount.source.is_empty() {
+                        self.status = Some("This mount has no host path".to_string());
+                    } else if !source.exists() {
+                        self.status =
+                            Some(format!("{} is not accessible from here", source.display()));
+                    } else {
+                        return ContainersOutcome::Jump(source);
+                    }
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => return ContainersOutcome::Close,
+            _ => {}
+        }
+        ContainersOutcome::Continue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const INSPECT: &str = r#"[
+        {
+            "Id": "3f2a",
+            "Name": "/web",
+            "Config": {"Image": "nginx:latest"},
+            "State": {"Status": "running"},
+            "Mounts": [
+                {"Type": "bind", "Source": "SOURCE", "Destination": "/usr/share/nginx/html", "RW": false},
+                {"Type": "volume", "Name": "web-logs", "Source": "/var/lib/docker/volumes/web-logs/_data", "Destination": "/var/log/nginx", "RW": true}
+            ]
+        },
+        {"Id": "9c1e", "Name": "cache", "Config": {"Image": "redis"}, "State": {"Status": "exited"}, "Mounts": []}
+    ]"#;
+
+    #[test]
+    fn test_parse_inspect() {
+        let containers = parse_inspect(INSPECT).unwrap();
+        assert_eq!(containers.len(), 2);
+        assert_eq!(containers[0].name, "web");
+        assert_eq!(containers[0].image, "nginx:latest");
+        assert_eq!(containers[0].mounts[1].name.as_deref(), Some("web-logs"));
+        assert!(containers[0].mounts[1].read_write);
+        assert_eq!(containers[1].name, "cache");
+        assert!(parse_inspect("not json").is_err());
+    }
+
+    #[test]
+    fn test_fake_runtime_and_jump() {
+        let dir = TempDir::new().unwrap();
+        let site = dir.path().join("site");
+        fs::create_dir(&site).unwrap();
+        let inspect = INSPECT.replace("SOURCE", &site.to_string_lossy());
+        fs::write(dir.path().join("inspect.json"), inspect).unwrap();
+
+        // Answers `ps` with two ids and `inspect` with the fixture
+        let program = dir.path().join("docker");
+        fs::write(
+            &program,
+            format!(
+                "#!/bin/sh\nif [ \"$1\" = ps ]; then printf '%s\\n' 3f2a 9c1e; else cat '{}'; fi\n",
+                dir.path().join("inspect.json").display()
+            ),
+        )
+        .unwrap();
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut view = ContainerVolumes::new(&program).unwrap();
+        // Sorted by name: "cache" (no mounts) comes first
+        assert_eq!(view.containers[0].name, "cache");
+        assert_eq!(view.rows, vec![(1, 0), (1, 1)]);
+        assert!(view.lines()[1].0.contains("(no mounts)"));
+
+        assert!(matches!(
+            view.handle_input(KeyCode::Enter),
+            ContainersOutcome::Jump(ref path) if *path == site
+        ));
+        // The volume's data directory does not exist on this machine
+        view.handle_input(KeyCode::Down);
+        assert!(matches!(
+            view.handle_input(KeyCode::Enter),
+            ContainersOutcome::Continue
+        ));
+        assert!(view.status.is_some());
+    }
+}
@@ -0,0 +1,313 @@
+use crate::ui::{SetBackgroundColor, SetForegroundColor};
+use anyhow::Result;
+use crossterm::{
+    cursor::MoveTo,
+    event::KeyCode,
+    execute,
+    style::{Color, Print, ResetColor},
+    terminal,
+};
+use serde::Deserialize;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// A `[commands]` entry: either a bare command line or a table with a key
+///
+/// ```toml
+/// [commands]
+/// gzip = "tar czf %f.tar.gz %f"
+/// sizes = { command = "du -sh %s", key = "D" }
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum CommandSpec {
+    Line(String),
+    Full {
+        command: String,
+        #[serde(default)]
+        key: Option<char>,
+    },
+}
+
+/// A user-defined shell command runnable from a key or the command palette
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomCommand {
+    pub name: String,
+    pub template: String,
+    pub key: Option<char>,
+}
+
+impl CustomCommand {
+    pub fn new(name: &str, spec: &CommandSpec) -> Self {
+        let (template, key) = match spec {
+            CommandSpec::Line(command) => (command.clone(), None),
+            CommandSpec::Full { command, key } => (command.clone(), *key),
+        };
+        Self {
+            name: name.to_string(),
+            template,
+            key,
+        }
+    }
+
+    /// Substitute placeholders, shell-quoting every path:
+    /// `%f` the highlighted entry, `%s` all selected entries, `%d` the
+    /// current directory and `%%` a literal percent sign
+    pub fn expand(&self, file: Option<&Path>, selected: &[PathBuf], dir: &Path) -> String {
+        let mut out = String::new();
+        let mut chars = self.template.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('f') => out.push_str(&file.map(shell_quote).unwrap_or_default()),
+                Some('s') => {
+                    let quoted: Vec<String> = selected.iter().map(|p| shell_quote(p)).collect();
+                    out.push_str(&quoted.join(" "));
+                }
+                Some('d') => out.push_str(&shell_quote(dir)),
+                Some('%') => out.push('%'),
+                Some(other) => {
+                    out.push('%');
+                    out.push(other);
+                }
+                None => out.push('%'),
+            }
+        }
+        out
+    }
+
+    /// Run the expanded command line through `sh`, collecting its output
+    pub fn run(&self, command_line: &str, dir: &Path) -> io::Result<CommandOutput> {
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(command_line)
+            .current_dir(dir)
+            .stdin(Stdio::inherit())
+            .output()?;
+
+        let mut lines: Vec<String> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|l| l.replace('\t', "    "))
+            .collect();
+        lines.extend(
+            String::from_utf8_lossy(&output.stderr)
+                .lines()
+                .map(|l| l.replace('\t', "    ")),
+        );
+
+        Ok(CommandOutput {
+            title: self.name.clone(),
+            command_line: command_line.to_string(),
+            lines,
+            status: output.status.code(),
+            scroll_offset: 0,
+        })
+    }
+}
+
+/// `path` quoted for `sh`; bytes that are not UTF-8 are spelled as octal
+/// `printf` escapes, so the command names the very same file
+pub fn shell_quote(path: &Path) -> String {
+    let mut quoted = String::new();
+    for chunk in path.as_os_str().as_bytes().utf8_chunks() {
+        if !chunk.valid().is_empty() {
+            let _ = write!(quoted, "'{}'", chunk.valid().replace('\'', r"'\''"));
+        }
+        if !chunk.invalid().is_empty() {
+            quoted.push_str("\"$(printf '");
+            for byte in chunk.invalid() {
+                let _ = write!(quoted, "\\{:03o}", byte);
+            }
+            quoted.push_str("')\"");
+        }
+    }
+    if quoted.is_empty() {
+        quoted.push_str("''");
+    }
+    quoted
+}
+
+/// Scrollable view of a finished custom command's output
+pub struct CommandOutput {
+    title: String,
+    command_line: String,
+    lines: Vec<String>,
+    /// Exit code, `None` when the command was killed by a signal
+    status: Option<i32>,
+    scroll_offset: usize,
+}
+
+impl CommandOutput {
+    pub fn succeeded(&self) -> bool {
+        self.status == Some(0)
+    }
+
+    pub fn render(&self) -> Result<()> {
+        let mut stdout = io::stdout();
+        let (terminal_width, terminal_height) = terminal::size()?;
+        let width = terminal_width as usize;
+
+        execute!(stdout, terminal::Clear(terminal::ClearType::All))?;
+
+        let header: String = format!(" ⚙ {}: {}", self.title, self.command_line)
+            .chars()
+            .take(width)
+            .collect();
+        execute!(
+            stdout,
+            MoveTo(0, 0),
+            SetBackgroundColor(Color::DarkBlue),
+            SetForegroundColor(Color::White),
+            Print(format!("{:<width$}", header, width = width)),
+            ResetColor
+        )?;
+
+        let status = match self.status {
+            Some(0) => "exit status 0".to_string(),
+            Some(code) => format!("exit status {}", code),
+            None => "terminated by signal".to_string(),
+        };
+        execute!(
+            stdout,
+            MoveTo(1, 1),
+            SetForegroundColor(if self.succeeded() {
+                Color::Green
+            } else {
+                Color::Red
+            }),
+            Print(format!("{} — {} lines", status, self.lines.len())),
+            ResetColor
+        )?;
+
+        let body_height = (terminal_height as usize).saturating_sub(4);
+        for (i, line) in self
+            .lines
+            .iter()
+            .skip(self.scroll_offset)
+            .take(body_height)
+            .enumerate()
+        {
+            execute!(
+                stdout,
+                MoveTo(1, 3 + i as u16),
+                Print(
+                    line.chars()
+                        .take(width.saturating_sub(2))
+                        .collect::<String>()
+                )
+            )?;
+        }
+
+        let footer = " ↑↓: Scroll | PageUp/Down: Page | Esc/q/Enter: Close";
+        execute!(
+            stdout,
+            MoveTo(0, terminal_height.saturating_sub(1)),
+            SetBackgroundColor(Color::DarkGrey),
+            SetForegroundColor(Color::White),
+            Print(format!(
+                "{:<width$}",
+                footer.chars().take(width).collect::<String>(),
+                width = width
+            )),
+            ResetColor
+        )?;
+
+        stdout.flush()?;
+        Ok(())
+    }
+
+    /// Returns false once the view should close
+    pub fn handle_input(&mut self, key: KeyCode) -> bool {
+        let max_offset = self.lines.len().saturating_sub(1);
+        match key {
+            KeyCode::Up => self.scroll_offset = self.scroll_offset.saturating_sub(1),
+            KeyCode::Down => self.scroll_offset = (self.scroll_offset + 1).min(max_offset),
+            KeyCode::PageUp => self.scroll_offset = self.scroll_offset.saturating_sub(10),
+            KeyCode::PageDown => self.scroll_offset = (self.scroll_offset + 10).min(max_offset),
+            KeyCode::Home => self.scroll_offset = 0,
+            KeyCode::End => self.scroll_offset = max_offset,
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter => return false,
+            _ => {}
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(template: &str) -> CustomCommand {
+        CustomCommand::new("test", &CommandSpec::Line(template.to_string()))
+    }
+
+    #[test]
+    fn test_expand_placeholders() {
+        let selected = vec![PathBuf::from("/tmp/a b"), PathBuf::from("/tmp/c")];
+        let expanded = command("tar czf %f.tar.gz %f; ls %s %d 100%% %x").expand(
+            Some(Path::new("/tmp/a b")),
+            &selected,
+            Path::new("/tmp"),
+        );
+        assert_eq!(
+            expanded,
+            "tar czf '/tmp/a b'.tar.gz '/tmp/a b'; ls '/tmp/a b' '/tmp/c' '/tmp' 100% %x"
+        );
+    }
+
+    #[test]
+    fn test_quotes_are_escaped() {
+        let expanded = command("cat %f").expand(Some(Path::new("it's")), &[], Path::new("/"));
+        assert_eq!(expanded, r"cat 'it'\''s'");
+    }
+
+    #[test]
+    fn test_non_utf8_bytes_are_kept() {
+        use std::ffi::OsStr;
+
+        let path = Path::new(OsStr::from_bytes(b"/tmp/caf\xe9 it's"));
+        let quoted = shell_quote(path);
+        assert_eq!(quoted, r#"'/tmp/caf'"$(printf '\351')"' it'\''s'"#);
+
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(format!("printf %s {}", quoted))
+            .output()
+            .unwrap();
+        assert_eq!(output.stdout, path.as_os_str().as_bytes());
+    }
+
+    #[test]
+    fn test_parse_specs() {
+        let specs: std::collections::BTreeMap<String, CommandSpec> = toml::from_str(
+            r#"
+            gzip = "tar czf %f.tar.gz %f"
+            sizes = { command = "du -sh %s", key = "D" }
+            "#,
+        )
+        .unwrap();
+
+        let sizes = CustomCommand::new("sizes", &specs["sizes"]);
+        assert_eq!(sizes.template, "du -sh %s");
+        assert_eq!(sizes.key, Some('D'));
+        assert_eq!(CustomCommand::new("gzip", &specs["gzip"]).key, None);
+    }
+
+    #[test]
+    fn test_run_captures_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = command("")
+            .run("echo out; echo err >&2; exit 3", dir.path())
+            .unwrap();
+        assert_eq!(output.lines, vec!["out", "err"]);
+        assert_eq!(output.status, Some(3));
+        assert!(!output.succeeded());
+    }
+}
@@ -0,0 +1,730 @@
+use anyhow::{bail, Context, Result};
+use crossterm::{
+    cursor::MoveTo,
+    event::KeyCode,
+    execute,
+    style::{Color, Print, ResetColor},
+    terminal,
+};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::tasks::Task;
+use crate::ui::{SetBackgroundColor, SetForegroundColor};
+
+/// Files larger than this are not compared
+const MAX_DIFF_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Past this many inserted and deleted lines the search for the shortest
+/// edit gives up and shows the rest as replaced
+const MAX_EDITS: usize = 2_000;
+
+/// Unchanged lines kept around each change
+const CONTEXT: usize = 3;
+
+/// How long opening the view waits before drawing it as still loading
+const LOAD_GRACE: Duration = Duration::from_millis(50);
+
+/// One step turning the old file into the new one, by line index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit {
+    Same(usize, usize),
+    Removed(usize),
+    Added(usize),
+}
+
+/// A row of the diff as drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiffRow {
+    /// `@@ -old_start,old_len +new_start,new_len @@`
+    Hunk {
+        old_start: usize,
+        old_len: usize,
+        new_start: usize,
+        new_len: usize,
+    },
+    Same(usize, usize),
+    /// A removed line, an added one, or side by side both of a replaced pair
+    Change(Option<usize>, Option<usize>),
+}
+
+/// The two files as compared
+#[derive(Debug)]
+enum Comparison {
+    Text {
+        old: Vec<String>,
+        new: Vec<String>,
+        edits: Vec<Edit>,
+    },
+    /// Files with NUL bytes are only compared whole
+    Binary { identical: bool },
+}
+
+/// Lines of `old` and `new` matched up with the fewest insertions and
+/// deletions (Myers' algorithm), after setting aside the common start and end
+pub fn diff_lines(old: &[String], new: &[String]) -> Vec<Edit> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut edits: Vec<Edit> = (0..prefix).map(|i| Edit::Same(i, i)).collect();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+    match shortest_edit(old_middle, new_middle) {
+        Some(middle) => edits.extend(middle.into_iter().map(|edit| match edit {
+            Edit::Same(i, j) => Edit::Same(i + prefix, j + prefix),
+            Edit::Removed(i) => Edit::Removed(i + prefix),
+            Edit::Added(j) => Edit::Added(j + prefix),
+        })),
+        None => {
+            edits.extend((prefix..old.len() - suffix).map(Edit::Removed));
+            edits.extend((prefix..new.len() - suffix).map(Edit::Added));
+        }
+    }
+    edits.extend((0..suffix).map(|i| Edit::Same(old.len() - suffix + i, new.len() - suffix + i)));
+    edits
+}
+
+/// Myers' greedy search, or `None` past [`MAX_EDITS`]
+fn shortest_edit(old: &[String], new: &[String]) -> Option<Vec<Edit>> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = (old.len() + new.len()).min(MAX_EDITS) as isize;
+    // Furthest x reached on each diagonal k = x - y, at v[k + offset]
+    let offset = max + 1;
+    let mut v = vec![0isize; 2 * max as usize + 3];
+    // v as it stood before each round, for diagonals -(d + 1)..=(d + 1)
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    for d in 0..=max {
+        trace.push(v[(offset - d - 1) as usize..=(offset + d + 1) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let at = |k: isize| v[(k + offset) as usize];
+            let mut x = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+                at(k + 1)
+            } else {
+                at(k - 1) + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[(k + offset) as usize] = x;
+            if x >= n && y >= m {
+                return Some(backtrack(&trace, n, m));
+            }
+        }
+    }
+    None
+}
+
+/// Walk the recorded rounds back from the end to list the edits
+fn backtrack(trace: &[Vec<isize>], n: isize, m: isize) -> Vec<Edit> {
+    let (mut x, mut y) = (n, m);
+    let mut edits = Vec::new();
+
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let at = |k: isize| v[(k + d + 1) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = at(prev_k);
+        let prev_y = prev_x - prev_k;
+
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            edits.push(Edit::Same(x as usize, y as usize));
+        }
+        if d > 0 {
+            if x == prev_x {
+                edits.push(Edit::Added(prev_y as usize));
+            } else {
+                edits.push(Edit::Removed(prev_x as usize));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+
+    edits.reverse();
+    edits
+}
+
+/// Changes with [`CONTEXT`] lines around them, grouped into hunks; side
+/// by side pairs each removed line with an added one
+fn diff_rows(edits: &[Edit], side_by_side: bool) -> Vec<DiffRow> {
+    let changed: Vec<usize> = edits
+        .iter()
+        .enumerate()
+        .filter(|(_, edit)| !matches!(edit, Edit::Same(..)))
+        .map(|(i, _)| i)
+        .collect();
+
+    // Ranges of edits shown, merged when their context touches
+    let mut groups: Vec<(usize, usize)> = Vec::new();
+    for &i in &changed {
+        let start = i.saturating_sub(CONTEXT);
+        let end = (i + CONTEXT + 1).min(edits.len());
+        match groups.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => groups.push((start, end)),
+        }
+    }
+
+    let mut rows = Vec::new();
+    for (start, end) in groups {
+        let group = &edits[start..end];
+        let old_before = edits[..start]
+            .iter()
+            .filter(|e| !matches!(e, Edit::Added(_)))
+            .count();
+        let new_before = edits[..start]
+            .iter()
+            .filter(|e| !matches!(e, Edit::Removed(_)))
+            .count();
+        let old_len = group
+            .iter()
+            .filter(|e| !matches!(e, Edit::Added(_)))
+            .count();
+        let new_len = group
+            .iter()
+            .filter(|e| !matches!(e, Edit::Removed(_)))
+            .count();
+        // Like `diff -u`, an empty side starts at the line before
+        rows.push(DiffRow::Hunk {
+            old_start: old_before + usize::from(old_len > 0),
+            old_len,
+            new_start: new_before + usize::from(new_len > 0),
+            new_len,
+        });
+
+        let mut i = 0;
+        while i < group.len() {
+            if let Edit::Same(a, b) = group[i] {
+                rows.push(DiffRow::Same(a, b));
+                i += 1;
+                continue;
+            }
+            let mut removed = Vec::new();
+            let mut added = Vec::new();
+            while let Some(edit) = group.get(i) {
+                match *edit {
+                    Edit::Removed(a) => removed.push(a),
+                    Edit::Added(b) => added.push(b),
+                    Edit::Same(..) => break,
+                }
+                i += 1;
+            }
+            if side_by_side {
+                for row in 0..removed.len().max(added.len()) {
+                    rows.push(DiffRow::Change(
+                        removed.get(row).copied(),
+                        added.get(row).copied(),
+                    ));
+                }
+            } else {
+                rows.extend(removed.into_iter().map(|a| DiffRow::Change(Some(a), None)));
+                rows.extend(added.into_iter().map(|b| DiffRow::Change(None, Some(b))));
+            }
+        }
+    }
+    rows
+}
+
+/// Contents of a file to compare, or `None` if it is binary
+fn read_lines(path: &Path) -> Result<Option<Vec<String>>> {
+    let size = fs::metadata(path)
+        .with_context(|| format!("Cannot read {}", path.display()))?
+        .len();
+    if size > MAX_DIFF_BYTES {
+        bail!("{} is too large to compare", path.display());
+    }
+    let bytes = fs::read(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    if bytes.contains(&0) {
+        return Ok(None);
+    }
+    Ok(Some(
+        String::from_utf8_lossy(&bytes)
+            .lines()
+            .map(str::to_string)
+            .collect(),
+    ))
+}
+
+fn compare(old_path: &Path, new_path: &Path) -> Result<Comparison> {
+    match (read_lines(old_path)?, read_lines(new_path)?) {
+        (Some(old), Some(new)) => {
+            let edits = diff_lines(&old, &new);
+            Ok(Comparison::Text { old, new, edits })
+        }
+        _ => Ok(Comparison::Binary {
+            identical: fs::read(old_path)? == fs::read(new_path)?,
+        }),
+    }
+}
+
+/// Full-screen comparison of two files, as a unified or side-by-side diff
+pub struct DiffView {
+    old_path: PathBuf,
+    new_path: PathBuf,
+    task: Option<Task<Result<Comparison>>>,
+    comparison: Option<Result<Comparison>>,
+    rows: Vec<DiffRow>,
+    side_by_side: bool,
+    scroll: usize,
+    status: Option<String>,
+}
+
+impl DiffView {
+    pub fn open(old_path: &Path, new_path: &Path) -> Self {
+        let (old, new) = (old_path.to_path_buf(), new_path.to_path_buf());
+        let mut task = Task::spawn(move |_| compare(&old, &new));
+        let loaded = task.wait(LOAD_GRACE);
+        let mut view = Self {
+            old_path: old_path.to_path_buf(),
+            new_path: new_path.to_path_buf(),
+            task: Some(task),
+            comparison: None,
+            rows: Vec::new(),
+            side_by_side: false,
+            scroll: 0,
+            status: None,
+        };
+        if let Some(comparison) = loaded {
+            view.finish_loading(comparison);
+        }
+        view
+    }
+
+    /// Pick up the comparison once the background read finishes
+    pub fn poll(&mut self) {
+        if let Some(comparison) = self.task.as_mut().and_then(|t| t.poll()) {
+            self.finish_loading(comparison);
+        }
+    }
+
+    fn finish_loading(&mut self, comparison: Result<Comparison>) {
+        self.task = None;
+        self.comparison = Some(comparison);
+        self.layout();
+    }
+
+    fn layout(&mut self) {
+        self.rows = match self.comparison {
+            Some(Ok(Comparison::Text { ref edits, .. })) => diff_rows(edits, self.side_by_side),
+            _ => Vec::new(),
+        };
+    }
+
+    /// Lines added and removed
+    fn counts(&self) -> (usize, usize) {
+        match self.comparison {
+            Some(Ok(Comparison::Text { ref edits, .. })) => (
+                edits.iter().filter(|e| matches!(e, Edit::Added(_))).count(),
+                edits
+                    .iter()
+                    .filter(|e| matches!(e, Edit::Removed(_)))
+                    .count(),
+            ),
+            _ => (0, 0),
+        }
+    }
+
+    fn hunk_rows(&self) -> Vec<usize> {
+        self.rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| matches!(row, DiffRow::Hunk { .. }))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// The hunk the top of the screen is in
+    fn current_hunk(&self) -> usize {
+        self.hunk_rows()
+            .iter()
+            .rposition(|&row| row <= self.scroll)
+            .unwrap_or(0)
+    }
+
+    fn page_rows() -> usize {
+        terminal::size()
+            .map(|(_, height)| height.saturating_sub(3) as usize)
+            .unwrap_or(20)
+            .max(1)
+    }
+
+    fn scroll_to(&mut self, row: usize) {
+        self.scroll = row.min(self.rows.len().saturating_sub(1));
+    }
+
+    pub fn render(&self) -> Result<()> {
+        let mut stdout = io::stdout();
+        let (terminal_width, terminal_height) = terminal::size()?;
+        let width = terminal_width as usize;
+        let rows = terminal_height.saturating_sub(3) as usize;
+
+        execute!(stdout, terminal::Clear(terminal::ClearType::All))?;
+
+        let header: String = format!(
+            " ⇄ {}  →  {}",
+            self.old_path.display(),
+            self.new_path.display()
+        )
+        .chars()
+        .take(width)
+        .collect();
+        execute!(
+            stdout,
+            MoveTo(0, 0),
+            SetBackgroundColor(Color::DarkBlue),
+            SetForegroundColor(Color::White),
+            Print(format!("{:<width$}", header, width = width)),
+            ResetColor
+        )?;
+
+        let message = match self.comparison {
+            None => Some("⏳ Comparing…".to_string()),
+            Some(Err(ref e)) => Some(format!("{:#}", e)),
+            Some(Ok(Comparison::Binary { identical: true })) => {
+                Some("Binary files are identical".to_string())
+            }
+            Some(Ok(Comparison::Binary { identical: false })) => {
+                Some("Binary files differ".to_string())
+            }
+            Some(Ok(Comparison::Text { .. })) if self.rows.is_empty() => {
+                Some("Files are identical".to_string())
+            }
+            Some(Ok(Comparison::Text { .. })) => None,
+        };
+        if let Some(message) = message {
+            execute!(
+                stdout,
+                MoveTo(1, 2),
+                SetForegroundColor(Color::DarkGrey),
+                Print(message),
+                ResetColor
+            )?;
+        }
+
+        if let Some(Ok(Comparison::Text {
+            ref old, ref new, ..
+        })) = self.comparison
+        {
+            let number_width = old.len().max(new.len()).max(1).to_string().len();
+            for (screen_row, row) in self.rows.iter().skip(self.scroll).take(rows).enumerate() {
+                execute!(stdout, MoveTo(0, 1 + screen_row as u16))?;
+                if let DiffRow::Hunk {
+                    old_start,
+                    old_len,
+                    new_start,
+                    new_len,
+                } = *row
+                {
+                    execute!(
+                        stdout,
+                        SetForegroundColor(Color::Cyan),
+                        Print(format!(
+                            "@@ -{},{} +{},{} @@",
+                            old_start, old_len, new_start, new_len
+                        )),
+                        ResetColor
+                    )?;
+                    continue;
+                }
+                let (old_line, new_line, changed) = match *row {
+                    DiffRow::Same(a, b) => (Some(a), Some(b), false),
+                    DiffRow::Change(a, b) => (a, b, true),
+                    DiffRow::Hunk { .. } => unreachable!(),
+                };
+                let side = |line: Option<usize>, lines: &[String], sign: char, cell: usize| {
+                    let Some(index) = line else {
+                        return (" ".repeat(cell), Color::Reset);
+                    };
+                    let (sign, color) = if changed {
+                        (
+                            sign,
+                            if sign == '-' {
+                                Color::Red
+                            } else {
+                                Color::Green
+                            },
+                        )
+                    } else {
+                        (' ', Color::Reset)
+                    };
+                    let text = format!(
+                        "{:>w$} {}{}",
+                        index + 1,
+                        sign,
+                        lines[index].replace('\t', "    "),
+                        w = number_width
+                    );
+                    let text: String = text.chars().take(cell).collect();
+                    let padding = cell.saturating_sub(text.chars().count());
+                    (format!("{}{}", text, " ".repeat(padding)), color)
+                };
+
+                if self.side_by_side {
+                    let cell = width.saturating_sub(1) / 2;
+                    let (left, left_color) = side(old_line, old, '-', cell);
+                    let (right, right_color) = side(new_line, new, '+', cell);
+                    execute!(
+                        stdout,
+                        SetForegroundColor(left_color),
+                        Print(left),
+                        SetForegroundColor(Color::DarkGrey),
+                        Print("│"),
+                        SetForegroundColor(right_color),
+                        Print(right),
+                        ResetColor
+                    )?;
+                } else {
+                    // Unchanged lines are numbered as in the new file
+                    let (text, color) = match (old_line, new_line) {
+                        (Some(a), None) => side(Some(a), old, '-', width),
+                        (_, b) => side(b, new, '+', width),
+                    };
+                    execute!(
+                        stdout,
+                        SetForegroundColor(color),
+                        Print(text.trim_end()),
+                        ResetColor
+                    )?;
+                }
+            }
+        }
+
+        let info = if let Some(ref status) = self.status {
+            status.clone()
+        } else {
+            let (added, removed) = self.counts();
+            let hunks = self.hunk_rows().len();
+            format!(
+                "{} hunk{}  +{} -{}  {}",
+                hunks,
+                if hunks == 1 { "" } else { "s" },
+                added,
+                removed,
+                if self.side_by_side {
+                    "side by side"
+                } else {
+                    "unified"
+                }
+            )
+        };
+        execute!(
+            stdout,
+            MoveTo(1, terminal_height.saturating_sub(2)),
+            SetForegroundColor(Color::Yellow),
+            Print(
+                info.chars()
+                    .take(width.saturating_sub(2))
+                    .collect::<String>()
+            ),
+            ResetColor
+        )?;
+
+        let footer = " ↑↓/jk: Line | PgUp/PgDn/Space/b: Page | g/G: Start/End | n/N: Next/Prev hunk | s: Side by side/Unified | q: Close";
+        execute!(
+            stdout,
+            MoveTo(0, terminal_height.saturating_sub(1)),
+            SetBackgroundColor(Color::DarkGrey),
+            SetForegroundColor(Color::White),
+            Print(format!(
+                "{:<width$}",
+                footer.chars().take(width).collect::<String>(),
+                width = width
+            )),
+            ResetColor
+        )?;
+
+        stdout.flush()?;
+        Ok(())
+    }
+
+    /// Returns false once the view should close
+    pub fn handle_input(&mut self, key: KeyCode) -> bool {
+        self.status = None;
+        let page = Self::page_rows();
+        match key {
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_to(self.scroll.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Enter => self.scroll_to(self.scroll + 1),
+            KeyCode::PageUp | KeyCode::Char('b') => {
+                self.scroll_to(self.scroll.saturating_sub(page))
+            }
+            KeyCode::PageDown | KeyCode::Char(' ') => self.scroll_to(self.scroll + page),
+            KeyCode::Home | KeyCode::Char('g') => self.scroll_to(0),
+            KeyCode::End | KeyCode::Char('G') => {
+                self.scroll_to(self.rows.len().saturating_sub(page))
+            }
+            KeyCode::Char(c @ ('n' | 'N')) => {
+                let hunks = self.hunk_rows();
+                let target = if c == 'n' {
+                    hunks.iter().find(|&&row| row > self.scroll)
+                } else {
+                    hunks.iter().rev().find(|&&row| row < self.scroll)
+                };
+                match target {
+                    Some(&row) => self.scroll_to(row),
+                    None => {
+                        self.status = Some(if c == 'n' {
+                            "No more changes below".to_string()
+                        } else {
+                            "No more changes above".to_string()
+                        })
+                    }
+                }
+            }
+            // Stays on the same hunk in the other layout
+            KeyCode::Char('s') => {
+                let hunk = self.current_hunk();
+                self.side_by_side = !self.side_by_side;
+                self.layout();
+                let row = self.hunk_rows().get(hunk).copied().unwrap_or(0);
+                self.scroll_to(row);
+            }
+            KeyCode::Esc | KeyCode::Char('q') => return false,
+            _ => {}
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+
+    /// The edits applied to `old`, which must give `new`
+    fn apply(edits: &[Edit], old: &[String], new: &[String]) -> Vec<String> {
+        edits
+            .iter()
+            .filter_map(|edit| match *edit {
+                Edit::Same(a, b) => {
+                    assert_eq!(old[a], new[b]);
+                    Some(old[a].clone())
+                }
+                Edit::Removed(_) => None,
+                Edit::Added(b) => Some(new[b].clone()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_minimal_line_diff() {
+        let old = lines("a\nb\nc\nd\ne\nf");
+        let new = lines("a\nc\nd\nx\ne\nf\ng");
+        let edits = diff_lines(&old, &new);
+        assert_eq!(apply(&edits, &old, &new), new);
+        let changes = edits
+            .iter()
+            .filter(|e| !matches!(e, Edit::Same(..)))
+            .count();
+        // b removed, x and g added
+        assert_eq!(changes, 3);
+
+        assert!(diff_lines(&old, &old)
+            .iter()
+            .all(|e| matches!(e, Edit::Same(..))));
+        let from_empty = diff_lines(&[], &new);
+        assert_eq!(apply(&from_empty, &[], &new), new);
+    }
+
+    #[test]
+    fn test_hunks_and_layouts() {
+        let old: Vec<String> = (1..=20).map(|i| i.to_string()).collect();
+        let mut new = old.clone();
+        new[1] = "two".to_string();
+        new.remove(15);
+        let edits = diff_lines(&old, &new);
+
+        let unified = diff_rows(&edits, false);
+        let hunks: Vec<&DiffRow> = unified
+            .iter()
+            .filter(|r| matches!(r, DiffRow::Hunk { .. }))
+            .collect();
+        assert_eq!(
+            hunks,
+            [
+                &DiffRow::Hunk {
+                    old_start: 1,
+                    old_len: 5,
+                    new_start: 1,
+                    new_len: 5
+                },
+                &DiffRow::Hunk {
+                    old_start: 13,
+                    old_len: 7,
+                    new_start: 13,
+                    new_len: 6
+                },
+            ]
+        );
+        assert!(unified.contains(&DiffRow::Change(Some(1), None)));
+        assert!(unified.contains(&DiffRow::Change(None, Some(1))));
+
+        // Side by side puts the replaced line next to its replacement
+        let side = diff_rows(&edits, true);
+        assert!(side.contains(&DiffRow::Change(Some(1), Some(1))));
+        assert!(side.contains(&DiffRow::Change(Some(15), None)));
+        assert_eq!(side.len(), unified.len() - 1);
+    }
+
+    #[test]
+    fn test_compare_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = dir.path().join("old.conf");
+        let new = dir.path().join("new.conf");
+        fs::write(&old, "port = 80\nhost = a\n").unwrap();
+        fs::write(&new, "port = 8080\nhost = a\n").unwrap();
+
+        let mut view = DiffView::open(&old, &new);
+        if let Some(comparison) = view
+            .task
+            .as_mut()
+            .and_then(|t| t.wait(Duration::from_secs(5)))
+        {
+            view.finish_loading(comparison);
+        }
+        assert_eq!(view.counts(), (1, 1));
+        assert_eq!(view.hunk_rows(), [0]);
+        assert!(view.handle_input(KeyCode::Char('s')));
+        assert_eq!(view.rows[1], DiffRow::Change(Some(0), Some(0)));
+        assert!(!view.handle_input(KeyCode::Char('q')));
+
+        fs::write(&new, b"\0binary").unwrap();
+        assert!(matches!(
+            compare(&old, &new).unwrap(),
+            Comparison::Binary { identical: false }
+        ));
+    }
+
+    proptest! {
+        #[test]
+        fn prop_edits_rebuild_the_new_file(
+            old in prop::collection::vec("[abc]", 0..40),
+            new in prop::collection::vec("[abc]", 0..40),
+        ) {
+            let edits = diff_lines(&old, &new);
+            prop_assert_eq!(apply(&edits, &old, &new), new.clone());
+            let removed = edits.iter().filter(|e| matches!(e, Edit::Removed(_))).count();
+            prop_assert_eq!(old.len() - removed, new.len() - (edits.len() - old.len()));
+        }
+    }
+}
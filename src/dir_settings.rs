@@ -0,0 +1,112 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::utils::config_dir;
+
+/// Settings remembered for one directory
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DirSettings {
+    /// Entry names listed first, in pinning order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pinned: Vec<String>,
+}
+
+impl DirSettings {
+    fn is_empty(&self) -> bool {
+        self.pinned.is_empty()
+    }
+}
+
+/// Per-directory settings cache persisted across sessions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DirSettingsCache {
+    directories: BTreeMap<PathBuf, DirSettings>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl DirSettingsCache {
+    pub fn load() -> Self {
+        let Ok(dir) = config_dir() else {
+            return Self::default();
+        };
+        Self::load_from(dir.join("dir_settings.json"))
+    }
+
+    fn load_from(path: PathBuf) -> Self {
+        let mut cache: Self = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        cache.path = path;
+        cache
+    }
+
+    pub fn pinned(&self, dir: &Path) -> &[String] {
+        self.directories
+            .get(dir)
+            .map(|settings| settings.pinned.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Pin `name` in `dir`, or unpin it if already pinned; returns whether it is now pinned
+    pub fn toggle_pin(&mut self, dir: &Path, name: &str) -> Result<bool> {
+        let settings = self.directories.entry(dir.to_path_buf()).or_default();
+        let pinned = match settings.pinned.iter().position(|n| n == name) {
+            Some(index) => {
+                settings.pinned.remove(index);
+                false
+            }
+            None => {
+                settings.pinned.push(name.to_string());
+                true
+            }
+        };
+
+        if settings.is_empty() {
+            self.directories.remove(dir);
+        }
+        self.save()?;
+        Ok(pinned)
+    }
+
+    fn save(&self) -> Result<()> {
+        if self.path.as_os_str().is_empty() {
+            return Ok(());
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&self.path, json)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggle_pin_persists() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("dir_settings.json");
+        let project = Path::new("/srv/project");
+
+        let mut cache = DirSettingsCache::load_from(path.clone());
+        assert!(cache.toggle_pin(project, "README.md").unwrap());
+        assert!(cache.toggle_pin(project, "docker-compose.yml").unwrap());
+
+        let mut reloaded = DirSettingsCache::load_from(path.clone());
+        assert_eq!(
+            reloaded.pinned(project),
+            ["README.md".to_string(), "docker-compose.yml".to_string()]
+        );
+
+        assert!(!reloaded.toggle_pin(project, "README.md").unwrap());
+        assert!(!reloaded.toggle_pin(project, "docker-compose.yml").unwrap());
+        assert!(DirSettingsCache::load_from(path).directories.is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::models::FileEntry;
+use crate::tasks::Task;
+
+/// Bytes under `path`, counted without following symbolic links; `None`
+/// when `cancel` is set before the walk ends. Entries that cannot be read
+/// count as empty.
+pub fn dir_size(path: &Path, cancel: &AtomicBool) -> Option<u64> {
+    let mut total = 0;
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else {
+                total += metadata.len();
+            }
+        }
+    }
+    Some(total)
+}
+
+/// Combined size of a selection so far
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeTotal {
+    pub bytes: u64,
+    /// Directories still being measured, not in `bytes` yet
+    pub pending: usize,
+}
+
+/// Sizes of selected directories, measured on background threads and
+/// remembered until the listing is reloaded
+///
+/// Files count with the size their entry already holds; each directory is
+/// walked once, when it is first part of a selection.
+#[derive(Default)]
+pub struct SelectionSize {
+    sizes: HashMap<PathBuf, u64>,
+    walks: HashMap<PathBuf, Task<Option<u64>>>,
+}
+
+impl SelectionSize {
+    /// Pick up finished walks; whether any did
+    pub fn poll(&mut self) -> bool {
+        let mut finished = Vec::new();
+        for (path, task) in &mut self.walks {
+            if let Some(size) = task.poll() {
+                finished.push((path.clone(), size));
+            }
+        }
+        for (path, size) in &finished {
+            self.walks.remove(path);
+            if let Some(size) = size {
+                self.sizes.insert(path.clone(), *size);
+            }
+        }
+        !finished.is_empty()
+    }
+
+    /// Total of `entries`, starting walks for directories not measured yet;
+    /// walks for directories no longer selected are dropped
+    pub fn total<'a>(&mut self, entries: impl IntoIterator<Item = &'a FileEntry>) -> SizeTotal {
+        let mut total = SizeTotal {
+            bytes: 0,
+            pending: 0,
+        };
+        let mut walking = HashMap::new();
+        for entry in entries {
+            // A link to a directory is a small file of its own
+            if !entry.is_dir || entry.is_symlink {
+                total.bytes += entry.size;
+            } else if let Some(size) = self.sizes.get(&entry.path) {
+                total.bytes += size;
+            } else {
+                let task = self.walks.remove(&entry.path).unwrap_or_else(|| {
+                    let path = entry.path.clone();
+                    Task::spawn(move |cancel| dir_size(&path, cancel))
+                });
+                walking.insert(entry.path.clone(), task);
+                total.pending += 1;
+            }
+        }
+        self.walks = walking;
+        total
+    }
+
+    /// Forget every size, after the directory changed on disk or another
+    /// one was opened
+    pub fn clear(&mut self) {
+        self.sizes.clear();
+        self.walks.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_selection_size() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), [0; 100]).unwrap();
+        fs::create_dir_all(dir.path().join("src/nested")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), [0; 30]).unwrap();
+        fs::write(dir.path().join("src/nested/lib.rs"), [0; 12]).unwrap();
+
+        let cancel = AtomicBool::new(false);
+        assert_eq!(dir_size(&dir.path().join("src"), &cancel), Some(42));
+        cancel.store(true, Ordering::Relaxed);
+        assert_eq!(dir_size(&dir.path().join("src"), &cancel), None);
+
+        let entries: Vec<FileEntry> = ["a.txt", "src"]
+            .iter()
+            .map(|name| FileEntry::from_path(dir.path().join(name), name.to_string()))
+            .collect();
+        let mut sizes = SelectionSize::default();
+        let first = sizes.total(&entries);
+        assert_eq!(first.pending, 1);
+        assert_eq!(first.bytes, 100);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !sizes.poll() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(
+            sizes.total(&entries),
+            SizeTotal {
+                bytes: 142,
+                pending: 0
+            }
+        );
+
+        // Cached: removing the file does not change the total until cleared
+        fs::remove_file(dir.path().join("src/main.rs")).unwrap();
+        assert_eq!(sizes.total(&entries).bytes, 142);
+        sizes.clear();
+        assert_eq!(sizes.total(&entries).pending, 1);
+    }
+}
@@ -0,0 +1,301 @@
+use anyhow::Result;
+use crossterm::{
+    cursor::MoveTo,
+    event::KeyCode,
+    execute,
+    style::{Color, Print, ResetColor},
+    terminal,
+};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::models::FileEntry;
+use crate::preview::FilePreview;
+use crate::ui::{SetBackgroundColor, SetForegroundColor};
+
+/// Files sharing one extension
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtensionStat {
+    /// Lowercase extension without the dot; `None` for files without one
+    pub extension: Option<String>,
+    pub count: usize,
+    pub size: u64,
+}
+
+impl ExtensionStat {
+    fn label(&self) -> String {
+        match self.extension {
+            Some(ref extension) => format!(".{}", extension),
+            None => "(no extension)".to_string(),
+        }
+    }
+}
+
+/// Lowercase extension of a file entry, as grouped by [`DirStats`]
+pub fn entry_extension(entry: &FileEntry) -> Option<String> {
+    if entry.is_dir {
+        return None;
+    }
+    entry
+        .path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+}
+
+pub enum StatsOutcome {
+    Continue,
+    Close,
+    /// Filter the listing to files with this extension
+    Filter(String),
+}
+
+/// Counts and sizes of a directory listing, broken down by extension
+pub struct DirStats {
+    root: PathBuf,
+    file_count: usize,
+    dir_count: usize,
+    total_size: u64,
+    /// Most common first
+    extensions: Vec<ExtensionStat>,
+    selected: usize,
+}
+
+impl DirStats {
+    pub fn new(root: &Path, entries: &[FileEntry]) -> Self {
+        let mut extensions: Vec<ExtensionStat> = Vec::new();
+        let mut file_count = 0;
+        let mut dir_count = 0;
+        let mut total_size = 0;
+
+        for entry in entries.iter().filter(|e| e.name != "..") {
+            if entry.is_dir {
+                dir_count += 1;
+                continue;
+            }
+            file_count += 1;
+            total_size += entry.size;
+
+            let extension = entry_extension(entry);
+            match extensions.iter_mut().find(|s| s.extension == extension) {
+                Some(stat) => {
+                    stat.count += 1;
+                    stat.size += entry.size;
+                }
+                None => extensions.push(ExtensionStat {
+                    extension,
+                    count: 1,
+                    size: entry.size,
+                }),
+            }
+        }
+        extensions.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| b.size.cmp(&a.size))
+                .then_with(|| a.extension.cmp(&b.extension))
+        });
+
+        Self {
+            root: root.to_path_buf(),
+            file_count,
+            dir_count,
+            total_size,
+            extensions,
+            selected: 0,
+        }
+    }
+
+    pub fn render(&self) -> Result<()> {
+        let mut stdout = io::stdout();
+        let (terminal_width, terminal_height) = terminal::size()?;
+        let width = terminal_width as usize;
+
+        execute!(stdout, terminal::Clear(terminal::ClearType::All))?;
+
+        let header: String = format!(" 📊 STATISTICS {}", self.root.display())
+            .chars()
+            .take(width)
+            .collect();
+        execute!(
+            stdout,
+            MoveTo(0, 0),
+            SetBackgroundColor(Color::DarkBlue),
+            SetForegroundColor(Color::White),
+            Print(format!("{:<width$}", header, width = width)),
+            ResetColor
+        )?;
+
+        let summary = format!(
+            "{} files ({}), {} directories, {} extensions",
+            self.file_count,
+            FilePreview::format_size(self.total_size),
+            self.dir_count,
+            self.extensions.len()
+        );
+        execute!(
+            stdout,
+            MoveTo(1, 1),
+            SetForegroundColor(Color::Yellow),
+            Print(
+                summary
+                    .chars()
+                    .take(width.saturating_sub(2))
+                    .collect::<String>()
+            ),
+            ResetColor
+        )?;
+
+        if self.extensions.is_empty() {
+            execute!(
+                stdout,
+                MoveTo(1, 3),
+                SetForegroundColor(Color::DarkGrey),
+                Print("No files"),
+                ResetColor
+            )?;
+        }
+
+        let list_height = (terminal_height as usize).saturating_sub(4);
+        let start = self.selected.saturating_sub(list_height.saturating_sub(1));
+        let bar_width = width.saturating_sub(46).min(30);
+
+        for (row, (i, stat)) in self
+            .extensions
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(list_height)
+            .enumerate()
+        {
+            let bar_len = (stat.count * bar_width).div_ceil(self.file_count.max(1));
+            let line = format!(
+                "{:<16} {:>6} files {:>10}  {}",
+                stat.label(),
+                stat.count,
+                FilePreview::format_size(stat.size),
+                "█".repeat(bar_len)
+            );
+            let is_selected = i == self.selected;
+            execute!(
+                stdout,
+                MoveTo(1, 3 + row as u16),
+                SetBackgroundColor(if is_selected {
+                    Color::DarkGreen
+                } else {
+                    Color::Reset
+                }),
+                SetForegroundColor(if is_selected {
+                    Color::White
+                } else {
+                    Color::Cyan
+                }),
+                Print(format!(
+                    "{:<w$}",
+                    line.chars()
+                        .take(width.saturating_sub(2))
+                        .collect::<String>(),
+                    w = width.saturating_sub(2)
+                )),
+                ResetColor
+            )?;
+        }
+
+        let footer = " ↑↓: Select | Enter/e: Filter listing to extension | Esc/q: Close";
+        execute!(
+            stdout,
+            MoveTo(0, terminal_height.saturating_sub(1)),
+            SetBackgroundColor(Color::DarkGrey),
+            SetForegroundColor(Color::White),
+            Print(format!(
+                "{:<width$}",
+                footer.chars().take(width).collect::<String>(),
+                width = width
+            )),
+            ResetColor
+        )?;
+
+        stdout.flush()?;
+        Ok(())
+    }
+
+    pub fn handle_input(&mut self, key: KeyCode) -> StatsOutcome {
+        match key {
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down if self.selected + 1 < self.extensions.len() => self.selected += 1,
+            KeyCode::Home => self.selected = 0,
+            KeyCode::End => self.selected = self.extensions.len().saturating_sub(1),
+            KeyCode::Enter | KeyCode::Char('e') => {
+                if let Some(extension) = self
+                    .extensions
+                    .get(self.selected)
+                    .and_then(|stat| stat.extension.clone())
+                {
+                    return StatsOutcome::Filter(extension);
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => return StatsOutcome::Close,
+            _ => {}
+        }
+        StatsOutcome::Continue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(name: &str, size: u64) -> FileEntry {
+        FileEntry {
+            name: name.to_string(),
+            path: PathBuf::from("/data").join(name),
+            is_dir: false,
+            is_accessible: true,
+            is_symlink: false,
+            permissions: None,
+            owner: None,
+            group: None,
+            uid: None,
+            gid: None,
+            size,
+            modified: None,
+        }
+    }
+
+    #[test]
+    fn test_extension_breakdown_and_filter() {
+        let mut dir = file("src", 0);
+        dir.is_dir = true;
+        let entries = vec![
+            FileEntry::parent_link(Path::new("/")),
+            dir,
+            file("a.rs", 10),
+            file("B.RS", 30),
+            file("notes.md", 100),
+            file("Makefile", 5),
+        ];
+
+        let mut stats = DirStats::new(Path::new("/data"), &entries);
+        assert_eq!((stats.file_count, stats.dir_count), (4, 1));
+        assert_eq!(stats.total_size, 145);
+        assert_eq!(
+            stats.extensions[0],
+            ExtensionStat {
+                extension: Some("rs".to_string()),
+                count: 2,
+                size: 40
+            }
+        );
+        assert_eq!(stats.extensions[1].label(), ".md");
+        assert_eq!(stats.extensions[2].label(), "(no extension)");
+
+        assert!(matches!(
+            stats.handle_input(KeyCode::Enter),
+            StatsOutcome::Filter(ref e) if e == "rs"
+        ));
+        stats.handle_input(KeyCode::End);
+        assert!(matches!(
+            stats.handle_input(KeyCode::Enter),
+            StatsOutcome::Continue
+        ));
+    }
+}
@@ -0,0 +1,449 @@
+use crossterm::terminal;
+use std::env;
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::bookmarks::{read_shared_bookmarks, SHARED_BOOKMARKS_PATH};
+use crate::config::Config;
+use crate::graphics::GraphicsProtocol;
+use crate::search::{locate_program, ripgrep_program};
+use crate::ui::ColorLevel;
+use crate::utils::{config_dir, editor_command, find_program, is_root_user};
+
+/// How a check came out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    /// Works, with a feature missing or degraded
+    Warn,
+    /// fsnav cannot run as configured
+    Fail,
+}
+
+impl Status {
+    fn marker(self) -> &'static str {
+        match self {
+            Status::Ok => "✓",
+            Status::Warn => "!",
+            Status::Fail => "✗",
+        }
+    }
+}
+
+struct Check {
+    status: Status,
+    name: String,
+    detail: String,
+}
+
+impl Check {
+    fn new(status: Status, name: &str, detail: impl Into<String>) -> Self {
+        Self {
+            status,
+            name: name.to_string(),
+            detail: detail.into(),
+        }
+    }
+}
+
+struct Section {
+    title: &'static str,
+    checks: Vec<Check>,
+}
+
+/// Print what fsnav finds about the terminal, the config file and the
+/// programs optional features rely on; the exit code is 1 when the config
+/// cannot be used
+pub fn run() -> i32 {
+    let (config, config_check) = check_config();
+    let sections = [
+        Section {
+            title: "Terminal",
+            checks: terminal_checks(&config),
+        },
+        Section {
+            title: "Configuration",
+            checks: vec![
+                config_check,
+                shared_bookmarks_check(Path::new(SHARED_BOOKMARKS_PATH)),
+                session_check(&config),
+            ],
+        },
+        Section {
+            title: "External tools",
+            checks: tool_checks(&config),
+        },
+    ];
+    print!("{}", format_report(&sections));
+    if failed(&sections) {
+        1
+    } else {
+        0
+    }
+}
+
+fn failed(sections: &[Section]) -> bool {
+    sections
+        .iter()
+        .flat_map(|section| &section.checks)
+        .any(|check| check.status == Status::Fail)
+}
+
+fn format_report(sections: &[Section]) -> String {
+    let name_width = sections
+        .iter()
+        .flat_map(|section| &section.checks)
+        .map(|check| check.name.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut report = format!("fsnav {} environment report\n", env!("CARGO_PKG_VERSION"));
+    for section in sections {
+        let _ = writeln!(report, "\n{}", section.title);
+        for check in &section.checks {
+            let _ = writeln!(
+                report,
+                "  {} {:<width$}  {}",
+                check.status.marker(),
+                check.name,
+                check.detail,
+                width = name_width
+            );
+        }
+    }
+
+    let count = |status| {
+        sections
+            .iter()
+            .flat_map(|section| &section.checks)
+            .filter(|check| check.status == status)
+            .count()
+    };
+    let _ = writeln!(
+        report,
+        "\n{} ok, {} warning{}, {} problem{}",
+        count(Status::Ok),
+        count(Status::Warn),
+        if count(Status::Warn) == 1 { "" } else { "s" },
+        count(Status::Fail),
+        if count(Status::Fail) == 1 { "" } else { "s" }
+    );
+    report
+}
+
+fn var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn terminal_checks(config: &Config) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    checks.push(match var("TERM") {
+        Some(term) if term != "dumb" => Check::new(Status::Ok, "TERM", term),
+        Some(term) => Check::new(
+            Status::Warn,
+            "TERM",
+            format!("{}: no cursor movement", term),
+        ),
+        None => Check::new(Status::Warn, "TERM", "not set"),
+    });
+
+    checks.push(match terminal::size() {
+        Ok((width, height)) => {
+            Check::new(Status::Ok, "Size", format!("{}×{} cells", width, height))
+        }
+        Err(_) => Check::new(Status::Warn, "Size", "unknown: not run from a terminal"),
+    });
+
+    let detected = ColorLevel::detect();
+    let level = config.color_level();
+    let source = if config.colors.is_some() {
+        "set by `colors` in config.toml"
+    } else if var("NO_COLOR").is_some() {
+        "NO_COLOR is set"
+    } else if var("COLORTERM").is_some() {
+        "from COLORTERM and TERM"
+    } else {
+        "from TERM"
+    };
+    let (status, description) = match level {
+        ColorLevel::Full => (Status::Ok, "full: 256 colors or truecolor"),
+        ColorLevel::Ansi16 => (Status::Ok, "16 colors"),
+        ColorLevel::Ansi8 => (
+            Status::Warn,
+            "8 colors: highlights use bold and reverse video",
+        ),
+        ColorLevel::Monochrome => (Status::Warn, "monochrome: highlights use reverse video"),
+    };
+    let mut detail = format!("{} ({})", description, source);
+    if level != detected {
+        let _ = write!(
+            detail,
+            "; detected {}",
+            format!("{:?}", detected).to_lowercase()
+        );
+    }
+    checks.push(Check::new(status, "Colors", detail));
+
+    let truecolor = var("COLORTERM").is_some_and(|v| v == "truecolor" || v == "24bit");
+    checks.push(if truecolor {
+        Check::new(Status::Ok, "Truecolor", "advertised by COLORTERM")
+    } else {
+        Check::new(
+            Status::Warn,
+            "Truecolor",
+            "not advertised: block images may show wrong colors",
+        )
+    });
+
+    let protocol = config.preview.graphics_protocol();
+    let (status, description) = match protocol {
+        GraphicsProtocol::Kitty => (Status::Ok, "kitty graphics protocol"),
+        GraphicsProtocol::Iterm2 => (Status::Ok, "iTerm2 inline images"),
+        GraphicsProtocol::Sixel => (Status::Ok, "sixel"),
+        GraphicsProtocol::Blocks if level != ColorLevel::Full => (
+            Status::Warn,
+            "none: the terminal has too few colors for block images",
+        ),
+        GraphicsProtocol::Blocks => (Status::Warn, "colored half blocks only"),
+    };
+    let mut detail = description.to_string();
+    if config.preview.graphics.is_some() {
+        detail.push_str(" (set by [preview] graphics)");
+    } else if var("TMUX").is_some() || var("STY").is_some() {
+        detail.push_str(" (inside tmux or screen)");
+    }
+    checks.push(Check::new(status, "Images", detail));
+
+    checks
+}
+
+/// The config file, read the way startup reads it
+fn check_config() -> (Config, Check) {
+    let path = match config_dir() {
+        Ok(dir) => dir.join("config.toml"),
+        Err(e) => {
+            let check = Check::new(Status::Warn, "config.toml", format!("{:#}", e));
+            return (Config::default(), check);
+        }
+    };
+    check_config_file(&path)
+}
+
+fn check_config_file(path: &Path) -> (Config, Check) {
+    let name = "config.toml";
+    if !path.exists() {
+        let detail = format!("{} not found: defaults in use", path.display());
+        return (Config::default(), Check::new(Status::Ok, name, detail));
+    }
+    match Config::load_from(path) {
+        Ok(config) => {
+            let commands = config.commands.len();
+            let detail = format!(
+                "{} is valid ({} custom command{})",
+                path.display(),
+                commands,
+                if commands == 1 { "" } else { "s" }
+            );
+            (config, Check::new(Status::Ok, name, detail))
+        }
+        Err(e) => (
+            Config::default(),
+            Check::new(Status::Fail, name, format!("{:#}", e)),
+        ),
+    }
+}
+
+/// Bookmarks an administrator ships to every user; fsnav still starts
+/// without them
+fn shared_bookmarks_check(path: &Path) -> Check {
+    let name = "Shared bookmarks";
+    if !path.exists() {
+        return Check::new(Status::Ok, name, format!("{} not found", path.display()));
+    }
+    match read_shared_bookmarks(path) {
+        Ok(bookmarks) => Check::new(
+            Status::Ok,
+            name,
+            format!(
+                "{}: {} bookmark{}",
+                path.display(),
+                bookmarks.len(),
+                if bookmarks.len() == 1 { "" } else { "s" }
+            ),
+        ),
+        Err(e) => Check::new(
+            Status::Warn,
+            name,
+            format!("{}: {:#}; not loaded", path.display(), e),
+        ),
+    }
+}
+
+fn session_check(config: &Config) -> Check {
+    let profile = format!("{:?}", config.profile).to_lowercase();
+    if is_root_user() {
+        Check::new(
+            Status::Ok,
+            "Privileges",
+            format!("root, {} profile", profile),
+        )
+    } else {
+        Check::new(
+            Status::Ok,
+            "Privileges",
+            format!(
+                "not root, {} profile: chmod and selection modes need root",
+                profile
+            ),
+        )
+    }
+}
+
+/// A program found in `$PATH`, or why its feature is unavailable
+fn tool_check(name: &str, found: Option<&Path>, feature: &str) -> Check {
+    match found {
+        Some(path) => Check::new(
+            Status::Ok,
+            name,
+            format!("{} ({})", path.display(), feature),
+        ),
+        None => Check::new(
+            Status::Warn,
+            name,
+            format!("not found: {} unavailable", feature),
+        ),
+    }
+}
+
+fn tool_checks(config: &Config) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    let rg = ripgrep_program();
+    checks.push(match (&rg, config.search.ripgrep) {
+        (Some(path), false) => Check::new(
+            Status::Ok,
+            "rg",
+            format!("{} (turned off by [search] ripgrep)", path.display()),
+        ),
+        (None, _) => Check::new(
+            Status::Warn,
+            "rg",
+            "not found: content search uses the built-in engine",
+        ),
+        (Some(path), true) => tool_check("rg", Some(path), "fast content search"),
+    });
+
+    checks.push(tool_check(
+        "plocate / locate",
+        locate_program().as_deref(),
+        "locate database search",
+    ));
+
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    checks.push(tool_check(
+        opener,
+        find_program(opener).as_deref(),
+        "opening files in their default application",
+    ));
+
+    let (editor, _) = editor_command();
+    let editor_path = if editor.contains('/') {
+        Some(Path::new(&editor).to_path_buf()).filter(|path| path.exists())
+    } else {
+        find_program(&editor)
+    };
+    checks.push(tool_check(
+        "Editor",
+        editor_path.as_deref(),
+        &format!("editing files with Enter, $VISUAL/$EDITOR is {}", editor),
+    ));
+
+    let shell = env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
+    checks.push(tool_check(
+        "Shell",
+        Some(Path::new(&shell)).filter(|path| path.exists()),
+        "dropping to a shell with S",
+    ));
+
+    let runtime = ["docker", "podman"]
+        .iter()
+        .find_map(|name| find_program(name));
+    checks.push(tool_check(
+        "docker / podman",
+        runtime.as_deref(),
+        "container volumes",
+    ));
+
+    checks.push(tool_check(
+        "systemctl",
+        find_program("systemctl").as_deref(),
+        "unit status with U",
+    ));
+
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_layout() {
+        let sections = [
+            Section {
+                title: "Terminal",
+                checks: vec![
+                    Check::new(Status::Ok, "TERM", "xterm-256color"),
+                    Check::new(Status::Warn, "Images", "colored half blocks only"),
+                ],
+            },
+            Section {
+                title: "External tools",
+                checks: vec![Check::new(Status::Ok, "systemctl", "/usr/bin/systemctl")],
+            },
+        ];
+        let report = format_report(&sections);
+        let lines: Vec<&str> = report.lines().skip(1).collect();
+        assert_eq!(
+            lines,
+            [
+                "",
+                "Terminal",
+                "  ✓ TERM       xterm-256color",
+                "  ! Images     colored half blocks only",
+                "",
+                "External tools",
+                "  ✓ systemctl  /usr/bin/systemctl",
+                "",
+                "2 ok, 1 warning, 0 problems",
+            ]
+        );
+        assert!(!failed(&sections));
+    }
+
+    #[test]
+    fn test_config_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+
+        let (_, check) = check_config_file(&path);
+        assert_eq!(check.status, Status::Ok);
+        assert!(check.detail.contains("defaults in use"));
+
+        std::fs::write(&path, "[commands]\nsizes = \"du -sh %s\"\n").unwrap();
+        let (config, check) = check_config_file(&path);
+        assert_eq!(check.status, Status::Ok);
+        assert!(check.detail.ends_with("is valid (1 custom command)"));
+        assert_eq!(config.commands.len(), 1);
+
+        std::fs::write(&path, "colors = \"sepia\"\n").unwrap();
+        let (_, check) = check_config_file(&path);
+        assert_eq!(check.status, Status::Fail);
+        assert!(check.detail.contains("sepia"), "{}", check.detail);
+    }
+}
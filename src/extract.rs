@@ -0,0 +1,286 @@
+use std::ffi::OsString;
+use std::fs::{self, DirBuilder, File};
+use std::io;
+use std::os::unix::fs::DirBuilderExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crate::archive::{self, decoder};
+use crate::managers::private_dir;
+use crate::tasks::Task;
+use crate::utils::{free_path, move_path};
+
+/// Staging directories are named `extract-<pid>-<n>`
+const STAGING_PREFIX: &str = "extract-";
+
+/// A scratch directory an archive is unpacked into before its contents
+/// move to where they belong; removed with whatever is left in it on drop
+pub struct Staging {
+    dir: PathBuf,
+}
+
+impl Staging {
+    /// A new directory in fsnav's private directory under `root`, which is
+    /// created if missing, after clearing out the ones fsnav processes that
+    /// are gone left behind
+    pub fn create(root: &Path) -> io::Result<Self> {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+        fs::create_dir_all(root)?;
+        let private = private_dir(root)?;
+        sweep(&private);
+        loop {
+            let n = NEXT.fetch_add(1, Ordering::Relaxed);
+            let dir = private.join(format!("{}{}-{}", STAGING_PREFIX, std::process::id(), n));
+            match DirBuilder::new().mode(0o700).create(&dir) {
+                Ok(()) => return Ok(Self { dir }),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl Drop for Staging {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Remove staging directories of fsnav processes that crashed or were
+/// killed mid-extraction
+fn sweep(private: &Path) {
+    let Ok(entries) = fs::read_dir(private) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let pid = name
+            .to_str()
+            .and_then(|name| name.strip_prefix(STAGING_PREFIX))
+            .and_then(|rest| rest.split('-').next())
+            .and_then(|pid| pid.parse::<libc::pid_t>().ok());
+        if pid.is_some_and(|pid| pid > 0 && !is_running(pid)) {
+            let _ = fs::remove_dir_all(entry.path());
+        }
+    }
+}
+
+fn is_running(pid: libc::pid_t) -> bool {
+    // Signal 0 only checks the process exists; EPERM means it runs as
+    // someone else
+    let alive = unsafe { libc::kill(pid, 0) } == 0;
+    alive || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Unpack the tarball at `path` into `dest` through a staging directory
+/// under `staging_root`, so a failed or cancelled run leaves `dest` as it
+/// was. Everything lands in a new directory named after the archive; with
+/// `into_subdirectory` off, an archive holding a single top-level entry
+/// puts that entry straight into `dest`. Returns what was created in `dest`.
+pub fn extract(
+    path: &Path,
+    dest: &Path,
+    staging_root: &Path,
+    into_subdirectory: bool,
+    cancel: &AtomicBool,
+) -> io::Result<PathBuf> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let staging = Staging::create(staging_root)?;
+    let unpacked = staging.path().join("contents");
+    fs::create_dir(&unpacked)?;
+
+    let mut tar = tar::Archive::new(decoder(File::open(path)?, &name));
+    for entry in tar.entries()? {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(io::Error::new(
+                io::ErrorKind::Interrupted,
+                "extraction cancelled",
+            ));
+        }
+        // Skips entries whose path leads outside, like `../x` or `/etc/x`
+        entry?.unpack_in(&unpacked)?;
+    }
+
+    let top = fs::read_dir(&unpacked)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    let (source, target_name) = match top.as_slice() {
+        [single] if !into_subdirectory => (
+            single.clone(),
+            single.file_name().unwrap_or_default().to_os_string(),
+        ),
+        _ => (unpacked, OsString::from(archive::archive_stem(&name))),
+    };
+    let target = free_path(dest, Path::new(&target_name), !source.is_dir());
+    move_path(&source, &target)?;
+    Ok(target)
+}
+
+/// An archive being extracted in the background
+pub struct Extraction {
+    pub archive: PathBuf,
+    pub dest: PathBuf,
+    task: Task<io::Result<PathBuf>>,
+}
+
+impl Extraction {
+    pub fn start(
+        archive: &Path,
+        dest: &Path,
+        staging_root: &Path,
+        into_subdirectory: bool,
+    ) -> Self {
+        let (path, target, root) = (
+            archive.to_path_buf(),
+            dest.to_path_buf(),
+            staging_root.to_path_buf(),
+        );
+        Self {
+            archive: archive.to_path_buf(),
+            dest: dest.to_path_buf(),
+            task: Task::spawn(move |cancel| {
+                extract(&path, &target, &root, into_subdirectory, cancel)
+            }),
+        }
+    }
+
+    /// The outcome, once, when the extraction has finished
+    pub fn poll(&mut self) -> Option<io::Result<PathBuf>> {
+        self.task.poll()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.task.is_pending()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::tests::tarball;
+    use tempfile::TempDir;
+
+    fn setup(files: &[(&str, &[u8])]) -> (TempDir, PathBuf, PathBuf, PathBuf) {
+        let temp_dir = TempDir::new().unwrap();
+        let dest = temp_dir.path().join("downloads");
+        let staging = temp_dir.path().join("tmp");
+        fs::create_dir(&dest).unwrap();
+        fs::create_dir(&staging).unwrap();
+        let archive = temp_dir.path().join("release.tar.gz");
+        fs::write(&archive, tarball(files, true)).unwrap();
+        (temp_dir, dest, staging, archive)
+    }
+
+    fn run(archive: &Path, dest: &Path, staging: &Path, into_subdirectory: bool) -> PathBuf {
+        extract(
+            archive,
+            dest,
+            staging,
+            into_subdirectory,
+            &AtomicBool::new(false),
+        )
+        .unwrap()
+    }
+
+    fn leftovers(staging: &Path) -> usize {
+        let private = staging.join(format!("fsnav-{}", crate::managers::euid()));
+        fs::read_dir(private).unwrap().count()
+    }
+
+    #[test]
+    fn test_tarbomb_lands_in_a_subdirectory() {
+        let (_temp_dir, dest, staging, archive) =
+            setup(&[("README", b"hi"), ("bin/tool", b"#!/bin/sh\n")]);
+
+        // Without the setting a tarbomb still gets its own directory
+        let target = run(&archive, &dest, &staging, false);
+        assert_eq!(target, dest.join("release"));
+        assert_eq!(fs::read(target.join("README")).unwrap(), b"hi");
+        assert!(target.join("bin/tool").is_file());
+
+        // A second run does not mix into the first
+        let again = run(&archive, &dest, &staging, true);
+        assert_eq!(again, dest.join("release (2)"));
+        assert_eq!(fs::read_dir(&dest).unwrap().count(), 2);
+        assert_eq!(leftovers(&staging), 0);
+    }
+
+    #[test]
+    fn test_single_top_level_entry() {
+        let (_temp_dir, dest, staging, archive) =
+            setup(&[("app-1.0/README", b"hi"), ("app-1.0/src/main.c", b"")]);
+
+        assert_eq!(run(&archive, &dest, &staging, false), dest.join("app-1.0"));
+        assert!(dest.join("app-1.0/src/main.c").is_file());
+
+        assert_eq!(run(&archive, &dest, &staging, true), dest.join("release"));
+        assert!(dest.join("release/app-1.0/README").is_file());
+    }
+
+    #[test]
+    fn test_entries_leaving_the_archive_are_skipped() {
+        let mut header = tar::Header::new_gnu();
+        let name = b"../escaped";
+        header.as_gnu_mut().unwrap().name[..name.len()].copy_from_slice(name);
+        header.set_size(1);
+        header.set_mode(0o644);
+        header.set_cksum();
+        let mut builder = tar::Builder::new(Vec::new());
+        builder.append(&header, &b"x"[..]).unwrap();
+        let mut inside = tar::Header::new_gnu();
+        inside.set_size(2);
+        inside.set_mode(0o644);
+        inside.set_cksum();
+        builder
+            .append_data(&mut inside, "kept", &b"ok"[..])
+            .unwrap();
+
+        let (temp_dir, dest, staging, _) = setup(&[]);
+        let archive = temp_dir.path().join("evil.tar");
+        fs::write(&archive, builder.into_inner().unwrap()).unwrap();
+
+        let target = run(&archive, &dest, &staging, true);
+        assert_eq!(fs::read(target.join("kept")).unwrap(), b"ok");
+        assert!(!dest.join("escaped").exists());
+        assert!(!temp_dir.path().join("escaped").exists());
+    }
+
+    #[test]
+    fn test_missing_staging_root_is_created() {
+        let (_temp_dir, dest, staging, archive) = setup(&[("a", b"a")]);
+        // Like `staging_dir = "~/.cache/fsnav"` on a fresh account
+        let root = staging.join(".cache/fsnav");
+        let target = run(&archive, &dest, &root, true);
+        assert_eq!(fs::read(target.join("a")).unwrap(), b"a");
+        assert_eq!(leftovers(&root), 0);
+    }
+
+    #[test]
+    fn test_stale_staging_is_swept() {
+        let (_temp_dir, dest, staging, archive) = setup(&[("a", b"a")]);
+        let private = private_dir(&staging).unwrap();
+        // No process runs under the largest pid, so this one is left over
+        let stale = private.join(format!("{}{}-7", STAGING_PREFIX, libc::pid_t::MAX));
+        fs::create_dir_all(stale.join("contents")).unwrap();
+        let live = private.join(format!("{}{}-99", STAGING_PREFIX, std::process::id()));
+        fs::create_dir(&live).unwrap();
+
+        run(&archive, &dest, &staging, true);
+        assert!(!stale.exists());
+        assert!(live.exists());
+    }
+
+    #[test]
+    fn test_cancelled_extraction_leaves_dest_alone() {
+        let (_temp_dir, dest, staging, archive) = setup(&[("a", b"a")]);
+        let error = extract(&archive, &dest, &staging, true, &AtomicBool::new(true)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Interrupted);
+        assert_eq!(fs::read_dir(&dest).unwrap().count(), 0);
+        assert_eq!(leftovers(&staging), 0);
+    }
+}
@@ -0,0 +1,358 @@
+use anyhow::Result;
+use crossterm::{
+    cursor::MoveTo,
+    event::{KeyCode, KeyModifiers},
+    execute,
+    style::{Color, Print, ResetColor},
+    terminal,
+};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::ignore::ExcludeRules;
+use crate::search::{fuzzy_match, FuzzyMatch};
+use crate::ui::{SetBackgroundColor, SetForegroundColor};
+
+/// Recursive scans stop after this many entries to stay responsive
+const MAX_CANDIDATES: usize = 50_000;
+
+struct Candidate {
+    path: PathBuf,
+    /// Path relative to the finder root, directories with a trailing '/'
+    display: String,
+    is_dir: bool,
+}
+
+pub enum FinderOutcome {
+    Continue,
+    Cancel,
+    Jump(PathBuf),
+}
+
+/// fzf-style jump mode ranking entries of a directory tree as you type
+pub struct FuzzyFinder {
+    root: PathBuf,
+    show_hidden: bool,
+    recursive: bool,
+    excludes: ExcludeRules,
+    use_excludes: bool,
+    query: String,
+    candidates: Vec<Candidate>,
+    /// Best match first, as (candidate index, match)
+    matches: Vec<(usize, FuzzyMatch)>,
+    selected: usize,
+    truncated: bool,
+}
+
+impl FuzzyFinder {
+    pub fn new(root: &Path, show_hidden: bool, excludes: ExcludeRules) -> Self {
+        let mut finder = Self {
+            root: root.to_path_buf(),
+            show_hidden,
+            recursive: false,
+            excludes,
+            use_excludes: true,
+            query: String::new(),
+            candidates: Vec::new(),
+            matches: Vec::new(),
+            selected: 0,
+            truncated: false,
+        };
+        finder.rescan();
+        finder
+    }
+
+    fn rescan(&mut self) {
+        let (candidates, truncated) = collect_candidates(
+            &self.root,
+            self.recursive,
+            self.show_hidden,
+            self.use_excludes.then_some(&self.excludes),
+            MAX_CANDIDATES,
+        );
+        self.candidates = candidates;
+        self.truncated = truncated;
+        self.update_matches();
+    }
+
+    fn update_matches(&mut self) {
+        self.matches = self
+            .candidates
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c)| fuzzy_match(&self.query, &c.display).map(|m| (i, m)))
+            .collect();
+        // Stable sort keeps listing order for equal scores
+        self.matches
+            .sort_by_key(|(_, m)| std::cmp::Reverse(m.score));
+        self.selected = 0;
+    }
+
+    pub fn render(&self) -> Result<()> {
+        let mut stdout = io::stdout();
+        let (terminal_width, terminal_height) = terminal::size()?;
+        let width = terminal_width as usize;
+
+        execute!(stdout, terminal::Clear(terminal::ClearType::All))?;
+
+        let title = format!(
+            " 🔎 FIND in {}{}{}",
+            self.root.display(),
+            if self.recursive { " (recursive)" } else { "" },
+            if !self.use_excludes && !self.excludes.is_empty() {
+                " (no exclusions)"
+            } else {
+                ""
+            }
+        );
+        execute!(
+            stdout,
+            MoveTo(0, 0),
+            SetBackgroundColor(Color::DarkBlue),
+            SetForegroundColor(Color::White),
+            Print(format!(
+                "{:<width$}",
+                title.chars().take(width).collect::<String>(),
+                width = width
+            )),
+            ResetColor
+        )?;
+
+        let count = format!(
+            "  {}/{}{}",
+            self.matches.len(),
+            self.candidates.len(),
+            if self.truncated { "+" } else { "" }
+        );
+        execute!(
+            stdout,
+            MoveTo(1, 1),
+            SetForegroundColor(Color::Yellow),
+            Print(format!("> {}_", self.query)),
+            SetForegroundColor(Color::DarkGrey),
+            Print(count),
+            ResetColor
+        )?;
+
+        let list_height = (terminal_height as usize).saturating_sub(4);
+        let start = self.selected.saturating_sub(list_height.saturating_sub(1));
+
+        for (row, (i, (index, m))) in self
+            .matches
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(list_height)
+            .enumerate()
+        {
+            let candidate = &self.candidates[*index];
+            let is_selected = i == self.selected;
+
+            execute!(
+                stdout,
+                MoveTo(1, 3 + row as u16),
+                SetBackgroundColor(if is_selected {
+                    Color::DarkGreen
+                } else {
+                    Color::Reset
+                }),
+                Print(if is_selected { "> " } else { "  " })
+            )?;
+
+            let base_color = if candidate.is_dir {
+                Color::Cyan
+            } else {
+                Color::White
+            };
+            for (pos, c) in candidate
+                .display
+                .chars()
+                .enumerate()
+                .take(width.saturating_sub(4))
+            {
+                let color = if m.positions.contains(&pos) {
+                    Color::Yellow
+                } else {
+                    base_color
+                };
+                execute!(stdout, SetForegroundColor(color), Print(c))?;
+            }
+            execute!(stdout, ResetColor)?;
+        }
+
+        let footer = " Type to match | ↑↓: Select | Enter: Jump | Ctrl+R: Recursive | Ctrl+E: Exclusions | Esc: Cancel";
+        execute!(
+            stdout,
+            MoveTo(0, terminal_height.saturating_sub(1)),
+            SetBackgroundColor(Color::DarkGrey),
+            SetForegroundColor(Color::White),
+            Print(format!(
+                "{:<width$}",
+                footer.chars().take(width).collect::<String>(),
+                width = width
+            )),
+            ResetColor
+        )?;
+
+        stdout.flush()?;
+        Ok(())
+    }
+
+    pub fn handle_input(&mut self, code: KeyCode, modifiers: KeyModifiers) -> FinderOutcome {
+        match code {
+            KeyCode::Up if self.selected > 0 => self.selected -= 1,
+            KeyCode::Down if self.selected + 1 < self.matches.len() => self.selected += 1,
+            KeyCode::Enter => {
+                if let Some((index, _)) = self.matches.get(self.selected) {
+                    return FinderOutcome::Jump(self.candidates[*index].path.clone());
+                }
+            }
+            KeyCode::Esc => return FinderOutcome::Cancel,
+            KeyCode::Char('r') if modifiers.contains(KeyModifiers::CONTROL) => {
+                self.recursive = !self.recursive;
+                self.rescan();
+            }
+            KeyCode::Char('e') if modifiers.contains(KeyModifiers::CONTROL) => {
+                self.use_excludes = !self.use_excludes;
+                self.rescan();
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.update_matches();
+            }
+            KeyCode::Char(c) if !modifiers.contains(KeyModifiers::CONTROL) => {
+                self.query.push(c);
+                self.update_matches();
+            }
+            _ => {}
+        }
+        FinderOutcome::Continue
+    }
+}
+
+/// Entries under `root`, breadth-first; the flag reports hitting `limit`
+///
+/// Symlinked directories are listed but not descended into, so link cycles
+/// cannot trap the scan. Entries matching `excludes` are skipped, excluded
+/// directories with everything below them.
+fn collect_candidates(
+    root: &Path,
+    recursive: bool,
+    show_hidden: bool,
+    excludes: Option<&ExcludeRules>,
+    limit: usize,
+) -> (Vec<Candidate>, bool) {
+    let mut excludes = excludes.cloned();
+    let mut candidates = Vec::new();
+    let mut queue = std::collections::VecDeque::from([(root.to_path_buf(), String::new())]);
+
+    while let Some((dir, prefix)) = queue.pop_front() {
+        let Ok(read_dir) = fs::read_dir(&dir) else {
+            continue;
+        };
+        if let Some(ref mut excludes) = excludes {
+            if dir != root {
+                excludes.enter_dir(&dir);
+            }
+        }
+        let mut children: Vec<_> = read_dir.flatten().collect();
+        children.sort_by_key(|e| e.file_name());
+
+        for child in children {
+            let name = child.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') && !show_hidden {
+                continue;
+            }
+            if candidates.len() >= limit {
+                return (candidates, true);
+            }
+
+            let path = child.path();
+            let is_dir = path.is_dir();
+            let is_symlink = child.file_type().map(|t| t.is_symlink()).unwrap_or(false);
+            if excludes
+                .as_ref()
+                .is_some_and(|rules| rules.is_excluded(&path, is_dir))
+            {
+                continue;
+            }
+            let relative = format!("{}{}", prefix, name);
+
+            if recursive && is_dir && !is_symlink {
+                queue.push_back((path.clone(), format!("{}/", relative)));
+            }
+            candidates.push(Candidate {
+                display: if is_dir {
+                    format!("{}/", relative)
+                } else {
+                    relative
+                },
+                path,
+                is_dir,
+            });
+        }
+    }
+    (candidates, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn tree() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("src/models")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), "").unwrap();
+        fs::write(dir.path().join("src/models/sort.rs"), "").unwrap();
+        fs::write(dir.path().join("Cargo.toml"), "").unwrap();
+        fs::write(dir.path().join(".hidden"), "").unwrap();
+        dir
+    }
+
+    fn displays(finder: &FuzzyFinder) -> Vec<&str> {
+        finder
+            .matches
+            .iter()
+            .map(|(i, _)| finder.candidates[*i].display.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_collect_candidates() {
+        let dir = tree();
+        let (flat, truncated) = collect_candidates(dir.path(), false, false, None, 100);
+        let names: Vec<_> = flat.iter().map(|c| c.display.as_str()).collect();
+        assert_eq!(names, vec!["Cargo.toml", "src/"]);
+        assert!(!truncated);
+
+        let (deep, _) = collect_candidates(dir.path(), true, true, None, 100);
+        assert_eq!(deep.len(), 6);
+
+        let (_, truncated) = collect_candidates(dir.path(), true, false, None, 3);
+        assert!(truncated);
+
+        let excludes = ExcludeRules::new(dir.path(), &["models/".to_string()], false);
+        let (pruned, _) = collect_candidates(dir.path(), true, false, Some(&excludes), 100);
+        let names: Vec<_> = pruned.iter().map(|c| c.display.as_str()).collect();
+        assert_eq!(names, vec!["Cargo.toml", "src/", "src/main.rs"]);
+    }
+
+    #[test]
+    fn test_recursive_ranking_and_jump() {
+        let dir = tree();
+        let mut finder = FuzzyFinder::new(dir.path(), false, ExcludeRules::default());
+        finder.handle_input(KeyCode::Char('r'), KeyModifiers::CONTROL);
+
+        for c in "sort".chars() {
+            finder.handle_input(KeyCode::Char(c), KeyModifiers::NONE);
+        }
+        assert_eq!(displays(&finder).first(), Some(&"src/models/sort.rs"));
+
+        match finder.handle_input(KeyCode::Enter, KeyModifiers::NONE) {
+            FinderOutcome::Jump(path) => assert_eq!(path, dir.path().join("src/models/sort.rs")),
+            _ => panic!("expected a jump"),
+        }
+    }
+}
@@ -0,0 +1,553 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use crossterm::{
+    cursor::MoveTo,
+    queue,
+    style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor},
+    terminal,
+};
+use image::{
+    imageops, imageops::FilterType, DynamicImage, ExtendedColorType, ImageDecoder, ImageFormat,
+    ImageReader, RgbaImage,
+};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::io::{self, Cursor, Write};
+use std::path::{Path, PathBuf};
+
+use crate::ui::{color_level, ColorLevel};
+
+/// Larger images are scaled down right after decoding; no preview area is
+/// wider than this
+const MAX_DECODED_SIZE: u32 = 1024;
+
+/// Pixel size of a terminal cell when the terminal does not report one
+const DEFAULT_CELL_SIZE: (u32, u32) = (8, 16);
+
+/// Encoded images kept for redrawing; the preview panel and quick view can
+/// show the same image at two sizes
+const CACHED_IMAGES: usize = 2;
+
+/// Largest payload of one kitty graphics escape
+const KITTY_CHUNK: usize = 4096;
+
+/// How images are drawn in the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphicsProtocol {
+    Kitty,
+    Iterm2,
+    Sixel,
+    /// Half-block characters in 24-bit color, for any terminal
+    Blocks,
+}
+
+impl GraphicsProtocol {
+    /// The best protocol the terminal advertises through its environment
+    pub fn detect() -> Self {
+        Self::detect_from(|name| std::env::var(name).ok())
+    }
+
+    fn detect_from(var: impl Fn(&str) -> Option<String>) -> Self {
+        // Multiplexers swallow graphics escapes unless configured to pass them on
+        if var("TMUX").is_some() || var("STY").is_some() {
+            return GraphicsProtocol::Blocks;
+        }
+
+        let term = var("TERM").unwrap_or_default();
+        let program = var("TERM_PROGRAM").unwrap_or_default();
+        if var("KITTY_WINDOW_ID").is_some()
+            || term.contains("kitty")
+            || term.contains("ghostty")
+            || matches!(program.as_str(), "WezTerm" | "ghostty")
+        {
+            GraphicsProtocol::Kitty
+        } else if program == "iTerm.app" || var("LC_TERMINAL").as_deref() == Some("iTerm2") {
+            GraphicsProtocol::Iterm2
+        } else if term.contains("sixel")
+            || ["foot", "mlterm", "yaft", "contour"]
+                .iter()
+                .any(|name| term.starts_with(name))
+        {
+            GraphicsProtocol::Sixel
+        } else {
+            GraphicsProtocol::Blocks
+        }
+    }
+}
+
+/// What an image file's header says about it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageHeader {
+    /// Format found from the contents, whatever the file's extension
+    pub format: ImageFormat,
+    pub dimensions: (u32, u32),
+    pub color: ExtendedColorType,
+}
+
+impl ImageHeader {
+    pub fn format_name(&self) -> String {
+        match self.format {
+            ImageFormat::Png => "PNG".to_string(),
+            ImageFormat::Jpeg => "JPEG".to_string(),
+            ImageFormat::Gif => "GIF".to_string(),
+            ImageFormat::WebP => "WebP".to_string(),
+            ImageFormat::Bmp => "BMP".to_string(),
+            ImageFormat::Tiff => "TIFF".to_string(),
+            other => format!("{:?}", other).to_uppercase(),
+        }
+    }
+
+    /// "RGBA, 8-bit" as stored in the file, before any conversion
+    pub fn color_label(&self) -> String {
+        use ExtendedColorType::*;
+        let channels = match self.color {
+            Cmyk8 | Cmyk16 => "CMYK",
+            Unknown(_) => "indexed",
+            A8 => "alpha",
+            color => match color.channel_count() {
+                1 => "grayscale",
+                2 => "grayscale + alpha",
+                3 => "RGB",
+                _ => "RGBA",
+            },
+        };
+        // Palette indices and packed pixels have no depth per channel
+        let depth = match self.color {
+            Unknown(bits) => bits as u16,
+            Rgb5x1 => 5,
+            color => color.bits_per_pixel() / color.channel_count() as u16,
+        };
+        format!("{}, {}-bit", channels, depth)
+    }
+}
+
+/// Read only the header of an image file: its format, size and color type
+/// without decoding the pixels, so it works for images too large or too
+/// damaged to decode
+pub fn probe(path: &Path) -> Option<ImageHeader> {
+    let reader = ImageReader::open(path).ok()?.with_guessed_format().ok()?;
+    let format = reader.format()?;
+    let decoder = reader.into_decoder().ok()?;
+    Some(ImageHeader {
+        format,
+        dimensions: decoder.dimensions(),
+        color: decoder.original_color_type(),
+    })
+}
+
+/// Decode an image file, scaled down to at most [`MAX_DECODED_SIZE`];
+/// returns it with its original dimensions
+pub fn decode(path: &Path) -> Option<(RgbaImage, (u32, u32))> {
+    let image = ImageReader::open(path)
+        .ok()?
+        .with_guessed_format()
+        .ok()?
+        .decode()
+        .ok()?;
+    let dimensions = (image.width(), image.height());
+    let image = if dimensions.0 > MAX_DECODED_SIZE || dimensions.1 > MAX_DECODED_SIZE {
+        image.thumbnail(MAX_DECODED_SIZE, MAX_DECODED_SIZE)
+    } else {
+        image
+    };
+    Some((image.to_rgba8(), dimensions))
+}
+
+/// Pixel size of `image` fitted into `cols`×`rows` cells of `cell` pixels,
+/// keeping its aspect ratio
+fn fit(image: (u32, u32), cols: u16, rows: u16, cell: (u32, u32)) -> (u32, u32) {
+    let (area_width, area_height) = (cols as f64 * cell.0 as f64, rows as f64 * cell.1 as f64);
+    let scale = (area_width / image.0.max(1) as f64).min(area_height / image.1.max(1) as f64);
+    (
+        ((image.0 as f64 * scale) as u32).clamp(1, area_width as u32),
+        ((image.1 as f64 * scale) as u32).clamp(1, area_height as u32),
+    )
+}
+
+/// Cells covered by `pixels`
+fn cells(pixels: (u32, u32), cell: (u32, u32)) -> (u16, u16) {
+    (
+        pixels.0.div_ceil(cell.0) as u16,
+        pixels.1.div_ceil(cell.1) as u16,
+    )
+}
+
+type Rgb = (u8, u8, u8);
+
+/// Colors of the top and bottom half of a cell
+type HalfBlock = (Rgb, Rgb);
+
+/// A pixel composited over black
+fn opaque(pixel: &image::Rgba<u8>) -> Rgb {
+    let [r, g, b, a] = pixel.0;
+    let blend = |c: u8| (c as u16 * a as u16 / 255) as u8;
+    (blend(r), blend(g), blend(b))
+}
+
+fn encode_png(image: &RgbaImage) -> Option<Vec<u8>> {
+    let mut png = Vec::new();
+    DynamicImage::ImageRgba8(image.clone())
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .ok()?;
+    Some(png)
+}
+
+/// Rows of half-block cells: top pixel as foreground, bottom as background
+fn encode_blocks(image: &RgbaImage) -> Vec<Vec<HalfBlock>> {
+    (0..image.height())
+        .step_by(2)
+        .map(|y| {
+            (0..image.width())
+                .map(|x| {
+                    let top = opaque(image.get_pixel(x, y));
+                    let bottom = if y + 1 < image.height() {
+                        opaque(image.get_pixel(x, y + 1))
+                    } else {
+                        (0, 0, 0)
+                    };
+                    (top, bottom)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Sixel data for `image`, quantized to a 6×6×6 color cube
+fn encode_sixel(image: &RgbaImage) -> String {
+    let (width, height) = image.dimensions();
+    let mut out = format!("\x1bPq\"1;1;{};{}", width, height);
+    for index in 0..216 {
+        let level = |c: u32| c * 20;
+        out.push_str(&format!(
+            "#{};2;{};{};{}",
+            index,
+            level(index / 36),
+            level(index / 6 % 6),
+            level(index % 6)
+        ));
+    }
+
+    let quantize = |c: u8| (c as usize + 25) / 51;
+    for band in (0..height).step_by(6) {
+        // Per color, the six-pixel column bits of every x in this band
+        let mut columns: BTreeMap<usize, Vec<u8>> = BTreeMap::new();
+        for y in band..(band + 6).min(height) {
+            for x in 0..width {
+                let (r, g, b) = opaque(image.get_pixel(x, y));
+                let color = quantize(r) * 36 + quantize(g) * 6 + quantize(b);
+                columns
+                    .entry(color)
+                    .or_insert_with(|| vec![0; width as usize])[x as usize] |= 1 << (y - band);
+            }
+        }
+
+        for (n, (color, bits)) in columns.iter().enumerate() {
+            if n > 0 {
+                // Back to the start of the band for the next color
+                out.push('$');
+            }
+            out.push_str(&format!("#{}", color));
+            let mut x = 0;
+            while x < bits.len() {
+                let run = bits[x..].iter().take_while(|&&b| b == bits[x]).count();
+                let sixel = (63 + bits[x]) as char;
+                if run > 3 {
+                    out.push_str(&format!("!{}{}", run, sixel));
+                } else {
+                    out.extend(std::iter::repeat_n(sixel, run));
+                }
+                x += run;
+            }
+        }
+        out.push('-');
+    }
+
+    out.push_str("\x1b\\");
+    out
+}
+
+fn encode_iterm2(image: &RgbaImage, cols: u16, rows: u16) -> Option<String> {
+    let png = encode_png(image)?;
+    Some(format!(
+        "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=1:{}\x07",
+        png.len(),
+        cols,
+        rows,
+        STANDARD.encode(&png)
+    ))
+}
+
+/// Transmission of `image` as kitty image `id`, without displaying it
+fn encode_kitty(image: &RgbaImage, id: u32) -> Option<String> {
+    let data = STANDARD.encode(encode_png(image)?);
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(KITTY_CHUNK).collect();
+    let mut out = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        let chunk = std::str::from_utf8(chunk).unwrap_or_default();
+        if i == 0 {
+            out.push_str(&format!(
+                "\x1b_Ga=t,f=100,i={},q=2,m={};{}\x1b\\",
+                id, more, chunk
+            ));
+        } else {
+            out.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+        }
+    }
+    Some(out)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct CacheKey {
+    path: PathBuf,
+    /// Address of the decoded pixels, so a re-decoded file is encoded again
+    pixels: usize,
+    cols: u16,
+    rows: u16,
+    cell: (u32, u32),
+}
+
+/// An image ready to be drawn at its size
+enum Encoded {
+    /// Escape sequence repeated on every frame (iTerm2, sixel)
+    Escape {
+        sequence: String,
+        rows: u16,
+    },
+    /// Image data already held by the terminal, placed by id
+    Kitty {
+        id: u32,
+        cols: u16,
+        rows: u16,
+    },
+    Blocks(Vec<Vec<HalfBlock>>),
+}
+
+/// Draws preview images with the terminal's graphics protocol
+///
+/// The screen is redrawn several times a second, so encoded images are
+/// cached per path and size; kitty terminals receive each image once.
+pub struct ImageCanvas {
+    protocol: GraphicsProtocol,
+    cache: Vec<(CacheKey, Encoded)>,
+    next_kitty_id: u32,
+}
+
+impl ImageCanvas {
+    pub fn new(protocol: GraphicsProtocol) -> Self {
+        Self {
+            protocol,
+            cache: Vec::new(),
+            next_kitty_id: 1,
+        }
+    }
+
+    /// Remove the images of the previous frame; call before redrawing
+    pub fn begin_frame(&self, out: &mut impl Write) -> io::Result<()> {
+        if self.protocol == GraphicsProtocol::Kitty {
+            // Placements only: the cached image data stays in the terminal
+            queue!(out, Print("\x1b_Ga=d,d=a,q=2\x1b\\"))?;
+        }
+        Ok(())
+    }
+
+    fn cell_size(&self) -> (u32, u32) {
+        if self.protocol == GraphicsProtocol::Blocks {
+            // Two vertically stacked pixels per cell
+            return (1, 2);
+        }
+        match terminal::window_size() {
+            Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => (
+                (size.width / size.columns) as u32,
+                (size.height / size.rows) as u32,
+            ),
+            _ => DEFAULT_CELL_SIZE,
+        }
+    }
+
+    /// Encode `image` for `key`, along with anything the terminal must
+    /// receive once before the first draw
+    fn encode(&mut self, key: &CacheKey, image: &RgbaImage) -> Option<(Encoded, String)> {
+        let pixels = fit(image.dimensions(), key.cols, key.rows, key.cell);
+        let scaled = imageops::resize(image, pixels.0, pixels.1, FilterType::Triangle);
+        let (cols, rows) = cells(pixels, key.cell);
+
+        Some(match self.protocol {
+            GraphicsProtocol::Blocks => (Encoded::Blocks(encode_blocks(&scaled)), String::new()),
+            GraphicsProtocol::Sixel => (
+                Encoded::Escape {
+                    sequence: encode_sixel(&scaled),
+                    rows,
+                },
+                String::new(),
+            ),
+            GraphicsProtocol::Iterm2 => (
+                Encoded::Escape {
+                    sequence: encode_iterm2(&scaled, cols, rows)?,
+                    rows,
+                },
+                String::new(),
+            ),
+            GraphicsProtocol::Kitty => {
+                let id = self.next_kitty_id;
+                self.next_kitty_id += 1;
+                (
+                    Encoded::Kitty { id, cols, rows },
+                    encode_kitty(&scaled, id)?,
+                )
+            }
+        })
+    }
+
+    /// Draw `image` (the decoded contents of `path`) fitted into `cols`×`rows`
+    /// cells at (`x`, `y`); returns the number of rows used
+    pub fn draw(
+        &mut self,
+        out: &mut impl Write,
+        path: &Path,
+        image: &RgbaImage,
+        (x, y): (u16, u16),
+        (cols, rows): (u16, u16),
+    ) -> io::Result<u16> {
+        // Half blocks need 24-bit color; the description line stands in
+        if cols == 0
+            || rows == 0
+            || (self.protocol == GraphicsProtocol::Blocks && color_level() != ColorLevel::Full)
+        {
+            return Ok(0);
+        }
+        let key = CacheKey {
+            path: path.to_path_buf(),
+            pixels: image.as_raw().as_ptr() as usize,
+            cols,
+            rows,
+            cell: self.cell_size(),
+        };
+
+        if !self.cache.iter().any(|(cached, _)| *cached == key) {
+            let Some((encoded, transmission)) = self.encode(&key, image) else {
+                return Ok(0);
+            };
+            queue!(out, Print(transmission))?;
+            if self.cache.len() == CACHED_IMAGES {
+                let (_, evicted) = self.cache.remove(0);
+                if let Encoded::Kitty { id, .. } = evicted {
+                    // Free the terminal's copy as well
+                    queue!(out, Print(format!("\x1b_Ga=d,d=I,i={},q=2\x1b\\", id)))?;
+                }
+            }
+            self.cache.push((key.clone(), encoded));
+        }
+        let Some((_, encoded)) = self.cache.iter().find(|(cached, _)| *cached == key) else {
+            return Ok(0);
+        };
+
+        match encoded {
+            Encoded::Escape { sequence, rows } => {
+                queue!(out, MoveTo(x, y), Print(sequence))?;
+                Ok(*rows)
+            }
+            Encoded::Kitty { id, cols, rows } => {
+                // C=1 keeps the cursor where it is
+                queue!(
+                    out,
+                    MoveTo(x, y),
+                    Print(format!(
+                        "\x1b_Ga=p,i={},c={},r={},C=1,q=2\x1b\\",
+                        id, cols, rows
+                    ))
+                )?;
+                Ok(*rows)
+            }
+            Encoded::Blocks(lines) => {
+                for (i, line) in lines.iter().enumerate() {
+                    queue!(out, MoveTo(x, y + i as u16))?;
+                    for &(top, bottom) in line {
+                        queue!(
+                            out,
+                            SetForegroundColor(Color::Rgb {
+                                r: top.0,
+                                g: top.1,
+                                b: top.2
+                            }),
+                            SetBackgroundColor(Color::Rgb {
+                                r: bottom.0,
+                                g: bottom.1,
+                                b: bottom.2
+                            }),
+                            Print('▀')
+                        )?;
+                    }
+                    queue!(out, ResetColor)?;
+                }
+                Ok(lines.len() as u16)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    #[test]
+    fn test_detect_protocol() {
+        use GraphicsProtocol::*;
+        assert_eq!(
+            GraphicsProtocol::detect_from(env(&[("TERM", "xterm-kitty")])),
+            Kitty
+        );
+        assert_eq!(
+            GraphicsProtocol::detect_from(env(&[("TERM_PROGRAM", "iTerm.app")])),
+            Iterm2
+        );
+        assert_eq!(
+            GraphicsProtocol::detect_from(env(&[("TERM", "foot")])),
+            Sixel
+        );
+        assert_eq!(
+            GraphicsProtocol::detect_from(env(&[("TERM", "xterm-256color")])),
+            Blocks
+        );
+        assert_eq!(
+            GraphicsProtocol::detect_from(env(&[("KITTY_WINDOW_ID", "1"), ("TMUX", "/tmp/t")])),
+            Blocks
+        );
+    }
+
+    #[test]
+    fn test_fit_keeps_aspect_ratio() {
+        // 200×100 into 10×10 cells of 8×16 pixels: width-bound
+        assert_eq!(fit((200, 100), 10, 10, (8, 16)), (80, 40));
+        assert_eq!(cells((80, 40), (8, 16)), (10, 3));
+        // Small images are enlarged to fill the area
+        assert_eq!(fit((4, 8), 10, 10, (1, 2)), (10, 20));
+    }
+
+    #[test]
+    fn test_block_and_sixel_encoding() {
+        let mut image = RgbaImage::new(2, 3);
+        image.put_pixel(0, 0, image::Rgba([255, 0, 0, 255]));
+        image.put_pixel(1, 1, image::Rgba([0, 0, 255, 255]));
+
+        let blocks = encode_blocks(&image);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0][0], ((255, 0, 0), (0, 0, 0)));
+        assert_eq!(blocks[0][1], ((0, 0, 0), (0, 0, 255)));
+        assert_eq!(blocks[1][0], ((0, 0, 0), (0, 0, 0)));
+
+        let sixel = encode_sixel(&image);
+        assert!(sixel.starts_with("\x1bPq\"1;1;2;3"));
+        assert!(sixel.ends_with("-\x1b\\"));
+        // One band: black, blue (0,0,5) and red (5,0,0) pixels, one color at a time
+        assert!(sixel.ends_with("#0ED$#5?A$#180@?-\x1b\\"));
+    }
+}
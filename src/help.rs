@@ -0,0 +1,674 @@
+use anyhow::Result;
+use crossterm::{
+    cursor::MoveTo,
+    event::KeyCode,
+    execute,
+    style::{Color, Print, ResetColor},
+    terminal,
+};
+use std::io::{self, Write};
+
+use crate::actions::{Action, Profile};
+use crate::custom_commands::CustomCommand;
+use crate::navigator::NavigatorMode;
+use crate::ui::{SetBackgroundColor, SetForegroundColor};
+
+type Binding = (String, String);
+
+fn bindings(pairs: &[(&str, &str)]) -> Vec<Binding> {
+    pairs
+        .iter()
+        .map(|(key, description)| (key.to_string(), description.to_string()))
+        .collect()
+}
+
+/// Key bindings of `mode`, most specific section first
+pub fn keymap(
+    mode: &NavigatorMode,
+    is_root: bool,
+    profile: Profile,
+    custom_commands: &[CustomCommand],
+    filter_active: bool,
+) -> Vec<(String, Vec<Binding>)> {
+    let mut sections = match mode {
+        NavigatorMode::Browse if filter_active => vec![(
+            "Type-to-filter".to_string(),
+            bindings(&[
+                ("Type", "Narrow the listing to matching names"),
+                ("Backspace", "Delete character (closes when empty)"),
+                ("↑ / ↓ / Enter / →", "Move and open as usual"),
+                ("Esc", "Clear the filter"),
+            ]),
+        )],
+        NavigatorMode::Browse | NavigatorMode::Preview => {
+            let mut navigation = bindings(&[
+                ("↑ / ↓", "Move cursor"),
+                ("→", "Enter directory"),
+                ("← / Backspace", "Go to parent directory"),
+                ("Tab", "Focus preview panel (when shown)"),
+                ("Ctrl+G", "Quick jump to bookmark"),
+                ("Unbound letter", "Start type-to-filter (Esc clears)"),
+            ]);
+            navigation.extend(
+                Action::ALL
+                    .iter()
+                    .filter(|action| action.is_available(is_root, profile))
+                    .filter(|action| !action.key_hint().is_empty())
+                    .map(|action| (action.key_hint().to_string(), action.label().to_string())),
+            );
+            navigation.push((":".to_string(), Action::CommandPalette.label().to_string()));
+
+            let mut sections = vec![("Browse".to_string(), navigation)];
+            let custom: Vec<Binding> = custom_commands
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| Action::Custom(*i).is_available(is_root, profile))
+                .map(|(_, command)| {
+                    let key = command
+                        .key
+                        .map(|k| k.to_string())
+                        .unwrap_or_else(|| "palette".to_string());
+                    (key, format!("{}: {}", command.name, command.template))
+                })
+                .collect();
+            if !custom.is_empty() {
+                sections.push(("Custom commands".to_string(), custom));
+            }
+            sections.push((
+                "Preview panel (focused)".to_string(),
+                bindings(&[
+                    ("↑ / ↓", "Scroll one line"),
+                    ("PageUp / PageDown", "Scroll one page"),
+                    ("/", "Search the previewed text"),
+                    ("n / N", "Next / previous search match"),
+                    (
+                        "n / p",
+                        "Next / previous message of a mailbox (no search active)",
+                    ),
+                    ("r", "Toggle rendered / raw Markdown"),
+                    (
+                        "Enter",
+                        "Fold / unfold the JSON object or array on the top line",
+                    ),
+                    ("l", "Toggle line numbers"),
+                    ("w", "Toggle wrapping of long lines"),
+                    ("g", "Go to an offset in a binary file"),
+                    (
+                        "Tab / Esc",
+                        "Back to the file list (Esc first clears a search)",
+                    ),
+                ]),
+            ));
+            sections
+        }
+        NavigatorMode::Select => vec![(
+            "Selection".to_string(),
+            bindings(&[
+                ("↑ / ↓", "Move cursor"),
+                ("Space", "Toggle selection"),
+                ("Click / drag", "Select that entry / range only"),
+                ("Ctrl+click / drag", "Toggle that entry / range"),
+                ("Enter", "Show selection count"),
+                ("c", "Chmod selected items"),
+                ("o", "Chown selected items"),
+                ("d", "Compare the two selected files"),
+                ("Ctrl+P", "Toggle preview / selection summary"),
+                ("Esc", "Clear selection and leave"),
+            ]),
+        )],
+        NavigatorMode::PatternSelect => vec![(
+            "Pattern selection".to_string(),
+            bindings(&[
+                ("Type", "Pattern to match names against"),
+                (
+                    "Tab",
+                    "Read it as a glob (*.rs, data?.csv, img[0-9]*), regex or literal text",
+                ),
+                ("Backspace", "Delete character"),
+                ("Enter", "Select matching entries"),
+                ("Esc", "Cancel"),
+            ]),
+        )],
+        NavigatorMode::Search => vec![(
+            "Search".to_string(),
+            bindings(&[
+                ("Type", "Edit query"),
+                (
+                    "size:>10M mtime:<7d",
+                    "Filter by size (B/K/M/G/T) or age (s/m/h/d/w/y)",
+                ),
+                ("Enter", "Run search, then go to the current result"),
+                ("↑ / ↓", "Recall earlier / later searches"),
+                ("Ctrl+N / Ctrl+P", "Next / previous result"),
+                ("Ctrl+R", "Toggle regex"),
+                ("Ctrl+C", "Toggle case sensitivity"),
+                ("Ctrl+G", "Toggle content search"),
+                ("Ctrl+S", "Cycle scope: listing, recursive, bookmarks, root"),
+                (
+                    "Ctrl+E",
+                    "Toggle exclusion rules ([search] exclude, .gitignore)",
+                ),
+                (
+                    "Ctrl+A",
+                    "Toggle content search inside .tar/.tar.gz archives",
+                ),
+                ("Ctrl+L", "Toggle machine-wide search (plocate/locate)"),
+                ("Esc", "Stop a running search, then leave"),
+            ]),
+        )],
+        NavigatorMode::FuzzyFinder => vec![(
+            "Fuzzy finder".to_string(),
+            bindings(&[
+                ("Type", "Fuzzy-match paths"),
+                ("↑ / ↓", "Select match"),
+                ("Enter", "Jump to match"),
+                ("Ctrl+R", "Toggle recursive matching"),
+                ("Ctrl+E", "Toggle exclusion rules"),
+                ("Esc", "Cancel"),
+            ]),
+        )],
+        NavigatorMode::Bookmarks => vec![(
+            "Bookmarks".to_string(),
+            bindings(&[
+                ("↑ / ↓", "Move cursor"),
+                ("Type", "Fuzzy-filter by name or path"),
+                ("#tag", "Filter by tag (prefix)"),
+                ("Enter", "Jump to the highlighted (best) match"),
+                ("Enter", "On a suggestion: bookmark it"),
+                ("Alt+a-z / 0-9", "Jump by shortcut"),
+                ("Ctrl+A", "Bookmark current directory"),
+                ("Ctrl+D", "Delete bookmark, or dismiss a suggestion"),
+                (
+                    "Ctrl+R",
+                    "Edit name and path (Tab switches, Ctrl+S makes the name portable)",
+                ),
+                ("Ctrl+S", "Change shortcut, showing conflicts"),
+                ("Ctrl+T", "Edit tags; the first is the category"),
+                ("Shift+↑/↓", "Move the bookmark up or down"),
+                ("Ctrl+W", "Watch for new files (hot)"),
+                ("Ctrl+X", "Prune dead bookmarks"),
+                ("Esc", "Clear the filter, then back"),
+            ]),
+        )],
+        NavigatorMode::SplitPane => vec![(
+            "Split pane".to_string(),
+            bindings(&[
+                ("Tab / Shift+Tab", "Focus the next / previous pane"),
+                ("Alt+1 … Alt+4", "Focus pane by number"),
+                (
+                    "Ctrl+F",
+                    "Filter the active pane as you type (Backspace past the start or Esc clears)",
+                ),
+                ("↑ / ↓", "Move cursor"),
+                ("→ / Enter", "Enter directory"),
+                ("← / Backspace", "Go to parent directory"),
+                ("Space", "Toggle selection"),
+                ("c", "Copy the selection to the next pane"),
+                ("m", "Move the selection to the next pane, after a y/n"),
+                (
+                    "d",
+                    "Compare two files selected in one pane, or one here and one in the next",
+                ),
+                ("o", "Open the directory under the cursor in the next pane"),
+                (
+                    "l",
+                    "Link panes: moves repeat in the others where their trees line up",
+                ),
+                ("C", "Chmod the selection (your own files without root)"),
+                ("O", "Chown the selection (simulated when not root)"),
+                (
+                    "=",
+                    "Compare with the next pane: + only here, ▲ newer, ▼ older, ≠ size or type differs",
+                ),
+                (
+                    "p",
+                    "Preview the entry under the cursor in place of the next pane",
+                ),
+                ("PageUp / PageDown", "Scroll that preview"),
+                ("F5", "Open this directory in every pane"),
+                ("F6", "Cycle layout: columns, rows, 2x2 grid"),
+                ("F7", "Add a pane on this directory (up to 4)"),
+                ("F8", "Close this pane (two stay open)"),
+                ("+ / -", "Resize the first column / row"),
+                ("Click", "Focus that pane and entry"),
+                ("Drag a divider", "Resize the panes"),
+                ("Wheel", "Move the cursor of the active pane"),
+                ("Esc / q", "Leave split view"),
+            ]),
+        )],
+        NavigatorMode::ChmodInterface => vec![(
+            "Chmod".to_string(),
+            bindings(&[
+                ("← / →", "Select owner/group/other digit"),
+                ("↑ / ↓", "Change digit (or template)"),
+                ("t", "Toggle permission templates"),
+                ("f", "Copy the mode of a reference file"),
+                ("s", "Type a symbolic mode such as u+x,go-w"),
+                ("r", "Toggle recursive"),
+                ("d", "Give files their own mode (directories keep the digits)"),
+                ("Tab", "Edit the directory or the file digits"),
+                ("x", "Execute only for directories and executables (X)"),
+                ("l", "List every entry that would change"),
+                ("n", "Toggle dry run: Enter lists the changes"),
+                ("w", "Save the list of changes as a report"),
+                ("1-5", "Use a recently applied mode"),
+                ("p", "Toggle preview"),
+                ("e", "Export the chmod command as a script"),
+                ("Enter", "Apply"),
+                ("Esc", "Cancel"),
+            ]),
+        )],
+        NavigatorMode::ChownInterface => vec![(
+            "Chown".to_string(),
+            bindings(&[
+                ("Tab", "Cycle focus (user, group, options)"),
+                ("↑ / ↓", "Move in the focused list"),
+                ("Type", "Filter users/groups"),
+                ("Space", "Toggle option"),
+                ("r", "Toggle recursive"),
+                ("o / g", "Tick whether the owner / group changes (in options)"),
+                (
+                    "m",
+                    "Change owner and group, owner only or group only (in options)",
+                ),
+                (
+                    "Ctrl+R",
+                    "Copy the owner of a reference file (f in options)",
+                ),
+                ("1-5", "Use a recently applied owner (in options)"),
+                ("Ctrl+L", "List every entry that would change (l in options)"),
+                ("Ctrl+N", "Toggle dry run as root (n in options)"),
+                ("Ctrl+U", "Roll back an earlier change or marked files of it (u in options)"),
+                ("w", "Save the simulation as a report (in results)"),
+                ("p", "Toggle preview"),
+                ("Enter", "Confirm (simulate when not root)"),
+                ("y / n", "Apply / cancel at confirmation"),
+                (
+                    "Ctrl+E",
+                    "Export the chown command as a script (e in options or results)",
+                ),
+                ("Esc", "Cancel (or back from a simulation)"),
+            ]),
+        )],
+        NavigatorMode::Workflows => vec![(
+            "Workflows".to_string(),
+            bindings(&[
+                ("↑ / ↓", "Select workflow"),
+                ("Enter", "Replay on the current directory"),
+                ("Ctrl+D", "Delete workflow"),
+                ("Enter / y", "Run the step shown (while replaying)"),
+                ("s / n", "Skip the step"),
+                ("Esc", "Close, or stop the replay"),
+            ]),
+        )],
+        NavigatorMode::Checksums => vec![(
+            "Checksums".to_string(),
+            bindings(&[
+                ("↑ / ↓", "Select file"),
+                ("f", "Next failure"),
+                ("r", "Read the checksum files and verify again"),
+                ("Esc / q", "Close; the ✔ ✘ markers stay in the listing"),
+            ]),
+        )],
+        NavigatorMode::Sessions => vec![(
+            "Sessions".to_string(),
+            bindings(&[
+                ("↑ / ↓", "Select session"),
+                ("Enter", "Restore it"),
+                ("Ctrl+N", "Save the current state as a new session"),
+                ("Ctrl+S", "Save over the selected session (name prefilled)"),
+                ("Ctrl+D", "Delete session"),
+                ("Esc", "Close"),
+            ]),
+        )],
+        NavigatorMode::OpenWith => vec![(
+            "Open with".to_string(),
+            bindings(&[
+                ("↑ / ↓", "Select application"),
+                ("Enter", "Open"),
+                ("r", "Open and remember for this extension"),
+                ("Esc / q", "Cancel"),
+            ]),
+        )],
+        NavigatorMode::CommandPalette => vec![(
+            "Command palette".to_string(),
+            bindings(&[
+                ("Type", "Fuzzy-filter actions"),
+                ("↑ / ↓", "Select action"),
+                ("Enter", "Run"),
+                ("Esc", "Close"),
+            ]),
+        )],
+        NavigatorMode::QuickView => {
+            vec![("Quick view".to_string(), bindings(&[("Any key", "Close")]))]
+        }
+        NavigatorMode::Help => Vec::new(),
+        NavigatorMode::Monitor => vec![(
+            "Monitor".to_string(),
+            bindings(&[
+                ("↑ / ↓", "Scroll one line"),
+                ("PageUp / PageDown", "Scroll one page"),
+                ("End", "Follow new events"),
+                ("r", "Watch directory / whole subtree"),
+                ("e", "Export log to a file"),
+                ("c", "Clear log"),
+                ("Esc / q", "Close"),
+            ]),
+        )],
+        NavigatorMode::HexEditor => vec![(
+            "Hex editor".to_string(),
+            bindings(&[
+                ("← / → / ↑ / ↓", "Move by a byte / a row"),
+                ("PageUp / PageDown", "Move one page"),
+                ("Home / End", "Start / end of the file"),
+                ("g", "Go to offset (decimal or 0x hex)"),
+                ("i", "Edit: type hex digits to overwrite bytes"),
+                ("Backspace", "Revert the byte under the cursor (editing)"),
+                ("w", "Save edits in place"),
+                ("Esc", "Stop editing, then close"),
+                ("q", "Close (asks first with unsaved edits)"),
+            ]),
+        )],
+        NavigatorMode::Pager => vec![(
+            "Pager".to_string(),
+            bindings(&[
+                ("↑ / ↓ / j / k / Enter", "Scroll one line"),
+                ("PageUp / PageDown / b / Space", "Scroll one page"),
+                ("Home / End / g / G", "Start / end of the file"),
+                ("/", "Search (Enter to find)"),
+                ("n / N", "Next / previous match"),
+                ("w", "Wrap or cut long lines"),
+                ("Esc", "Clear the search, then close"),
+                ("q", "Close"),
+            ]),
+        )],
+        NavigatorMode::Diff => vec![(
+            "Diff".to_string(),
+            bindings(&[
+                ("↑ / ↓ / j / k / Enter", "Scroll one line"),
+                ("PageUp / PageDown / b / Space", "Scroll one page"),
+                ("Home / End / g / G", "Start / end of the diff"),
+                ("n / N", "Next / previous change"),
+                ("s", "Side by side or unified"),
+                ("Esc / q", "Close"),
+            ]),
+        )],
+        NavigatorMode::DirStats => vec![(
+            "Directory statistics".to_string(),
+            bindings(&[
+                ("↑ / ↓", "Select extension"),
+                ("Home / End", "Jump to first / last"),
+                ("Enter / e", "Filter the listing to the extension"),
+                ("Esc / q", "Close"),
+            ]),
+        )],
+        NavigatorMode::Containers => vec![(
+            "Container volumes".to_string(),
+            bindings(&[
+                ("↑ / ↓", "Select mount"),
+                ("Home / End", "Jump to first / last"),
+                ("Enter", "Go to the mount's host path"),
+                ("r", "Refresh"),
+                ("Esc / q", "Close"),
+            ]),
+        )],
+        NavigatorMode::SnapshotReport => vec![(
+            "Snapshot comparison".to_string(),
+            bindings(&[
+                ("↑ / ↓", "Scroll one line"),
+                ("PageUp / PageDown", "Scroll one page"),
+                ("Home / End", "Jump to start / end"),
+                ("Esc / q / Enter", "Close"),
+            ]),
+        )],
+        NavigatorMode::CommandOutput => vec![(
+            "Command output".to_string(),
+            bindings(&[
+                ("↑ / ↓", "Scroll one line"),
+                ("PageUp / PageDown", "Scroll one page"),
+                ("Home / End", "Jump to start / end"),
+                ("Esc / q / Enter", "Close"),
+            ]),
+        )],
+    };
+
+    sections.push(("Help".to_string(), bindings(&[("F1", "Show this help")])));
+    sections
+}
+
+/// Full-screen list of the bindings for the mode it was opened from
+pub struct HelpOverlay {
+    sections: Vec<(String, Vec<Binding>)>,
+    query: String,
+    scroll_offset: usize,
+}
+
+enum HelpLine<'a> {
+    Heading(&'a str),
+    Binding(&'a str, &'a str),
+}
+
+impl HelpOverlay {
+    pub fn new(sections: Vec<(String, Vec<Binding>)>) -> Self {
+        Self {
+            sections,
+            query: String::new(),
+            scroll_offset: 0,
+        }
+    }
+
+    /// Headings and bindings left after filtering by the search query
+    fn lines(&self) -> Vec<HelpLine<'_>> {
+        let query = self.query.to_lowercase();
+        let mut lines = Vec::new();
+
+        for (title, bindings) in &self.sections {
+            let matching: Vec<&Binding> = bindings
+                .iter()
+                .filter(|(key, description)| {
+                    query.is_empty()
+                        || key.to_lowercase().contains(&query)
+                        || description.to_lowercase().contains(&query)
+                })
+                .collect();
+            if matching.is_empty() {
+                continue;
+            }
+            lines.push(HelpLine::Heading(title));
+            lines.extend(
+                matching
+                    .into_iter()
+                    .map(|(key, description)| HelpLine::Binding(key, description)),
+            );
+        }
+        lines
+    }
+
+    pub fn render(&self) -> Result<()> {
+        let mut stdout = io::stdout();
+        let (terminal_width, terminal_height) = terminal::size()?;
+        let width = terminal_width as usize;
+
+        execute!(stdout, terminal::Clear(terminal::ClearType::All))?;
+
+        execute!(
+            stdout,
+            MoveTo(0, 0),
+            SetBackgroundColor(Color::DarkBlue),
+            SetForegroundColor(Color::White),
+            Print(format!(
+                "{:<width$}",
+                " ❓ KEYBOARD SHORTCUTS",
+                width = width
+            )),
+            ResetColor
+        )?;
+
+        execute!(
+            stdout,
+            MoveTo(1, 1),
+            SetForegroundColor(Color::Yellow),
+            Print(format!("Filter: {}_", self.query)),
+            ResetColor
+        )?;
+
+        let lines = self.lines();
+        let body_height = (terminal_height as usize).saturating_sub(4);
+        if lines.is_empty() {
+            execute!(
+                stdout,
+                MoveTo(2, 3),
+                SetForegroundColor(Color::DarkGrey),
+                Print("No matching shortcut"),
+                ResetColor
+            )?;
+        }
+
+        let key_width = 20;
+        for (i, line) in lines
+            .iter()
+            .skip(self.scroll_offset)
+            .take(body_height)
+            .enumerate()
+        {
+            let row = 3 + i as u16;
+            match line {
+                HelpLine::Heading(title) => execute!(
+                    stdout,
+                    MoveTo(1, row),
+                    SetForegroundColor(Color::Cyan),
+                    Print(title),
+                    ResetColor
+                )?,
+                HelpLine::Binding(key, description) => {
+                    let description: String = description
+                        .chars()
+                        .take(width.saturating_sub(key_width + 4))
+                        .collect();
+                    execute!(
+                        stdout,
+                        MoveTo(3, row),
+                        SetForegroundColor(Color::Green),
+                        Print(format!("{:<width$}", key, width = key_width)),
+                        ResetColor,
+                        Print(description)
+                    )?;
+                }
+            }
+        }
+
+        let footer = " Type to filter | ↑↓/PageUp/PageDown: Scroll | Esc/F1: Close";
+        execute!(
+            stdout,
+            MoveTo(0, terminal_height.saturating_sub(1)),
+            SetBackgroundColor(Color::DarkGrey),
+            SetForegroundColor(Color::White),
+            Print(format!(
+                "{:<width$}",
+                footer.chars().take(width).collect::<String>(),
+                width = width
+            )),
+            ResetColor
+        )?;
+
+        stdout.flush()?;
+        Ok(())
+    }
+
+    /// Returns false once the overlay should close
+    pub fn handle_input(&mut self, key: KeyCode) -> bool {
+        let max_offset = self.lines().len().saturating_sub(1);
+        match key {
+            KeyCode::Up => self.scroll_offset = self.scroll_offset.saturating_sub(1),
+            KeyCode::Down => self.scroll_offset = (self.scroll_offset + 1).min(max_offset),
+            KeyCode::PageUp => self.scroll_offset = self.scroll_offset.saturating_sub(10),
+            KeyCode::PageDown => self.scroll_offset = (self.scroll_offset + 10).min(max_offset),
+            // Esc clears an active filter before closing
+            KeyCode::Esc if !self.query.is_empty() => {
+                self.query.clear();
+                self.scroll_offset = 0;
+            }
+            KeyCode::Esc | KeyCode::F(1) => return false,
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.scroll_offset = 0;
+            }
+            KeyCode::Char(c) => {
+                self.query.push(c);
+                self.scroll_offset = 0;
+            }
+            _ => {}
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(sections: &[(String, Vec<Binding>)]) -> Vec<String> {
+        sections
+            .iter()
+            .flat_map(|(_, bindings)| bindings.iter().map(|(key, _)| key.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_browse_keymap_follows_actions() {
+        let user = keys(&keymap(
+            &NavigatorMode::Browse,
+            false,
+            Profile::Admin,
+            &[],
+            false,
+        ));
+        assert!(user.contains(&"Ctrl+S".to_string()));
+        assert!(user.contains(&"F1".to_string()));
+        assert!(user.contains(&"c".to_string()));
+        assert!(!user.contains(&"s".to_string()));
+
+        let root = keys(&keymap(
+            &NavigatorMode::Browse,
+            true,
+            Profile::Admin,
+            &[],
+            false,
+        ));
+        assert!(root.contains(&"s".to_string()));
+
+        let basic = keys(&keymap(
+            &NavigatorMode::Browse,
+            true,
+            Profile::Basic,
+            &[],
+            false,
+        ));
+        assert!(!basic.contains(&"c".to_string()));
+        assert!(!basic.contains(&"S".to_string()));
+        assert!(basic.contains(&"Ctrl+F".to_string()));
+    }
+
+    #[test]
+    fn test_filter_keeps_matching_sections() {
+        let mut overlay = HelpOverlay::new(keymap(
+            &NavigatorMode::Search,
+            false,
+            Profile::Admin,
+            &[],
+            false,
+        ));
+        for c in "regex".chars() {
+            overlay.handle_input(KeyCode::Char(c));
+        }
+
+        let lines = overlay.lines();
+        assert_eq!(lines.len(), 2);
+        assert!(matches!(lines[0], HelpLine::Heading("Search")));
+        assert!(matches!(lines[1], HelpLine::Binding("Ctrl+R", _)));
+
+        // First Esc clears the filter, the second closes
+        assert!(overlay.handle_input(KeyCode::Esc));
+        assert!(!overlay.handle_input(KeyCode::Esc));
+    }
+}
@@ -0,0 +1,571 @@
+use anyhow::{Context, Result};
+use crossterm::{
+    cursor::MoveTo,
+    event::KeyCode,
+    execute,
+    style::{Color, Print, ResetColor},
+    terminal,
+};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::preview::FilePreview;
+use crate::tasks::Task;
+use crate::ui::{SetBackgroundColor, SetForegroundColor};
+
+/// Bytes shown per row
+const ROW_BYTES: u64 = 16;
+
+/// How long a frame waits for the bytes it shows before drawing them as
+/// still being read
+const PAGE_GRACE: Duration = Duration::from_millis(30);
+
+/// Bytes read from the file for the screen, before edits
+#[derive(Debug)]
+struct Page {
+    start: u64,
+    bytes: Vec<u8>,
+}
+
+impl Page {
+    fn end(&self) -> u64 {
+        self.start + self.bytes.len() as u64
+    }
+}
+
+/// Parse an offset typed at a goto prompt: decimal, or hex with `0x`
+pub fn parse_offset(text: &str) -> Option<u64> {
+    let text = text.trim();
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+/// Full-screen hex view of a file, read a screen at a time so files of any
+/// size page smoothly; edits stay in memory until saved in place
+pub struct HexEditor {
+    path: PathBuf,
+    len: u64,
+    /// Offset of the byte under the cursor
+    cursor: u64,
+    /// Offset of the first row on screen
+    top: u64,
+    /// Changed bytes by offset, not yet written
+    edits: BTreeMap<u64, u8>,
+    /// Whether editing is allowed at all
+    editable: bool,
+    /// Typing hex digits overwrites bytes
+    editing: bool,
+    /// High nibble typed for the byte under the cursor
+    pending_nibble: Option<u8>,
+    /// Text typed at the goto-offset prompt
+    goto_input: Option<String>,
+    /// Esc was pressed once with unsaved edits
+    confirm_discard: bool,
+    status: Option<String>,
+    /// The bytes on screen, read on a worker
+    page: Option<Page>,
+    /// A page being read, by where it starts
+    loading: Option<(u64, Task<io::Result<Vec<u8>>>)>,
+}
+
+impl HexEditor {
+    pub fn open(path: &Path, editable: bool) -> Result<Self> {
+        let metadata = path
+            .metadata()
+            .with_context(|| format!("Cannot read {}", path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            len: metadata.len(),
+            cursor: 0,
+            top: 0,
+            edits: BTreeMap::new(),
+            editable,
+            editing: false,
+            pending_nibble: None,
+            goto_input: None,
+            confirm_discard: false,
+            status: None,
+            page: None,
+            loading: None,
+        })
+    }
+
+    /// Rows of bytes that fit on screen
+    fn page_rows() -> u64 {
+        terminal::size()
+            .map(|(_, h)| (h as u64).saturating_sub(5))
+            .unwrap_or(16)
+            .max(1)
+    }
+
+    /// `len` bytes of the file at `path` from `start`
+    fn read_range(path: &Path, start: u64, len: u64) -> io::Result<Vec<u8>> {
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(start))?;
+        let mut bytes = Vec::new();
+        file.take(len).read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Start reading the bytes the screen shows unless they are loaded or
+    /// on their way, and pick up a page read meanwhile; called once per
+    /// event-loop pass so drawing never waits on the disk
+    pub fn poll(&mut self) {
+        let start = self.top;
+        let len = Self::page_rows() * ROW_BYTES;
+        let loaded = self.page.as_ref().is_some_and(|page| {
+            page.start <= start && (start + len <= page.end() || page.end() >= self.len)
+        });
+        let requested = self
+            .loading
+            .as_ref()
+            .is_some_and(|(loading, _)| *loading == start);
+        if loaded {
+            // A read for a page scrolled away from is no longer needed
+            self.loading = None;
+            return;
+        }
+        if !requested {
+            let path = self.path.clone();
+            let task = Task::spawn(move |_| Self::read_range(&path, start, len));
+            // Replacing a load for another page cancels it
+            self.loading = Some((start, task));
+        }
+
+        let Some((start, ref mut task)) = self.loading else {
+            return;
+        };
+        // Quick disks show the page at once instead of flashing the
+        // placeholder
+        let wait = if requested {
+            Duration::ZERO
+        } else {
+            PAGE_GRACE
+        };
+        match task.wait(wait) {
+            Some(Ok(bytes)) => {
+                self.page = Some(Page { start, bytes });
+                self.loading = None;
+            }
+            // The finished task stays, so the page is not read again
+            // until the view moves
+            Some(Err(e)) => self.status = Some(format!("Cannot read the file: {}", e)),
+            None => {}
+        }
+    }
+
+    /// `len` bytes from `start` as they would be saved, edits included, or
+    /// `None` while the page holding them is being read
+    fn shown_range(&self, start: u64, len: u64) -> Option<Vec<u8>> {
+        let page = self.page.as_ref().filter(|page| page.start <= start)?;
+        let from = (start - page.start) as usize;
+        let to = ((start + len).min(page.end()) - page.start) as usize;
+        let mut bytes = page.bytes.get(from..to)?.to_vec();
+        for (&offset, &byte) in self.edits.range(start..start + bytes.len() as u64) {
+            bytes[(offset - start) as usize] = byte;
+        }
+        Some(bytes)
+    }
+
+    /// Move the cursor to `offset`, clamped to the file, keeping it on screen
+    fn move_to(&mut self, offset: u64) {
+        self.cursor = offset.min(self.len.saturating_sub(1));
+        self.pending_nibble = None;
+        let rows = Self::page_rows();
+        let row_start = self.cursor - self.cursor % ROW_BYTES;
+        if row_start < self.top {
+            self.top = row_start;
+        } else if row_start >= self.top + rows * ROW_BYTES {
+            self.top = row_start - (rows - 1) * ROW_BYTES;
+        }
+    }
+
+    /// Overwrite half of the byte under the cursor with a typed hex digit;
+    /// the second digit completes the byte and moves on
+    fn type_nibble(&mut self, digit: u8) {
+        let current = match self.shown_range(self.cursor, 1) {
+            Some(bytes) if !bytes.is_empty() => bytes[0],
+            _ => return,
+        };
+        match self.pending_nibble.take() {
+            None => {
+                self.edits
+                    .insert(self.cursor, (digit << 4) | (current & 0x0f));
+                self.pending_nibble = Some(digit);
+            }
+            Some(high) => {
+                self.edits.insert(self.cursor, (high << 4) | digit);
+                if self.cursor + 1 < self.len {
+                    self.move_to(self.cursor + 1);
+                }
+            }
+        }
+        self.confirm_discard = false;
+    }
+
+    /// Write the edited bytes in place; the file keeps its size
+    fn save(&mut self) -> io::Result<usize> {
+        let mut file = OpenOptions::new().write(true).open(&self.path)?;
+        for (&offset, &byte) in &self.edits {
+            file.seek(SeekFrom::Start(offset))?;
+            file.write_all(&[byte])?;
+        }
+        file.flush()?;
+        // The page on screen now matches the file again
+        if let Some(ref mut page) = self.page {
+            for (&offset, &byte) in self.edits.range(page.start..page.end()) {
+                page.bytes[(offset - page.start) as usize] = byte;
+            }
+        }
+        let count = self.edits.len();
+        self.edits.clear();
+        Ok(count)
+    }
+
+    fn save_with_status(&mut self) {
+        self.status = Some(match self.save() {
+            Ok(0) => "Nothing to save".to_string(),
+            Ok(count) => format!("Saved {} changed byte(s)", count),
+            Err(e) => format!("Save failed: {}", e),
+        });
+        self.confirm_discard = false;
+    }
+
+    pub fn render(&self) -> Result<()> {
+        let mut stdout = io::stdout();
+        let (terminal_width, terminal_height) = terminal::size()?;
+        let width = terminal_width as usize;
+
+        execute!(stdout, terminal::Clear(terminal::ClearType::All))?;
+
+        let header: String = format!(
+            " 🔢 HEX  {}  ({})",
+            self.path.display(),
+            FilePreview::format_size(self.len)
+        )
+        .chars()
+        .take(width)
+        .collect();
+        execute!(
+            stdout,
+            MoveTo(0, 0),
+            SetBackgroundColor(Color::DarkBlue),
+            SetForegroundColor(Color::White),
+            Print(format!("{:<width$}", header, width = width)),
+            ResetColor
+        )?;
+
+        let info = if let Some(ref input) = self.goto_input {
+            format!("Go to offset (decimal or 0x hex): {}_", input)
+        } else if let Some(ref status) = self.status {
+            status.clone()
+        } else {
+            format!(
+                "Offset 0x{:08x} ({}) of {}{}{}",
+                self.cursor,
+                self.cursor,
+                self.len,
+                if self.editing { "  [EDIT]" } else { "" },
+                if self.edits.is_empty() {
+                    String::new()
+                } else {
+                    format!("  {} unsaved byte(s)", self.edits.len())
+                }
+            )
+        };
+        execute!(
+            stdout,
+            MoveTo(1, 1),
+            SetForegroundColor(if self.editing {
+                Color::Magenta
+            } else {
+                Color::Yellow
+            }),
+            Print(
+                info.chars()
+                    .take(width.saturating_sub(2))
+                    .collect::<String>()
+            ),
+            ResetColor
+        )?;
+
+        let rows = (terminal_height as u64).saturating_sub(5);
+        let shown = self.shown_range(self.top, rows * ROW_BYTES);
+        let placeholder = match shown {
+            _ if self.len == 0 => Some("Empty file"),
+            None => Some("Reading…"),
+            Some(_) => None,
+        };
+        if let Some(placeholder) = placeholder {
+            execute!(
+                stdout,
+                MoveTo(1, 3),
+                SetForegroundColor(Color::DarkGrey),
+                Print(placeholder),
+                ResetColor
+            )?;
+        }
+        let bytes = shown.unwrap_or_default();
+
+        for (row, chunk) in bytes.chunks(ROW_BYTES as usize).enumerate() {
+            let row_offset = self.top + row as u64 * ROW_BYTES;
+            let y = 3 + row as u16;
+            execute!(
+                stdout,
+                MoveTo(1, y),
+                SetForegroundColor(Color::DarkGrey),
+                Print(format!("{:08x}  ", row_offset))
+            )?;
+            for (i, byte) in chunk.iter().enumerate() {
+                let offset = row_offset + i as u64;
+                self.print_byte(&mut stdout, offset, format!("{:02x}", byte))?;
+                // A wider gap halfway through the row
+                execute!(stdout, Print(if i == 7 { "  " } else { " " }))?;
+            }
+            let padding = (ROW_BYTES as usize - chunk.len()) * 3 + usize::from(chunk.len() <= 7);
+            execute!(stdout, Print(" ".repeat(padding + 1)), Print("|"))?;
+            for (i, &byte) in chunk.iter().enumerate() {
+                let c = if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                };
+                self.print_byte(&mut stdout, row_offset + i as u64, c.to_string())?;
+            }
+            execute!(stdout, ResetColor, Print("|"))?;
+        }
+
+        let footer = if self.editing {
+            " 0-9a-f: Overwrite byte | Backspace: Revert byte | ←→↑↓: Move | w: Save | Esc: Stop editing"
+        } else if self.editable {
+            " ←→↑↓/PgUp/PgDn: Move | Home/End: Start/End | g: Go to offset | i: Edit | w: Save | Esc/q: Close"
+        } else {
+            " ←→↑↓/PgUp/PgDn: Move | Home/End: Start/End | g: Go to offset | Esc/q: Close"
+        };
+        execute!(
+            stdout,
+            MoveTo(0, terminal_height.saturating_sub(1)),
+            SetBackgroundColor(Color::DarkGrey),
+            SetForegroundColor(Color::White),
+            Print(format!(
+                "{:<width$}",
+                footer.chars().take(width).collect::<String>(),
+                width = width
+            )),
+            ResetColor
+        )?;
+
+        stdout.flush()?;
+        Ok(())
+    }
+
+    /// Print a byte's hex or character cell: the cursor on green, unsaved
+    /// edits in yellow
+    fn print_byte(&self, stdout: &mut io::Stdout, offset: u64, text: String) -> Result<()> {
+        let background = if offset == self.cursor {
+            Color::DarkGreen
+        } else {
+            Color::Reset
+        };
+        let foreground = if self.edits.contains_key(&offset) {
+            Color::Yellow
+        } else if offset == self.cursor {
+            Color::White
+        } else {
+            Color::Reset
+        };
+        execute!(
+            stdout,
+            SetBackgroundColor(background),
+            SetForegroundColor(foreground),
+            Print(text),
+            ResetColor
+        )?;
+        Ok(())
+    }
+
+    /// Returns false once the view should close
+    pub fn handle_input(&mut self, key: KeyCode) -> bool {
+        if let Some(mut input) = self.goto_input.take() {
+            match key {
+                KeyCode::Char(c) if c.is_ascii_hexdigit() || c == 'x' || c == 'X' => {
+                    input.push(c);
+                    self.goto_input = Some(input);
+                }
+                KeyCode::Backspace => {
+                    input.pop();
+                    self.goto_input = Some(input);
+                }
+                KeyCode::Enter => match parse_offset(&input) {
+                    Some(offset) if offset < self.len => self.move_to(offset),
+                    Some(_) => self.status = Some(format!("The file is {} bytes long", self.len)),
+                    None => self.status = Some(format!("Not an offset: {}", input)),
+                },
+                KeyCode::Esc => {}
+                _ => self.goto_input = Some(input),
+            }
+            return true;
+        }
+
+        self.status = None;
+        let rows = Self::page_rows();
+        match key {
+            KeyCode::Left => self.move_to(self.cursor.saturating_sub(1)),
+            KeyCode::Right => self.move_to(self.cursor + 1),
+            KeyCode::Up => self.move_to(self.cursor.saturating_sub(ROW_BYTES)),
+            KeyCode::Down if self.cursor + ROW_BYTES < self.len => {
+                self.move_to(self.cursor + ROW_BYTES)
+            }
+            KeyCode::PageUp => {
+                self.top = self.top.saturating_sub(rows * ROW_BYTES);
+                self.move_to(self.cursor.saturating_sub(rows * ROW_BYTES));
+            }
+            KeyCode::PageDown => {
+                let last_row = self.len.saturating_sub(1) / ROW_BYTES * ROW_BYTES;
+                self.top = (self.top + rows * ROW_BYTES).min(last_row);
+                self.move_to(self.cursor + rows * ROW_BYTES);
+            }
+            KeyCode::Home => self.move_to(0),
+            KeyCode::End => self.move_to(self.len),
+            KeyCode::Char(c) if self.editing && c.is_ascii_hexdigit() => {
+                self.type_nibble(c.to_digit(16).unwrap_or(0) as u8);
+            }
+            KeyCode::Backspace if self.editing => {
+                self.edits.remove(&self.cursor);
+                self.pending_nibble = None;
+            }
+            KeyCode::Char('w') if self.editable => self.save_with_status(),
+            KeyCode::Esc if self.editing => {
+                self.editing = false;
+                self.pending_nibble = None;
+            }
+            KeyCode::Char('g') => self.goto_input = Some(String::new()),
+            KeyCode::Char('i') if self.editable && self.len > 0 => self.editing = true,
+            KeyCode::Char('i') if !self.editable => {
+                self.status = Some("Editing is not available in this profile".to_string());
+            }
+            KeyCode::Esc | KeyCode::Char('q')
+                if !self.edits.is_empty() && !self.confirm_discard =>
+            {
+                self.confirm_discard = true;
+                self.status = Some(format!(
+                    "{} unsaved byte(s): w to save, Esc again to discard",
+                    self.edits.len()
+                ));
+            }
+            KeyCode::Esc | KeyCode::Char('q') => return false,
+            _ => {}
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Poll until the page on screen has been read
+    fn load(editor: &mut HexEditor) {
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+        editor.poll();
+        while editor.loading.is_some() {
+            assert!(std::time::Instant::now() < deadline);
+            std::thread::sleep(Duration::from_millis(5));
+            editor.poll();
+        }
+    }
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset("4096"), Some(4096));
+        assert_eq!(parse_offset("0x1F"), Some(31));
+        assert_eq!(parse_offset("zz"), None);
+    }
+
+    #[test]
+    fn test_edit_and_save() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("data.bin");
+        std::fs::write(&path, (0u8..40).collect::<Vec<_>>()).unwrap();
+
+        let mut editor = HexEditor::open(&path, true).unwrap();
+        load(&mut editor);
+        editor.handle_input(KeyCode::Char('g'));
+        for c in "0x11".chars() {
+            editor.handle_input(KeyCode::Char(c));
+        }
+        editor.handle_input(KeyCode::Enter);
+        assert_eq!(editor.cursor, 17);
+
+        // Hex digits only edit in edit mode
+        editor.handle_input(KeyCode::Char('a'));
+        assert!(editor.edits.is_empty());
+        editor.handle_input(KeyCode::Char('i'));
+        for c in "ffA".chars() {
+            editor.handle_input(KeyCode::Char(c));
+        }
+        assert_eq!(editor.cursor, 18);
+        // The high nibble alone already shows: 0x12 became 0xa2
+        assert_eq!(editor.shown_range(16, 3).unwrap(), vec![16, 0xff, 0xa2]);
+        editor.handle_input(KeyCode::Backspace);
+        assert_eq!(editor.edits.len(), 1);
+
+        // Closing with unsaved edits asks first
+        editor.handle_input(KeyCode::Esc);
+        assert!(!editor.editing);
+        assert!(editor.handle_input(KeyCode::Esc));
+        assert!(editor.confirm_discard);
+
+        editor.handle_input(KeyCode::Char('w'));
+        let saved = std::fs::read(&path).unwrap();
+        assert_eq!(saved.len(), 40);
+        assert_eq!(&saved[16..19], &[16, 0xff, 18]);
+        assert_eq!(editor.shown_range(16, 3).unwrap(), vec![16, 0xff, 18]);
+        assert!(!editor.handle_input(KeyCode::Esc));
+    }
+
+    #[test]
+    fn test_pages_are_read_as_the_view_moves() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("data.bin");
+        std::fs::write(&path, vec![7u8; 4096]).unwrap();
+
+        let mut editor = HexEditor::open(&path, false).unwrap();
+        assert!(editor.shown_range(0, 16).is_none());
+        load(&mut editor);
+        assert_eq!(editor.shown_range(0, 2).unwrap(), vec![7, 7]);
+
+        // Past the loaded page nothing is shown until it has been read
+        editor.handle_input(KeyCode::End);
+        assert!(editor.shown_range(editor.top, 16).is_none());
+        load(&mut editor);
+        assert_eq!(editor.shown_range(4095, 16).unwrap(), vec![7]);
+    }
+
+    #[test]
+    fn test_read_only_and_bounds() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("data.bin");
+        std::fs::write(&path, [1u8, 2, 3]).unwrap();
+
+        let mut editor = HexEditor::open(&path, false).unwrap();
+        editor.handle_input(KeyCode::Char('i'));
+        assert!(!editor.editing);
+        editor.handle_input(KeyCode::End);
+        assert_eq!(editor.cursor, 2);
+        editor.handle_input(KeyCode::Right);
+        assert_eq!(editor.cursor, 2);
+
+        editor.handle_input(KeyCode::Char('g'));
+        editor.handle_input(KeyCode::Char('9'));
+        editor.handle_input(KeyCode::Enter);
+        assert_eq!(editor.cursor, 2);
+        assert!(editor.status.as_deref().unwrap().contains("3 bytes"));
+    }
+}
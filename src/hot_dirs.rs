@@ -0,0 +1,292 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// How often the directories are rescanned where inotify is not available
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Hidden, or still being written: browsers rename a download once it is
+/// complete, and the rename is reported as a new file
+fn is_partial(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    name.starts_with('.')
+        || [".part", ".crdownload", ".download", ".tmp"]
+            .iter()
+            .any(|suffix| name.ends_with(suffix))
+}
+
+/// Thread reporting the entries that appear in a set of directories
+struct Watcher {
+    receiver: Receiver<PathBuf>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Watcher {
+    fn spawn(dirs: Vec<PathBuf>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let worker_cancel = Arc::clone(&cancel);
+
+        thread::spawn(move || {
+            #[cfg(target_os = "linux")]
+            if inotify::watch(&dirs, &sender, &worker_cancel).is_ok() {
+                return;
+            }
+            poll_dirs(&dirs, &sender, &worker_cancel);
+        });
+
+        Self { receiver, cancel }
+    }
+}
+
+/// Fallback for systems without inotify: compare the entries of each
+/// directory every [`POLL_INTERVAL`]
+fn poll_dirs(dirs: &[PathBuf], sender: &Sender<PathBuf>, cancel: &AtomicBool) {
+    let entries = |dir: &Path| -> HashSet<PathBuf> {
+        fs::read_dir(dir)
+            .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+            .unwrap_or_default()
+    };
+    let mut known: Vec<HashSet<PathBuf>> = dirs.iter().map(|dir| entries(dir)).collect();
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        if cancel.load(Ordering::Relaxed) {
+            return;
+        }
+        for (dir, known) in dirs.iter().zip(&mut known) {
+            let current = entries(dir);
+            for path in current.difference(known) {
+                if !is_partial(path) && sender.send(path.clone()).is_err() {
+                    return;
+                }
+            }
+            *known = current;
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod inotify {
+    use std::collections::HashMap;
+    use std::ffi::{CString, OsStr};
+    use std::io;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc::Sender;
+
+    /// Report entries created in or moved into `dirs` until `cancel` is
+    /// set; fails only when inotify cannot be set up at all
+    pub fn watch(
+        dirs: &[PathBuf],
+        sender: &Sender<PathBuf>,
+        cancel: &AtomicBool,
+    ) -> io::Result<()> {
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        // Directories that cannot be watched, such as missing ones, are skipped
+        let mut watches = HashMap::new();
+        for dir in dirs {
+            let Ok(path) = CString::new(dir.as_os_str().as_bytes()) else {
+                continue;
+            };
+            let wd = unsafe {
+                libc::inotify_add_watch(
+                    fd.as_raw_fd(),
+                    path.as_ptr(),
+                    libc::IN_CREATE | libc::IN_MOVED_TO,
+                )
+            };
+            if wd >= 0 {
+                watches.insert(wd, dir.clone());
+            }
+        }
+
+        let mut buffer = [0u8; 4096];
+        while !cancel.load(Ordering::Relaxed) {
+            let mut pollfd = libc::pollfd {
+                fd: fd.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            if unsafe { libc::poll(&mut pollfd, 1, 500) } <= 0 {
+                continue;
+            }
+            let read =
+                unsafe { libc::read(fd.as_raw_fd(), buffer.as_mut_ptr().cast(), buffer.len()) };
+            if read <= 0 {
+                continue;
+            }
+            for (wd, name) in events(&buffer[..read as usize]) {
+                let Some(dir) = watches.get(&wd) else {
+                    continue;
+                };
+                let path = dir.join(OsStr::from_bytes(name));
+                if !super::is_partial(&path) && sender.send(path).is_err() {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Watch descriptors and names of the events packed in one read
+    fn events(buffer: &[u8]) -> Vec<(i32, &[u8])> {
+        let header = std::mem::size_of::<libc::inotify_event>();
+        let field = |at: usize| u32::from_ne_bytes(buffer[at..at + 4].try_into().unwrap());
+        let mut events = Vec::new();
+        let mut offset = 0;
+        while offset + header <= buffer.len() {
+            let wd = field(offset) as i32;
+            let len = field(offset + 12) as usize;
+            let start = offset + header;
+            let name = &buffer[start..(start + len).min(buffer.len())];
+            // The name is padded with NULs to an aligned length
+            let name = name.split(|&byte| byte == 0).next().unwrap_or_default();
+            events.push((wd, name));
+            offset = start + len;
+        }
+        events
+    }
+}
+
+/// Bookmarked directories marked hot, watched in the background for new
+/// files until they are opened
+#[derive(Default)]
+pub struct HotDirs {
+    dirs: Vec<PathBuf>,
+    watcher: Option<Watcher>,
+    /// Files that appeared since their directory was last opened, oldest
+    /// first
+    arrivals: Vec<PathBuf>,
+}
+
+impl HotDirs {
+    /// Watch `dirs` from now on, restarting the watcher when they changed
+    pub fn watch(&mut self, dirs: Vec<PathBuf>) {
+        if dirs == self.dirs {
+            return;
+        }
+        self.arrivals.retain(|path| {
+            path.parent()
+                .is_some_and(|parent| dirs.iter().any(|dir| dir == parent))
+        });
+        self.watcher = (!dirs.is_empty()).then(|| Watcher::spawn(dirs.clone()));
+        self.dirs = dirs;
+    }
+
+    /// Files reported since the last call
+    pub fn poll(&mut self) -> Vec<PathBuf> {
+        let Some(ref watcher) = self.watcher else {
+            return Vec::new();
+        };
+        let arrived: Vec<PathBuf> = watcher.receiver.try_iter().collect();
+        for path in &arrived {
+            self.arrivals.retain(|known| known != path);
+            self.arrivals.push(path.clone());
+        }
+        arrived
+    }
+
+    /// New files not looked at yet
+    pub fn unseen(&self) -> usize {
+        self.arrivals.len()
+    }
+
+    /// `dir` was opened, so the files that appeared there have been seen
+    pub fn mark_seen(&mut self, dir: &Path) {
+        self.arrivals.retain(|path| path.parent() != Some(dir));
+    }
+
+    /// The latest arrival still on disk
+    pub fn newest(&self) -> Option<&Path> {
+        self.arrivals
+            .iter()
+            .rev()
+            .map(PathBuf::as_path)
+            .find(|path| path.exists())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    /// Poll until `count` arrivals are in, or give up after a few seconds
+    fn wait_for(hot: &mut HotDirs, count: usize) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while hot.unseen() < count && Instant::now() < deadline {
+            hot.poll();
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    #[test]
+    fn test_hot_dirs() {
+        let downloads = tempfile::tempdir().unwrap();
+        let inbox = tempfile::tempdir().unwrap();
+        let mut hot = HotDirs::default();
+        hot.watch(vec![
+            downloads.path().to_path_buf(),
+            inbox.path().to_path_buf(),
+        ]);
+        // Give the watcher time to take its baseline
+        thread::sleep(Duration::from_millis(100));
+
+        fs::write(downloads.path().join("report.pdf.part"), "").unwrap();
+        fs::write(downloads.path().join(".hidden"), "").unwrap();
+        fs::rename(
+            downloads.path().join("report.pdf.part"),
+            downloads.path().join("report.pdf"),
+        )
+        .unwrap();
+        wait_for(&mut hot, 1);
+        fs::write(inbox.path().join("upload.csv"), "").unwrap();
+        wait_for(&mut hot, 2);
+
+        assert_eq!(hot.unseen(), 2);
+        assert_eq!(
+            hot.newest(),
+            Some(inbox.path().join("upload.csv").as_path())
+        );
+
+        // Opening the inbox leaves the download as the newest file
+        hot.mark_seen(inbox.path());
+        assert_eq!(
+            hot.newest(),
+            Some(downloads.path().join("report.pdf").as_path())
+        );
+
+        // No longer hot: its arrivals are forgotten
+        hot.watch(vec![inbox.path().to_path_buf()]);
+        assert_eq!(hot.unseen(), 0);
+        assert_eq!(hot.newest(), None);
+    }
+
+    #[test]
+    fn test_is_partial() {
+        assert!(is_partial(Path::new("/dl/video.mp4.crdownload")));
+        assert!(is_partial(Path::new("/dl/.~lock.odt#")));
+        assert!(!is_partial(Path::new("/dl/video.mp4")));
+    }
+}
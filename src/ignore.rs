@@ -0,0 +1,219 @@
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::utils::path_glob_to_regex;
+
+/// One gitignore-style pattern
+#[derive(Debug, Clone)]
+struct Rule {
+    /// Directory the pattern is relative to; `None` for configured globs
+    /// without a `/`, which match a name anywhere
+    base: Option<PathBuf>,
+    regex: Regex,
+    /// Pattern contains a `/`, so it matches the path relative to `base`
+    anchored: bool,
+    /// Trailing `/`: only directories match
+    dir_only: bool,
+    /// Leading `!`: re-includes what an earlier pattern excluded
+    negated: bool,
+}
+
+impl Rule {
+    fn parse(line: &str, base: Option<&Path>) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (negated, pattern) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, pattern) = match pattern.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+        if pattern.is_empty() {
+            return None;
+        }
+
+        let anchored = pattern.contains('/');
+        let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+        // Anchored configured globs are relative to the search root, which
+        // callers pass as `base`
+        let base = base.map(Path::to_path_buf);
+        if anchored && base.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            base,
+            regex: Regex::new(&path_glob_to_regex(pattern)).ok()?,
+            anchored,
+            dir_only,
+            negated,
+        })
+    }
+
+    fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let relative = match &self.base {
+            Some(base) => match path.strip_prefix(base) {
+                Ok(relative) if !relative.as_os_str().is_empty() => relative,
+                _ => return false,
+            },
+            None => path,
+        };
+
+        if self.anchored {
+            self.regex.is_match(&relative.to_string_lossy())
+        } else {
+            relative
+                .file_name()
+                .is_some_and(|name| self.regex.is_match(&name.to_string_lossy()))
+        }
+    }
+}
+
+/// Paths left out of searches: configured globs plus, optionally, the
+/// rules of `.gitignore` files
+#[derive(Debug, Clone, Default)]
+pub struct ExcludeRules {
+    rules: Vec<Rule>,
+    gitignore: bool,
+}
+
+impl ExcludeRules {
+    /// Rules for a search rooted at `root`
+    ///
+    /// With `gitignore`, the `.gitignore` files from the enclosing
+    /// repository's top level down to `root` are read; walks pick up nested
+    /// ones through [`ExcludeRules::enter_dir`].
+    pub fn new(root: &Path, globs: &[String], gitignore: bool) -> Self {
+        let mut rules = Self {
+            rules: Vec::new(),
+            gitignore,
+        };
+        for glob in globs {
+            let base = glob.trim_end_matches('/').contains('/').then_some(root);
+            rules.rules.extend(Rule::parse(glob, base));
+        }
+
+        if gitignore {
+            let top = root
+                .ancestors()
+                .find(|dir| dir.join(".git").exists())
+                .unwrap_or(root);
+            let mut dirs: Vec<&Path> = root
+                .ancestors()
+                .take_while(|dir| dir.starts_with(top))
+                .collect();
+            dirs.reverse();
+            for dir in dirs {
+                rules.load_gitignore(dir);
+            }
+        }
+        rules
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty() && !self.gitignore
+    }
+
+    fn load_gitignore(&mut self, dir: &Path) {
+        if let Ok(content) = fs::read_to_string(dir.join(".gitignore")) {
+            self.rules.extend(
+                content
+                    .lines()
+                    .filter_map(|line| Rule::parse(line, Some(dir))),
+            );
+        }
+    }
+
+    /// Pick up the `.gitignore` of a directory a walk descends into
+    pub fn enter_dir(&mut self, dir: &Path) {
+        if self.gitignore {
+            self.load_gitignore(dir);
+        }
+    }
+
+    /// Whether `path` itself is excluded; the last matching rule wins
+    pub fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(path, is_dir))
+            .is_some_and(|rule| !rule.negated)
+    }
+
+    /// Whether `path` or any directory above it is excluded, for paths that
+    /// did not come from a walk honoring the rules
+    pub fn is_excluded_path(&self, path: &Path, is_dir: bool) -> bool {
+        path.ancestors()
+            .skip(1)
+            .filter(|dir| dir.parent().is_some())
+            .any(|dir| self.is_excluded(dir, true))
+            || self.is_excluded(path, is_dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn globs(patterns: &[&str]) -> Vec<String> {
+        patterns.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_configured_globs() {
+        let root = Path::new("/srv/app");
+        let rules = ExcludeRules::new(root, &globs(&["target/", "*.min.js", "docs/api"]), false);
+
+        assert!(rules.is_excluded(Path::new("/srv/app/target"), true));
+        assert!(!rules.is_excluded(Path::new("/srv/app/target"), false));
+        assert!(rules.is_excluded(Path::new("/srv/app/web/app.min.js"), false));
+        assert!(rules.is_excluded(Path::new("/srv/app/docs/api"), true));
+        assert!(!rules.is_excluded(Path::new("/srv/app/web/docs/api"), true));
+
+        // Anything below an excluded directory, anywhere on the machine
+        assert!(rules.is_excluded_path(Path::new("/home/u/crate/target/debug/app"), false));
+        assert!(!rules.is_excluded_path(Path::new("/home/u/crate/src/main.rs"), false));
+    }
+
+    #[test]
+    fn test_gitignore_rules() {
+        let repo = TempDir::new().unwrap();
+        let root = repo.path();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join("web/dist")).unwrap();
+        fs::write(
+            root.join(".gitignore"),
+            "# build output\n*.log\n!keep.log\n/dist/\n",
+        )
+        .unwrap();
+        fs::write(root.join("web/.gitignore"), "dist/\n").unwrap();
+
+        // Rooted below the repository top, the top-level file still applies
+        let rules = ExcludeRules::new(&root.join("web"), &[], true);
+        assert!(rules.is_excluded(&root.join("web/debug.log"), false));
+        assert!(!rules.is_excluded(&root.join("web/keep.log"), false));
+        assert!(rules.is_excluded(&root.join("web/dist"), true));
+
+        // `/dist/` is anchored to the top level; nested files load on entry
+        let mut top = ExcludeRules::new(root, &[], true);
+        assert!(top.is_excluded(&root.join("dist"), true));
+        assert!(!top.is_excluded(&root.join("web/dist"), true));
+        top.enter_dir(&root.join("web"));
+        assert!(top.is_excluded(&root.join("web/dist"), true));
+        assert!(!top.is_excluded(&root.join("web/main.rs"), false));
+
+        let without = ExcludeRules::new(&root.join("web"), &[], false);
+        assert!(!without.is_excluded(&root.join("web/debug.log"), false));
+        assert!(without.is_empty());
+    }
+}
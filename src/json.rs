@@ -0,0 +1,247 @@
+use crossterm::style::Color;
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// Spaces per nesting level of the pretty-printed document
+const INDENT: usize = 2;
+
+/// A pretty-printed line: colored segments, and the object or array it
+/// opens or closes, if any
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonLine {
+    pub segments: Vec<(Color, String)>,
+    pub node: Option<usize>,
+}
+
+impl JsonLine {
+    pub fn text(&self) -> String {
+        self.segments
+            .iter()
+            .map(|(_, text)| text.as_str())
+            .collect()
+    }
+}
+
+/// Where and why a document failed to parse
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub message: String,
+    /// 1-based line and column of the error
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A parsed JSON document, pretty-printed with some of its objects and
+/// arrays folded to one line
+#[derive(Debug, Clone)]
+pub struct JsonDocument {
+    value: Value,
+    /// Objects and arrays shown folded, numbered in document order
+    folded: HashSet<usize>,
+    max_lines: usize,
+    pub lines: Vec<JsonLine>,
+}
+
+impl JsonDocument {
+    pub fn parse(source: &str, max_lines: usize) -> Result<Self, ParseError> {
+        let value: Value = serde_json::from_str(source).map_err(|e| ParseError {
+            message: e.to_string(),
+            line: e.line(),
+            column: e.column(),
+        })?;
+        let mut document = Self {
+            value,
+            folded: HashSet::new(),
+            max_lines,
+            lines: Vec::new(),
+        };
+        document.layout();
+        Ok(document)
+    }
+
+    /// Fold or unfold the object or array opened or closed on `line`, and
+    /// return the line where it now starts
+    pub fn toggle_fold(&mut self, line: usize) -> Option<usize> {
+        let node = self.lines.get(line)?.node?;
+        if !self.folded.remove(&node) {
+            self.folded.insert(node);
+        }
+        self.layout();
+        self.lines.iter().position(|l| l.node == Some(node))
+    }
+
+    fn layout(&mut self) {
+        let mut lines = Vec::new();
+        let mut next_node = 0;
+        self.push_value(&self.value, None, 0, false, &mut next_node, &mut lines);
+        if lines.len() > self.max_lines {
+            lines.truncate(self.max_lines);
+            lines.push(JsonLine {
+                segments: vec![(Color::DarkGrey, "… (fold to see more)".to_string())],
+                node: None,
+            });
+        }
+        self.lines = lines;
+    }
+
+    fn push_value(
+        &self,
+        value: &Value,
+        key: Option<&str>,
+        depth: usize,
+        comma: bool,
+        next_node: &mut usize,
+        lines: &mut Vec<JsonLine>,
+    ) {
+        // Deeply nested content is laid out anyway, but not kept past the limit
+        if lines.len() > self.max_lines {
+            *next_node += containers(value);
+            return;
+        }
+
+        let indent = " ".repeat(depth * INDENT);
+        let mut segments = vec![(Color::Reset, indent.clone())];
+        if let Some(key) = key {
+            segments.push((Color::Cyan, quote(key)));
+            segments.push((Color::Reset, ": ".to_string()));
+        }
+        let trailer = if comma { "," } else { "" };
+
+        let children: Vec<(Option<&str>, &Value)> = match value {
+            Value::Object(map) => map.iter().map(|(k, v)| (Some(k.as_str()), v)).collect(),
+            Value::Array(items) => items.iter().map(|v| (None, v)).collect(),
+            scalar => {
+                let color = match scalar {
+                    Value::String(_) => Color::Green,
+                    Value::Number(_) => Color::Yellow,
+                    _ => Color::Magenta,
+                };
+                segments.push((color, scalar.to_string()));
+                segments.push((Color::Reset, trailer.to_string()));
+                lines.push(JsonLine {
+                    segments,
+                    node: None,
+                });
+                return;
+            }
+        };
+        let (open, close, noun) = match value {
+            Value::Object(_) => ("{", "}", "key"),
+            _ => ("[", "]", "item"),
+        };
+        if children.is_empty() {
+            segments.push((Color::Reset, format!("{}{}{}", open, close, trailer)));
+            lines.push(JsonLine {
+                segments,
+                node: None,
+            });
+            return;
+        }
+
+        let node = *next_node;
+        *next_node += 1;
+        if self.folded.contains(&node) {
+            *next_node += containers(value) - 1;
+            segments.push((Color::Reset, format!("{}…{}{}", open, close, trailer)));
+            segments.push((
+                Color::DarkGrey,
+                format!(
+                    "  {} {}{}",
+                    children.len(),
+                    noun,
+                    if children.len() == 1 { "" } else { "s" }
+                ),
+            ));
+            lines.push(JsonLine {
+                segments,
+                node: Some(node),
+            });
+            return;
+        }
+
+        segments.push((Color::Reset, open.to_string()));
+        lines.push(JsonLine {
+            segments,
+            node: Some(node),
+        });
+        let last = children.len() - 1;
+        for (i, (key, child)) in children.into_iter().enumerate() {
+            self.push_value(child, key, depth + 1, i < last, next_node, lines);
+        }
+        lines.push(JsonLine {
+            segments: vec![(Color::Reset, format!("{}{}{}", indent, close, trailer))],
+            node: Some(node),
+        });
+    }
+}
+
+/// Non-empty objects and arrays in `value`, itself included
+fn containers(value: &Value) -> usize {
+    match value {
+        Value::Object(map) if !map.is_empty() => 1 + map.values().map(containers).sum::<usize>(),
+        Value::Array(items) if !items.is_empty() => 1 + items.iter().map(containers).sum::<usize>(),
+        _ => 0,
+    }
+}
+
+fn quote(key: &str) -> String {
+    Value::String(key.to_string()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(document: &JsonDocument) -> Vec<String> {
+        document.lines.iter().map(JsonLine::text).collect()
+    }
+
+    #[test]
+    fn test_pretty_print_and_fold() {
+        let mut document = JsonDocument::parse(
+            r#"{"name":"fsnav","tags":["cli","tui"],"deps":{"serde":{"v":1}},"empty":[],"ok":true}"#,
+            100,
+        )
+        .unwrap();
+        assert_eq!(
+            text(&document),
+            vec![
+                "{",
+                "  \"name\": \"fsnav\",",
+                "  \"tags\": [",
+                "    \"cli\",",
+                "    \"tui\"",
+                "  ],",
+                "  \"deps\": {",
+                "    \"serde\": {",
+                "      \"v\": 1",
+                "    }",
+                "  },",
+                "  \"empty\": [],",
+                "  \"ok\": true",
+                "}",
+            ]
+        );
+        assert_eq!(
+            document.lines[1].segments[1],
+            (Color::Cyan, "\"name\"".to_string())
+        );
+
+        // Folding from the closing line of `tags` lands on its opening line
+        assert_eq!(document.toggle_fold(5), Some(2));
+        assert_eq!(document.lines[2].text(), "  \"tags\": […],  2 items");
+        // Later containers keep their numbers while an earlier one is folded
+        assert_eq!(document.toggle_fold(4), Some(4));
+        assert_eq!(document.lines[4].text(), "    \"serde\": {…}  1 key");
+        assert_eq!(document.toggle_fold(2), Some(2));
+        assert_eq!(document.lines.len(), 12);
+        assert_eq!(document.toggle_fold(1), None);
+    }
+
+    #[test]
+    fn test_parse_error_location() {
+        let error = JsonDocument::parse("{\n  \"a\": 1\n  \"b\": 2\n}", 100).unwrap_err();
+        assert_eq!((error.line, error.column), (3, 3));
+        assert!(error.message.contains("expected `,` or `}`"));
+    }
+}
@@ -0,0 +1,420 @@
+use std::path::Path;
+
+use base64::Engine;
+
+/// Headers summarized above a message body, in display order
+const SUMMARY_HEADERS: &[&str] = &["From", "To", "Cc", "Subject", "Date"];
+
+/// Nested multipart bodies followed before giving up on finding text
+const MAX_PART_DEPTH: usize = 4;
+
+pub fn is_eml_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("eml"))
+}
+
+/// Whether a file starting with `head` is an mbox spool: messages each
+/// introduced by a `From sender date` line followed by headers
+pub fn is_mbox(path: &Path, head: &str) -> bool {
+    let by_extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("mbox"));
+    let mut lines = head.lines();
+    let separator = lines.next().is_some_and(|line| line.starts_with("From "));
+    let header = lines.next().is_some_and(|line| {
+        line.split_once(':')
+            .is_some_and(|(name, _)| !name.is_empty() && !name.contains(char::is_whitespace))
+    });
+    by_extension || (separator && header)
+}
+
+/// A message with its headers unfolded and decoded, and its text body
+#[derive(Debug, Clone, PartialEq)]
+pub struct MailMessage {
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<String>,
+    /// 1-based line of the file where the message starts
+    pub start_line: usize,
+}
+
+impl MailMessage {
+    pub fn parse(raw: &str) -> Self {
+        let (headers, body) = split_headers(raw);
+        let headers: Vec<(String, String)> = headers
+            .into_iter()
+            .map(|(name, value)| (name, decode_encoded_words(&value)))
+            .collect();
+        let body = text_body(&headers, body, 0)
+            .lines()
+            .map(|line| line.replace('\t', "    "))
+            .collect();
+
+        Self {
+            headers,
+            body,
+            start_line: 1,
+        }
+    }
+
+    /// First value of a header, matched case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// `Name: value` lines for the headers worth showing above the body
+    pub fn summary_lines(&self) -> Vec<String> {
+        SUMMARY_HEADERS
+            .iter()
+            .filter_map(|name| {
+                self.header(name)
+                    .map(|value| format!("{}: {}", name, value))
+            })
+            .collect()
+    }
+}
+
+/// The messages of an `.eml` file or mbox spool, one shown at a time
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mailbox {
+    pub messages: Vec<MailMessage>,
+    pub current: usize,
+}
+
+impl Mailbox {
+    pub fn current_message(&self) -> &MailMessage {
+        &self.messages[self.current]
+    }
+
+    /// Headers of the current message, preceded by its position in a spool
+    pub fn summary_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if self.messages.len() > 1 {
+            lines.push(format!(
+                "Message {} of {}",
+                self.current + 1,
+                self.messages.len()
+            ));
+        }
+        lines.extend(self.current_message().summary_lines());
+        lines
+    }
+}
+
+/// Split an mbox spool into its messages
+pub fn parse_mbox(content: &str) -> Vec<MailMessage> {
+    let mut messages = Vec::new();
+    let mut current: Option<(usize, String)> = None;
+    let mut previous_blank = true;
+
+    for (index, line) in content.lines().enumerate() {
+        // A separator only counts at the start of the file or after a blank line
+        if previous_blank && line.starts_with("From ") {
+            if let Some((start, raw)) = current.take() {
+                messages.push(mbox_message(start, &raw));
+            }
+            current = Some((index + 1, String::new()));
+        } else if let Some((_, ref mut raw)) = current {
+            // Body lines starting with `From ` are stored as `>From `
+            let line = match line.strip_prefix('>') {
+                Some(rest) if rest.trim_start_matches('>').starts_with("From ") => rest,
+                _ => line,
+            };
+            raw.push_str(line);
+            raw.push('\n');
+        }
+        previous_blank = line.trim().is_empty();
+    }
+    if let Some((start, raw)) = current {
+        messages.push(mbox_message(start, &raw));
+    }
+    messages
+}
+
+fn mbox_message(start_line: usize, raw: &str) -> MailMessage {
+    MailMessage {
+        start_line,
+        ..MailMessage::parse(raw)
+    }
+}
+
+/// Unfolded headers and the remaining body of a message or MIME part
+fn split_headers(raw: &str) -> (Vec<(String, String)>, &str) {
+    let mut headers: Vec<(String, String)> = Vec::new();
+    let mut rest = raw;
+
+    while !rest.is_empty() {
+        let (line, next) = match rest.find('\n') {
+            Some(i) => (&rest[..i], &rest[i + 1..]),
+            None => (rest, ""),
+        };
+        let line = line.trim_end_matches('\r');
+        if line.is_empty() {
+            return (headers, next);
+        }
+        if line.starts_with([' ', '\t']) {
+            // Folded continuation of the previous header
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        } else {
+            // Not a header block at all: everything is body
+            return (Vec::new(), raw);
+        }
+        rest = next;
+    }
+    (headers, rest)
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
+}
+
+/// `Content-Type` value without parameters, lowercased
+fn content_type(headers: &[(String, String)]) -> String {
+    header(headers, "Content-Type")
+        .and_then(|v| v.split(';').next())
+        .unwrap_or("text/plain")
+        .trim()
+        .to_lowercase()
+}
+
+/// Value of a `; name=value` parameter of a header
+fn parameter(value: &str, name: &str) -> Option<String> {
+    value.split(';').skip(1).find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+/// Readable text of a message: the first `text/plain` part of a multipart
+/// message (`text/html` with its tags stripped otherwise), decoded
+fn text_body(headers: &[(String, String)], body: &str, depth: usize) -> String {
+    let kind = content_type(headers);
+    if kind.starts_with("multipart/") {
+        let boundary = header(headers, "Content-Type").and_then(|v| parameter(v, "boundary"));
+        let parts = match boundary {
+            Some(ref boundary) if depth < MAX_PART_DEPTH => split_parts(body, boundary),
+            _ => return body.to_string(),
+        };
+        let parts: Vec<(Vec<(String, String)>, &str)> =
+            parts.into_iter().map(split_headers).collect();
+        let preferred = ["text/plain", "multipart/", "text/html"];
+        return preferred
+            .iter()
+            .find_map(|wanted| {
+                parts
+                    .iter()
+                    .find(|(h, _)| content_type(h).starts_with(wanted))
+            })
+            .map(|(h, b)| text_body(h, b, depth + 1))
+            .unwrap_or_else(|| "(no text part)".to_string());
+    }
+    if !kind.starts_with("text/") {
+        return format!("({} attachment)", kind);
+    }
+
+    let encoding = header(headers, "Content-Transfer-Encoding")
+        .unwrap_or("7bit")
+        .to_lowercase();
+    let text = match encoding.trim() {
+        "base64" => {
+            let compact: String = body.chars().filter(|c| !c.is_whitespace()).collect();
+            base64::engine::general_purpose::STANDARD
+                .decode(compact)
+                .map(|bytes| String::from_utf8_lossy(&bytes).to_string())
+                .unwrap_or_else(|_| body.to_string())
+        }
+        "quoted-printable" => decode_quoted_printable(body),
+        _ => body.to_string(),
+    };
+    if kind == "text/html" {
+        strip_tags(&text)
+    } else {
+        text
+    }
+}
+
+/// Bodies of the parts of a multipart body delimited by `--boundary`
+fn split_parts<'a>(body: &'a str, boundary: &str) -> Vec<&'a str> {
+    let delimiter = format!("--{}", boundary);
+    let mut parts = Vec::new();
+    for chunk in body.split(delimiter.as_str()).skip(1) {
+        // The closing delimiter is `--boundary--`
+        if chunk.starts_with("--") {
+            break;
+        }
+        let chunk = chunk.trim_start_matches(['\r', ' ', '\t']);
+        parts.push(chunk.strip_prefix('\n').unwrap_or(chunk));
+    }
+    parts
+}
+
+fn decode_quoted_printable(text: &str) -> String {
+    let mut bytes = Vec::with_capacity(text.len());
+    for line in text.lines() {
+        let line = line.trim_end_matches('\r');
+        // A trailing `=` is a soft line break
+        let (line, soft_break) = match line.strip_suffix('=') {
+            Some(line) => (line, true),
+            None => (line, false),
+        };
+        let raw = line.as_bytes();
+        let mut i = 0;
+        while i < raw.len() {
+            let hex = raw
+                .get(i + 1..i + 3)
+                .and_then(|h| std::str::from_utf8(h).ok())
+                .and_then(|h| u8::from_str_radix(h, 16).ok());
+            match (raw[i], hex) {
+                (b'=', Some(byte)) => {
+                    bytes.push(byte);
+                    i += 3;
+                }
+                (byte, _) => {
+                    bytes.push(byte);
+                    i += 1;
+                }
+            }
+        }
+        if !soft_break {
+            bytes.push(b'\n');
+        }
+    }
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+/// Decode RFC 2047 `=?charset?B|Q?text?=` words; charsets other than
+/// UTF-8 are read as if they were
+fn decode_encoded_words(value: &str) -> String {
+    let mut result = String::new();
+    let mut rest = value;
+    let mut after_word = false;
+
+    while let Some(start) = rest.find("=?") {
+        let decoded = rest[start + 2..].splitn(3, '?').collect::<Vec<_>>();
+        let word = match decoded.as_slice() {
+            [_, encoding, tail] => tail.find("?=").map(|end| (encoding, &tail[..end], end)),
+            _ => None,
+        };
+        let Some((encoding, text, end)) = word else {
+            break;
+        };
+        let bytes = match encoding.to_ascii_uppercase().as_str() {
+            "B" => base64::engine::general_purpose::STANDARD.decode(text).ok(),
+            "Q" => Some(decode_quoted_printable(&text.replace('_', " ")).into_bytes()),
+            _ => None,
+        };
+        let Some(bytes) = bytes else {
+            break;
+        };
+
+        // Whitespace between two encoded words is dropped
+        let between = &rest[..start];
+        if !(after_word && between.trim().is_empty()) {
+            result.push_str(between);
+        }
+        result.push_str(String::from_utf8_lossy(&bytes).trim_end_matches('\n'));
+        let consumed = start + 2 + decoded[0].len() + 1 + encoding.len() + 1 + end + 2;
+        rest = &rest[consumed..];
+        after_word = true;
+    }
+    result.push_str(rest);
+    result
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_multipart_message() {
+        let message = MailMessage::parse(
+            "From: Alice <alice@example.com>\r\n\
+             To: ops@example.com\r\n\
+             Subject: =?UTF-8?B?RGlzayBhbG1vc3Q=?=\r\n \
+             =?UTF-8?Q?_full_=E2=9A=A0?=\r\n\
+             Date: Mon, 5 Oct 2026 09:00:00 +0000\r\n\
+             Content-Type: multipart/alternative; boundary=\"b1\"\r\n\
+             \r\n\
+             --b1\r\n\
+             Content-Type: text/html\r\n\
+             \r\n\
+             <p>HTML version</p>\r\n\
+             --b1\r\n\
+             Content-Type: text/plain; charset=utf-8\r\n\
+             Content-Transfer-Encoding: quoted-printable\r\n\
+             \r\n\
+             /var is at 97% =3D nearly=\r\n\
+             \x20full\r\n\
+             --b1--\r\n",
+        );
+        assert_eq!(
+            message.summary_lines(),
+            vec![
+                "From: Alice <alice@example.com>",
+                "To: ops@example.com",
+                "Subject: Disk almost full ⚠",
+                "Date: Mon, 5 Oct 2026 09:00:00 +0000",
+            ]
+        );
+        assert_eq!(message.body, vec!["/var is at 97% = nearly full"]);
+    }
+
+    #[test]
+    fn test_parse_mbox() {
+        let messages = parse_mbox(
+            "From alice@example.com Mon Oct  5 09:00:00 2026\n\
+             From: alice@example.com\n\
+             Subject: First\n\
+             \n\
+             Hello\n\
+             >From the archive\n\
+             \n\
+             From bob@example.com Mon Oct  5 10:00:00 2026\n\
+             From: bob@example.com\n\
+             Subject: Second\n\
+             \n\
+             SGk=\n",
+        );
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].header("subject"), Some("First"));
+        assert_eq!(messages[0].body, vec!["Hello", "From the archive", ""]);
+        assert_eq!(messages[1].header("Subject"), Some("Second"));
+        assert_eq!(messages[1].start_line, 8);
+        assert!(is_mbox(
+            Path::new("/var/mail/root"),
+            "From root Mon Oct  5 09:00:00 2026\nReturn-Path: <root@host>\n"
+        ));
+        assert!(!is_mbox(Path::new("notes.txt"), "From here on:\nwe agree"));
+    }
+}
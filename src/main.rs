@@ -1,36 +1,73 @@
 use anyhow::{Context, Result};
 use crossterm::{
     cursor::{Hide, Show},
+    event::DisableMouseCapture,
     execute,
     terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use std::{env, io, process::Command};
 
 // Core modules
+mod actions;
+mod archive;
+mod checksums;
+mod config;
+mod containers;
+mod custom_commands;
+mod diff;
+mod dir_settings;
+mod dir_size;
+mod dir_stats;
+mod doctor;
+mod extract;
+mod finder;
+mod graphics;
+mod help;
+mod hex_editor;
+mod hot_dirs;
+mod ignore;
+mod json;
+mod mail;
 mod managers;
+mod markdown;
+mod media;
 mod models;
+mod monitor;
 mod navigator;
 mod ui;
 mod utils;
 
 // v0.4.0 Enhanced Navigation modules
 mod bookmarks;
+mod open_with;
+mod pager;
+mod palette;
+mod photo;
 mod preview;
+mod quick_view;
 mod search;
+mod sessions;
+mod snapshot;
 mod split_pane;
+mod systemd;
+mod tasks;
+mod workflows;
 
 use models::ExitAction;
 use navigator::Navigator;
 
-fn run_app() -> Result<ExitAction> {
+fn run_app(session: Option<sessions::Session>) -> Result<ExitAction> {
     terminal::enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, Hide)?;
 
     let mut nav = Navigator::new()?;
+    if let Some(session) = session {
+        nav.restore_session(session);
+    }
     let exit_action = nav.run()?;
 
-    execute!(stdout, LeaveAlternateScreen, Show)?;
+    execute!(stdout, DisableMouseCapture, LeaveAlternateScreen, Show)?;
     terminal::disable_raw_mode()?;
 
     Ok(exit_action)
@@ -61,36 +98,86 @@ fn print_version() {
     println!("  • Search with Ctrl+F (regex support)");
     println!("  • File preview panel with Ctrl+P");
     println!("  • Bookmarks system with Ctrl+B");
-    println!("  • Split-pane view with F2");
+    println!("  • Split-pane view with F2, up to four panes in columns, rows or a grid");
+    println!("    (p previews the active pane's entry in place of the next pane)");
     println!("\nFor more information, visit: https://github.com/AlexArtaud-Dev/fsnav");
 }
 
 fn print_help() {
     println!("Usage: fsnav [OPTIONS] [PATH]");
+    println!("       fsnav doctor");
+    println!("       fsnav --list-bookmarks | --export-bookmarks FILE | --import-bookmarks FILE");
     println!("\nOptions:");
     println!("  -h, --help     Show this help message");
     println!("  -v, --version  Show version information");
+    println!("  --session NAME Restore a session saved with F4");
+    println!("  --list-bookmarks          Print bookmarks as tab-separated lines: shortcut,");
+    println!("                            name, path, tags and user/shared");
+    println!("  --export-bookmarks FILE   Write your bookmarks as JSON (- for stdout)");
+    println!("  --import-bookmarks FILE   Merge bookmarks from an export (- for stdin);");
+    println!("                            paths already bookmarked are skipped");
     println!("  PATH           Start in the specified directory");
-    println!("\nKeyboard Shortcuts:");
+    println!("  doctor         Report terminal capabilities, config problems and the");
+    println!("                 external tools optional features need (./doctor for a");
+    println!("                 directory of that name)");
+    println!("\nKeyboard Shortcuts (press F1 inside fsnav for the current mode):");
     println!("\nNavigation:");
     println!("  ↑/↓           Navigate up/down");
     println!("  →/Enter       Enter directory");
+    println!("  Enter         Open file in $VISUAL/$EDITOR (media/documents: default app)");
+    println!("  Space         Quick view (any key closes)");
+    println!("  i/F3          Full-screen pager with search");
+    println!("  D             Diff the two selected files (d in split panes)");
+    println!("  /             Filter the listing as you type (also any unbound letter)");
+    println!("  x             Open with the system default application");
+    println!("  X             Open with… (choose an application)");
     println!("  ←/Backspace   Go to parent directory");
+    println!("  Ctrl+S        Cycle sort order (name, size, modified)");
+    println!("  Ctrl+O        Cycle grouping (dirs first, mixed, files first)");
+    println!("  .             Show/hide hidden files");
+    println!("  R             Go to the real path when symlinks led here (header shows both)");
+    println!("  N             Jump to the newest file in a hot directory (Ctrl+W in bookmarks)");
+    println!("  V             Verify the checksum files (.sha256, .md5, SHA256SUMS) here");
+    println!("  t             Toggle relative/absolute dates");
+    println!("  P             Pin/unpin entry at the top of its directory");
+    println!("  M             Monitor the directory for changes");
+    println!("  H             Hex view/editor of the highlighted file");
+    println!("  E             Filter listing to the highlighted entry's extension");
+    println!("  I             Directory statistics by extension");
+    println!("  Z             Extract the highlighted tarball into a new directory");
+    println!("  U             Show systemctl status of the highlighted unit file");
+    println!("  :             Command palette (also Ctrl+Shift+P)");
     println!("  S/Ctrl+D      Spawn shell in current directory");
-    println!("  Esc/q         Quit");
+    println!("  Esc/q         Quit (again with `confirm_quit` set)");
     println!("\nSearch & Preview:");
     println!("  Ctrl+F        Search files (supports regex)");
+    println!("  Ctrl+T        Fuzzy finder (Ctrl+R inside: recursive)");
     println!("  Ctrl+N/P      Next/Previous search result");
+    println!("  Ctrl+A        Content search inside tar/tar.gz archives (in search)");
+    println!("  Ctrl+L        Machine-wide search via plocate/locate (in search)");
+    println!("  Ctrl+S        Cycle search scope (in search)");
+    println!("  Ctrl+E        Toggle search exclusions (in search/finder)");
     println!("  Ctrl+P        Toggle preview panel");
+    println!("  /             Search the previewed text (preview focused)");
+    println!("  n/N           Next/Previous match in the preview (after /)");
+    println!("  n/p           Next/Previous mailbox message (preview focused)");
+    println!("  r             Rendered/raw Markdown (preview focused)");
+    println!("  Enter         Fold/unfold JSON object or array (preview focused)");
+    println!("  l/w           Line numbers/Soft wrap (preview focused)");
+    println!("  g             Go to an offset in a binary file (preview focused)");
     println!("  F2            Split-pane view");
+    println!("  O             Split view with the highlighted directory on the right");
+    println!("  F4            Save or restore a named session (also in split panes)");
     println!("\nBookmarks:");
     println!("  Ctrl+B        Open bookmarks");
     println!("  Ctrl+G        Quick jump to bookmark");
     println!("\nRoot Mode (when running as root):");
     println!("  s             Selection mode");
     println!("  p             Pattern selection");
-    println!("  c             Chmod interface");
-    println!("  o             Chown interface");
+    println!("  c             Chmod interface (your own files for other users)");
+    println!("  o             Chown interface (a simulation for other users)");
+    println!("  w             Start/stop recording a workflow");
+    println!("  W             Replay a saved workflow, confirming each step");
 }
 
 #[cfg(windows)]
@@ -104,6 +191,7 @@ fn main() -> Result<()> {
     let args: Vec<String> = env::args().collect();
 
     // Parse command line arguments
+    let mut session = None;
     if args.len() > 1 {
         match args[1].as_str() {
             "-h" | "--help" => {
@@ -114,11 +202,48 @@ fn main() -> Result<()> {
                 print_version();
                 return Ok(());
             }
+            "doctor" => std::process::exit(doctor::run()),
+            "--session" => {
+                let Some(name) = args.get(2) else {
+                    eprintln!("Error: --session needs a session name");
+                    std::process::exit(1);
+                };
+                let saved = sessions::Sessions::load();
+                match saved.get(name) {
+                    Some(found) => session = Some(found.clone()),
+                    None => {
+                        eprintln!("Error: no session named '{}'", name);
+                        if !saved.names().is_empty() {
+                            eprintln!("Saved sessions: {}", saved.names().join(", "));
+                        }
+                        std::process::exit(1);
+                    }
+                }
+            }
+            flag @ ("--list-bookmarks" | "--export-bookmarks" | "--import-bookmarks") => {
+                std::process::exit(bookmarks::run_cli(flag, args.get(2).map(String::as_str)))
+            }
             path => {
                 // Try to start in the specified directory
                 let target_path = std::path::Path::new(path);
                 if target_path.exists() && target_path.is_dir() {
+                    // Keep the path as typed, links included, for the header
+                    let logical = if target_path.is_absolute() {
+                        Some(target_path.to_path_buf())
+                    } else {
+                        utils::logical_current_dir()
+                            .ok()
+                            .map(|dir| dir.join(target_path))
+                    }
+                    .filter(|path| {
+                        !path
+                            .components()
+                            .any(|c| c == std::path::Component::ParentDir)
+                    });
                     env::set_current_dir(target_path)?;
+                    if let Some(logical) = logical {
+                        env::set_var("PWD", logical);
+                    }
                 } else {
                     eprintln!("Error: '{}' is not a valid directory", path);
                     std::process::exit(1);
@@ -127,10 +252,11 @@ fn main() -> Result<()> {
         }
     }
 
-    let result = run_app();
+    utils::init_locale();
+    let result = run_app(session);
 
     let mut stdout = io::stdout();
-    let _ = execute!(stdout, LeaveAlternateScreen, Show);
+    let _ = execute!(stdout, DisableMouseCapture, LeaveAlternateScreen, Show);
     let _ = terminal::disable_raw_mode();
 
     match result {
@@ -147,6 +273,7 @@ fn main() -> Result<()> {
 #[cfg(test)]
 mod tests {
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn test_basic() {
         assert!(true);
     }
@@ -0,0 +1,142 @@
+use anyhow::Result;
+use crossterm::{
+    cursor::MoveTo,
+    execute,
+    style::{Color, Print, ResetColor},
+};
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use crate::ui::SetForegroundColor;
+use crate::utils::truncate_chars;
+
+/// A path chmod or chown was refused on, and why
+pub type Failure = (PathBuf, io::Error);
+
+/// The effective user id the managers act as
+pub fn euid() -> u32 {
+    unsafe { libc::geteuid() }
+}
+
+/// Whether chmod(2) would be allowed on `path`: root may change any file,
+/// everyone else only the files they own
+pub fn can_chmod(path: &Path, uid: u32) -> bool {
+    uid == 0 || path.metadata().is_ok_and(|metadata| metadata.uid() == uid)
+}
+
+/// (uid, gid) of the file chown(2) would change at `path`, or with
+/// `follow` off of a symlink there itself, as lchown(2) sees it
+pub fn ownership(path: &Path, follow: bool) -> io::Result<(u32, u32)> {
+    let metadata = if follow {
+        path.metadata()?
+    } else {
+        path.symlink_metadata()?
+    };
+    Ok((metadata.uid(), metadata.gid()))
+}
+
+/// "a.txt: Operation not permitted (os error 1)" for the first failure, and
+/// how many more there were
+pub fn summarize(failures: &[Failure]) -> Option<String> {
+    let (path, error) = failures.first()?;
+    Some(match failures.len() {
+        1 => format!("{}: {}", path.display(), error),
+        count => format!("{}: {} (and {} more)", path.display(), error, count - 1),
+    })
+}
+
+/// Every refused path with its error, from `y` down over at most `rows`
+/// rows
+pub fn render_failures(
+    stdout: &mut io::Stdout,
+    y: u16,
+    width: u16,
+    rows: u16,
+    failures: &[Failure],
+) -> Result<()> {
+    let width = width.saturating_sub(4) as usize;
+    execute!(
+        stdout,
+        MoveTo(2, y),
+        SetForegroundColor(Color::Red),
+        Print(truncate_chars(
+            &format!(
+                "✗ {} item(s) could not be changed; the rest were:",
+                failures.len()
+            ),
+            width
+        )),
+        ResetColor
+    )?;
+
+    let list_rows = rows.saturating_sub(2) as usize;
+    for (i, (path, error)) in failures.iter().take(list_rows).enumerate() {
+        execute!(
+            stdout,
+            MoveTo(4, y + 1 + i as u16),
+            SetForegroundColor(Color::Yellow),
+            Print(truncate_chars(
+                &format!("{}: {}", path.display(), error),
+                width.saturating_sub(2)
+            )),
+            ResetColor
+        )?;
+    }
+    if failures.len() > list_rows {
+        execute!(
+            stdout,
+            MoveTo(4, y + 1 + list_rows as u16),
+            SetForegroundColor(Color::DarkGrey),
+            Print(format!("... and {} more", failures.len() - list_rows)),
+            ResetColor
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// `count` groups other than `gid` that chown(2) lets the tests hand
+    /// files to: any as root, otherwise the caller's supplementary groups.
+    /// `None` when the caller belongs to too few, so the test is skipped.
+    pub(crate) fn other_groups(gid: u32, count: usize) -> Option<Vec<u32>> {
+        if euid() == 0 {
+            return Some((1..=count as u32).map(|n| gid + n).collect());
+        }
+        let size = unsafe { libc::getgroups(0, std::ptr::null_mut()) };
+        let mut groups = vec![0 as libc::gid_t; size.max(0) as usize];
+        let size = unsafe { libc::getgroups(size, groups.as_mut_ptr()) };
+        groups.truncate(size.max(0) as usize);
+        groups.sort_unstable();
+        groups.dedup();
+        groups.retain(|&group| group != gid);
+        (groups.len() >= count).then(|| groups[..count].to_vec())
+    }
+
+    #[test]
+    fn test_can_chmod() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("mine.txt");
+        std::fs::write(&file, "").unwrap();
+        let owner = file.metadata().unwrap().uid();
+
+        assert!(can_chmod(&file, owner));
+        assert!(can_chmod(&file, 0));
+        assert!(!can_chmod(&file, owner + 1));
+        assert!(!can_chmod(&dir.path().join("missing"), owner + 1));
+
+        let denied = || io::Error::from_raw_os_error(libc::EPERM);
+        assert_eq!(summarize(&[]), None);
+        assert_eq!(
+            summarize(&[(file.clone(), denied()), (file.clone(), denied())]),
+            Some(format!(
+                "{}: Operation not permitted (os error 1) (and 1 more)",
+                file.display()
+            ))
+        );
+    }
+}
@@ -0,0 +1,481 @@
+use anyhow::Result;
+use crossterm::{
+    cursor::MoveTo,
+    event::KeyCode,
+    execute,
+    style::{Color, Print, ResetColor},
+};
+use std::fs;
+use std::io;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+use crate::ui::SetForegroundColor;
+use crate::utils::truncate_chars;
+
+/// Entries a walk collects before it stops
+const MAX_WALKED: usize = 100_000;
+
+/// Entries sent to the UI thread at a time
+const BATCH: usize = 256;
+
+/// Subtrees summarized above the list
+const MAX_SUMMARIES: usize = 6;
+
+/// An entry chmod or chown would reach, as it was when walked
+#[derive(Debug, Clone)]
+pub struct Affected {
+    pub path: PathBuf,
+    pub is_dir: bool,
+    /// A symbolic link below a selected directory, which `chmod -R` leaves
+    /// alone
+    pub is_link: bool,
+    pub mode: u32,
+    /// (uid, gid)
+    pub owner: (u32, u32),
+    /// Index of the subtree it counts toward
+    subtree: usize,
+}
+
+#[derive(Debug)]
+enum Message {
+    /// A selected path, or a directory directly inside a selected one,
+    /// summarized on its own
+    Subtree(PathBuf),
+    Entries(Vec<Affected>),
+    /// A directory whose contents could not be listed
+    Unreadable(PathBuf),
+}
+
+/// The selection, and with `recursive` everything below it, walked on a
+/// background thread and streamed in as it is found
+#[derive(Debug)]
+pub struct AffectedWalk {
+    recursive: bool,
+    receiver: Receiver<Message>,
+    cancel: Arc<AtomicBool>,
+    entries: Vec<Affected>,
+    subtrees: Vec<PathBuf>,
+    unreadable: Vec<PathBuf>,
+    done: bool,
+}
+
+impl Drop for AffectedWalk {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl AffectedWalk {
+    pub fn spawn(paths: Vec<PathBuf>, recursive: bool) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let worker_cancel = Arc::clone(&cancel);
+        thread::spawn(move || {
+            let mut walker = Walker {
+                sender,
+                cancel: &worker_cancel,
+                batch: Vec::new(),
+                walked: 0,
+                subtrees: 0,
+            };
+            walker.walk(&paths, recursive);
+        });
+
+        Self {
+            recursive,
+            receiver,
+            cancel,
+            entries: Vec::new(),
+            subtrees: Vec::new(),
+            unreadable: Vec::new(),
+            done: false,
+        }
+    }
+
+    /// Take in what the walk found since the last call
+    pub fn poll(&mut self) {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(Message::Subtree(path)) => self.subtrees.push(path),
+                Ok(Message::Entries(entries)) => self.entries.extend(entries),
+                Ok(Message::Unreadable(path)) => self.unreadable.push(path),
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    self.done = true;
+                    return;
+                }
+            }
+        }
+    }
+
+    pub fn recursive(&self) -> bool {
+        self.recursive
+    }
+}
+
+struct Walker<'a> {
+    sender: Sender<Message>,
+    cancel: &'a AtomicBool,
+    batch: Vec<Affected>,
+    walked: usize,
+    subtrees: usize,
+}
+
+impl Walker<'_> {
+    /// Whether to go on: the UI still wants the walk and it is not too big
+    fn alive(&self) -> bool {
+        !self.cancel.load(Ordering::Relaxed) && self.walked < MAX_WALKED
+    }
+
+    fn send(&mut self, message: Message) {
+        if !self.batch.is_empty() {
+            let batch = std::mem::take(&mut self.batch);
+            let _ = self.sender.send(Message::Entries(batch));
+        }
+        let _ = self.sender.send(message);
+    }
+
+    fn subtree(&mut self, path: &Path) -> usize {
+        self.send(Message::Subtree(path.to_path_buf()));
+        self.subtrees += 1;
+        self.subtrees - 1
+    }
+
+    fn walk(&mut self, paths: &[PathBuf], recursive: bool) {
+        for path in paths {
+            if !self.alive() {
+                break;
+            }
+            let subtree = self.subtree(path);
+            // Selected links are followed, as chmod and chown do
+            let Ok(metadata) = path.metadata() else {
+                continue;
+            };
+            self.push(path, &metadata, false, subtree);
+            if !recursive || !metadata.is_dir() {
+                continue;
+            }
+            for child in self.children(path) {
+                let Ok(metadata) = child.symlink_metadata() else {
+                    continue;
+                };
+                let subtree = if metadata.is_dir() {
+                    self.subtree(&child)
+                } else {
+                    subtree
+                };
+                self.visit(&child, &metadata, subtree);
+            }
+        }
+        self.send(Message::Entries(Vec::new()));
+    }
+
+    /// `path` and everything below it, without following links
+    fn visit(&mut self, path: &Path, metadata: &fs::Metadata, subtree: usize) {
+        if !self.alive() {
+            return;
+        }
+        let is_link = metadata.file_type().is_symlink();
+        self.push(path, metadata, is_link, subtree);
+        if !metadata.is_dir() {
+            return;
+        }
+        for child in self.children(path) {
+            if let Ok(metadata) = child.symlink_metadata() {
+                self.visit(&child, &metadata, subtree);
+            }
+        }
+    }
+
+    /// Entries of `dir` in name order
+    fn children(&mut self, dir: &Path) -> Vec<PathBuf> {
+        match fs::read_dir(dir) {
+            Ok(entries) => {
+                let mut children: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+                children.sort();
+                children
+            }
+            Err(_) => {
+                self.send(Message::Unreadable(dir.to_path_buf()));
+                Vec::new()
+            }
+        }
+    }
+
+    fn push(&mut self, path: &Path, metadata: &fs::Metadata, is_link: bool, subtree: usize) {
+        self.walked += 1;
+        self.batch.push(Affected {
+            path: path.to_path_buf(),
+            is_dir: metadata.is_dir(),
+            is_link,
+            mode: metadata.permissions().mode(),
+            owner: (metadata.uid(), metadata.gid()),
+            subtree,
+        });
+        if self.batch.len() >= BATCH {
+            let batch = std::mem::take(&mut self.batch);
+            let _ = self.sender.send(Message::Entries(batch));
+        }
+    }
+}
+
+/// Scrollable list of what Enter would change, over a walk of the
+/// selection
+#[derive(Debug)]
+pub struct AffectedView {
+    pub walk: AffectedWalk,
+    scroll: usize,
+}
+
+impl AffectedView {
+    pub fn new(paths: Vec<PathBuf>, recursive: bool) -> Self {
+        Self {
+            walk: AffectedWalk::spawn(paths, recursive),
+            scroll: 0,
+        }
+    }
+
+    /// Scroll with the arrows and page keys; false for other keys
+    pub fn handle_scroll(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down => self.scroll += 1,
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::PageDown => self.scroll += 10,
+            KeyCode::Home => self.scroll = 0,
+            _ => return false,
+        }
+        true
+    }
+
+    /// The count of entries `change` describes as changing out of those
+    /// walked, and how far the walk got
+    pub fn headline(&self, changing: usize) -> String {
+        let walked = self.walk.entries.len();
+        format!(
+            "{} of {} item(s) would change{}",
+            changing,
+            walked,
+            if !self.walk.done {
+                " (walking…)"
+            } else if walked >= MAX_WALKED {
+                " (stopped early)"
+            } else {
+                ""
+            }
+        )
+    }
+
+    /// Entries `change` finds changing, each with the change
+    pub fn changing<T>(&self, change: impl Fn(&Affected) -> Option<T>) -> Vec<(&Affected, T)> {
+        self.walk
+            .entries
+            .iter()
+            .filter_map(|entry| change(entry).map(|change| (entry, change)))
+            .collect()
+    }
+
+    /// The headline, then a line per entry `change` finds changing, as
+    /// `describe` puts it, for a dry-run report
+    pub fn report<T>(
+        &self,
+        change: impl Fn(&Affected) -> Option<T>,
+        describe: impl Fn(&T) -> String,
+    ) -> Vec<String> {
+        let changing = self.changing(change);
+        std::iter::once(format!("# {}", self.headline(changing.len())))
+            .chain(
+                changing.iter().map(|(entry, change)| {
+                    format!("{}: {}", entry.path.display(), describe(change))
+                }),
+            )
+            .collect()
+    }
+
+    /// Totals, per-subtree summaries, then from the scroll position every
+    /// entry `change` finds changing, as `describe` puts it
+    pub fn render<T>(
+        &mut self,
+        stdout: &mut io::Stdout,
+        y: u16,
+        width: u16,
+        rows: u16,
+        change: impl Fn(&Affected) -> Option<T>,
+        describe: impl Fn(&T) -> String,
+    ) -> Result<()> {
+        let width = width.saturating_sub(4) as usize;
+        let mut per_subtree = vec![(0, 0); self.walk.subtrees.len()];
+        for entry in &self.walk.entries {
+            if let Some(counts) = per_subtree.get_mut(entry.subtree) {
+                counts.0 += 1;
+            }
+        }
+        let changing = self.changing(change);
+        for (entry, _) in &changing {
+            if let Some(counts) = per_subtree.get_mut(entry.subtree) {
+                counts.1 += 1;
+            }
+        }
+        let walk = &self.walk;
+
+        execute!(
+            stdout,
+            MoveTo(2, y),
+            SetForegroundColor(Color::Yellow),
+            Print(truncate_chars(
+                &format!("📊 {}", self.headline(changing.len())),
+                width
+            )),
+            ResetColor
+        )?;
+
+        let mut row = y + 1;
+        for (path, (walked, touched)) in walk.subtrees.iter().zip(&per_subtree).take(MAX_SUMMARIES)
+        {
+            execute!(
+                stdout,
+                MoveTo(4, row),
+                SetForegroundColor(if *touched > 0 {
+                    Color::Cyan
+                } else {
+                    Color::DarkGrey
+                }),
+                Print(truncate_chars(
+                    &format!("{}: {} of {}", path.display(), touched, walked),
+                    width
+                )),
+                ResetColor
+            )?;
+            row += 1;
+        }
+        let hidden = walk.subtrees.len().saturating_sub(MAX_SUMMARIES);
+        let mut notes = Vec::new();
+        if hidden > 0 {
+            notes.push(format!("{} more subtrees", hidden));
+        }
+        if !walk.unreadable.is_empty() {
+            notes.push(format!(
+                "{} directories could not be read",
+                walk.unreadable.len()
+            ));
+        }
+        if !notes.is_empty() {
+            execute!(
+                stdout,
+                MoveTo(4, row),
+                SetForegroundColor(Color::DarkGrey),
+                Print(truncate_chars(&format!("… {}", notes.join(", ")), width)),
+                ResetColor
+            )?;
+            row += 1;
+        }
+
+        let list_rows = (y + rows).saturating_sub(row + 1) as usize;
+        let scroll = self
+            .scroll
+            .min(changing.len().saturating_sub(list_rows.max(1)));
+        for (i, (entry, change)) in changing.iter().skip(scroll).take(list_rows).enumerate() {
+            execute!(
+                stdout,
+                MoveTo(2, row + 1 + i as u16),
+                SetForegroundColor(Color::Green),
+                Print(truncate_chars(
+                    &format!("{}  {}", entry.path.display(), describe(change)),
+                    width
+                )),
+                ResetColor
+            )?;
+        }
+        self.scroll = scroll;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn finish(walk: &mut AffectedWalk) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !walk.done && Instant::now() < deadline {
+            walk.poll();
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_walk() {
+        let dir = tempfile::tempdir().unwrap();
+        let site = dir.path().join("site");
+        fs::create_dir_all(site.join("assets/img")).unwrap();
+        fs::create_dir_all(site.join("docs")).unwrap();
+        fs::write(site.join("index.html"), "").unwrap();
+        fs::write(site.join("assets/app.js"), "").unwrap();
+        fs::write(site.join("assets/img/logo.png"), "").unwrap();
+        std::os::unix::fs::symlink(dir.path(), site.join("docs/up")).unwrap();
+        let notes = dir.path().join("notes.txt");
+        fs::write(&notes, "").unwrap();
+
+        let mut walk = AffectedWalk::spawn(vec![site.clone(), notes.clone()], true);
+        finish(&mut walk);
+        let names: Vec<String> = walk
+            .entries
+            .iter()
+            .map(|entry| {
+                entry
+                    .path
+                    .strip_prefix(dir.path())
+                    .unwrap()
+                    .display()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "site",
+                "site/assets",
+                "site/assets/app.js",
+                "site/assets/img",
+                "site/assets/img/logo.png",
+                "site/docs",
+                "site/docs/up",
+                "site/index.html",
+                "notes.txt",
+            ]
+        );
+        // The link is listed but not followed
+        let link = &walk.entries[6];
+        assert!(link.is_link && !link.is_dir);
+        // Selected paths and the directories directly inside them are
+        // summarized apart; files directly inside count toward the selection
+        assert_eq!(
+            walk.subtrees,
+            [
+                site.clone(),
+                site.join("assets"),
+                site.join("docs"),
+                notes.clone()
+            ]
+        );
+        let subtrees: Vec<usize> = walk.entries.iter().map(|e| e.subtree).collect();
+        assert_eq!(subtrees, [0, 1, 1, 1, 1, 2, 2, 0, 3]);
+
+        // Without recursion only the selection is looked at
+        let mut walk = AffectedWalk::spawn(vec![site.clone()], false);
+        finish(&mut walk);
+        assert_eq!(walk.entries.len(), 1);
+        assert!(!walk.recursive());
+
+        let mut view = AffectedView { walk, scroll: 0 };
+        assert_eq!(view.headline(1), "1 of 1 item(s) would change");
+        view.handle_scroll(KeyCode::PageDown);
+        assert_eq!(view.scroll, 10);
+    }
+}
@@ -0,0 +1,263 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use super::access::{ownership, Failure};
+use crate::utils::config_dir;
+
+/// Batches kept, oldest dropped first
+const MAX_BATCHES: usize = 20;
+
+/// Files recorded per batch; a bigger change can only be rolled back in part
+pub const MAX_RECORDED: usize = 10_000;
+
+/// One file chown changed, with its ownership before and after
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OwnershipChange {
+    pub path: PathBuf,
+    pub old_uid: u32,
+    pub old_gid: u32,
+    pub new_uid: u32,
+    pub new_gid: u32,
+    /// A symlink below the selection, changed itself rather than its target
+    #[serde(default)]
+    pub link: bool,
+    #[serde(default)]
+    pub rolled_back: bool,
+}
+
+/// The files one Enter in the chown interface changed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OwnershipBatch {
+    /// Owner argument applied: "user:group", "user" or ":group"
+    pub owner: String,
+    pub timestamp: SystemTime,
+    pub changes: Vec<OwnershipChange>,
+    /// More files changed than were recorded
+    #[serde(default)]
+    pub truncated: bool,
+    /// Every change has been rolled back
+    #[serde(default)]
+    pub rolled_back: bool,
+}
+
+impl OwnershipBatch {
+    /// Indices of the changes not rolled back yet
+    pub fn pending(&self) -> Vec<usize> {
+        self.changes
+            .iter()
+            .enumerate()
+            .filter(|(_, change)| !change.rolled_back && !self.rolled_back)
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+/// Ownership changes made through the chown interface, saved in
+/// `ownership_history.json` so they can be rolled back later
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct OwnershipHistory {
+    /// Oldest first
+    #[serde(default)]
+    batches: Vec<OwnershipBatch>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl OwnershipHistory {
+    pub fn load() -> Self {
+        let Ok(dir) = config_dir() else {
+            return Self::default();
+        };
+        Self::load_from(dir.join("ownership_history.json"))
+    }
+
+    fn load_from(path: PathBuf) -> Self {
+        let mut history: Self = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        history.path = path;
+        history
+    }
+
+    /// Newest first
+    pub fn batches(&self) -> impl Iterator<Item = &OwnershipBatch> {
+        self.batches.iter().rev()
+    }
+
+    pub fn len(&self) -> usize {
+        self.batches.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.batches.is_empty()
+    }
+
+    /// Keep `batch` unless nothing was changed
+    pub fn record(&mut self, batch: OwnershipBatch) -> Result<()> {
+        if batch.changes.is_empty() {
+            return Ok(());
+        }
+        self.batches.push(batch);
+        let excess = self.batches.len().saturating_sub(MAX_BATCHES);
+        self.batches.drain(..excess);
+        self.save()
+    }
+
+    /// Give the files of `changes`, indices into the batch `index` places
+    /// from the newest, their previous owner and group back; files whose
+    /// ownership changed again since are left alone and reported with
+    /// those chown refused, to be tried again later
+    pub fn roll_back(&mut self, index: usize, changes: &[usize]) -> (usize, Vec<Failure>) {
+        let Some(position) = self.batches.len().checked_sub(index + 1) else {
+            return (0, Vec::new());
+        };
+        let batch = &mut self.batches[position];
+        let mut restored = 0;
+        let mut failures = Vec::new();
+        for &i in changes {
+            let Some(change) = batch.changes.get_mut(i) else {
+                continue;
+            };
+            if change.rolled_back || batch.rolled_back {
+                continue;
+            }
+            let outcome = ownership(&change.path, !change.link).and_then(|current| {
+                if current != (change.new_uid, change.new_gid) {
+                    return Err(io::Error::other(format!(
+                        "changed again since, now {}:{}",
+                        current.0, current.1
+                    )));
+                }
+                let old = (Some(change.old_uid), Some(change.old_gid));
+                if change.link {
+                    std::os::unix::fs::lchown(&change.path, old.0, old.1)
+                } else {
+                    std::os::unix::fs::chown(&change.path, old.0, old.1)
+                }
+            });
+            match outcome {
+                Ok(()) => {
+                    change.rolled_back = true;
+                    restored += 1;
+                }
+                Err(e) => failures.push((change.path.clone(), e)),
+            }
+        }
+        batch.rolled_back = batch.changes.iter().all(|change| change.rolled_back);
+        if let Err(e) = self.save() {
+            failures.push((self.path.clone(), io::Error::other(e.to_string())));
+        }
+        (restored, failures)
+    }
+
+    fn save(&self) -> Result<()> {
+        if self.path.as_os_str().is_empty() {
+            return Ok(());
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&self.path, json)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::managers::access::tests::other_groups;
+    use tempfile::TempDir;
+
+    fn batch(owner: &str, changes: Vec<OwnershipChange>) -> OwnershipBatch {
+        OwnershipBatch {
+            owner: owner.to_string(),
+            timestamp: SystemTime::UNIX_EPOCH,
+            changes,
+            truncated: false,
+            rolled_back: false,
+        }
+    }
+
+    #[test]
+    fn test_roll_back() {
+        let dir = TempDir::new().unwrap();
+        let moved = dir.path().join("moved.txt");
+        let again = dir.path().join("again.txt");
+        fs::write(&moved, "").unwrap();
+        fs::write(&again, "").unwrap();
+        let (uid, gid) = ownership(&moved, true).unwrap();
+        let Some(groups) = other_groups(gid, 2) else {
+            return;
+        };
+        let (staff, other) = (groups[0], groups[1]);
+
+        // Both files went from staff to gid; one has changed again since
+        let change = |path: &PathBuf| OwnershipChange {
+            path: path.clone(),
+            old_uid: uid,
+            old_gid: staff,
+            new_uid: uid,
+            new_gid: gid,
+            link: false,
+            rolled_back: false,
+        };
+        let path = dir.path().join("ownership_history.json");
+        let mut history = OwnershipHistory::load_from(path.clone());
+        history.record(batch(":old", Vec::new())).unwrap();
+        history
+            .record(batch(":staff", vec![change(&moved), change(&again)]))
+            .unwrap();
+        assert_eq!(history.len(), 1);
+        std::os::unix::fs::chown(&again, None, Some(other)).unwrap();
+
+        let mut history = OwnershipHistory::load_from(path.clone());
+        let all = history.batches().next().unwrap().pending();
+        let (restored, failures) = history.roll_back(0, &all);
+        assert_eq!(restored, 1);
+        assert_eq!(ownership(&moved, true).unwrap(), (uid, staff));
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, again);
+        assert_eq!(ownership(&again, true).unwrap(), (uid, other));
+
+        // The refused file can be tried again on its own once put back
+        let mut history = OwnershipHistory::load_from(path.clone());
+        let batch = history.batches().next().unwrap();
+        assert!(!batch.rolled_back);
+        assert_eq!(batch.pending(), [1]);
+        std::os::unix::fs::chown(&again, None, Some(gid)).unwrap();
+        assert_eq!(history.roll_back(0, &[1]).0, 1);
+        assert_eq!(ownership(&again, true).unwrap(), (uid, staff));
+        // Nothing is restored twice
+        assert_eq!(history.roll_back(0, &[0, 1]).0, 0);
+
+        let history = OwnershipHistory::load_from(path);
+        assert!(history.batches().next().unwrap().rolled_back);
+    }
+
+    #[test]
+    fn test_keeps_the_latest_batches() {
+        let mut history = OwnershipHistory::default();
+        let change = OwnershipChange {
+            path: PathBuf::from("/srv/app"),
+            old_uid: 0,
+            old_gid: 0,
+            new_uid: 1000,
+            new_gid: 1000,
+            link: false,
+            rolled_back: false,
+        };
+        for i in 0..MAX_BATCHES + 3 {
+            history
+                .record(batch(&format!("user{}", i), vec![change.clone()]))
+                .unwrap();
+        }
+        assert_eq!(history.len(), MAX_BATCHES);
+        assert_eq!(
+            history.batches().next().unwrap().owner,
+            format!("user{}", MAX_BATCHES + 2)
+        );
+    }
+}
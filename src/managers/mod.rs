@@ -1,5 +1,15 @@
+mod access;
+mod affected;
+mod history;
 mod ownership;
 mod permissions;
+mod picker;
+mod recent;
+mod script;
 
-pub use ownership::ChownInterface;
-pub use permissions::ChmodInterface;
+pub use access::{can_chmod, euid};
+pub use history::OwnershipHistory;
+pub use ownership::{chown_paths, ChownInterface};
+pub use permissions::{set_mode, ChmodInterface};
+pub use recent::RecentChoices;
+pub use script::private_dir;
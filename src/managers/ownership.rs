@@ -1,17 +1,37 @@
 use anyhow::Result;
 use crossterm::{
     cursor::MoveTo,
-    event::KeyCode,
+    event::{KeyCode, KeyModifiers},
     execute,
-    style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor},
+    style::{Color, Print, ResetColor},
     terminal,
 };
 use std::{
+    collections::BTreeSet,
+    fs,
     io::{self, Write},
-    path::PathBuf,
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+    time::SystemTime,
 };
 
-#[derive(Debug, Clone)]
+use super::access::{ownership, render_failures, Failure};
+use super::affected::{Affected, AffectedView};
+use super::history::{OwnershipBatch, OwnershipChange, OwnershipHistory, MAX_RECORDED};
+use super::picker::{PickerOutcome, ReferencePicker};
+use super::recent::{recent_hint, recent_index, RecentChoices};
+use super::script::{comment_safe, report_path, script_path, write_report, write_script};
+use crate::custom_commands::shell_quote;
+use crate::ui::{SetBackgroundColor, SetForegroundColor};
+use crate::utils::{truncate_chars, TimeFormat};
+
+/// Entries a simulation looks at before it stops descending
+const MAX_PLANNED: usize = 10_000;
+
+/// (uid, gid)
+type Owner = (u32, u32);
+
+#[derive(Debug)]
 pub struct ChownInterface {
     // Selected files/directories
     selected_paths: Vec<PathBuf>,
@@ -28,10 +48,159 @@ pub struct ChownInterface {
     focus: Focus,
     show_preview: bool,
     recursive: bool,
-    // Changes history
-    history: Vec<OwnershipChange>,
+    scope: Scope,
+    /// Changes made through the interface, saved to be rolled back
+    history: OwnershipHistory,
+    /// Batch highlighted in the history, newest first
+    history_index: usize,
+    /// The cursor is on this file of the highlighted batch instead
+    history_file: Option<usize>,
+    /// Files of the highlighted batch marked to be rolled back
+    history_marked: BTreeSet<usize>,
+    /// Files of the highlighted batch waiting for y/n before rolling back
+    history_confirm: Option<Vec<usize>>,
     // Warnings for critical files
     warnings: Vec<String>,
+    /// Not root, or a dry run: Enter reports what chown would do instead
+    /// of doing it
+    simulate: bool,
+    /// Running as root, so the simulation is a dry run that can be
+    /// turned off
+    root: bool,
+    /// Outcome of the last simulation, shown in place of the lists
+    plan: Vec<PlannedChange>,
+    plan_scroll: usize,
+    status: Option<String>,
+    /// Open while choosing a reference file
+    picker: Option<ReferencePicker>,
+    /// File the user and group were copied from, with its (uid, gid)
+    reference: Option<(PathBuf, (u32, u32))>,
+    /// Owners applied before, picked again with 1-5 in the options
+    recent: RecentChoices,
+    /// Owner argument and recursion applied when the interface closed
+    applied: Option<(String, bool)>,
+    /// Walk of the entries Enter would reach, restarted when recursion is
+    /// toggled
+    affected: Option<AffectedView>,
+    /// Whether the list of changes takes the place of the lists
+    show_affected: bool,
+    /// Paths chown was refused on, listed until the interface closes
+    failures: Vec<Failure>,
+}
+
+/// The user chown runs as, and the groups it may hand files to
+#[derive(Debug, Clone)]
+struct Caller {
+    uid: u32,
+    groups: Vec<u32>,
+}
+
+impl Caller {
+    fn current() -> Self {
+        #[cfg(unix)]
+        {
+            let uid = unsafe { libc::geteuid() };
+            let mut groups = vec![unsafe { libc::getegid() }];
+            let count = unsafe { libc::getgroups(0, std::ptr::null_mut()) };
+            if count > 0 {
+                let mut buffer = vec![0 as libc::gid_t; count as usize];
+                let count = unsafe { libc::getgroups(count, buffer.as_mut_ptr()) };
+                groups.extend(buffer.iter().take(count.max(0) as usize));
+            }
+            Self { uid, groups }
+        }
+        #[cfg(not(unix))]
+        {
+            Self {
+                uid: 0,
+                groups: Vec::new(),
+            }
+        }
+    }
+
+    /// What chown(2) would do with a file owned by `old` when asked for
+    /// `new`, both as (uid, gid)
+    fn predict(&self, old: (u32, u32), new: (u32, u32)) -> Outcome {
+        if old == new {
+            Outcome::Unchanged
+        } else if self.uid == 0 {
+            Outcome::Changes
+        } else if old.0 != new.0 {
+            Outcome::Fails("only root can give a file to another user".to_string())
+        } else if self.uid != old.0 {
+            Outcome::Fails("not the owner".to_string())
+        } else if !self.groups.contains(&new.1) {
+            Outcome::Fails("not a member of the new group".to_string())
+        } else {
+            Outcome::Changes
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Outcome {
+    /// Already owned by the chosen user and group
+    Unchanged,
+    Changes,
+    /// Refused without root, and why
+    Fails(String),
+}
+
+/// One file in a simulation: its current owner and what chown would do
+#[derive(Debug, Clone)]
+struct PlannedChange {
+    path: PathBuf,
+    /// Current (uid, gid), unknown when the file cannot be read
+    old: Option<(u32, u32)>,
+    outcome: Outcome,
+    /// A selected path, whose symlink is followed; below it links are
+    /// changed themselves
+    follow: bool,
+}
+
+/// Which part of the ownership chown changes, as in `user:group`, `user`
+/// or `:group`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Scope {
+    Both,
+    OwnerOnly,
+    GroupOnly,
+}
+
+impl Scope {
+    fn next(self) -> Self {
+        match self {
+            Scope::Both => Scope::OwnerOnly,
+            Scope::OwnerOnly => Scope::GroupOnly,
+            Scope::GroupOnly => Scope::Both,
+        }
+    }
+
+    fn changes_owner(self) -> bool {
+        self != Scope::GroupOnly
+    }
+
+    fn changes_group(self) -> bool {
+        self != Scope::OwnerOnly
+    }
+
+    /// The scope changing the owner and the group as asked, if any
+    fn from_parts(owner: bool, group: bool) -> Option<Self> {
+        match (owner, group) {
+            (true, true) => Some(Scope::Both),
+            (true, false) => Some(Scope::OwnerOnly),
+            (false, true) => Some(Scope::GroupOnly),
+            (false, false) => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Scope::Both => "owner and group",
+            Scope::OwnerOnly => "owner only",
+            Scope::GroupOnly => "group only",
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -40,6 +209,10 @@ enum Focus {
     GroupList,
     Options,
     Confirm,
+    /// Simulation results
+    Report,
+    /// Earlier changes, to roll back
+    History,
 }
 
 #[derive(Debug, Clone)]
@@ -55,19 +228,8 @@ struct GroupInfo {
     name: String,
 }
 
-#[allow(dead_code)]
-#[derive(Debug, Clone)]
-struct OwnershipChange {
-    path: PathBuf,
-    old_uid: u32,
-    old_gid: u32,
-    new_uid: u32,
-    new_gid: u32,
-    timestamp: std::time::SystemTime,
-}
-
 impl ChownInterface {
-    pub fn new(selected_paths: Vec<PathBuf>) -> Self {
+    pub fn new(selected_paths: Vec<PathBuf>, simulate: bool, recent: RecentChoices) -> Self {
         let users = Self::get_system_users();
         let groups = Self::get_system_groups();
         let warnings = Self::check_critical_paths(&selected_paths);
@@ -96,9 +258,27 @@ impl ChownInterface {
             group_search: String::new(),
             focus: Focus::UserList,
             show_preview: true,
-            recursive: false,
-            history: Vec::new(),
+            // Sticky from the last change applied
+            recursive: recent.recursive(),
+            scope: Scope::Both,
+            history: OwnershipHistory::default(),
+            history_index: 0,
+            history_file: None,
+            history_marked: BTreeSet::new(),
+            history_confirm: None,
             warnings,
+            simulate,
+            root: !simulate,
+            plan: Vec::new(),
+            plan_scroll: 0,
+            status: None,
+            picker: None,
+            reference: None,
+            recent,
+            applied: None,
+            affected: None,
+            show_affected: false,
+            failures: Vec::new(),
         }
     }
 
@@ -205,7 +385,21 @@ impl ChownInterface {
         warnings
     }
 
-    pub fn render(&self) -> Result<()> {
+    /// Take in the walk of the affected entries, starting it over when
+    /// recursion was toggled
+    pub fn poll(&mut self) {
+        match self.affected {
+            Some(ref mut view) if view.walk.recursive() == self.recursive => view.walk.poll(),
+            _ => {
+                self.affected = Some(AffectedView::new(
+                    self.selected_paths.clone(),
+                    self.recursive,
+                ))
+            }
+        }
+    }
+
+    pub fn render(&mut self) -> Result<()> {
         let mut stdout = io::stdout();
         let (terminal_width, terminal_height) = terminal::size()?;
 
@@ -214,6 +408,83 @@ impl ChownInterface {
         // Title
         self.render_title(&mut stdout)?;
 
+        if self.simulate {
+            execute!(
+                stdout,
+                MoveTo(0, 3),
+                SetBackgroundColor(Color::DarkYellow),
+                SetForegroundColor(Color::Black),
+                Print(if self.root {
+                    " DRY RUN - nothing will be changed (Ctrl+N, or n in the options: turn off) "
+                } else {
+                    " SIMULATION - not running as root, nothing will be changed "
+                }),
+                ResetColor
+            )?;
+        }
+
+        if !self.failures.is_empty() {
+            render_failures(&mut stdout, 5, terminal_width, 18, &self.failures)?;
+            self.render_controls(&mut stdout, terminal_height - 2)?;
+            stdout.flush()?;
+            return Ok(());
+        }
+
+        if self.focus == Focus::History {
+            self.render_history(&mut stdout, 5, terminal_width, terminal_height)?;
+            self.render_controls(&mut stdout, terminal_height - 2)?;
+            stdout.flush()?;
+            return Ok(());
+        }
+
+        if self.focus == Focus::Report {
+            self.render_report(&mut stdout, 5, terminal_width, terminal_height)?;
+            self.render_controls(&mut stdout, terminal_height - 2)?;
+            stdout.flush()?;
+            return Ok(());
+        }
+
+        if self.show_affected && self.picker.is_none() {
+            // Out of the way while the closures borrow the names
+            let mut affected = self.affected.take();
+            let change = self.owner_change();
+            let describe = |(old, new): &(Owner, Owner)| {
+                format!(
+                    "{}:{} → {}:{}",
+                    self.user_name(old.0),
+                    self.group_name(old.1),
+                    self.user_name(new.0),
+                    self.group_name(new.1)
+                )
+            };
+            if let Some(ref mut view) = affected {
+                view.render(
+                    &mut stdout,
+                    5,
+                    terminal_width,
+                    terminal_height.saturating_sub(8),
+                    change,
+                    describe,
+                )?;
+            }
+            self.affected = affected;
+            self.render_controls(&mut stdout, terminal_height - 2)?;
+            stdout.flush()?;
+            return Ok(());
+        }
+
+        if let Some(ref picker) = self.picker {
+            picker.render(
+                &mut stdout,
+                5,
+                terminal_width,
+                terminal_height.saturating_sub(8),
+            )?;
+            self.render_controls(&mut stdout, terminal_height - 2)?;
+            stdout.flush()?;
+            return Ok(());
+        }
+
         // Warnings if any
         if !self.warnings.is_empty() {
             self.render_warnings(&mut stdout, 4)?;
@@ -230,10 +501,20 @@ impl ChownInterface {
 
         // Preview if enabled
         if self.show_preview {
-            self.render_preview(&mut stdout, content_start + 14, terminal_width)?;
+            self.render_preview(&mut stdout, content_start + 15, terminal_width)?;
             // Adjusted for 5 items
         }
 
+        if let Some(ref status) = self.status {
+            execute!(
+                stdout,
+                MoveTo(2, terminal_height.saturating_sub(3)),
+                SetForegroundColor(Color::Yellow),
+                Print(status),
+                ResetColor
+            )?;
+        }
+
         // Controls
         self.render_controls(&mut stdout, terminal_height - 2)?;
 
@@ -293,7 +574,11 @@ impl ChownInterface {
             } else {
                 Color::Cyan
             }),
-            Print("👤 USER SELECTION"),
+            Print(if self.scope.changes_owner() {
+                "👤 USER SELECTION"
+            } else {
+                "👤 USER SELECTION (left unchanged)"
+            }),
             ResetColor
         )?;
 
@@ -355,11 +640,11 @@ impl ChownInterface {
                         Print(format!(
                             " {} {:<12} ({:>5}) {:<20} ",
                             if is_selected { ">" } else { " " },
-                            &user.name[..user.name.len().min(12)],
+                            truncate_chars(&user.name, 12),
                             user.uid,
                             user.full_name
-                                .as_ref()
-                                .map(|s| &s[..s.len().min(20)])
+                                .as_deref()
+                                .map(|s| truncate_chars(s, 20))
                                 .unwrap_or("")
                         )),
                         ResetColor
@@ -379,7 +664,11 @@ impl ChownInterface {
             } else {
                 Color::Cyan
             }),
-            Print("👥 GROUP SELECTION"),
+            Print(if self.scope.changes_group() {
+                "👥 GROUP SELECTION"
+            } else {
+                "👥 GROUP SELECTION (left unchanged)"
+            }),
             ResetColor
         )?;
 
@@ -441,7 +730,7 @@ impl ChownInterface {
                         Print(format!(
                             " {} {:<15} ({:>5}) ",
                             if is_selected { ">" } else { " " },
-                            &group.name[..group.name.len().min(15)],
+                            truncate_chars(&group.name, 15),
                             group.gid
                         )),
                         ResetColor
@@ -478,122 +767,395 @@ impl ChownInterface {
             )),
             ResetColor
         )?;
+        let checkbox = |checked: bool| {
+            if checked {
+                (Color::Green, "✓")
+            } else {
+                (Color::DarkGrey, " ")
+            }
+        };
+        let (owner_color, owner_mark) = checkbox(self.scope.changes_owner());
+        let (group_color, group_mark) = checkbox(self.scope.changes_group());
+        execute!(
+            stdout,
+            MoveTo(4, options_y + 2),
+            SetForegroundColor(owner_color),
+            Print(format!("[{}] Change the owner (o)", owner_mark)),
+            SetForegroundColor(group_color),
+            Print(format!("   [{}] Change the group (g)", group_mark)),
+            SetForegroundColor(Color::DarkGrey),
+            Print("   m: cycle"),
+            ResetColor
+        )?;
+
+        if let Some(reference) = self.active_reference() {
+            execute!(
+                stdout,
+                MoveTo(4, options_y + 3),
+                SetForegroundColor(Color::Cyan),
+                Print(format!("📎 Reference: {}", reference.display())),
+                ResetColor
+            )?;
+        }
+
+        if let Some(hint) = recent_hint(self.recent.owners()) {
+            execute!(
+                stdout,
+                MoveTo(4, options_y + 4),
+                SetForegroundColor(Color::DarkGrey),
+                Print(hint),
+                ResetColor
+            )?;
+        }
 
         Ok(())
     }
 
-    fn render_preview(&self, stdout: &mut io::Stdout, y: u16, _width: u16) -> Result<()> {
+    /// The first entries Enter would change, from the walk of the selection
+    fn render_preview(&self, stdout: &mut io::Stdout, y: u16, width: u16) -> Result<()> {
+        let Some(ref view) = self.affected else {
+            return Ok(());
+        };
+        let changing = view.changing(self.owner_change());
+        let width = width.saturating_sub(6) as usize;
         execute!(
             stdout,
             MoveTo(2, y),
             SetForegroundColor(Color::Yellow),
-            Print("📊 PREVIEW - Files to be affected:"),
+            Print(format!("📊 PREVIEW - {}", view.headline(changing.len()))),
             ResetColor
         )?;
 
-        // Get filtered lists to show correct preview
-        let filtered_users: Vec<&UserInfo> = self
-            .users
-            .iter()
-            .filter(|u| {
-                self.user_search.is_empty()
-                    || u.name
-                        .to_lowercase()
-                        .contains(&self.user_search.to_lowercase())
-            })
-            .collect();
+        for (i, (entry, (old, new))) in changing.iter().take(5).enumerate() {
+            let line = format!(
+                "• {}  {}:{} → {}:{}",
+                entry.path.display(),
+                self.user_name(old.0),
+                self.group_name(old.1),
+                self.user_name(new.0),
+                self.group_name(new.1)
+            );
+            execute!(
+                stdout,
+                MoveTo(4, y + 1 + i as u16),
+                SetForegroundColor(Color::DarkGrey),
+                Print(truncate_chars(&line, width)),
+                ResetColor
+            )?;
+        }
 
-        let filtered_groups: Vec<&GroupInfo> = self
-            .groups
-            .iter()
-            .filter(|g| {
-                self.group_search.is_empty()
-                    || g.name
-                        .to_lowercase()
-                        .contains(&self.group_search.to_lowercase())
-            })
-            .collect();
+        if changing.len() > 5 {
+            execute!(
+                stdout,
+                MoveTo(4, y + 6),
+                SetForegroundColor(Color::DarkGrey),
+                Print(format!(
+                    "... and {} more (Ctrl+L: list them all)",
+                    changing.len() - 5
+                )),
+                ResetColor
+            )?;
+        }
 
-        let selected_user = filtered_users.get(
-            self.selected_user_idx
-                .min(filtered_users.len().saturating_sub(1)),
-        );
-        let selected_group = filtered_groups.get(
-            self.selected_group_idx
-                .min(filtered_groups.len().saturating_sub(1)),
-        );
+        Ok(())
+    }
 
-        // Show affected files
-        let mut all_files = Vec::new();
-        for path in &self.selected_paths {
-            all_files.push(path.clone());
-            if self.recursive && path.is_dir() {
-                // In real implementation, would recursively get all files
-                // For now, just show indication
-                all_files.push(PathBuf::from(format!(
-                    "  {} (and all contents)",
-                    path.display()
-                )));
-            }
+    /// The (uid, gid) before and after Enter of an entry it would change
+    fn owner_change(&self) -> impl Fn(&Affected) -> Option<(Owner, Owner)> {
+        let target = self.target();
+        move |entry| {
+            let new = resolve(entry.owner, target?);
+            (new != entry.owner).then_some((entry.owner, new))
         }
+    }
 
-        for (i, file) in all_files.iter().take(5).enumerate() {
-            let (current_uid, current_gid) = Self::get_file_ownership(file);
-            let current_user = self.users.iter().find(|u| u.uid == current_uid);
-            let current_group = self.groups.iter().find(|g| g.gid == current_gid);
+    /// The simulation: totals, the command to re-run under sudo, and the
+    /// outcome for every file
+    fn render_report(
+        &self,
+        stdout: &mut io::Stdout,
+        y: u16,
+        width: u16,
+        height: u16,
+    ) -> Result<()> {
+        let width = width as usize;
+        let count = |wanted: fn(&Outcome) -> bool| {
+            self.plan
+                .iter()
+                .filter(|change| wanted(&change.outcome))
+                .count()
+        };
+        let failing = count(|o| matches!(o, Outcome::Fails(_)));
+        execute!(
+            stdout,
+            MoveTo(2, y),
+            SetForegroundColor(Color::Yellow),
+            Print(format!(
+                "📊 {} item(s): {} would change, {} would fail without root, {} unchanged{}",
+                self.plan.len(),
+                count(|o| *o == Outcome::Changes),
+                failing,
+                count(|o| *o == Outcome::Unchanged),
+                if self.plan.len() >= MAX_PLANNED {
+                    " (stopped early)"
+                } else {
+                    ""
+                }
+            )),
+            MoveTo(2, y + 1),
+            SetForegroundColor(Color::Cyan),
+            Print(
+                self.command()
+                    .unwrap_or_default()
+                    .chars()
+                    .take(width.saturating_sub(4))
+                    .collect::<String>()
+            ),
+            ResetColor
+        )?;
 
+        let rows = (height as usize).saturating_sub(y as usize + 6);
+        let target = self.target();
+        for (i, change) in self
+            .plan
+            .iter()
+            .skip(self.plan_scroll)
+            .take(rows)
+            .enumerate()
+        {
+            let (color, line) = self.report_line(change, target);
             execute!(
                 stdout,
-                MoveTo(4, y + 1 + i as u16),
-                SetForegroundColor(Color::DarkGrey),
-                Print(format!("• {}", file.display())),
+                MoveTo(2, y + 3 + i as u16),
+                SetForegroundColor(color),
+                Print(
+                    line.chars()
+                        .take(width.saturating_sub(4))
+                        .collect::<String>()
+                ),
                 ResetColor
             )?;
+        }
 
+        if let Some(ref status) = self.status {
             execute!(
                 stdout,
-                MoveTo(6, y + 2 + i as u16),
-                SetForegroundColor(Color::Red),
-                Print(format!(
-                    "  {} : {} ",
-                    current_user.map(|u| u.name.as_str()).unwrap_or("?"),
-                    current_group.map(|g| g.name.as_str()).unwrap_or("?")
-                )),
-                SetForegroundColor(Color::White),
-                Print("→"),
-                SetForegroundColor(Color::Green),
-                Print(format!(
-                    " {} : {}",
-                    selected_user.map(|u| u.name.as_str()).unwrap_or("?"),
-                    selected_group.map(|g| g.name.as_str()).unwrap_or("?")
-                )),
+                MoveTo(2, height.saturating_sub(3)),
+                SetForegroundColor(if failing > 0 {
+                    Color::Yellow
+                } else {
+                    Color::Green
+                }),
+                Print(
+                    status
+                        .chars()
+                        .take(width.saturating_sub(4))
+                        .collect::<String>()
+                ),
                 ResetColor
             )?;
         }
+        Ok(())
+    }
 
-        if all_files.len() > 5 {
+    /// Earlier changes, newest first, then the files of the highlighted one
+    fn render_history(
+        &self,
+        stdout: &mut io::Stdout,
+        y: u16,
+        width: u16,
+        height: u16,
+    ) -> Result<()> {
+        let width = width.saturating_sub(4) as usize;
+        let time_format = TimeFormat {
+            pattern: "%Y-%m-%d %H:%M".to_string(),
+            relative: false,
+        };
+        execute!(
+            stdout,
+            MoveTo(2, y),
+            SetForegroundColor(Color::Yellow),
+            Print("🕘 Ownership changes, newest first"),
+            ResetColor
+        )?;
+
+        let mut row = y + 2;
+        for (i, batch) in self.history.batches().enumerate() {
+            let selected = i == self.history_index;
+            let line = format!(
+                "{}  {}  {} file(s){}{}",
+                time_format.format(batch.timestamp),
+                batch.owner,
+                batch.changes.len(),
+                if batch.truncated {
+                    " (more not recorded)"
+                } else {
+                    ""
+                },
+                if batch.rolled_back {
+                    "  - rolled back"
+                } else {
+                    ""
+                }
+            );
             execute!(
                 stdout,
-                MoveTo(4, y + 6),
-                SetForegroundColor(Color::DarkGrey),
-                Print(format!("... and {} more files", all_files.len() - 5)),
+                MoveTo(2, row),
+                if selected {
+                    SetBackgroundColor(Color::DarkGreen)
+                } else {
+                    SetBackgroundColor(Color::Reset)
+                },
+                SetForegroundColor(if batch.rolled_back {
+                    Color::DarkGrey
+                } else {
+                    Color::White
+                }),
+                Print(format!("{:<width$}", truncate_chars(&line, width))),
                 ResetColor
             )?;
+            row += 1;
+        }
+
+        let owner =
+            |uid: u32, gid: u32| format!("{}:{}", self.user_name(uid), self.group_name(gid));
+        let rows = height.saturating_sub(row + 5).max(1) as usize;
+        if let Some(batch) = self.history.batches().nth(self.history_index) {
+            // Keep the file under the cursor in view
+            let first = self
+                .history_file
+                .map_or(0, |file| (file + 1).saturating_sub(rows));
+            for (i, change) in batch.changes.iter().enumerate().skip(first).take(rows) {
+                row += 1;
+                let mark = if change.rolled_back || batch.rolled_back {
+                    "↺"
+                } else if self.history_marked.contains(&i) {
+                    "[x]"
+                } else {
+                    "[ ]"
+                };
+                execute!(
+                    stdout,
+                    MoveTo(4, row),
+                    if self.history_file == Some(i) {
+                        SetBackgroundColor(Color::DarkGreen)
+                    } else {
+                        SetBackgroundColor(Color::Reset)
+                    },
+                    SetForegroundColor(if change.rolled_back || batch.rolled_back {
+                        Color::DarkGrey
+                    } else {
+                        Color::Cyan
+                    }),
+                    Print(truncate_chars(
+                        &format!(
+                            "{} {}  {} → back to {}",
+                            mark,
+                            change.path.display(),
+                            owner(change.new_uid, change.new_gid),
+                            owner(change.old_uid, change.old_gid)
+                        ),
+                        width.saturating_sub(2)
+                    )),
+                    ResetColor
+                )?;
+            }
+            let more = batch.changes.len().saturating_sub(first + rows);
+            if more > 0 {
+                execute!(
+                    stdout,
+                    MoveTo(4, row + 1),
+                    SetForegroundColor(Color::DarkGrey),
+                    Print(format!("... and {} more", more)),
+                    ResetColor
+                )?;
+            }
         }
 
+        if let Some(ref status) = self.status {
+            execute!(
+                stdout,
+                MoveTo(2, height.saturating_sub(3)),
+                SetForegroundColor(Color::Yellow),
+                Print(truncate_chars(status, width)),
+                ResetColor
+            )?;
+        }
         Ok(())
     }
 
+    /// The outcome of one file of the simulation, and its colour
+    fn report_line(
+        &self,
+        change: &PlannedChange,
+        target: Option<(Option<u32>, Option<u32>)>,
+    ) -> (Color, String) {
+        let old = change.old.map_or("?:?".to_string(), |(uid, gid)| {
+            format!("{}:{}", self.user_name(uid), self.group_name(gid))
+        });
+        let new = match (change.old, target) {
+            (Some(old), Some(target)) => {
+                let (uid, gid) = resolve(old, target);
+                format!("{}:{}", self.user_name(uid), self.group_name(gid))
+            }
+            _ => "?:?".to_string(),
+        };
+        match &change.outcome {
+            Outcome::Unchanged => (
+                Color::DarkGrey,
+                format!("= {}  {}", change.path.display(), old),
+            ),
+            Outcome::Changes => (
+                Color::Green,
+                format!("✓ {}  {} → {}", change.path.display(), old, new),
+            ),
+            Outcome::Fails(reason) => (
+                Color::Red,
+                format!(
+                    "✗ {}  {} → {}  ({})",
+                    change.path.display(),
+                    old,
+                    new,
+                    reason
+                ),
+            ),
+        }
+    }
+
     fn render_controls(&self, stdout: &mut io::Stdout, y: u16) -> Result<()> {
+        let apply = if self.simulate {
+            "Enter: Simulate"
+        } else {
+            "Enter: Apply"
+        };
         let controls = match self.focus {
-            Focus::UserList | Focus::GroupList => {
-                " Tab: Switch Focus | ↑↓: Navigate | Type: Search | r: Toggle Recursive | p: Toggle Preview | Enter: Apply | Esc: Cancel "
+            _ if !self.failures.is_empty() => " Enter/Esc: Close ".to_string(),
+            _ if self.picker.is_some() => ReferencePicker::controls().to_string(),
+            _ if self.show_affected => {
+                " ↑↓/PgUp/PgDn: Scroll | r: Toggle Recursive | l/Esc: Back to selection ".to_string()
+            }
+            Focus::UserList | Focus::GroupList => format!(
+                " Tab: Switch Focus | ↑↓: Navigate | Type: Search | r: Toggle Recursive | p: Toggle Preview | Ctrl+R: Reference File | Ctrl+L: List Changes | Ctrl+N: Dry Run | Ctrl+U: History | Ctrl+E: Export | {} | Esc: Cancel ",
+                apply
+            ),
+            Focus::Options => format!(
+                " Tab: Switch Focus | Space/r: Toggle Recursive | o/g: Owner/Group | m: Cycle | f: Reference File | l: List Changes | n: Dry Run | u: History | p: Toggle Preview | e: Export | {} | Esc: Cancel ",
+                apply
+            ),
+            Focus::Confirm => " y: Yes, Apply Changes | n/Esc: No, Cancel ".to_string(),
+            Focus::Report if !self.root => {
+                " ↑↓: Scroll | Enter: Apply the allowed changes | e: Export as script | w: Save report | Esc: Back to selection "
+                    .to_string()
             }
-            Focus::Options => {
-                " Tab: Switch Focus | Space/r: Toggle Recursive | p: Toggle Preview | Enter: Apply | Esc: Cancel "
+            Focus::Report => {
+                " ↑↓: Scroll | e: Export as script | w: Save report | Esc: Back to selection "
+                    .to_string()
             }
-            Focus::Confirm => {
-                " y: Yes, Apply Changes | n/Esc: No, Cancel "
+            Focus::History if self.history_confirm.is_some() => {
+                " y: Yes, Roll Back | n/Esc: No, Cancel ".to_string()
+            }
+            Focus::History => {
+                " ↑↓: Select | Tab: Batches/Files | Space: Mark File | Enter: Roll back marked (or all) | Esc: Back to selection ".to_string()
             }
         };
 
@@ -609,14 +1171,479 @@ impl ChownInterface {
         Ok(())
     }
 
-    pub fn handle_input(&mut self, key: KeyCode) -> bool {
+    fn user_name(&self, uid: u32) -> String {
+        self.users
+            .iter()
+            .find(|u| u.uid == uid)
+            .map_or(uid.to_string(), |u| u.name.clone())
+    }
+
+    fn group_name(&self, gid: u32) -> String {
+        self.groups
+            .iter()
+            .find(|g| g.gid == gid)
+            .map_or(gid.to_string(), |g| g.name.clone())
+    }
+
+    /// The user and group highlighted in the filtered lists
+    fn selection(&self) -> (Option<&UserInfo>, Option<&GroupInfo>) {
+        let users: Vec<&UserInfo> = self
+            .users
+            .iter()
+            .filter(|u| {
+                self.user_search.is_empty()
+                    || u.name
+                        .to_lowercase()
+                        .contains(&self.user_search.to_lowercase())
+            })
+            .collect();
+        let groups: Vec<&GroupInfo> = self
+            .groups
+            .iter()
+            .filter(|g| {
+                self.group_search.is_empty()
+                    || g.name
+                        .to_lowercase()
+                        .contains(&self.group_search.to_lowercase())
+            })
+            .collect();
+        (
+            users
+                .get(self.selected_user_idx.min(users.len().saturating_sub(1)))
+                .copied(),
+            groups
+                .get(self.selected_group_idx.min(groups.len().saturating_sub(1)))
+                .copied(),
+        )
+    }
+
+    /// The new (uid, gid), each `None` when the scope leaves it alone;
+    /// `None` when the part to change has nothing selected
+    fn target(&self) -> Option<(Option<u32>, Option<u32>)> {
+        let (user, group) = self.selection();
+        match self.scope {
+            Scope::Both => Some((Some(user?.uid), Some(group?.gid))),
+            Scope::OwnerOnly => Some((Some(user?.uid), None)),
+            Scope::GroupOnly => Some((None, Some(group?.gid))),
+        }
+    }
+
+    /// The reference file, while the user and group to apply are still
+    /// the ones copied from it
+    fn active_reference(&self) -> Option<&Path> {
+        let (path, (uid, gid)) = self.reference.as_ref()?;
+        (self.target() == Some((Some(*uid), Some(*gid)))).then_some(path.as_path())
+    }
+
+    fn open_picker(&mut self) {
+        let start = self
+            .selected_paths
+            .first()
+            .and_then(|p| p.parent())
+            .unwrap_or(Path::new("/"));
+        self.picker = Some(ReferencePicker::new(start));
+    }
+
+    /// Select the user and group owning `path`, as `chown --reference`
+    fn use_reference(&mut self, path: &Path) {
+        let (uid, gid) = match ownership(path, true) {
+            Ok(owner) => owner,
+            Err(e) => {
+                self.status = Some(format!("Cannot read {}: {}", path.display(), e));
+                return;
+            }
+        };
+        let user = self.users.iter().position(|u| u.uid == uid);
+        let group = self.groups.iter().position(|g| g.gid == gid);
+        let (Some(user), Some(group)) = (user, group) else {
+            self.status = Some(format!("{}:{} is not a known user and group", uid, gid));
+            return;
+        };
+        // Indices are into the filtered lists
+        self.user_search.clear();
+        self.group_search.clear();
+        self.selected_user_idx = user;
+        self.selected_group_idx = group;
+        self.scope = Scope::Both;
+        self.reference = Some((path.to_path_buf(), (uid, gid)));
+        self.status = None;
+    }
+
+    /// The owner argument and whether it was applied recursively, if the
+    /// interface closed by changing ownership
+    pub fn applied(&self) -> Option<(&str, bool)> {
+        self.applied
+            .as_ref()
+            .map(|(owner, recursive)| (owner.as_str(), *recursive))
+    }
+
+    /// Record changes in `history`, and offer its batches to roll back
+    pub fn with_history(mut self, history: OwnershipHistory) -> Self {
+        self.history = history;
+        self
+    }
+
+    pub fn recent(&self) -> &RecentChoices {
+        &self.recent
+    }
+
+    /// Select the recent owner under `key`, as `user:group`, `user` or
+    /// `:group`
+    fn use_recent(&mut self, key: char) {
+        let Some(owner) = recent_index(key).and_then(|i| self.recent.owners().get(i)) else {
+            return;
+        };
+        let (user, group, scope) = match owner.split_once(':') {
+            Some(("", group)) => (None, Some(group), Scope::GroupOnly),
+            Some((user, group)) => (Some(user), Some(group), Scope::Both),
+            None => (Some(owner.as_str()), None, Scope::OwnerOnly),
+        };
+        let user = user.map(|name| self.users.iter().position(|u| u.name == name));
+        let group = group.map(|name| self.groups.iter().position(|g| g.name == name));
+        if user == Some(None) || group == Some(None) {
+            self.status = Some(format!("{} is no longer a known owner", owner));
+            return;
+        }
+        // Indices are into the filtered lists
+        self.user_search.clear();
+        self.group_search.clear();
+        if let Some(Some(index)) = user {
+            self.selected_user_idx = index;
+        }
+        if let Some(Some(index)) = group {
+            self.selected_group_idx = index;
+        }
+        self.scope = scope;
+        self.status = None;
+    }
+
+    /// The owner argument of chown: `user:group`, `user` or `:group`
+    fn owner_spec(&self) -> Option<String> {
+        let (user, group) = self.selection();
+        Some(match self.scope {
+            Scope::Both => format!("{}:{}", user?.name, group?.name),
+            Scope::OwnerOnly => user?.name.clone(),
+            Scope::GroupOnly => format!(":{}", group?.name),
+        })
+    }
+
+    /// Work out what chown would do to every selected file, and below them
+    /// when recursive, without touching anything
+    fn simulate_changes(&mut self, caller: &Caller) {
+        let Some(new) = self.target() else {
+            self.status = Some(format!("Pick the new {} first", self.scope.label()));
+            return;
+        };
+
+        let mut plan = Vec::new();
+        for path in &self.selected_paths {
+            plan_path(path, new, self.recursive, true, caller, &mut plan);
+        }
+        self.plan = plan;
+        self.plan_scroll = 0;
+        self.status = None;
+        self.focus = Focus::Report;
+    }
+
+    /// One `chown` covering the selection and options, under `sudo` in a
+    /// simulation without root
+    fn command(&self) -> Option<String> {
+        let paths: Vec<String> = self.selected_paths.iter().map(|p| shell_quote(p)).collect();
+        let owner = match self.active_reference() {
+            Some(reference) => format!("--reference={} --", shell_quote(reference)),
+            None => format!("-- {}", shell_quote(Path::new(&self.owner_spec()?))),
+        };
+        Some(format!(
+            "{}chown {}{} {}",
+            if self.simulate && !self.root {
+                "sudo "
+            } else {
+                ""
+            },
+            if self.recursive { "-R " } else { "" },
+            owner,
+            paths.join(" ")
+        ))
+    }
+
+    /// Write the command as a script, noting the files a simulation found
+    /// to need root
+    fn export_to(&self, path: &Path) -> io::Result<()> {
+        let command = self
+            .command()
+            .ok_or_else(|| io::Error::other("no user or group selected"))?;
+        let mut lines: Vec<String> = self
+            .plan
+            .iter()
+            .filter_map(|change| match change.outcome {
+                Outcome::Fails(ref reason) => Some(format!(
+                    "# needs root: {} ({})",
+                    comment_safe(&change.path.display().to_string()),
+                    comment_safe(reason)
+                )),
+                _ => None,
+            })
+            .collect();
+        lines.push(command);
+        write_script(path, "chown", &lines)
+    }
+
+    fn export(&mut self) {
+        let saved = script_path("chown").and_then(|path| self.export_to(&path).map(|()| path));
+        self.status = Some(match saved {
+            Ok(path) => format!("Saved; run it with: sh {}", path.display()),
+            Err(e) => format!("Export failed: {}", e),
+        });
+    }
+
+    /// Write the command and the outcome for every file of the simulation
+    /// to a report in the temp directory
+    fn write_report_to(&self, path: &Path) -> io::Result<()> {
+        let target = self.target();
+        let lines: Vec<String> = self
+            .plan
+            .iter()
+            .map(|change| self.report_line(change, target).1)
+            .collect();
+        write_report(
+            path,
+            "chown",
+            &self.command().into_iter().collect::<Vec<_>>(),
+            &lines,
+        )
+    }
+
+    fn write_report(&mut self) {
+        let saved =
+            report_path("chown").and_then(|path| self.write_report_to(&path).map(|()| path));
+        self.status = Some(match saved {
+            Ok(path) => format!("Report saved to {}", path.display()),
+            Err(e) => format!("Report failed: {}", e),
+        });
+    }
+
+    /// Switch the dry run on or off; without root there is nothing else
+    fn toggle_dry_run(&mut self) {
+        if self.root {
+            self.simulate = !self.simulate;
+        } else {
+            self.status = Some("Only root can turn the simulation off".to_string());
+        }
+    }
+
+    fn open_history(&mut self) {
+        if self.history.is_empty() {
+            self.status = Some("No ownership changes recorded yet".to_string());
+            return;
+        }
+        self.select_batch(0);
+        self.status = None;
+        self.focus = Focus::History;
+    }
+
+    fn select_batch(&mut self, index: usize) {
+        self.history_index = index;
+        self.history_file = None;
+        self.history_marked.clear();
+    }
+
+    fn handle_history_input(&mut self, key: KeyCode) -> bool {
+        if let Some(changes) = self.history_confirm.take() {
+            match key {
+                KeyCode::Char('y') | KeyCode::Char('Y') => self.roll_back(&changes),
+                _ => self.status = Some("Roll back cancelled".to_string()),
+            }
+            return true;
+        }
+
+        let files = self
+            .history
+            .batches()
+            .nth(self.history_index)
+            .map_or(0, |batch| batch.changes.len());
+        match key {
+            KeyCode::Up => match self.history_file {
+                Some(file) => self.history_file = Some(file.saturating_sub(1)),
+                None => self.select_batch(self.history_index.saturating_sub(1)),
+            },
+            KeyCode::Down => match self.history_file {
+                Some(file) if file + 1 < files => self.history_file = Some(file + 1),
+                None if self.history_index + 1 < self.history.len() => {
+                    self.select_batch(self.history_index + 1)
+                }
+                _ => {}
+            },
+            KeyCode::Tab if files > 0 => {
+                self.history_file = match self.history_file {
+                    Some(_) => None,
+                    None => Some(0),
+                };
+            }
+            KeyCode::Char(' ') => {
+                if let Some(file) = self.history_file {
+                    if !self.history_marked.remove(&file) {
+                        self.history_marked.insert(file);
+                    }
+                }
+            }
+            KeyCode::Enter => self.ask_roll_back(),
+            KeyCode::Esc | KeyCode::Backspace => {
+                self.focus = Focus::UserList;
+                self.status = None;
+            }
+            KeyCode::Char('q') => return false,
+            _ => {}
+        }
+        true
+    }
+
+    /// Ask before rolling back the marked files of the highlighted batch,
+    /// or all of them when none are marked
+    fn ask_roll_back(&mut self) {
+        let Some(batch) = self.history.batches().nth(self.history_index) else {
+            return;
+        };
+        let changes: Vec<usize> = batch
+            .pending()
+            .into_iter()
+            .filter(|i| self.history_marked.is_empty() || self.history_marked.contains(i))
+            .collect();
+        if changes.is_empty() {
+            self.status = Some("Already rolled back".to_string());
+        } else if self.simulate && self.root {
+            self.status = Some(format!(
+                "Dry run: rolling back would restore {} file(s)",
+                changes.len()
+            ));
+        } else {
+            self.status = Some(format!(
+                "Give {} file(s) changed to {} their previous owner back? (y/n)",
+                changes.len(),
+                batch.owner
+            ));
+            self.history_confirm = Some(changes);
+        }
+    }
+
+    fn roll_back(&mut self, changes: &[usize]) {
+        let (restored, failures) = self.history.roll_back(self.history_index, changes);
+        self.history_marked.clear();
+        self.status = Some(format!(
+            "Restored the previous owner of {} file(s)",
+            restored
+        ));
+        self.failures = failures;
+    }
+
+    fn handle_report_input(&mut self, key: KeyCode) -> bool {
         match key {
+            KeyCode::Up => self.plan_scroll = self.plan_scroll.saturating_sub(1),
+            KeyCode::Down if self.plan_scroll + 1 < self.plan.len() => self.plan_scroll += 1,
+            KeyCode::PageUp => self.plan_scroll = self.plan_scroll.saturating_sub(10),
+            KeyCode::PageDown => {
+                self.plan_scroll = (self.plan_scroll + 10).min(self.plan.len().saturating_sub(1))
+            }
+            KeyCode::Char('e') | KeyCode::Char('E') => self.export(),
+            KeyCode::Char('w') | KeyCode::Char('W') => self.write_report(),
+            // As root this is a dry run, which stays one
+            KeyCode::Enter if !self.root => {
+                let allowed = self
+                    .plan
+                    .iter()
+                    .filter(|change| change.outcome == Outcome::Changes)
+                    .count();
+                if allowed == 0 {
+                    self.status = Some("Nothing here can be changed without root".to_string());
+                } else if self.plan.len() >= MAX_PLANNED {
+                    self.status = Some(
+                        "The simulation stopped early; select fewer files to apply it".to_string(),
+                    );
+                } else {
+                    self.apply_allowed_changes();
+                    return !self.failures.is_empty();
+                }
+            }
+            KeyCode::Esc | KeyCode::Backspace => {
+                // The outcomes no longer hold once the choice changes
+                self.plan.clear();
+                self.focus = Focus::UserList;
+                self.status = None;
+            }
+            KeyCode::Char('q') => return false,
+            _ => {}
+        }
+        true
+    }
+
+    pub fn handle_input(&mut self, key: KeyCode, modifiers: KeyModifiers) -> bool {
+        // The change is made; only leaving is left
+        if !self.failures.is_empty() {
+            return !matches!(key, KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q'));
+        }
+        if self.focus == Focus::Report {
+            return self.handle_report_input(key);
+        }
+        if self.focus == Focus::History {
+            return self.handle_history_input(key);
+        }
+        if self.show_affected {
+            match key {
+                KeyCode::Char('l') | KeyCode::Char('L') | KeyCode::Esc => {
+                    self.show_affected = false;
+                }
+                KeyCode::Char('r') | KeyCode::Char('R') => self.recursive = !self.recursive,
+                _ => {
+                    if let Some(ref mut view) = self.affected {
+                        view.handle_scroll(key);
+                    }
+                }
+            }
+            return true;
+        }
+        if let Some(ref mut picker) = self.picker {
+            match picker.handle_input(key) {
+                PickerOutcome::Continue => {}
+                PickerOutcome::Cancel => self.picker = None,
+                PickerOutcome::Pick(path) => {
+                    self.picker = None;
+                    self.use_reference(&path);
+                }
+            }
+            return true;
+        }
+        match key {
+            KeyCode::Char('e') if modifiers.contains(KeyModifiers::CONTROL) => self.export(),
+            KeyCode::Char('r') if modifiers.contains(KeyModifiers::CONTROL) => self.open_picker(),
+            KeyCode::Char('n') if modifiers.contains(KeyModifiers::CONTROL) => {
+                self.toggle_dry_run()
+            }
+            KeyCode::Char('u') if modifiers.contains(KeyModifiers::CONTROL) => self.open_history(),
+            KeyCode::Char('u') | KeyCode::Char('U') if self.focus == Focus::Options => {
+                self.open_history()
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') if self.focus == Focus::Options => {
+                self.toggle_dry_run()
+            }
+            KeyCode::Char('l') if modifiers.contains(KeyModifiers::CONTROL) => {
+                self.show_affected = true
+            }
+            KeyCode::Char('l') | KeyCode::Char('L') if self.focus == Focus::Options => {
+                self.show_affected = true
+            }
+            KeyCode::Char('f') | KeyCode::Char('F') if self.focus == Focus::Options => {
+                self.open_picker()
+            }
+            KeyCode::Char('e') | KeyCode::Char('E') if self.focus == Focus::Options => {
+                self.export()
+            }
             KeyCode::Tab => {
                 self.focus = match self.focus {
                     Focus::UserList => Focus::GroupList,
                     Focus::GroupList => Focus::Options,
                     Focus::Options => Focus::UserList,
                     Focus::Confirm => Focus::Confirm,
+                    Focus::Report => Focus::Report,
+                    Focus::History => Focus::History,
                 };
             }
             KeyCode::Up => {
@@ -704,6 +1731,25 @@ impl ChownInterface {
             KeyCode::Char(' ') if self.focus == Focus::Options => {
                 self.recursive = !self.recursive;
             }
+            KeyCode::Char('m') | KeyCode::Char('M') if self.focus == Focus::Options => {
+                self.scope = self.scope.next();
+            }
+            KeyCode::Char(c @ ('o' | 'O' | 'g' | 'G')) if self.focus == Focus::Options => {
+                let (mut owner, mut group) =
+                    (self.scope.changes_owner(), self.scope.changes_group());
+                if c.eq_ignore_ascii_case(&'o') {
+                    owner = !owner;
+                } else {
+                    group = !group;
+                }
+                match Scope::from_parts(owner, group) {
+                    Some(scope) => self.scope = scope,
+                    None => self.status = Some("Change the owner, the group or both".to_string()),
+                }
+            }
+            KeyCode::Char(c @ '1'..='9') if self.focus == Focus::Options => {
+                self.use_recent(c);
+            }
             KeyCode::Char('r') | KeyCode::Char('R') => {
                 self.recursive = !self.recursive;
             }
@@ -740,17 +1786,18 @@ impl ChownInterface {
                     _ => {}
                 }
             }
+            KeyCode::Enter if self.simulate => self.simulate_changes(&Caller::current()),
             KeyCode::Enter => {
                 if !self.warnings.is_empty() && self.focus != Focus::Confirm {
                     self.focus = Focus::Confirm;
                 } else {
                     self.apply_ownership_changes();
-                    return false; // Exit interface
+                    return !self.failures.is_empty(); // Exit unless refused
                 }
             }
             KeyCode::Char('y') | KeyCode::Char('Y') if self.focus == Focus::Confirm => {
                 self.apply_ownership_changes();
-                return false; // Exit interface
+                return !self.failures.is_empty(); // Exit unless refused
             }
             KeyCode::Char('n') | KeyCode::Char('N') if self.focus == Focus::Confirm => {
                 return false; // Exit without applying
@@ -768,85 +1815,795 @@ impl ChownInterface {
     }
 
     fn apply_ownership_changes(&mut self) {
-        // Get filtered lists
-        let filtered_users: Vec<&UserInfo> = self
-            .users
-            .iter()
-            .filter(|u| {
-                self.user_search.is_empty()
-                    || u.name
-                        .to_lowercase()
-                        .contains(&self.user_search.to_lowercase())
-            })
-            .collect();
+        let Some(target) = self.target() else {
+            return;
+        };
+        if let Some(owner) = self.owner_spec() {
+            let _ = self.recent.record_owner(&owner, self.recursive);
+            self.applied = Some((owner, self.recursive));
+        }
 
-        let filtered_groups: Vec<&GroupInfo> = self
-            .groups
-            .iter()
-            .filter(|g| {
-                self.group_search.is_empty()
-                    || g.name
-                        .to_lowercase()
-                        .contains(&self.group_search.to_lowercase())
-            })
-            .collect();
+        let mut changes = Vec::new();
+        let mut failures = Vec::new();
+        for path in &self.selected_paths {
+            // Selected links are followed, as chown does
+            Self::change_ownership(path, target, true, &mut changes, &mut failures);
 
-        // Get the actual selected items from filtered lists
-        let selected_user = filtered_users.get(
-            self.selected_user_idx
-                .min(filtered_users.len().saturating_sub(1)),
-        );
-        let selected_group = filtered_groups.get(
-            self.selected_group_idx
-                .min(filtered_groups.len().saturating_sub(1)),
-        );
+            // If recursive and directory, apply to contents
+            if self.recursive && path.is_dir() {
+                Self::apply_recursive(path, target, &mut changes, &mut failures);
+            }
+        }
+        self.record_batch(changes);
+        self.failures = failures;
+    }
 
-        if let (Some(&user), Some(&group)) = (selected_user, selected_group) {
-            for path in &self.selected_paths {
-                let (old_uid, old_gid) = Self::get_file_ownership(path);
-
-                // Record the change in history
-                self.history.push(OwnershipChange {
-                    path: path.clone(),
-                    old_uid,
-                    old_gid,
-                    new_uid: user.uid,
-                    new_gid: group.gid,
-                    timestamp: std::time::SystemTime::now(),
-                });
-
-                // Apply the ownership change
-                self.change_ownership(path, user.uid, group.gid);
-
-                // If recursive and directory, apply to contents
-                if self.recursive && path.is_dir() {
-                    self.apply_recursive(path, user.uid, group.gid);
-                }
+    /// Without root: make the changes the simulation found allowed, one
+    /// file at a time, leaving out those it found would fail
+    fn apply_allowed_changes(&mut self) {
+        let Some(target) = self.target() else {
+            return;
+        };
+        if let Some(owner) = self.owner_spec() {
+            let _ = self.recent.record_owner(&owner, self.recursive);
+            self.applied = Some((owner, self.recursive));
+        }
+
+        let mut changes = Vec::new();
+        let mut failures = Vec::new();
+        for change in &self.plan {
+            if change.outcome == Outcome::Changes {
+                Self::change_ownership(
+                    &change.path,
+                    target,
+                    change.follow,
+                    &mut changes,
+                    &mut failures,
+                );
             }
         }
+        self.record_batch(changes);
+        self.failures = failures;
     }
 
-    fn change_ownership(&self, _path: &PathBuf, _uid: u32, _gid: u32) {
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs;
-            let _ = fs::chown(_path, Some(_uid), Some(_gid));
+    /// Keep what was changed in the history, to be rolled back later
+    fn record_batch(&mut self, mut changes: Vec<OwnershipChange>) {
+        let truncated = changes.len() > MAX_RECORDED;
+        changes.truncate(MAX_RECORDED);
+        let batch = OwnershipBatch {
+            owner: self.owner_spec().unwrap_or_default(),
+            timestamp: SystemTime::now(),
+            changes,
+            truncated,
+            rolled_back: false,
+        };
+        if let Err(e) = self.history.record(batch) {
+            self.status = Some(format!("Failed to save the ownership history: {}", e));
         }
     }
 
-    fn apply_recursive(&self, _dir: &PathBuf, _uid: u32, _gid: u32) {
-        #[cfg(unix)]
-        {
-            use std::fs;
-            if let Ok(entries) = fs::read_dir(_dir) {
+    /// Chown `path`, noting the change in `changes` or the refusal in
+    /// `failures`; without `follow` a symlink is changed itself, as
+    /// `chown -R` does below the selection
+    fn change_ownership(
+        path: &Path,
+        target: (Option<u32>, Option<u32>),
+        follow: bool,
+        changes: &mut Vec<OwnershipChange>,
+        failures: &mut Vec<Failure>,
+    ) {
+        let metadata = if follow {
+            fs::metadata(path)
+        } else {
+            fs::symlink_metadata(path)
+        };
+        let link = metadata
+            .as_ref()
+            .is_ok_and(|metadata| metadata.file_type().is_symlink());
+        let outcome = metadata.and_then(|metadata| {
+            let old = (metadata.uid(), metadata.gid());
+            if link {
+                std::os::unix::fs::lchown(path, target.0, target.1)?;
+            } else {
+                std::os::unix::fs::chown(path, target.0, target.1)?;
+            }
+            Ok(old)
+        });
+        match outcome {
+            Ok(old) => {
+                let new = resolve(old, target);
+                if old != new && changes.len() <= MAX_RECORDED {
+                    changes.push(OwnershipChange {
+                        path: path.to_path_buf(),
+                        old_uid: old.0,
+                        old_gid: old.1,
+                        new_uid: new.0,
+                        new_gid: new.1,
+                        link,
+                        rolled_back: false,
+                    });
+                }
+            }
+            Err(e) => failures.push((path.to_path_buf(), e)),
+        }
+    }
+
+    /// Chown everything below `dir` like `chown -R -P`: symlinks are
+    /// changed themselves and never followed, matching the simulation and
+    /// the preview
+    fn apply_recursive(
+        dir: &Path,
+        target: (Option<u32>, Option<u32>),
+        changes: &mut Vec<OwnershipChange>,
+        failures: &mut Vec<Failure>,
+    ) {
+        match fs::read_dir(dir) {
+            Ok(entries) => {
                 for entry in entries.flatten() {
                     let path = entry.path();
-                    self.change_ownership(&path, _uid, _gid);
-                    if path.is_dir() {
-                        self.apply_recursive(&path, _uid, _gid);
+                    Self::change_ownership(&path, target, false, changes, failures);
+                    if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                        Self::apply_recursive(&path, target, changes, failures);
                     }
                 }
             }
+            Err(e) => failures.push((dir.to_path_buf(), e)),
+        }
+    }
+}
+
+/// Run `chown` on `paths` with an owner argument as the interface builds
+/// it: "user:group", "user" or ":group"
+pub fn chown_paths(paths: &[PathBuf], owner: &str, recursive: bool) -> io::Result<()> {
+    let mut command = std::process::Command::new("chown");
+    if recursive {
+        command.arg("-R");
+    }
+    let output = command.arg("--").arg(owner).args(paths).output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(io::Error::other(
+            stderr.lines().next().unwrap_or("chown failed").to_string(),
+        ))
+    }
+}
+
+/// The ownership a file owned by `old` ends up with, keeping what `target`
+/// leaves alone
+fn resolve(old: (u32, u32), target: (Option<u32>, Option<u32>)) -> (u32, u32) {
+    (target.0.unwrap_or(old.0), target.1.unwrap_or(old.1))
+}
+
+/// Add `path`, and with `recursive` everything below it, to `plan`; a
+/// symlink is followed only when `follow`, as it is for selected paths
+fn plan_path(
+    path: &Path,
+    new: (Option<u32>, Option<u32>),
+    recursive: bool,
+    follow: bool,
+    caller: &Caller,
+    plan: &mut Vec<PlannedChange>,
+) {
+    if plan.len() >= MAX_PLANNED {
+        return;
+    }
+    let metadata = if follow {
+        fs::metadata(path)
+    } else {
+        fs::symlink_metadata(path)
+    };
+    plan.push(match &metadata {
+        Ok(metadata) => {
+            let old = (metadata.uid(), metadata.gid());
+            PlannedChange {
+                path: path.to_path_buf(),
+                old: Some(old),
+                outcome: caller.predict(old, resolve(old, new)),
+                follow,
+            }
+        }
+        Err(e) => PlannedChange {
+            path: path.to_path_buf(),
+            old: None,
+            outcome: Outcome::Fails(e.to_string()),
+            follow,
+        },
+    });
+
+    let is_dir = metadata.is_ok_and(|m| m.is_dir());
+    if recursive && is_dir {
+        match fs::read_dir(path) {
+            Ok(entries) => {
+                let mut children: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+                children.sort();
+                for child in children {
+                    plan_path(&child, new, recursive, false, caller, plan);
+                }
+            }
+            Err(e) => plan.push(PlannedChange {
+                path: path.join("*"),
+                old: None,
+                outcome: Outcome::Fails(format!("cannot list: {}", e)),
+                follow: false,
+            }),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::managers::access::tests::other_groups;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_predict_without_root() {
+        let caller = Caller {
+            uid: 1000,
+            groups: vec![1000, 27],
+        };
+        assert_eq!(
+            caller.predict((1000, 1000), (1000, 1000)),
+            Outcome::Unchanged
+        );
+        assert_eq!(caller.predict((1000, 1000), (1000, 27)), Outcome::Changes);
+        assert!(matches!(
+            caller.predict((1000, 1000), (0, 1000)),
+            Outcome::Fails(ref reason) if reason.contains("only root")
+        ));
+        assert_eq!(
+            caller.predict((1001, 1001), (1001, 27)),
+            Outcome::Fails("not the owner".to_string())
+        );
+        assert_eq!(
+            caller.predict((1000, 1000), (1000, 50)),
+            Outcome::Fails("not a member of the new group".to_string())
+        );
+        let root = Caller {
+            uid: 0,
+            groups: vec![0],
+        };
+        assert_eq!(root.predict((1000, 1000), (0, 0)), Outcome::Changes);
+    }
+
+    #[test]
+    fn test_simulation_plans_and_exports() {
+        let dir = TempDir::new().unwrap();
+        let project = dir.path().join("it's here");
+        fs::create_dir(&project).unwrap();
+        fs::write(project.join("a.txt"), "a").unwrap();
+        let (uid, gid) = ownership(&project, true).unwrap();
+
+        let mut chown = ChownInterface::new(vec![project.clone()], true, RecentChoices::default());
+        chown.users = vec![
+            UserInfo {
+                uid,
+                name: "me".to_string(),
+                full_name: None,
+            },
+            UserInfo {
+                uid: uid + 1,
+                name: "other".to_string(),
+                full_name: None,
+            },
+        ];
+        chown.groups = vec![GroupInfo {
+            gid,
+            name: "mine".to_string(),
+        }];
+        chown.selected_user_idx = 1;
+        chown.selected_group_idx = 0;
+        chown.recursive = true;
+
+        // Some other unprivileged user, whoever runs the tests
+        let caller = Caller {
+            uid: uid + 2,
+            groups: vec![gid],
+        };
+        chown.simulate_changes(&caller);
+        assert_eq!(chown.focus, Focus::Report);
+        assert_eq!(chown.plan.len(), 2);
+        assert_eq!(chown.plan[1].path, project.join("a.txt"));
+        assert!(chown
+            .plan
+            .iter()
+            .all(|change| matches!(change.outcome, Outcome::Fails(_))));
+        // Nothing was changed
+        assert_eq!(ownership(&project, true).unwrap(), (uid, gid));
+
+        let script = dir.path().join("chown.sh");
+        chown.export_to(&script).unwrap();
+        let script = fs::read_to_string(script).unwrap();
+        assert!(script.starts_with("#!/bin/sh\n# Generated by fsnav's chown interface\n"));
+        assert!(script.ends_with(&format!(
+            "sudo chown -R -- 'other:mine' '{}/it'\\''s here'\n",
+            dir.path().display()
+        )));
+        assert_eq!(script.matches("# needs root:").count(), 2);
+
+        chown.handle_input(KeyCode::Esc, KeyModifiers::NONE);
+        assert_eq!(chown.focus, Focus::UserList);
+        assert!(chown.plan.is_empty());
+
+        // As root the exported command runs as is
+        chown.simulate = false;
+        assert!(chown
+            .command()
+            .unwrap()
+            .starts_with("chown -R -- 'other:mine' "));
+    }
+
+    #[test]
+    fn test_export_escapes_comment_lines() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("x\ntouch pwned");
+        fs::write(&file, "x").unwrap();
+        let (uid, gid) = ownership(&file, true).unwrap();
+
+        let mut chown = ChownInterface::new(vec![file.clone()], true, RecentChoices::default());
+        chown.users = vec![UserInfo {
+            uid: uid + 1,
+            name: "other".to_string(),
+            full_name: None,
+        }];
+        chown.groups = vec![GroupInfo {
+            gid,
+            name: "mine".to_string(),
+        }];
+        chown.simulate_changes(&Caller {
+            uid: uid + 2,
+            groups: vec![gid],
+        });
+
+        let script = dir.path().join("chown.sh");
+        chown.export_to(&script).unwrap();
+        let script = fs::read_to_string(script).unwrap();
+        assert!(script.contains(&format!(
+            "# needs root: {}/x\\ntouch pwned (",
+            dir.path().display()
+        )));
+        // The only line not a comment is the chown, whose quotes hold the
+        // newline
+        let commands: Vec<&str> = script
+            .lines()
+            .filter(|line| !line.starts_with('#') && *line != "set -e")
+            .collect();
+        assert_eq!(
+            commands,
+            [
+                format!("sudo chown -- 'other:mine' '{}/x", dir.path().display()).as_str(),
+                "touch pwned'"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_owner_or_group_only() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("a.txt");
+        fs::write(&file, "a").unwrap();
+        let (uid, gid) = ownership(&file, true).unwrap();
+
+        let mut chown = ChownInterface::new(vec![file.clone()], true, RecentChoices::default());
+        chown.users = vec![UserInfo {
+            uid: uid + 1,
+            name: "other".to_string(),
+            full_name: None,
+        }];
+        chown.groups = vec![GroupInfo {
+            gid: gid + 1,
+            name: "team".to_string(),
+        }];
+        chown.selected_user_idx = 0;
+        chown.selected_group_idx = 0;
+        // Someone else who belongs to the new group, whoever runs the tests
+        let caller = Caller {
+            uid: uid + 2,
+            groups: vec![gid, gid + 1],
+        };
+
+        chown.focus = Focus::Options;
+        chown.handle_input(KeyCode::Char('m'), KeyModifiers::NONE);
+        assert_eq!(chown.scope, Scope::OwnerOnly);
+        assert!(chown
+            .command()
+            .unwrap()
+            .starts_with("sudo chown -- 'other' "));
+        chown.simulate_changes(&caller);
+        assert!(matches!(
+            chown.plan[0].outcome,
+            Outcome::Fails(ref reason) if reason.contains("only root")
+        ));
+
+        chown.handle_input(KeyCode::Esc, KeyModifiers::NONE);
+        chown.focus = Focus::Options;
+        chown.handle_input(KeyCode::Char('m'), KeyModifiers::NONE);
+        assert_eq!(chown.scope, Scope::GroupOnly);
+        assert!(chown
+            .command()
+            .unwrap()
+            .starts_with("sudo chown -- ':team' "));
+        // The owner stays, so only the file's owner may move the group
+        chown.simulate_changes(&caller);
+        assert_eq!(
+            chown.plan[0].outcome,
+            Outcome::Fails("not the owner".to_string())
+        );
+        assert_eq!(resolve((uid, gid), chown.target().unwrap()), (uid, gid + 1));
+
+        // The same through the checkboxes, which cannot both be cleared
+        chown.focus = Focus::Options;
+        chown.handle_input(KeyCode::Char('o'), KeyModifiers::NONE);
+        assert_eq!(chown.scope, Scope::Both);
+        chown.handle_input(KeyCode::Char('g'), KeyModifiers::NONE);
+        assert_eq!(chown.scope, Scope::OwnerOnly);
+        assert_eq!(chown.target(), Some((Some(uid + 1), None)));
+        chown.handle_input(KeyCode::Char('o'), KeyModifiers::NONE);
+        assert_eq!(chown.scope, Scope::OwnerOnly);
+        assert_eq!(
+            chown.status.as_deref(),
+            Some("Change the owner, the group or both")
+        );
+        chown.handle_input(KeyCode::Char('g'), KeyModifiers::NONE);
+        chown.handle_input(KeyCode::Char('o'), KeyModifiers::NONE);
+        assert_eq!(chown.scope, Scope::GroupOnly);
+
+        // Nothing to change to in the part that is changed
+        chown.groups.clear();
+        chown.handle_input(KeyCode::Esc, KeyModifiers::NONE);
+        chown.simulate_changes(&caller);
+        assert_eq!(
+            chown.status.as_deref(),
+            Some("Pick the new group only first")
+        );
+    }
+
+    #[test]
+    fn test_apply_allowed_changes() {
+        let dir = TempDir::new().unwrap();
+        let mine = dir.path().join("mine.txt");
+        let theirs = dir.path().join("theirs.txt");
+        fs::write(&mine, "").unwrap();
+        fs::write(&theirs, "").unwrap();
+        let (uid, gid) = ownership(&mine, true).unwrap();
+        let Some(team) = other_groups(gid, 1).map(|groups| groups[0]) else {
+            return;
+        };
+
+        let mut chown = ChownInterface::new(
+            vec![mine.clone(), theirs.clone()],
+            true,
+            RecentChoices::default(),
+        );
+        chown.groups = vec![GroupInfo {
+            gid: team,
+            name: "team".to_string(),
+        }];
+        chown.selected_group_idx = 0;
+        chown.scope = Scope::GroupOnly;
+        // The owner of the files, in the new group
+        chown.simulate_changes(&Caller {
+            uid,
+            groups: vec![gid, team],
+        });
+        // As if someone else owned the second file
+        chown.plan[1].outcome = Outcome::Fails("not the owner".to_string());
+
+        // Only the allowed change is made, and the interface closes
+        assert!(!chown.handle_input(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(ownership(&mine, true).unwrap(), (uid, team));
+        assert_eq!(ownership(&theirs, true).unwrap(), (uid, gid));
+        assert_eq!(chown.history.len(), 1);
+        assert_eq!(chown.applied(), Some((":team", false)));
+
+        // Rolled back from the history
+        chown.focus = Focus::UserList;
+        chown.handle_input(KeyCode::Char('u'), KeyModifiers::CONTROL);
+        assert_eq!(chown.focus, Focus::History);
+        // Nothing happens before y
+        assert!(chown.handle_input(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(ownership(&mine, true).unwrap(), (uid, team));
+        chown.handle_input(KeyCode::Char('n'), KeyModifiers::NONE);
+        assert_eq!(chown.status.as_deref(), Some("Roll back cancelled"));
+        assert_eq!(ownership(&mine, true).unwrap(), (uid, team));
+        chown.handle_input(KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(
+            chown.status.as_deref(),
+            Some("Give 1 file(s) changed to :team their previous owner back? (y/n)")
+        );
+        assert!(chown.handle_input(KeyCode::Char('y'), KeyModifiers::NONE));
+        assert_eq!(ownership(&mine, true).unwrap(), (uid, gid));
+        assert_eq!(
+            chown.status.as_deref(),
+            Some("Restored the previous owner of 1 file(s)")
+        );
+        chown.handle_input(KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(chown.status.as_deref(), Some("Already rolled back"));
+        chown.handle_input(KeyCode::Esc, KeyModifiers::NONE);
+        assert_eq!(chown.focus, Focus::UserList);
+
+        // Refused paths keep it open until they have been read
+        chown.failures = vec![(theirs, io::Error::from_raw_os_error(libc::EPERM))];
+        assert!(chown.handle_input(KeyCode::Down, KeyModifiers::NONE));
+        assert!(!chown.handle_input(KeyCode::Esc, KeyModifiers::NONE));
+    }
+
+    #[test]
+    fn test_recursive_chown_does_not_follow_links() {
+        let dir = TempDir::new().unwrap();
+        let tree = dir.path().join("tree");
+        let outside = dir.path().join("outside");
+        fs::create_dir(&tree).unwrap();
+        fs::create_dir(&outside).unwrap();
+        fs::write(tree.join("a.txt"), "").unwrap();
+        fs::write(outside.join("secret"), "").unwrap();
+        let link = tree.join("link");
+        std::os::unix::fs::symlink(&outside, &link).unwrap();
+        let (uid, gid) = ownership(&tree, true).unwrap();
+        let Some(team) = other_groups(gid, 1).map(|groups| groups[0]) else {
+            return;
+        };
+        let target = (None, Some(team));
+
+        let mut plan = Vec::new();
+        let caller = Caller {
+            uid: 0,
+            groups: Vec::new(),
+        };
+        plan_path(&tree, target, true, true, &caller, &mut plan);
+
+        let mut changes = Vec::new();
+        let mut failures = Vec::new();
+        ChownInterface::change_ownership(&tree, target, true, &mut changes, &mut failures);
+        ChownInterface::apply_recursive(&tree, target, &mut changes, &mut failures);
+        assert!(failures.is_empty());
+        assert_eq!(ownership(&tree.join("a.txt"), true).unwrap(), (uid, team));
+        // The link itself changed, not the directory it points to
+        assert_eq!(fs::symlink_metadata(&link).unwrap().gid(), team);
+        assert_eq!(ownership(&outside, true).unwrap(), (uid, gid));
+        assert_eq!(
+            ownership(&outside.join("secret"), true).unwrap(),
+            (uid, gid)
+        );
+
+        // Exactly what the simulation planned
+        let mut planned: Vec<&Path> = plan.iter().map(|change| change.path.as_path()).collect();
+        let mut changed: Vec<&Path> = changes.iter().map(|change| change.path.as_path()).collect();
+        planned.sort();
+        changed.sort();
+        assert_eq!(planned, changed);
+
+        // Rolling back restores the link, still without following it
+        let mut history = OwnershipHistory::default();
+        history
+            .record(OwnershipBatch {
+                owner: ":team".to_string(),
+                timestamp: SystemTime::now(),
+                changes,
+                truncated: false,
+                rolled_back: false,
+            })
+            .unwrap();
+        let (restored, failures) = history.roll_back(0, &[0, 1, 2]);
+        assert_eq!((restored, failures.len()), (3, 0));
+        assert_eq!(fs::symlink_metadata(&link).unwrap().gid(), gid);
+        assert_eq!(ownership(&outside, true).unwrap(), (uid, gid));
+    }
+
+    #[test]
+    fn test_roll_back_marked_files() {
+        let dir = TempDir::new().unwrap();
+        let first = dir.path().join("first.txt");
+        let second = dir.path().join("second.txt");
+        fs::write(&first, "").unwrap();
+        fs::write(&second, "").unwrap();
+        let (uid, gid) = ownership(&first, true).unwrap();
+        let Some(staff) = other_groups(gid, 1).map(|groups| groups[0]) else {
+            return;
+        };
+        let change = |path: &Path| OwnershipChange {
+            path: path.to_path_buf(),
+            old_uid: uid,
+            old_gid: staff,
+            new_uid: uid,
+            new_gid: gid,
+            link: false,
+            rolled_back: false,
+        };
+        let mut history = OwnershipHistory::default();
+        history
+            .record(OwnershipBatch {
+                owner: ":staff".to_string(),
+                timestamp: SystemTime::now(),
+                changes: vec![change(&first), change(&second)],
+                truncated: false,
+                rolled_back: false,
+            })
+            .unwrap();
+
+        let mut chown = ChownInterface::new(vec![first.clone()], false, RecentChoices::default())
+            .with_history(history);
+        chown.root = true;
+        chown.simulate = false;
+        chown.open_history();
+        // Into the files, onto the second one, marked
+        for key in [
+            KeyCode::Tab,
+            KeyCode::Down,
+            KeyCode::Char(' '),
+            KeyCode::Enter,
+        ] {
+            chown.handle_input(key, KeyModifiers::NONE);
+        }
+        assert_eq!(chown.history_confirm.as_deref(), Some(&[1][..]));
+        chown.handle_input(KeyCode::Char('y'), KeyModifiers::NONE);
+        assert_eq!(ownership(&first, true).unwrap(), (uid, gid));
+        assert_eq!(ownership(&second, true).unwrap(), (uid, staff));
+
+        // With nothing marked, Enter offers what is left
+        chown.handle_input(KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(chown.history_confirm.as_deref(), Some(&[0][..]));
+    }
+
+    #[test]
+    fn test_dry_run() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("a.txt");
+        fs::write(&file, "a").unwrap();
+        let (uid, gid) = ownership(&file, true).unwrap();
+
+        // Root: the dry run can be switched on and off
+        let mut chown = ChownInterface::new(vec![file.clone()], false, RecentChoices::default());
+        chown.users = vec![UserInfo {
+            uid: uid + 1,
+            name: "other".to_string(),
+            full_name: None,
+        }];
+        chown.groups = vec![GroupInfo {
+            gid,
+            name: "mine".to_string(),
+        }];
+        chown.selected_user_idx = 0;
+        chown.selected_group_idx = 0;
+        chown.handle_input(KeyCode::Char('n'), KeyModifiers::CONTROL);
+        assert!(chown.simulate);
+        chown.simulate_changes(&Caller {
+            uid: 0,
+            groups: Vec::new(),
+        });
+        assert_eq!(chown.plan[0].outcome, Outcome::Changes);
+        assert_eq!(ownership(&file, true).unwrap(), (uid, gid));
+
+        let report = dir.path().join("report.txt");
+        chown.write_report_to(&report).unwrap();
+        assert_eq!(
+            fs::read_to_string(&report).unwrap(),
+            format!(
+                "# Dry run of fsnav's chown interface: nothing was changed\n\
+                 # chown -- 'other:mine' '{0}'\n\
+                 ✓ {0}  {1}:mine → other:mine\n",
+                file.display(),
+                uid
+            )
+        );
+
+        chown.handle_input(KeyCode::Esc, KeyModifiers::NONE);
+        chown.focus = Focus::Options;
+        chown.handle_input(KeyCode::Char('n'), KeyModifiers::NONE);
+        assert!(!chown.simulate);
+
+        // Without root there is nothing but the simulation
+        let mut chown = ChownInterface::new(vec![file], true, RecentChoices::default());
+        chown.handle_input(KeyCode::Char('n'), KeyModifiers::CONTROL);
+        assert!(chown.simulate);
+        assert_eq!(
+            chown.status.as_deref(),
+            Some("Only root can turn the simulation off")
+        );
+    }
+
+    #[test]
+    fn test_reference_file() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("a.txt");
+        let reference = dir.path().join("b.txt");
+        fs::write(&file, "a").unwrap();
+        fs::write(&reference, "b").unwrap();
+        let (uid, gid) = ownership(&reference, true).unwrap();
+
+        let mut chown = ChownInterface::new(vec![file.clone()], true, RecentChoices::default());
+        chown.users = vec![
+            UserInfo {
+                uid: uid + 1,
+                name: "other".to_string(),
+                full_name: None,
+            },
+            UserInfo {
+                uid,
+                name: "owner".to_string(),
+                full_name: None,
+            },
+        ];
+        chown.groups = vec![GroupInfo {
+            gid,
+            name: "team".to_string(),
+        }];
+        chown.selected_user_idx = 0;
+        chown.user_search = "oth".to_string();
+        chown.scope = Scope::GroupOnly;
+
+        chown.handle_input(KeyCode::Char('r'), KeyModifiers::CONTROL);
+        assert!(chown.picker.is_some());
+        // a.txt, then b.txt
+        chown.handle_input(KeyCode::Down, KeyModifiers::NONE);
+        chown.handle_input(KeyCode::Enter, KeyModifiers::NONE);
+        assert!(chown.picker.is_none());
+        assert_eq!(chown.scope, Scope::Both);
+        assert!(chown.user_search.is_empty());
+        assert_eq!(chown.target(), Some((Some(uid), Some(gid))));
+        assert_eq!(
+            chown.command().unwrap(),
+            format!(
+                "sudo chown --reference='{}' -- '{}'",
+                reference.display(),
+                file.display()
+            )
+        );
+
+        // Picking another user drops the reference
+        chown.focus = Focus::UserList;
+        chown.handle_input(KeyCode::Up, KeyModifiers::NONE);
+        assert!(chown
+            .command()
+            .unwrap()
+            .starts_with("sudo chown -- 'other:team' "));
+    }
+
+    #[test]
+    fn test_recent_owners() {
+        let mut recent = RecentChoices::default();
+        recent.record_owner(":staff", false).unwrap();
+        recent.record_owner("bob:web", true).unwrap();
+        recent.record_owner("gone:web", true).unwrap();
+
+        let mut chown = ChownInterface::new(Vec::new(), false, recent);
+        // Recursive sticks from the last change
+        assert!(chown.recursive);
+        chown.users = ["alice", "bob"]
+            .iter()
+            .enumerate()
+            .map(|(uid, name)| UserInfo {
+                uid: uid as u32,
+                name: name.to_string(),
+                full_name: None,
+            })
+            .collect();
+        chown.groups = ["staff", "web"]
+            .iter()
+            .enumerate()
+            .map(|(gid, name)| GroupInfo {
+                gid: gid as u32,
+                name: name.to_string(),
+            })
+            .collect();
+        chown.user_search = "ali".to_string();
+
+        // Digits pick recent owners only from the options
+        chown.handle_input(KeyCode::Char('2'), KeyModifiers::NONE);
+        assert_eq!(chown.user_search, "ali2");
+        chown.focus = Focus::Options;
+        chown.handle_input(KeyCode::Char('2'), KeyModifiers::NONE);
+        assert_eq!(chown.owner_spec().as_deref(), Some("bob:web"));
+        chown.handle_input(KeyCode::Char('3'), KeyModifiers::NONE);
+        assert_eq!(chown.scope, Scope::GroupOnly);
+        assert_eq!(chown.owner_spec().as_deref(), Some(":staff"));
+        chown.handle_input(KeyCode::Char('1'), KeyModifiers::NONE);
+        assert_eq!(
+            chown.status.as_deref(),
+            Some("gone:web is no longer a known owner")
+        );
+        assert_eq!(chown.owner_spec().as_deref(), Some(":staff"));
+    }
+}
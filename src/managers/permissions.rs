@@ -3,16 +3,78 @@ use crossterm::{
     cursor::MoveTo,
     event::KeyCode,
     execute,
-    style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor},
+    style::{Color, Print, ResetColor},
     terminal,
 };
 use std::{
+    fs,
     io::{self, Write},
     os::unix::fs::PermissionsExt,
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
+use super::access::{render_failures, summarize, Failure};
+use super::affected::{Affected, AffectedView};
+use super::picker::{PickerOutcome, ReferencePicker};
+use super::recent::{recent_hint, recent_index, RecentChoices};
+use super::script::{report_path, script_path, write_report, write_script};
+use crate::config::PermissionTemplate;
+use crate::custom_commands::shell_quote;
+use crate::ui::{SetBackgroundColor, SetForegroundColor};
+use crate::utils::truncate_chars;
+
+/// Templates listed first in template mode: mode, name and what it suits
+const BUILTIN_TEMPLATES: [([u8; 3], &str, &str); 10] = [
+    (
+        [7, 5, 5],
+        "Standard (rwxr-xr-x)",
+        "Executables and directories",
+    ),
+    ([6, 4, 4], "Read Only (rw-r--r--)", "Regular files"),
+    (
+        [6, 0, 0],
+        "Private (rw-------)",
+        "Sensitive files, owner only",
+    ),
+    (
+        [7, 0, 0],
+        "Private Exec (rwx------)",
+        "Private scripts/directories",
+    ),
+    ([7, 7, 5], "Group Share (rwxrwxr-x)", "Shared directories"),
+    ([6, 6, 4], "Group Write (rw-rw-r--)", "Collaborative files"),
+    ([6, 6, 6], "All Write (rw-rw-rw-)", "Temporary/log files"),
+    (
+        [7, 7, 7],
+        "Full Access (rwxrwxrwx)",
+        "⚠️ DANGEROUS - Everyone has full access",
+    ),
+    (
+        [4, 0, 0],
+        "Read Only Owner (r--------)",
+        "Protected configs",
+    ),
+    (
+        [5, 0, 0],
+        "Exec Only Owner (r-x------)",
+        "Protected scripts",
+    ),
+];
+
+/// Templates shown at once; the list scrolls past them
+const TEMPLATE_ROWS: usize = 15;
+
+/// An entry of the template list
 #[derive(Debug, Clone)]
+struct Template {
+    digits: [u8; 3],
+    name: String,
+    description: String,
+    /// From `[[chmod_templates]]` in `config.toml`
+    custom: bool,
+}
+
+#[derive(Debug)]
 pub struct ChmodInterface {
     // Current chmod value as 3 digits (e.g., [7, 5, 5] for 755)
     digits: [u8; 3],
@@ -24,11 +86,43 @@ pub struct ChmodInterface {
     preview_mode: bool,
     // Template mode
     show_templates: bool,
+    templates: Vec<Template>,
     template_index: usize,
+    // Result of the last export
+    status: Option<String>,
+    /// Open while choosing a reference file
+    picker: Option<ReferencePicker>,
+    /// File the digits were copied from, with those digits
+    reference: Option<(PathBuf, [u8; 3])>,
+    /// Modes applied before, picked again with 1-5
+    recent: RecentChoices,
+    /// Mode, file mode and recursion applied when the interface closed
+    /// with Enter
+    applied: Option<(String, Option<String>, bool)>,
+    /// Apply through whole directory trees, as `chmod -R`
+    recursive: bool,
+    /// Mode for files, while `digits` are the mode for directories
+    file_digits: Option<[u8; 3]>,
+    /// Whether the arrows change `file_digits`
+    editing_files: bool,
+    /// Execute bits apply only to directories and to files someone can
+    /// already execute, as `X` does
+    capital_x: bool,
+    /// Symbolic mode being typed, such as "u+x,go-w"
+    symbolic: Option<String>,
+    /// Walk of the entries Enter would reach, restarted when recursion is
+    /// toggled
+    affected: Option<AffectedView>,
+    /// Whether the list of changes takes the place of the digits
+    show_affected: bool,
+    /// Enter lists the changes instead of making them
+    dry_run: bool,
+    /// Paths Enter could not change, listed until the interface closes
+    failures: Vec<Failure>,
 }
 
 impl ChmodInterface {
-    pub fn new(selected_paths: Vec<PathBuf>) -> Self {
+    pub fn new(selected_paths: Vec<PathBuf>, recent: RecentChoices) -> Self {
         // Try to get current permissions from first file
         let initial_digits = if let Some(first_path) = selected_paths.first() {
             if let Ok(metadata) = first_path.metadata() {
@@ -51,13 +145,75 @@ impl ChmodInterface {
             selected_paths,
             preview_mode: true,
             show_templates: false,
+            templates: BUILTIN_TEMPLATES
+                .iter()
+                .map(|(digits, name, description)| Template {
+                    digits: *digits,
+                    name: name.to_string(),
+                    description: description.to_string(),
+                    custom: false,
+                })
+                .collect(),
             template_index: 0,
+            status: None,
+            picker: None,
+            reference: None,
+            recent,
+            applied: None,
+            symbolic: None,
+            recursive: false,
+            file_digits: None,
+            editing_files: false,
+            capital_x: false,
+            affected: None,
+            show_affected: false,
+            dry_run: false,
+            failures: Vec::new(),
         }
     }
 
-    pub fn render(&self) -> Result<()> {
+    /// Add the templates of `config.toml` after the built-in ones; those
+    /// without a valid three-digit octal mode are left out and named in
+    /// the status line
+    pub fn with_templates(mut self, templates: &[PermissionTemplate]) -> Self {
+        let mut invalid = Vec::new();
+        for template in templates {
+            match parse_mode(&template.mode) {
+                Some(digits) => self.templates.push(Template {
+                    digits,
+                    name: template.name.clone(),
+                    description: template.description.clone(),
+                    custom: true,
+                }),
+                None => invalid.push(format!("{} ({})", template.name, template.mode)),
+            }
+        }
+        if !invalid.is_empty() {
+            self.status = Some(format!(
+                "Skipped chmod templates without a valid octal mode: {}",
+                invalid.join(", ")
+            ));
+        }
+        self
+    }
+
+    /// Take in the walk of the affected entries, starting it over when
+    /// recursion was toggled
+    pub fn poll(&mut self) {
+        match self.affected {
+            Some(ref mut view) if view.walk.recursive() == self.recursive => view.walk.poll(),
+            _ => {
+                self.affected = Some(AffectedView::new(
+                    self.selected_paths.clone(),
+                    self.recursive,
+                ))
+            }
+        }
+    }
+
+    pub fn render(&mut self) -> Result<()> {
         let mut stdout = io::stdout();
-        let (_terminal_width, _) = terminal::size()?;
+        let (terminal_width, _) = terminal::size()?;
 
         // Clear and setup
         execute!(stdout, terminal::Clear(terminal::ClearType::All))?;
@@ -112,7 +268,26 @@ impl ChmodInterface {
             )?;
         }
 
-        if self.show_templates {
+        if let Some((ref path, _)) = self.active_reference() {
+            execute!(
+                stdout,
+                MoveTo(0, 8),
+                SetForegroundColor(Color::Cyan),
+                Print(format!("📎 Reference: {}", path.display())),
+                ResetColor
+            )?;
+        }
+
+        let change = self.mode_change();
+        if !self.failures.is_empty() {
+            render_failures(&mut stdout, 9, terminal_width, 18, &self.failures)?;
+        } else if let Some(ref picker) = self.picker {
+            picker.render(&mut stdout, 9, terminal_width, 18)?;
+        } else if let Some(view) = self.affected.as_mut().filter(|_| self.show_affected) {
+            view.render(&mut stdout, 9, terminal_width, 18, change, describe_modes)?;
+        } else if let Some(ref expr) = self.symbolic {
+            self.render_symbolic(&mut stdout, expr, 9)?;
+        } else if self.show_templates {
             self.render_templates(&mut stdout)?;
         } else {
             // Chmod selector interface
@@ -125,6 +300,16 @@ impl ChmodInterface {
             self.render_explanation(&mut stdout, 22)?;
         }
 
+        if let Some(hint) = recent_hint(self.recent.modes()) {
+            execute!(
+                stdout,
+                MoveTo(5, 27),
+                SetForegroundColor(Color::DarkGrey),
+                Print(hint),
+                ResetColor
+            )?;
+        }
+
         // Controls - moved down accordingly
         self.render_controls(&mut stdout, 28)?;
 
@@ -133,38 +318,34 @@ impl ChmodInterface {
     }
 
     fn render_templates(&self, stdout: &mut io::Stdout) -> Result<()> {
+        let custom = self.templates.iter().filter(|t| t.custom).count();
         execute!(
             stdout,
             MoveTo(5, 9),
             SetForegroundColor(Color::Cyan),
             Print("📋 PERMISSION TEMPLATES"),
+            SetForegroundColor(Color::DarkGrey),
+            Print(if custom > 0 {
+                format!("  ({} from config.toml)", custom)
+            } else {
+                String::new()
+            }),
             ResetColor
         )?;
 
-        let templates = [
-            ("755", "Standard (rwxr-xr-x)", "Executables and directories"),
-            ("644", "Read Only (rw-r--r--)", "Regular files"),
-            ("600", "Private (rw-------)", "Sensitive files, owner only"),
-            (
-                "700",
-                "Private Exec (rwx------)",
-                "Private scripts/directories",
-            ),
-            ("775", "Group Share (rwxrwxr-x)", "Shared directories"),
-            ("664", "Group Write (rw-rw-r--)", "Collaborative files"),
-            ("666", "All Write (rw-rw-rw-)", "Temporary/log files"),
-            (
-                "777",
-                "Full Access (rwxrwxrwx)",
-                "⚠️ DANGEROUS - Everyone has full access",
-            ),
-            ("400", "Read Only Owner (r--------)", "Protected configs"),
-            ("500", "Exec Only Owner (r-x------)", "Protected scripts"),
-        ];
-
-        for (i, (value, name, desc)) in templates.iter().enumerate() {
+        let start = self
+            .template_index
+            .saturating_sub(TEMPLATE_ROWS.saturating_sub(1));
+        for (i, template) in self
+            .templates
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(TEMPLATE_ROWS)
+        {
             let is_selected = i == self.template_index;
-            let y = 11 + i as u16;
+            let y = 11 + (i - start) as u16;
+            let [owner, group, others] = template.digits;
 
             execute!(stdout, MoveTo(5, y))?;
 
@@ -186,19 +367,19 @@ impl ChmodInterface {
                 } else {
                     Color::Grey
                 }),
-                Print(format!("{} ", value)),
-                SetForegroundColor(if is_selected {
-                    Color::Yellow
-                } else {
-                    Color::DarkGrey
+                Print(format!("{}{}{} ", owner, group, others)),
+                SetForegroundColor(match (is_selected, template.custom) {
+                    (true, _) => Color::Yellow,
+                    (false, true) => Color::Magenta,
+                    (false, false) => Color::DarkGrey,
                 }),
-                Print(format!("{:<18} ", name)),
+                Print(format!("{:<18} ", template.name)),
                 SetForegroundColor(if is_selected {
                     Color::Cyan
                 } else {
                     Color::DarkGrey
                 }),
-                Print(desc),
+                Print(&template.description),
                 ResetColor
             )?;
         }
@@ -219,8 +400,22 @@ impl ChmodInterface {
             ResetColor
         )?;
 
+        if self.file_digits.is_some() {
+            execute!(
+                stdout,
+                MoveTo(11, y),
+                SetForegroundColor(Color::Yellow),
+                Print(if self.editing_files {
+                    " FILES "
+                } else {
+                    " DIRECTORIES "
+                }),
+                ResetColor
+            )?;
+        }
+
         // Render the three digit selectors with visual indicators
-        for (i, digit) in self.digits.iter().enumerate() {
+        for (i, digit) in self.edited().iter().enumerate() {
             let base_x = 20; // Moved from 18 to 20 (2 units right)
             let spacing = 11;
             let x = base_x + (i as u16 * spacing);
@@ -286,7 +481,8 @@ impl ChmodInterface {
     }
 
     fn render_permission_preview(&self, stdout: &mut io::Stdout, y: u16) -> Result<()> {
-        let mode_value = format!("{}{}{}", self.digits[0], self.digits[1], self.digits[2]);
+        let digits = self.edited();
+        let mode_value = format!("{}{}{}", digits[0], digits[1], digits[2]);
 
         execute!(
             stdout,
@@ -354,7 +550,7 @@ impl ChmodInterface {
             SetForegroundColor(Color::DarkGrey),
             Print(format!(
                 "(Binary: {:03b} {:03b} {:03b})",
-                self.digits[0], self.digits[1], self.digits[2]
+                digits[0], digits[1], digits[2]
             )),
             ResetColor
         )?;
@@ -395,11 +591,125 @@ impl ChmodInterface {
         Ok(())
     }
 
+    /// The expression being typed and what it makes of each selected path
+    fn render_symbolic(&self, stdout: &mut io::Stdout, expr: &str, y: u16) -> Result<()> {
+        execute!(
+            stdout,
+            MoveTo(5, y),
+            SetForegroundColor(Color::Yellow),
+            Print(format!("Symbolic mode: {}_", expr)),
+            MoveTo(5, y + 1),
+            SetForegroundColor(Color::DarkGrey),
+            Print("Classes u g o a, then + - =, then r w x X; separate changes with commas"),
+            ResetColor
+        )?;
+        if expr.is_empty() {
+            return Ok(());
+        }
+
+        let preview = match self.symbolic_preview(expr) {
+            Ok(preview) => preview,
+            Err(e) => {
+                execute!(
+                    stdout,
+                    MoveTo(5, y + 3),
+                    SetForegroundColor(Color::Red),
+                    Print(e),
+                    ResetColor
+                )?;
+                return Ok(());
+            }
+        };
+        const ROWS: usize = 12;
+        for (i, (name, before, after)) in preview.iter().take(ROWS).enumerate() {
+            execute!(
+                stdout,
+                MoveTo(5, y + 3 + i as u16),
+                SetForegroundColor(if before == after {
+                    Color::DarkGrey
+                } else {
+                    Color::Green
+                }),
+                Print(format!(
+                    "{:<32} {:03o} {} → {:03o} {}",
+                    truncate_chars(name, 32),
+                    before,
+                    mode_string(*before),
+                    after,
+                    mode_string(*after)
+                )),
+                ResetColor
+            )?;
+        }
+        if preview.len() > ROWS {
+            execute!(
+                stdout,
+                MoveTo(5, y + 3 + ROWS as u16),
+                SetForegroundColor(Color::DarkGrey),
+                Print(format!("... and {} more", preview.len() - ROWS)),
+                ResetColor
+            )?;
+        }
+        Ok(())
+    }
+
+    /// The mode bits before and after Enter of an entry it would change
+    fn mode_change(&self) -> impl Fn(&Affected) -> Option<(u32, u32)> {
+        let (mode, files) = self.chosen_modes();
+        let dirs = ModeSpec::parse(&mode).ok();
+        let files = files.and_then(|files| ModeSpec::parse(&files).ok());
+        move |entry| {
+            // chmod -R leaves links found inside directories alone
+            if entry.is_link {
+                return None;
+            }
+            let dirs = dirs.as_ref()?;
+            let spec = match files {
+                Some(ref files) if !entry.is_dir => files,
+                _ => dirs,
+            };
+            let before = entry.mode & 0o7777;
+            let after = spec.apply(entry.mode, entry.is_dir) & 0o7777;
+            (before != after).then_some((before, after))
+        }
+    }
+
+    /// Name and permission bits before and after `expr` of every selected
+    /// path that can be read
+    fn symbolic_preview(&self, expr: &str) -> Result<Vec<(String, u32, u32)>, String> {
+        let spec = ModeSpec::parse(expr)?;
+        Ok(self
+            .selected_paths
+            .iter()
+            .filter_map(|path| {
+                let metadata = path.metadata().ok()?;
+                let before = metadata.permissions().mode();
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| path.display().to_string());
+                Some((
+                    name,
+                    before & 0o777,
+                    spec.apply(before, metadata.is_dir()) & 0o777,
+                ))
+            })
+            .collect())
+    }
+
     fn render_controls(&self, stdout: &mut io::Stdout, y: u16) -> Result<()> {
-        let controls = if self.show_templates {
-            " ↑↓: Select Template | Enter: Apply | t: Manual Mode | Esc: Cancel "
+        let controls = if !self.failures.is_empty() {
+            " Enter/Esc: Close "
+        } else if self.picker.is_some() {
+            ReferencePicker::controls()
+        } else if self.symbolic.is_some() {
+            " Type a mode such as u+x,go-w | Enter: Apply | e: Export | Esc: Back "
+        } else if self.show_templates {
+            " ↑↓: Select Template | Enter: Apply | t: Manual Mode | s: Symbolic | f: Reference File | l: List Changes | n: Dry Run | e: Export | Esc: Cancel "
+        } else if self.show_affected {
+            " ↑↓/PgUp/PgDn: Scroll | l/Esc: Back to the digits | r/d/x: Options | w: Save Report | n: Dry Run | Enter: Apply "
         } else {
-            " ←→: Navigate | ↑↓: Change | t: Templates | Enter: Apply | Esc: Cancel "
+            " ←→: Navigate | ↑↓: Change | t: Templates | s: Symbolic | f: Reference File | l: List Changes | n: Dry Run | e: Export | Enter: Apply | Esc: Cancel "
         };
 
         execute!(
@@ -411,7 +721,16 @@ impl ChmodInterface {
             ResetColor
         )?;
 
-        if self.preview_mode {
+        if self.dry_run {
+            execute!(
+                stdout,
+                MoveTo(0, y + 1),
+                SetBackgroundColor(Color::DarkYellow),
+                SetForegroundColor(Color::Black),
+                Print(" DRY RUN - Enter lists the changes instead of making them (n: turn off) "),
+                ResetColor
+            )?;
+        } else if self.preview_mode {
             execute!(
                 stdout,
                 MoveTo(0, y + 1),
@@ -422,13 +741,241 @@ impl ChmodInterface {
             )?;
         }
 
+        if let Some(ref status) = self.status {
+            execute!(
+                stdout,
+                MoveTo(0, y + 2),
+                SetForegroundColor(Color::Yellow),
+                Print(status),
+                ResetColor
+            )?;
+        }
+
+        let check = |on: bool| if on { "✓" } else { " " };
+        let split = match self.chosen_pair() {
+            (dirs, Some(files)) => format!(
+                "[{}] d: Directories {} / files {} (Tab: edit {})",
+                check(true),
+                digits_mode(dirs, false),
+                digits_mode(files, false),
+                if self.editing_files {
+                    "directories"
+                } else {
+                    "files"
+                }
+            ),
+            (_, None) => format!("[{}] d: Separate file mode", check(false)),
+        };
+        execute!(
+            stdout,
+            MoveTo(0, y + 3),
+            SetForegroundColor(Color::Cyan),
+            Print(format!(
+                " [{}] r: Recursive   {}   [{}] x: Execute only where executable or directory (X)",
+                check(self.recursive),
+                split,
+                check(self.capital_x)
+            )),
+            ResetColor
+        )?;
+
         Ok(())
     }
 
+    /// The digits the arrows change
+    fn edited(&self) -> [u8; 3] {
+        match self.file_digits {
+            Some(files) if self.editing_files => files,
+            _ => self.digits,
+        }
+    }
+
+    fn edited_mut(&mut self) -> &mut [u8; 3] {
+        match self.file_digits {
+            Some(ref mut files) if self.editing_files => files,
+            _ => &mut self.digits,
+        }
+    }
+
+    /// The digits Enter would apply to directories, or to everything, and
+    /// to files when they have their own; the highlighted template takes
+    /// the place of the digits being edited
+    fn chosen_pair(&self) -> ([u8; 3], Option<[u8; 3]>) {
+        let template = self
+            .show_templates
+            .then(|| self.templates[self.template_index].digits);
+        match (template, self.file_digits) {
+            (Some(template), Some(_)) if self.editing_files => (self.digits, Some(template)),
+            (Some(template), files) => (template, files),
+            (None, files) => (self.digits, files),
+        }
+    }
+
+    /// The modes Enter would apply, as `set_mode` takes them
+    fn chosen_modes(&self) -> (String, Option<String>) {
+        if let Some(ref expr) = self.symbolic {
+            return (expr.clone(), None);
+        }
+        let (dirs, files) = self.chosen_pair();
+        (
+            digits_mode(dirs, self.capital_x),
+            files.map(|files| digits_mode(files, self.capital_x)),
+        )
+    }
+
+    /// The reference file, while the digits Enter would apply are still
+    /// the ones copied from it
+    fn active_reference(&self) -> Option<&(PathBuf, [u8; 3])> {
+        self.reference
+            .as_ref()
+            .filter(|(_, digits)| *digits == self.chosen_pair().0)
+    }
+
+    /// Take the digits of `path`'s permissions, as `chmod --reference`
+    fn use_reference(&mut self, path: &Path) {
+        match path.metadata() {
+            Ok(metadata) => {
+                let mode = metadata.permissions().mode();
+                self.digits = [
+                    ((mode >> 6) & 0b111) as u8,
+                    ((mode >> 3) & 0b111) as u8,
+                    (mode & 0b111) as u8,
+                ];
+                self.show_templates = false;
+                self.reference = Some((path.to_path_buf(), self.digits));
+                self.status = None;
+            }
+            Err(e) => self.status = Some(format!("Cannot read {}: {}", path.display(), e)),
+        }
+    }
+
+    /// The mode applied, octal or symbolic, the mode given to files if
+    /// they got their own and whether it was applied recursively, if the
+    /// interface closed by applying one
+    pub fn applied(&self) -> Option<(&str, Option<&str>, bool)> {
+        self.applied
+            .as_ref()
+            .map(|(mode, files, recursive)| (mode.as_str(), files.as_deref(), *recursive))
+    }
+
+    pub fn recent(&self) -> &RecentChoices {
+        &self.recent
+    }
+
+    /// Load the recent mode under `key`, if any; a symbolic one is put
+    /// back in the symbolic entry
+    fn use_recent(&mut self, key: char) -> bool {
+        let Some(mode) = recent_index(key).and_then(|index| self.recent.modes().get(index)) else {
+            return false;
+        };
+        match parse_mode(mode) {
+            Some(digits) => self.digits = digits,
+            None => self.symbolic = Some(mode.clone()),
+        }
+        self.show_templates = false;
+        true
+    }
+
+    /// The commands Enter amounts to: one `chmod`, or with a separate file
+    /// mode one for the directories and one for the files
+    fn commands(&self) -> Vec<String> {
+        let quote = |paths: &[&PathBuf]| {
+            paths
+                .iter()
+                .map(|path| shell_quote(path))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let all = quote(&self.selected_paths.iter().collect::<Vec<_>>());
+        let flag = if self.recursive { "-R " } else { "" };
+        let (mode, files) = self.chosen_modes();
+        let Some(files) = files else {
+            let reference = self
+                .active_reference()
+                .filter(|_| self.symbolic.is_none() && !self.capital_x);
+            if let Some((ref reference, _)) = reference {
+                return vec![format!(
+                    "chmod {}--reference={} -- {}",
+                    flag,
+                    shell_quote(reference),
+                    all
+                )];
+            }
+            return vec![format!("chmod {}{} -- {}", flag, mode, all)];
+        };
+        if self.recursive {
+            return vec![
+                format!("find {} -type d -exec chmod {} {{}} +", all, mode),
+                format!("find {} -type f -exec chmod {} {{}} +", all, files),
+            ];
+        }
+        let (dirs, others): (Vec<&PathBuf>, Vec<&PathBuf>) =
+            self.selected_paths.iter().partition(|path| path.is_dir());
+        [(mode, dirs), (files, others)]
+            .into_iter()
+            .filter(|(_, paths)| !paths.is_empty())
+            .map(|(mode, paths)| format!("chmod {} -- {}", mode, quote(&paths)))
+            .collect()
+    }
+
+    fn export_to(&self, path: &Path) -> io::Result<()> {
+        write_script(path, "chmod", &self.commands())
+    }
+
+    fn export(&mut self) {
+        let saved = script_path("chmod").and_then(|path| self.export_to(&path).map(|()| path));
+        self.status = Some(match saved {
+            Ok(path) => format!("Saved to {}", path.display()),
+            Err(e) => format!("Export failed: {}", e),
+        });
+    }
+
+    /// Write the commands and every change the walk found to a report in
+    /// the temp directory
+    fn write_report(&mut self) {
+        let lines = self.affected.as_ref().map_or(Vec::new(), |view| {
+            view.report(self.mode_change(), describe_modes)
+        });
+        let saved = report_path("chmod")
+            .and_then(|path| write_report(&path, "chmod", &self.commands(), &lines).map(|()| path));
+        self.status = Some(match saved {
+            Ok(path) => format!("Report saved to {}", path.display()),
+            Err(e) => format!("Report failed: {}", e),
+        });
+    }
+
+    /// Apply the chosen mode, or in a dry run list what it would change;
+    /// true while the interface stays open, as it does to list the paths
+    /// that were refused
+    fn confirm(&mut self) -> bool {
+        if self.dry_run {
+            self.show_affected = true;
+            self.status =
+                Some("Dry run: nothing changed | w: Save this report | e: Export".to_string());
+            return true;
+        }
+        self.apply_permissions();
+        !self.failures.is_empty()
+    }
+
+    /// Paths left out because only root or their owner may chmod them,
+    /// named in the status line
+    pub fn with_skipped(mut self, skipped: &[PathBuf]) -> Self {
+        if !skipped.is_empty() {
+            let names: Vec<String> = skipped.iter().map(|p| p.display().to_string()).collect();
+            self.status = Some(format!(
+                "Skipped {} item(s) you do not own: {}",
+                skipped.len(),
+                names.join(", ")
+            ));
+        }
+        self
+    }
+
     fn get_visual_permissions(&self) -> String {
         let mut result = String::new();
 
-        for digit in &self.digits {
+        for digit in &self.edited() {
             result.push(if digit & 4 != 0 { 'r' } else { '-' });
             result.push(if digit & 2 != 0 { 'w' } else { '-' });
             result.push(if digit & 1 != 0 { 'x' } else { '-' });
@@ -439,21 +986,22 @@ impl ChmodInterface {
 
     fn get_explanations(&self) -> Vec<String> {
         let mut explanations = Vec::new();
+        let digits = self.edited();
 
         // Owner permissions
-        let owner_perms = self.digit_to_permissions(self.digits[0]);
+        let owner_perms = self.digit_to_permissions(digits[0]);
         explanations.push(format!("Owner can: {}", owner_perms));
 
         // Group permissions
-        let group_perms = self.digit_to_permissions(self.digits[1]);
+        let group_perms = self.digit_to_permissions(digits[1]);
         explanations.push(format!("Group members can: {}", group_perms));
 
         // Others permissions
-        let others_perms = self.digit_to_permissions(self.digits[2]);
+        let others_perms = self.digit_to_permissions(digits[2]);
         explanations.push(format!("Everyone else can: {}", others_perms));
 
         // Security assessment
-        let pattern = format!("{}{}{}", self.digits[0], self.digits[1], self.digits[2]);
+        let pattern = format!("{}{}{}", digits[0], digits[1], digits[2]);
         let security = match pattern.as_str() {
             "777" => "⚠️ VERY INSECURE - Anyone can do anything!",
             "666" => "⚠️ Risky - Anyone can modify these files",
@@ -463,7 +1011,7 @@ impl ChmodInterface {
             "700" => "✓ Secure - Private directory/executable",
             "000" => "⚠️ Locked - Nobody can access (unusual)",
             _ => {
-                let world_write = self.digits[2] & 2 != 0;
+                let world_write = digits[2] & 2 != 0;
                 if world_write {
                     "⚠️ World-writable - Consider restricting"
                 } else {
@@ -497,39 +1045,126 @@ impl ChmodInterface {
     }
 
     pub fn handle_input(&mut self, key: KeyCode) -> bool {
-        if self.show_templates {
+        // The change is made; only leaving is left
+        if !self.failures.is_empty() {
+            return !matches!(key, KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q'));
+        }
+        if let Some(ref mut picker) = self.picker {
+            match picker.handle_input(key) {
+                PickerOutcome::Continue => {}
+                PickerOutcome::Cancel => self.picker = None,
+                PickerOutcome::Pick(path) => {
+                    self.picker = None;
+                    self.use_reference(&path);
+                }
+            }
+            return true;
+        }
+        if self.show_affected {
             match key {
-                KeyCode::Up => {
-                    if self.template_index > 0 {
-                        self.template_index -= 1;
-                    }
+                KeyCode::Char('l') | KeyCode::Char('L') | KeyCode::Esc => {
+                    self.show_affected = false;
+                    return true;
+                }
+                KeyCode::Char('w') | KeyCode::Char('W') => {
+                    self.write_report();
+                    return true;
                 }
-                KeyCode::Down => {
-                    if self.template_index < 9 {
-                        self.template_index += 1;
+                _ => {
+                    if let Some(ref mut view) = self.affected {
+                        if view.handle_scroll(key) {
+                            return true;
+                        }
                     }
                 }
-                KeyCode::Enter => {
-                    // Apply template
-                    let templates = [
-                        [7, 5, 5], // 755
-                        [6, 4, 4], // 644
-                        [6, 0, 0], // 600
-                        [7, 0, 0], // 700
-                        [7, 7, 5], // 775
-                        [6, 6, 4], // 664
-                        [6, 6, 6], // 666
-                        [7, 7, 7], // 777
-                        [4, 0, 0], // 400
-                        [5, 0, 0], // 500
-                    ];
-                    self.digits = templates[self.template_index];
-                    self.apply_permissions();
-                    return false; // Exit interface
+            }
+        }
+        if let Some(ref mut expr) = self.symbolic {
+            match key {
+                KeyCode::Enter => match ModeSpec::parse(expr) {
+                    Ok(_) => return self.confirm(),
+                    Err(e) => self.status = Some(e),
+                },
+                KeyCode::Esc => {
+                    self.symbolic = None;
+                    self.status = None;
+                }
+                KeyCode::Backspace => {
+                    expr.pop();
                 }
+                // Exporting is the one command of the entry: `e` is no
+                // permission class
+                KeyCode::Char('e') => self.export(),
+                KeyCode::Char(c) if !c.is_whitespace() => expr.push(c),
+                _ => {}
+            }
+            return true;
+        }
+        if let KeyCode::Char('s') | KeyCode::Char('S') = key {
+            self.symbolic = Some(String::new());
+            self.status = None;
+            return true;
+        }
+        if let KeyCode::Char(c) = key {
+            if self.use_recent(c) {
+                return true;
+            }
+        }
+        match key {
+            KeyCode::Char('r') | KeyCode::Char('R') => {
+                self.recursive = !self.recursive;
+                return true;
+            }
+            KeyCode::Char('x') | KeyCode::Char('X') => {
+                self.capital_x = !self.capital_x;
+                return true;
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') => {
+                self.dry_run = !self.dry_run;
+                return true;
+            }
+            // Files start from the directory mode without execute, as
+            // 755 gives 644
+            KeyCode::Char('d') | KeyCode::Char('D') => {
+                self.file_digits = match self.file_digits {
+                    Some(_) => None,
+                    None => Some(self.digits.map(|digit| digit & 0b110)),
+                };
+                self.editing_files = false;
+                return true;
+            }
+            KeyCode::Tab if self.file_digits.is_some() => {
+                self.editing_files = !self.editing_files;
+                return true;
+            }
+            KeyCode::Char('l') | KeyCode::Char('L') => {
+                self.show_affected = true;
+                return true;
+            }
+            _ => {}
+        }
+        if let KeyCode::Char('f') | KeyCode::Char('F') = key {
+            let start = self
+                .selected_paths
+                .first()
+                .and_then(|p| p.parent())
+                .unwrap_or(Path::new("/"));
+            self.picker = Some(ReferencePicker::new(start));
+            return true;
+        }
+        if self.show_templates {
+            match key {
+                KeyCode::Up if self.template_index > 0 => {
+                    self.template_index -= 1;
+                }
+                KeyCode::Down if self.template_index + 1 < self.templates.len() => {
+                    self.template_index += 1;
+                }
+                KeyCode::Enter => return self.confirm(),
                 KeyCode::Char('t') | KeyCode::Char('T') => {
                     self.show_templates = false;
                 }
+                KeyCode::Char('e') | KeyCode::Char('E') => self.export(),
                 KeyCode::Esc => {
                     return false; // Exit without applying
                 }
@@ -537,37 +1172,29 @@ impl ChmodInterface {
             }
         } else {
             match key {
-                KeyCode::Left => {
-                    if self.position > 0 {
-                        self.position -= 1;
-                    }
+                KeyCode::Left if self.position > 0 => {
+                    self.position -= 1;
                 }
-                KeyCode::Right => {
-                    if self.position < 2 {
-                        self.position += 1;
-                    }
+                KeyCode::Right if self.position < 2 => {
+                    self.position += 1;
                 }
-                KeyCode::Up => {
-                    if self.digits[self.position] < 7 {
-                        self.digits[self.position] += 1;
-                    }
+                KeyCode::Up if self.edited()[self.position] < 7 => {
+                    let position = self.position;
+                    self.edited_mut()[position] += 1;
                 }
-                KeyCode::Down => {
-                    if self.digits[self.position] > 0 {
-                        self.digits[self.position] -= 1;
-                    }
+                KeyCode::Down if self.edited()[self.position] > 0 => {
+                    let position = self.position;
+                    self.edited_mut()[position] -= 1;
                 }
                 KeyCode::Char('t') | KeyCode::Char('T') => {
                     self.show_templates = true;
                     self.template_index = 0;
                 }
-                KeyCode::Enter => {
-                    self.apply_permissions();
-                    return false; // Exit interface
-                }
+                KeyCode::Enter => return self.confirm(),
                 KeyCode::Char('p') | KeyCode::Char('P') => {
                     self.preview_mode = !self.preview_mode;
                 }
+                KeyCode::Char('e') | KeyCode::Char('E') => self.export(),
                 KeyCode::Esc => {
                     return false; // Exit without applying
                 }
@@ -577,21 +1204,650 @@ impl ChmodInterface {
         true // Continue
     }
 
-    fn apply_permissions(&self) {
-        let mode =
-            (self.digits[0] as u32) * 64 + (self.digits[1] as u32) * 8 + (self.digits[2] as u32);
+    fn apply_permissions(&mut self) {
+        let (mode, files) = self.chosen_modes();
+        let _ = self.recent.record_mode(&mode);
+        match set_modes(
+            &self.selected_paths,
+            &mode,
+            files.as_deref(),
+            self.recursive,
+        ) {
+            Ok(failures) => self.failures = failures,
+            Err(e) => self.status = Some(format!("Invalid mode: {}", e)),
+        }
+        self.applied = Some((mode, files, self.recursive));
+    }
+}
+
+/// Give every existing path `mode`: octal, such as "644", or symbolic, such
+/// as "u+x,go-w". Files get `file_mode` instead when there is one, and with
+/// `recursive` everything below the directories changes too; the failures
+/// are summed up after trying them all
+pub fn set_mode(
+    paths: &[PathBuf],
+    mode: &str,
+    file_mode: Option<&str>,
+    recursive: bool,
+) -> io::Result<()> {
+    let failures = set_modes(paths, mode, file_mode, recursive)?;
+    match summarize(&failures) {
+        Some(summary) => Err(io::Error::new(failures[0].1.kind(), summary)),
+        None => Ok(()),
+    }
+}
+
+/// As `set_mode`, with every path that was refused, such as those owned by
+/// someone else
+fn set_modes(
+    paths: &[PathBuf],
+    mode: &str,
+    file_mode: Option<&str>,
+    recursive: bool,
+) -> io::Result<Vec<Failure>> {
+    let invalid = |e| io::Error::new(io::ErrorKind::InvalidInput, e);
+    let dirs = ModeSpec::parse(mode).map_err(invalid)?;
+    let files = file_mode
+        .map(ModeSpec::parse)
+        .transpose()
+        .map_err(invalid)?;
+
+    let mut failures = Vec::new();
+    for path in paths.iter().filter(|path| path.exists()) {
+        set_mode_of(
+            path,
+            &dirs,
+            files.as_ref().unwrap_or(&dirs),
+            recursive,
+            &mut failures,
+        );
+    }
+    Ok(failures)
+}
 
-        for path in &self.selected_paths {
-            if path.exists() {
-                #[cfg(unix)]
-                {
-                    if let Ok(metadata) = path.metadata() {
-                        let mut permissions = metadata.permissions();
-                        permissions.set_mode(0o100000 | mode); // Preserve file type bits
-                        let _ = std::fs::set_permissions(path, permissions);
+/// Change `path` and, with `recursive`, what is below it; symbolic links
+/// below the top are left alone, as `chmod -R` does
+fn set_mode_of(
+    path: &Path,
+    dirs: &ModeSpec,
+    files: &ModeSpec,
+    recursive: bool,
+    failures: &mut Vec<Failure>,
+) {
+    let outcome = path.metadata().and_then(|metadata| {
+        let is_dir = metadata.is_dir();
+        let spec = if is_dir { dirs } else { files };
+        let mut permissions = metadata.permissions();
+        permissions.set_mode(spec.apply(permissions.mode(), is_dir));
+        fs::set_permissions(path, permissions).map(|()| is_dir)
+    });
+    let entries = match outcome {
+        Ok(true) if recursive => fs::read_dir(path),
+        Ok(_) => return,
+        Err(e) => Err(e),
+    };
+    match entries {
+        Ok(entries) => {
+            for entry in entries.flatten() {
+                if !entry.file_type().is_ok_and(|kind| kind.is_symlink()) {
+                    set_mode_of(&entry.path(), dirs, files, recursive, failures);
+                }
+            }
+        }
+        Err(e) => failures.push((path.to_path_buf(), e)),
+    }
+}
+
+/// `digits` as a mode: octal, or with `capital_x` symbolic with `X` in
+/// place of `x`, such as "u=rwX,g=rX,o=" for 750
+fn digits_mode(digits: [u8; 3], capital_x: bool) -> String {
+    if !capital_x {
+        return digits.iter().map(u8::to_string).collect();
+    }
+    ["u", "g", "o"]
+        .iter()
+        .zip(digits)
+        .map(|(class, digit)| {
+            format!(
+                "{}={}{}{}",
+                class,
+                if digit & 4 != 0 { "r" } else { "" },
+                if digit & 2 != 0 { "w" } else { "" },
+                if digit & 1 != 0 { "X" } else { "" }
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// What `set_mode` makes of a path's permissions
+enum ModeSpec {
+    /// Every path gets these bits
+    Octal(u32),
+    /// Each path's bits change in these steps, in order
+    Symbolic(Vec<SymbolicChange>),
+}
+
+/// One step of a symbolic mode, such as the `go-w` of `u+x,go-w`
+#[derive(Debug, Clone, Copy)]
+struct SymbolicChange {
+    /// Bits of the classes named: 0o700 for `u`, 0o070 for `g`, ...
+    who: u32,
+    op: char,
+    /// The permissions named, in every class
+    perms: u32,
+    /// `X`: execute, but only for directories and for files that someone
+    /// can already execute
+    exec_if_any: bool,
+}
+
+impl ModeSpec {
+    fn parse(mode: &str) -> Result<Self, String> {
+        if mode.is_empty() {
+            return Err("Type a mode".to_string());
+        }
+        if let Some([owner, group, others]) = parse_mode(mode) {
+            return Ok(Self::Octal(
+                (owner as u32) << 6 | (group as u32) << 3 | others as u32,
+            ));
+        }
+        mode.split(',')
+            .map(parse_clause)
+            .collect::<Result<Vec<Vec<_>>, _>>()
+            .map(|clauses| Self::Symbolic(clauses.concat()))
+    }
+
+    /// The mode to give a path whose mode is `current`; special bits are
+    /// cleared by an octal mode, as before, and kept by a symbolic one.
+    /// `X` looks at `current`, before any of the changes
+    fn apply(&self, current: u32, is_dir: bool) -> u32 {
+        match self {
+            Self::Octal(mode) => *mode,
+            Self::Symbolic(changes) => {
+                let mut mode = current & 0o7777;
+                for change in changes {
+                    let mut perms = change.perms;
+                    if change.exec_if_any && (is_dir || current & 0o111 != 0) {
+                        perms |= 0o111;
                     }
+                    let bits = perms & change.who;
+                    mode = match change.op {
+                        '+' => mode | bits,
+                        '-' => mode & !bits,
+                        _ => (mode & !change.who) | bits,
+                    };
                 }
+                mode
             }
         }
     }
 }
+
+/// The steps of one comma-separated clause: classes, then one or more
+/// operators each followed by its permissions, as in `ug+rw-x`; without a
+/// class the clause applies to everyone
+fn parse_clause(clause: &str) -> Result<Vec<SymbolicChange>, String> {
+    let mut chars = clause.chars().peekable();
+    let mut who = 0;
+    while let Some(&c) = chars.peek() {
+        who |= match c {
+            'u' => 0o700,
+            'g' => 0o070,
+            'o' => 0o007,
+            'a' => 0o777,
+            _ => break,
+        };
+        chars.next();
+    }
+    if who == 0 {
+        who = 0o777;
+    }
+
+    let mut changes: Vec<SymbolicChange> = Vec::new();
+    for c in chars {
+        match c {
+            '+' | '-' | '=' => changes.push(SymbolicChange {
+                who,
+                op: c,
+                perms: 0,
+                exec_if_any: false,
+            }),
+            'r' | 'w' | 'x' | 'X' => {
+                let Some(change) = changes.last_mut() else {
+                    return Err(format!("'{}': expected + - or = before '{}'", clause, c));
+                };
+                match c {
+                    'r' => change.perms |= 0o444,
+                    'w' => change.perms |= 0o222,
+                    'x' => change.perms |= 0o111,
+                    _ => change.exec_if_any = true,
+                }
+            }
+            _ => return Err(format!("'{}': unexpected '{}'", clause, c)),
+        }
+    }
+    if changes.is_empty() {
+        return Err(format!("'{}': expected + - or =", clause));
+    }
+    Ok(changes)
+}
+
+/// Permission bits before and after a change, as "644 rw-r--r-- → 755 rwxr-xr-x"
+fn describe_modes((before, after): &(u32, u32)) -> String {
+    format!(
+        "{:03o} {} → {:03o} {}",
+        before,
+        mode_string(*before),
+        after,
+        mode_string(*after)
+    )
+}
+
+/// `rwxr-x---` for the permission bits of `mode`
+fn mode_string(mode: u32) -> String {
+    (0..9)
+        .map(|bit| {
+            if mode & (0o400 >> bit) == 0 {
+                '-'
+            } else {
+                ['r', 'w', 'x'][bit % 3]
+            }
+        })
+        .collect()
+}
+
+/// Digits of an octal mode such as "750"
+fn parse_mode(mode: &str) -> Option<[u8; 3]> {
+    let digits: Vec<u8> = mode
+        .chars()
+        .map(|c| c.to_digit(8).map(|d| d as u8))
+        .collect::<Option<_>>()?;
+    digits.try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_export_script() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("run me.sh");
+        std::fs::write(&file, "").unwrap();
+        let mut chmod = ChmodInterface::new(vec![file.clone()], RecentChoices::default());
+        chmod.digits = [7, 5, 0];
+
+        let script = dir.path().join("chmod.sh");
+        chmod.export_to(&script).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&script).unwrap(),
+            format!(
+                "#!/bin/sh\n# Generated by fsnav's chmod interface\nset -e\nchmod 750 -- '{}'\n",
+                file.display()
+            )
+        );
+        assert_eq!(
+            script.metadata().unwrap().permissions().mode() & 0o777,
+            0o755
+        );
+
+        // In template mode the highlighted template is exported
+        chmod.handle_input(KeyCode::Char('t'));
+        chmod.handle_input(KeyCode::Down);
+        assert!(chmod.commands()[0].starts_with("chmod 644 -- "));
+        // Exporting changes nothing
+        assert_eq!(chmod.digits, [7, 5, 0]);
+    }
+
+    #[test]
+    fn test_reference_file() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("new.conf");
+        let reference = dir.path().join("old.conf");
+        std::fs::write(&file, "").unwrap();
+        std::fs::write(&reference, "").unwrap();
+        std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o644)).unwrap();
+        std::fs::set_permissions(&reference, std::fs::Permissions::from_mode(0o640)).unwrap();
+
+        let mut chmod = ChmodInterface::new(vec![file.clone()], RecentChoices::default());
+        chmod.handle_input(KeyCode::Char('f'));
+        assert!(chmod.picker.is_some());
+        // The picker opens beside the selection: old.conf follows new.conf
+        chmod.handle_input(KeyCode::Down);
+        chmod.handle_input(KeyCode::Enter);
+        assert!(chmod.picker.is_none());
+        assert_eq!(chmod.digits, [6, 4, 0]);
+        assert_eq!(
+            chmod.commands()[0],
+            format!(
+                "chmod --reference='{}' -- '{}'",
+                reference.display(),
+                file.display()
+            )
+        );
+
+        // Changing a digit goes back to an explicit mode
+        chmod.handle_input(KeyCode::Up);
+        assert!(chmod.commands()[0].starts_with("chmod 740 -- "));
+
+        chmod.apply_permissions();
+        assert_eq!(file.metadata().unwrap().permissions().mode() & 0o777, 0o740);
+
+        assert_eq!(chmod.recent().modes(), ["740"]);
+        chmod.handle_input(KeyCode::Down);
+        chmod.handle_input(KeyCode::Char('1'));
+        assert_eq!(chmod.digits, [7, 4, 0]);
+    }
+
+    #[test]
+    fn test_symbolic_mode() {
+        let apply = |expr: &str, mode: u32, is_dir: bool| {
+            ModeSpec::parse(expr).map(|spec| spec.apply(mode, is_dir))
+        };
+        assert_eq!(apply("u+x", 0o644, false), Ok(0o744));
+        assert_eq!(apply("go-w", 0o666, false), Ok(0o644));
+        assert_eq!(apply("a=r", 0o755, false), Ok(0o444));
+        assert_eq!(apply("+x", 0o644, false), Ok(0o755));
+        assert_eq!(apply("u=rwx,g=rx,o=", 0o000, false), Ok(0o750));
+        assert_eq!(apply("ug+rw-x", 0o711, false), Ok(0o661));
+        // X only adds execute to directories and files already executable
+        assert_eq!(apply("a+X", 0o644, false), Ok(0o644));
+        assert_eq!(apply("a+X", 0o644, true), Ok(0o755));
+        assert_eq!(apply("go+X", 0o744, false), Ok(0o755));
+        // Special bits survive symbolic changes
+        assert_eq!(apply("o-w", 0o4757, false), Ok(0o4755));
+        assert_eq!(apply("640", 0o4757, false), Ok(0o640));
+
+        for invalid in ["", "u", "u+q", "z+x", "u+x,", "rw"] {
+            assert!(apply(invalid, 0o644, false).is_err(), "{}", invalid);
+        }
+        assert_eq!(mode_string(0o751), "rwxr-x--x");
+    }
+
+    #[test]
+    fn test_symbolic_entry() {
+        let dir = TempDir::new().unwrap();
+        let script = dir.path().join("run.sh");
+        let secret = dir.path().join("secret");
+        std::fs::write(&script, "").unwrap();
+        std::fs::write(&secret, "").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o644)).unwrap();
+        std::fs::set_permissions(&secret, std::fs::Permissions::from_mode(0o600)).unwrap();
+
+        let mut chmod = ChmodInterface::new(
+            vec![script.clone(), secret.clone()],
+            RecentChoices::default(),
+        );
+        chmod.handle_input(KeyCode::Char('s'));
+        for c in "u+x,o+rq".chars() {
+            chmod.handle_input(KeyCode::Char(c));
+        }
+        assert!(chmod.symbolic_preview("u+x,o+rq").is_err());
+        // Enter refuses an invalid expression and keeps the entry open
+        assert!(chmod.handle_input(KeyCode::Enter));
+        assert!(chmod.status.is_some());
+
+        // Digits are typed, not taken as recent modes
+        chmod.handle_input(KeyCode::Backspace);
+        assert_eq!(
+            chmod.symbolic_preview("u+x,o+r").unwrap(),
+            [
+                ("run.sh".to_string(), 0o644, 0o744),
+                ("secret".to_string(), 0o600, 0o704),
+            ]
+        );
+        assert!(chmod.commands()[0].starts_with("chmod u+x,o+r -- "));
+
+        assert!(!chmod.handle_input(KeyCode::Enter));
+        assert_eq!(
+            script.metadata().unwrap().permissions().mode() & 0o777,
+            0o744
+        );
+        assert_eq!(
+            secret.metadata().unwrap().permissions().mode() & 0o777,
+            0o704
+        );
+        assert_eq!(chmod.applied(), Some(("u+x,o+r", None, false)));
+
+        // A recent symbolic mode goes back into the entry
+        let mut chmod = ChmodInterface::new(vec![script], chmod.recent().clone());
+        chmod.handle_input(KeyCode::Char('1'));
+        assert_eq!(chmod.symbolic.as_deref(), Some("u+x,o+r"));
+    }
+
+    #[test]
+    fn test_recursive_chmod() {
+        let dir = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let tree = dir.path().join("site");
+        let sub = tree.join("assets");
+        std::fs::create_dir_all(&sub).unwrap();
+        let script = tree.join("deploy.sh");
+        let page = sub.join("index.html");
+        let target = outside.path().join("secret");
+        std::fs::write(&script, "").unwrap();
+        std::fs::write(&page, "").unwrap();
+        std::fs::write(&target, "").unwrap();
+        std::os::unix::fs::symlink(&target, sub.join("link")).unwrap();
+        let mode_of = |path: &Path| path.metadata().unwrap().permissions().mode() & 0o777;
+        let set = |path: &Path, mode: u32| {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode)).unwrap()
+        };
+        for (path, mode) in [
+            (&sub, 0o700),
+            (&script, 0o744),
+            (&page, 0o600),
+            (&target, 0o600),
+        ] {
+            set(path, mode);
+        }
+
+        // 755 for directories and 644 for files in one pass
+        let mut chmod = ChmodInterface::new(vec![tree.clone()], RecentChoices::default());
+        chmod.digits = [7, 5, 5];
+        chmod.handle_input(KeyCode::Char('r'));
+        chmod.handle_input(KeyCode::Char('d'));
+        assert_eq!(chmod.file_digits, Some([6, 4, 4]));
+        // Tab moves the arrows to the file digits
+        chmod.handle_input(KeyCode::Tab);
+        chmod.handle_input(KeyCode::Down);
+        assert_eq!(
+            (chmod.digits, chmod.file_digits),
+            ([7, 5, 5], Some([5, 4, 4]))
+        );
+        chmod.handle_input(KeyCode::Up);
+        assert_eq!(
+            chmod.commands(),
+            [
+                format!("find '{}' -type d -exec chmod 755 {{}} +", tree.display()),
+                format!("find '{}' -type f -exec chmod 644 {{}} +", tree.display()),
+            ]
+        );
+        assert!(!chmod.handle_input(KeyCode::Enter));
+        assert_eq!(chmod.applied(), Some(("755", Some("644"), true)));
+        assert_eq!(
+            [
+                mode_of(&tree),
+                mode_of(&sub),
+                mode_of(&script),
+                mode_of(&page)
+            ],
+            [0o755, 0o755, 0o644, 0o644]
+        );
+        // The link is not followed below the top
+        assert_eq!(mode_of(&target), 0o600);
+
+        // X: execute only for directories and files already executable
+        set(&sub, 0o700);
+        set(&script, 0o744);
+        set(&page, 0o600);
+        let mut chmod = ChmodInterface::new(vec![tree.clone()], RecentChoices::default());
+        chmod.digits = [7, 5, 5];
+        chmod.handle_input(KeyCode::Char('r'));
+        chmod.handle_input(KeyCode::Char('x'));
+        assert_eq!(
+            chmod.commands(),
+            [format!("chmod -R u=rwX,g=rX,o=rX -- '{}'", tree.display())]
+        );
+        chmod.handle_input(KeyCode::Enter);
+        assert_eq!(
+            [mode_of(&sub), mode_of(&script), mode_of(&page)],
+            [0o755, 0o755, 0o644]
+        );
+
+        // Without recursion a separate file mode splits the selection
+        let mut chmod =
+            ChmodInterface::new(vec![sub.clone(), script.clone()], RecentChoices::default());
+        chmod.digits = [7, 0, 0];
+        chmod.handle_input(KeyCode::Char('d'));
+        assert_eq!(
+            chmod.commands(),
+            [
+                format!("chmod 700 -- '{}'", sub.display()),
+                format!("chmod 600 -- '{}'", script.display()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_affected_changes() {
+        let dir = TempDir::new().unwrap();
+        let tree = dir.path().join("www");
+        std::fs::create_dir(&tree).unwrap();
+        std::fs::write(tree.join("index.html"), "").unwrap();
+        std::fs::write(tree.join("secret.key"), "").unwrap();
+        let set = |path: &Path, mode: u32| {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode)).unwrap()
+        };
+        set(&tree, 0o755);
+        set(&tree.join("index.html"), 0o644);
+        set(&tree.join("secret.key"), 0o600);
+
+        let mut chmod = ChmodInterface::new(vec![tree.clone()], RecentChoices::default());
+        chmod.digits = [7, 5, 5];
+        chmod.handle_input(KeyCode::Char('d'));
+        let changing = |chmod: &mut ChmodInterface| {
+            chmod.poll();
+            let change = chmod.mode_change();
+            chmod.affected.as_ref().map_or(Vec::new(), |view| {
+                view.changing(change)
+                    .into_iter()
+                    .map(|(entry, modes)| (entry.path.clone(), modes))
+                    .collect::<Vec<_>>()
+            })
+        };
+        // Not recursive: the directory is already 755
+        let walking = |chmod: &ChmodInterface| {
+            chmod
+                .affected
+                .as_ref()
+                .is_none_or(|view| view.headline(0).contains("walking"))
+        };
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while walking(&chmod) && std::time::Instant::now() < deadline {
+            changing(&mut chmod);
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(changing(&mut chmod), []);
+
+        // Recursive: the walk starts over and finds the private file
+        chmod.handle_input(KeyCode::Char('r'));
+        let mut found = Vec::new();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while found.is_empty() && std::time::Instant::now() < deadline {
+            found = changing(&mut chmod);
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(found, [(tree.join("secret.key"), (0o600, 0o644))]);
+    }
+
+    #[test]
+    fn test_dry_run() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("deploy.sh");
+        std::fs::write(&file, "").unwrap();
+        std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        let mut chmod = ChmodInterface::new(vec![file.clone()], RecentChoices::default());
+        chmod.digits = [7, 5, 5];
+        chmod.handle_input(KeyCode::Char('n'));
+        // Enter lists the change and stays open
+        assert!(chmod.handle_input(KeyCode::Enter));
+        assert!(chmod.show_affected);
+        assert_eq!(chmod.applied(), None);
+        let mode = std::fs::metadata(&file).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o644);
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while chmod
+            .affected
+            .as_ref()
+            .is_none_or(|view| view.headline(0).contains("walking"))
+            && std::time::Instant::now() < deadline
+        {
+            chmod.poll();
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let lines = chmod
+            .affected
+            .as_ref()
+            .unwrap()
+            .report(chmod.mode_change(), describe_modes);
+        assert_eq!(
+            lines,
+            [
+                "# 1 of 1 item(s) would change".to_string(),
+                format!("{}: 644 rw-r--r-- → 755 rwxr-xr-x", file.display()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_refused_paths() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("mine.txt");
+        std::fs::write(&file, "").unwrap();
+        let other = PathBuf::from("/etc/shadow");
+
+        let mut chmod = ChmodInterface::new(vec![file.clone()], RecentChoices::default())
+            .with_skipped(std::slice::from_ref(&other));
+        assert_eq!(
+            chmod.status.as_deref(),
+            Some("Skipped 1 item(s) you do not own: /etc/shadow")
+        );
+        chmod.digits = [6, 0, 0];
+        assert!(!chmod.handle_input(KeyCode::Enter));
+        assert!(chmod.failures.is_empty());
+
+        // Refused paths keep it open until they have been read
+        chmod.failures = vec![(other, io::Error::from_raw_os_error(libc::EPERM))];
+        assert!(chmod.handle_input(KeyCode::Up));
+        assert_eq!(chmod.digits, [6, 0, 0]);
+        assert!(!chmod.handle_input(KeyCode::Esc));
+
+        assert!(set_mode(&[file], "u+q", None, false).is_err());
+    }
+
+    #[test]
+    fn test_config_templates() {
+        let template = |name: &str, mode: &str| PermissionTemplate {
+            name: name.to_string(),
+            mode: mode.to_string(),
+            description: String::new(),
+        };
+        let mut chmod =
+            ChmodInterface::new(vec![PathBuf::from("/srv/www")], RecentChoices::default())
+                .with_templates(&[template("Web root", "750"), template("Broken", "8x")]);
+        assert_eq!(chmod.templates.len(), BUILTIN_TEMPLATES.len() + 1);
+        assert!(chmod.status.as_deref().unwrap().contains("Broken (8x)"));
+
+        // The custom template follows the built-in ones, and the list stops there
+        chmod.handle_input(KeyCode::Char('t'));
+        for _ in 0..20 {
+            chmod.handle_input(KeyCode::Down);
+        }
+        assert_eq!(chmod.templates[chmod.template_index].name, "Web root");
+        assert_eq!(chmod.commands(), ["chmod 750 -- '/srv/www'"]);
+    }
+}
@@ -0,0 +1,213 @@
+use crate::ui::{SetBackgroundColor, SetForegroundColor};
+use anyhow::Result;
+use crossterm::{
+    cursor::MoveTo,
+    event::KeyCode,
+    execute,
+    style::{Color, Print, ResetColor},
+};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub enum PickerOutcome {
+    Continue,
+    Cancel,
+    Pick(PathBuf),
+}
+
+/// A small directory browser for choosing the file a manager copies its
+/// permissions or ownership from
+#[derive(Debug, Clone)]
+pub struct ReferencePicker {
+    dir: PathBuf,
+    /// Names in `dir`, directories first, each with whether it is one
+    entries: Vec<(String, bool)>,
+    selected: usize,
+}
+
+impl ReferencePicker {
+    pub fn new(dir: &Path) -> Self {
+        let mut picker = Self {
+            dir: dir.to_path_buf(),
+            entries: Vec::new(),
+            selected: 0,
+        };
+        picker.read_dir();
+        picker
+    }
+
+    fn read_dir(&mut self) {
+        let mut entries: Vec<(String, bool)> = fs::read_dir(&self.dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|e| {
+                        (
+                            e.file_name().to_string_lossy().to_string(),
+                            e.path().is_dir(),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        self.entries = entries;
+        self.selected = 0;
+    }
+
+    fn highlighted(&self) -> Option<&(String, bool)> {
+        self.entries.get(self.selected)
+    }
+
+    fn enter(&mut self, dir: PathBuf) {
+        self.dir = dir;
+        self.read_dir();
+    }
+
+    pub fn handle_input(&mut self, key: KeyCode) -> PickerOutcome {
+        match key {
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down if self.selected + 1 < self.entries.len() => self.selected += 1,
+            KeyCode::PageUp => self.selected = self.selected.saturating_sub(10),
+            KeyCode::PageDown => {
+                self.selected = (self.selected + 10).min(self.entries.len().saturating_sub(1))
+            }
+            KeyCode::Left | KeyCode::Backspace => {
+                if let Some(parent) = self.dir.parent() {
+                    let name = self
+                        .dir
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string());
+                    self.enter(parent.to_path_buf());
+                    // Land on the directory just left
+                    if let Some(index) = self
+                        .entries
+                        .iter()
+                        .position(|e| Some(&e.0) == name.as_ref())
+                    {
+                        self.selected = index;
+                    }
+                }
+            }
+            KeyCode::Right | KeyCode::Enter => {
+                if let Some((name, is_dir)) = self.highlighted().cloned() {
+                    let path = self.dir.join(name);
+                    if is_dir {
+                        self.enter(path);
+                    } else if key == KeyCode::Enter {
+                        return PickerOutcome::Pick(path);
+                    }
+                }
+            }
+            // Directories are references too
+            KeyCode::Char(' ') => {
+                if let Some((name, _)) = self.highlighted() {
+                    return PickerOutcome::Pick(self.dir.join(name));
+                }
+            }
+            KeyCode::Char('.') => return PickerOutcome::Pick(self.dir.clone()),
+            KeyCode::Esc | KeyCode::Char('q') => return PickerOutcome::Cancel,
+            _ => {}
+        }
+        PickerOutcome::Continue
+    }
+
+    pub fn render(&self, stdout: &mut io::Stdout, y: u16, width: u16, height: u16) -> Result<()> {
+        let width = width as usize;
+        let rows = (height as usize).saturating_sub(3).max(1);
+        // Keep the highlighted entry on the last row once it scrolls
+        let scroll = (self.selected + 1).saturating_sub(rows);
+
+        execute!(
+            stdout,
+            MoveTo(2, y),
+            SetForegroundColor(Color::Cyan),
+            Print("📎 REFERENCE FILE - copy from:"),
+            MoveTo(4, y + 1),
+            SetForegroundColor(Color::Yellow),
+            Print(
+                self.dir
+                    .display()
+                    .to_string()
+                    .chars()
+                    .take(width.saturating_sub(6))
+                    .collect::<String>()
+            ),
+            ResetColor
+        )?;
+
+        if self.entries.is_empty() {
+            execute!(
+                stdout,
+                MoveTo(4, y + 2),
+                SetForegroundColor(Color::DarkGrey),
+                Print("(empty or unreadable)"),
+                ResetColor
+            )?;
+        }
+
+        for (i, (name, is_dir)) in self.entries.iter().enumerate().skip(scroll).take(rows) {
+            let label = format!("{}{}", name, if *is_dir { "/" } else { "" });
+            let label: String = label.chars().take(width.saturating_sub(8)).collect();
+            execute!(stdout, MoveTo(4, y + 2 + (i - scroll) as u16))?;
+            if i == self.selected {
+                execute!(
+                    stdout,
+                    SetBackgroundColor(Color::DarkGreen),
+                    SetForegroundColor(Color::White),
+                    Print(format!(" > {} ", label)),
+                    ResetColor
+                )?;
+            } else {
+                execute!(
+                    stdout,
+                    SetForegroundColor(if *is_dir { Color::Blue } else { Color::Grey }),
+                    Print(format!("   {}", label)),
+                    ResetColor
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn controls() -> &'static str {
+        " ↑↓: Select | →/Enter: Open dir | ←: Parent | Enter: Pick file | Space: Pick any | .: Pick this dir | Esc: Back "
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_pick_reference() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("conf")).unwrap();
+        fs::write(dir.path().join("conf/app.toml"), "").unwrap();
+        fs::write(dir.path().join("a.txt"), "").unwrap();
+
+        let mut picker = ReferencePicker::new(dir.path());
+        // Directories come first
+        assert_eq!(picker.entries[0], ("conf".to_string(), true));
+        picker.handle_input(KeyCode::Enter);
+        assert_eq!(picker.dir, dir.path().join("conf"));
+        assert!(matches!(
+            picker.handle_input(KeyCode::Enter),
+            PickerOutcome::Pick(ref path) if *path == dir.path().join("conf/app.toml")
+        ));
+
+        picker.handle_input(KeyCode::Left);
+        assert_eq!(picker.dir, dir.path());
+        assert_eq!(picker.selected, 0);
+        assert!(matches!(
+            picker.handle_input(KeyCode::Char(' ')),
+            PickerOutcome::Pick(ref path) if *path == dir.path().join("conf")
+        ));
+        assert!(matches!(
+            picker.handle_input(KeyCode::Esc),
+            PickerOutcome::Cancel
+        ));
+    }
+}
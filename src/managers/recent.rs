@@ -0,0 +1,132 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::utils::config_dir;
+
+/// Recent values kept per manager
+const MAX_RECENT: usize = 5;
+
+/// Modes and owners last applied by the chmod and chown interfaces,
+/// offered again next time
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecentChoices {
+    /// Modes such as "750" or "u+x", most recent first
+    #[serde(default)]
+    modes: Vec<String>,
+    /// chown owner arguments: "user:group", "user" or ":group", most
+    /// recent first
+    #[serde(default)]
+    owners: Vec<String>,
+    /// Whether the last chown was recursive
+    #[serde(default)]
+    recursive: bool,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl RecentChoices {
+    pub fn load() -> Self {
+        let Ok(dir) = config_dir() else {
+            return Self::default();
+        };
+        Self::load_from(dir.join("managers.json"))
+    }
+
+    fn load_from(path: PathBuf) -> Self {
+        let mut recent: Self = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        recent.path = path;
+        recent
+    }
+
+    pub fn modes(&self) -> &[String] {
+        &self.modes
+    }
+
+    pub fn owners(&self) -> &[String] {
+        &self.owners
+    }
+
+    pub fn recursive(&self) -> bool {
+        self.recursive
+    }
+
+    pub fn record_mode(&mut self, mode: &str) -> Result<()> {
+        push_front(&mut self.modes, mode);
+        self.save()
+    }
+
+    pub fn record_owner(&mut self, owner: &str, recursive: bool) -> Result<()> {
+        push_front(&mut self.owners, owner);
+        self.recursive = recursive;
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        if self.path.as_os_str().is_empty() {
+            return Ok(());
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&self.path, json)?;
+        Ok(())
+    }
+}
+
+/// Move `value` to the front of `list`, dropping the oldest past the limit
+fn push_front(list: &mut Vec<String>, value: &str) {
+    list.retain(|v| v != value);
+    list.insert(0, value.to_string());
+    list.truncate(MAX_RECENT);
+}
+
+/// "Recent: 1) 750  2) 644" hint listing `values` under their keys
+pub fn recent_hint(values: &[String]) -> Option<String> {
+    if values.is_empty() {
+        return None;
+    }
+    let items: Vec<String> = values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| format!("{}) {}", i + 1, value))
+        .collect();
+    Some(format!("Recent: {}", items.join("  ")))
+}
+
+/// Index into the recent values picked by a digit key
+pub fn recent_index(c: char) -> Option<usize> {
+    let index = c.to_digit(10)?.checked_sub(1)? as usize;
+    (index < MAX_RECENT).then_some(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_choices_persist() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("managers.json");
+
+        let mut recent = RecentChoices::load_from(path.clone());
+        for mode in ["644", "750", "600", "644", "700", "755", "400"] {
+            recent.record_mode(mode).unwrap();
+        }
+        recent.record_owner("www-data:www-data", true).unwrap();
+
+        let reloaded = RecentChoices::load_from(path);
+        assert_eq!(reloaded.modes(), ["400", "755", "700", "644", "600"]);
+        assert_eq!(reloaded.owners(), ["www-data:www-data"]);
+        assert!(reloaded.recursive());
+        assert_eq!(
+            recent_hint(&reloaded.modes()[..2]).as_deref(),
+            Some("Recent: 1) 400  2) 755")
+        );
+        assert_eq!(recent_index('2'), Some(1));
+        assert_eq!(recent_index('0'), None);
+        assert_eq!(recent_index('9'), None);
+    }
+}
@@ -0,0 +1,150 @@
+use std::fs::{self, DirBuilder, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::access::euid;
+use crate::utils::TimeFormat;
+
+/// A timestamped path in fsnav's private temp directory for a script of
+/// `manager`
+pub fn script_path(manager: &str) -> io::Result<PathBuf> {
+    temp_path(manager, "sh")
+}
+
+/// A timestamped path in fsnav's private temp directory for a dry-run
+/// report of `manager`
+pub fn report_path(manager: &str) -> io::Result<PathBuf> {
+    temp_path(&format!("{}-dry-run", manager), "txt")
+}
+
+fn temp_path(name: &str, extension: &str) -> io::Result<PathBuf> {
+    let dir = private_dir(&std::env::temp_dir())?;
+    let stamp = TimeFormat {
+        pattern: "%Y%m%d-%H%M%S".to_string(),
+        relative: false,
+    }
+    .format(SystemTime::now());
+    let mut path = dir.join(format!("fsnav-{}-{}.{}", name, stamp, extension));
+    // Two exports within the same second
+    for n in 2.. {
+        if fs::symlink_metadata(&path).is_err() {
+            break;
+        }
+        path = dir.join(format!("fsnav-{}-{}-{}.{}", name, stamp, n, extension));
+    }
+    Ok(path)
+}
+
+/// `fsnav-<uid>` under `temp`, created with mode 0700; refused when someone
+/// else made it, or left it a symlink or open to others
+pub fn private_dir(temp: &Path) -> io::Result<PathBuf> {
+    let uid = euid();
+    let dir = temp.join(format!("fsnav-{}", uid));
+    match DirBuilder::new().mode(0o700).create(&dir) {
+        Err(e) if e.kind() != io::ErrorKind::AlreadyExists => return Err(e),
+        _ => {}
+    }
+    let metadata = fs::symlink_metadata(&dir)?;
+    if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o077 != 0 {
+        return Err(io::Error::other(format!(
+            "{} is not a private directory",
+            dir.display()
+        )));
+    }
+    Ok(dir)
+}
+
+/// Create `path` with `mode`, failing if anything (a symlink included)
+/// already exists there
+fn create_new(path: &Path, mode: u32, content: &str) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(mode)
+        .open(path)?;
+    file.write_all(content.as_bytes())?;
+    // The umask may have taken bits away
+    file.set_permissions(fs::Permissions::from_mode(mode))
+}
+
+/// `text` with control characters escaped (`\n`, `\u{1b}`…), so it stays
+/// on the `#` line it is written to instead of starting a command
+pub fn comment_safe(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_control() {
+                c.escape_default().to_string()
+            } else {
+                c.to_string()
+            }
+        })
+        .collect()
+}
+
+/// Write a dry-run report: the commands that would run, then one line per
+/// file they would touch
+pub fn write_report(
+    path: &Path,
+    manager: &str,
+    commands: &[String],
+    lines: &[String],
+) -> io::Result<()> {
+    let mut report = format!(
+        "# Dry run of fsnav's {} interface: nothing was changed\n",
+        manager
+    );
+    for command in commands {
+        report.push_str(&format!("# {}\n", comment_safe(command)));
+    }
+    for line in lines {
+        report.push_str(line);
+        report.push('\n');
+    }
+    create_new(path, 0o644, &report)
+}
+
+/// Write `lines` as an executable shell script that stops at the first
+/// failing command
+pub fn write_script(path: &Path, manager: &str, lines: &[String]) -> io::Result<()> {
+    let mut script = format!(
+        "#!/bin/sh\n# Generated by fsnav's {} interface\nset -e\n",
+        manager
+    );
+    for line in lines {
+        script.push_str(line);
+        script.push('\n');
+    }
+    create_new(path, 0o755, &script)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_files_are_created_new_in_a_private_dir() {
+        let temp = TempDir::new().unwrap();
+        let dir = private_dir(temp.path()).unwrap();
+        assert_eq!(fs::metadata(&dir).unwrap().mode() & 0o777, 0o700);
+        // Made already: reused
+        assert_eq!(private_dir(temp.path()).unwrap(), dir);
+
+        let script = dir.join("chmod.sh");
+        write_script(&script, "chmod", &["chmod 644 -- 'a'".to_string()]).unwrap();
+        assert_eq!(fs::metadata(&script).unwrap().mode() & 0o777, 0o755);
+
+        // A symlink planted at the path is not followed
+        let target = temp.path().join("target");
+        let planted = dir.join("planted.txt");
+        std::os::unix::fs::symlink(&target, &planted).unwrap();
+        assert!(write_report(&planted, "chmod", &[], &[]).is_err());
+        assert!(!target.exists());
+
+        // Open to others: refused
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o777)).unwrap();
+        assert!(private_dir(temp.path()).is_err());
+    }
+}
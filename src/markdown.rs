@@ -0,0 +1,305 @@
+use crossterm::style::{Attribute, Color};
+use std::path::Path;
+
+/// Width of the line drawn for a `---` thematic break
+const RULE_WIDTH: usize = 40;
+
+pub fn is_markdown_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| matches!(e.to_lowercase().as_str(), "md" | "markdown"))
+}
+
+/// How a piece of rendered Markdown is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Plain,
+    Heading,
+    Strong,
+    Emphasis,
+    Code,
+    Link,
+    /// List bullets, quote bars, fences and rules
+    Marker,
+}
+
+impl Style {
+    pub fn color(self) -> Color {
+        match self {
+            Style::Plain | Style::Strong | Style::Emphasis => Color::Reset,
+            Style::Heading => Color::Cyan,
+            Style::Code => Color::Green,
+            Style::Link => Color::Blue,
+            Style::Marker => Color::DarkGrey,
+        }
+    }
+
+    pub fn attribute(self) -> Option<Attribute> {
+        match self {
+            Style::Heading | Style::Strong => Some(Attribute::Bold),
+            Style::Emphasis => Some(Attribute::Italic),
+            Style::Link => Some(Attribute::Underlined),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub text: String,
+    pub style: Style,
+}
+
+impl Span {
+    fn new(text: impl Into<String>, style: Style) -> Self {
+        Self {
+            text: text.into(),
+            style,
+        }
+    }
+}
+
+/// Render Markdown source with its markup replaced by styles. Every source
+/// line gives exactly one rendered line, so line numbers and scrolling are
+/// shared with the raw text.
+pub fn render(lines: &[String]) -> Vec<Vec<Span>> {
+    let mut in_code_block = false;
+    lines
+        .iter()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_code_block = !in_code_block;
+                let language = trimmed.trim_start_matches(['`', '~']).trim();
+                return vec![Span::new(
+                    format!("─── {}", language).trim_end(),
+                    Style::Marker,
+                )];
+            }
+            if in_code_block {
+                return vec![Span::new(line.as_str(), Style::Code)];
+            }
+            render_block_line(line)
+        })
+        .collect()
+}
+
+fn render_block_line(line: &str) -> Vec<Span> {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+
+    let hashes = trimmed.chars().take_while(|&c| c == '#').count();
+    if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(' ') {
+        let text = trimmed[hashes..].trim().trim_end_matches('#').trim_end();
+        return inline(text)
+            .into_iter()
+            .map(|span| Span::new(span.text, Style::Heading))
+            .collect();
+    }
+
+    let rule = trimmed.replace(' ', "");
+    if rule.len() >= 3 && ["-", "*", "_"].iter().any(|c| rule == c.repeat(rule.len())) {
+        return vec![Span::new("─".repeat(RULE_WIDTH), Style::Marker)];
+    }
+
+    if let Some(rest) = trimmed.strip_prefix('>') {
+        let mut spans = vec![Span::new(format!("{}│ ", indent), Style::Marker)];
+        spans.extend(inline(rest.trim_start()));
+        return spans;
+    }
+
+    if let Some(rest) = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|bullet| trimmed.strip_prefix(bullet))
+    {
+        let (marker, rest) = match rest {
+            _ if rest.starts_with("[ ] ") => ("☐ ", &rest[4..]),
+            _ if rest.starts_with("[x] ") || rest.starts_with("[X] ") => ("☑ ", &rest[4..]),
+            _ => ("• ", rest),
+        };
+        let mut spans = vec![Span::new(format!("{}{}", indent, marker), Style::Marker)];
+        spans.extend(inline(rest));
+        return spans;
+    }
+
+    let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 && (trimmed[digits..].starts_with(". ") || trimmed[digits..].starts_with(") ")) {
+        let mut spans = vec![Span::new(
+            format!("{}{}. ", indent, &trimmed[..digits]),
+            Style::Marker,
+        )];
+        spans.extend(inline(&trimmed[digits + 2..]));
+        return spans;
+    }
+
+    let mut spans = inline(trimmed);
+    if !indent.is_empty() {
+        spans.insert(0, Span::new(indent, Style::Plain));
+    }
+    spans
+}
+
+/// Spans of a line's inline markup: `code`, **strong**, *emphasis* and
+/// [links](url), the latter shown by their text alone
+fn inline(text: &str) -> Vec<Span> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut spans: Vec<Span> = Vec::new();
+    let mut current = String::new();
+    let mut strong = false;
+    let mut emphasis = false;
+    let mut i = 0;
+
+    let style = |strong: bool, emphasis: bool| match (strong, emphasis) {
+        (true, _) => Style::Strong,
+        (false, true) => Style::Emphasis,
+        _ => Style::Plain,
+    };
+    let flush = |spans: &mut Vec<Span>, current: &mut String, style: Style| {
+        if !current.is_empty() {
+            spans.push(Span::new(std::mem::take(current), style));
+        }
+    };
+    let find = |from: usize, wanted: char| chars[from..].iter().position(|&c| c == wanted);
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            '\\' if next.is_some_and(|n| n.is_ascii_punctuation()) => {
+                current.push(chars[i + 1]);
+                i += 2;
+            }
+            '`' => match find(i + 1, '`') {
+                Some(len) => {
+                    flush(&mut spans, &mut current, style(strong, emphasis));
+                    spans.push(Span::new(
+                        chars[i + 1..i + 1 + len].iter().collect::<String>(),
+                        Style::Code,
+                    ));
+                    i += len + 2;
+                }
+                None => {
+                    current.push(c);
+                    i += 1;
+                }
+            },
+            '*' | '_' if next == Some(c) => {
+                flush(&mut spans, &mut current, style(strong, emphasis));
+                strong = !strong;
+                i += 2;
+            }
+            '*' | '_' => {
+                let before = i.checked_sub(1).map(|p| chars[p]);
+                // `2 * 3` and snake_case are not emphasis
+                let opens = !emphasis
+                    && next.is_some_and(|n| !n.is_whitespace())
+                    && (c == '*' || !before.is_some_and(char::is_alphanumeric));
+                let closes = emphasis
+                    && before.is_some_and(|b| !b.is_whitespace())
+                    && (c == '*' || !next.is_some_and(char::is_alphanumeric));
+                if opens || closes {
+                    flush(&mut spans, &mut current, style(strong, emphasis));
+                    emphasis = !emphasis;
+                } else {
+                    current.push(c);
+                }
+                i += 1;
+            }
+            '[' | '!' => {
+                let start = if c == '!' { i + 1 } else { i };
+                let link = (chars.get(start) == Some(&'['))
+                    .then(|| find(start + 1, ']'))
+                    .flatten()
+                    .map(|len| start + 1 + len)
+                    .filter(|&close| chars.get(close + 1) == Some(&'('))
+                    .and_then(|close| find(close + 2, ')').map(|len| (close, close + 2 + len)));
+                match link {
+                    Some((close, end)) => {
+                        flush(&mut spans, &mut current, style(strong, emphasis));
+                        spans.push(Span::new(
+                            chars[start + 1..close].iter().collect::<String>(),
+                            Style::Link,
+                        ));
+                        i = end + 1;
+                    }
+                    None => {
+                        current.push(c);
+                        i += 1;
+                    }
+                }
+            }
+            _ => {
+                current.push(c);
+                i += 1;
+            }
+        }
+    }
+    flush(&mut spans, &mut current, style(strong, emphasis));
+    spans
+}
+
+/// The text of a rendered line, without styles
+pub fn plain_text(spans: &[Span]) -> String {
+    spans.iter().map(|span| span.text.as_str()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_render_blocks() {
+        let rendered = render(&source(
+            "# Title #\n\
+             - [x] done\n\
+             \x20 * nested\n\
+             2. second\n\
+             > quoted\n\
+             ---\n\
+             ```rust\n\
+             let x = *y;\n\
+             ```",
+        ));
+        let text: Vec<String> = rendered.iter().map(|line| plain_text(line)).collect();
+        assert_eq!(
+            text,
+            vec![
+                "Title".to_string(),
+                "☑ done".to_string(),
+                "  • nested".to_string(),
+                "2. second".to_string(),
+                "│ quoted".to_string(),
+                "─".repeat(RULE_WIDTH),
+                "─── rust".to_string(),
+                "let x = *y;".to_string(),
+                "───".to_string(),
+            ]
+        );
+        assert_eq!(rendered[0][0].style, Style::Heading);
+        assert_eq!(rendered[7][0].style, Style::Code);
+    }
+
+    #[test]
+    fn test_render_inline() {
+        let spans =
+            inline("Run `cargo test` **now**, see [docs](https://x.y) or *2 * 3* my_var \\*");
+        assert_eq!(
+            spans,
+            vec![
+                Span::new("Run ", Style::Plain),
+                Span::new("cargo test", Style::Code),
+                Span::new(" ", Style::Plain),
+                Span::new("now", Style::Strong),
+                Span::new(", see ", Style::Plain),
+                Span::new("docs", Style::Link),
+                Span::new(" or ", Style::Plain),
+                Span::new("2 * 3", Style::Emphasis),
+                Span::new(" my_var *", Style::Plain),
+            ]
+        );
+    }
+}
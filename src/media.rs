@@ -0,0 +1,837 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Bytes of tags or container metadata read from one place in a file
+const MAX_HEADER_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Top-level boxes or Matroska elements stepped over before giving up
+const MAX_ELEMENTS: usize = 100_000;
+
+/// Tags, duration and stream details of an audio or video file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MediaInfo {
+    /// "MP3", "FLAC", "MP4" or "Matroska"
+    pub format: &'static str,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    /// In seconds
+    pub duration: Option<f64>,
+    /// Average over the whole file, in kbit/s
+    pub bitrate: Option<u32>,
+    /// Width and height of the first video track
+    pub resolution: Option<(u32, u32)>,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u32>,
+}
+
+/// Whether `path` names a file [`MediaInfo::read`] understands
+pub fn is_media_file(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()).is_some_and(|e| {
+        matches!(
+            e.to_lowercase().as_str(),
+            "mp3" | "flac" | "mp4" | "m4a" | "m4v" | "mov" | "mkv" | "mka" | "webm"
+        )
+    })
+}
+
+impl MediaInfo {
+    /// Read what the file's own headers say about it; `None` when they
+    /// cannot be made sense of
+    pub fn read(path: &Path) -> Option<Self> {
+        let mut file = File::open(path).ok()?;
+        let size = file.metadata().ok()?.len();
+        let mut magic = [0u8; 12];
+        let read = file.read(&mut magic).ok()?;
+        let magic = &magic[..read];
+        file.seek(SeekFrom::Start(0)).ok()?;
+
+        let mut info = if magic.starts_with(b"fLaC") {
+            read_flac(&mut file)
+        } else if magic.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+            read_matroska(&mut file)
+        } else if magic.get(4..8) == Some(b"ftyp") {
+            read_mp4(&mut file, size)
+        } else if magic.starts_with(b"ID3") || magic.first() == Some(&0xFF) {
+            read_mp3(&mut file, size)
+        } else {
+            return None;
+        }
+        .ok()?;
+
+        if info.bitrate.is_none() {
+            info.bitrate = info
+                .duration
+                .filter(|&seconds| seconds > 0.0)
+                .map(|seconds| (size as f64 * 8.0 / seconds / 1000.0).round() as u32);
+        }
+        Some(info)
+    }
+
+    /// One labelled line per known detail
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("Format:   {}", self.format)];
+        for (label, value) in [
+            ("Title:   ", &self.title),
+            ("Artist:  ", &self.artist),
+            ("Album:   ", &self.album),
+        ] {
+            if let Some(value) = value {
+                lines.push(format!("{} {}", label, value));
+            }
+        }
+        if let Some(seconds) = self.duration {
+            lines.push(format!("Duration: {}", format_duration(seconds)));
+        }
+        if let Some(bitrate) = self.bitrate {
+            lines.push(format!("Bitrate:  {} kbit/s", bitrate));
+        }
+        if let Some((width, height)) = self.resolution {
+            lines.push(format!("Video:    {}×{}", width, height));
+        }
+        let channels = self.channels.map(|channels| match channels {
+            1 => "mono".to_string(),
+            2 => "stereo".to_string(),
+            n => format!("{} channels", n),
+        });
+        let rate = self
+            .sample_rate
+            .map(|rate| format!("{} kHz", rate as f64 / 1000.0));
+        let audio: Vec<String> = [rate, channels].into_iter().flatten().collect();
+        if !audio.is_empty() {
+            lines.push(format!("Audio:    {}", audio.join(" ")));
+        }
+        lines
+    }
+}
+
+/// `1:02:03` or `2:03`
+pub fn format_duration(seconds: f64) -> String {
+    let total = seconds.round() as u64;
+    let (hours, minutes, seconds) = (total / 3600, total / 60 % 60, total % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+fn read_exact_at(file: &mut File, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+    file.seek(SeekFrom::Start(offset))?;
+    let mut buffer = Vec::new();
+    file.take(len.min(MAX_HEADER_BYTES))
+        .read_to_end(&mut buffer)?;
+    Ok(buffer)
+}
+
+fn be(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0, |value, &b| value << 8 | b as u64)
+}
+
+/// Text with trailing NULs and blanks removed; `None` when nothing is left
+fn clean(text: String) -> Option<String> {
+    let text = text.trim_end_matches('\0').trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+// MP3: ID3v2 (or ID3v1) tags and the first MPEG audio frame
+
+fn read_mp3(file: &mut File, size: u64) -> io::Result<MediaInfo> {
+    let mut info = MediaInfo {
+        format: "MP3",
+        ..Default::default()
+    };
+    let head = read_exact_at(file, 0, 10)?;
+    let mut audio_start = 0;
+    if head.starts_with(b"ID3") && head.len() == 10 {
+        let tag_size = head[6..10]
+            .iter()
+            .fold(0u64, |value, &b| value << 7 | (b & 0x7F) as u64);
+        let tag = read_exact_at(file, 10, tag_size)?;
+        read_id3v2(&tag, head[3], &mut info);
+        audio_start = 10 + tag_size;
+    } else if size >= 128 {
+        let tail = read_exact_at(file, size - 128, 128)?;
+        if tail.starts_with(b"TAG") {
+            let field = |range: std::ops::Range<usize>| {
+                clean(String::from_utf8_lossy(&tail[range]).to_string())
+            };
+            info.title = field(3..33);
+            info.artist = field(33..63);
+            info.album = field(63..93);
+        }
+    }
+
+    // The first frame follows the tag, give or take some padding
+    let window = read_exact_at(file, audio_start, 64 * 1024)?;
+    let Some(start) =
+        (0..window.len().saturating_sub(4)).find(|&i| parse_frame(&window[i..]).is_some())
+    else {
+        return Ok(info);
+    };
+    let frame = parse_frame(&window[start..]).unwrap_or_default();
+    info.sample_rate = Some(frame.sample_rate);
+    info.channels = Some(frame.channels);
+
+    let audio_bytes = size.saturating_sub(audio_start + start as u64);
+    // A Xing or Info header counts the frames of a variable bitrate file
+    let side_info = match (frame.mpeg1, frame.channels) {
+        (true, 1) => 17,
+        (true, _) => 32,
+        (false, 1) => 9,
+        (false, _) => 17,
+    };
+    let xing = start + 4 + side_info;
+    let frames = window
+        .get(xing..xing + 12)
+        .filter(|h| (h.starts_with(b"Xing") || h.starts_with(b"Info")) && h[7] & 1 != 0)
+        .map(|h| be(&h[8..12]));
+    match frames {
+        Some(frames) => {
+            let samples = if frame.mpeg1 { 1152 } else { 576 };
+            info.duration = Some(frames as f64 * samples as f64 / frame.sample_rate as f64);
+        }
+        None if frame.bitrate > 0 => {
+            info.bitrate = Some(frame.bitrate);
+            info.duration = Some(audio_bytes as f64 * 8.0 / (frame.bitrate as f64 * 1000.0));
+        }
+        None => {}
+    }
+    Ok(info)
+}
+
+#[derive(Debug, Default)]
+struct MpegFrame {
+    mpeg1: bool,
+    /// kbit/s
+    bitrate: u32,
+    sample_rate: u32,
+    channels: u32,
+}
+
+/// The header of an MPEG-1/2/2.5 Layer III frame at the start of `bytes`
+fn parse_frame(bytes: &[u8]) -> Option<MpegFrame> {
+    const MPEG1: [u32; 15] = [
+        0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+    ];
+    const MPEG2: [u32; 15] = [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+    let header = bytes.get(..4)?;
+    if header[0] != 0xFF || header[1] & 0xE0 != 0xE0 {
+        return None;
+    }
+    let version = (header[1] >> 3) & 3;
+    let layer = (header[1] >> 1) & 3;
+    let bitrate_index = (header[2] >> 4) as usize;
+    let rate_index = ((header[2] >> 2) & 3) as usize;
+    if version == 1 || layer != 1 || bitrate_index == 15 || rate_index == 3 {
+        return None;
+    }
+    let mpeg1 = version == 3;
+    let base_rate = [44100, 48000, 32000][rate_index];
+    Some(MpegFrame {
+        mpeg1,
+        bitrate: if mpeg1 { MPEG1 } else { MPEG2 }[bitrate_index],
+        sample_rate: match version {
+            3 => base_rate,
+            2 => base_rate / 2,
+            _ => base_rate / 4,
+        },
+        channels: if header[3] >> 6 == 3 { 1 } else { 2 },
+    })
+}
+
+fn read_id3v2(tag: &[u8], major: u8, info: &mut MediaInfo) {
+    let (id_len, header_len) = if major == 2 { (3, 6) } else { (4, 10) };
+    let mut pos = 0;
+    while pos + header_len <= tag.len() {
+        let id = &tag[pos..pos + id_len];
+        if id[0] == 0 {
+            break; // Padding
+        }
+        let size_bytes = &tag[pos + id_len..pos + id_len + if major == 2 { 3 } else { 4 }];
+        let size = if major == 4 {
+            size_bytes
+                .iter()
+                .fold(0usize, |value, &b| value << 7 | (b & 0x7F) as usize)
+        } else {
+            be(size_bytes) as usize
+        };
+        let body =
+            &tag[(pos + header_len).min(tag.len())..(pos + header_len + size).min(tag.len())];
+        let slot = match id {
+            b"TIT2" | b"TT2" => &mut info.title,
+            b"TPE1" | b"TP1" => &mut info.artist,
+            b"TALB" | b"TAL" => &mut info.album,
+            _ => {
+                pos += header_len + size;
+                continue;
+            }
+        };
+        *slot = id3_text(body);
+        pos += header_len + size;
+    }
+}
+
+/// The text of an ID3v2 text frame, after its encoding byte
+fn id3_text(body: &[u8]) -> Option<String> {
+    let (&encoding, text) = body.split_first()?;
+    let text = match encoding {
+        1 | 2 => {
+            let big_endian = encoding == 2 || text.starts_with(&[0xFE, 0xFF]);
+            let text = if text.starts_with(&[0xFE, 0xFF]) || text.starts_with(&[0xFF, 0xFE]) {
+                &text[2..]
+            } else {
+                text
+            };
+            let units: Vec<u16> = text
+                .chunks_exact(2)
+                .map(|pair| {
+                    if big_endian {
+                        u16::from_be_bytes([pair[0], pair[1]])
+                    } else {
+                        u16::from_le_bytes([pair[0], pair[1]])
+                    }
+                })
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+        3 => String::from_utf8_lossy(text).to_string(),
+        // Latin-1
+        _ => text.iter().map(|&b| b as char).collect(),
+    };
+    // Several values are separated by NULs; the first one will do
+    clean(text.split('\0').next().unwrap_or_default().to_string())
+}
+
+// FLAC: STREAMINFO and VORBIS_COMMENT metadata blocks
+
+fn read_flac(file: &mut File) -> io::Result<MediaInfo> {
+    let mut info = MediaInfo {
+        format: "FLAC",
+        ..Default::default()
+    };
+    let mut offset = 4;
+    loop {
+        let header = read_exact_at(file, offset, 4)?;
+        if header.len() < 4 {
+            break;
+        }
+        let length = be(&header[1..4]);
+        let body = read_exact_at(file, offset + 4, length)?;
+        match header[0] & 0x7F {
+            0 if body.len() >= 18 => {
+                let sample_rate = (be(&body[10..13]) >> 4) as u32;
+                let samples = be(&body[13..18]) & 0xF_FFFF_FFFF;
+                info.sample_rate = Some(sample_rate);
+                info.channels = Some(((body[12] >> 1) & 7) as u32 + 1);
+                if sample_rate > 0 && samples > 0 {
+                    info.duration = Some(samples as f64 / sample_rate as f64);
+                }
+            }
+            4 => read_vorbis_comments(&body, &mut info),
+            _ => {}
+        }
+        if header[0] & 0x80 != 0 {
+            break; // Last metadata block
+        }
+        offset += 4 + length;
+    }
+    Ok(info)
+}
+
+fn read_vorbis_comments(body: &[u8], info: &mut MediaInfo) {
+    let le = |pos: usize| {
+        body.get(pos..pos + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+    };
+    let Some(vendor) = le(0) else {
+        return;
+    };
+    let mut pos = 4 + vendor;
+    let count = le(pos).unwrap_or(0);
+    pos += 4;
+    for _ in 0..count {
+        let Some(len) = le(pos) else {
+            return;
+        };
+        let Some(comment) = body.get(pos + 4..pos + 4 + len) else {
+            return;
+        };
+        pos += 4 + len;
+        let comment = String::from_utf8_lossy(comment);
+        let Some((key, value)) = comment.split_once('=') else {
+            continue;
+        };
+        let slot = match key.to_uppercase().as_str() {
+            "TITLE" => &mut info.title,
+            "ARTIST" => &mut info.artist,
+            "ALBUM" => &mut info.album,
+            _ => continue,
+        };
+        if slot.is_none() {
+            *slot = clean(value.to_string());
+        }
+    }
+}
+
+// MP4 / QuickTime: the moov box, wherever it is
+
+fn read_mp4(file: &mut File, size: u64) -> io::Result<MediaInfo> {
+    let mut info = MediaInfo {
+        format: "MP4",
+        ..Default::default()
+    };
+    let mut offset = 0;
+    for _ in 0..MAX_ELEMENTS {
+        if offset + 8 > size {
+            break;
+        }
+        let header = read_exact_at(file, offset, 16)?;
+        let Some((kind, header_len, box_len)) = box_header(&header, size - offset) else {
+            break;
+        };
+        if kind == *b"moov" {
+            let moov = read_exact_at(file, offset + header_len, box_len - header_len)?;
+            read_moov(&moov, &mut info);
+            break;
+        }
+        offset += box_len;
+    }
+    Ok(info)
+}
+
+/// Type, header length and total length of the box starting `bytes`, of
+/// which `remaining` bytes are left in its parent
+fn box_header(bytes: &[u8], remaining: u64) -> Option<([u8; 4], u64, u64)> {
+    let kind: [u8; 4] = bytes.get(4..8)?.try_into().ok()?;
+    let (header_len, len) = match be(bytes.get(..4)?) {
+        0 => (8, remaining),
+        1 => (16, be(bytes.get(8..16)?)),
+        len => (8, len),
+    };
+    (len >= header_len && len <= remaining).then_some((kind, header_len, len))
+}
+
+/// Child boxes of `bytes` as (type, body)
+fn boxes(bytes: &[u8]) -> Vec<([u8; 4], &[u8])> {
+    let mut children = Vec::new();
+    let mut pos = 0;
+    while let Some((kind, header_len, len)) = box_header(&bytes[pos..], (bytes.len() - pos) as u64)
+    {
+        children.push((kind, &bytes[pos + header_len as usize..pos + len as usize]));
+        pos += len as usize;
+    }
+    children
+}
+
+fn read_moov(moov: &[u8], info: &mut MediaInfo) {
+    for (kind, body) in boxes(moov) {
+        match &kind {
+            b"mvhd" => {
+                // Version 1 has 64-bit times and duration
+                let (timescale, duration) = if body.first() == Some(&1) {
+                    (body.get(20..24), body.get(24..32))
+                } else {
+                    (body.get(12..16), body.get(16..20))
+                };
+                if let (Some(timescale), Some(duration)) = (timescale, duration) {
+                    let timescale = be(timescale);
+                    if timescale > 0 {
+                        info.duration = Some(be(duration) as f64 / timescale as f64);
+                    }
+                }
+            }
+            b"trak" if info.resolution.is_none() => {
+                if let Some((_, tkhd)) = boxes(body).into_iter().find(|(k, _)| k == b"tkhd") {
+                    // Width and height are 16.16 fixed point, last in the box
+                    let size = tkhd.len().checked_sub(8).map(|at| &tkhd[at..]);
+                    if let Some(size) = size {
+                        let (width, height) = (be(&size[..4]) >> 16, be(&size[4..]) >> 16);
+                        if width > 0 && height > 0 {
+                            info.resolution = Some((width as u32, height as u32));
+                        }
+                    }
+                }
+            }
+            b"udta" => {
+                let meta = boxes(body).into_iter().find(|(k, _)| k == b"meta");
+                // meta is a full box: version and flags come first
+                if let Some((_, meta)) = meta.filter(|(_, m)| m.len() >= 4) {
+                    if let Some((_, ilst)) =
+                        boxes(&meta[4..]).into_iter().find(|(k, _)| k == b"ilst")
+                    {
+                        read_ilst(ilst, info);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn read_ilst(ilst: &[u8], info: &mut MediaInfo) {
+    for (kind, item) in boxes(ilst) {
+        let slot = match &kind {
+            b"\xa9nam" => &mut info.title,
+            b"\xa9ART" => &mut info.artist,
+            b"\xa9alb" => &mut info.album,
+            _ => continue,
+        };
+        // A data box: type and locale, then the UTF-8 text
+        if let Some((_, data)) = boxes(item).into_iter().find(|(k, _)| k == b"data") {
+            *slot = data
+                .get(8..)
+                .and_then(|text| clean(String::from_utf8_lossy(text).to_string()));
+        }
+    }
+}
+
+// Matroska / WebM: EBML elements of the Segment
+
+const SEGMENT: u64 = 0x1853_8067;
+const INFO: u64 = 0x1549_A966;
+const TRACKS: u64 = 0x1654_AE6B;
+const TAGS: u64 = 0x1254_C367;
+
+fn read_matroska(file: &mut File) -> io::Result<MediaInfo> {
+    let mut info = MediaInfo {
+        format: "Matroska",
+        ..Default::default()
+    };
+    let file_size = file.metadata()?.len();
+    // Skip the EBML header to reach the Segment
+    let head = read_exact_at(file, 0, 64)?;
+    let Some((_, header_len, ebml_len)) = ebml_element(&head) else {
+        return Ok(info);
+    };
+    let segment_at = header_len + ebml_len.unwrap_or(0);
+    let head = read_exact_at(file, segment_at, 16)?;
+    let Some((SEGMENT, header_len, segment_len)) = ebml_element(&head) else {
+        return Ok(info);
+    };
+    let start = segment_at + header_len;
+    let end = segment_len.map_or(file_size, |len| (start + len).min(file_size));
+
+    let mut timecode_scale = 1_000_000.0;
+    let mut duration = None;
+    let mut offset = start;
+    for _ in 0..MAX_ELEMENTS {
+        if offset >= end {
+            break;
+        }
+        let head = read_exact_at(file, offset, 16)?;
+        let Some((id, header_len, len)) = ebml_element(&head) else {
+            break;
+        };
+        // Live streams leave their clusters unsized: nothing after is reachable
+        let Some(len) = len else {
+            break;
+        };
+        if matches!(id, INFO | TRACKS | TAGS) {
+            let body = read_exact_at(file, offset + header_len, len)?;
+            for (child, value) in ebml_children(&body) {
+                match (id, child) {
+                    (INFO, 0x2A_D7B1) => timecode_scale = be(value) as f64,
+                    (INFO, 0x4489) => duration = ebml_float(value),
+                    (INFO, 0x7BA9) if info.title.is_none() => {
+                        info.title = clean(String::from_utf8_lossy(value).to_string())
+                    }
+                    (TRACKS, 0xAE) => read_track(value, &mut info),
+                    (TAGS, 0x7373) => read_tag(value, &mut info),
+                    _ => {}
+                }
+            }
+        }
+        offset += header_len + len;
+    }
+    info.duration = duration.map(|ticks| ticks * timecode_scale / 1e9);
+    Ok(info)
+}
+
+/// ID, header length and body length (`None` when unknown) of the element
+/// starting `bytes`
+fn ebml_element(bytes: &[u8]) -> Option<(u64, u64, Option<u64>)> {
+    let id_len = bytes.first()?.leading_zeros() as usize + 1;
+    if id_len > 4 {
+        return None;
+    }
+    let id = be(bytes.get(..id_len)?);
+    let first = *bytes.get(id_len)?;
+    let size_len = first.leading_zeros() as usize + 1;
+    if size_len > 8 {
+        return None;
+    }
+    let raw = bytes.get(id_len..id_len + size_len)?;
+    let marker = 1u64 << (7 * size_len);
+    let len = be(raw) & (marker - 1);
+    let unknown = len == marker - 1;
+    Some((id, (id_len + size_len) as u64, (!unknown).then_some(len)))
+}
+
+/// Child elements of a master element's body as (ID, body)
+fn ebml_children(bytes: &[u8]) -> Vec<(u64, &[u8])> {
+    let mut children = Vec::new();
+    let mut pos = 0;
+    while let Some((id, header_len, Some(len))) = ebml_element(&bytes[pos..]) {
+        let body_start = pos + header_len as usize;
+        let Some(body) = bytes.get(body_start..body_start + len as usize) else {
+            break;
+        };
+        children.push((id, body));
+        pos = body_start + len as usize;
+    }
+    children
+}
+
+fn ebml_float(bytes: &[u8]) -> Option<f64> {
+    match bytes.len() {
+        4 => Some(f32::from_bits(be(bytes) as u32) as f64),
+        8 => Some(f64::from_bits(be(bytes))),
+        _ => None,
+    }
+}
+
+fn read_track(entry: &[u8], info: &mut MediaInfo) {
+    for (id, value) in ebml_children(entry) {
+        match id {
+            // Video
+            0xE0 if info.resolution.is_none() => {
+                let mut width = None;
+                let mut height = None;
+                for (id, value) in ebml_children(value) {
+                    match id {
+                        0xB0 => width = Some(be(value) as u32),
+                        0xBA => height = Some(be(value) as u32),
+                        _ => {}
+                    }
+                }
+                info.resolution = width.zip(height);
+            }
+            // Audio
+            0xE1 if info.sample_rate.is_none() => {
+                for (id, value) in ebml_children(value) {
+                    match id {
+                        0xB5 => info.sample_rate = ebml_float(value).map(|rate| rate as u32),
+                        0x9F => info.channels = Some(be(value) as u32),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn read_tag(tag: &[u8], info: &mut MediaInfo) {
+    for (id, simple) in ebml_children(tag) {
+        if id != 0x67C8 {
+            continue;
+        }
+        let mut name = None;
+        let mut value = None;
+        for (id, body) in ebml_children(simple) {
+            match id {
+                0x45A3 => name = Some(String::from_utf8_lossy(body).to_uppercase()),
+                0x4487 => value = clean(String::from_utf8_lossy(body).to_string()),
+                _ => {}
+            }
+        }
+        let slot = match name.as_deref() {
+            Some("TITLE") => &mut info.title,
+            Some("ARTIST") => &mut info.artist,
+            Some("ALBUM") => &mut info.album,
+            _ => continue,
+        };
+        if slot.is_none() {
+            *slot = value;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn mp4_box(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut bytes = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+        bytes.extend_from_slice(kind);
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    /// An EBML element with a two-byte size
+    fn element(id: &[u8], body: &[u8]) -> Vec<u8> {
+        let mut bytes = id.to_vec();
+        bytes.extend_from_slice(&(0x4000 | body.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    #[test]
+    fn test_mp3_tags_and_duration() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("song.mp3");
+
+        let frame = |id: &[u8; 4], text: &str| {
+            let mut bytes = id.to_vec();
+            bytes.extend_from_slice(&(text.len() as u32 + 1).to_be_bytes());
+            bytes.extend_from_slice(&[0, 0, 3]);
+            bytes.extend_from_slice(text.as_bytes());
+            bytes
+        };
+        let mut tag = [frame(b"TIT2", "Intro"), frame(b"TPE1", "Band")].concat();
+        tag.extend_from_slice(&[0; 20]);
+        let mut bytes = b"ID3\x03\x00\x00".to_vec();
+        bytes.extend_from_slice(&[0, 0, 0, tag.len() as u8]);
+        bytes.extend_from_slice(&tag);
+        // MPEG-1 Layer III, 128 kbit/s, 44.1 kHz, joint stereo; 16 kB of it
+        // last one second
+        let mut audio = vec![0u8; 16_000];
+        audio[..4].copy_from_slice(&[0xFF, 0xFB, 0x90, 0x40]);
+        bytes.extend_from_slice(&audio);
+        fs::write(&path, bytes).unwrap();
+
+        let info = MediaInfo::read(&path).unwrap();
+        assert_eq!(info.title.as_deref(), Some("Intro"));
+        assert_eq!(info.artist.as_deref(), Some("Band"));
+        assert_eq!(info.bitrate, Some(128));
+        assert_eq!(info.sample_rate, Some(44100));
+        assert_eq!(info.duration.map(format_duration).as_deref(), Some("0:01"));
+        assert!(info
+            .lines()
+            .contains(&"Audio:    44.1 kHz stereo".to_string()));
+    }
+
+    #[test]
+    fn test_flac_streaminfo_and_comments() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("track.flac");
+
+        let mut streaminfo = vec![0u8; 34];
+        // 48 kHz, 2 channels, 16 bits, 48000 * 185 samples
+        let samples: u64 = 48_000 * 185;
+        let packed: u64 = (48_000u64 << 44) | (1 << 41) | (15 << 36) | samples;
+        streaminfo[10..18].copy_from_slice(&packed.to_be_bytes());
+        let mut comments = Vec::new();
+        comments.extend_from_slice(&3u32.to_le_bytes());
+        comments.extend_from_slice(b"lib");
+        comments.extend_from_slice(&2u32.to_le_bytes());
+        for comment in ["album=Live", "ARTIST=Someone"] {
+            comments.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+            comments.extend_from_slice(comment.as_bytes());
+        }
+
+        let mut bytes = b"fLaC".to_vec();
+        bytes.push(0);
+        bytes.extend_from_slice(&(streaminfo.len() as u32).to_be_bytes()[1..]);
+        bytes.extend_from_slice(&streaminfo);
+        bytes.push(0x80 | 4);
+        bytes.extend_from_slice(&(comments.len() as u32).to_be_bytes()[1..]);
+        bytes.extend_from_slice(&comments);
+        fs::write(&path, bytes).unwrap();
+
+        let info = MediaInfo::read(&path).unwrap();
+        assert_eq!(info.format, "FLAC");
+        assert_eq!(info.album.as_deref(), Some("Live"));
+        assert_eq!(info.artist.as_deref(), Some("Someone"));
+        assert_eq!(info.sample_rate, Some(48_000));
+        assert_eq!(info.channels, Some(2));
+        assert_eq!(info.duration.map(format_duration).as_deref(), Some("3:05"));
+    }
+
+    #[test]
+    fn test_mp4_and_matroska() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // mvhd version 0: timescale 1000, 2 h 3 s
+        let mut mvhd = vec![0u8; 20];
+        mvhd[12..16].copy_from_slice(&1000u32.to_be_bytes());
+        mvhd[16..20].copy_from_slice(&7_203_000u32.to_be_bytes());
+        let mut tkhd = vec![0u8; 84];
+        tkhd[76..80].copy_from_slice(&(1920u32 << 16).to_be_bytes());
+        tkhd[80..84].copy_from_slice(&(1080u32 << 16).to_be_bytes());
+        let mut data = vec![0, 0, 0, 1, 0, 0, 0, 0];
+        data.extend_from_slice(b"Holiday");
+        let ilst = mp4_box(b"ilst", &mp4_box(b"\xa9nam", &mp4_box(b"data", &data)));
+        let meta = mp4_box(b"meta", &[vec![0; 4], ilst].concat());
+        let moov = mp4_box(
+            b"moov",
+            &[
+                mp4_box(b"mvhd", &mvhd),
+                mp4_box(b"trak", &mp4_box(b"tkhd", &tkhd)),
+                mp4_box(b"udta", &meta),
+            ]
+            .concat(),
+        );
+        // moov after the media data, as many encoders write it
+        let bytes = [
+            mp4_box(b"ftyp", b"isom\0\0\0\0"),
+            mp4_box(b"mdat", &[0; 1000]),
+            moov,
+        ]
+        .concat();
+        let path = dir.path().join("clip.mp4");
+        fs::write(&path, bytes).unwrap();
+        let info = MediaInfo::read(&path).unwrap();
+        assert_eq!(info.format, "MP4");
+        assert_eq!(info.title.as_deref(), Some("Holiday"));
+        assert_eq!(info.resolution, Some((1920, 1080)));
+        assert_eq!(
+            info.duration.map(format_duration).as_deref(),
+            Some("2:00:03")
+        );
+
+        // Info with a 1 ms timecode scale and a 90.5 s duration
+        let segment_info = element(
+            &[0x15, 0x49, 0xA9, 0x66],
+            &[
+                element(&[0x2A, 0xD7, 0xB1], &[0x0F, 0x42, 0x40]),
+                element(&[0x44, 0x89], &90_500f64.to_be_bytes()),
+            ]
+            .concat(),
+        );
+        let video = element(
+            &[0xE0],
+            &[
+                element(&[0xB0], &[0x05, 0x00]),
+                element(&[0xBA], &[0x02, 0xD0]),
+            ]
+            .concat(),
+        );
+        let tracks = element(&[0x16, 0x54, 0xAE, 0x6B], &element(&[0xAE], &video));
+        let simple_tag = element(
+            &[0x67, 0xC8],
+            &[
+                element(&[0x45, 0xA3], b"ARTIST"),
+                element(&[0x44, 0x87], b"Crew"),
+            ]
+            .concat(),
+        );
+        let tags = element(
+            &[0x12, 0x54, 0xC3, 0x67],
+            &element(&[0x73, 0x73], &simple_tag),
+        );
+        let cluster = element(&[0x1F, 0x43, 0xB6, 0x75], &[0; 100]);
+        let segment = element(
+            &[0x18, 0x53, 0x80, 0x67],
+            &[segment_info, tracks, cluster, tags].concat(),
+        );
+        let bytes = [
+            element(&[0x1A, 0x45, 0xDF, 0xA3], &[0x42, 0x82, 0x84]),
+            segment,
+        ]
+        .concat();
+        let path = dir.path().join("film.mkv");
+        fs::write(&path, bytes).unwrap();
+        let info = MediaInfo::read(&path).unwrap();
+        assert_eq!(info.format, "Matroska");
+        assert_eq!(info.resolution, Some((1280, 720)));
+        assert_eq!(info.artist.as_deref(), Some("Crew"));
+        assert_eq!(info.duration.map(format_duration).as_deref(), Some("1:31"));
+
+        assert!(is_media_file(&path));
+        assert!(!is_media_file(Path::new("notes.txt")));
+    }
+}
@@ -1,4 +1,7 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::utils::{get_owner_group, is_virtual_fs};
 
 #[derive(Debug, Clone)]
 pub struct FileEntry {
@@ -14,9 +17,87 @@ pub struct FileEntry {
     pub uid: Option<u32>,
     #[allow(dead_code)]
     pub gid: Option<u32>,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
 }
 
 impl FileEntry {
+    /// Build an entry from a path on disk, following symlinks for type and size
+    ///
+    /// Files in procfs and sysfs get a size of 0: the 0 or 4096 bytes they
+    /// report says nothing about what reading them returns.
+    pub fn from_path(path: PathBuf, name: String) -> Self {
+        let metadata = path.metadata();
+        let is_symlink = path
+            .symlink_metadata()
+            .map(|m| m.file_type().is_symlink())
+            .unwrap_or(false);
+
+        let permissions = metadata.as_ref().ok().map(|m| {
+            use std::os::unix::fs::PermissionsExt;
+            m.permissions().mode()
+        });
+
+        let (owner, group, uid, gid) = get_owner_group(&path);
+        let is_dir = metadata.as_ref().map(|m| m.is_dir()).unwrap_or(false);
+        let size = if is_dir || !is_virtual_fs(&path) {
+            metadata.as_ref().map(|m| m.len()).unwrap_or(0)
+        } else {
+            0
+        };
+
+        Self {
+            name,
+            is_dir,
+            is_accessible: metadata.is_ok(),
+            is_symlink,
+            permissions,
+            owner,
+            group,
+            uid,
+            gid,
+            size,
+            modified: metadata.as_ref().ok().and_then(|m| m.modified().ok()),
+            path,
+        }
+    }
+
+    /// The synthetic ".." entry pointing at `parent`
+    pub fn parent_link(parent: &Path) -> Self {
+        Self {
+            name: "..".to_string(),
+            path: parent.to_path_buf(),
+            is_dir: true,
+            is_accessible: true,
+            is_symlink: false,
+            permissions: None,
+            owner: None,
+            group: None,
+            uid: None,
+            gid: None,
+            size: 0,
+            modified: None,
+        }
+    }
+
+    /// Placeholder entry shown when a directory cannot be read
+    pub fn error(path: &Path, error: &std::io::Error) -> Self {
+        Self {
+            name: format!("⚠️  Error: {}", error),
+            path: path.to_path_buf(),
+            is_dir: false,
+            is_accessible: false,
+            is_symlink: false,
+            permissions: None,
+            owner: None,
+            group: None,
+            uid: None,
+            gid: None,
+            size: 0,
+            modified: None,
+        }
+    }
+
     pub fn display_name(&self) -> String {
         let icon = if self.is_symlink {
             "🔗"
@@ -83,6 +164,8 @@ mod tests {
             group: Some("group".to_string()),
             uid: Some(1000),
             gid: Some(1000),
+            size: 0,
+            modified: None,
         };
         assert_eq!(dir_entry.display_name(), "📁 test_dir/");
 
@@ -97,6 +180,8 @@ mod tests {
             group: Some("group".to_string()),
             uid: Some(1000),
             gid: Some(1000),
+            size: 0,
+            modified: None,
         };
         assert_eq!(file_entry.display_name(), "📄 test.txt");
     }
@@ -114,6 +199,8 @@ mod tests {
             group: None,
             uid: None,
             gid: None,
+            size: 0,
+            modified: None,
         };
         assert_eq!(entry.permissions_string(), "rwxr-xr-x");
     }
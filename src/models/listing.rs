@@ -0,0 +1,163 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::{sort_entries, FileEntry, Grouping, NameOrder, SortKey};
+use crate::utils::{Pattern, PatternMode};
+
+/// Settings that shape how a directory is listed
+#[derive(Debug, Clone, Default)]
+pub struct ListingOptions {
+    pub sort_key: SortKey,
+    pub name_order: NameOrder,
+    pub grouping: Grouping,
+    pub show_hidden: bool,
+}
+
+/// Sorted entries of a directory, without the ".." link
+#[derive(Debug, Clone, Default)]
+pub struct DirectoryListing {
+    pub entries: Vec<FileEntry>,
+    /// Number of dotfiles left out because hidden entries are not shown
+    pub hidden_count: usize,
+}
+
+/// Entries whose name contains `text`, ignoring case, or matches it as a
+/// glob (`*.rs`) when it contains `*`; ".." never matches
+pub fn filter_entries(entries: &[FileEntry], text: &str) -> Vec<FileEntry> {
+    let needle = text.to_lowercase();
+    let mode = if needle.contains('*') {
+        PatternMode::Glob
+    } else {
+        PatternMode::Literal
+    };
+    // Globs and literals always compile
+    let Ok(pattern) = Pattern::new(&needle, mode) else {
+        return Vec::new();
+    };
+    entries
+        .iter()
+        .filter(|e| e.name != ".." && pattern.is_match(&e.name.to_lowercase()))
+        .cloned()
+        .collect()
+}
+
+/// Move entries named in `pinned` to the front, in pinning order
+///
+/// The rest keep their sorted order, so pins survive any sort key.
+pub fn pin_entries(entries: &mut [FileEntry], pinned: &[String]) {
+    if pinned.is_empty() {
+        return;
+    }
+    entries.sort_by_key(|e| {
+        pinned
+            .iter()
+            .position(|name| *name == e.name)
+            .unwrap_or(usize::MAX)
+    });
+}
+
+pub fn read_directory(path: &Path, options: &ListingOptions) -> io::Result<DirectoryListing> {
+    let mut listing = DirectoryListing::default();
+
+    for entry in fs::read_dir(path)?.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+
+        // Skip hidden files on Unix-like systems
+        #[cfg(unix)]
+        if name.starts_with('.') && !options.show_hidden {
+            listing.hidden_count += 1;
+            continue;
+        }
+
+        listing
+            .entries
+            .push(FileEntry::from_path(entry.path(), name));
+    }
+
+    sort_entries(
+        &mut listing.entries,
+        options.sort_key,
+        options.name_order,
+        options.grouping,
+    );
+    Ok(listing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_read_directory_counts_hidden() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("sub")).unwrap();
+        fs::write(temp_dir.path().join("file.txt"), "hello").unwrap();
+        fs::write(temp_dir.path().join(".hidden"), "").unwrap();
+
+        let listing = read_directory(temp_dir.path(), &ListingOptions::default()).unwrap();
+        let names: Vec<_> = listing.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["sub", "file.txt"]);
+        assert_eq!(listing.hidden_count, 1);
+        assert_eq!(listing.entries[1].size, 5);
+
+        let options = ListingOptions {
+            show_hidden: true,
+            ..Default::default()
+        };
+        let listing = read_directory(temp_dir.path(), &options).unwrap();
+        assert_eq!(listing.entries.len(), 3);
+        assert_eq!(listing.hidden_count, 0);
+    }
+
+    #[test]
+    fn test_pin_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        for name in ["a.txt", "b.txt", "docker-compose.yml", "README.md"] {
+            fs::write(temp_dir.path().join(name), "").unwrap();
+        }
+        fs::create_dir(temp_dir.path().join("src")).unwrap();
+
+        let options = ListingOptions {
+            sort_key: SortKey::Size,
+            ..Default::default()
+        };
+        let mut entries = read_directory(temp_dir.path(), &options).unwrap().entries;
+        let pinned = ["README.md".to_string(), "docker-compose.yml".to_string()];
+        pin_entries(&mut entries, &pinned);
+
+        let names: Vec<_> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["README.md", "docker-compose.yml", "src", "a.txt", "b.txt"]
+        );
+    }
+
+    #[test]
+    fn test_filter_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("Cargo.toml"), "").unwrap();
+        fs::write(temp_dir.path().join("cargo.lock"), "").unwrap();
+        fs::write(temp_dir.path().join("README.md"), "").unwrap();
+
+        let mut entries = vec![FileEntry::parent_link(temp_dir.path())];
+        entries.extend(
+            read_directory(temp_dir.path(), &ListingOptions::default())
+                .unwrap()
+                .entries,
+        );
+
+        let names = |text| -> Vec<String> {
+            filter_entries(&entries, text)
+                .into_iter()
+                .map(|e| e.name)
+                .collect()
+        };
+        assert_eq!(names("CARGO"), vec!["cargo.lock", "Cargo.toml"]);
+        assert_eq!(names("."), vec!["cargo.lock", "Cargo.toml", "README.md"]);
+        assert!(names("zzz").is_empty());
+        assert_eq!(names("*.TOML"), vec!["Cargo.toml"]);
+        assert!(names("*.toml.bak").is_empty());
+    }
+}
@@ -1,5 +1,9 @@
 mod exit_action;
 mod file_entry;
+mod listing;
+mod sort;
 
 pub use exit_action::ExitAction;
 pub use file_entry::FileEntry;
+pub use listing::{filter_entries, pin_entries, read_directory, DirectoryListing, ListingOptions};
+pub use sort::{sort_entries, Grouping, NameOrder, SortKey};
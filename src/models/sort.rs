@@ -0,0 +1,303 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::ffi::CString;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use super::FileEntry;
+
+/// Where directories go relative to files, whatever the sort key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Grouping {
+    #[default]
+    DirsFirst,
+    /// Directories and files interleaved in a single ordering
+    Mixed,
+    FilesFirst,
+}
+
+impl Grouping {
+    pub fn next(self) -> Self {
+        match self {
+            Grouping::DirsFirst => Grouping::Mixed,
+            Grouping::Mixed => Grouping::FilesFirst,
+            Grouping::FilesFirst => Grouping::DirsFirst,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Grouping::DirsFirst => "directories first",
+            Grouping::Mixed => "mixed",
+            Grouping::FilesFirst => "files first",
+        }
+    }
+
+    fn compare(self, a: &FileEntry, b: &FileEntry) -> Ordering {
+        match self {
+            Grouping::DirsFirst => b.is_dir.cmp(&a.is_dir),
+            Grouping::Mixed => Ordering::Equal,
+            Grouping::FilesFirst => a.is_dir.cmp(&b.is_dir),
+        }
+    }
+}
+
+/// How names are compared when sorting by name (and breaking ties)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NameOrder {
+    /// Case-insensitive, with digit runs compared by value: file2 < file10
+    #[default]
+    Natural,
+    /// Case-insensitive, character by character: file10 < file2
+    Lexicographic,
+    /// The user's `LC_COLLATE` rules (`strcoll`)
+    Locale,
+}
+
+impl NameOrder {
+    pub fn compare(self, a: &str, b: &str) -> Ordering {
+        match self {
+            NameOrder::Natural => natural_cmp(a, b),
+            NameOrder::Lexicographic => a.to_lowercase().cmp(&b.to_lowercase()),
+            NameOrder::Locale => locale_cmp(a, b),
+        }
+        // Names differing only in case still get a stable order
+        .then_with(|| a.cmp(b))
+    }
+}
+
+/// Compare names case-insensitively, treating runs of ASCII digits as numbers
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let ordering = compare_numbers(&take_digits(&mut a), &take_digits(&mut b));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let ordering = x.to_lowercase().cmp(y.to_lowercase());
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+fn take_digits(chars: &mut Peekable<Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+        digits.push(c);
+    }
+    digits
+}
+
+/// Compare digit strings by value without parsing, so any length works;
+/// equal values with more leading zeros sort last ("01" after "1")
+fn compare_numbers(a: &str, b: &str) -> Ordering {
+    let a_trimmed = a.trim_start_matches('0');
+    let b_trimmed = b.trim_start_matches('0');
+    a_trimmed
+        .len()
+        .cmp(&b_trimmed.len())
+        .then_with(|| a_trimmed.cmp(b_trimmed))
+        .then_with(|| a.len().cmp(&b.len()))
+}
+
+fn locale_cmp(a: &str, b: &str) -> Ordering {
+    match (CString::new(a), CString::new(b)) {
+        // Safety: both pointers are valid NUL-terminated strings
+        (Ok(a), Ok(b)) => unsafe { libc::strcoll(a.as_ptr(), b.as_ptr()) }.cmp(&0),
+        _ => a.cmp(b),
+    }
+}
+
+/// Key used to order directory listings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    #[default]
+    Name,
+    Size,
+    Modified,
+}
+
+impl SortKey {
+    pub fn next(self) -> Self {
+        match self {
+            SortKey::Name => SortKey::Size,
+            SortKey::Size => SortKey::Modified,
+            SortKey::Modified => SortKey::Name,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortKey::Name => "name",
+            SortKey::Size => "size",
+            SortKey::Modified => "modified",
+        }
+    }
+
+    /// Compare two entries by this key
+    /// Size and modification time sort largest/newest first, ties fall back to name
+    pub fn compare(self, a: &FileEntry, b: &FileEntry, names: NameOrder) -> Ordering {
+        let by_name = || names.compare(&a.name, &b.name);
+        match self {
+            SortKey::Name => by_name(),
+            SortKey::Size => b.size.cmp(&a.size).then_with(by_name),
+            SortKey::Modified => b.modified.cmp(&a.modified).then_with(by_name),
+        }
+    }
+}
+
+/// Sort entries by `key`, grouping directories and files as requested
+pub fn sort_entries(entries: &mut [FileEntry], key: SortKey, names: NameOrder, grouping: Grouping) {
+    entries.sort_by(|a, b| {
+        grouping
+            .compare(a, b)
+            .then_with(|| key.compare(a, b, names))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::time::{Duration, SystemTime};
+
+    fn entry(name: &str, is_dir: bool, size: u64, age_secs: u64) -> FileEntry {
+        let mut entry = FileEntry::parent_link(&PathBuf::from("/"));
+        entry.name = name.to_string();
+        entry.is_dir = is_dir;
+        entry.size = size;
+        entry.modified = Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000 - age_secs));
+        entry
+    }
+
+    fn names(entries: &[FileEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_sort_by_name_groups_directories() {
+        let mut entries = vec![
+            entry("b.txt", false, 1, 0),
+            entry("Zdir", true, 0, 0),
+            entry("a.txt", false, 1, 0),
+            entry("adir", true, 0, 0),
+        ];
+        sort_entries(
+            &mut entries,
+            SortKey::Name,
+            NameOrder::Natural,
+            Grouping::DirsFirst,
+        );
+        assert_eq!(names(&entries), vec!["adir", "Zdir", "a.txt", "b.txt"]);
+    }
+
+    #[test]
+    fn test_sort_by_size_and_modified() {
+        let mut entries = vec![
+            entry("small", false, 10, 5),
+            entry("big", false, 1000, 50),
+            entry("medium", false, 100, 1),
+        ];
+        sort_entries(
+            &mut entries,
+            SortKey::Size,
+            NameOrder::Natural,
+            Grouping::DirsFirst,
+        );
+        assert_eq!(names(&entries), vec!["big", "medium", "small"]);
+
+        sort_entries(
+            &mut entries,
+            SortKey::Modified,
+            NameOrder::Natural,
+            Grouping::DirsFirst,
+        );
+        assert_eq!(names(&entries), vec!["medium", "small", "big"]);
+    }
+
+    #[test]
+    fn test_natural_order() {
+        let mut names = vec![
+            "file10", "File2", "file1", "file02", "file", "a100b", "a99c",
+        ];
+        names.sort_by(|a, b| NameOrder::Natural.compare(a, b));
+        assert_eq!(
+            names,
+            vec!["a99c", "a100b", "file", "file1", "File2", "file02", "file10"]
+        );
+
+        // Digit runs longer than any integer type still compare by value
+        assert_eq!(
+            NameOrder::Natural.compare("v99999999999999999999999", "v100000000000000000000000"),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_lexicographic_order() {
+        let mut names = vec!["file10", "File2", "file1"];
+        names.sort_by(|a, b| NameOrder::Lexicographic.compare(a, b));
+        assert_eq!(names, vec!["file1", "file10", "File2"]);
+    }
+
+    #[test]
+    fn test_parse_name_order() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            order: NameOrder,
+        }
+        let parsed: Wrapper = toml::from_str("order = \"locale\"").unwrap();
+        assert_eq!(parsed.order, NameOrder::Locale);
+        // Under the default C locale collation is plain byte order
+        assert_eq!(NameOrder::Locale.compare("a", "b"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_grouping_policies() {
+        let mut entries = vec![
+            entry("medium.txt", false, 100, 0),
+            entry("dir", true, 4096, 0),
+            entry("big.iso", false, 10_000, 0),
+        ];
+        sort_entries(
+            &mut entries,
+            SortKey::Size,
+            NameOrder::Natural,
+            Grouping::Mixed,
+        );
+        assert_eq!(names(&entries), vec!["big.iso", "dir", "medium.txt"]);
+
+        sort_entries(
+            &mut entries,
+            SortKey::Size,
+            NameOrder::Natural,
+            Grouping::FilesFirst,
+        );
+        assert_eq!(names(&entries), vec!["big.iso", "medium.txt", "dir"]);
+
+        sort_entries(
+            &mut entries,
+            SortKey::Size,
+            NameOrder::Natural,
+            Grouping::DirsFirst,
+        );
+        assert_eq!(names(&entries), vec!["dir", "big.iso", "medium.txt"]);
+    }
+}
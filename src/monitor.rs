@@ -0,0 +1,390 @@
+use anyhow::Result;
+use crossterm::{
+    cursor::MoveTo,
+    event::KeyCode,
+    execute,
+    style::{Color, Print, ResetColor},
+    terminal,
+};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::snapshot::{Change, Snapshot};
+use crate::ui::{SetBackgroundColor, SetForegroundColor};
+use crate::utils::TimeFormat;
+
+/// How often the watched tree is rescanned
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    Created,
+    Modified,
+    Deleted,
+}
+
+impl EventKind {
+    fn label(self) -> &'static str {
+        match self {
+            EventKind::Created => "created",
+            EventKind::Modified => "modified",
+            EventKind::Deleted => "deleted",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            EventKind::Created => Color::Green,
+            EventKind::Modified => Color::Yellow,
+            EventKind::Deleted => Color::Red,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorEvent {
+    pub time: SystemTime,
+    pub kind: EventKind,
+    /// Path relative to the watched directory
+    pub path: String,
+    pub details: String,
+}
+
+impl MonitorEvent {
+    fn from_change(change: Change, time: SystemTime) -> Self {
+        let (kind, path, details) = match change {
+            Change::Added(path) => (EventKind::Created, path, String::new()),
+            Change::Removed(path) => (EventKind::Deleted, path, String::new()),
+            Change::Modified(path, details) => (EventKind::Modified, path, details.join(", ")),
+        };
+        Self {
+            time,
+            kind,
+            path,
+            details,
+        }
+    }
+
+    fn line(&self, time_format: &TimeFormat) -> String {
+        let mut line = format!(
+            "{}  {:<8}  {}",
+            time_format.format(self.time),
+            self.kind.label(),
+            self.path
+        );
+        if !self.details.is_empty() {
+            line.push_str(&format!("  ({})", self.details));
+        }
+        line
+    }
+}
+
+/// Polling thread streaming the changes of a directory
+struct MonitorWorker {
+    receiver: Receiver<MonitorEvent>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl Drop for MonitorWorker {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl MonitorWorker {
+    fn spawn(root: PathBuf, recursive: bool) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let worker_cancel = Arc::clone(&cancel);
+
+        thread::spawn(move || {
+            let mut previous = Snapshot::capture(&root, false, recursive).ok();
+            loop {
+                thread::sleep(POLL_INTERVAL);
+                if worker_cancel.load(Ordering::Relaxed) {
+                    return;
+                }
+
+                // A vanished directory reads as everything deleted
+                let current = Snapshot::capture(&root, false, recursive).ok();
+                if let (Some(before), Some(after)) = (&previous, &current) {
+                    let now = SystemTime::now();
+                    for change in before.compare(after) {
+                        if sender.send(MonitorEvent::from_change(change, now)).is_err() {
+                            return;
+                        }
+                    }
+                }
+                if current.is_some() {
+                    previous = current;
+                }
+            }
+        });
+
+        Self { receiver, cancel }
+    }
+}
+
+/// Live log of created, modified and deleted entries under a directory
+pub struct Monitor {
+    root: PathBuf,
+    recursive: bool,
+    events: Vec<MonitorEvent>,
+    worker: MonitorWorker,
+    scroll_offset: usize,
+    /// Keep the newest events in view
+    follow: bool,
+    status: Option<String>,
+}
+
+impl Monitor {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            recursive: false,
+            events: Vec::new(),
+            worker: MonitorWorker::spawn(root.to_path_buf(), false),
+            scroll_offset: 0,
+            follow: true,
+            status: None,
+        }
+    }
+
+    /// Collect events reported since the last call; returns how many arrived
+    pub fn poll(&mut self) -> usize {
+        let mut received = 0;
+        while let Ok(event) = self.worker.receiver.try_recv() {
+            self.events.push(event);
+            received += 1;
+        }
+        received
+    }
+
+    fn toggle_recursive(&mut self) {
+        self.recursive = !self.recursive;
+        // The new worker takes a fresh baseline at the new depth
+        self.worker = MonitorWorker::spawn(self.root.clone(), self.recursive);
+        self.status = Some(if self.recursive {
+            "Watching the whole subtree".to_string()
+        } else {
+            "Watching this directory only".to_string()
+        });
+    }
+
+    /// Write the log with full timestamps to `path`
+    fn export_to(&self, path: &Path) -> io::Result<()> {
+        let time_format = TimeFormat {
+            pattern: "%Y-%m-%d %H:%M:%S".to_string(),
+            relative: false,
+        };
+        let mut content = format!("# fsnav monitor of {}\n", self.root.display());
+        for event in &self.events {
+            content.push_str(&event.line(&time_format));
+            content.push('\n');
+        }
+        fs::write(path, content)
+    }
+
+    fn export(&mut self) {
+        let stamp = TimeFormat {
+            pattern: "%Y%m%d-%H%M%S".to_string(),
+            relative: false,
+        }
+        .format(SystemTime::now());
+        let path = std::env::temp_dir().join(format!("fsnav-monitor-{}.log", stamp));
+
+        self.status = Some(match self.export_to(&path) {
+            Ok(()) => format!(
+                "Exported {} events to {}",
+                self.events.len(),
+                path.display()
+            ),
+            Err(e) => format!("Export failed: {}", e),
+        });
+    }
+
+    pub fn render(&self) -> Result<()> {
+        let mut stdout = io::stdout();
+        let (terminal_width, terminal_height) = terminal::size()?;
+        let width = terminal_width as usize;
+
+        execute!(stdout, terminal::Clear(terminal::ClearType::All))?;
+
+        let header: String = format!(
+            " 👁 MONITOR {} ({})",
+            self.root.display(),
+            if self.recursive {
+                "subtree"
+            } else {
+                "directory"
+            }
+        )
+        .chars()
+        .take(width)
+        .collect();
+        execute!(
+            stdout,
+            MoveTo(0, 0),
+            SetBackgroundColor(Color::DarkBlue),
+            SetForegroundColor(Color::White),
+            Print(format!("{:<width$}", header, width = width)),
+            ResetColor
+        )?;
+
+        let info = self.status.clone().unwrap_or_else(|| {
+            format!(
+                "{} events — rescanning every {} ms",
+                self.events.len(),
+                POLL_INTERVAL.as_millis()
+            )
+        });
+        execute!(
+            stdout,
+            MoveTo(1, 1),
+            SetForegroundColor(Color::Yellow),
+            Print(
+                info.chars()
+                    .take(width.saturating_sub(2))
+                    .collect::<String>()
+            ),
+            ResetColor
+        )?;
+
+        let body_height = (terminal_height as usize).saturating_sub(4);
+        let start = if self.follow {
+            self.events.len().saturating_sub(body_height)
+        } else {
+            self.scroll_offset
+        };
+
+        if self.events.is_empty() {
+            execute!(
+                stdout,
+                MoveTo(1, 3),
+                SetForegroundColor(Color::DarkGrey),
+                Print("Waiting for changes…"),
+                ResetColor
+            )?;
+        }
+
+        let time_format = TimeFormat {
+            pattern: "%H:%M:%S".to_string(),
+            relative: false,
+        };
+        for (i, event) in self.events.iter().skip(start).take(body_height).enumerate() {
+            execute!(
+                stdout,
+                MoveTo(1, 3 + i as u16),
+                SetForegroundColor(event.kind.color()),
+                Print(
+                    event
+                        .line(&time_format)
+                        .chars()
+                        .take(width.saturating_sub(2))
+                        .collect::<String>()
+                ),
+                ResetColor
+            )?;
+        }
+
+        let footer = " ↑↓: Scroll | End: Follow | r: Directory/subtree | e: Export | c: Clear | Esc/q: Close";
+        execute!(
+            stdout,
+            MoveTo(0, terminal_height.saturating_sub(1)),
+            SetBackgroundColor(Color::DarkGrey),
+            SetForegroundColor(Color::White),
+            Print(format!(
+                "{:<width$}",
+                footer.chars().take(width).collect::<String>(),
+                width = width
+            )),
+            ResetColor
+        )?;
+
+        stdout.flush()?;
+        Ok(())
+    }
+
+    /// Returns false once the monitor should close
+    pub fn handle_input(&mut self, key: KeyCode) -> bool {
+        let max_offset = self.events.len().saturating_sub(1);
+        if self.follow && matches!(key, KeyCode::Up | KeyCode::PageUp | KeyCode::Home) {
+            // Start scrolling from what is currently on screen
+            let body_height = terminal::size()
+                .map(|(_, h)| (h as usize).saturating_sub(4))
+                .unwrap_or(0);
+            self.scroll_offset = self.events.len().saturating_sub(body_height);
+            self.follow = false;
+        }
+
+        match key {
+            KeyCode::Up => self.scroll_offset = self.scroll_offset.saturating_sub(1),
+            KeyCode::Down => self.scroll_offset = (self.scroll_offset + 1).min(max_offset),
+            KeyCode::PageUp => self.scroll_offset = self.scroll_offset.saturating_sub(10),
+            KeyCode::PageDown => self.scroll_offset = (self.scroll_offset + 10).min(max_offset),
+            KeyCode::Home => self.scroll_offset = 0,
+            KeyCode::End => self.follow = true,
+            KeyCode::Char('r') => self.toggle_recursive(),
+            KeyCode::Char('e') => self.export(),
+            KeyCode::Char('c') => {
+                self.events.clear();
+                self.scroll_offset = 0;
+                self.follow = true;
+                self.status = None;
+            }
+            KeyCode::Esc | KeyCode::Char('q') => return false,
+            _ => {}
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn wait_for_events(monitor: &mut Monitor, count: usize) {
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+        while monitor.events.len() < count && std::time::Instant::now() < deadline {
+            monitor.poll();
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    #[test]
+    fn test_monitor_streams_events_and_exports() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("old"), "").unwrap();
+
+        let mut monitor = Monitor::new(dir.path());
+        // Let the worker take its baseline before changing anything
+        thread::sleep(POLL_INTERVAL / 2);
+        fs::write(dir.path().join("new"), "").unwrap();
+        fs::remove_file(dir.path().join("old")).unwrap();
+
+        wait_for_events(&mut monitor, 2);
+        let mut kinds: Vec<_> = monitor
+            .events
+            .iter()
+            .map(|e| (e.kind, e.path.as_str()))
+            .collect();
+        kinds.sort_by_key(|(_, path)| *path);
+        assert_eq!(
+            kinds,
+            vec![(EventKind::Created, "new"), (EventKind::Deleted, "old")]
+        );
+
+        let export = dir.path().join("export.log");
+        monitor.export_to(&export).unwrap();
+        let content = fs::read_to_string(&export).unwrap();
+        assert_eq!(content.lines().count(), 3);
+        assert!(content.contains("created   new"));
+    }
+}
@@ -1,26 +1,186 @@
-use crate::bookmarks::BookmarksManager;
-use crate::managers::{ChmodInterface, ChownInterface};
-use crate::models::{ExitAction, FileEntry};
-use crate::preview::{FilePreview, PreviewContent};
-use crate::search::SearchMode;
-use crate::split_pane::SplitPaneView;
-use crate::ui::{RenderContext, Renderer};
-use crate::utils::{get_owner_group, is_root_user, match_pattern};
+use crate::actions::{Action, Profile};
+use crate::archive;
+use crate::bookmarks::{
+    groups_by_category, is_valid_shortcut, parse_tags, Availability, BookmarkHealth,
+    BookmarksManager, DirVisits, SHARED_BOOKMARKS_PATH,
+};
+use crate::checksums::{Algorithm, ChecksumOutcome, ChecksumRun};
+use crate::config::Config;
+use crate::containers::{container_runtime, ContainerVolumes, ContainersOutcome};
+use crate::custom_commands::{CommandOutput, CustomCommand};
+use crate::diff::DiffView;
+use crate::dir_settings::DirSettingsCache;
+use crate::dir_size::{SelectionSize, SizeTotal};
+use crate::dir_stats::{entry_extension, DirStats, StatsOutcome};
+use crate::extract::Extraction;
+use crate::finder::{FinderOutcome, FuzzyFinder};
+use crate::graphics::ImageCanvas;
+use crate::help::{keymap, HelpOverlay};
+use crate::hex_editor::{parse_offset, HexEditor};
+use crate::hot_dirs::HotDirs;
+use crate::ignore::ExcludeRules;
+use crate::managers::{
+    can_chmod, chown_paths, euid, set_mode, ChmodInterface, ChownInterface, OwnershipHistory,
+    RecentChoices,
+};
+use crate::markdown;
+use crate::models::{
+    filter_entries, pin_entries, read_directory, DirectoryListing, ExitAction, FileEntry,
+    ListingOptions, SortKey,
+};
+use crate::monitor::Monitor;
+use crate::open_with::{Associations, OpenWithMenu, OpenWithOutcome};
+use crate::pager::Pager;
+use crate::palette::{CommandPalette, PaletteOutcome};
+use crate::preview::{
+    wrap_rows, DirectoryPreview, FilePreview, PreviewCache, PreviewContent, PreviewLoader,
+    ReadmeStrip, SelectionSummary, HEX_ROW,
+};
+use crate::quick_view::QuickView;
+use crate::search::{ripgrep_program, ScopeRoots, SearchHistory, SearchMode};
+use crate::sessions::{Session, SessionOutcome, SessionPicker, Sessions, SplitSession};
+use crate::snapshot::{Snapshot, SnapshotReport};
+use crate::split_pane::{Layout, Pane, SplitPaneView, MAX_PANES};
+use crate::systemd;
+use crate::tasks::Task;
+use crate::ui::{
+    list_entry_at, list_rows, set_color_level, DragSelect, RenderContext, Renderer,
+    SetBackgroundColor, SetForegroundColor, HINT_PERIOD,
+};
+use crate::utils::{
+    editor_command, ellipsize, expand_home, is_root_user, logical_current_dir,
+    open_with_system_handler, sanitize_filename, spawn_detached, truncate_chars, validate_filename,
+    with_suspended_tui, FilenameIssue, Pattern, PatternMode, TimeFormat,
+};
+use crate::workflows::{Step, WorkflowOutcome, WorkflowView, Workflows};
 use anyhow::{Context, Result};
-use crossterm::style::SetBackgroundColor;
 use crossterm::{
     cursor::MoveTo,
-    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+        KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    },
     execute,
-    style::{Color, Print, ResetColor, SetForegroundColor},
+    style::{Attribute, Color, Print, ResetColor, SetAttribute},
     terminal,
 };
 use std::{
     collections::HashSet,
-    env, fs,
+    fs, io,
     path::{Path, PathBuf},
+    time::{Duration, Instant},
 };
 
+/// Lines of a text file loaded for the preview panel
+const PREVIEW_LINES: usize = 50;
+
+/// How long a frame waits for a preview before showing a placeholder
+const PREVIEW_GRACE: Duration = Duration::from_millis(30);
+
+/// How long entering a directory waits for its listing before drawing it
+/// as still being read
+const LISTING_GRACE: Duration = Duration::from_millis(50);
+
+/// How long chmod waits for the ownership check of the selection before
+/// opening once it is done
+const OWNERSHIP_GRACE: Duration = Duration::from_millis(50);
+
+/// A directory listing and its README read on a worker, with where the
+/// cursor goes once they arrive
+struct PendingListing {
+    /// Listing, README and canonical path of the directory
+    task: Task<(
+        io::Result<DirectoryListing>,
+        Option<ReadmeStrip>,
+        Option<PathBuf>,
+    )>,
+    /// Entry to highlight
+    select: Option<PathBuf>,
+    /// Type-to-filter text to narrow the listing to again
+    filter: Option<String>,
+    /// Entries to select once listed, from a restored session
+    reselect: Vec<PathBuf>,
+}
+
+/// Colors and attribute of a run of preview text
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct RunStyle {
+    foreground: Color,
+    background: Color,
+    attribute: Option<Attribute>,
+}
+
+impl RunStyle {
+    const PLAIN: Self = Self::colored(Color::Reset);
+    const MATCH: Self = Self {
+        foreground: Color::Black,
+        background: Color::Yellow,
+        attribute: None,
+    };
+
+    const fn colored(foreground: Color) -> Self {
+        Self {
+            foreground,
+            background: Color::Reset,
+            attribute: None,
+        }
+    }
+
+    fn markdown(style: markdown::Style) -> Self {
+        Self {
+            attribute: style.attribute(),
+            ..Self::colored(style.color())
+        }
+    }
+}
+
+/// A numbered line of the preview panel, split into styled runs
+struct PreviewLine {
+    number: usize,
+    /// Holds a search match: the line number stands out
+    marked: bool,
+    runs: Vec<(RunStyle, String)>,
+}
+
+/// The name and path of a bookmark being edited, and which one has the
+/// cursor
+struct BookmarkEdit {
+    name: String,
+    path: String,
+    editing_path: bool,
+}
+
+impl BookmarkEdit {
+    /// The worst problem the name would have as a file name here or on a
+    /// FAT/NTFS drive, shown while it is typed
+    fn name_issue(&self) -> Option<FilenameIssue> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        validate_filename(name).into_iter().next()
+    }
+
+    fn field(&mut self) -> &mut String {
+        if self.editing_path {
+            &mut self.path
+        } else {
+            &mut self.name
+        }
+    }
+}
+
+/// A line of the bookmarks list: a category header, or the bookmark at a
+/// position among the matches and its index
+enum BookmarkRow<'a> {
+    Category(Option<&'a str>),
+    Bookmark(usize, usize),
+    SuggestionsHeader,
+    /// A suggested directory: its position in the list and its index among
+    /// the suggestions
+    Suggestion(usize, usize),
+}
+
 #[derive(Debug, PartialEq)]
 pub enum NavigatorMode {
     Browse,
@@ -33,64 +193,230 @@ pub enum NavigatorMode {
     Preview,
     Bookmarks,
     SplitPane,
+    OpenWith,
+    CommandPalette,
+    QuickView,
+    CommandOutput,
+    FuzzyFinder,
+    SnapshotReport,
+    Monitor,
+    HexEditor,
+    Pager,
+    Diff,
+    DirStats,
+    Containers,
+    Workflows,
+    Sessions,
+    Checksums,
+    Help,
 }
 
 pub struct Navigator {
     current_dir: PathBuf,
     entries: Vec<FileEntry>,
+    /// Full listing while a type-to-filter narrows `entries`
+    unfiltered_entries: Vec<FileEntry>,
+    filter: Option<String>,
     selected_index: usize,
     selected_items: HashSet<usize>,
+    selection_size: SelectionSize,
     scroll_offset: usize,
     terminal_height: u16,
     mode: NavigatorMode,
     is_root: bool,
     pattern_input: String,
+    /// How the pattern-select prompt reads its pattern
+    pattern_mode: PatternMode,
     chmod_interface: Option<ChmodInterface>,
     chown_interface: Option<ChownInterface>,
     status_message: Option<String>,
     renderer: Renderer,
     // New v0.4.0 features
     search_mode: Option<SearchMode>,
+    search_history: SearchHistory,
     file_preview: Option<FilePreview>,
     bookmarks_manager: BookmarksManager,
+    /// Whether bookmark targets can be opened, checked in the background
+    bookmark_health: BookmarkHealth,
     split_pane_view: Option<SplitPaneView>,
     show_preview_panel: bool,
+    /// Opening lines of the current directory's README
+    readme: Option<ReadmeStrip>,
+    /// Where the current directory really is, when reached through
+    /// symbolic links
+    real_dir: Option<PathBuf>,
+    /// Pattern being typed at the `/` prompt of the focused preview
+    preview_search_input: Option<String>,
+    /// Offset being typed at the `g` prompt of a focused hex preview
+    preview_goto_input: Option<String>,
+    /// Preview display options, toggled while the panel is focused
+    preview_line_numbers: bool,
+    preview_wrap: bool,
+    listing_options: ListingOptions,
+    time_format: TimeFormat,
+    image_canvas: ImageCanvas,
+    config: Config,
+    associations: Associations,
+    dir_settings: DirSettingsCache,
+    /// Modes and owners last applied by the chmod and chown interfaces
+    recent_choices: RecentChoices,
+    open_with_menu: Option<OpenWithMenu>,
+    command_palette: Option<CommandPalette>,
+    quick_view: Option<QuickView>,
+    custom_commands: Vec<CustomCommand>,
+    command_output: Option<CommandOutput>,
+    fuzzy_finder: Option<FuzzyFinder>,
+    snapshot_report: Option<SnapshotReport>,
+    monitor: Option<Monitor>,
+    hex_editor: Option<HexEditor>,
+    pager: Option<Pager>,
+    diff_view: Option<DiffView>,
+    /// Preview of the highlighted entry being loaded in the background
+    preview_loader: Option<PreviewLoader>,
+    preview_cache: PreviewCache,
+    /// Current directory being read in the background
+    pending_listing: Option<PendingListing>,
+    dir_stats: Option<DirStats>,
+    containers: Option<ContainerVolumes>,
+    /// Help overlay and the mode to return to when it closes
+    help: Option<(HelpOverlay, NavigatorMode)>,
     // Add these new fields for fixes
     bookmark_selected_index: Option<usize>,
     preview_focused: bool,
-    bookmark_rename_mode: bool,
-    bookmark_rename_input: String,
+    /// Ctrl+R form editing the selected bookmark's name and path
+    bookmark_edit: Option<BookmarkEdit>,
+    /// Fuzzy filter typed on the bookmarks screen
+    bookmark_filter: String,
+    /// Ctrl+T prompt editing the selected bookmark's tags
+    bookmark_tags_input: Option<String>,
+    /// Ctrl+S prompt picking the selected bookmark's shortcut; `Some(None)`
+    /// removes it
+    bookmark_shortcut_input: Option<Option<char>>,
+    /// Ctrl+X listed the dead bookmarks; pressing it again removes them
+    bookmark_prune_pending: bool,
+    /// Hot bookmarks, watched for new files
+    hot_dirs: HotDirs,
+    /// When the navigator started, for rotating the footer hints
+    started: Instant,
+    /// Checksum verification of the current directory, marking its entries
+    checksums: Option<ChecksumRun>,
+    /// Archive being extracted with `Z`
+    extraction: Option<Extraction>,
+    /// Selection chmod opens on, being split into the paths chmod(2) would
+    /// allow and the ones it would refuse
+    pending_chmod: Option<Task<(Vec<PathBuf>, Vec<PathBuf>)>>,
+    /// Directories opened so far, for bookmark suggestions
+    dir_visits: DirVisits,
+    /// Saved workflows, replayed from the `W` picker
+    workflows: Workflows,
+    workflow_view: Option<WorkflowView>,
+    /// Steps recorded since `w` started recording
+    workflow_recording: Option<Vec<Step>>,
+    /// Named sessions, saved and restored from the F4 picker
+    sessions: Sessions,
+    /// Session picker and the mode to return to when it closes
+    session_picker: Option<(SessionPicker, NavigatorMode)>,
+    /// Mouse reporting is on, in selection mode
+    mouse_captured: bool,
+    /// Press-and-drag selection under way
+    mouse_drag: Option<DragSelect>,
+    /// `q` was pressed once and asked for confirmation
+    quit_pending: bool,
 }
 
 impl Navigator {
     pub fn new() -> Result<Self> {
-        let current_dir = env::current_dir().context("Failed to get current directory")?;
+        let current_dir = logical_current_dir().context("Failed to get current directory")?;
         let is_root = is_root_user();
         let bookmarks_manager = BookmarksManager::new()?;
+        let (config, config_error) = match Config::load() {
+            Ok(config) => (config, None),
+            Err(e) => (Config::default(), Some(format!("⚠️  {:#}", e))),
+        };
+
+        let custom_commands = config.custom_commands();
+        set_color_level(config.color_level());
 
         let mut nav = Self {
             current_dir: current_dir.clone(),
             entries: Vec::new(),
+            unfiltered_entries: Vec::new(),
+            filter: None,
             selected_index: 0,
             selected_items: HashSet::new(),
+            selection_size: SelectionSize::default(),
             scroll_offset: 0,
             terminal_height: terminal::size()?.1,
             mode: NavigatorMode::Browse,
             is_root,
             pattern_input: String::new(),
+            pattern_mode: PatternMode::default(),
             chmod_interface: None,
             chown_interface: None,
-            status_message: None,
+            status_message: config_error,
             renderer: Renderer::new(),
             search_mode: None,
+            search_history: SearchHistory::load(),
             file_preview: None,
             bookmarks_manager,
+            bookmark_health: BookmarkHealth::default(),
             split_pane_view: None,
             show_preview_panel: false,
+            readme: None,
+            real_dir: None,
+            preview_search_input: None,
+            preview_goto_input: None,
+            preview_line_numbers: config.preview.line_numbers,
+            preview_wrap: config.preview.wrap,
+            listing_options: ListingOptions {
+                name_order: config.sort.name_order,
+                grouping: config.sort.grouping,
+                ..Default::default()
+            },
+            time_format: config.time_format(),
+            image_canvas: ImageCanvas::new(config.preview.graphics_protocol()),
+            config,
+            associations: Associations::load(),
+            dir_settings: DirSettingsCache::load(),
+            recent_choices: RecentChoices::load(),
+            open_with_menu: None,
+            command_palette: None,
+            quick_view: None,
+            custom_commands,
+            command_output: None,
+            fuzzy_finder: None,
+            snapshot_report: None,
+            monitor: None,
+            hex_editor: None,
+            pager: None,
+            diff_view: None,
+            preview_loader: None,
+            preview_cache: PreviewCache::default(),
+            pending_listing: None,
+            dir_stats: None,
+            containers: None,
+            help: None,
             bookmark_selected_index: None, // Initialize new field
             preview_focused: false,        // Initialize new field
-            bookmark_rename_mode: false,
-            bookmark_rename_input: "".to_string(),
+            bookmark_edit: None,
+            bookmark_filter: String::new(),
+            bookmark_tags_input: None,
+            bookmark_shortcut_input: None,
+            bookmark_prune_pending: false,
+            hot_dirs: HotDirs::default(),
+            started: Instant::now(),
+            checksums: None,
+            extraction: None,
+            pending_chmod: None,
+            dir_visits: DirVisits::load(),
+            workflows: Workflows::load(),
+            workflow_view: None,
+            workflow_recording: None,
+            sessions: Sessions::load(),
+            session_picker: None,
+            mouse_captured: false,
+            mouse_drag: None,
+            quit_pending: false,
         };
         nav.load_directory(&current_dir)?;
         Ok(nav)
@@ -106,54 +432,205 @@ impl Navigator {
             // Update terminal height in case of resize
             self.terminal_height = terminal::size()?.1;
 
+            self.poll_listing();
+            self.poll_search();
+            self.poll_preview();
+            if let Some(ref mut split) = self.split_pane_view {
+                split.poll();
+            }
+            if let Some(ref mut pager) = self.pager {
+                pager.poll();
+            }
+            if let Some(ref mut diff) = self.diff_view {
+                diff.poll();
+            }
+            if let Some(ref mut editor) = self.hex_editor {
+                editor.poll();
+            }
+            if let Some(ref mut monitor) = self.monitor {
+                monitor.poll();
+            }
+            if let Some(ref mut chmod) = self.chmod_interface {
+                chmod.poll();
+            }
+            if let Some(ref mut chown) = self.chown_interface {
+                chown.poll();
+            }
+            self.selection_size.poll();
+            self.hot_dirs.watch(self.bookmarks_manager.hot_dirs());
+            self.notify_hot_arrivals();
+            if let Some(ref mut run) = self.checksums {
+                if run.poll() && !run.is_running() {
+                    self.status_message = Some(format!("🔐 Checksums: {}", run.summary()));
+                }
+            }
+            if let Some(outcome) = self.extraction.as_mut().and_then(Extraction::poll) {
+                self.finish_extraction(outcome);
+            }
+            if let Some((allowed, skipped)) = self.pending_chmod.as_mut().and_then(Task::poll) {
+                self.pending_chmod = None;
+                self.show_chmod_interface(allowed, skipped);
+            }
+            if self.mode == NavigatorMode::Bookmarks {
+                self.bookmark_health
+                    .poll(self.bookmarks_manager.list_bookmarks());
+            }
+
+            // The mouse is only captured where it does something, so the
+            // terminal's own text selection works everywhere else
+            let capture = matches!(self.mode, NavigatorMode::Select | NavigatorMode::SplitPane);
+            if capture != self.mouse_captured {
+                if capture {
+                    execute!(std::io::stdout(), EnableMouseCapture)?;
+                } else {
+                    execute!(std::io::stdout(), DisableMouseCapture)?;
+                }
+                self.mouse_captured = capture;
+                self.mouse_drag = None;
+            }
+
             // Render
             self.render()?;
 
             // Handle input
             if event::poll(std::time::Duration::from_millis(100))? {
-                if let Event::Key(KeyEvent {
-                    code,
-                    modifiers,
-                    kind: KeyEventKind::Press,
-                    ..
-                }) = event::read()?
-                {
-                    if let Some(action) = self.handle_input(code, modifiers)? {
-                        return Ok(action);
+                match event::read()? {
+                    Event::Key(KeyEvent {
+                        code,
+                        modifiers,
+                        kind: KeyEventKind::Press,
+                        ..
+                    }) => {
+                        if let Some(action) = self.handle_input(code, modifiers)? {
+                            return Ok(action);
+                        }
                     }
+                    Event::Mouse(mouse) => self.handle_mouse(mouse)?,
+                    _ => {}
                 }
             }
         }
     }
 
     fn render(&mut self) -> Result<()> {
+        self.image_canvas.begin_frame(&mut std::io::stdout())?;
+
         // Handle special render modes
         match self.mode {
             NavigatorMode::ChmodInterface => {
-                if let Some(ref chmod) = self.chmod_interface {
+                if let Some(ref mut chmod) = self.chmod_interface {
                     return chmod.render();
                 }
             }
             NavigatorMode::ChownInterface => {
-                if let Some(ref chown) = self.chown_interface {
+                if let Some(ref mut chown) = self.chown_interface {
                     return chown.render();
                 }
             }
-            NavigatorMode::SplitPane => {
-                if let Some(ref mut split) = self.split_pane_view {
-                    return split.render();
-                }
-            }
+            NavigatorMode::SplitPane => return self.render_split(),
             NavigatorMode::Bookmarks => {
                 return self.render_bookmarks_interface();
             }
+            NavigatorMode::CommandOutput => {
+                if let Some(ref output) = self.command_output {
+                    return output.render();
+                }
+            }
+            NavigatorMode::Help => {
+                if let Some((ref overlay, _)) = self.help {
+                    return overlay.render();
+                }
+            }
+            NavigatorMode::FuzzyFinder => {
+                if let Some(ref finder) = self.fuzzy_finder {
+                    return finder.render();
+                }
+            }
+            NavigatorMode::SnapshotReport => {
+                if let Some(ref report) = self.snapshot_report {
+                    return report.render();
+                }
+            }
+            NavigatorMode::Monitor => {
+                if let Some(ref monitor) = self.monitor {
+                    return monitor.render();
+                }
+            }
+            NavigatorMode::HexEditor => {
+                if let Some(ref editor) = self.hex_editor {
+                    return editor.render();
+                }
+            }
+            NavigatorMode::Pager => {
+                if let Some(ref pager) = self.pager {
+                    return pager.render();
+                }
+            }
+            NavigatorMode::Diff => {
+                if let Some(ref diff) = self.diff_view {
+                    return diff.render();
+                }
+            }
+            NavigatorMode::DirStats => {
+                if let Some(ref stats) = self.dir_stats {
+                    return stats.render();
+                }
+            }
+            NavigatorMode::Checksums => {
+                if let Some(ref run) = self.checksums {
+                    return run.render();
+                }
+            }
+            NavigatorMode::Containers => {
+                if let Some(ref containers) = self.containers {
+                    return containers.render();
+                }
+            }
+            NavigatorMode::OpenWith => {
+                self.render_main_view()?;
+                if let Some(ref menu) = self.open_with_menu {
+                    return menu.render();
+                }
+            }
+            NavigatorMode::CommandPalette => {
+                self.render_main_view()?;
+                if let Some(ref palette) = self.command_palette {
+                    return palette.render();
+                }
+            }
+            NavigatorMode::Sessions => {
+                match self.session_picker {
+                    Some((_, NavigatorMode::SplitPane)) => self.render_split()?,
+                    _ => self.render_main_view()?,
+                }
+                if let Some((ref picker, _)) = self.session_picker {
+                    return picker.render(&self.sessions);
+                }
+            }
+            NavigatorMode::Workflows => {
+                self.render_main_view()?;
+                if let Some(ref view) = self.workflow_view {
+                    return view.render(&self.workflows);
+                }
+            }
+            NavigatorMode::QuickView => {
+                self.render_main_view()?;
+                if let Some(ref quick_view) = self.quick_view {
+                    return quick_view.render(&mut self.image_canvas);
+                }
+            }
             _ => {}
         }
 
+        self.render_main_view()
+    }
+
+    fn render_main_view(&mut self) -> Result<()> {
         // Normal rendering with optional preview panel
         if self.show_preview_panel {
             self.render_with_preview()
         } else {
+            let selection_total = self.selection_total();
             let ctx = RenderContext {
                 current_dir: &self.current_dir,
                 entries: &self.entries,
@@ -164,9 +641,21 @@ impl Navigator {
                 mode: &self.mode,
                 is_root: self.is_root,
                 pattern_input: &self.pattern_input,
+                pattern_mode: self.pattern_mode,
+                filter: self.filter.as_deref(),
+                pinned: self.dir_settings.pinned(&self.current_dir),
                 status_message: &self.status_message,
                 search_mode: self.search_mode.as_ref(), // Pass the search mode
                 preview_focused: self.preview_focused,  // Pass the preview focus state
+                readme: self.readme.as_ref(),
+                real_dir: self.real_dir.as_deref(),
+                hot_new: self.hot_dirs.unseen(),
+                checksums: self.checksums.as_ref(),
+                preview_search: self.preview_search_prompt(),
+                loading: self.pending_listing.is_some(),
+                selection_total: selection_total.as_deref(),
+                recording: self.workflow_recording.as_ref().map(Vec::len),
+                hint_turn: self.hint_turn(),
             };
             self.renderer.render(ctx)
         }
@@ -183,6 +672,7 @@ impl Navigator {
         let preview_width = terminal_width - split_pos - 1;
 
         // Render file list on the left
+        let selection_total = self.selection_total();
         let ctx = RenderContext {
             current_dir: &self.current_dir,
             entries: &self.entries,
@@ -193,9 +683,21 @@ impl Navigator {
             mode: &self.mode,
             is_root: self.is_root,
             pattern_input: &self.pattern_input,
+            pattern_mode: self.pattern_mode,
+            filter: self.filter.as_deref(),
+            pinned: self.dir_settings.pinned(&self.current_dir),
             status_message: &self.status_message,
             search_mode: self.search_mode.as_ref(),
             preview_focused: self.preview_focused,
+            readme: self.readme.as_ref(),
+            real_dir: self.real_dir.as_deref(),
+            hot_new: self.hot_dirs.unseen(),
+            checksums: self.checksums.as_ref(),
+            preview_search: self.preview_search_prompt(),
+            loading: self.pending_listing.is_some(),
+            selection_total: selection_total.as_deref(),
+            recording: self.workflow_recording.as_ref().map(Vec::len),
+            hint_turn: self.hint_turn(),
         };
 
         // Render main view (will be clipped to split_pos width)
@@ -212,65 +714,333 @@ impl Navigator {
             )?;
         }
 
-        // Update preview based on current selection (skip directories)
-        if let Some(entry) = self.entries.get(self.selected_index) {
-            if !entry.is_dir {
-                let should_reload = self.file_preview.is_none();
-                if should_reload {
-                    self.file_preview = FilePreview::new(&entry.path, 50).ok();
-                }
-            } else {
-                // Clear preview if directory is selected
-                self.file_preview = None;
+        // A batch selection replaces the single-file preview with a summary
+        if !self.selected_items.is_empty() {
+            let total = self.selected_size();
+            self.render_selection_summary(
+                &mut stdout,
+                total,
+                split_pos + 1,
+                0,
+                preview_width,
+                terminal_height - 1,
+            )?;
+            stdout.flush()?;
+            return Ok(());
+        }
+
+        // Update preview based on current selection
+        self.request_preview();
+
+        if self
+            .preview_loader
+            .as_ref()
+            .is_some_and(PreviewLoader::is_loading)
+        {
+            execute!(
+                stdout,
+                MoveTo(split_pos + 1, 0),
+                SetBackgroundColor(Color::DarkBlue),
+                SetForegroundColor(Color::White),
+                Print(format!(
+                    "{:<width$}",
+                    " Preview ",
+                    width = preview_width as usize
+                )),
+                ResetColor,
+                MoveTo(split_pos + 2, 2),
+                SetForegroundColor(Color::DarkGrey),
+                Print("Loading preview…"),
+                ResetColor
+            )?;
+        } else if self.file_preview.is_some() {
+            self.render_preview_panel(
+                &mut stdout,
+                split_pos + 1,
+                0,
+                preview_width,
+                terminal_height - 1,
+            )?;
+        }
+
+        stdout.flush()?;
+        Ok(())
+    }
+
+    /// "3 selected · 1.2 MB" for the status bar, with directories still
+    /// being measured left out and counted
+    fn selection_total(&mut self) -> Option<String> {
+        if self.selected_items.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "{} selected · {}",
+            self.selected_items.len(),
+            format_size_total(self.selected_size())
+        ))
+    }
+
+    /// Combined size of the selected items, directories included once
+    /// their walks finish
+    fn selected_size(&mut self) -> SizeTotal {
+        self.selection_size.total(
+            self.selected_items
+                .iter()
+                .filter_map(|&i| self.entries.get(i)),
+        )
+    }
+
+    /// Often visited directories to offer as bookmarks, below the list
+    /// while no filter is typed
+    fn bookmark_suggestions(&self) -> Vec<(PathBuf, usize)> {
+        if !self.bookmark_filter.is_empty() {
+            return Vec::new();
+        }
+        self.dir_visits
+            .suggestions(self.bookmarks_manager.list_bookmarks())
+    }
+
+    fn render_selection_summary(
+        &self,
+        stdout: &mut std::io::Stdout,
+        total: SizeTotal,
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+    ) -> Result<()> {
+        let mut selected: Vec<&FileEntry> = self
+            .selected_items
+            .iter()
+            .filter_map(|&i| self.entries.get(i))
+            .collect();
+        selected.sort_by_key(|e| e.name.to_lowercase());
+        let summary = SelectionSummary::from_entries(&selected);
+        let inner_width = width.saturating_sub(2) as usize;
+
+        execute!(
+            stdout,
+            MoveTo(x, y),
+            SetBackgroundColor(Color::DarkBlue),
+            SetForegroundColor(Color::White),
+            Print(" Selection "),
+            Print(" ".repeat((width as usize).saturating_sub(11))),
+            ResetColor
+        )?;
+
+        let common_parent = summary
+            .common_parent
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| "-".to_string());
+        let lines = [
+            (
+                Color::Yellow,
+                format!(
+                    "Items: {} ({} files, {} dirs)",
+                    summary.count, summary.file_count, summary.dir_count
+                ),
+            ),
+            (
+                Color::Yellow,
+                format!("Total size: {}", format_size_total(total)),
+            ),
+            (Color::Cyan, format!("Parent: {}", common_parent)),
+        ];
+        for (i, (color, line)) in lines.iter().enumerate() {
+            execute!(
+                stdout,
+                MoveTo(x + 1, y + 1 + i as u16),
+                SetForegroundColor(*color),
+                Print(line.chars().take(inner_width).collect::<String>()),
+                ResetColor
+            )?;
+        }
+
+        execute!(
+            stdout,
+            MoveTo(x + 1, y + 4),
+            SetForegroundColor(Color::DarkGrey),
+            Print("─".repeat(inner_width)),
+            ResetColor
+        )?;
+
+        // Type breakdown first, then as many of the selected names as fit
+        let mut row = y + 5;
+        let last_row = y + height.saturating_sub(1);
+        for (kind, count) in &summary.type_breakdown {
+            if row >= last_row {
+                break;
             }
+            execute!(
+                stdout,
+                MoveTo(x + 1, row),
+                SetForegroundColor(Color::Green),
+                Print(format!("{:>5} × ", count)),
+                SetForegroundColor(Color::White),
+                Print(
+                    kind.chars()
+                        .take(inner_width.saturating_sub(8))
+                        .collect::<String>()
+                ),
+                ResetColor
+            )?;
+            row += 1;
         }
 
-        // Render preview or show message for directories
-        if let Some(entry) = self.entries.get(self.selected_index) {
-            if entry.is_dir {
-                // Show directory message
-                execute!(
-                    stdout,
-                    MoveTo(split_pos + 1, 0),
-                    SetBackgroundColor(Color::DarkBlue),
-                    SetForegroundColor(Color::White),
-                    Print(" Preview "),
-                    Print(" ".repeat((preview_width - 9) as usize)),
-                    ResetColor
-                )?;
+        row += 1;
+        for entry in selected {
+            if row >= last_row {
+                break;
+            }
+            execute!(
+                stdout,
+                MoveTo(x + 1, row),
+                SetForegroundColor(Color::DarkGrey),
+                Print(
+                    entry
+                        .display_name()
+                        .chars()
+                        .take(inner_width)
+                        .collect::<String>()
+                ),
+                ResetColor
+            )?;
+            row += 1;
+        }
 
-                execute!(
-                    stdout,
-                    MoveTo(split_pos + 2, terminal_height / 2),
-                    SetForegroundColor(Color::DarkGrey),
-                    Print("  Directory preview not available"),
-                    ResetColor
-                )?;
+        Ok(())
+    }
+
+    /// Draw `lines` from `top`, under a line-number gutter when enabled, cut
+    /// or wrapped to the panel width. The first line is the cursor while the
+    /// preview is focused.
+    fn draw_preview_lines(
+        &self,
+        stdout: &mut std::io::Stdout,
+        (x, top): (u16, u16),
+        (width, height): (u16, u16),
+        lines: Vec<PreviewLine>,
+    ) -> Result<()> {
+        let gutter = if self.preview_line_numbers { 5 } else { 0 };
+        let text_width = width.saturating_sub(2 + gutter) as usize;
+        let mut row = 0;
 
+        for (i, line) in lines.into_iter().enumerate() {
+            let cursor = self.preview_focused && i == 0;
+            let background = if cursor {
+                Color::DarkGreen
+            } else {
+                Color::Reset
+            };
+            for (part, runs) in wrap_rows(&line.runs, text_width, self.preview_wrap)
+                .into_iter()
+                .enumerate()
+            {
+                if row >= height {
+                    return Ok(());
+                }
                 execute!(
                     stdout,
-                    MoveTo(split_pos + 2, terminal_height / 2 + 1),
-                    SetForegroundColor(Color::DarkGrey),
-                    Print("  Press Enter to navigate into it"),
-                    ResetColor
-                )?;
-            } else if self.file_preview.is_some() {
-                self.render_preview_panel(
-                    &mut stdout,
-                    split_pos + 1,
-                    0,
-                    preview_width,
-                    terminal_height - 1,
+                    MoveTo(x + 1, top + row),
+                    SetBackgroundColor(background),
+                    Print(" ".repeat(width.saturating_sub(2) as usize)),
+                    MoveTo(x + 1, top + row)
                 )?;
+                if self.preview_line_numbers {
+                    // Continuation rows of a wrapped line leave the gutter empty
+                    let number = if part == 0 {
+                        format!("{:4} ", line.number)
+                    } else {
+                        " ".repeat(5)
+                    };
+                    execute!(
+                        stdout,
+                        SetForegroundColor(if line.marked {
+                            Color::Yellow
+                        } else {
+                            Color::DarkGrey
+                        }),
+                        Print(number)
+                    )?;
+                }
+                for (style, text) in runs {
+                    execute!(
+                        stdout,
+                        SetForegroundColor(if cursor && style.foreground == Color::Reset {
+                            Color::White
+                        } else {
+                            style.foreground
+                        }),
+                        SetBackgroundColor(if style.background == Color::Reset {
+                            background
+                        } else {
+                            style.background
+                        })
+                    )?;
+                    if let Some(attribute) = style.attribute {
+                        execute!(stdout, SetAttribute(attribute))?;
+                    }
+                    execute!(stdout, Print(text), SetAttribute(Attribute::Reset))?;
+                }
+                execute!(stdout, ResetColor)?;
+                row += 1;
             }
         }
+        Ok(())
+    }
+
+    /// The split panes, with the preview of the active pane's entry in
+    /// place of the next pane when it is on
+    fn render_split(&mut self) -> Result<()> {
+        use std::io::Write;
+
+        let Some(ref mut split) = self.split_pane_view else {
+            return Ok(());
+        };
+        let target = split.preview_target().map(Path::to_path_buf);
+        let Some(cell) = split.render()? else {
+            return Ok(());
+        };
+        if let Some(path) = target {
+            self.request_preview_of(&path);
+        }
+        // Narrower than the preview's header
+        if cell.width < 10 || cell.height < 2 {
+            return Ok(());
+        }
 
+        let mut stdout = std::io::stdout();
+        if self
+            .preview_loader
+            .as_ref()
+            .is_some_and(PreviewLoader::is_loading)
+        {
+            execute!(
+                stdout,
+                MoveTo(cell.x, cell.y),
+                SetBackgroundColor(Color::DarkBlue),
+                SetForegroundColor(Color::White),
+                Print(format!(
+                    "{:<width$}",
+                    " Preview ",
+                    width = cell.width as usize
+                )),
+                ResetColor,
+                MoveTo(cell.x + 1, cell.y + 1),
+                SetForegroundColor(Color::DarkGrey),
+                Print("Loading preview…"),
+                ResetColor
+            )?;
+        } else if self.file_preview.is_some() {
+            self.render_preview_panel(&mut stdout, cell.x, cell.y, cell.width, cell.height)?;
+        }
         stdout.flush()?;
         Ok(())
     }
 
     fn render_preview_panel(
-        &self,
+        &mut self,
         stdout: &mut std::io::Stdout,
         x: u16,
         y: u16,
@@ -298,10 +1068,7 @@ impl Navigator {
                 stdout,
                 MoveTo(x + 1, y + 1),
                 SetForegroundColor(Color::Yellow),
-                Print(format!(
-                    "Size: {}",
-                    FilePreview::format_size(preview.file_info.size)
-                )),
+                Print(format!("Size: {}", preview.file_info.size_label())),
                 ResetColor
             )?;
 
@@ -323,120 +1090,243 @@ impl Navigator {
                 ResetColor
             )?;
 
+            if let Some(modified) = preview.file_info.modified {
+                execute!(
+                    stdout,
+                    MoveTo(x + 1, y + 4),
+                    SetForegroundColor(Color::Magenta),
+                    Print(format!("Modified: {}", self.time_format.format(modified))),
+                    ResetColor
+                )?;
+            }
+
             // Divider line
             execute!(
                 stdout,
-                MoveTo(x + 1, y + 4),
+                MoveTo(x + 1, y + 5),
                 SetForegroundColor(Color::DarkGrey),
                 Print("─".repeat((width - 2) as usize)),
                 ResetColor
             )?;
 
             // Content preview
-            let content_start = y + 5;
-            let content_height = height.saturating_sub(6);
+            let mut content_start = y + 6;
+            let mut content_height = height.saturating_sub(7);
+
+            // Key settings of a systemd unit or PDF metadata, using at most
+            // half the space
+            let summary = preview.summary_lines();
+            let shown = summary.len().min(content_height as usize / 2) as u16;
+            for (i, line) in summary.iter().take(shown as usize).enumerate() {
+                execute!(
+                    stdout,
+                    MoveTo(x + 1, content_start + i as u16),
+                    SetForegroundColor(if line.starts_with('[') {
+                        Color::Cyan
+                    } else {
+                        Color::White
+                    }),
+                    Print(
+                        line.chars()
+                            .take(width.saturating_sub(2) as usize)
+                            .collect::<String>()
+                    ),
+                    ResetColor
+                )?;
+            }
+            if shown > 0 {
+                execute!(
+                    stdout,
+                    MoveTo(x + 1, content_start + shown),
+                    SetForegroundColor(Color::DarkGrey),
+                    Print("─".repeat((width - 2) as usize)),
+                    ResetColor
+                )?;
+                content_start += shown + 1;
+                content_height = content_height.saturating_sub(shown + 1);
+            }
 
             match &preview.content {
                 PreviewContent::Text(lines) => {
-                    for (i, line) in lines
+                    let rendered = preview.rendered_markdown();
+                    let visible = lines
                         .iter()
+                        .enumerate()
                         .skip(preview.scroll_offset)
                         .take(content_height as usize)
-                        .enumerate()
-                    {
-                        let line_num = preview.scroll_offset + i + 1;
-                        let row = content_start + i as u16;
-
-                        // Highlight current line if preview is focused
-                        if self.preview_focused && i == 0 {
-                            execute!(
-                                stdout,
-                                MoveTo(x + 1, row),
-                                SetBackgroundColor(Color::DarkGreen),
-                                SetForegroundColor(Color::White),
-                                Print(" ".repeat((width - 2) as usize)),
-                                MoveTo(x + 1, row)
-                            )?;
-                        }
-
-                        // Line number
-                        execute!(
-                            stdout,
-                            MoveTo(x + 1, row),
-                            SetForegroundColor(Color::DarkGrey),
-                            Print(format!("{:4} ", line_num)),
-                            SetForegroundColor(if self.preview_focused && i == 0 {
-                                Color::White
-                            } else {
-                                Color::Reset
-                            }),
-                            ResetColor
-                        )?;
-
-                        // Line content
-                        let line_start_pos = x + 6;
-                        let max_line_width = (width.saturating_sub(7)) as usize;
-                        let truncated = if line.len() > max_line_width {
-                            &line[..max_line_width]
-                        } else {
-                            line
-                        };
-
-                        execute!(
-                            stdout,
-                            MoveTo(line_start_pos, row),
-                            if self.preview_focused && i == 0 {
-                                SetBackgroundColor(Color::DarkGreen)
+                        .map(|(index, line)| {
+                            let number = index + 1;
+                            let highlight = preview.line_highlight(index);
+                            // Search matches are drawn black on yellow
+                            let runs = if let Some(ref highlight) = highlight {
+                                highlight
+                                    .segments(line, usize::MAX)
+                                    .into_iter()
+                                    .map(|(text, matched)| {
+                                        let style = if matched {
+                                            RunStyle::MATCH
+                                        } else {
+                                            RunStyle::PLAIN
+                                        };
+                                        (style, text)
+                                    })
+                                    .collect()
+                            } else if let Some(spans) = rendered.and_then(|r| r.get(index)) {
+                                spans
+                                    .iter()
+                                    .map(|span| (RunStyle::markdown(span.style), span.text.clone()))
+                                    .collect()
                             } else {
-                                SetBackgroundColor(Color::Reset)
+                                vec![(RunStyle::PLAIN, line.clone())]
+                            };
+                            PreviewLine {
+                                number,
+                                marked: preview.is_marked(index),
+                                runs,
+                            }
+                        })
+                        .collect();
+                    self.draw_preview_lines(
+                        stdout,
+                        (x, content_start),
+                        (width, content_height),
+                        visible,
+                    )?;
+                }
+                PreviewContent::Json(document) => {
+                    let visible = document
+                        .lines
+                        .iter()
+                        .enumerate()
+                        .skip(preview.scroll_offset)
+                        .take(content_height as usize)
+                        .map(|(index, line)| PreviewLine {
+                            number: index + 1,
+                            marked: preview.is_marked(index),
+                            // Lines with matches lose their colors to the highlight
+                            runs: match preview.line_highlight(index) {
+                                Some(highlight) => highlight
+                                    .segments(&line.text(), usize::MAX)
+                                    .into_iter()
+                                    .map(|(text, matched)| {
+                                        let style = if matched {
+                                            RunStyle::MATCH
+                                        } else {
+                                            RunStyle::PLAIN
+                                        };
+                                        (style, text)
+                                    })
+                                    .collect(),
+                                None => line
+                                    .segments
+                                    .iter()
+                                    .map(|(color, text)| (RunStyle::colored(*color), text.clone()))
+                                    .collect(),
                             },
-                            Print(truncated),
-                            ResetColor
-                        )?;
-                    }
+                        })
+                        .collect();
+                    self.draw_preview_lines(
+                        stdout,
+                        (x, content_start),
+                        (width, content_height),
+                        visible,
+                    )?;
                 }
-                PreviewContent::Binary(bytes) => {
+                PreviewContent::Binary(chunk) => {
+                    let offset = preview.hex_offset();
+                    let title = match self.preview_goto_input {
+                        Some(ref input) => format!("Go to offset (decimal or 0x hex): {}_", input),
+                        None => format!(
+                            "Hex preview at 0x{:08x} of {}",
+                            offset,
+                            FilePreview::format_size(preview.hex_len())
+                        ),
+                    };
                     execute!(
                         stdout,
                         MoveTo(x + 1, content_start),
                         SetForegroundColor(Color::DarkGrey),
-                        Print("Binary file - Hex preview:"),
+                        Print(ellipsize(&title, width.saturating_sub(2) as usize)),
                         ResetColor
                     )?;
 
-                    for (i, chunk) in bytes
-                        .chunks(16)
+                    for (i, row) in chunk
+                        .from(offset)
+                        .chunks(HEX_ROW as usize)
                         .enumerate()
-                        .take((content_height - 2) as usize)
+                        .take(content_height.saturating_sub(2) as usize)
                     {
-                        let hex = chunk
+                        let hex = row
                             .iter()
                             .map(|b| format!("{:02x} ", b))
                             .collect::<String>();
-                        let ascii = chunk
+                        let ascii = row
                             .iter()
                             .map(|&b| if b.is_ascii_graphic() { b as char } else { '.' })
                             .collect::<String>();
 
+                        // Narrow panels cut the row rather than wrap it
+                        let mut remaining = width.saturating_sub(2) as usize;
+                        execute!(stdout, MoveTo(x + 1, content_start + 2 + i as u16))?;
+                        for (color, text) in [
+                            (
+                                Color::DarkGrey,
+                                format!("{:08x}  ", offset + i as u64 * HEX_ROW),
+                            ),
+                            (Color::Blue, format!("{:<48}", hex)),
+                            (Color::Green, " | ".to_string()),
+                            (Color::White, ascii),
+                        ] {
+                            let shown = truncate_chars(&text, remaining);
+                            remaining -= shown.chars().count();
+                            execute!(stdout, SetForegroundColor(color), Print(shown))?;
+                        }
+                        execute!(stdout, ResetColor)?;
+                    }
+                }
+                PreviewContent::Image(info) => {
+                    execute!(
+                        stdout,
+                        MoveTo(x + 1, content_start),
+                        SetForegroundColor(Color::DarkGrey),
+                        Print(
+                            info.description()
+                                .chars()
+                                .take(width.saturating_sub(2) as usize)
+                                .collect::<String>()
+                        ),
+                        ResetColor
+                    )?;
+                    let details = info.details();
+                    for (i, line) in details.iter().enumerate() {
                         execute!(
                             stdout,
-                            MoveTo(x + 1, content_start + 2 + i as u16),
-                            SetForegroundColor(Color::Blue),
-                            Print(hex),
-                            SetForegroundColor(Color::Green),
-                            Print(" | "),
-                            SetForegroundColor(Color::White),
-                            Print(ascii),
+                            MoveTo(x + 1, content_start + 1 + i as u16),
+                            SetForegroundColor(Color::Cyan),
+                            Print(
+                                line.chars()
+                                    .take(width.saturating_sub(2) as usize)
+                                    .collect::<String>()
+                            ),
                             ResetColor
                         )?;
                     }
-                }
-                PreviewContent::Image(info) => {
-                    if let Some(ref art) = info.ascii_art {
-                        for (i, line) in art.lines().enumerate().take(content_height as usize) {
+                    let top = content_start + details.len() as u16;
+                    let below = content_height.saturating_sub(details.len() as u16);
+                    if let Some(ref image) = info.image {
+                        self.image_canvas.draw(
+                            stdout,
+                            &preview.path,
+                            image,
+                            (x + 1, top + 2),
+                            (width.saturating_sub(2), below.saturating_sub(2)),
+                        )?;
+                    } else if let Some(ref art) = info.ascii_art {
+                        let art_height = below.saturating_sub(1) as usize;
+                        for (i, line) in art.lines().enumerate().take(art_height) {
                             execute!(
                                 stdout,
-                                MoveTo(x + 1, content_start + i as u16),
+                                MoveTo(x + 1, top + 1 + i as u16),
                                 SetForegroundColor(Color::Magenta),
                                 Print(line),
                                 ResetColor
@@ -444,17 +1334,98 @@ impl Navigator {
                         }
                     }
                 }
-                PreviewContent::Directory(entries) => {
-                    for (i, entry) in entries
+                PreviewContent::Media(info) => {
+                    for (i, line) in info
+                        .lines()
                         .iter()
-                        .skip(preview.scroll_offset)
-                        .take(content_height as usize)
                         .enumerate()
+                        .take(content_height as usize)
                     {
                         execute!(
                             stdout,
                             MoveTo(x + 1, content_start + i as u16),
-                            Print(entry)
+                            SetForegroundColor(Color::Cyan),
+                            Print(
+                                line.chars()
+                                    .take(width.saturating_sub(2) as usize)
+                                    .collect::<String>()
+                            ),
+                            ResetColor
+                        )?;
+                    }
+                }
+                PreviewContent::Directory(dir) => {
+                    let summary = if dir.entries.is_empty() {
+                        "(empty directory)".to_string()
+                    } else {
+                        dir.summary()
+                    };
+                    execute!(
+                        stdout,
+                        MoveTo(x + 1, content_start),
+                        SetForegroundColor(Color::DarkGrey),
+                        Print(ellipsize(&summary, width.saturating_sub(2) as usize)),
+                        ResetColor
+                    )?;
+                    let content_start = content_start + 1;
+                    let content_height = content_height.saturating_sub(1);
+
+                    let list_height = if dir.hidden_count > 0 {
+                        content_height.saturating_sub(1)
+                    } else {
+                        content_height
+                    };
+                    let visible: Vec<(&FileEntry, String)> = dir
+                        .entries
+                        .iter()
+                        .skip(preview.scroll_offset)
+                        .take(list_height as usize)
+                        .map(|entry| {
+                            let modified = entry
+                                .modified
+                                .map(|t| self.time_format.format(t))
+                                .unwrap_or_else(|| "-".to_string());
+                            (entry, modified)
+                        })
+                        .collect();
+
+                    // Size and date columns on the right, name takes the rest
+                    let date_width = visible
+                        .iter()
+                        .map(|(_, modified)| modified.chars().count())
+                        .max()
+                        .unwrap_or(0);
+                    let info_width = 11 + date_width;
+                    let name_width = (width as usize).saturating_sub(info_width + 2);
+
+                    for (i, (entry, modified)) in visible.into_iter().enumerate() {
+                        let name: String = entry.display_name().chars().take(name_width).collect();
+                        let size = DirectoryPreview::annotation(entry);
+                        execute!(
+                            stdout,
+                            MoveTo(x + 1, content_start + i as u16),
+                            SetForegroundColor(if entry.is_dir {
+                                Color::Cyan
+                            } else {
+                                Color::White
+                            }),
+                            Print(&name),
+                            MoveTo(x + 1 + name_width as u16, content_start + i as u16),
+                            SetForegroundColor(Color::Yellow),
+                            Print(format!("{:>10} ", size)),
+                            SetForegroundColor(Color::DarkGrey),
+                            Print(format!("{:>width$}", modified, width = date_width)),
+                            ResetColor
+                        )?;
+                    }
+
+                    if dir.hidden_count > 0 {
+                        execute!(
+                            stdout,
+                            MoveTo(x + 1, content_start + list_height),
+                            SetForegroundColor(Color::DarkGrey),
+                            Print(format!("({} hidden)", dir.hidden_count)),
+                            ResetColor
                         )?;
                     }
                 }
@@ -489,6 +1460,8 @@ impl Navigator {
         let (terminal_width, terminal_height) = terminal::size()?;
 
         execute!(stdout, terminal::Clear(terminal::ClearType::All))?;
+        let bookmarks = self.bookmarks_manager.list_bookmarks();
+        let matches = self.bookmarks_manager.filter(&self.bookmark_filter);
 
         // Title
         execute!(
@@ -506,32 +1479,236 @@ impl Navigator {
             stdout,
             MoveTo(2, 2),
             SetForegroundColor(Color::Yellow),
-            if self.bookmark_rename_mode {
-                Print(format!("Renaming: {}_", self.bookmark_rename_input))
-            } else {
+            if let Some(ref edit) = self.bookmark_edit {
+                Print(format!(
+                    "Name: {}{}",
+                    edit.name,
+                    if edit.editing_path { "" } else { "_" }
+                ))
+            } else if let Some(shortcut) = self.bookmark_shortcut_input {
+                Print(format!(
+                    "Shortcut: {}",
+                    shortcut.map_or("none".to_string(), |c| format!("[{}]", c))
+                ))
+            } else if let Some(ref tags) = self.bookmark_tags_input {
+                Print(format!("Tags (first is the category): {}_", tags))
+            } else if self.bookmark_filter.is_empty() {
                 Print(
-                    "Press letter for quick jump | Use arrows to navigate, Enter to go".to_string(),
+                    "Type to filter by name or path, #tag by tag | Alt+letter: jump by shortcut | Enter to go"
+                        .to_string(),
                 )
+            } else {
+                Print(format!(
+                    "Filter: {}_   {} of {}",
+                    self.bookmark_filter,
+                    matches.len(),
+                    bookmarks.len()
+                ))
             },
             ResetColor
         )?;
 
-        // List bookmarks with selection highlight
-        let bookmarks = self.bookmarks_manager.list_bookmarks();
-        for (i, bookmark) in bookmarks
+        // Whether the shortcut being picked is free, shown as it is typed
+        if let Some(shortcut) = self.bookmark_shortcut_input {
+            let selected = matches.get(self.bookmark_selected_index.unwrap_or(0));
+            let owner = shortcut.map(|c| self.bookmarks_manager.shortcut_owner(c));
+            let (color, text) = match owner {
+                None => (Color::DarkGrey, "Enter removes the shortcut".to_string()),
+                Some(None) => (Color::Green, "Free".to_string()),
+                Some(Some(owner)) if Some(&owner) == selected => {
+                    (Color::DarkGrey, "Current shortcut".to_string())
+                }
+                Some(Some(owner)) if bookmarks[owner].shared => (
+                    Color::Yellow,
+                    format!("Used by shared '{}': yours will win", bookmarks[owner].name),
+                ),
+                Some(Some(owner)) => (Color::Red, format!("Taken by '{}'", bookmarks[owner].name)),
+            };
+            execute!(
+                stdout,
+                MoveTo(2, 3),
+                SetForegroundColor(color),
+                Print(text),
+                ResetColor
+            )?;
+        }
+
+        if let Some(ref edit) = self.bookmark_edit {
+            if let Some(issue) = edit.name_issue() {
+                let x = 2 + format!("Name: {}_", edit.name).chars().count() as u16 + 3;
+                execute!(
+                    stdout,
+                    MoveTo(x, 2),
+                    SetForegroundColor(if issue.is_fatal() {
+                        Color::Red
+                    } else {
+                        Color::DarkYellow
+                    }),
+                    Print(format!(
+                        "⚠ {} (Ctrl+S: {})",
+                        issue,
+                        sanitize_filename(edit.name.trim())
+                    )),
+                    ResetColor
+                )?;
+            }
+            execute!(
+                stdout,
+                MoveTo(2, 3),
+                SetForegroundColor(if edit.editing_path {
+                    Color::Yellow
+                } else {
+                    Color::DarkGrey
+                }),
+                Print(format!(
+                    "Path: {}{}",
+                    edit.path,
+                    if edit.editing_path { "_" } else { "" }
+                )),
+                ResetColor
+            )?;
+        }
+
+        if matches.is_empty() && !bookmarks.is_empty() {
+            execute!(
+                stdout,
+                MoveTo(4, 4),
+                SetForegroundColor(Color::DarkGrey),
+                Print("(no bookmarks match)"),
+                ResetColor
+            )?;
+        }
+
+        // Without fuzzy text the matches come grouped by category, each
+        // group under a header row
+        let mut rows = Vec::new();
+        let grouped = groups_by_category(&self.bookmark_filter);
+        for (position, &index) in matches.iter().enumerate() {
+            let category = bookmarks[index].category();
+            if grouped && (position == 0 || bookmarks[matches[position - 1]].category() != category)
+            {
+                rows.push(BookmarkRow::Category(category));
+            }
+            rows.push(BookmarkRow::Bookmark(position, index));
+        }
+        let suggestions = self.bookmark_suggestions();
+        if !suggestions.is_empty() {
+            rows.push(BookmarkRow::SuggestionsHeader);
+            rows.extend(
+                (0..suggestions.len()).map(|i| BookmarkRow::Suggestion(matches.len() + i, i)),
+            );
+        }
+
+        // List the matches with selection highlight, scrolled to keep the
+        // selection in view above the status lines
+        let list_height = terminal_height.saturating_sub(8).max(1) as usize;
+        let selected = self.bookmark_selected_index.unwrap_or(0);
+        let selected_row = rows
             .iter()
-            .enumerate()
-            .take((terminal_height - 5) as usize)
-        {
+            .position(|row| match *row {
+                BookmarkRow::Bookmark(position, _) | BookmarkRow::Suggestion(position, _) => {
+                    position == selected
+                }
+                _ => false,
+            })
+            .unwrap_or(0);
+        let top = selected_row.saturating_sub(list_height - 1);
+        for (i, entry) in rows.iter().skip(top).take(list_height).enumerate() {
             let row = 4 + i as u16;
-            let is_selected = self.bookmark_selected_index == Some(i);
+            let (position, index) = match *entry {
+                BookmarkRow::Category(category) => {
+                    execute!(
+                        stdout,
+                        MoveTo(2, row),
+                        SetForegroundColor(Color::Magenta),
+                        Print(format!("── {} ──", category.unwrap_or("untagged"))),
+                        ResetColor
+                    )?;
+                    continue;
+                }
+                BookmarkRow::SuggestionsHeader => {
+                    execute!(
+                        stdout,
+                        MoveTo(2, row),
+                        SetForegroundColor(Color::Green),
+                        Print("── suggested: Enter bookmarks, Ctrl+D dismisses ──"),
+                        ResetColor
+                    )?;
+                    continue;
+                }
+                BookmarkRow::Suggestion(position, i) => {
+                    let (ref path, visits) = suggestions[i];
+                    let is_selected = position == selected;
+                    if is_selected {
+                        execute!(
+                            stdout,
+                            MoveTo(0, row),
+                            SetBackgroundColor(Color::DarkGreen),
+                            Print(" ".repeat(terminal_width as usize)),
+                        )?;
+                    }
+                    let name = path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default();
+                    execute!(
+                        stdout,
+                        MoveTo(2, row),
+                        Print(if is_selected { "> " } else { "  " }),
+                        SetForegroundColor(Color::Green),
+                        Print(" + "),
+                        SetForegroundColor(Color::White),
+                        Print(format!(" {:25} ", name)),
+                        SetForegroundColor(if is_selected {
+                            Color::Cyan
+                        } else {
+                            Color::Green
+                        }),
+                        Print(format!("{:35} ", path.display())),
+                        SetForegroundColor(if is_selected {
+                            Color::White
+                        } else {
+                            Color::DarkGrey
+                        }),
+                        Print(format!("visited {} times", visits)),
+                        ResetColor
+                    )?;
+                    continue;
+                }
+                BookmarkRow::Bookmark(position, index) => (position, index),
+            };
+            let bookmark = &bookmarks[index];
+            let is_selected = position == selected;
+            let is_dead = self.bookmark_health.is_dead(&bookmark.path);
 
             let shortcut_str = bookmark
                 .shortcut
                 .map(|c| format!("[{}]", c))
                 .unwrap_or_else(|| "   ".to_string());
 
-            let access_str = format!("({}x)", bookmark.access_count);
+            // Shared bookmarks keep no statistics of their own
+            let access_str = if bookmark.shared {
+                "(shared)".to_string()
+            } else {
+                format!("({}x)", bookmark.access_count)
+            };
+            let tags_str = bookmark
+                .tags
+                .iter()
+                .map(|tag| format!(" #{}", tag))
+                .collect::<String>();
+            let (badge, badge_color) = match self.bookmark_health.get(&bookmark.path) {
+                None => ("…", Color::DarkGrey),
+                Some(availability) => (
+                    availability.label(),
+                    match availability {
+                        Availability::Available => Color::Green,
+                        Availability::PermissionDenied => Color::Yellow,
+                        Availability::Missing | Availability::NotADirectory => Color::Red,
+                        Availability::Unreachable => Color::Magenta,
+                    },
+                ),
+            };
 
             // Apply selection highlighting
             if is_selected {
@@ -559,27 +1736,64 @@ impl Navigator {
                     Color::Cyan
                 }),
                 Print(shortcut_str),
-                SetForegroundColor(Color::White),
+                SetForegroundColor(if is_dead {
+                    Color::Red
+                } else if bookmark.shared {
+                    Color::Blue
+                } else {
+                    Color::White
+                }),
                 Print(format!(" {:25} ", bookmark.name)),
-                SetForegroundColor(if is_selected {
+                SetForegroundColor(if is_dead {
+                    Color::Red
+                } else if is_selected {
                     Color::Cyan
                 } else {
                     Color::Green
                 }),
                 Print(format!("{:35} ", bookmark.path.display())),
+                SetForegroundColor(badge_color),
+                Print(format!("{:<12}", format!("[{}]", badge))),
                 SetForegroundColor(if is_selected {
                     Color::White
                 } else {
                     Color::DarkGrey
                 }),
                 Print(access_str),
+                Print(if bookmark.hot { " 🔥" } else { "" }),
+                SetForegroundColor(if is_selected {
+                    Color::Yellow
+                } else {
+                    Color::Magenta
+                }),
+                Print(tags_str),
+                ResetColor
+            )?;
+        }
+
+        // Tags in use, for filtering with #tag
+        let tags = self.bookmarks_manager.all_tags();
+        if !tags.is_empty() {
+            let tags_str = tags
+                .iter()
+                .map(|tag| format!("#{}", tag))
+                .collect::<Vec<_>>()
+                .join(" ");
+            execute!(
+                stdout,
+                MoveTo(2, terminal_height - 2),
+                SetForegroundColor(Color::DarkGrey),
+                Print(truncate_chars(
+                    &format!("Tags: {}", tags_str),
+                    (terminal_width as usize).saturating_sub(4)
+                )),
                 ResetColor
             )?;
         }
 
         // Available shortcuts
         let available = self.bookmarks_manager.get_available_shortcuts();
-        if !available.is_empty() && !self.bookmark_rename_mode {
+        if !available.is_empty() && self.bookmark_edit.is_none() {
             let avail_str = available
                 .iter()
                 .take(15)
@@ -613,10 +1827,14 @@ impl Navigator {
             MoveTo(0, terminal_height - 1),
             SetBackgroundColor(Color::DarkGrey),
             SetForegroundColor(Color::White),
-            if self.bookmark_rename_mode {
+            if self.bookmark_edit.is_some() {
+                Print(" Tab: Name/Path | Enter: Save | Esc: Cancel ")
+            } else if self.bookmark_shortcut_input.is_some() {
+                Print(" a-z/0-9: Pick | Backspace: None | Enter: Save | Esc: Cancel ")
+            } else if self.bookmark_tags_input.is_some() {
                 Print(" Enter: Save | Esc: Cancel ")
             } else {
-                Print(" ↑↓: Select | Enter: Go | [a-z]: Jump | Ctrl+A: Add | Ctrl+D: Delete | Ctrl+R: Rename | Esc: Back ")
+                Print(" ↑↓: Select | Shift+↑↓: Move | Enter: Go | Alt+[a-z]: Jump | Ctrl+A: Add | Ctrl+D: Delete | Ctrl+R: Edit | Ctrl+S: Shortcut | Ctrl+T: Tags | Ctrl+W: Hot | Ctrl+X: Prune dead | Esc: Clear/Back ")
             },
             Print(" ".repeat((terminal_width as usize).saturating_sub(90))),
             ResetColor
@@ -634,8 +1852,44 @@ impl Navigator {
         // Clear status message on any key press
         self.status_message = None;
 
+        // A quit waiting for confirmation lasts one key press
+        if std::mem::take(&mut self.quit_pending) && code == KeyCode::Char('q') {
+            return Ok(Some(ExitAction::Quit));
+        }
+
+        if let Some((ref mut overlay, _)) = self.help {
+            if !overlay.handle_input(code) {
+                if let Some((_, previous_mode)) = self.help.take() {
+                    self.mode = previous_mode;
+                }
+            }
+            return Ok(None);
+        }
+
+        // F1 shows the bindings of whatever mode is active
+        if code == KeyCode::F(1) {
+            let sections = keymap(
+                &self.mode,
+                self.is_root,
+                self.config.profile,
+                &self.custom_commands,
+                self.filter.is_some(),
+            );
+            let previous_mode = std::mem::replace(&mut self.mode, NavigatorMode::Help);
+            self.help = Some((HelpOverlay::new(sections), previous_mode));
+            return Ok(None);
+        }
+
+        if self.mode == NavigatorMode::Sessions {
+            return self.handle_session_input(code, modifiers);
+        }
+
         // Handle special modes first
         if self.mode == NavigatorMode::SplitPane {
+            if code == KeyCode::F(4) {
+                self.open_session_picker();
+                return Ok(None);
+            }
             return self.handle_split_pane_input(code, modifiers);
         }
 
@@ -647,100 +1901,332 @@ impl Navigator {
             return self.handle_bookmarks_input(code, modifiers);
         }
 
-        match self.mode {
-            NavigatorMode::Browse => {
-                // Handle preview-focused controls first
-                if self.show_preview_panel && self.preview_focused {
-                    match code {
-                        KeyCode::Up => {
-                            if let Some(ref mut preview) = self.file_preview {
-                                preview.scroll_up(1);
-                            }
-                        }
-                        KeyCode::Down => {
-                            if let Some(ref mut preview) = self.file_preview {
-                                preview.scroll_down(1);
-                            }
-                        }
-                        KeyCode::PageUp => {
-                            if let Some(ref mut preview) = self.file_preview {
-                                preview.scroll_up(10);
-                            }
-                        }
-                        KeyCode::PageDown => {
-                            if let Some(ref mut preview) = self.file_preview {
-                                preview.scroll_down(10);
-                            }
-                        }
-                        KeyCode::Tab => {
-                            self.preview_focused = false;
-                        }
-                        KeyCode::Esc => {
-                            self.preview_focused = false;
-                        }
-                        _ => {}
-                    }
-                } else {
-                    // Normal browse mode controls
-                    match code {
-                        KeyCode::Tab if self.show_preview_panel => {
-                            self.preview_focused = true;
-                        }
-                        KeyCode::Up => self.move_selection_up(),
-                        KeyCode::Down => self.move_selection_down(),
-                        KeyCode::Right | KeyCode::Enter => self.navigate_to_selected()?,
-                        KeyCode::Left | KeyCode::Backspace => self.navigate_up()?,
+        if self.mode == NavigatorMode::OpenWith {
+            return self.handle_open_with_input(code);
+        }
 
-                        // New v0.4.0 shortcuts
-                        KeyCode::Char('f') if modifiers.contains(KeyModifiers::CONTROL) => {
-                            self.enter_search_mode();
-                        }
-                        KeyCode::Char('b') if modifiers.contains(KeyModifiers::CONTROL) => {
-                            self.mode = NavigatorMode::Bookmarks;
-                            self.bookmark_selected_index = Some(0);
-                        }
-                        KeyCode::Char('g') if modifiers.contains(KeyModifiers::CONTROL) => {
-                            self.show_goto_dialog()?;
-                        }
-                        KeyCode::Char('p') if modifiers.contains(KeyModifiers::CONTROL) => {
-                            self.toggle_preview_panel();
-                        }
-                        KeyCode::F(2) => {
-                            self.enter_split_pane_mode()?;
-                        }
+        if self.mode == NavigatorMode::CommandPalette {
+            return self.handle_palette_input(code);
+        }
 
-                        // Existing shortcuts
-                        KeyCode::Char('s') if self.is_root => {
-                            self.mode = NavigatorMode::Select;
-                        }
-                        KeyCode::Char('p')
-                            if self.is_root && !modifiers.contains(KeyModifiers::CONTROL) =>
-                        {
-                            self.mode = NavigatorMode::PatternSelect;
-                            self.pattern_input.clear();
-                        }
-                        KeyCode::Char('c') if self.is_root => {
-                            self.open_chmod_interface();
-                        }
-                        KeyCode::Char('o') if self.is_root => {
-                            self.open_chown_interface();
-                        }
-                        KeyCode::Char('d') if modifiers.contains(KeyModifiers::CONTROL) => {
-                            return Ok(Some(ExitAction::SpawnShell(self.current_dir.clone())));
-                        }
-                        KeyCode::Char('S') => {
-                            return Ok(Some(ExitAction::SpawnShell(self.current_dir.clone())));
-                        }
-                        KeyCode::Esc | KeyCode::Char('q') => {
-                            if self.show_preview_panel {
-                                self.show_preview_panel = false;
-                                self.preview_focused = false;
-                                self.file_preview = None;
-                            } else {
-                                return Ok(Some(ExitAction::Quit));
-                            }
-                        }
-                        _ => {}
+        if self.mode == NavigatorMode::Workflows {
+            return self.handle_workflow_input(code, modifiers);
+        }
+
+        if self.mode == NavigatorMode::FuzzyFinder {
+            return self.handle_finder_input(code, modifiers);
+        }
+
+        if self.mode == NavigatorMode::Monitor {
+            if let Some(ref mut monitor) = self.monitor {
+                if monitor.handle_input(code) {
+                    return Ok(None);
+                }
+            }
+            // Dropping the monitor stops its worker
+            self.monitor = None;
+            self.mode = NavigatorMode::Browse;
+            self.refresh_directory()?;
+            return Ok(None);
+        }
+
+        if self.mode == NavigatorMode::HexEditor {
+            if let Some(ref mut editor) = self.hex_editor {
+                if editor.handle_input(code) {
+                    return Ok(None);
+                }
+            }
+            self.hex_editor = None;
+            self.mode = NavigatorMode::Browse;
+            // Saved edits change the size and time shown in the listing
+            self.refresh_directory()?;
+            return Ok(None);
+        }
+
+        if self.mode == NavigatorMode::Pager {
+            if let Some(ref mut pager) = self.pager {
+                if pager.handle_input(code) {
+                    return Ok(None);
+                }
+            }
+            self.pager = None;
+            self.mode = NavigatorMode::Browse;
+            return Ok(None);
+        }
+
+        if self.mode == NavigatorMode::Diff {
+            if let Some(ref mut diff) = self.diff_view {
+                if diff.handle_input(code) {
+                    return Ok(None);
+                }
+            }
+            self.diff_view = None;
+            // Back to where the files were picked
+            self.mode = if self.split_pane_view.is_some() {
+                NavigatorMode::SplitPane
+            } else if !self.selected_items.is_empty() {
+                NavigatorMode::Select
+            } else {
+                NavigatorMode::Browse
+            };
+            return Ok(None);
+        }
+
+        if self.mode == NavigatorMode::DirStats {
+            let outcome = match self.dir_stats {
+                Some(ref mut stats) => stats.handle_input(code),
+                None => StatsOutcome::Close,
+            };
+            match outcome {
+                StatsOutcome::Continue => {}
+                StatsOutcome::Close => {
+                    self.dir_stats = None;
+                    self.mode = NavigatorMode::Browse;
+                }
+                StatsOutcome::Filter(extension) => {
+                    self.dir_stats = None;
+                    self.mode = NavigatorMode::Browse;
+                    self.filter_by_extension(&extension);
+                }
+            }
+            return Ok(None);
+        }
+
+        if self.mode == NavigatorMode::Checksums {
+            let outcome = match self.checksums {
+                Some(ref mut run) => run.handle_input(code),
+                None => ChecksumOutcome::Close,
+            };
+            match outcome {
+                ChecksumOutcome::Continue => {}
+                // The markers stay in the listing
+                ChecksumOutcome::Close => self.mode = NavigatorMode::Browse,
+                ChecksumOutcome::Rerun => {
+                    self.checksums = None;
+                    self.verify_checksums();
+                }
+            }
+            return Ok(None);
+        }
+
+        if self.mode == NavigatorMode::Containers {
+            let outcome = match self.containers {
+                Some(ref mut containers) => containers.handle_input(code),
+                None => ContainersOutcome::Close,
+            };
+            match outcome {
+                ContainersOutcome::Continue => {}
+                ContainersOutcome::Close => {
+                    self.containers = None;
+                    self.mode = NavigatorMode::Browse;
+                }
+                ContainersOutcome::Jump(path) => {
+                    self.containers = None;
+                    self.mode = NavigatorMode::Browse;
+                    self.jump_to_path(&path)?;
+                }
+            }
+            return Ok(None);
+        }
+
+        if self.mode == NavigatorMode::SnapshotReport {
+            if let Some(ref mut report) = self.snapshot_report {
+                if report.handle_input(code) {
+                    return Ok(None);
+                }
+            }
+            self.snapshot_report = None;
+            self.mode = NavigatorMode::Browse;
+            return Ok(None);
+        }
+
+        if self.mode == NavigatorMode::CommandOutput {
+            if let Some(ref mut output) = self.command_output {
+                if output.handle_input(code) {
+                    return Ok(None);
+                }
+            }
+            self.command_output = None;
+            self.mode = NavigatorMode::Browse;
+            return Ok(None);
+        }
+
+        if self.mode == NavigatorMode::QuickView {
+            // Any key dismisses the peek
+            self.quick_view = None;
+            self.mode = NavigatorMode::Browse;
+            return Ok(None);
+        }
+
+        match self.mode {
+            NavigatorMode::Browse => {
+                // Handle preview-focused controls first
+                if self.show_preview_panel && self.preview_focused {
+                    if self.preview_search_input.is_some() {
+                        self.handle_preview_search_input(code, modifiers);
+                        return Ok(None);
+                    }
+                    if self.preview_goto_input.is_some() {
+                        self.handle_preview_goto_input(code);
+                        return Ok(None);
+                    }
+                    let searching = self
+                        .file_preview
+                        .as_ref()
+                        .is_some_and(|preview| preview.search.is_some());
+                    match code {
+                        KeyCode::Up => {
+                            if let Some(ref mut preview) = self.file_preview {
+                                preview.scroll_up(1);
+                            }
+                        }
+                        KeyCode::Down => {
+                            if let Some(ref mut preview) = self.file_preview {
+                                preview.scroll_down(1);
+                            }
+                        }
+                        KeyCode::PageUp => {
+                            if let Some(ref mut preview) = self.file_preview {
+                                preview.scroll_up(10);
+                            }
+                        }
+                        KeyCode::PageDown => {
+                            if let Some(ref mut preview) = self.file_preview {
+                                preview.scroll_down(10);
+                            }
+                        }
+                        KeyCode::Char('g')
+                            if self.file_preview.as_ref().is_some_and(|preview| {
+                                matches!(preview.content, PreviewContent::Binary(_))
+                            }) =>
+                        {
+                            self.preview_goto_input = Some(String::new());
+                        }
+                        KeyCode::Char('/') if self.file_preview.is_some() => {
+                            if let Some(ref mut preview) = self.file_preview {
+                                preview.cancel_search();
+                            }
+                            self.preview_search_input = Some(String::new());
+                        }
+                        KeyCode::Char('n') | KeyCode::Char('N') if searching => {
+                            let forward = code == KeyCode::Char('n');
+                            self.status_message = self
+                                .file_preview
+                                .as_mut()
+                                .and_then(|preview| preview.jump_to_match(forward))
+                                .map(|(n, total)| format!("Match {} of {}", n, total))
+                                .or_else(|| Some("No matches".to_string()));
+                        }
+                        KeyCode::Char('n') => {
+                            if let Some(ref mut preview) = self.file_preview {
+                                preview.next_message();
+                            }
+                        }
+                        KeyCode::Enter => {
+                            if let Some(ref mut preview) = self.file_preview {
+                                preview.toggle_fold();
+                            }
+                        }
+                        KeyCode::Char('l') => {
+                            self.preview_line_numbers = !self.preview_line_numbers;
+                            self.status_message = Some(
+                                if self.preview_line_numbers {
+                                    "Line numbers shown"
+                                } else {
+                                    "Line numbers hidden"
+                                }
+                                .to_string(),
+                            );
+                        }
+                        KeyCode::Char('w') => {
+                            self.preview_wrap = !self.preview_wrap;
+                            self.status_message = Some(
+                                if self.preview_wrap {
+                                    "Wrapping long lines"
+                                } else {
+                                    "Cutting long lines"
+                                }
+                                .to_string(),
+                            );
+                        }
+                        KeyCode::Char('r') => {
+                            if let Some(ref mut preview) = self.file_preview {
+                                if preview.toggle_raw() {
+                                    self.status_message = Some(
+                                        if preview.show_raw {
+                                            "Markdown: raw source"
+                                        } else {
+                                            "Markdown: rendered"
+                                        }
+                                        .to_string(),
+                                    );
+                                }
+                            }
+                        }
+                        KeyCode::Char('p') => {
+                            if let Some(ref mut preview) = self.file_preview {
+                                preview.previous_message();
+                            }
+                        }
+                        KeyCode::Tab => {
+                            self.preview_focused = false;
+                        }
+                        // The first Esc clears search highlights
+                        KeyCode::Esc if searching => {
+                            if let Some(ref mut preview) = self.file_preview {
+                                preview.search = None;
+                            }
+                        }
+                        KeyCode::Esc => {
+                            self.preview_focused = false;
+                        }
+                        _ => {}
+                    }
+                } else {
+                    // While filtering, typed characters extend the filter
+                    // instead of triggering commands
+                    if self.filter.is_some() && self.handle_filter_input(code, modifiers) {
+                        return Ok(None);
+                    }
+
+                    // Normal browse mode controls
+                    match code {
+                        KeyCode::Tab if self.show_preview_panel => {
+                            self.preview_focused = true;
+                        }
+                        KeyCode::Up => self.move_selection_up(),
+                        KeyCode::Down => self.move_selection_down(),
+                        KeyCode::Right => self.navigate_to_selected()?,
+                        KeyCode::Left | KeyCode::Backspace => self.navigate_up()?,
+                        KeyCode::Esc | KeyCode::Char('q') if self.show_preview_panel => {
+                            self.toggle_preview_panel();
+                        }
+                        // Ctrl+G jumps straight to the bookmark list without a highlighted row
+                        KeyCode::Char('g') if modifiers.contains(KeyModifiers::CONTROL) => {
+                            self.show_goto_dialog()?;
+                        }
+                        // Root-only letters fall through for other users, then
+                        // to custom commands bound to keys nothing else uses
+                        _ => match Action::from_browse_key(code, modifiers) {
+                            Some(action)
+                                if action.is_available(self.is_root, self.config.profile) =>
+                            {
+                                return self.perform(action);
+                            }
+                            _ => {
+                                if let Some(index) = self.custom_command_for_key(code) {
+                                    return self.perform(Action::Custom(index));
+                                }
+                                // Any other plain character starts a type-to-filter
+                                if let KeyCode::Char(c) = code {
+                                    if !modifiers
+                                        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+                                    {
+                                        self.set_filter(Some(c.to_string()));
+                                    }
+                                }
+                            }
+                        },
                     }
                 }
             }
@@ -748,18 +2234,20 @@ impl Navigator {
                 KeyCode::Up => self.move_selection_up(),
                 KeyCode::Down => self.move_selection_down(),
                 KeyCode::Char(' ') => self.toggle_selection(),
-                KeyCode::Enter => {
-                    if !self.selected_items.is_empty() {
-                        self.status_message =
-                            Some(format!("{} items selected", self.selected_items.len()));
-                    }
+                KeyCode::Char('p') if modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.toggle_preview_panel();
+                }
+                KeyCode::Enter if !self.selected_items.is_empty() => {
+                    self.status_message =
+                        Some(format!("{} items selected", self.selected_items.len()));
                 }
                 KeyCode::Char('c') => {
-                    self.open_chmod_interface();
+                    self.open_chmod_interface(self.get_selected_paths());
                 }
                 KeyCode::Char('o') => {
-                    self.open_chown_interface();
+                    self.open_chown_interface(self.get_selected_paths());
                 }
+                KeyCode::Char('d') => self.open_diff(),
                 KeyCode::Esc => {
                     self.mode = NavigatorMode::Browse;
                     self.selected_items.clear();
@@ -768,9 +2256,13 @@ impl Navigator {
             },
             NavigatorMode::PatternSelect => match code {
                 KeyCode::Enter => {
-                    self.select_by_pattern();
-                    self.mode = NavigatorMode::Select;
+                    // An invalid regex keeps the prompt open
+                    let selected = self.select_by_pattern();
+                    if selected {
+                        self.mode = NavigatorMode::Select;
+                    }
                 }
+                KeyCode::Tab => self.pattern_mode = self.pattern_mode.next(),
                 KeyCode::Esc => {
                     self.mode = NavigatorMode::Browse;
                     self.pattern_input.clear();
@@ -786,22 +2278,30 @@ impl Navigator {
             NavigatorMode::ChmodInterface => {
                 if let Some(ref mut chmod) = self.chmod_interface {
                     if !chmod.handle_input(code) {
-                        self.mode = NavigatorMode::Browse;
+                        self.recent_choices = chmod.recent().clone();
+                        let step = chmod.applied().map(|(mode, files, recursive)| Step::Chmod {
+                            mode: mode.to_string(),
+                            files: files.map(str::to_string),
+                            recursive,
+                        });
+                        if let Some(step) = step {
+                            self.record_step(step);
+                        }
                         self.chmod_interface = None;
-                        self.selected_items.clear();
-                        let current_dir = self.current_dir.clone();
-                        self.load_directory(&current_dir)?;
+                        self.leave_permissions_interface()?;
                     }
                 }
             }
             NavigatorMode::ChownInterface => {
                 if let Some(ref mut chown) = self.chown_interface {
-                    if !chown.handle_input(code) {
-                        self.mode = NavigatorMode::Browse;
+                    if !chown.handle_input(code, modifiers) {
+                        self.recent_choices = chown.recent().clone();
+                        if let Some((owner, recursive)) = chown.applied() {
+                            let owner = owner.to_string();
+                            self.record_step(Step::Chown { owner, recursive });
+                        }
                         self.chown_interface = None;
-                        self.selected_items.clear();
-                        let current_dir = self.current_dir.clone();
-                        self.load_directory(&current_dir)?;
+                        self.leave_permissions_interface()?;
                     }
                 }
             }
@@ -817,13 +2317,21 @@ impl Navigator {
     ) -> Result<Option<ExitAction>> {
         if let Some(ref mut search) = self.search_mode {
             match code {
+                KeyCode::Enter if search.has_current_results() => {
+                    // Stay on the result the cursor was taken to
+                    self.mode = NavigatorMode::Browse;
+                    self.search_mode = None;
+                }
                 KeyCode::Enter => {
-                    // Execute search
-                    search.search(&self.entries, &self.current_dir)?;
-                    if !search.results.is_empty() {
-                        self.jump_to_search_result();
+                    // Results stream in through poll_search
+                    search.start(&self.entries);
+                    search.stop_recall();
+                    if let Err(e) = self.search_history.record(search.history_entry()) {
+                        self.status_message = Some(format!("Failed to save search history: {}", e));
                     }
                 }
+                KeyCode::Up => search.recall(&self.search_history, true),
+                KeyCode::Down => search.recall(&self.search_history, false),
                 KeyCode::Char('n') if modifiers.contains(KeyModifiers::CONTROL) => {
                     search.next_result();
                     self.jump_to_search_result();
@@ -841,12 +2349,42 @@ impl Navigator {
                 KeyCode::Char('g') if modifiers.contains(KeyModifiers::CONTROL) => {
                     search.toggle_search_contents();
                 }
+                KeyCode::Char('s') if modifiers.contains(KeyModifiers::CONTROL) => {
+                    search.cycle_scope();
+                }
+                KeyCode::Char('e') if modifiers.contains(KeyModifiers::CONTROL) => {
+                    if search.has_excludes() {
+                        search.toggle_excludes();
+                    } else {
+                        self.status_message =
+                            Some("No exclusions configured ([search] in config.toml)".to_string());
+                    }
+                }
+                KeyCode::Char('a') if modifiers.contains(KeyModifiers::CONTROL) => {
+                    search.toggle_archives();
+                }
+                KeyCode::Char('l') if modifiers.contains(KeyModifiers::CONTROL) => {
+                    let toggled = search.toggle_locate();
+                    if !toggled {
+                        self.status_message =
+                            Some("Neither plocate nor locate is installed".to_string());
+                    }
+                }
                 KeyCode::Backspace => {
+                    search.stop_recall();
                     search.query.pop();
                 }
                 KeyCode::Char(c) => {
+                    search.stop_recall();
                     search.query.push(c);
                 }
+                KeyCode::Esc if search.is_searching() => {
+                    search.cancel();
+                    self.status_message = Some(format!(
+                        "Search cancelled ({} results so far)",
+                        search.results.len()
+                    ));
+                }
                 KeyCode::Esc => {
                     self.mode = NavigatorMode::Browse;
                     self.search_mode = None;
@@ -860,26 +2398,133 @@ impl Navigator {
     fn handle_split_pane_input(
         &mut self,
         code: KeyCode,
-        _modifiers: KeyModifiers,
+        modifiers: KeyModifiers,
     ) -> Result<Option<ExitAction>> {
+        // Chmod and chown open on the active pane's selection once the
+        // view is no longer borrowed
+        let mut permissions = None;
+        let (is_root, profile) = (self.is_root, self.config.profile);
         if let Some(ref mut split) = self.split_pane_view {
+            split.status = None;
+            if std::mem::take(&mut split.move_confirm) {
+                split.status = Some(match code {
+                    KeyCode::Char('y') | KeyCode::Char('Y') => {
+                        split.move_to_other_pane()?.summary("Moved")
+                    }
+                    _ => "Move cancelled".to_string(),
+                });
+                return Ok(None);
+            }
+            if split
+                .get_active_pane_mut()
+                .handle_filter_input(code, modifiers)
+            {
+                return Ok(None);
+            }
             match code {
-                KeyCode::Tab => split.toggle_focus(),
-                KeyCode::Up => split.get_active_pane_mut().move_up(),
-                KeyCode::Down => split.get_active_pane_mut().move_down(),
-                KeyCode::Enter | KeyCode::Right => {
-                    split.get_active_pane_mut().navigate_to_selected()?;
+                KeyCode::Char('f') if modifiers.contains(KeyModifiers::CONTROL) => {
+                    let pane = split.get_active_pane_mut();
+                    if pane.filter.is_none() {
+                        pane.set_filter(Some(String::new()));
+                    }
+                }
+                KeyCode::Tab => split.cycle_focus(false),
+                KeyCode::BackTab => split.cycle_focus(true),
+                KeyCode::Char(c) if modifiers.contains(KeyModifiers::ALT) && c.is_ascii_digit() => {
+                    let number = c.to_digit(10).unwrap_or(0);
+                    if !split.focus_pane(number) {
+                        split.status = Some(format!("No pane {}", number));
+                    }
+                }
+                KeyCode::Up | KeyCode::Down => {
+                    let pane = split.get_active_pane_mut();
+                    if code == KeyCode::Up {
+                        pane.move_up();
+                    } else {
+                        pane.move_down();
+                    }
+                    let before = pane.current_dir.clone();
+                    split.mirror(&before)?;
                 }
-                KeyCode::Backspace | KeyCode::Left => {
-                    split.get_active_pane_mut().navigate_up()?;
+                KeyCode::Enter | KeyCode::Right | KeyCode::Backspace | KeyCode::Left => {
+                    let pane = split.get_active_pane_mut();
+                    let before = pane.current_dir.clone();
+                    if matches!(code, KeyCode::Enter | KeyCode::Right) {
+                        pane.navigate_to_selected()?;
+                    } else {
+                        pane.navigate_up()?;
+                    }
+                    split.mirror(&before)?;
                 }
+                KeyCode::Char('l') => split.toggle_link(),
                 KeyCode::F(5) => split.sync_directories()?,
-                KeyCode::F(6) => split.toggle_layout(),
+                KeyCode::F(6) => split.cycle_layout(),
+                KeyCode::F(7) => {
+                    split.status =
+                        (!split.add_pane()?).then(|| format!("At most {} panes", MAX_PANES));
+                }
+                KeyCode::F(8) => {
+                    split.status = (!split.close_pane())
+                        .then(|| "Two panes stay open; q leaves the split view".to_string());
+                }
+                KeyCode::Char('o') => {
+                    split.status =
+                        (!split.open_in_other_pane()?).then(|| "Not a directory".to_string());
+                }
+                KeyCode::Char('p') => split.toggle_preview(),
+                KeyCode::Char('=') => split.toggle_compare(),
+                KeyCode::PageUp | KeyCode::PageDown if split.preview => {
+                    if let Some(ref mut preview) = self.file_preview {
+                        if code == KeyCode::PageUp {
+                            preview.scroll_up(10);
+                        } else {
+                            preview.scroll_down(10);
+                        }
+                    }
+                }
                 KeyCode::Char('+') => split.adjust_split(0.05),
                 KeyCode::Char('-') => split.adjust_split(-0.05),
                 KeyCode::Char(' ') => {
                     split.get_active_pane_mut().toggle_selection();
                 }
+                // The basic profile only browses
+                KeyCode::Char('c' | 'm') if profile == Profile::Basic => {
+                    split.status = Some(
+                        "⚠️  Copying and moving are not available in this profile".to_string(),
+                    );
+                }
+                KeyCode::Char('c') => {
+                    let report = split.copy_to_other_pane()?;
+                    split.status = Some(report.summary("Copied"));
+                }
+                KeyCode::Char('m') => split.ask_move(),
+                KeyCode::Char('d') => match split.diff_paths() {
+                    Some((old, new)) => {
+                        self.diff_view = Some(DiffView::open(&old, &new));
+                        self.mode = NavigatorMode::Diff;
+                    }
+                    None => {
+                        split.status = Some(
+                            "Select two files in one pane, or one file in each, to compare"
+                                .to_string(),
+                        );
+                    }
+                },
+                KeyCode::Char(key @ ('C' | 'O')) => {
+                    let action = if key == 'C' {
+                        Action::OpenChmod
+                    } else {
+                        Action::OpenChown
+                    };
+                    let paths = split.get_active_pane().get_selected_paths();
+                    if !action.is_available(is_root, profile) {
+                        split.status = Some(format!("⚠️  {} is not available", action.label()));
+                    } else if paths.is_empty() {
+                        split.status = Some("No items selected".to_string());
+                    } else {
+                        permissions = Some((key, paths));
+                    }
+                }
                 KeyCode::Esc | KeyCode::Char('q') => {
                     self.mode = NavigatorMode::Browse;
                     self.split_pane_view = None;
@@ -887,6 +2532,11 @@ impl Navigator {
                 _ => {}
             }
         }
+        match permissions {
+            Some(('C', paths)) => self.open_chmod_interface(paths),
+            Some((_, paths)) => self.open_chown_interface(paths),
+            None => {}
+        }
         Ok(None)
     }
 
@@ -900,309 +2550,1805 @@ impl Navigator {
             self.bookmark_selected_index = Some(0);
         }
 
-        let bookmarks_count = self.bookmarks_manager.list_bookmarks().len();
+        // The cursor moves through the bookmarks the filter lets through
+        let matches = self.bookmarks_manager.filter(&self.bookmark_filter);
+        let selected = self.bookmark_selected_index.unwrap_or(0);
+        let selected_bookmark = matches.get(selected).copied();
+        let selected_shared = selected_bookmark
+            .is_some_and(|idx| self.bookmarks_manager.list_bookmarks()[idx].shared);
+        // A prune waiting for confirmation lasts one key press
+        let prune_confirmed = std::mem::take(&mut self.bookmark_prune_pending)
+            && code == KeyCode::Char('x')
+            && modifiers.contains(KeyModifiers::CONTROL);
+        // Suggestions follow the matches
+        let suggestions = self.bookmark_suggestions();
+        let selected_suggestion = selected
+            .checked_sub(matches.len())
+            .and_then(|i| suggestions.get(i))
+            .map(|(path, _)| path.clone());
 
         // Handle rename mode input
-        if self.bookmark_rename_mode {
+        if let Some(ref mut edit) = self.bookmark_edit {
             match code {
+                KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down => {
+                    edit.editing_path = !edit.editing_path;
+                }
                 KeyCode::Enter => {
-                    if let Some(idx) = self.bookmark_selected_index {
-                        if !self.bookmark_rename_input.is_empty() {
-                            if let Err(e) = self
-                                .bookmarks_manager
-                                .rename_bookmark(idx, self.bookmark_rename_input.clone())
-                            {
-                                self.status_message = Some(format!("Failed to rename: {}", e));
-                            } else {
-                                self.status_message = Some("Bookmark renamed!".to_string());
-                            }
-                        }
+                    if let Some(idx) = selected_bookmark {
+                        self.status_message = Some(match self.save_bookmark_edit(idx) {
+                            Ok(()) => "Bookmark updated!".to_string(),
+                            Err(e) => format!("Failed to update bookmark: {}", e),
+                        });
                     }
-                    self.bookmark_rename_mode = false;
-                    self.bookmark_rename_input.clear();
+                    self.bookmark_edit = None;
                 }
-                KeyCode::Esc => {
-                    self.bookmark_rename_mode = false;
-                    self.bookmark_rename_input.clear();
+                KeyCode::Esc => self.bookmark_edit = None,
+                // Ctrl+S swaps the name for its portable variant
+                KeyCode::Char('s')
+                    if modifiers.contains(KeyModifiers::CONTROL) && edit.name_issue().is_some() =>
+                {
+                    edit.name = sanitize_filename(edit.name.trim());
                 }
+                KeyCode::Char(_) if modifiers.contains(KeyModifiers::CONTROL) => {}
                 KeyCode::Backspace => {
-                    self.bookmark_rename_input.pop();
-                }
-                KeyCode::Char(c) => {
-                    self.bookmark_rename_input.push(c);
+                    edit.field().pop();
                 }
+                KeyCode::Char(c) => edit.field().push(c),
                 _ => {}
             }
             return Ok(None);
         }
 
-        match code {
-            KeyCode::Up => {
-                if let Some(ref mut idx) = self.bookmark_selected_index {
-                    if *idx > 0 {
-                        *idx -= 1;
+        if let Some(ref mut shortcut) = self.bookmark_shortcut_input {
+            match code {
+                KeyCode::Enter => {
+                    let shortcut = *shortcut;
+                    self.bookmark_shortcut_input = None;
+                    if let Some(idx) = selected_bookmark {
+                        self.status_message = Some(
+                            match self.bookmarks_manager.update_shortcut(idx, shortcut) {
+                                Ok(()) => "Shortcut saved!".to_string(),
+                                Err(e) => format!("Failed to set shortcut: {}", e),
+                            },
+                        );
                     }
                 }
+                KeyCode::Esc => self.bookmark_shortcut_input = None,
+                KeyCode::Backspace | KeyCode::Delete => *shortcut = None,
+                KeyCode::Char(c) if is_valid_shortcut(c.to_ascii_lowercase()) => {
+                    *shortcut = Some(c.to_ascii_lowercase());
+                }
+                _ => {}
             }
-            KeyCode::Down => {
-                if let Some(ref mut idx) = self.bookmark_selected_index {
-                    if *idx < bookmarks_count - 1 {
-                        *idx += 1;
+            return Ok(None);
+        }
+
+        if let Some(ref mut input) = self.bookmark_tags_input {
+            match code {
+                KeyCode::Enter => {
+                    let tags = parse_tags(input);
+                    self.bookmark_tags_input = None;
+                    if let Some(idx) = selected_bookmark {
+                        self.status_message =
+                            Some(match self.bookmarks_manager.set_tags(idx, tags) {
+                                Ok(()) => "Tags saved!".to_string(),
+                                Err(e) => format!("Failed to save tags: {}", e),
+                            });
                     }
                 }
+                KeyCode::Esc => self.bookmark_tags_input = None,
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(c) => input.push(c),
+                _ => {}
+            }
+            return Ok(None);
+        }
+
+        match code {
+            // Shift+↑/↓ moves the bookmark within its category
+            KeyCode::Up | KeyCode::Down if modifiers.contains(KeyModifiers::SHIFT) => {
+                self.move_bookmark(selected, &matches, code == KeyCode::Up);
+            }
+            KeyCode::Up => {
+                self.bookmark_selected_index = Some(selected.saturating_sub(1));
+            }
+            KeyCode::Down if selected + 1 < matches.len() + suggestions.len() => {
+                self.bookmark_selected_index = Some(selected + 1);
             }
             KeyCode::Enter => {
-                // Navigate to selected bookmark
-                if let Some(idx) = self.bookmark_selected_index {
-                    if let Some(bookmark) = self.bookmarks_manager.get_bookmark_by_index(idx) {
+                // Navigate to the selected bookmark, the best match when filtering
+                if let Some(idx) = selected_bookmark {
+                    let bookmark = &self.bookmarks_manager.list_bookmarks()[idx];
+                    if let Some(availability) = self
+                        .bookmark_health
+                        .get(&bookmark.path)
+                        .filter(|availability| availability.is_dead())
+                    {
+                        self.status_message = Some(format!(
+                            "⚠️  {} is {}: Ctrl+X prunes dead bookmarks",
+                            bookmark.path.display(),
+                            availability.label()
+                        ));
+                    } else if let Some(bookmark) = self
+                        .bookmarks_manager
+                        .get_bookmark_by_index(idx, &self.bookmark_health)
+                    {
                         let path = bookmark.path.clone();
                         self.load_directory(&path)?;
                         self.mode = NavigatorMode::Browse;
                         self.bookmark_selected_index = None;
+                        self.bookmark_filter.clear();
                     }
+                } else if let Some(path) = selected_suggestion {
+                    self.bookmark_dir(path);
                 }
             }
+            // Ctrl+X lists the dead bookmarks, and again removes them
+            KeyCode::Char('x') if modifiers.contains(KeyModifiers::CONTROL) => {
+                self.prune_dead_bookmarks(prune_confirmed);
+            }
             // Ctrl+A to add bookmark
             KeyCode::Char('a') if modifiers.contains(KeyModifiers::CONTROL) => {
-                let name = self
-                    .current_dir
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("Bookmark")
-                    .to_string();
-
-                let available = self.bookmarks_manager.get_available_shortcuts();
-                let shortcut = available.first().copied();
-
-                if let Err(e) =
-                    self.bookmarks_manager
-                        .add_bookmark(name, self.current_dir.clone(), shortcut)
-                {
-                    self.status_message = Some(format!("Failed to add bookmark: {}", e));
-                } else {
-                    self.status_message = Some(format!(
-                        "Bookmark added with shortcut '{}'!",
-                        shortcut
-                            .map(|c| c.to_string())
-                            .unwrap_or_else(|| "none".to_string())
-                    ));
-                }
+                self.bookmark_current_dir();
+            }
+            // Shared bookmarks come from a file only an administrator edits
+            KeyCode::Char('d' | 'r' | 's' | 't' | 'w')
+                if modifiers.contains(KeyModifiers::CONTROL) && selected_shared =>
+            {
+                self.status_message = Some(format!(
+                    "Shared bookmarks are read-only: edit {}",
+                    SHARED_BOOKMARKS_PATH
+                ));
             }
             // Ctrl+D to delete bookmark
             KeyCode::Char('d') if modifiers.contains(KeyModifiers::CONTROL) => {
-                if let Some(idx) = self.bookmark_selected_index {
+                if let Some(idx) = selected_bookmark {
                     if let Err(e) = self.bookmarks_manager.remove_bookmark(idx) {
                         self.status_message = Some(format!("Failed to delete bookmark: {}", e));
                     } else {
                         self.status_message = Some("Bookmark deleted!".to_string());
                         // Adjust selection if necessary
-                        if idx >= bookmarks_count - 1 && idx > 0 {
-                            self.bookmark_selected_index = Some(idx - 1);
+                        if selected + 1 >= matches.len() && selected > 0 {
+                            self.bookmark_selected_index = Some(selected - 1);
                         }
                     }
+                } else if let Some(path) = selected_suggestion {
+                    self.status_message = Some(match self.dir_visits.dismiss(&path) {
+                        Ok(()) => format!("No longer suggesting {}", path.display()),
+                        Err(e) => format!("Failed to dismiss suggestion: {}", e),
+                    });
+                    if selected + 1 >= matches.len() + suggestions.len() && selected > 0 {
+                        self.bookmark_selected_index = Some(selected - 1);
+                    }
                 }
             }
-            // Ctrl+R to rename bookmark
+            // Ctrl+R to edit the name and path, starting from the current ones
             KeyCode::Char('r') if modifiers.contains(KeyModifiers::CONTROL) => {
-                if self.bookmark_selected_index.is_some() {
-                    self.bookmark_rename_mode = true;
-                    self.bookmark_rename_input.clear();
-                    self.status_message = Some("Enter new name:".to_string());
+                if let Some(idx) = selected_bookmark {
+                    let bookmark = &self.bookmarks_manager.list_bookmarks()[idx];
+                    self.bookmark_edit = Some(BookmarkEdit {
+                        name: bookmark.name.clone(),
+                        path: bookmark.path.display().to_string(),
+                        editing_path: false,
+                    });
                 }
             }
-            // Direct letter access to jump to bookmark
-            KeyCode::Char(c)
-                if c.is_alphanumeric() && !modifiers.contains(KeyModifiers::CONTROL) =>
-            {
-                if let Some(bookmark) = self.bookmarks_manager.get_bookmark_by_shortcut(c) {
-                    let path = bookmark.path.clone();
-                    self.load_directory(&path)?;
-                    self.mode = NavigatorMode::Browse;
-                    self.bookmark_selected_index = None;
-                } else {
+            // Ctrl+S to pick another shortcut
+            KeyCode::Char('s') if modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Some(idx) = selected_bookmark {
+                    self.bookmark_shortcut_input =
+                        Some(self.bookmarks_manager.list_bookmarks()[idx].shortcut);
+                }
+            }
+            // Ctrl+T to edit tags, starting from the current ones
+            KeyCode::Char('t') if modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Some(idx) = selected_bookmark {
+                    let tags = &self.bookmarks_manager.list_bookmarks()[idx].tags;
+                    self.bookmark_tags_input = Some(tags.join(" "));
+                }
+            }
+            // Ctrl+W to watch the directory for new files, or stop
+            KeyCode::Char('w') if modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Some(idx) = selected_bookmark {
+                    self.status_message = Some(match self.bookmarks_manager.toggle_hot(idx) {
+                        Ok(true) => "🔥 Watching for new files (N: jump to the newest)".to_string(),
+                        Ok(false) => "No longer watching this directory".to_string(),
+                        Err(e) => format!("Failed to update bookmark: {}", e),
+                    });
+                }
+            }
+            // Alt+letter jumps straight to the bookmark with that shortcut
+            KeyCode::Char(c) if c.is_alphanumeric() && modifiers.contains(KeyModifiers::ALT) => {
+                if let Some(bookmark) = self
+                    .bookmarks_manager
+                    .get_bookmark_by_shortcut(c, &self.bookmark_health)
+                {
+                    let path = bookmark.path.clone();
+                    self.load_directory(&path)?;
+                    self.mode = NavigatorMode::Browse;
+                    self.bookmark_selected_index = None;
+                    self.bookmark_filter.clear();
+                } else {
                     self.status_message = Some(format!("No bookmark with shortcut '{}'", c));
                 }
             }
-            KeyCode::Esc => {
-                self.mode = NavigatorMode::Browse;
-                self.bookmark_selected_index = None;
+            // Anything else typed narrows the list, best match on top
+            KeyCode::Char(c) if !modifiers.contains(KeyModifiers::CONTROL) => {
+                self.bookmark_filter.push(c);
+                self.bookmark_selected_index = Some(0);
+            }
+            KeyCode::Backspace => {
+                self.bookmark_filter.pop();
+                self.bookmark_selected_index = Some(0);
+            }
+            KeyCode::Esc if !self.bookmark_filter.is_empty() => {
+                self.bookmark_filter.clear();
+                self.bookmark_selected_index = Some(0);
+            }
+            KeyCode::Esc => {
+                self.mode = NavigatorMode::Browse;
+                self.bookmark_selected_index = None;
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn handle_open_with_input(&mut self, code: KeyCode) -> Result<Option<ExitAction>> {
+        let Some(ref mut menu) = self.open_with_menu else {
+            self.mode = NavigatorMode::Browse;
+            return Ok(None);
+        };
+
+        match menu.handle_input(code) {
+            OpenWithOutcome::Continue => {}
+            OpenWithOutcome::Cancel => {
+                self.mode = NavigatorMode::Browse;
+                self.open_with_menu = None;
+            }
+            OpenWithOutcome::Launch { choice, remember } => {
+                let path = menu.path.clone();
+                let extension = menu.extension().to_string();
+                self.mode = NavigatorMode::Browse;
+                self.open_with_menu = None;
+
+                if remember {
+                    if let Err(e) = self.associations.remember(&extension, &choice.command) {
+                        self.status_message = Some(format!("Failed to remember choice: {}", e));
+                    }
+                }
+
+                let Some(mut command) = choice.build_command(&path) else {
+                    self.status_message = Some("Empty command".to_string());
+                    return Ok(None);
+                };
+
+                let result = if choice.terminal {
+                    command.current_dir(&self.current_dir);
+                    with_suspended_tui(self.mouse_captured, || command.status().map(|_| ()))?
+                } else {
+                    spawn_detached(command)
+                };
+
+                match result {
+                    Ok(()) if remember => {
+                        self.status_message = Some(format!(
+                            "Opened with {} (remembered for .{})",
+                            choice.name, extension
+                        ));
+                    }
+                    Ok(()) => {
+                        self.status_message = Some(format!("Opened with {}", choice.name));
+                    }
+                    Err(e) => {
+                        self.status_message =
+                            Some(format!("Failed to launch {}: {}", choice.name, e));
+                    }
+                }
+
+                if choice.terminal {
+                    self.refresh_directory()?;
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn handle_palette_input(&mut self, code: KeyCode) -> Result<Option<ExitAction>> {
+        let Some(ref mut palette) = self.command_palette else {
+            self.mode = NavigatorMode::Browse;
+            return Ok(None);
+        };
+
+        match palette.handle_input(code) {
+            PaletteOutcome::Continue => Ok(None),
+            PaletteOutcome::Cancel => {
+                self.mode = NavigatorMode::Browse;
+                self.command_palette = None;
+                Ok(None)
+            }
+            PaletteOutcome::Run(action) => {
+                self.mode = NavigatorMode::Browse;
+                self.command_palette = None;
+                self.perform(action)
+            }
+        }
+    }
+
+    /// Run an action from a keybinding or the command palette
+    fn perform(&mut self, action: Action) -> Result<Option<ExitAction>> {
+        if !action.is_available(self.is_root, self.config.profile) {
+            self.status_message = Some(format!("⚠️  {} is not available", action.label()));
+            return Ok(None);
+        }
+
+        match action {
+            Action::OpenSelected => self.open_selected()?,
+            Action::OpenExternally => self.open_highlighted_externally(),
+            Action::OpenWith => self.open_with_menu_for_selected(),
+            Action::QuickView => self.open_quick_view(),
+            Action::Search => self.enter_search_mode(),
+            Action::FuzzyFind => {
+                self.fuzzy_finder = Some(FuzzyFinder::new(
+                    &self.current_dir,
+                    self.listing_options.show_hidden,
+                    self.exclude_rules(),
+                ));
+                self.mode = NavigatorMode::FuzzyFinder;
+            }
+            Action::Filter => self.set_filter(Some(String::new())),
+            Action::FilterByExtension => {
+                match self.entries.get(self.selected_index).map(entry_extension) {
+                    Some(Some(extension)) => self.filter_by_extension(&extension),
+                    _ => {
+                        self.status_message =
+                            Some("The highlighted entry has no extension".to_string())
+                    }
+                }
+            }
+            Action::DirectoryStats => {
+                let entries = if self.filter.is_some() {
+                    &self.unfiltered_entries
+                } else {
+                    &self.entries
+                };
+                self.dir_stats = Some(DirStats::new(&self.current_dir, entries));
+                self.mode = NavigatorMode::DirStats;
+            }
+            Action::TogglePreview => self.toggle_preview_panel(),
+            Action::ToggleHidden => self.toggle_hidden()?,
+            Action::GoToRealPath => self.go_to_real_path(),
+            Action::JumpToHotFile => self.jump_to_hot_file(),
+            Action::VerifyChecksums => self.verify_checksums(),
+            Action::ExtractArchive => self.extract_archive(),
+            Action::ToggleRelativeDates => {
+                self.time_format.relative = !self.time_format.relative;
+                self.status_message = Some(if self.time_format.relative {
+                    "Showing relative dates".to_string()
+                } else {
+                    "Showing absolute dates".to_string()
+                });
+            }
+            Action::CycleSort => self.set_sort_key(self.listing_options.sort_key.next())?,
+            Action::SortByName => self.set_sort_key(SortKey::Name)?,
+            Action::SortBySize => self.set_sort_key(SortKey::Size)?,
+            Action::SortByModified => self.set_sort_key(SortKey::Modified)?,
+            Action::CycleGrouping => {
+                self.listing_options.grouping = self.listing_options.grouping.next();
+                self.clear_preview();
+                self.refresh_directory()?;
+                self.status_message = Some(format!(
+                    "Grouping: {}",
+                    self.listing_options.grouping.label()
+                ));
+            }
+            Action::OpenBookmarks => {
+                self.mode = NavigatorMode::Bookmarks;
+                self.bookmark_selected_index = Some(0);
+                if let Err(e) = self.bookmarks_manager.sync() {
+                    self.status_message = Some(format!("Failed to reload bookmarks: {}", e));
+                }
+                self.bookmark_health
+                    .check(self.bookmarks_manager.list_bookmarks());
+                if let Some(error) = self.bookmarks_manager.shared_error() {
+                    self.status_message = Some(format!("Shared bookmarks not loaded: {}", error));
+                }
+            }
+            Action::AddBookmark => self.bookmark_current_dir(),
+            Action::TogglePin => self.toggle_pin()?,
+            Action::SplitPane => self.enter_split_pane_mode()?,
+            Action::OpenInSplit => self.open_in_split()?,
+            Action::TakeSnapshot => self.take_snapshot(),
+            Action::CompareSnapshot => self.compare_to_snapshot(),
+            Action::Monitor => {
+                self.monitor = Some(Monitor::new(&self.current_dir));
+                self.mode = NavigatorMode::Monitor;
+            }
+            Action::HexEditor => self.open_hex_editor(),
+            Action::Pager => self.open_pager(),
+            Action::Diff => self.open_diff(),
+            Action::UnitStatus => self.show_unit_status()?,
+            Action::ContainerVolumes => self.open_container_volumes(),
+            Action::SelectMode if self.is_root => {
+                self.mode = NavigatorMode::Select;
+            }
+            Action::PatternSelect if self.is_root => {
+                self.mode = NavigatorMode::PatternSelect;
+                self.pattern_input.clear();
+            }
+            Action::SelectMode | Action::PatternSelect => {
+                self.status_message =
+                    Some("⚠️  Selection modes require root privileges".to_string());
+            }
+            Action::OpenChmod => self.open_chmod_interface(self.get_selected_paths()),
+            Action::OpenChown => self.open_chown_interface(self.get_selected_paths()),
+            Action::Sessions => self.open_session_picker(),
+            Action::RecordWorkflow => self.toggle_workflow_recording(),
+            Action::Workflows => {
+                if self.workflows.list().is_empty() {
+                    self.status_message =
+                        Some("No workflows saved yet: press w to record one".to_string());
+                } else {
+                    self.workflow_view = Some(WorkflowView::picking());
+                    self.mode = NavigatorMode::Workflows;
+                }
+            }
+            Action::SpawnShell => {
+                return Ok(Some(ExitAction::SpawnShell(self.current_dir.clone())));
+            }
+            Action::CommandPalette => {
+                self.command_palette = Some(CommandPalette::new(
+                    self.is_root,
+                    self.config.profile,
+                    &self.custom_commands,
+                ));
+                self.mode = NavigatorMode::CommandPalette;
+            }
+            Action::Custom(index) => self.run_custom_command(index)?,
+            Action::Quit => {
+                let prompt = self
+                    .config
+                    .confirm_quit
+                    .prompt(self.running_jobs(), self.selected_items.len());
+                match prompt {
+                    Some(prompt) => {
+                        self.status_message = Some(prompt);
+                        self.quit_pending = true;
+                    }
+                    None => return Ok(Some(ExitAction::Quit)),
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Background work quitting would abandon: a search, a directory
+    /// being read, a preview being loaded, checksums being verified and an
+    /// archive being extracted
+    fn running_jobs(&self) -> usize {
+        [
+            self.search_mode
+                .as_ref()
+                .is_some_and(|search| search.is_searching()),
+            self.pending_listing
+                .as_ref()
+                .is_some_and(|pending| pending.task.is_pending()),
+            self.preview_loader
+                .as_ref()
+                .is_some_and(|loader| loader.is_loading()),
+            self.checksums.as_ref().is_some_and(ChecksumRun::is_running),
+            self.extraction.as_ref().is_some_and(Extraction::is_running),
+        ]
+        .iter()
+        .filter(|&&running| running)
+        .count()
+    }
+
+    /// Extract the highlighted tarball into the current directory in the
+    /// background, through the staging area set in `[extract]`
+    fn extract_archive(&mut self) {
+        if self.extraction.as_ref().is_some_and(Extraction::is_running) {
+            self.status_message = Some("An archive is already being extracted".to_string());
+            return;
+        }
+        let Some(entry) = self.entries.get(self.selected_index) else {
+            return;
+        };
+        if entry.is_dir || !archive::is_tar_archive(&entry.name) {
+            self.status_message =
+                Some("Only .tar, .tar.gz and .tgz archives can be extracted".to_string());
+            return;
+        }
+        self.status_message = Some(format!("📦 Extracting {}…", entry.name));
+        self.extraction = Some(Extraction::start(
+            &entry.path,
+            &self.current_dir,
+            &self.config.extract.staging_root(),
+            self.config.extract.into_subdirectory,
+        ));
+    }
+
+    /// Report a finished extraction, showing what it made when it landed
+    /// in the directory on screen
+    fn finish_extraction(&mut self, outcome: io::Result<PathBuf>) {
+        let Some(extraction) = self.extraction.take() else {
+            return;
+        };
+        let name = extraction
+            .archive
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        self.status_message = Some(match outcome {
+            Ok(target) => {
+                if extraction.dest == self.current_dir {
+                    self.clear_preview();
+                    let dir = self.current_dir.clone();
+                    self.load_directory_at(&dir, Some(target.clone()), None);
+                }
+                format!("📦 Extracted {} to {}", name, target.display())
+            }
+            Err(e) => format!("Extracting {} failed: {}", name, e),
+        });
+    }
+
+    /// Record the current tree, replacing any earlier snapshot of it
+    fn take_snapshot(&mut self) {
+        let result = Snapshot::path_for(&self.current_dir).and_then(|path| {
+            let snapshot =
+                Snapshot::capture(&self.current_dir, self.config.snapshot.hash_contents, true)?;
+            snapshot.save(&path)?;
+            Ok(snapshot)
+        });
+
+        self.status_message = Some(match result {
+            Ok(snapshot) if snapshot.truncated => format!(
+                "📸 Snapshot saved, but stopped after {} entries",
+                snapshot.entries.len()
+            ),
+            Ok(snapshot) => format!("📸 Snapshot of {} entries saved", snapshot.entries.len()),
+            Err(e) => format!("Snapshot failed: {:#}", e),
+        });
+    }
+
+    fn compare_to_snapshot(&mut self) {
+        let result = Snapshot::path_for(&self.current_dir).and_then(|path| {
+            let Some(snapshot) = Snapshot::load(&path)? else {
+                return Ok(None);
+            };
+            let current = Snapshot::capture(&self.current_dir, snapshot.hashed, true)?;
+            let changes = snapshot.compare(&current);
+            Ok(Some(SnapshotReport::new(
+                &snapshot,
+                &current,
+                changes,
+                &self.time_format,
+            )))
+        });
+
+        match result {
+            Ok(Some(report)) => {
+                self.status_message = Some(report.summary());
+                self.snapshot_report = Some(report);
+                self.mode = NavigatorMode::SnapshotReport;
+            }
+            Ok(None) => {
+                self.status_message =
+                    Some("No snapshot of this directory yet; take one first".to_string());
+            }
+            Err(e) => self.status_message = Some(format!("Compare failed: {:#}", e)),
+        }
+    }
+
+    fn toggle_pin(&mut self) -> Result<()> {
+        let Some(entry) = self
+            .entries
+            .get(self.selected_index)
+            .filter(|e| e.name != ".." && e.is_accessible)
+        else {
+            return Ok(());
+        };
+        let name = entry.name.clone();
+
+        self.status_message = Some(
+            match self.dir_settings.toggle_pin(&self.current_dir, &name) {
+                Ok(true) => format!("📌 Pinned {}", name),
+                Ok(false) => format!("Unpinned {}", name),
+                Err(e) => format!("Failed to save pins: {}", e),
+            },
+        );
+        self.refresh_directory()
+    }
+
+    fn handle_finder_input(
+        &mut self,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+    ) -> Result<Option<ExitAction>> {
+        let Some(ref mut finder) = self.fuzzy_finder else {
+            self.mode = NavigatorMode::Browse;
+            return Ok(None);
+        };
+
+        match finder.handle_input(code, modifiers) {
+            FinderOutcome::Continue => {}
+            FinderOutcome::Cancel => {
+                self.fuzzy_finder = None;
+                self.mode = NavigatorMode::Browse;
+            }
+            FinderOutcome::Jump(path) => {
+                self.fuzzy_finder = None;
+                self.mode = NavigatorMode::Browse;
+                self.jump_to_path(&path)?;
+            }
+        }
+        Ok(None)
+    }
+
+    /// Enter a directory, or open a file's parent with the file highlighted
+    fn jump_to_path(&mut self, path: &Path) -> Result<()> {
+        self.clear_preview();
+        if path.is_dir() {
+            return self.load_directory(path);
+        }
+
+        let Some(parent) = path.parent() else {
+            return Ok(());
+        };
+        self.load_directory_at(parent, Some(path.to_path_buf()), None);
+        Ok(())
+    }
+
+    fn custom_command_for_key(&self, code: KeyCode) -> Option<usize> {
+        let KeyCode::Char(c) = code else {
+            return None;
+        };
+        self.custom_commands
+            .iter()
+            .position(|command| command.key == Some(c))
+    }
+
+    fn run_custom_command(&mut self, index: usize) -> Result<()> {
+        match self.custom_commands.get(index).cloned() {
+            Some(command) => self.run_command(command),
+            None => Ok(()),
+        }
+    }
+
+    /// Hex view of the highlighted file; editing follows the profile
+    fn open_hex_editor(&mut self) {
+        let Some(entry) = self.entries.get(self.selected_index).filter(|e| !e.is_dir) else {
+            self.status_message = Some("⚠️  The hex view works on files".to_string());
+            return;
+        };
+        let editable = self.config.profile == Profile::Admin;
+        match HexEditor::open(&entry.path, editable) {
+            Ok(editor) => {
+                self.hex_editor = Some(editor);
+                self.mode = NavigatorMode::HexEditor;
+            }
+            Err(e) => self.status_message = Some(format!("⚠️  {:#}", e)),
+        }
+    }
+
+    /// The highlighted file, full screen, with scrolling and search
+    fn open_pager(&mut self) {
+        let Some(entry) = self.entries.get(self.selected_index).filter(|e| !e.is_dir) else {
+            self.status_message = Some("⚠️  The pager works on files".to_string());
+            return;
+        };
+        self.pager = Some(Pager::open(&entry.path, &self.listing_options));
+        self.mode = NavigatorMode::Pager;
+    }
+
+    /// Diff of the two selected files, the first in listing order as the
+    /// old side
+    fn open_diff(&mut self) {
+        let mut selected: Vec<usize> = self.selected_items.iter().copied().collect();
+        selected.sort_unstable();
+        let files: Vec<&FileEntry> = selected
+            .iter()
+            .filter_map(|&i| self.entries.get(i))
+            .filter(|e| !e.is_dir)
+            .collect();
+        match files[..] {
+            [old, new] if selected.len() == 2 => {
+                self.diff_view = Some(DiffView::open(&old.path, &new.path));
+                self.mode = NavigatorMode::Diff;
+            }
+            _ => {
+                self.status_message = Some(
+                    "⚠️  Select exactly two files to compare, or one in each split pane (F2, d)"
+                        .to_string(),
+                );
+            }
+        }
+    }
+
+    fn open_container_volumes(&mut self) {
+        let Some(program) = container_runtime() else {
+            self.status_message = Some("⚠️  Neither docker nor podman is installed".to_string());
+            return;
+        };
+        match ContainerVolumes::new(&program) {
+            Ok(containers) => {
+                self.containers = Some(containers);
+                self.mode = NavigatorMode::Containers;
+            }
+            Err(e) => self.status_message = Some(format!("⚠️  {:#}", e)),
+        }
+    }
+
+    /// `systemctl status` of the highlighted unit file, in the command output view
+    fn show_unit_status(&mut self) -> Result<()> {
+        let command = self
+            .entries
+            .get(self.selected_index)
+            .filter(|e| !e.is_dir && systemd::is_unit_file(&e.path))
+            .and_then(|e| systemd::status_command(&e.path));
+        match command {
+            Some(command) => self.run_command(command),
+            None => {
+                self.status_message = Some("⚠️  Not a systemd unit file".to_string());
+                Ok(())
+            }
+        }
+    }
+
+    /// `command` with the highlighted entry, the selection and the current
+    /// directory filled in
+    fn expand_command(&self, command: &CustomCommand) -> String {
+        let file = self
+            .entries
+            .get(self.selected_index)
+            .filter(|e| e.name != "..")
+            .map(|e| e.path.clone());
+        command.expand(
+            file.as_deref(),
+            &self.get_selected_paths(),
+            &self.current_dir,
+        )
+    }
+
+    /// Run a command with the TUI suspended and show what it printed
+    fn run_command(&mut self, command: CustomCommand) -> Result<()> {
+        let command_line = self.expand_command(&command);
+
+        let dir = self.current_dir.clone();
+        match with_suspended_tui(self.mouse_captured, || command.run(&command_line, &dir))? {
+            Ok(output) => {
+                self.record_step(Step::Command {
+                    name: command.name.clone(),
+                    command: command.template.clone(),
+                });
+                self.status_message = Some(if output.succeeded() {
+                    format!("✓ {} finished", command.name)
+                } else {
+                    format!("⚠️  {} failed", command.name)
+                });
+                self.command_output = Some(output);
+                self.mode = NavigatorMode::CommandOutput;
+            }
+            Err(e) => {
+                self.status_message = Some(format!("Failed to run {}: {}", command.name, e));
+            }
+        }
+
+        // The command may have created, moved or deleted files
+        self.clear_preview();
+        self.refresh_directory()
+    }
+
+    fn open_quick_view(&mut self) {
+        let Some(entry) = self.entries.get(self.selected_index) else {
+            return;
+        };
+        if !entry.is_accessible {
+            return;
+        }
+
+        match QuickView::new(&entry.path, &self.listing_options, &self.time_format) {
+            Ok(quick_view) => {
+                self.quick_view = Some(quick_view);
+                self.mode = NavigatorMode::QuickView;
+            }
+            Err(e) => self.status_message = Some(format!("Cannot preview: {}", e)),
+        }
+    }
+
+    fn open_with_menu_for_selected(&mut self) {
+        let Some(entry) = self.entries.get(self.selected_index) else {
+            return;
+        };
+        if entry.is_dir || !entry.is_accessible {
+            self.status_message = Some("Select a file to choose an application".to_string());
+            return;
+        }
+
+        self.open_with_menu = Some(OpenWithMenu::new(
+            entry.path.clone(),
+            &self.config,
+            &self.associations,
+        ));
+        self.mode = NavigatorMode::OpenWith;
+    }
+
+    fn enter_search_mode(&mut self) {
+        let ripgrep = if self.config.search.ripgrep {
+            ripgrep_program()
+        } else {
+            None
+        };
+        let roots = ScopeRoots {
+            current_dir: self.current_dir.clone(),
+            bookmarks: self
+                .bookmarks_manager
+                .list_bookmarks()
+                .iter()
+                .map(|bookmark| bookmark.path.clone())
+                .collect(),
+            root: self.config.search.root_dir(),
+            show_hidden: self.listing_options.show_hidden,
+            name_order: self.listing_options.name_order,
+        };
+        self.search_mode = Some(
+            SearchMode::new()
+                .with_excludes(self.exclude_rules())
+                .with_ripgrep(ripgrep)
+                .with_archives(self.config.search.archives)
+                .with_roots(roots),
+        );
+        self.mode = NavigatorMode::Search;
+    }
+
+    /// Configured exclusions for a search rooted at the current directory
+    fn exclude_rules(&self) -> ExcludeRules {
+        ExcludeRules::new(
+            &self.current_dir,
+            &self.config.search.exclude,
+            self.config.search.gitignore,
+        )
+    }
+
+    fn enter_split_pane_mode(&mut self) -> Result<()> {
+        let second_path = if let Some(parent) = self.current_dir.parent() {
+            parent.to_path_buf()
+        } else {
+            self.current_dir.clone()
+        };
+
+        self.split_pane_view = Some(SplitPaneView::new(
+            self.current_dir.clone(),
+            second_path,
+            self.listing_options.clone(),
+        )?);
+        self.mode = NavigatorMode::SplitPane;
+        Ok(())
+    }
+
+    /// Split view with the current directory on the left and the
+    /// highlighted directory on the right
+    fn open_in_split(&mut self) -> Result<()> {
+        let Some(dir) = self
+            .entries
+            .get(self.selected_index)
+            .filter(|entry| entry.is_dir && entry.is_accessible)
+            .map(|entry| entry.path.clone())
+        else {
+            self.status_message = Some("Not a directory".to_string());
+            return Ok(());
+        };
+        self.split_pane_view = Some(SplitPaneView::new(
+            self.current_dir.clone(),
+            dir,
+            self.listing_options.clone(),
+        )?);
+        self.mode = NavigatorMode::SplitPane;
+        Ok(())
+    }
+
+    fn toggle_preview_panel(&mut self) {
+        self.show_preview_panel = !self.show_preview_panel;
+        // Shown again, the panel loads the highlighted entry afresh
+        self.clear_preview();
+        if !self.show_preview_panel {
+            self.preview_focused = false;
+        }
+    }
+
+    /// Drop the preview, any load in flight and the cache, so the next
+    /// frame loads the highlighted entry again
+    fn clear_preview(&mut self) {
+        self.file_preview = None;
+        self.preview_loader = None;
+        // Directory previews also depend on the listing options
+        self.preview_cache.clear();
+    }
+
+    /// Start loading the highlighted entry's preview unless it is shown or
+    /// on its way
+    fn request_preview(&mut self) {
+        if let Some(path) = self
+            .entries
+            .get(self.selected_index)
+            .map(|e| e.path.clone())
+        {
+            self.request_preview_of(&path);
+        }
+    }
+
+    /// Start loading the preview of `path` unless it is shown or on its way
+    fn request_preview_of(&mut self, path: &Path) {
+        let shown = self
+            .file_preview
+            .as_ref()
+            .is_some_and(|preview| preview.path == path);
+        let requested = self
+            .preview_loader
+            .as_ref()
+            .is_some_and(|loader| loader.path == path);
+        if shown || requested {
+            return;
+        }
+        // Dropping or replacing the loader cancels the previous one; a
+        // cached preview still has to be checked against the file by it
+        let cached = self.preview_cache.get(path);
+        let mut loader =
+            PreviewLoader::spawn_reusing(path, PREVIEW_LINES, &self.listing_options, cached);
+        // Quick files show at once instead of flashing the placeholder
+        self.file_preview = loader.wait(PREVIEW_GRACE).flatten();
+        self.preview_loader = Some(loader);
+        if let Some(ref preview) = self.file_preview {
+            self.preview_cache.insert(preview);
+        }
+    }
+
+    /// Pick up a preview finished on the background thread
+    fn poll_preview(&mut self) {
+        if let Some(preview) = self.preview_loader.as_mut().and_then(|l| l.poll()) {
+            if let Some(ref preview) = preview {
+                self.preview_cache.insert(preview);
+            }
+            self.file_preview = preview;
+        }
+    }
+
+    fn show_goto_dialog(&mut self) -> Result<()> {
+        // Quick bookmark jump - show numbered list
+        self.mode = NavigatorMode::Bookmarks;
+        self.bookmark_health
+            .check(self.bookmarks_manager.list_bookmarks());
+        Ok(())
+    }
+
+    /// Pick up results from a background search, jumping to the first one
+    fn poll_search(&mut self) {
+        let Some(ref mut search) = self.search_mode else {
+            return;
+        };
+        let had_results = !search.results.is_empty();
+        if search.poll() > 0 && !had_results {
+            self.jump_to_search_result();
+        }
+    }
+
+    fn jump_to_search_result(&mut self) {
+        let Some(result) = self
+            .search_mode
+            .as_ref()
+            .and_then(|search| search.get_current_result())
+        else {
+            return;
+        };
+        let path = result.entry.path.clone();
+        let line_number = result.line_number;
+        let archive_entry = result.archive_entry.clone();
+        let archive_match = archive_entry.is_some().then(|| {
+            format!(
+                "{}: {}",
+                result.location(),
+                result.match_context.clone().unwrap_or_default()
+            )
+        });
+
+        // Find the entry in our list, or go to where a locate result lives
+        if let Some(index) = self.entries.iter().position(|e| e.path == path) {
+            self.selected_index = index;
+            self.adjust_scroll();
+        } else if let Err(e) = self.jump_to_path(&path) {
+            self.status_message = Some(format!("Cannot open {}: {}", path.display(), e));
+            return;
+        }
+
+        // Content matches open the preview at the matching line
+        if let Some(line) = line_number {
+            let mut preview = match archive_entry {
+                Some(ref inner) => {
+                    FilePreview::for_archive_entry(&path, inner, line + PREVIEW_LINES).ok()
+                }
+                None => FilePreview::new(&path, line + PREVIEW_LINES, &self.listing_options).ok(),
+            };
+            if archive_match.is_some() {
+                self.status_message = archive_match;
+            }
+            if let (Some(preview), Some(search)) = (&mut preview, &self.search_mode) {
+                preview.focus_line(line, |text| search.match_ranges(text));
+            }
+            self.preview_loader = None;
+            self.file_preview = preview;
+            self.show_preview_panel = true;
+        } else if self
+            .file_preview
+            .as_ref()
+            .is_some_and(|preview| preview.highlight.is_some())
+        {
+            self.clear_preview();
+        }
+    }
+
+    fn load_directory(&mut self, path: &Path) -> Result<()> {
+        self.load_directory_at(path, None, None);
+        Ok(())
+    }
+
+    /// Show `path`, read on a worker, highlighting `select` and narrowing
+    /// to `filter` once its entries are in
+    fn load_directory_at(&mut self, path: &Path, select: Option<PathBuf>, filter: Option<String>) {
+        self.entries.clear();
+        self.unfiltered_entries.clear();
+        self.filter = None;
+        self.selected_index = 0;
+        self.selected_items.clear();
+        // Reloads follow changes on disk, so sizes are measured again
+        self.selection_size.clear();
+        self.scroll_offset = 0;
+
+        // Add parent directory entry if not at root
+        if let Some(parent) = path.parent() {
+            if parent != path {
+                self.entries.push(FileEntry::parent_link(parent));
+            }
+        }
+
+        self.readme = None;
+        self.real_dir = None;
+        self.hot_dirs.mark_seen(path);
+        // Markers belong to the directory verified; reloading it keeps them
+        if self.checksums.as_ref().is_some_and(|run| run.dir != path) {
+            self.checksums = None;
+        }
+        // Reloads of the same directory are not visits
+        if path != self.current_dir {
+            let _ = self.dir_visits.record(path);
+        }
+        self.current_dir = path.to_path_buf();
+
+        let worker_path = path.to_path_buf();
+        let options = self.listing_options.clone();
+        let readme_lines = self.config.preview.readme_lines;
+        let mut task = Task::spawn(move |_| {
+            (
+                read_directory(&worker_path, &options),
+                ReadmeStrip::load(&worker_path, readme_lines),
+                fs::canonicalize(&worker_path).ok(),
+            )
+        });
+        // Quick directories show at once instead of flashing the placeholder
+        let result = task.wait(LISTING_GRACE);
+        self.pending_listing = Some(PendingListing {
+            task,
+            select,
+            filter,
+            reselect: Vec::new(),
+        });
+        if let Some(result) = result {
+            self.apply_listing(result);
+        }
+    }
+
+    /// Pick up a directory listing finished on the background thread
+    fn poll_listing(&mut self) {
+        if let Some(result) = self.pending_listing.as_mut().and_then(|p| p.task.poll()) {
+            self.apply_listing(result);
+        }
+    }
+
+    fn apply_listing(
+        &mut self,
+        result: (
+            io::Result<DirectoryListing>,
+            Option<ReadmeStrip>,
+            Option<PathBuf>,
+        ),
+    ) {
+        let Some(pending) = self.pending_listing.take() else {
+            return;
+        };
+        let (listing, readme, real_dir) = result;
+        self.real_dir = real_dir.filter(|real| *real != self.current_dir);
+        match listing {
+            Ok(mut listing) => {
+                pin_entries(
+                    &mut listing.entries,
+                    self.dir_settings.pinned(&self.current_dir),
+                );
+                self.entries.extend(listing.entries);
+            }
+            // If directory is not accessible, show error but don't crash
+            Err(e) => self.entries.push(FileEntry::error(&self.current_dir, &e)),
+        }
+        self.readme = readme;
+
+        if pending.filter.is_some() {
+            self.set_filter(pending.filter);
+        }
+        if let Some(path) = pending.select {
+            if let Some(index) = self.entries.iter().position(|e| e.path == path) {
+                self.selected_index = index;
+                self.adjust_scroll();
+            }
+        }
+        self.select_paths(&pending.reselect);
+    }
+
+    fn select_paths(&mut self, paths: &[PathBuf]) {
+        for (i, entry) in self.entries.iter().enumerate() {
+            if entry.name != ".." && paths.contains(&entry.path) {
+                self.selected_items.insert(i);
+            }
+        }
+    }
+
+    /// Handle a key while a type-to-filter is active; false lets the normal
+    /// browse bindings see it
+    /// Pattern typed at the preview's `/` prompt and how many matches it has
+    fn preview_search_prompt(&self) -> Option<(&str, usize)> {
+        let pattern = self.preview_search_input.as_deref()?;
+        let found = self
+            .file_preview
+            .as_ref()
+            .and_then(|preview| preview.search.as_ref())
+            .map_or(0, |search| search.matches.len());
+        Some((pattern, found))
+    }
+
+    /// Footer hints that do not fit take turns in its last slot
+    fn hint_turn(&self) -> usize {
+        (self.started.elapsed().as_secs() / HINT_PERIOD.as_secs()) as usize
+    }
+
+    /// Typing at the `/` prompt of the focused preview, which searches as
+    /// the pattern grows
+    fn handle_preview_search_input(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        let Some(mut pattern) = self.preview_search_input.take() else {
+            return;
+        };
+        let Some(ref mut preview) = self.file_preview else {
+            return;
+        };
+
+        match code {
+            KeyCode::Char(c)
+                if !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                pattern.push(c);
+                preview.search(&pattern);
+            }
+            KeyCode::Backspace => {
+                pattern.pop();
+                preview.search(&pattern);
+            }
+            KeyCode::Enter => {
+                let found = preview.search.as_ref().map_or(0, |s| s.matches.len());
+                if pattern.is_empty() {
+                    preview.cancel_search();
+                } else if found == 0 {
+                    self.status_message = Some(format!("No matches for '{}'", pattern));
+                } else {
+                    self.status_message = Some(format!(
+                        "{} match{} (n/N: next/previous)",
+                        found,
+                        if found == 1 { "" } else { "es" }
+                    ));
+                }
+                return;
+            }
+            KeyCode::Esc => {
+                preview.cancel_search();
+                return;
+            }
+            _ => {}
+        }
+        self.preview_search_input = Some(pattern);
+    }
+
+    /// Typing at the `g` prompt of a focused hex preview
+    fn handle_preview_goto_input(&mut self, code: KeyCode) {
+        let Some(mut input) = self.preview_goto_input.take() else {
+            return;
+        };
+        let Some(ref mut preview) = self.file_preview else {
+            return;
+        };
+
+        match code {
+            KeyCode::Char(c) if c.is_ascii_hexdigit() || c == 'x' || c == 'X' => input.push(c),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Enter => {
+                match parse_offset(&input) {
+                    Some(offset) if preview.seek_hex(offset) => {}
+                    Some(_) => {
+                        self.status_message =
+                            Some(format!("The file is {} bytes long", preview.hex_len()))
+                    }
+                    None => self.status_message = Some(format!("Not an offset: {}", input)),
+                }
+                return;
+            }
+            KeyCode::Esc => return,
+            _ => {}
+        }
+        self.preview_goto_input = Some(input);
+    }
+
+    fn handle_filter_input(&mut self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        let Some(mut filter) = self.filter.clone() else {
+            return false;
+        };
+
+        match code {
+            KeyCode::Char(c)
+                if !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                filter.push(c);
+                self.set_filter(Some(filter));
+            }
+            KeyCode::Backspace => {
+                // Deleting past the start closes the filter
+                if filter.pop().is_some() {
+                    self.set_filter(Some(filter));
+                } else {
+                    self.set_filter(None);
+                }
+            }
+            KeyCode::Esc => self.set_filter(None),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Narrow the listing to names containing `filter` (case-insensitive),
+    /// or restore the full listing with `None`, keeping the cursor on the
+    /// same entry when it is still visible
+    fn set_filter(&mut self, filter: Option<String>) {
+        if self.filter.is_none() {
+            self.unfiltered_entries = self.entries.clone();
+        }
+        let selected_path = self
+            .entries
+            .get(self.selected_index)
+            .map(|e| e.path.clone());
+
+        self.entries = match filter.as_deref() {
+            Some(text) if !text.is_empty() => filter_entries(&self.unfiltered_entries, text),
+            _ => self.unfiltered_entries.clone(),
+        };
+        if filter.is_none() {
+            self.unfiltered_entries.clear();
+        }
+        self.filter = filter;
+
+        // Indices into the old listing no longer apply
+        self.selected_items.clear();
+        self.clear_preview();
+        self.selected_index = selected_path
+            .and_then(|path| self.entries.iter().position(|e| e.path == path))
+            .or_else(|| self.entries.iter().position(|e| e.name != ".."))
+            .unwrap_or(0);
+        self.scroll_offset = 0;
+        self.adjust_scroll();
+    }
+
+    /// Narrow the listing to files ending in `.extension`
+    fn filter_by_extension(&mut self, extension: &str) {
+        self.set_filter(Some(format!("*.{}", extension)));
+    }
+
+    fn set_sort_key(&mut self, sort_key: SortKey) -> Result<()> {
+        self.listing_options.sort_key = sort_key;
+        self.clear_preview();
+        self.refresh_directory()?;
+        self.status_message = Some(format!(
+            "Sorted by {}",
+            self.listing_options.sort_key.label()
+        ));
+        Ok(())
+    }
+
+    fn open_session_picker(&mut self) {
+        let previous_mode = std::mem::replace(&mut self.mode, NavigatorMode::Sessions);
+        self.session_picker = Some((SessionPicker::new(), previous_mode));
+    }
+
+    fn handle_session_input(
+        &mut self,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+    ) -> Result<Option<ExitAction>> {
+        let Some((ref mut picker, _)) = self.session_picker else {
+            self.mode = NavigatorMode::Browse;
+            return Ok(None);
+        };
+
+        match picker.handle_input(code, modifiers, &mut self.sessions) {
+            SessionOutcome::Continue => {}
+            SessionOutcome::Close(message) => {
+                if message.is_some() {
+                    self.status_message = message;
+                }
+                self.close_session_picker();
+            }
+            SessionOutcome::Save(name) => {
+                self.close_session_picker();
+                let message = match self.sessions.save_as(&name, self.session()) {
+                    Ok(()) => format!("Saved session '{}'", name),
+                    Err(e) => format!("Failed to save session: {}", e),
+                };
+                match self.split_pane_view {
+                    Some(ref mut split) if self.mode == NavigatorMode::SplitPane => {
+                        split.status = Some(message)
+                    }
+                    _ => self.status_message = Some(message),
+                }
+            }
+            SessionOutcome::Restore(session) => {
+                self.session_picker = None;
+                self.restore_session(session);
             }
-            _ => {}
         }
         Ok(None)
     }
 
-    fn enter_search_mode(&mut self) {
-        self.search_mode = Some(SearchMode::new());
-        self.mode = NavigatorMode::Search;
+    fn close_session_picker(&mut self) {
+        if let Some((_, previous_mode)) = self.session_picker.take() {
+            self.mode = previous_mode;
+        }
     }
 
-    fn enter_split_pane_mode(&mut self) -> Result<()> {
-        let second_path = if let Some(parent) = self.current_dir.parent() {
-            parent.to_path_buf()
-        } else {
-            self.current_dir.clone()
-        };
-
-        self.split_pane_view = Some(SplitPaneView::new(self.current_dir.clone(), second_path)?);
-        self.mode = NavigatorMode::SplitPane;
-        Ok(())
+    /// The current directory, cursor, selection, listing options and split
+    /// panes, to save as a named session
+    fn session(&self) -> Session {
+        let mut selected: Vec<usize> = self.selected_items.iter().copied().collect();
+        selected.sort_unstable();
+        let split = self
+            .split_pane_view
+            .as_ref()
+            .filter(|_| self.mode == NavigatorMode::SplitPane)
+            .map(|split| SplitSession {
+                left: split.panes[0].current_dir.clone(),
+                right: split.panes[1].current_dir.clone(),
+                right_focused: split.focus == 1,
+                vertical: split.layout != Layout::Rows,
+                ratio: split.split_ratio,
+                more: split.panes[2..]
+                    .iter()
+                    .map(|pane| pane.current_dir.clone())
+                    .collect(),
+                focused: Some(split.focus),
+                grid: split.layout == Layout::Grid,
+            });
+        Session {
+            dir: self.current_dir.clone(),
+            highlighted: self
+                .entries
+                .get(self.selected_index)
+                .filter(|e| e.name != "..")
+                .map(|e| e.path.clone()),
+            selected: selected
+                .iter()
+                .filter_map(|&i| self.entries.get(i))
+                .map(|e| e.path.clone())
+                .collect(),
+            sort_key: self.listing_options.sort_key,
+            grouping: self.listing_options.grouping,
+            show_hidden: self.listing_options.show_hidden,
+            show_preview: self.show_preview_panel,
+            split,
+        }
     }
 
-    fn toggle_preview_panel(&mut self) {
-        self.show_preview_panel = !self.show_preview_panel;
-        if self.show_preview_panel {
-            // Load preview for current selection only if it's not a directory
-            if let Some(entry) = self.entries.get(self.selected_index) {
-                if !entry.is_dir {
-                    self.file_preview = FilePreview::new(&entry.path, 50).ok();
-                } else {
-                    self.file_preview = None;
+    /// Go back to a saved session; entries deleted since are left out
+    pub fn restore_session(&mut self, session: Session) {
+        if !session.dir.is_dir() {
+            self.status_message = Some(format!("⚠️  {} no longer exists", session.dir.display()));
+            return;
+        }
+
+        self.listing_options.sort_key = session.sort_key;
+        self.listing_options.grouping = session.grouping;
+        self.listing_options.show_hidden = session.show_hidden;
+        self.show_preview_panel = session.show_preview;
+        self.preview_focused = false;
+        self.clear_preview();
+        self.load_directory_at(&session.dir, session.highlighted, None);
+        match self.pending_listing {
+            Some(ref mut pending) => pending.reselect = session.selected,
+            None => self.select_paths(&session.selected),
+        }
+
+        self.split_pane_view = None;
+        self.mode = NavigatorMode::Browse;
+        if let Some(saved) = session.split {
+            let restored =
+                SplitPaneView::new(saved.left, saved.right, self.listing_options.clone()).and_then(
+                    |mut split| {
+                        for dir in saved.more.into_iter().take(MAX_PANES - 2) {
+                            split
+                                .panes
+                                .push(Pane::new(dir, self.listing_options.clone())?);
+                        }
+                        Ok(split)
+                    },
+                );
+            match restored {
+                Ok(mut split) => {
+                    let focused = saved.focused.unwrap_or(saved.right_focused as usize);
+                    split.focus = focused.min(split.panes.len() - 1);
+                    split.layout = if saved.grid && split.panes.len() > 2 {
+                        Layout::Grid
+                    } else if saved.vertical {
+                        Layout::Columns
+                    } else {
+                        Layout::Rows
+                    };
+                    split.split_ratio = saved.ratio.clamp(0.2, 0.8);
+                    self.split_pane_view = Some(split);
+                    self.mode = NavigatorMode::SplitPane;
+                }
+                Err(e) => {
+                    self.status_message = Some(format!("⚠️  Split panes not restored: {}", e))
                 }
             }
+        }
+    }
+
+    /// Re-open the current directory at its canonical path, so `..` leads
+    /// to its real parent
+    fn go_to_real_path(&mut self) {
+        let Some(real_dir) = self.real_dir.clone() else {
+            self.status_message =
+                Some("This directory is not reached through a symbolic link".to_string());
+            return;
+        };
+        let select = self
+            .entries
+            .get(self.selected_index)
+            .filter(|e| e.name != "..")
+            .map(|e| real_dir.join(&e.name));
+        self.clear_preview();
+        self.load_directory_at(&real_dir, select, None);
+        self.status_message = Some(format!("Now at {}", real_dir.display()));
+    }
+
+    /// Verify the checksum files of the current directory in the
+    /// background, showing the report; a run already made here is shown
+    /// again
+    fn verify_checksums(&mut self) {
+        if self
+            .checksums
+            .as_ref()
+            .is_some_and(|run| run.dir == self.current_dir)
+        {
+            self.mode = NavigatorMode::Checksums;
+            return;
+        }
+        let entries = if self.filter.is_some() {
+            &self.unfiltered_entries
         } else {
-            self.file_preview = None;
-            self.preview_focused = false;
+            &self.entries
+        };
+        let files: Vec<PathBuf> = entries
+            .iter()
+            .filter(|e| !e.is_dir && Algorithm::of_checksum_file(&e.name).is_some())
+            .map(|e| e.path.clone())
+            .collect();
+        if files.is_empty() {
+            self.status_message =
+                Some("No checksum files here (.sha256, .md5, SHA256SUMS…)".to_string());
+            return;
+        }
+        let run = ChecksumRun::start(&self.current_dir, &files);
+        if run.is_empty() {
+            self.status_message = Some(format!(
+                "No digests found in {} checksum file{}",
+                files.len(),
+                if files.len() == 1 { "" } else { "s" }
+            ));
+            return;
         }
+        self.checksums = Some(run);
+        self.mode = NavigatorMode::Checksums;
     }
 
-    fn show_goto_dialog(&mut self) -> Result<()> {
-        // Quick bookmark jump - show numbered list
-        self.mode = NavigatorMode::Bookmarks;
+    /// Announce files that appeared in hot directories
+    fn notify_hot_arrivals(&mut self) {
+        let arrived = self.hot_dirs.poll();
+        let Some(newest) = arrived.last() else {
+            return;
+        };
+        self.status_message = Some(if arrived.len() == 1 {
+            format!(
+                "🔥 New in {}: {} (N: jump to it)",
+                newest.parent().unwrap_or(newest).display(),
+                newest
+                    .file_name()
+                    .map(|name| name.to_string_lossy())
+                    .unwrap_or_default()
+            )
+        } else {
+            format!(
+                "🔥 {} new files in hot directories (N: jump to the newest)",
+                arrived.len()
+            )
+        });
+    }
+
+    /// Open the hot directory that got the latest file, on that file
+    fn jump_to_hot_file(&mut self) {
+        let Some(path) = self.hot_dirs.newest().map(Path::to_path_buf) else {
+            self.status_message = Some(if self.bookmarks_manager.hot_dirs().is_empty() {
+                "No hot directories: Ctrl+W on a bookmark watches it".to_string()
+            } else {
+                "No new files in hot directories".to_string()
+            });
+            return;
+        };
+        let Some(dir) = path.parent().map(Path::to_path_buf) else {
+            return;
+        };
+        self.clear_preview();
+        self.load_directory_at(&dir, Some(path.clone()), None);
+        self.status_message = Some(format!("🔥 {}", path.display()));
+    }
+
+    fn toggle_hidden(&mut self) -> Result<()> {
+        self.listing_options.show_hidden = !self.listing_options.show_hidden;
+        self.clear_preview();
+        self.refresh_directory()?;
+        self.status_message = Some(if self.listing_options.show_hidden {
+            "Showing hidden files".to_string()
+        } else {
+            "Hiding hidden files".to_string()
+        });
         Ok(())
     }
 
-    fn jump_to_search_result(&mut self) {
-        if let Some(ref search) = self.search_mode {
-            if let Some(result) = search.get_current_result() {
-                // Find the entry in our list
-                if let Some(index) = self
-                    .entries
-                    .iter()
-                    .position(|e| e.path == result.entry.path)
-                {
-                    self.selected_index = index;
-                    self.adjust_scroll();
-                }
-            }
+    /// Apply the Ctrl+R form to bookmark `index`; an unchanged field is
+    /// left alone
+    fn save_bookmark_edit(&mut self, index: usize) -> Result<()> {
+        let Some(edit) = self.bookmark_edit.take() else {
+            return Ok(());
+        };
+        let bookmark = &self.bookmarks_manager.list_bookmarks()[index];
+        let name = edit.name.trim().to_string();
+        let path = expand_home(edit.path.trim());
+        let rename = name != bookmark.name;
+        let moved = path != bookmark.path;
+
+        if name.is_empty() {
+            return Err(anyhow::anyhow!("The name cannot be empty"));
+        }
+        if moved {
+            self.bookmarks_manager.set_path(index, path)?;
+            self.bookmark_health
+                .check(self.bookmarks_manager.list_bookmarks());
         }
+        if rename {
+            self.bookmarks_manager.rename_bookmark(index, name)?;
+        }
+        Ok(())
     }
 
-    fn load_directory(&mut self, path: &Path) -> Result<()> {
-        self.entries.clear();
-        self.selected_index = 0;
-        self.selected_items.clear();
-        self.scroll_offset = 0;
+    /// Swap the bookmark at `selected` in the list with the one above or
+    /// below it, staying within its category
+    fn move_bookmark(&mut self, selected: usize, matches: &[usize], up: bool) {
+        let Some(&index) = matches.get(selected) else {
+            return;
+        };
+        // Search results are ordered by score, not by hand
+        if !groups_by_category(&self.bookmark_filter) {
+            self.status_message = Some("Clear the search to reorder bookmarks".to_string());
+            return;
+        }
+        let bookmarks = self.bookmarks_manager.list_bookmarks();
+        let category = bookmarks[index].category();
+        let target = if up {
+            selected.checked_sub(1)
+        } else {
+            Some(selected + 1)
+        }
+        .filter(|&target| {
+            matches
+                .get(target)
+                .is_some_and(|&other| bookmarks[other].category() == category)
+        });
+        let Some(target) = target else {
+            self.status_message = Some(format!(
+                "Already at the {} of {}",
+                if up { "top" } else { "bottom" },
+                category.map_or("the untagged bookmarks".to_string(), |c| format!("#{}", c))
+            ));
+            return;
+        };
+        match self.bookmarks_manager.swap(index, matches[target]) {
+            Ok(()) => self.bookmark_selected_index = Some(target),
+            Err(e) => self.status_message = Some(format!("Failed to move bookmark: {}", e)),
+        }
+    }
 
-        // Add parent directory entry if not at root
-        if let Some(parent) = path.parent() {
-            if parent != path {
-                self.entries.push(FileEntry {
-                    name: "..".to_string(),
-                    path: parent.to_path_buf(),
-                    is_dir: true,
-                    is_accessible: true,
-                    is_symlink: false,
-                    permissions: None,
-                    owner: None,
-                    group: None,
-                    uid: None,
-                    gid: None,
-                });
+    fn prune_dead_bookmarks(&mut self, confirmed: bool) {
+        if confirmed {
+            self.status_message = Some(
+                match self.bookmarks_manager.prune_dead(&self.bookmark_health) {
+                    Ok(removed) => format!(
+                        "Removed {} dead bookmark{}",
+                        removed,
+                        if removed == 1 { "" } else { "s" }
+                    ),
+                    Err(e) => format!("Failed to remove dead bookmarks: {}", e),
+                },
+            );
+            self.bookmark_selected_index = Some(0);
+            return;
+        }
+
+        let dead = self.bookmarks_manager.dead_bookmarks(&self.bookmark_health);
+        self.status_message = Some(if dead.is_empty() {
+            if self.bookmark_health.is_checking() {
+                "No dead bookmarks so far, still checking".to_string()
+            } else {
+                "No dead bookmarks".to_string()
             }
+        } else {
+            self.bookmark_prune_pending = true;
+            let names: Vec<&str> = dead.iter().map(|b| b.name.as_str()).collect();
+            format!(
+                "Remove {} dead bookmark{} ({})? Ctrl+X again to confirm",
+                dead.len(),
+                if dead.len() == 1 { "" } else { "s" },
+                ellipsize(&names.join(", "), 60)
+            )
+        });
+    }
+
+    fn bookmark_current_dir(&mut self) {
+        self.bookmark_dir(self.current_dir.clone());
+    }
+
+    /// Bookmark `dir` under its own name with the first free shortcut
+    fn bookmark_dir(&mut self, dir: PathBuf) {
+        let name = dir
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("Bookmark")
+            .to_string();
+
+        let available = self.bookmarks_manager.get_available_shortcuts();
+        let shortcut = available.first().copied();
+
+        if let Err(e) = self.bookmarks_manager.add_bookmark(name, dir, shortcut) {
+            self.status_message = Some(format!("Failed to add bookmark: {}", e));
+        } else {
+            self.status_message = Some(format!(
+                "Bookmark added with shortcut '{}'!",
+                shortcut
+                    .map(|c| c.to_string())
+                    .unwrap_or_else(|| "none".to_string())
+            ));
         }
+    }
 
-        // Read directory entries
-        match fs::read_dir(path) {
-            Ok(read_dir) => {
-                let mut dir_entries = Vec::new();
-                let mut file_entries = Vec::new();
+    fn navigate_to_selected(&mut self) -> Result<()> {
+        if let Some(entry) = self.entries.get(self.selected_index) {
+            if entry.is_dir && entry.is_accessible {
+                let new_path = entry.path.clone();
+                self.load_directory(&new_path)?;
+            }
+        }
+        Ok(())
+    }
 
-                for entry in read_dir.flatten() {
-                    let path = entry.path();
-                    let metadata = entry.metadata();
-                    let symlink_metadata = entry.path().symlink_metadata();
+    /// Enter directories, open media and documents with the system handler,
+    /// and anything else in the user's editor
+    fn open_selected(&mut self) -> Result<()> {
+        let Some(entry) = self.entries.get(self.selected_index) else {
+            return Ok(());
+        };
 
-                    let is_symlink = symlink_metadata
-                        .as_ref()
-                        .map(|m| m.file_type().is_symlink())
-                        .unwrap_or(false);
+        if entry.is_dir {
+            return self.navigate_to_selected();
+        }
 
-                    let is_dir = metadata.as_ref().map(|m| m.is_dir()).unwrap_or(false);
-                    let is_accessible = metadata.is_ok();
+        if !entry.is_accessible {
+            return Ok(());
+        }
 
-                    let permissions = metadata.as_ref().ok().map(|m| {
-                        use std::os::unix::fs::PermissionsExt;
-                        m.permissions().mode()
-                    });
+        // The basic profile never hands the terminal to another program
+        if self.config.profile == Profile::Basic {
+            self.open_quick_view();
+            return Ok(());
+        }
 
-                    // Get owner and group info
-                    let (owner, group, uid, gid) = get_owner_group(&path);
+        let path = entry.path.clone();
+        if FilePreview::prefers_system_handler(&path) {
+            self.open_externally(&path);
+            Ok(())
+        } else {
+            self.open_in_editor(&path)
+        }
+    }
 
-                    let name = entry.file_name().to_string_lossy().to_string();
+    fn open_highlighted_externally(&mut self) {
+        if let Some(entry) = self.entries.get(self.selected_index) {
+            if entry.name != ".." {
+                let path = entry.path.clone();
+                self.open_externally(&path);
+            }
+        }
+    }
 
-                    // Skip hidden files on Unix-like systems
-                    #[cfg(unix)]
-                    if name.starts_with('.') && name != ".." {
-                        continue;
-                    }
+    fn open_externally(&mut self, path: &Path) {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
 
-                    let file_entry = FileEntry {
-                        name,
-                        path,
-                        is_dir,
-                        is_accessible,
-                        is_symlink,
-                        permissions,
-                        owner,
-                        group,
-                        uid,
-                        gid,
-                    };
+        self.status_message = Some(match open_with_system_handler(path) {
+            Ok(()) => format!("Opened {} with the default application", name),
+            Err(e) => format!("Failed to open {}: {}", name, e),
+        });
+    }
 
-                    if is_dir {
-                        dir_entries.push(file_entry);
-                    } else {
-                        file_entries.push(file_entry);
-                    }
-                }
+    fn open_in_editor(&mut self, path: &Path) -> Result<()> {
+        let (program, args) = editor_command();
 
-                // Sort directories and files separately
-                dir_entries.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-                file_entries.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+        let status = with_suspended_tui(self.mouse_captured, || {
+            std::process::Command::new(&program)
+                .args(&args)
+                .arg(path)
+                .current_dir(&self.current_dir)
+                .status()
+        })?;
 
-                // Add sorted entries (directories first)
-                self.entries.extend(dir_entries);
-                self.entries.extend(file_entries);
+        match status {
+            Ok(status) if !status.success() => {
+                self.status_message = Some(format!("{} exited with {}", program, status));
             }
+            Ok(_) => {}
             Err(e) => {
-                // If directory is not accessible, show error but don't crash
-                self.entries.push(FileEntry {
-                    name: format!("⚠️  Error: {}", e),
-                    path: path.to_path_buf(),
-                    is_dir: false,
-                    is_accessible: false,
-                    is_symlink: false,
-                    permissions: None,
-                    owner: None,
-                    group: None,
-                    uid: None,
-                    gid: None,
-                });
+                self.status_message = Some(format!("Failed to launch {}: {}", program, e));
             }
         }
 
-        self.current_dir = path.to_path_buf();
-        Ok(())
+        // The file may have changed size or been renamed by the editor
+        self.clear_preview();
+        self.refresh_directory()
     }
 
-    fn navigate_to_selected(&mut self) -> Result<()> {
-        if let Some(entry) = self.entries.get(self.selected_index) {
-            if entry.is_dir && entry.is_accessible {
-                let new_path = entry.path.clone();
-                self.load_directory(&new_path)?;
-            }
-        }
+    /// Reload the current directory, keeping the cursor on the same entry when possible
+    fn refresh_directory(&mut self) -> Result<()> {
+        let selected_path = self
+            .entries
+            .get(self.selected_index)
+            .map(|e| e.path.clone());
+        let filter = self.filter.take();
+        let current_dir = self.current_dir.clone();
+        self.load_directory_at(&current_dir, selected_path, filter);
         Ok(())
     }
 
@@ -1214,6 +4360,69 @@ impl Navigator {
         Ok(())
     }
 
+    /// Click, Ctrl+click and drag over the file list in selection mode;
+    /// the wheel moves the cursor
+    fn handle_mouse(&mut self, mouse: MouseEvent) -> Result<()> {
+        if self.mode == NavigatorMode::SplitPane {
+            if let Some(ref mut split) = self.split_pane_view {
+                let (width, height) = terminal::size()?;
+                split.handle_mouse(mouse, width, height);
+            }
+            return Ok(());
+        }
+        if self.mode != NavigatorMode::Select {
+            return Ok(());
+        }
+        // Beside the preview panel the list takes the left 60%
+        let list_width = if self.show_preview_panel {
+            (terminal::size()?.0 as f32 * 0.6) as u16
+        } else {
+            u16::MAX
+        };
+        let dragging = matches!(mouse.kind, MouseEventKind::Drag(MouseButton::Left));
+        let entry = list_entry_at(
+            mouse.row,
+            dragging,
+            self.scroll_offset,
+            self.terminal_height,
+            self.readme.as_ref(),
+        )
+        .filter(|&index| index < self.entries.len());
+
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) if mouse.column < list_width => {
+                let Some(index) = entry else {
+                    return Ok(());
+                };
+                let toggle = mouse.modifiers.contains(KeyModifiers::CONTROL);
+                let drag = DragSelect::start(index, toggle, &self.selected_items);
+                self.select_dragged(&drag, index);
+                self.mouse_drag = Some(drag);
+            }
+            MouseEventKind::Drag(MouseButton::Left) => {
+                if let (Some(drag), Some(index)) = (self.mouse_drag.take(), entry) {
+                    self.select_dragged(&drag, index);
+                    self.mouse_drag = Some(drag);
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => self.mouse_drag = None,
+            MouseEventKind::ScrollUp => self.move_selection_up(),
+            MouseEventKind::ScrollDown => self.move_selection_down(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Select what `drag` covers with the pointer over entry `index`, which
+    /// gets the cursor
+    fn select_dragged(&mut self, drag: &DragSelect, index: usize) {
+        let entries = &self.entries;
+        self.selected_items =
+            drag.selection(index, |i| entries.get(i).is_some_and(|e| e.name != ".."));
+        self.selected_index = index;
+        self.adjust_scroll();
+    }
+
     fn move_selection_up(&mut self) {
         if self.selected_index > 0 {
             self.selected_index -= 1;
@@ -1241,60 +4450,267 @@ impl Navigator {
         }
     }
 
-    fn select_by_pattern(&mut self) {
+    /// Select the entries matching the typed pattern; false leaves the
+    /// prompt open to fix an invalid regex
+    fn select_by_pattern(&mut self) -> bool {
         if self.pattern_input.is_empty() {
-            return;
+            return true;
         }
+        let pattern = match Pattern::new(&self.pattern_input, self.pattern_mode) {
+            Ok(pattern) => pattern,
+            Err(e) => {
+                self.status_message = Some(format!("⚠️  Invalid regex: {}", e));
+                return false;
+            }
+        };
 
-        self.selected_items.clear();
+        self.select_matching(&pattern);
+
+        self.status_message = Some(format!(
+            "Selected {} items matching {} '{}'",
+            self.selected_items.len(),
+            self.pattern_mode.label(),
+            self.pattern_input
+        ));
+        let pattern = std::mem::take(&mut self.pattern_input);
+        self.record_step(Step::Select {
+            pattern,
+            mode: self.pattern_mode,
+        });
+        true
+    }
 
+    /// Replace the selection with the entries whose name matches
+    fn select_matching(&mut self, pattern: &Pattern) {
+        self.selected_items.clear();
         for (i, entry) in self.entries.iter().enumerate() {
-            if entry.name != ".." && match_pattern(&self.pattern_input, &entry.name) {
+            if entry.name != ".." && pattern.is_match(&entry.name) {
                 self.selected_items.insert(i);
             }
         }
+    }
 
-        self.status_message = Some(format!(
-            "Selected {} items matching '{}'",
-            self.selected_items.len(),
-            self.pattern_input
-        ));
+    /// Start recording, or stop and name the steps recorded
+    fn toggle_workflow_recording(&mut self) {
+        match self.workflow_recording.take() {
+            None => {
+                self.workflow_recording = Some(Vec::new());
+                self.status_message = Some(
+                    "Recording: pattern selections, chmod, chown and custom commands become steps; w stops"
+                        .to_string(),
+                );
+            }
+            Some(steps) if steps.is_empty() => {
+                self.status_message = Some("Recording stopped, no steps recorded".to_string());
+            }
+            Some(steps) => {
+                self.workflow_view = Some(WorkflowView::naming(steps));
+                self.mode = NavigatorMode::Workflows;
+            }
+        }
+    }
 
-        self.pattern_input.clear();
+    fn record_step(&mut self, step: Step) {
+        if let Some(ref mut steps) = self.workflow_recording {
+            steps.push(step);
+        }
     }
 
-    fn open_chmod_interface(&mut self) {
-        if !self.is_root {
-            self.status_message = Some("⚠️  Chmod interface requires root privileges".to_string());
-            return;
+    fn handle_workflow_input(
+        &mut self,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+    ) -> Result<Option<ExitAction>> {
+        let Some(ref mut view) = self.workflow_view else {
+            self.mode = NavigatorMode::Browse;
+            return Ok(None);
+        };
+
+        match view.handle_input(code, modifiers, &mut self.workflows) {
+            WorkflowOutcome::Continue => {}
+            WorkflowOutcome::Close(message) => {
+                if message.is_some() {
+                    self.status_message = message;
+                }
+                self.close_workflow_view()?;
+            }
+            WorkflowOutcome::Run(step) => {
+                let description = step.describe();
+                let result = self.run_workflow_step(step);
+                let Some(ref view) = self.workflow_view else {
+                    return Ok(None);
+                };
+                let name = view.running().unwrap_or_default().to_string();
+                match result {
+                    Err(e) => {
+                        self.status_message = Some(format!(
+                            "⚠️  {} failed: {}; workflow '{}' stopped",
+                            description, e, name
+                        ));
+                        self.close_workflow_view()?;
+                    }
+                    Ok(()) if view.is_finished() => {
+                        self.status_message = Some(format!("✓ Workflow '{}' finished", name));
+                        self.close_workflow_view()?;
+                    }
+                    Ok(()) => self.status_message = Some(format!("✓ {}", description)),
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Back to the listing, reloaded if a replay may have changed it; a
+    /// failed command's output stays on screen
+    fn close_workflow_view(&mut self) -> Result<()> {
+        let replayed = self
+            .workflow_view
+            .take()
+            .is_some_and(|view| view.running().is_some());
+        if self.mode == NavigatorMode::Workflows {
+            self.mode = NavigatorMode::Browse;
+        }
+        if replayed {
+            self.clear_preview();
+            self.refresh_directory()?;
         }
+        Ok(())
+    }
+
+    /// Carry out one confirmed step on the current directory; the
+    /// selection a step makes is what the following steps work on
+    fn run_workflow_step(&mut self, step: Step) -> std::result::Result<(), String> {
+        // Chmod and chown report the files they were refused on
+        let needs_root = matches!(step, Step::Select { .. });
+        if needs_root && !self.is_root {
+            return Err("it needs root privileges".to_string());
+        }
+
+        match step {
+            Step::Select { pattern, mode } => {
+                let pattern = Pattern::new(&pattern, mode).map_err(|e| e.to_string())?;
+                self.select_matching(&pattern);
+                if self.selected_items.is_empty() {
+                    return Err("nothing matches here".to_string());
+                }
+            }
+            Step::Chmod {
+                mode,
+                files,
+                recursive,
+            } => {
+                set_mode(
+                    &self.workflow_targets()?,
+                    &mode,
+                    files.as_deref(),
+                    recursive,
+                )
+                .map_err(|e| e.to_string())?;
+            }
+            Step::Chown { owner, recursive } => {
+                chown_paths(&self.workflow_targets()?, &owner, recursive)
+                    .map_err(|e| e.to_string())?;
+            }
+            Step::Command { name, command } => {
+                let command = CustomCommand {
+                    name,
+                    template: command,
+                    key: None,
+                };
+                let command_line = self.expand_command(&command);
+                let dir = self.current_dir.clone();
+                let output =
+                    with_suspended_tui(self.mouse_captured, || command.run(&command_line, &dir))
+                        .map_err(|e| e.to_string())?
+                        .map_err(|e| e.to_string())?;
+                if !output.succeeded() {
+                    // Leave the output up to show why
+                    self.command_output = Some(output);
+                    self.mode = NavigatorMode::CommandOutput;
+                    return Err("the command exited with an error".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn workflow_targets(&self) -> std::result::Result<Vec<PathBuf>, String> {
+        let paths = self.get_selected_paths();
+        if paths.is_empty() {
+            return Err("nothing is selected".to_string());
+        }
+        Ok(paths)
+    }
 
-        let selected_paths = self.get_selected_paths();
+    /// Other users get the interface for the items they own
+    fn open_chmod_interface(&mut self, selected_paths: Vec<PathBuf>) {
         if selected_paths.is_empty() {
             self.status_message = Some("No items selected for chmod".to_string());
             return;
         }
 
-        self.chmod_interface = Some(ChmodInterface::new(selected_paths));
-        self.mode = NavigatorMode::ChmodInterface;
+        let uid = euid();
+        let mut task = Task::spawn(move |_| {
+            selected_paths
+                .into_iter()
+                .partition(|path| can_chmod(path, uid))
+        });
+        // Quick disks open the interface at once
+        match task.wait(OWNERSHIP_GRACE) {
+            Some((allowed, skipped)) => self.show_chmod_interface(allowed, skipped),
+            None => {
+                self.pending_chmod = Some(task);
+                self.status_message = Some("Checking who owns the selection…".to_string());
+            }
+        }
     }
 
-    fn open_chown_interface(&mut self) {
-        if !self.is_root {
-            self.status_message = Some("⚠️  Chown interface requires root privileges".to_string());
+    /// Open chmod on the paths the ownership check allowed, naming the
+    /// skipped ones
+    fn show_chmod_interface(&mut self, allowed: Vec<PathBuf>, skipped: Vec<PathBuf>) {
+        if allowed.is_empty() {
+            self.status_message = Some(
+                "⚠️  Only root or their owner can change the permissions of these items"
+                    .to_string(),
+            );
             return;
         }
 
-        let selected_paths = self.get_selected_paths();
+        self.chmod_interface = Some(
+            ChmodInterface::new(allowed, self.recent_choices.clone())
+                .with_templates(&self.config.chmod_templates)
+                .with_skipped(&skipped),
+        );
+        self.mode = NavigatorMode::ChmodInterface;
+    }
+
+    /// Other users get the interface as a simulation of what would happen
+    fn open_chown_interface(&mut self, selected_paths: Vec<PathBuf>) {
         if selected_paths.is_empty() {
             self.status_message = Some("No items selected for chown".to_string());
             return;
         }
 
-        self.chown_interface = Some(ChownInterface::new(selected_paths));
+        self.chown_interface = Some(
+            ChownInterface::new(selected_paths, !self.is_root, self.recent_choices.clone())
+                .with_history(OwnershipHistory::load()),
+        );
         self.mode = NavigatorMode::ChownInterface;
     }
 
+    /// Back from chmod or chown to the split view or listing it was opened
+    /// from, showing the changed entries
+    fn leave_permissions_interface(&mut self) -> Result<()> {
+        if let Some(ref mut split) = self.split_pane_view {
+            self.mode = NavigatorMode::SplitPane;
+            return split.get_active_pane_mut().reload();
+        }
+        self.mode = NavigatorMode::Browse;
+        self.selected_items.clear();
+        let current_dir = self.current_dir.clone();
+        self.load_directory(&current_dir)
+    }
+
     fn get_selected_paths(&self) -> Vec<PathBuf> {
         if self.selected_items.is_empty() {
             // Use currently highlighted item
@@ -1319,7 +4735,7 @@ impl Navigator {
     }
 
     fn adjust_scroll(&mut self) {
-        let visible_area = (self.terminal_height as usize).saturating_sub(5);
+        let visible_area = list_rows(self.terminal_height, self.readme.as_ref());
 
         if self.selected_index < self.scroll_offset {
             self.scroll_offset = self.selected_index;
@@ -1328,3 +4744,13 @@ impl Navigator {
         }
     }
 }
+
+/// `1.2 MB`, noting directories whose size is not in it yet
+fn format_size_total(total: SizeTotal) -> String {
+    let size = FilePreview::format_size(total.bytes);
+    match total.pending {
+        0 => size,
+        1 => format!("{} (sizing 1 dir…)", size),
+        n => format!("{} (sizing {} dirs…)", size, n),
+    }
+}
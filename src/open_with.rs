@@ -0,0 +1,463 @@
+use anyhow::Result;
+use crossterm::{
+    cursor::MoveTo,
+    event::KeyCode,
+    execute,
+    style::{Color, Print, ResetColor},
+    terminal,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::config::Config;
+use crate::preview::FilePreview;
+use crate::ui::{SetBackgroundColor, SetForegroundColor};
+use crate::utils::{config_dir, editor_command, home_dir};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppSource {
+    Remembered,
+    Config,
+    Desktop,
+    Editor,
+    SystemDefault,
+}
+
+/// An application the selected file can be opened with
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppChoice {
+    pub name: String,
+    /// Program and arguments; `%f`-style field codes are replaced by the file path
+    pub command: String,
+    /// Runs inside the terminal (TUI suspended) instead of detached
+    pub terminal: bool,
+    pub source: AppSource,
+}
+
+impl AppChoice {
+    /// Build the process for this choice, substituting the file path
+    pub fn build_command(&self, path: &Path) -> Option<Command> {
+        let mut parts = self.command.split_whitespace();
+        let program = parts.next()?;
+        let mut command = Command::new(program);
+        let mut substituted = false;
+
+        for arg in parts {
+            match arg {
+                "%f" | "%F" | "%u" | "%U" => {
+                    command.arg(path);
+                    substituted = true;
+                }
+                // Other desktop-entry field codes (icon, name, location) are not supported
+                code if code.len() == 2 && code.starts_with('%') => {}
+                arg => {
+                    command.arg(arg);
+                }
+            }
+        }
+
+        if !substituted {
+            command.arg(path);
+        }
+        Some(command)
+    }
+}
+
+/// Per-extension application choices remembered across sessions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Associations {
+    remembered: HashMap<String, String>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl Associations {
+    pub fn load() -> Self {
+        let Ok(dir) = config_dir() else {
+            return Self::default();
+        };
+        let path = dir.join("open_with.json");
+
+        let mut associations: Self = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        associations.path = path;
+        associations
+    }
+
+    pub fn get(&self, extension: &str) -> Option<&String> {
+        self.remembered.get(extension)
+    }
+
+    pub fn remember(&mut self, extension: &str, command: &str) -> Result<()> {
+        self.remembered
+            .insert(extension.to_string(), command.to_string());
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&self.path, json)?;
+        Ok(())
+    }
+}
+
+pub enum OpenWithOutcome {
+    Continue,
+    Cancel,
+    Launch { choice: AppChoice, remember: bool },
+}
+
+/// Menu listing the applications that can open a file
+pub struct OpenWithMenu {
+    pub path: PathBuf,
+    extension: String,
+    choices: Vec<AppChoice>,
+    selected: usize,
+}
+
+impl OpenWithMenu {
+    pub fn new(path: PathBuf, config: &Config, associations: &Associations) -> Self {
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let mime_type = FilePreview::detect_mime_type(&path);
+
+        let mut choices = Vec::new();
+
+        if let Some(command) = associations.get(&extension) {
+            choices.push(AppChoice {
+                name: command.clone(),
+                command: command.clone(),
+                terminal: false,
+                source: AppSource::Remembered,
+            });
+        }
+
+        for command in config.open_with.get(&extension).into_iter().flatten() {
+            choices.push(AppChoice {
+                name: command.clone(),
+                command: command.clone(),
+                terminal: false,
+                source: AppSource::Config,
+            });
+        }
+
+        choices.extend(discover_desktop_apps(&mime_type));
+
+        let (editor, args) = editor_command();
+        let mut editor_cmd = editor.clone();
+        for arg in args {
+            editor_cmd.push(' ');
+            editor_cmd.push_str(&arg);
+        }
+        choices.push(AppChoice {
+            name: format!("Text editor ({})", editor),
+            command: editor_cmd,
+            terminal: true,
+            source: AppSource::Editor,
+        });
+        choices.push(AppChoice {
+            name: "System default".to_string(),
+            command: if cfg!(target_os = "macos") {
+                "open".to_string()
+            } else {
+                "xdg-open".to_string()
+            },
+            terminal: false,
+            source: AppSource::SystemDefault,
+        });
+
+        // A remembered choice also shows up in its original source; keep the first one
+        let mut seen = Vec::new();
+        choices.retain(|c| {
+            if seen.contains(&c.command) {
+                false
+            } else {
+                seen.push(c.command.clone());
+                true
+            }
+        });
+
+        Self {
+            path,
+            extension,
+            choices,
+            selected: 0,
+        }
+    }
+
+    pub fn extension(&self) -> &str {
+        &self.extension
+    }
+
+    pub fn render(&self) -> Result<()> {
+        let mut stdout = io::stdout();
+        let (terminal_width, terminal_height) = terminal::size()?;
+
+        let width = 64.min(terminal_width.saturating_sub(4)).max(20);
+        let height = (self.choices.len() as u16 + 6).min(terminal_height.saturating_sub(2));
+        let x = (terminal_width.saturating_sub(width)) / 2;
+        let y = (terminal_height.saturating_sub(height)) / 2;
+
+        // Clear the popup area before drawing on top of the listing
+        for row in y..y + height {
+            execute!(stdout, MoveTo(x, row), Print(" ".repeat(width as usize)))?;
+        }
+
+        let file_name = self
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        crate::ui::draw_box(
+            &mut stdout,
+            x,
+            y,
+            width,
+            height,
+            Some(&format!("Open with… {}", file_name)),
+            Color::Cyan,
+        )?;
+
+        let list_height = height.saturating_sub(4) as usize;
+        let start = self.selected.saturating_sub(list_height.saturating_sub(1));
+        let inner_width = width.saturating_sub(4) as usize;
+
+        for (i, choice) in self
+            .choices
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(list_height)
+        {
+            let is_selected = i == self.selected;
+            let tag = match choice.source {
+                AppSource::Remembered => " (remembered)",
+                AppSource::Config => " (config)",
+                _ => "",
+            };
+            let label: String = format!("{}{}", choice.name, tag)
+                .chars()
+                .take(inner_width.saturating_sub(2))
+                .collect();
+
+            execute!(
+                stdout,
+                MoveTo(x + 2, y + 1 + (i - start) as u16),
+                if is_selected {
+                    SetBackgroundColor(Color::DarkGreen)
+                } else {
+                    SetBackgroundColor(Color::Reset)
+                },
+                SetForegroundColor(if is_selected {
+                    Color::White
+                } else {
+                    Color::Grey
+                }),
+                Print(format!(
+                    "{}{:<width$}",
+                    if is_selected { "> " } else { "  " },
+                    label,
+                    width = inner_width.saturating_sub(2)
+                )),
+                ResetColor
+            )?;
+        }
+
+        execute!(
+            stdout,
+            MoveTo(x + 2, y + height - 2),
+            SetForegroundColor(Color::DarkGrey),
+            Print(
+                " ↑↓: Select | Enter: Open | r: Open & remember | Esc: Cancel"
+                    .chars()
+                    .take(inner_width)
+                    .collect::<String>()
+            ),
+            ResetColor
+        )?;
+
+        stdout.flush()?;
+        Ok(())
+    }
+
+    pub fn handle_input(&mut self, key: KeyCode) -> OpenWithOutcome {
+        match key {
+            KeyCode::Up if self.selected > 0 => {
+                self.selected -= 1;
+            }
+            KeyCode::Down if self.selected + 1 < self.choices.len() => {
+                self.selected += 1;
+            }
+            KeyCode::Enter | KeyCode::Char('r') => {
+                if let Some(choice) = self.choices.get(self.selected) {
+                    return OpenWithOutcome::Launch {
+                        choice: choice.clone(),
+                        remember: key == KeyCode::Char('r') && !self.extension.is_empty(),
+                    };
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => return OpenWithOutcome::Cancel,
+            _ => {}
+        }
+        OpenWithOutcome::Continue
+    }
+}
+
+/// Applications from `.desktop` files that declare support for `mime_type`
+#[cfg(not(target_os = "macos"))]
+fn discover_desktop_apps(mime_type: &str) -> Vec<AppChoice> {
+    let mut dirs = Vec::new();
+    match std::env::var("XDG_DATA_HOME") {
+        Ok(dir) if !dir.is_empty() => dirs.push(PathBuf::from(dir).join("applications")),
+        _ => {
+            if let Some(home) = home_dir() {
+                dirs.push(home.join(".local/share/applications"));
+            }
+        }
+    }
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|d| !d.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+    dirs.extend(
+        data_dirs
+            .split(':')
+            .map(|d| PathBuf::from(d).join("applications")),
+    );
+
+    let mut apps: Vec<AppChoice> = Vec::new();
+    for dir in dirs {
+        let Ok(read_dir) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in read_dir.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("desktop") {
+                continue;
+            }
+            let Ok(content) = fs::read_to_string(&path) else {
+                continue;
+            };
+            if let Some(app) = parse_desktop_entry(&content, mime_type) {
+                if !apps.iter().any(|a| a.name == app.name) {
+                    apps.push(app);
+                }
+            }
+        }
+    }
+
+    apps.sort_by_key(|a| a.name.to_lowercase());
+    apps
+}
+
+#[cfg(target_os = "macos")]
+fn discover_desktop_apps(_mime_type: &str) -> Vec<AppChoice> {
+    Vec::new()
+}
+
+/// Parse the `[Desktop Entry]` group of a desktop file, keeping it only if it
+/// handles `mime_type` (text files also match generic `text/plain` handlers)
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+fn parse_desktop_entry(content: &str, mime_type: &str) -> Option<AppChoice> {
+    let mut in_entry = false;
+    let mut name = None;
+    let mut exec = None;
+    let mut mime_types = "";
+    let mut terminal = false;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_entry {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        match key.trim() {
+            "Name" => name = Some(value.trim().to_string()),
+            "Exec" => exec = Some(value.trim().to_string()),
+            "MimeType" => mime_types = value,
+            "Terminal" => terminal = value.trim() == "true",
+            "NoDisplay" | "Hidden" if value.trim() == "true" => return None,
+            "Type" if value.trim() != "Application" => return None,
+            _ => {}
+        }
+    }
+
+    let generic = mime_type.starts_with("text/").then_some("text/plain");
+    let handles = mime_types.split(';').any(|m| {
+        let m = m.trim();
+        !m.is_empty() && (m == mime_type || Some(m) == generic)
+    });
+    if !handles {
+        return None;
+    }
+
+    Some(AppChoice {
+        name: name?,
+        command: exec?,
+        terminal,
+        source: AppSource::Desktop,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DESKTOP: &str = "[Desktop Entry]
+Type=Application
+Name=Document Viewer
+Exec=evince %U
+MimeType=application/pdf;image/tiff;
+
+[Desktop Action new-window]
+Name=New Window
+Exec=evince --new-window
+";
+
+    #[test]
+    fn test_parse_desktop_entry() {
+        let app = parse_desktop_entry(DESKTOP, "application/pdf").unwrap();
+        assert_eq!(app.name, "Document Viewer");
+        assert_eq!(app.command, "evince %U");
+        assert!(!app.terminal);
+
+        assert!(parse_desktop_entry(DESKTOP, "image/png").is_none());
+        assert!(parse_desktop_entry(
+            "[Desktop Entry]\nName=X\nExec=x\nNoDisplay=true\nMimeType=application/pdf;",
+            "application/pdf"
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_build_command_substitutes_path() {
+        let choice = AppChoice {
+            name: "viewer".to_string(),
+            command: "evince --page 1 %U %i".to_string(),
+            terminal: false,
+            source: AppSource::Desktop,
+        };
+        let command = choice.build_command(Path::new("/tmp/doc.pdf")).unwrap();
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(args, vec!["--page", "1", "/tmp/doc.pdf"]);
+
+        let plain = AppChoice {
+            command: "zathura".to_string(),
+            ..choice
+        };
+        let command = plain.build_command(Path::new("/tmp/doc.pdf")).unwrap();
+        assert_eq!(command.get_args().collect::<Vec<_>>(), vec!["/tmp/doc.pdf"]);
+    }
+}
@@ -0,0 +1,402 @@
+use anyhow::Result;
+use crossterm::{
+    cursor::MoveTo,
+    event::KeyCode,
+    execute,
+    style::{Color, Print, ResetColor},
+    terminal,
+};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::models::ListingOptions;
+use crate::preview::{wrap_rows, DirectoryPreview, FilePreview, PreviewContent, PreviewLoader};
+use crate::ui::{SetBackgroundColor, SetForegroundColor};
+
+/// Lines of a text file the pager loads; files past the preview's 10 MB
+/// limit are refused before this matters
+const PAGER_LINES: usize = 1_000_000;
+
+/// How long opening the pager waits before drawing it as still loading
+const LOAD_GRACE: Duration = Duration::from_millis(50);
+
+/// Full-screen `less`-like view of a file, built from the same content
+/// the preview panel shows
+pub struct Pager {
+    path: PathBuf,
+    loader: Option<PreviewLoader>,
+    preview: Option<FilePreview>,
+    /// The lines paged through; search matches index into the same list
+    lines: Vec<String>,
+    /// Long lines continue on the next row rather than being cut
+    wrap: bool,
+    /// Pattern being typed at the `/` prompt
+    search_input: Option<String>,
+    status: Option<String>,
+}
+
+impl Pager {
+    pub fn open(path: &Path, listing: &ListingOptions) -> Self {
+        let mut loader = PreviewLoader::spawn(path, PAGER_LINES, listing);
+        let loaded = loader.wait(LOAD_GRACE);
+        let mut pager = Self {
+            path: path.to_path_buf(),
+            loader: Some(loader),
+            preview: None,
+            lines: Vec::new(),
+            wrap: true,
+            search_input: None,
+            status: None,
+        };
+        if let Some(preview) = loaded {
+            pager.finish_loading(preview);
+        }
+        pager
+    }
+
+    /// Pick up the file once the background read finishes
+    pub fn poll(&mut self) {
+        if let Some(preview) = self.loader.as_mut().and_then(|l| l.poll()) {
+            self.finish_loading(preview);
+        }
+    }
+
+    fn finish_loading(&mut self, preview: Option<FilePreview>) {
+        self.loader = None;
+        if preview.is_none() {
+            self.status = Some(format!("Cannot read {}", self.path.display()));
+        }
+        self.lines = preview.as_ref().map(Self::page_lines).unwrap_or_default();
+        self.preview = preview;
+    }
+
+    /// Lines of `preview` as the search sees them, with a hex dump or a
+    /// listing for what is not text
+    fn page_lines(preview: &FilePreview) -> Vec<String> {
+        match &preview.content {
+            PreviewContent::Text(lines) => lines.clone(),
+            PreviewContent::Json(document) => document.lines.iter().map(|l| l.text()).collect(),
+            PreviewContent::Media(info) => info.lines(),
+            PreviewContent::Binary(chunk) => chunk
+                .bytes
+                .chunks(16)
+                .enumerate()
+                .map(|(i, row)| {
+                    let hex: String = row.iter().map(|b| format!("{:02x} ", b)).collect();
+                    let ascii: String = row
+                        .iter()
+                        .map(|&b| if b.is_ascii_graphic() { b as char } else { '.' })
+                        .collect();
+                    format!(
+                        "{:08x}  {:<48}|{}|",
+                        chunk.start as usize + i * 16,
+                        hex,
+                        ascii
+                    )
+                })
+                .collect(),
+            PreviewContent::Image(info) => {
+                let mut lines = vec![info.description()];
+                lines.extend(info.details());
+                lines
+            }
+            PreviewContent::Directory(dir) => std::iter::once(dir.summary())
+                .chain(dir.entries.iter().map(|entry| {
+                    format!(
+                        "{:<40} {:>10}",
+                        entry.display_name(),
+                        DirectoryPreview::annotation(entry)
+                    )
+                }))
+                .collect(),
+            PreviewContent::Error(msg) => vec![msg.clone()],
+            PreviewContent::Empty => Vec::new(),
+        }
+    }
+
+    fn page_rows() -> usize {
+        terminal::size()
+            .map(|(_, height)| height.saturating_sub(3) as usize)
+            .unwrap_or(20)
+            .max(1)
+    }
+
+    /// First line on screen
+    fn top(&self) -> usize {
+        self.preview.as_ref().map_or(0, |p| p.scroll_offset)
+    }
+
+    fn scroll_to(&mut self, line: usize) {
+        let last = self.lines.len().saturating_sub(1);
+        if let Some(ref mut preview) = self.preview {
+            preview.scroll_offset = line.min(last);
+        }
+    }
+
+    /// How far through the file the bottom of the screen is, as `less`
+    /// shows it: a percentage, or END once the last line is in view
+    fn position(&self, rows: usize) -> String {
+        let total = self.lines.len();
+        let bottom = self.top() + rows;
+        if bottom >= total {
+            "END".to_string()
+        } else {
+            format!("{}%", bottom * 100 / total)
+        }
+    }
+
+    pub fn render(&self) -> Result<()> {
+        let mut stdout = io::stdout();
+        let (terminal_width, terminal_height) = terminal::size()?;
+        let width = terminal_width as usize;
+        let rows = terminal_height.saturating_sub(3) as usize;
+
+        execute!(stdout, terminal::Clear(terminal::ClearType::All))?;
+
+        let header: String = format!(" 📖 {}", self.path.display())
+            .chars()
+            .take(width)
+            .collect();
+        execute!(
+            stdout,
+            MoveTo(0, 0),
+            SetBackgroundColor(Color::DarkBlue),
+            SetForegroundColor(Color::White),
+            Print(format!("{:<width$}", header, width = width)),
+            ResetColor
+        )?;
+
+        let lines = &self.lines;
+        if self.loader.is_some() {
+            execute!(
+                stdout,
+                MoveTo(1, 2),
+                SetForegroundColor(Color::DarkGrey),
+                Print("⏳ Loading…"),
+                ResetColor
+            )?;
+        } else if lines.is_empty() {
+            execute!(
+                stdout,
+                MoveTo(1, 2),
+                SetForegroundColor(Color::DarkGrey),
+                Print("(empty file)"),
+                ResetColor
+            )?;
+        }
+
+        let mut row = 0;
+        for (index, line) in lines.iter().enumerate().skip(self.top()) {
+            if row >= rows {
+                break;
+            }
+            let highlight = self
+                .preview
+                .as_ref()
+                .and_then(|preview| preview.line_highlight(index));
+            let runs = match highlight {
+                Some(highlight) => highlight.segments(line, usize::MAX),
+                None => vec![(line.clone(), false)],
+            };
+            let runs: Vec<(bool, String)> = runs.into_iter().map(|(text, m)| (m, text)).collect();
+            for wrapped in wrap_rows(&runs, width.saturating_sub(1), self.wrap) {
+                if row >= rows {
+                    break;
+                }
+                execute!(stdout, MoveTo(1, 1 + row as u16))?;
+                for (matched, text) in wrapped {
+                    if matched {
+                        execute!(
+                            stdout,
+                            SetBackgroundColor(Color::Yellow),
+                            SetForegroundColor(Color::Black),
+                            Print(text),
+                            ResetColor
+                        )?;
+                    } else {
+                        execute!(stdout, Print(text))?;
+                    }
+                }
+                row += 1;
+            }
+        }
+
+        let info = if let Some(ref input) = self.search_input {
+            format!("/{}_", input)
+        } else if let Some(ref status) = self.status {
+            status.clone()
+        } else {
+            let mut info = format!(
+                "Line {}/{}  {}",
+                (self.top() + 1).min(lines.len()),
+                lines.len(),
+                self.position(rows)
+            );
+            if let Some(search) = self.preview.as_ref().and_then(|p| p.search.as_ref()) {
+                info.push_str(&format!(
+                    "  /{} ({} matches)",
+                    search.pattern,
+                    search.matches.len()
+                ));
+            }
+            info
+        };
+        execute!(
+            stdout,
+            MoveTo(1, terminal_height.saturating_sub(2)),
+            SetForegroundColor(Color::Yellow),
+            Print(
+                info.chars()
+                    .take(width.saturating_sub(2))
+                    .collect::<String>()
+            ),
+            ResetColor
+        )?;
+
+        let footer = " ↑↓/jk: Line | PgUp/PgDn/Space/b: Page | g/G: Start/End | /: Search | n/N: Next/Prev match | w: Wrap | q: Close";
+        execute!(
+            stdout,
+            MoveTo(0, terminal_height.saturating_sub(1)),
+            SetBackgroundColor(Color::DarkGrey),
+            SetForegroundColor(Color::White),
+            Print(format!(
+                "{:<width$}",
+                footer.chars().take(width).collect::<String>(),
+                width = width
+            )),
+            ResetColor
+        )?;
+
+        stdout.flush()?;
+        Ok(())
+    }
+
+    /// Returns false once the pager should close
+    pub fn handle_input(&mut self, key: KeyCode) -> bool {
+        if let Some(mut pattern) = self.search_input.take() {
+            let Some(ref mut preview) = self.preview else {
+                return true;
+            };
+            match key {
+                KeyCode::Char(c) => {
+                    pattern.push(c);
+                    self.search_input = Some(pattern);
+                }
+                KeyCode::Backspace => {
+                    pattern.pop();
+                    self.search_input = Some(pattern);
+                }
+                KeyCode::Enter if pattern.is_empty() => preview.cancel_search(),
+                KeyCode::Enter => {
+                    preview.search(&pattern);
+                    if preview
+                        .search
+                        .as_ref()
+                        .is_some_and(|s| s.matches.is_empty())
+                    {
+                        self.status = Some(format!("Pattern not found: {}", pattern));
+                    }
+                }
+                KeyCode::Esc => {}
+                _ => self.search_input = Some(pattern),
+            }
+            return true;
+        }
+
+        self.status = None;
+        let page = Self::page_rows();
+        let top = self.top();
+        match key {
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_to(top.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Enter => self.scroll_to(top + 1),
+            KeyCode::PageUp | KeyCode::Char('b') => self.scroll_to(top.saturating_sub(page)),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.scroll_to(top + page),
+            KeyCode::Home | KeyCode::Char('g') => self.scroll_to(0),
+            // The last page, not just the last line
+            KeyCode::End | KeyCode::Char('G') => {
+                self.scroll_to(self.lines.len().saturating_sub(page))
+            }
+            KeyCode::Char('w') => self.wrap = !self.wrap,
+            KeyCode::Char('/') if self.preview.is_some() => self.search_input = Some(String::new()),
+            KeyCode::Char(c @ ('n' | 'N')) => {
+                let found = self
+                    .preview
+                    .as_mut()
+                    .and_then(|preview| preview.jump_to_match(c == 'n'));
+                self.status = Some(match found {
+                    Some((position, count)) => format!("Match {} of {}", position, count),
+                    None => "No search, or no matches: / to search".to_string(),
+                });
+            }
+            // Esc drops a search first, like the preview panel
+            KeyCode::Esc
+                if self
+                    .preview
+                    .as_ref()
+                    .is_some_and(|preview| preview.search.is_some()) =>
+            {
+                if let Some(ref mut preview) = self.preview {
+                    preview.search = None;
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => return false,
+            _ => {}
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn loaded(path: &Path) -> Pager {
+        let mut pager = Pager::open(path, &ListingOptions::default());
+        if let Some(preview) = pager
+            .loader
+            .as_mut()
+            .and_then(|l| l.wait(Duration::from_secs(5)))
+        {
+            pager.finish_loading(preview);
+        }
+        pager
+    }
+
+    #[test]
+    fn test_scroll_and_search() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.txt");
+        let text: Vec<String> = (1..=200).map(|i| format!("line {}", i)).collect();
+        fs::write(&path, text.join("\n")).unwrap();
+
+        let mut pager = loaded(&path);
+        // Longer than a preview's 50 lines: the whole file is paged
+        assert_eq!(pager.lines.len(), 200);
+        assert_eq!(pager.position(10), "5%");
+
+        pager.handle_input(KeyCode::Char('G'));
+        assert_eq!(pager.position(Pager::page_rows()), "END");
+        pager.handle_input(KeyCode::Char('g'));
+        assert_eq!(pager.top(), 0);
+
+        pager.handle_input(KeyCode::Char('/'));
+        for c in "line 15".chars() {
+            pager.handle_input(KeyCode::Char(c));
+        }
+        pager.handle_input(KeyCode::Enter);
+        // "line 15" and "line 150" to "line 159"
+        let search = pager.preview.as_ref().unwrap().search.as_ref().unwrap();
+        assert_eq!(search.matches.len(), 11);
+        assert_eq!(pager.top(), 14 - 3);
+        pager.handle_input(KeyCode::Char('n'));
+        assert_eq!(pager.status.as_deref(), Some("Match 2 of 11"));
+        assert_eq!(pager.top(), 149 - 3);
+
+        // Esc clears the search before it closes the pager
+        assert!(pager.handle_input(KeyCode::Esc));
+        assert!(pager.preview.as_ref().unwrap().search.is_none());
+        assert!(!pager.handle_input(KeyCode::Char('q')));
+    }
+}
@@ -0,0 +1,286 @@
+use anyhow::Result;
+use crossterm::{
+    cursor::MoveTo,
+    event::KeyCode,
+    execute,
+    style::{Color, Print, ResetColor},
+    terminal,
+};
+use std::io::{self, Write};
+
+use crate::actions::{Action, Profile};
+use crate::custom_commands::CustomCommand;
+use crate::search::fuzzy_score;
+use crate::ui::{SetBackgroundColor, SetForegroundColor};
+
+pub enum PaletteOutcome {
+    Continue,
+    Cancel,
+    Run(Action),
+}
+
+struct PaletteEntry {
+    action: Action,
+    label: String,
+    hint: String,
+}
+
+/// Fuzzy-searchable list of every action available in browse mode
+pub struct CommandPalette {
+    query: String,
+    available: Vec<PaletteEntry>,
+    /// Indices into `available`, best match first
+    matches: Vec<usize>,
+    selected: usize,
+}
+
+impl CommandPalette {
+    pub fn new(is_root: bool, profile: Profile, custom_commands: &[CustomCommand]) -> Self {
+        let mut available: Vec<PaletteEntry> = Action::ALL
+            .iter()
+            .copied()
+            .filter(|action| action.is_available(is_root, profile))
+            .map(|action| PaletteEntry {
+                action,
+                label: action.label().to_string(),
+                hint: action.key_hint().to_string(),
+            })
+            .collect();
+
+        available.extend(
+            custom_commands
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| Action::Custom(*i).is_available(is_root, profile))
+                .map(|(i, command)| PaletteEntry {
+                    action: Action::Custom(i),
+                    label: format!("Run: {}", command.name),
+                    hint: command.key.map(|k| k.to_string()).unwrap_or_default(),
+                }),
+        );
+
+        Self {
+            query: String::new(),
+            matches: (0..available.len()).collect(),
+            available,
+            selected: 0,
+        }
+    }
+
+    fn update_matches(&mut self) {
+        let mut scored: Vec<(i64, usize)> = self
+            .available
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| fuzzy_score(&self.query, &entry.label).map(|s| (s, i)))
+            .collect();
+        // Stable sort keeps the palette order for equal scores
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+
+        self.matches = scored.into_iter().map(|(_, i)| i).collect();
+        self.selected = 0;
+    }
+
+    pub fn render(&self) -> Result<()> {
+        let mut stdout = io::stdout();
+        let (terminal_width, terminal_height) = terminal::size()?;
+
+        let width = 60.min(terminal_width.saturating_sub(4)).max(20);
+        let height = 16.min(terminal_height.saturating_sub(2)).max(6);
+        let x = (terminal_width.saturating_sub(width)) / 2;
+        let y = (terminal_height.saturating_sub(height)) / 3;
+
+        // Clear the popup area before drawing on top of the listing
+        for row in y..y + height {
+            execute!(stdout, MoveTo(x, row), Print(" ".repeat(width as usize)))?;
+        }
+
+        crate::ui::draw_box(
+            &mut stdout,
+            x,
+            y,
+            width,
+            height,
+            Some("Command palette"),
+            Color::Cyan,
+        )?;
+
+        let inner_width = width.saturating_sub(4) as usize;
+        let prompt: String = format!("> {}_", self.query)
+            .chars()
+            .take(inner_width)
+            .collect();
+        execute!(
+            stdout,
+            MoveTo(x + 2, y + 1),
+            SetForegroundColor(Color::Yellow),
+            Print(prompt),
+            ResetColor
+        )?;
+
+        let list_height = height.saturating_sub(5) as usize;
+        let start = self.selected.saturating_sub(list_height.saturating_sub(1));
+
+        if self.matches.is_empty() {
+            execute!(
+                stdout,
+                MoveTo(x + 2, y + 3),
+                SetForegroundColor(Color::DarkGrey),
+                Print("No matching command"),
+                ResetColor
+            )?;
+        }
+
+        for (i, &index) in self
+            .matches
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(list_height)
+        {
+            let is_selected = i == self.selected;
+            let entry = &self.available[index];
+            let hint = &entry.hint;
+            let label_width = inner_width.saturating_sub(hint.chars().count() + 3);
+            let label: String = entry.label.chars().take(label_width).collect();
+
+            execute!(
+                stdout,
+                MoveTo(x + 2, y + 3 + (i - start) as u16),
+                if is_selected {
+                    SetBackgroundColor(Color::DarkGreen)
+                } else {
+                    SetBackgroundColor(Color::Reset)
+                },
+                SetForegroundColor(if is_selected {
+                    Color::White
+                } else {
+                    Color::Grey
+                }),
+                Print(format!(
+                    "{}{:<width$}",
+                    if is_selected { "> " } else { "  " },
+                    label,
+                    width = label_width
+                )),
+                SetForegroundColor(Color::DarkGrey),
+                Print(format!(" {}", hint)),
+                ResetColor
+            )?;
+        }
+
+        execute!(
+            stdout,
+            MoveTo(x + 2, y + height - 2),
+            SetForegroundColor(Color::DarkGrey),
+            Print(
+                " Type to filter | ↑↓: Select | Enter: Run | Esc: Close"
+                    .chars()
+                    .take(inner_width)
+                    .collect::<String>()
+            ),
+            ResetColor
+        )?;
+
+        stdout.flush()?;
+        Ok(())
+    }
+
+    pub fn handle_input(&mut self, key: KeyCode) -> PaletteOutcome {
+        match key {
+            KeyCode::Up if self.selected > 0 => {
+                self.selected -= 1;
+            }
+            KeyCode::Down if self.selected + 1 < self.matches.len() => {
+                self.selected += 1;
+            }
+            KeyCode::Enter => {
+                if let Some(&index) = self.matches.get(self.selected) {
+                    return PaletteOutcome::Run(self.available[index].action);
+                }
+            }
+            KeyCode::Esc => return PaletteOutcome::Cancel,
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.update_matches();
+            }
+            KeyCode::Char(c) => {
+                self.query.push(c);
+                self.update_matches();
+            }
+            _ => {}
+        }
+        PaletteOutcome::Continue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matched_actions(palette: &CommandPalette) -> Vec<Action> {
+        palette
+            .matches
+            .iter()
+            .map(|&i| palette.available[i].action)
+            .collect()
+    }
+
+    fn type_query(palette: &mut CommandPalette, query: &str) {
+        for c in query.chars() {
+            palette.handle_input(KeyCode::Char(c));
+        }
+    }
+
+    #[test]
+    fn test_root_actions_hidden_for_users() {
+        let palette = CommandPalette::new(false, Profile::Admin, &[]);
+        assert!(!matched_actions(&palette).contains(&Action::SelectMode));
+        assert!(matched_actions(&palette).contains(&Action::OpenChmod));
+        let palette = CommandPalette::new(true, Profile::Admin, &[]);
+        assert!(matched_actions(&palette).contains(&Action::SelectMode));
+    }
+
+    #[test]
+    fn test_basic_profile_hides_privileged_actions() {
+        let commands = vec![CustomCommand::new(
+            "gzip",
+            &crate::custom_commands::CommandSpec::Line("tar czf %f.tar.gz %f".to_string()),
+        )];
+        let palette = CommandPalette::new(true, Profile::Basic, &commands);
+        let actions = matched_actions(&palette);
+        assert!(!actions.contains(&Action::OpenChmod));
+        assert!(!actions.contains(&Action::SpawnShell));
+        assert!(!actions.contains(&Action::Custom(0)));
+        assert!(actions.contains(&Action::Search));
+    }
+
+    #[test]
+    fn test_filter_and_run() {
+        let mut palette = CommandPalette::new(false, Profile::Admin, &[]);
+        type_query(&mut palette, "sort size");
+        assert_eq!(matched_actions(&palette).first(), Some(&Action::SortBySize));
+        assert!(matches!(
+            palette.handle_input(KeyCode::Enter),
+            PaletteOutcome::Run(Action::SortBySize)
+        ));
+
+        type_query(&mut palette, "zzz");
+        assert!(palette.matches.is_empty());
+        assert!(matches!(
+            palette.handle_input(KeyCode::Enter),
+            PaletteOutcome::Continue
+        ));
+    }
+
+    #[test]
+    fn test_custom_commands_listed() {
+        let commands = vec![CustomCommand::new(
+            "gzip",
+            &crate::custom_commands::CommandSpec::Line("tar czf %f.tar.gz %f".to_string()),
+        )];
+        let mut palette = CommandPalette::new(false, Profile::Admin, &commands);
+        type_query(&mut palette, "run gzip");
+        assert_eq!(matched_actions(&palette), vec![Action::Custom(0)]);
+    }
+}
@@ -0,0 +1,188 @@
+use exif::{Exif, In, Reader, Tag, Value};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// What a photographer triages by, read from an image's EXIF block
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PhotoInfo {
+    /// Make and model of the camera
+    pub camera: Option<String>,
+    /// Pixel size the camera recorded
+    pub dimensions: Option<(u32, u32)>,
+    /// As recorded, `2024:06:01 18:30:12`
+    pub taken: Option<String>,
+    /// Shutter speed, aperture and ISO, as far as recorded
+    pub exposure: Option<String>,
+    /// Latitude and longitude in degrees, south and west negative
+    pub gps: Option<(f64, f64)>,
+}
+
+impl PhotoInfo {
+    /// EXIF details of the JPEG, PNG, TIFF or WebP at `path`; `None` when it
+    /// has no EXIF block or nothing worth showing in it
+    pub fn read(path: &Path) -> Option<Self> {
+        let file = File::open(path).ok()?;
+        let exif = Reader::new()
+            .read_from_container(&mut BufReader::new(file))
+            .ok()?;
+        let info = Self::from_exif(&exif);
+        (info != Self::default()).then_some(info)
+    }
+
+    fn from_exif(exif: &Exif) -> Self {
+        let text = |tag| {
+            let field = exif.get_field(tag, In::PRIMARY)?;
+            match field.value {
+                Value::Ascii(ref parts) => parts
+                    .first()
+                    .map(|part| String::from_utf8_lossy(part).trim().to_string())
+                    .filter(|s| !s.is_empty()),
+                _ => None,
+            }
+        };
+        let uint = |tag| exif.get_field(tag, In::PRIMARY)?.value.get_uint(0);
+        let shown = |tag| {
+            exif.get_field(tag, In::PRIMARY)
+                .map(|field| field.display_value().with_unit(exif).to_string())
+        };
+
+        let camera = match (text(Tag::Make), text(Tag::Model)) {
+            // Models often repeat the make: "Canon" + "Canon EOS R6"
+            (Some(make), Some(model)) if model.starts_with(&make) => Some(model),
+            (Some(make), Some(model)) => Some(format!("{} {}", make, model)),
+            (make, model) => make.or(model),
+        };
+        let dimensions = uint(Tag::PixelXDimension)
+            .zip(uint(Tag::PixelYDimension))
+            .or_else(|| uint(Tag::ImageWidth).zip(uint(Tag::ImageLength)));
+        let exposure: Vec<String> = [
+            shown(Tag::ExposureTime),
+            shown(Tag::FNumber),
+            uint(Tag::PhotographicSensitivity).map(|iso| format!("ISO {}", iso)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let latitude = coordinate(exif, Tag::GPSLatitude, Tag::GPSLatitudeRef, 'S');
+        let longitude = coordinate(exif, Tag::GPSLongitude, Tag::GPSLongitudeRef, 'W');
+
+        Self {
+            camera,
+            dimensions,
+            taken: text(Tag::DateTimeOriginal).or_else(|| text(Tag::DateTime)),
+            exposure: (!exposure.is_empty()).then(|| exposure.join("  ")),
+            gps: latitude.zip(longitude),
+        }
+    }
+
+    /// One labelled line per known detail, for the preview header
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(ref camera) = self.camera {
+            lines.push(format!("Camera: {}", camera));
+        }
+        if let Some((width, height)) = self.dimensions {
+            lines.push(format!("Size:   {}×{}", width, height));
+        }
+        if let Some(ref taken) = self.taken {
+            lines.push(format!("Taken:  {}", taken));
+        }
+        if let Some(ref exposure) = self.exposure {
+            lines.push(format!("Exp.:   {}", exposure));
+        }
+        if let Some((latitude, longitude)) = self.gps {
+            lines.push(format!("GPS:    {:.5}, {:.5}", latitude, longitude));
+        }
+        lines
+    }
+}
+
+/// Degrees of a GPS latitude or longitude stored as degrees, minutes and
+/// seconds, negative when its reference is `negative`
+fn coordinate(exif: &Exif, tag: Tag, reference: Tag, negative: char) -> Option<f64> {
+    let Value::Rational(ref parts) = exif.get_field(tag, In::PRIMARY)?.value else {
+        return None;
+    };
+    let degrees = parts
+        .iter()
+        .take(3)
+        .zip([1.0, 60.0, 3600.0])
+        .map(|(part, unit)| part.to_f64() / unit)
+        .sum::<f64>();
+    let negative = match exif.get_field(reference, In::PRIMARY)?.value {
+        Value::Ascii(ref parts) => parts
+            .first()
+            .is_some_and(|r| r.first() == Some(&(negative as u8))),
+        _ => false,
+    };
+    Some(if negative { -degrees } else { degrees })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use exif::experimental::Writer;
+    use exif::{Field, Rational};
+    use std::io::Cursor;
+
+    /// A TIFF file holding only these EXIF details, with no pixels
+    pub(crate) fn tiff_with_exif() -> Vec<u8> {
+        let field = |tag, value| Field {
+            tag,
+            ifd_num: In::PRIMARY,
+            value,
+        };
+        let ascii = |s: &str| Value::Ascii(vec![s.as_bytes().to_vec()]);
+        let rational = |num, denom| Rational { num, denom };
+        let fields = [
+            field(Tag::Make, ascii("Canon")),
+            field(Tag::Model, ascii("Canon EOS R6")),
+            field(Tag::DateTimeOriginal, ascii("2024:06:01 18:30:12")),
+            field(Tag::PixelXDimension, Value::Long(vec![6000])),
+            field(Tag::PixelYDimension, Value::Long(vec![4000])),
+            field(Tag::ExposureTime, Value::Rational(vec![rational(1, 250)])),
+            field(Tag::FNumber, Value::Rational(vec![rational(28, 10)])),
+            field(Tag::PhotographicSensitivity, Value::Short(vec![400])),
+            field(Tag::GPSLatitudeRef, ascii("N")),
+            field(
+                Tag::GPSLatitude,
+                Value::Rational(vec![rational(48, 1), rational(51, 1), rational(2988, 100)]),
+            ),
+            field(Tag::GPSLongitudeRef, ascii("W")),
+            field(
+                Tag::GPSLongitude,
+                Value::Rational(vec![rational(2, 1), rational(17, 1), rational(4020, 100)]),
+            ),
+        ];
+        let mut writer = Writer::new();
+        for field in &fields {
+            writer.push_field(field);
+        }
+        let mut tiff = Cursor::new(Vec::new());
+        writer.write(&mut tiff, false).unwrap();
+        tiff.into_inner()
+    }
+
+    #[test]
+    fn test_read_photo_info() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("photo.tif");
+        std::fs::write(&path, tiff_with_exif()).unwrap();
+
+        let info = PhotoInfo::read(&path).unwrap();
+        assert_eq!(info.camera.as_deref(), Some("Canon EOS R6"));
+        assert_eq!(info.dimensions, Some((6000, 4000)));
+        assert_eq!(info.taken.as_deref(), Some("2024:06:01 18:30:12"));
+        assert_eq!(info.exposure.as_deref(), Some("1/250 s  f/2.8  ISO 400"));
+        let (latitude, longitude) = info.gps.unwrap();
+        assert!((latitude - 48.8583).abs() < 1e-4);
+        assert!((longitude + 2.2945).abs() < 1e-4);
+        assert_eq!(info.lines().len(), 5);
+
+        // Images without EXIF have nothing to show
+        let plain = temp_dir.path().join("plain.png");
+        image::RgbaImage::new(1, 1).save(&plain).unwrap();
+        assert!(PhotoInfo::read(&plain).is_none());
+    }
+}
@@ -1,53 +1,571 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use std::collections::VecDeque;
 use std::fs::{self, File};
-use std::io::{BufRead, BufReader, Read};
-use std::path::Path;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use image::{ImageFormat, RgbaImage};
+
+use crate::archive;
+use crate::graphics::{self, ImageHeader};
+use crate::json::{JsonDocument, ParseError};
+use crate::mail::{self, MailMessage, Mailbox};
+use crate::markdown::{self, Span};
+use crate::media::{self, MediaInfo};
+use crate::models::{read_directory, FileEntry, ListingOptions};
+use crate::photo::PhotoInfo;
+use crate::systemd::{self, UnitFile};
+use crate::tasks::Task;
+use crate::utils::is_virtual_fs;
 
 #[derive(Debug, Clone)]
 pub struct FilePreview {
+    pub path: PathBuf,
     pub content: PreviewContent,
     pub file_info: FileInfo,
     pub scroll_offset: usize,
+    /// Search match brought into view by [`FilePreview::focus_line`]
+    pub highlight: Option<LineHighlight>,
+    /// Parsed systemd unit, summarized above the file body
+    pub unit: Option<UnitFile>,
+    /// Title and page count of a PDF whose text is shown as content
+    pub pdf: Option<PdfInfo>,
+    /// Email or mbox messages; the content is the body of the current one
+    pub mail: Option<Mailbox>,
+    /// Styled rendering of a Markdown file, one line per line of content
+    pub markdown: Option<Vec<Vec<Span>>>,
+    /// Show the Markdown source instead of its rendering
+    pub show_raw: bool,
+    /// Why a JSON file is shown as raw text rather than pretty-printed
+    pub json_error: Option<ParseError>,
+    /// File inside the archive at `path` whose text is shown
+    pub archive_entry: Option<String>,
+    /// Pattern searched for with `/` in the focused panel
+    pub search: Option<PreviewSearch>,
+}
+
+/// Matches of a pattern within the previewed text, ignoring ASCII case
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreviewSearch {
+    pub pattern: String,
+    /// 0-based line and byte range of every match, in document order
+    pub matches: Vec<(usize, Range<usize>)>,
+    /// Index into `matches` of the one in view
+    pub current: usize,
+    /// Scroll offset when the search began, restored if it is cancelled
+    origin: usize,
+}
+
+impl PreviewSearch {
+    fn find(lines: &[String], pattern: &str) -> Vec<(usize, Range<usize>)> {
+        if pattern.is_empty() {
+            return Vec::new();
+        }
+        // ASCII lowercasing keeps byte offsets valid for the original line
+        let pattern = pattern.to_ascii_lowercase();
+        lines
+            .iter()
+            .enumerate()
+            .flat_map(|(index, line)| {
+                line.to_ascii_lowercase()
+                    .match_indices(&pattern)
+                    .map(|(start, text)| (index, start..start + text.len()))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Line of the match in view
+    pub fn current_line(&self) -> Option<usize> {
+        self.matches.get(self.current).map(|(line, _)| *line)
+    }
+}
+
+/// Metadata of a PDF document, summarized above its extracted text
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfInfo {
+    pub title: Option<String>,
+    pub page_count: usize,
+}
+
+/// Lines of context kept above a focused line
+const FOCUS_CONTEXT: usize = 3;
+
+/// Most bytes read from a procfs or sysfs file, whatever size it reports
+const VIRTUAL_READ_LIMIT: u64 = 64 * 1024;
+
+/// Bytes of a binary file read at a time for its hex preview
+const HEX_CHUNK: u64 = 4096;
+
+/// Bytes per row of the hex preview; the scroll offset counts rows
+pub const HEX_ROW: u64 = 16;
+
+/// Leading pages of a PDF whose text is extracted for the preview
+const PDF_PREVIEW_PAGES: usize = 3;
+
+/// A text line to emphasize, with the byte ranges that matched
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineHighlight {
+    /// 1-based line number
+    pub line: usize,
+    pub ranges: Vec<Range<usize>>,
+}
+
+impl LineHighlight {
+    /// Split the first `max_chars` characters of `line` into runs, flagging
+    /// the runs that fall inside a matched range
+    pub fn segments(&self, line: &str, max_chars: usize) -> Vec<(String, bool)> {
+        let mut segments: Vec<(String, bool)> = Vec::new();
+        for (offset, c) in line.char_indices().take(max_chars) {
+            let matched = self.ranges.iter().any(|r| r.contains(&offset));
+            match segments.last_mut() {
+                Some((text, flag)) if *flag == matched => text.push(c),
+                _ => segments.push((c.to_string(), matched)),
+            }
+        }
+        segments
+    }
+}
+
+/// Split styled runs into rows of at most `width` characters; without
+/// `wrap`, only the first row is kept
+pub fn wrap_rows<T: Copy>(runs: &[(T, String)], width: usize, wrap: bool) -> Vec<Vec<(T, String)>> {
+    let width = width.max(1);
+    let mut rows: Vec<Vec<(T, String)>> = vec![Vec::new()];
+    let mut used = 0;
+
+    for (style, text) in runs {
+        let mut piece = String::new();
+        for c in text.chars() {
+            if used == width {
+                if let Some(row) = rows.last_mut().filter(|_| !piece.is_empty()) {
+                    row.push((*style, std::mem::take(&mut piece)));
+                }
+                if !wrap {
+                    return rows;
+                }
+                rows.push(Vec::new());
+                used = 0;
+            }
+            piece.push(c);
+            used += 1;
+        }
+        if let Some(row) = rows.last_mut().filter(|_| !piece.is_empty()) {
+            row.push((*style, piece));
+        }
+    }
+    rows
 }
 
 #[derive(Debug, Clone)]
 pub enum PreviewContent {
     Text(Vec<String>),
-    Binary(Vec<u8>),
+    Binary(HexChunk),
     Image(ImageInfo),
-    Directory(Vec<String>),
+    /// Tags and stream details of an audio or video file
+    Media(MediaInfo),
+    /// Pretty-printed JSON with foldable objects and arrays
+    Json(JsonDocument),
+    Directory(DirectoryPreview),
     Error(String),
     #[allow(dead_code)]
     Empty,
 }
 
+/// The part of a binary file loaded for its hex preview, read again
+/// around the scroll position as it moves
+#[derive(Debug, Clone)]
+pub struct HexChunk {
+    /// Offset of `bytes` in the file
+    pub start: u64,
+    pub bytes: Vec<u8>,
+    /// More of the file can be read on demand; a procfs or sysfs file is
+    /// read once, and `bytes` is all there is
+    pub streamed: bool,
+}
+
+impl HexChunk {
+    fn read(path: &Path, start: u64) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(start))?;
+        let mut bytes = Vec::new();
+        file.take(HEX_CHUNK).read_to_end(&mut bytes)?;
+        Ok(Self {
+            start,
+            bytes,
+            streamed: true,
+        })
+    }
+
+    fn end(&self) -> u64 {
+        self.start + self.bytes.len() as u64
+    }
+
+    /// Loaded bytes from `offset` on
+    pub fn from(&self, offset: u64) -> &[u8] {
+        let skip = offset
+            .saturating_sub(self.start)
+            .min(self.bytes.len() as u64);
+        &self.bytes[skip as usize..]
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct FileInfo {
     pub size: u64,
-    #[allow(dead_code)]
     pub modified: Option<std::time::SystemTime>,
     pub permissions: Option<u32>,
     pub mime_type: String,
     #[allow(dead_code)]
     pub line_count: Option<usize>,
+    /// procfs/sysfs file: `size` and `modified` are meaningless and left empty
+    pub virtual_fs: bool,
+}
+
+impl FileInfo {
+    pub fn size_label(&self) -> String {
+        if self.virtual_fs {
+            "n/a (virtual file)".to_string()
+        } else {
+            FilePreview::format_size(self.size)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DirectoryPreview {
+    pub entries: Vec<FileEntry>,
+    pub hidden_count: usize,
+}
+
+impl DirectoryPreview {
+    /// Counts and total apparent size of the listed entries, e.g.
+    /// "3 dirs, 12 files · 4.20 MB"; subdirectories are not descended into
+    pub fn summary(&self) -> String {
+        let dirs = self.entries.iter().filter(|entry| entry.is_dir).count();
+        let files = self.entries.len() - dirs;
+        let size: u64 = self
+            .entries
+            .iter()
+            .filter(|entry| !entry.is_dir)
+            .map(|entry| entry.size)
+            .sum();
+        let plural =
+            |n: usize, word: &str| format!("{} {}{}", n, word, if n == 1 { "" } else { "s" });
+        format!(
+            "{}, {} · {}",
+            plural(dirs, "dir"),
+            plural(files, "file"),
+            FilePreview::format_size(size)
+        )
+    }
+
+    /// Size of a listed file, or what kind of entry it is when a size
+    /// would mean nothing
+    pub fn annotation(entry: &FileEntry) -> String {
+        if entry.is_symlink && entry.is_dir {
+            "link".to_string()
+        } else if entry.is_dir {
+            "dir".to_string()
+        } else if is_virtual_fs(&entry.path) {
+            "virtual".to_string()
+        } else {
+            FilePreview::format_size(entry.size)
+        }
+    }
+}
+
+/// A preview built on a background thread so slow files do not stall the UI
+#[derive(Debug)]
+pub struct PreviewLoader {
+    pub path: PathBuf,
+    task: Task<Option<FilePreview>>,
+}
+
+impl PreviewLoader {
+    pub fn spawn(path: &Path, max_lines: usize, listing: &ListingOptions) -> Self {
+        Self::spawn_reusing(path, max_lines, listing, None)
+    }
+
+    /// Like [`Self::spawn`], but hands back `cached` when the file still
+    /// has the modification time and size it was loaded with, which the
+    /// worker checks so the UI thread never stats it
+    pub fn spawn_reusing(
+        path: &Path,
+        max_lines: usize,
+        listing: &ListingOptions,
+        cached: Option<FilePreview>,
+    ) -> Self {
+        let worker_path = path.to_path_buf();
+        let listing = listing.clone();
+        Self {
+            path: path.to_path_buf(),
+            task: Task::spawn(move |_| match cached {
+                Some(cached) if is_current(&cached) => Some(cached),
+                _ => FilePreview::new(&worker_path, max_lines, &listing).ok(),
+            }),
+        }
+    }
+
+    /// The preview if it gets ready within `timeout`, `None` inside when it
+    /// failed; returns it only once
+    pub fn wait(&mut self, timeout: Duration) -> Option<Option<FilePreview>> {
+        self.task.wait(timeout)
+    }
+
+    pub fn poll(&mut self) -> Option<Option<FilePreview>> {
+        self.task.poll()
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.task.is_pending()
+    }
+}
+
+/// Previews a [`PreviewCache`] holds before dropping the least recently used
+const PREVIEW_CACHE_SIZE: usize = 32;
+
+/// Recently loaded previews, so moving back over a file shows it at once.
+/// An entry is used only while the file keeps the modification time and
+/// size it was loaded with, which [`PreviewLoader::spawn_reusing`] checks.
+#[derive(Debug, Default)]
+pub struct PreviewCache {
+    /// Least recently used first
+    previews: VecDeque<FilePreview>,
+}
+
+impl PreviewCache {
+    /// Keep a freshly loaded preview; virtual files, which have no
+    /// modification time, change behind it and are not kept
+    pub fn insert(&mut self, preview: &FilePreview) {
+        if preview.file_info.modified.is_none() {
+            return;
+        }
+        self.previews.retain(|cached| cached.path != preview.path);
+        if self.previews.len() >= PREVIEW_CACHE_SIZE {
+            self.previews.pop_front();
+        }
+        self.previews.push_back(preview.clone());
+    }
+
+    /// The cached preview of `path`, which the file may have changed since
+    pub fn get(&mut self, path: &Path) -> Option<FilePreview> {
+        let index = self
+            .previews
+            .iter()
+            .position(|cached| cached.path == path)?;
+        let cached = self.previews.remove(index)?;
+        self.previews.push_back(cached.clone());
+        Some(cached)
+    }
+
+    pub fn clear(&mut self) {
+        self.previews.clear();
+    }
+}
+
+/// Whether the file still has the modification time and size `preview`
+/// was loaded with
+fn is_current(preview: &FilePreview) -> bool {
+    fs::metadata(&preview.path).is_ok_and(|metadata| {
+        metadata.modified().ok() == preview.file_info.modified
+            && metadata.len() == preview.file_info.size
+    })
+}
+
+/// README names looked for in a directory, in order of preference
+const README_NAMES: &[&str] = &["readme.md", "readme.markdown", "readme", "readme.txt"];
+
+/// Bytes of a README read for its opening lines
+const README_READ_LIMIT: u64 = 16 * 1024;
+
+/// File list rows always left above a README strip
+const MIN_LIST_ROWS: usize = 5;
+
+/// Opening lines of a directory's README, shown below the file list
+#[derive(Debug, Clone)]
+pub struct ReadmeStrip {
+    pub name: String,
+    pub lines: Vec<Vec<Span>>,
+}
+
+impl ReadmeStrip {
+    /// The first `max_lines` non-blank lines of the README in `dir`, with
+    /// Markdown rendered
+    pub fn load(dir: &Path, max_lines: usize) -> Option<Self> {
+        if max_lines == 0 {
+            return None;
+        }
+        let names: Vec<String> = fs::read_dir(dir)
+            .ok()?
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect();
+        let name = README_NAMES.iter().find_map(|wanted| {
+            names
+                .iter()
+                .find(|name| name.to_lowercase() == *wanted)
+                .cloned()
+        })?;
+
+        let mut text = String::new();
+        File::open(dir.join(&name))
+            .ok()?
+            .take(README_READ_LIMIT)
+            .read_to_string(&mut text)
+            .ok()?;
+        let source: Vec<String> = text
+            .lines()
+            .map(|line| line.replace('\t', "    "))
+            .collect();
+        let lines: Vec<Vec<Span>> = markdown::render(&source)
+            .into_iter()
+            .filter(|spans| !markdown::plain_text(spans).trim().is_empty())
+            .take(max_lines)
+            .collect();
+
+        (!lines.is_empty()).then_some(Self { name, lines })
+    }
+
+    /// Rows taken from a file list of `list_rows`: a title and the lines,
+    /// or nothing when the list would get too short
+    pub fn rows(&self, list_rows: usize) -> usize {
+        let rows = self.lines.len() + 1;
+        if list_rows >= rows + MIN_LIST_ROWS {
+            rows
+        } else {
+            0
+        }
+    }
+}
+
+/// Aggregate view of a multi-item selection
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectionSummary {
+    pub count: usize,
+    pub file_count: usize,
+    pub dir_count: usize,
+    /// Apparent size of the selected files (directory contents are not walked)
+    pub total_size: u64,
+    /// Entry counts per extension, most common first
+    pub type_breakdown: Vec<(String, usize)>,
+    pub common_parent: Option<PathBuf>,
+}
+
+impl SelectionSummary {
+    pub fn from_entries(entries: &[&FileEntry]) -> Self {
+        let mut by_type: Vec<(String, usize)> = Vec::new();
+        let mut file_count = 0;
+        let mut dir_count = 0;
+        let mut total_size = 0;
+
+        for entry in entries {
+            let kind = if entry.is_dir {
+                dir_count += 1;
+                "directory".to_string()
+            } else {
+                file_count += 1;
+                total_size += entry.size;
+                entry
+                    .path
+                    .extension()
+                    .map(|e| format!(".{}", e.to_string_lossy().to_lowercase()))
+                    .unwrap_or_else(|| "(no extension)".to_string())
+            };
+
+            match by_type.iter_mut().find(|(k, _)| *k == kind) {
+                Some((_, count)) => *count += 1,
+                None => by_type.push((kind, 1)),
+            }
+        }
+        by_type.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        Self {
+            count: entries.len(),
+            file_count,
+            dir_count,
+            total_size,
+            type_breakdown: by_type,
+            common_parent: Self::common_parent(entries),
+        }
+    }
+
+    fn common_parent(entries: &[&FileEntry]) -> Option<PathBuf> {
+        let mut parents = entries.iter().filter_map(|e| e.path.parent());
+        let mut common = parents.next()?.to_path_buf();
+        for parent in parents {
+            while !parent.starts_with(&common) {
+                if !common.pop() {
+                    return None;
+                }
+            }
+        }
+        Some(common)
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct ImageInfo {
-    #[allow(dead_code)]
+    /// The file's extension
     pub format: String,
-    #[allow(dead_code)]
+    /// Format, size and color type read from the file's header
+    pub header: Option<ImageHeader>,
+    /// Size of the original image, from its header or once decoded
     pub dimensions: Option<(u32, u32)>,
+    /// Decoded pixels, scaled down for display
+    pub image: Option<Arc<RgbaImage>>,
+    /// Shown instead of an image that cannot be decoded
     pub ascii_art: Option<String>,
+    /// Camera, date taken and the like, from the EXIF block
+    pub photo: Option<PhotoInfo>,
+}
+
+impl ImageInfo {
+    /// "PNG 1920×1080 · RGBA, 8-bit" summary line; a file whose contents
+    /// are not what its extension says is flagged
+    pub fn description(&self) -> String {
+        let Some(header) = self.header else {
+            return match self.dimensions {
+                Some((width, height)) => {
+                    format!("{} {}×{}", self.format.to_uppercase(), width, height)
+                }
+                None => self.format.to_uppercase(),
+            };
+        };
+        let (width, height) = header.dimensions;
+        let mut description = format!(
+            "{} {}×{} · {}",
+            header.format_name(),
+            width,
+            height,
+            header.color_label()
+        );
+        if ImageFormat::from_extension(&self.format).is_some_and(|f| f != header.format) {
+            description.push_str(&format!(" (named .{})", self.format));
+        }
+        description
+    }
+
+    /// EXIF lines shown below the description
+    pub fn details(&self) -> Vec<String> {
+        self.photo
+            .as_ref()
+            .map(PhotoInfo::lines)
+            .unwrap_or_default()
+    }
 }
 
 impl FilePreview {
-    pub fn new(path: &Path, max_lines: usize) -> Result<Self> {
+    pub fn new(path: &Path, max_lines: usize, listing: &ListingOptions) -> Result<Self> {
         let metadata = fs::metadata(path)?;
+        let virtual_fs = !metadata.is_dir() && is_virtual_fs(path);
 
         let file_info = FileInfo {
-            size: metadata.len(),
-            modified: metadata.modified().ok(),
+            size: if virtual_fs { 0 } else { metadata.len() },
+            modified: metadata.modified().ok().filter(|_| !virtual_fs),
             permissions: {
                 #[cfg(unix)]
                 {
@@ -61,22 +579,164 @@ impl FilePreview {
             },
             mime_type: Self::detect_mime_type(path),
             line_count: None,
+            virtual_fs,
         };
 
+        let mut pdf = None;
+        let mut mail = None;
+        let mut json_error = None;
         let content = if metadata.is_dir() {
-            Self::preview_directory(path, max_lines)?
+            Self::preview_directory(path, listing)
+        } else if virtual_fs {
+            Self::preview_virtual_file(path, max_lines)
+        } else if let Some((info, content)) =
+            Self::preview_pdf_file(path, max_lines, metadata.len())
+        {
+            pdf = Some(info);
+            content
+        } else if let Some(mailbox) = Self::preview_mail_file(path, metadata.len()) {
+            let content = PreviewContent::Text(mailbox.current_message().body.clone());
+            mail = Some(mailbox);
+            content
+        } else if let Some(parsed) = Self::preview_json_file(path, max_lines, metadata.len()) {
+            match parsed {
+                Ok(document) => PreviewContent::Json(document),
+                Err(error) => {
+                    json_error = Some(error);
+                    Self::preview_file(path, max_lines, metadata.len())?
+                }
+            }
         } else {
             Self::preview_file(path, max_lines, metadata.len())?
         };
+        let unit = match content {
+            PreviewContent::Text(ref lines) if systemd::is_unit_file(path) => {
+                Some(UnitFile::parse(&lines.join("\n")))
+            }
+            _ => None,
+        };
 
-        Ok(Self {
+        let markdown = match content {
+            PreviewContent::Text(ref lines) if markdown::is_markdown_file(path) => {
+                Some(markdown::render(lines))
+            }
+            _ => None,
+        };
+
+        let mut preview = Self {
+            path: path.to_path_buf(),
             content,
             file_info,
             scroll_offset: 0,
+            highlight: None,
+            unit,
+            pdf,
+            mail,
+            markdown,
+            show_raw: false,
+            json_error,
+            archive_entry: None,
+            search: None,
+        };
+        // Point at where a JSON file stops parsing
+        if let Some((line, column)) = preview.json_error.as_ref().map(|e| (e.line, e.column)) {
+            preview.focus_line(line, |text| {
+                let start = text
+                    .char_indices()
+                    .nth(column.saturating_sub(1))
+                    .map_or(text.len(), |(i, _)| i);
+                let width = text[start..].chars().next().map_or(0, char::len_utf8);
+                let error_char = start..start + width;
+                vec![error_char]
+            });
+        }
+        Ok(preview)
+    }
+
+    /// Text of the file at `inner` in the tarball at `archive`, kept under
+    /// the archive's path so it stays up while the archive is highlighted
+    pub fn for_archive_entry(archive: &Path, inner: &str, max_lines: usize) -> Result<Self> {
+        let text = archive::read_entry(archive, inner)?
+            .ok_or_else(|| anyhow!("{} not found in {}", inner, archive.display()))?;
+        let lines = text
+            .lines()
+            .take(max_lines)
+            .map(|line| line.replace('\t', "    "))
+            .collect();
+
+        Ok(Self {
+            path: archive.to_path_buf(),
+            content: PreviewContent::Text(lines),
+            file_info: FileInfo {
+                size: text.len() as u64,
+                modified: None,
+                permissions: None,
+                mime_type: Self::detect_mime_type(Path::new(inner)),
+                line_count: None,
+                virtual_fs: false,
+            },
+            scroll_offset: 0,
+            highlight: None,
+            unit: None,
+            pdf: None,
+            mail: None,
+            markdown: None,
+            show_raw: false,
+            json_error: None,
+            archive_entry: Some(inner.to_string()),
+            search: None,
         })
     }
 
-    fn detect_mime_type(path: &Path) -> String {
+    /// Rendered Markdown lines, unless the raw source was asked for
+    pub fn rendered_markdown(&self) -> Option<&[Vec<Span>]> {
+        self.markdown.as_deref().filter(|_| !self.show_raw)
+    }
+
+    /// Switch between rendered Markdown and its source; `false` when the
+    /// file is not Markdown
+    pub fn toggle_raw(&mut self) -> bool {
+        if self.markdown.is_none() {
+            return false;
+        }
+        self.show_raw = !self.show_raw;
+        true
+    }
+
+    /// Lines shown above the content: the key settings of a systemd unit,
+    /// the headers of an email, or the title and page count of a PDF
+    pub fn summary_lines(&self) -> Vec<String> {
+        if let Some(ref inner) = self.archive_entry {
+            return vec![format!("[Archive entry] {}", inner)];
+        }
+        if let Some(ref unit) = self.unit {
+            return unit.summary_lines();
+        }
+        if let Some(ref mailbox) = self.mail {
+            return mailbox.summary_lines();
+        }
+        if let Some(ref error) = self.json_error {
+            return vec![format!("⚠️  Invalid JSON: {}", error.message)];
+        }
+        let Some(ref pdf) = self.pdf else {
+            return Vec::new();
+        };
+        let mut lines = Vec::new();
+        if let Some(ref title) = pdf.title {
+            lines.push(format!("Title: {}", title));
+        }
+        lines.push(if pdf.page_count > PDF_PREVIEW_PAGES {
+            format!(
+                "Pages: {} (text of the first {})",
+                pdf.page_count, PDF_PREVIEW_PAGES
+            )
+        } else {
+            format!("Pages: {}", pdf.page_count)
+        });
+        lines
+    }
+
+    pub fn detect_mime_type(path: &Path) -> String {
         if path.is_dir() {
             return "inode/directory".to_string();
         }
@@ -119,6 +779,7 @@ impl FilePreview {
             "svg" => "image/svg+xml",
             "ico" => "image/x-icon",
             "webp" => "image/webp",
+            "tif" | "tiff" => "image/tiff",
 
             // Archives
             "zip" => "application/zip",
@@ -137,20 +798,144 @@ impl FilePreview {
 
             // Media
             "mp3" => "audio/mpeg",
+            "flac" => "audio/flac",
+            "m4a" => "audio/mp4",
             "wav" => "audio/wav",
             "ogg" => "audio/ogg",
-            "mp4" => "video/mp4",
+            "mka" => "audio/x-matroska",
+            "mp4" | "m4v" => "video/mp4",
+            "mov" => "video/quicktime",
             "avi" => "video/x-msvideo",
             "mkv" => "video/x-matroska",
+            "webm" => "video/webm",
 
             _ => "application/octet-stream",
         }
         .to_string()
     }
 
+    /// Whether a file is better handled by an external application than a text editor
+    pub fn prefers_system_handler(path: &Path) -> bool {
+        let mime_type = Self::detect_mime_type(path);
+        match mime_type.split('/').next() {
+            Some("image" | "audio" | "video") => true,
+            Some("application") => !matches!(
+                mime_type.as_str(),
+                "application/json" | "application/octet-stream"
+            ),
+            _ => false,
+        }
+    }
+
+    /// Text of the first pages of a PDF, or `None` when the file is not a
+    /// readable PDF and should be previewed like any other file
+    fn preview_pdf_file(
+        path: &Path,
+        max_lines: usize,
+        file_size: u64,
+    ) -> Option<(PdfInfo, PreviewContent)> {
+        if file_size > 10 * 1024 * 1024 || Self::detect_mime_type(path) != "application/pdf" {
+            return None;
+        }
+        let document = lopdf::Document::load(path).ok()?;
+        let pages: Vec<u32> = document.get_pages().keys().copied().collect();
+
+        let title = document
+            .trailer
+            .get(b"Info")
+            .ok()
+            .and_then(|info| match info {
+                lopdf::Object::Reference(id) => document.get_dictionary(*id).ok(),
+                lopdf::Object::Dictionary(dict) => Some(dict),
+                _ => None,
+            })
+            .and_then(|info| info.get(b"Title").ok())
+            .and_then(|title| lopdf::decode_text_string(title).ok())
+            .map(|title| title.trim().to_string())
+            .filter(|title| !title.is_empty());
+
+        let text = document
+            .extract_text(&pages[..pages.len().min(PDF_PREVIEW_PAGES)])
+            .unwrap_or_default();
+        let mut lines: Vec<String> = text
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+            .take(max_lines)
+            .map(|line| line.replace('\t', "    "))
+            .collect();
+        if lines.is_empty() {
+            lines.push("(no extractable text)".to_string());
+        }
+
+        Some((
+            PdfInfo {
+                title,
+                page_count: pages.len(),
+            },
+            PreviewContent::Text(lines),
+        ))
+    }
+
+    /// Parsed JSON document, or `None` for other files
+    fn preview_json_file(
+        path: &Path,
+        max_lines: usize,
+        file_size: u64,
+    ) -> Option<Result<JsonDocument, ParseError>> {
+        if file_size > 10 * 1024 * 1024 || Self::detect_mime_type(path) != "application/json" {
+            return None;
+        }
+        let source = fs::read_to_string(path).ok()?;
+        Some(JsonDocument::parse(&source, max_lines))
+    }
+
+    /// Messages of an `.eml` file or mbox spool, or `None` for other files
+    fn preview_mail_file(path: &Path, file_size: u64) -> Option<Mailbox> {
+        if file_size > 10 * 1024 * 1024 {
+            return None;
+        }
+        let mut head = [0; 1024];
+        let head_len = File::open(path).and_then(|mut f| f.read(&mut head)).ok()?;
+        let head = String::from_utf8_lossy(&head[..head_len]);
+        let eml = mail::is_eml_file(path);
+        if !eml && !mail::is_mbox(path, &head) {
+            return None;
+        }
+
+        let raw = fs::read(path).ok()?;
+        let raw = String::from_utf8_lossy(&raw);
+        let messages = if eml {
+            vec![MailMessage::parse(&raw)]
+        } else {
+            mail::parse_mbox(&raw)
+        };
+        (!messages.is_empty()).then_some(Mailbox {
+            messages,
+            current: 0,
+        })
+    }
+
     fn preview_file(path: &Path, max_lines: usize, file_size: u64) -> Result<PreviewContent> {
-        // Don't preview files larger than 10MB
+        // Media files are large, but only their headers are read
+        if media::is_media_file(path) {
+            if let Some(info) = MediaInfo::read(path) {
+                return Ok(PreviewContent::Media(info));
+            }
+        }
+
+        // Don't preview files larger than 10MB, past an image's header
         if file_size > 10 * 1024 * 1024 {
+            if let Some(header) = graphics::probe(path) {
+                return Ok(PreviewContent::Image(ImageInfo {
+                    format: Self::extension(path),
+                    header: Some(header),
+                    dimensions: Some(header.dimensions),
+                    image: None,
+                    ascii_art: None,
+                    photo: PhotoInfo::read(path),
+                }));
+            }
             return Ok(PreviewContent::Error(
                 "File too large to preview".to_string(),
             ));
@@ -214,25 +999,94 @@ impl FilePreview {
     }
 
     fn preview_binary_file(path: &Path) -> Result<PreviewContent> {
-        let mut file = File::open(path)?;
-        let mut buffer = vec![0; 256]; // First 256 bytes for hex preview
-        let bytes_read = file.read(&mut buffer)?;
-        buffer.truncate(bytes_read);
+        Ok(PreviewContent::Binary(HexChunk::read(path, 0)?))
+    }
+
+    /// Preview a procfs/sysfs file from a single bounded, non-blocking read
+    ///
+    /// Their reported sizes are 0 or 4096 regardless of content, and some
+    /// (`/proc/kmsg`, trace pipes) block until data arrives. NUL separators,
+    /// as in `/proc/<pid>/cmdline` and `environ`, are shown as line breaks.
+    fn preview_virtual_file(path: &Path, max_lines: usize) -> PreviewContent {
+        let mut options = fs::OpenOptions::new();
+        options.read(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.custom_flags(libc::O_NONBLOCK);
+        }
+
+        let mut buffer = Vec::new();
+        let result = options
+            .open(path)
+            .and_then(|file| file.take(VIRTUAL_READ_LIMIT + 1).read_to_end(&mut buffer));
+        match result {
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                return PreviewContent::Error("No data available without blocking".to_string())
+            }
+            Err(e) => return PreviewContent::Error(format!("Cannot read file: {}", e)),
+        }
 
-        Ok(PreviewContent::Binary(buffer))
+        let truncated = buffer.len() as u64 > VIRTUAL_READ_LIMIT;
+        buffer.truncate(VIRTUAL_READ_LIMIT as usize);
+        if buffer.is_empty() {
+            return PreviewContent::Text(Vec::new());
+        }
+
+        let is_text = buffer
+            .iter()
+            .all(|&b| b >= 0x20 || matches!(b, 0x00 | 0x09 | 0x0A | 0x0D));
+        let text = match std::str::from_utf8(&buffer) {
+            Ok(text) if is_text => text,
+            _ => {
+                return PreviewContent::Binary(HexChunk {
+                    start: 0,
+                    bytes: buffer,
+                    streamed: false,
+                })
+            }
+        };
+
+        let mut lines: Vec<String> = text
+            .trim_end_matches(['\0', '\n'])
+            .split(['\0', '\n'])
+            .take(max_lines)
+            .map(|line| line.replace('\t', "    "))
+            .collect();
+        if truncated && lines.len() < max_lines {
+            lines.push(format!(
+                "… (only the first {} shown)",
+                Self::format_size(VIRTUAL_READ_LIMIT)
+            ));
+        }
+        PreviewContent::Text(lines)
     }
 
-    fn preview_image_file(path: &Path) -> Result<PreviewContent> {
-        let ext = path
-            .extension()
+    fn extension(path: &Path) -> String {
+        path.extension()
             .and_then(|e| e.to_str())
             .unwrap_or("")
-            .to_lowercase();
+            .to_lowercase()
+    }
+
+    fn preview_image_file(path: &Path) -> Result<PreviewContent> {
+        let ext = Self::extension(path);
 
+        let header = graphics::probe(path);
+        let decoded = graphics::decode(path);
         let image_info = ImageInfo {
-            format: ext.clone(),
-            dimensions: None, // Would need image crate to get actual dimensions
-            ascii_art: Self::generate_ascii_placeholder(&ext),
+            ascii_art: match decoded {
+                Some(_) => None,
+                None => Self::generate_ascii_placeholder(&ext),
+            },
+            dimensions: header
+                .map(|header| header.dimensions)
+                .or(decoded.as_ref().map(|(_, dimensions)| *dimensions)),
+            header,
+            image: decoded.map(|(image, _)| Arc::new(image)),
+            photo: PhotoInfo::read(path),
+            format: ext,
         };
 
         Ok(PreviewContent::Image(image_info))
@@ -245,9 +1099,9 @@ impl FilePreview {
     ┌───────────────┐
     │   🖼️ IMAGE    │
     │               │
-    │   [Preview    │
-    │    not yet    │
-    │   available]  │
+    │   [Cannot     │
+    │   decode      │
+    │   image]      │
     │               │
     └───────────────┘"#
             }
@@ -267,48 +1121,247 @@ impl FilePreview {
         Some(art.to_string())
     }
 
-    fn preview_directory(path: &Path, max_entries: usize) -> Result<PreviewContent> {
-        let mut entries = Vec::new();
-        let mut count = 0;
+    fn preview_directory(path: &Path, listing: &ListingOptions) -> PreviewContent {
+        // The whole listing is kept so the preview can scroll through all of it
+        match read_directory(path, listing) {
+            Ok(listing) => PreviewContent::Directory(DirectoryPreview {
+                entries: listing.entries,
+                hidden_count: listing.hidden_count,
+            }),
+            Err(e) => PreviewContent::Error(format!("Cannot read directory: {}", e)),
+        }
+    }
 
-        if let Ok(read_dir) = fs::read_dir(path) {
-            for entry in read_dir.flatten() {
-                if count >= max_entries {
-                    entries.push("...".to_string());
-                    break;
-                }
+    /// Scroll text so 1-based `line` is in view and highlight what
+    /// `matcher` finds in it
+    pub fn focus_line(&mut self, line: usize, matcher: impl Fn(&str) -> Vec<Range<usize>>) {
+        // Line numbers count from the top of the spool, not of a message body
+        if let Some(index) = self.mail.as_ref().and_then(|mailbox| {
+            mailbox
+                .messages
+                .iter()
+                .rposition(|message| message.start_line <= line)
+        }) {
+            self.show_message(index);
+            return;
+        }
+        let PreviewContent::Text(ref lines) = self.content else {
+            return;
+        };
+        let Some(text) = line.checked_sub(1).and_then(|i| lines.get(i)) else {
+            return;
+        };
 
-                let file_name = entry.file_name().to_string_lossy().to_string();
-                let file_type = if entry.path().is_dir() {
-                    "📁"
-                } else {
-                    "📄"
-                };
+        self.highlight = Some(LineHighlight {
+            line,
+            ranges: matcher(text),
+        });
+        // Match ranges are byte offsets into the source
+        self.show_raw = true;
+        self.scroll_offset = line.saturating_sub(1 + FOCUS_CONTEXT);
+    }
+
+    pub fn next_message(&mut self) {
+        if let Some(current) = self.mail.as_ref().map(|mailbox| mailbox.current) {
+            self.show_message(current + 1);
+        }
+    }
 
-                entries.push(format!("{} {}", file_type, file_name));
-                count += 1;
+    pub fn previous_message(&mut self) {
+        if let Some(current) = self.mail.as_ref().map(|mailbox| mailbox.current) {
+            self.show_message(current.saturating_sub(1));
+        }
+    }
+
+    fn show_message(&mut self, index: usize) {
+        let Some(ref mut mailbox) = self.mail else {
+            return;
+        };
+        if index >= mailbox.messages.len() {
+            return;
+        }
+        mailbox.current = index;
+        self.content = PreviewContent::Text(mailbox.current_message().body.clone());
+        self.scroll_offset = 0;
+        self.highlight = None;
+        self.refresh_search();
+    }
+
+    /// Fold or unfold the JSON object or array on the first visible line
+    pub fn toggle_fold(&mut self) {
+        if let PreviewContent::Json(ref mut document) = self.content {
+            if let Some(line) = document.toggle_fold(self.scroll_offset) {
+                self.scroll_offset = line;
             }
+            self.refresh_search();
         }
+    }
+
+    /// Lines the search looks through: text as shown, or the pretty-printed
+    /// JSON
+    fn searchable_lines(&self) -> Vec<String> {
+        match &self.content {
+            PreviewContent::Text(lines) => lines.clone(),
+            PreviewContent::Json(document) => document.lines.iter().map(|l| l.text()).collect(),
+            PreviewContent::Media(info) => info.lines(),
+            _ => Vec::new(),
+        }
+    }
 
-        if entries.is_empty() {
-            entries.push("(empty directory)".to_string());
+    /// Find `pattern` and bring the first match from where the search began
+    /// into view; an empty pattern scrolls back there
+    pub fn search(&mut self, pattern: &str) {
+        let origin = self
+            .search
+            .as_ref()
+            .map_or(self.scroll_offset, |search| search.origin);
+        let matches = PreviewSearch::find(&self.searchable_lines(), pattern);
+        let current = matches
+            .iter()
+            .position(|(line, _)| *line >= origin)
+            .unwrap_or(0);
+        self.search = Some(PreviewSearch {
+            pattern: pattern.to_string(),
+            matches,
+            current,
+            origin,
+        });
+        // Match ranges are byte offsets into the source
+        self.show_raw = true;
+        match self.search.as_ref().and_then(PreviewSearch::current_line) {
+            Some(line) => self.scroll_offset = line.saturating_sub(FOCUS_CONTEXT),
+            None => self.scroll_offset = origin,
         }
+    }
+
+    /// Drop the search and scroll back to where it began
+    pub fn cancel_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            self.scroll_offset = search.origin;
+        }
+    }
+
+    /// Move to the next match, or the previous one, wrapping around the
+    /// ends; returns the 1-based position of the match and the total
+    pub fn jump_to_match(&mut self, forward: bool) -> Option<(usize, usize)> {
+        let search = self.search.as_mut().filter(|s| !s.matches.is_empty())?;
+        let count = search.matches.len();
+        search.current = if forward {
+            (search.current + 1) % count
+        } else {
+            (search.current + count - 1) % count
+        };
+        let line = search.current_line()?;
+        let position = (search.current + 1, count);
+        self.scroll_offset = line.saturating_sub(FOCUS_CONTEXT);
+        Some(position)
+    }
+
+    /// Match again after the content changed under the search
+    fn refresh_search(&mut self) {
+        let Some(pattern) = self.search.as_ref().map(|s| s.pattern.clone()) else {
+            return;
+        };
+        let matches = PreviewSearch::find(&self.searchable_lines(), &pattern);
+        if let Some(search) = self.search.as_mut() {
+            search.matches = matches;
+            search.current = 0;
+        }
+    }
+
+    /// Whether 0-based line `index` holds the focused line or the search
+    /// match in view, whose numbers stand out in the gutter
+    pub fn is_marked(&self, index: usize) -> bool {
+        self.highlight.as_ref().is_some_and(|h| h.line == index + 1)
+            || self.search.as_ref().and_then(PreviewSearch::current_line) == Some(index)
+    }
 
-        Ok(PreviewContent::Directory(entries))
+    /// Search matches on 0-based line `index`, together with the match
+    /// brought into view by [`FilePreview::focus_line`]
+    pub fn line_highlight(&self, index: usize) -> Option<LineHighlight> {
+        let mut ranges: Vec<Range<usize>> = self
+            .highlight
+            .iter()
+            .filter(|highlight| highlight.line == index + 1)
+            .flat_map(|highlight| highlight.ranges.iter().cloned())
+            .collect();
+        if let Some(search) = &self.search {
+            ranges.extend(
+                search
+                    .matches
+                    .iter()
+                    .filter(|(line, _)| *line == index)
+                    .map(|(_, range)| range.clone()),
+            );
+        }
+        let focused = self.highlight.as_ref().is_some_and(|h| h.line == index + 1);
+        (focused || !ranges.is_empty()).then_some(LineHighlight {
+            line: index + 1,
+            ranges,
+        })
     }
 
     pub fn scroll_up(&mut self, lines: usize) {
         self.scroll_offset = self.scroll_offset.saturating_sub(lines);
+        self.load_hex();
     }
 
     pub fn scroll_down(&mut self, lines: usize) {
         let max_offset = match &self.content {
             PreviewContent::Text(text) => text.len().saturating_sub(1),
-            PreviewContent::Directory(entries) => entries.len().saturating_sub(1),
+            PreviewContent::Json(document) => document.lines.len().saturating_sub(1),
+            PreviewContent::Directory(dir) => dir.entries.len().saturating_sub(1),
+            PreviewContent::Binary(_) => (self.hex_len().saturating_sub(1) / HEX_ROW) as usize,
             _ => 0,
         };
 
         self.scroll_offset = (self.scroll_offset + lines).min(max_offset);
+        self.load_hex();
+    }
+
+    /// Bytes the hex preview can page through
+    pub fn hex_len(&self) -> u64 {
+        match &self.content {
+            PreviewContent::Binary(chunk) if chunk.streamed => self.file_info.size,
+            PreviewContent::Binary(chunk) => chunk.end(),
+            _ => 0,
+        }
+    }
+
+    /// Offset of the first byte in view of the hex preview
+    pub fn hex_offset(&self) -> u64 {
+        self.scroll_offset as u64 * HEX_ROW
+    }
+
+    /// Bring the row holding `offset` to the top of the hex preview; false
+    /// when the offset is past the end
+    pub fn seek_hex(&mut self, offset: u64) -> bool {
+        if offset >= self.hex_len() {
+            return false;
+        }
+        self.scroll_offset = (offset / HEX_ROW) as usize;
+        self.load_hex();
+        true
+    }
+
+    /// Read the chunk of a binary file around the scroll position unless
+    /// the rows in view, and half a chunk after them, are loaded already
+    fn load_hex(&mut self) {
+        let offset = self.hex_offset();
+        let PreviewContent::Binary(ref chunk) = self.content else {
+            return;
+        };
+        let loaded = offset >= chunk.start
+            && (offset + HEX_CHUNK / 2 <= chunk.end() || chunk.end() >= self.file_info.size);
+        if !chunk.streamed || loaded {
+            return;
+        }
+        // Start half a chunk back so scrolling up stays in memory too
+        let start = offset.saturating_sub(HEX_CHUNK / 2);
+        self.content = match HexChunk::read(&self.path, start) {
+            Ok(chunk) => PreviewContent::Binary(chunk),
+            Err(e) => PreviewContent::Error(format!("Cannot read file: {}", e)),
+        };
     }
 
     pub fn format_size(bytes: u64) -> String {
@@ -374,6 +1427,566 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_focus_line_highlights_match() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("notes.txt");
+        let text: Vec<String> = (1..=100).map(|i| format!("line {}", i)).collect();
+        fs::write(&path, text.join("\n")).unwrap();
+
+        let mut preview = FilePreview::new(&path, 150, &ListingOptions::default()).unwrap();
+        preview.focus_line(80, |line| {
+            line.find("80").map(|i| i..i + 2).into_iter().collect()
+        });
+        assert_eq!(preview.scroll_offset, 76);
+
+        let highlight = preview.highlight.clone().unwrap();
+        assert_eq!(highlight.line, 80);
+        assert_eq!(
+            highlight.segments("line 80", 10),
+            vec![("line ".to_string(), false), ("80".to_string(), true)]
+        );
+        assert_eq!(
+            highlight.segments("line 80", 6),
+            vec![("line ".to_string(), false), ("8".to_string(), true)]
+        );
+
+        // Out of range lines leave the preview untouched
+        preview.focus_line(500, |_| Vec::new());
+        assert_eq!(preview.highlight.unwrap().line, 80);
+    }
+
+    #[test]
+    fn test_directory_preview_lists_everything() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        for i in 0..30 {
+            fs::write(temp_dir.path().join(format!("file{:02}", i)), "x").unwrap();
+        }
+        fs::write(temp_dir.path().join(".hidden"), "").unwrap();
+
+        let mut preview =
+            FilePreview::new(temp_dir.path(), 10, &ListingOptions::default()).unwrap();
+        match &preview.content {
+            PreviewContent::Directory(dir) => {
+                assert_eq!(dir.entries.len(), 30);
+                assert_eq!(dir.hidden_count, 1);
+                assert_eq!(dir.summary(), "0 dirs, 30 files · 30 B");
+                assert_eq!(DirectoryPreview::annotation(&dir.entries[0]), "1 B");
+            }
+            other => panic!("unexpected preview: {:?}", other),
+        }
+
+        preview.scroll_down(25);
+        assert_eq!(preview.scroll_offset, 25);
+
+        fs::create_dir(temp_dir.path().join("sub")).unwrap();
+        let preview = FilePreview::new(temp_dir.path(), 10, &ListingOptions::default()).unwrap();
+        let PreviewContent::Directory(dir) = preview.content else {
+            panic!("unexpected preview: {:?}", preview.content);
+        };
+        assert_eq!(dir.summary(), "1 dir, 30 files · 30 B");
+        assert_eq!(DirectoryPreview::annotation(&dir.entries[0]), "dir");
+    }
+
+    #[test]
+    fn test_image_preview_decodes_pixels() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("dot.png");
+        RgbaImage::from_pixel(3, 2, image::Rgba([255, 0, 0, 255]))
+            .save(&path)
+            .unwrap();
+        fs::write(temp_dir.path().join("broken.png"), [0x89, b'P', 0, 0]).unwrap();
+
+        let listing = ListingOptions::default();
+        let preview = FilePreview::new(&path, 10, &listing).unwrap();
+        match &preview.content {
+            PreviewContent::Image(info) => {
+                assert_eq!(info.description(), "PNG 3×2 · RGBA, 8-bit");
+                assert!(info.image.is_some());
+                assert!(info.ascii_art.is_none());
+            }
+            other => panic!("unexpected preview: {:?}", other),
+        }
+
+        let broken = FilePreview::new(&temp_dir.path().join("broken.png"), 10, &listing).unwrap();
+        match &broken.content {
+            PreviewContent::Image(info) => {
+                assert!(info.image.is_none());
+                assert!(info.ascii_art.is_some());
+                assert!(info.details().is_empty());
+            }
+            other => panic!("unexpected preview: {:?}", other),
+        }
+
+        // EXIF details show even when there are no pixels to draw
+        let photo = temp_dir.path().join("photo.TIFF");
+        fs::write(&photo, crate::photo::tests::tiff_with_exif()).unwrap();
+        match FilePreview::new(&photo, 10, &listing).unwrap().content {
+            PreviewContent::Image(info) => {
+                assert_eq!(info.details()[0], "Camera: Canon EOS R6");
+            }
+            other => panic!("unexpected preview: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_image_header_details() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let listing = ListingOptions::default();
+        let description = |path: &Path| match FilePreview::new(path, 10, &listing).unwrap().content
+        {
+            PreviewContent::Image(info) => info.description(),
+            other => panic!("unexpected preview: {:?}", other),
+        };
+
+        let gray = temp_dir.path().join("scan.png");
+        image::ImageBuffer::<image::Luma<u16>, _>::new(640, 480)
+            .save(&gray)
+            .unwrap();
+        assert_eq!(description(&gray), "PNG 640×480 · grayscale, 16-bit");
+
+        // The header is enough when the pixels cannot be decoded
+        let bytes = fs::read(&gray).unwrap();
+        let truncated = temp_dir.path().join("truncated.png");
+        fs::write(&truncated, &bytes[..bytes.len() - 20]).unwrap();
+        assert!(graphics::decode(&truncated).is_none());
+        assert_eq!(description(&truncated), "PNG 640×480 · grayscale, 16-bit");
+
+        let misnamed = temp_dir.path().join("photo.jpg");
+        fs::write(&misnamed, &bytes).unwrap();
+        assert_eq!(
+            description(&misnamed),
+            "PNG 640×480 · grayscale, 16-bit (named .jpg)"
+        );
+    }
+
+    #[test]
+    fn test_search_in_preview() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("log.txt");
+        let text: String = (1..=40)
+            .map(|i| {
+                if i % 10 == 0 {
+                    format!("ERROR {} error\n", i)
+                } else {
+                    format!("ok {}\n", i)
+                }
+            })
+            .collect();
+        fs::write(&path, text).unwrap();
+        let mut preview = FilePreview::new(&path, 100, &ListingOptions::default()).unwrap();
+        preview.scroll_offset = 15;
+
+        // Typing narrows the pattern; the first match below the view is shown
+        preview.search("e");
+        preview.search("err");
+        let search = preview.search.clone().unwrap();
+        assert_eq!(search.matches.len(), 8);
+        assert_eq!(search.matches[2], (19, 0..3));
+        assert_eq!(search.current, 2);
+        assert_eq!(preview.scroll_offset, 19 - FOCUS_CONTEXT);
+        assert!(preview.is_marked(19));
+        assert_eq!(
+            preview.line_highlight(19).unwrap().ranges,
+            vec![0..3, 9..12]
+        );
+        assert!(preview.line_highlight(18).is_none());
+
+        assert_eq!(preview.jump_to_match(false), Some((2, 8)));
+        assert_eq!(preview.jump_to_match(true), Some((3, 8)));
+        preview.search.as_mut().unwrap().current = 7;
+        assert_eq!(preview.jump_to_match(true), Some((1, 8)));
+        assert_eq!(preview.scroll_offset, 9 - FOCUS_CONTEXT);
+
+        preview.cancel_search();
+        assert!(preview.search.is_none());
+        assert_eq!(preview.scroll_offset, 15);
+        assert_eq!(preview.jump_to_match(true), None);
+    }
+
+    #[test]
+    fn test_preview_loader() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("notes.txt");
+        fs::write(&path, "one\ntwo\n").unwrap();
+
+        let mut loader = PreviewLoader::spawn(&path, 10, &ListingOptions::default());
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        let result = loop {
+            if let Some(result) = loader.poll() {
+                break result;
+            }
+            assert!(std::time::Instant::now() < deadline);
+            std::thread::sleep(Duration::from_millis(5));
+        };
+        assert!(
+            matches!(result.unwrap().content, PreviewContent::Text(ref lines) if lines.len() == 2)
+        );
+        assert!(!loader.is_loading());
+        assert!(loader.poll().is_none());
+
+        // A file that vanished gives a failed load, not a hang
+        let mut missing = PreviewLoader::spawn(
+            &temp_dir.path().join("gone"),
+            10,
+            &ListingOptions::default(),
+        );
+        let result = loop {
+            if let Some(result) = missing.poll() {
+                break result;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        };
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_preview_cache() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let listing = ListingOptions::default();
+        let mut cache = PreviewCache::default();
+        let paths: Vec<PathBuf> = (0..=PREVIEW_CACHE_SIZE)
+            .map(|i| temp_dir.path().join(format!("{}.txt", i)))
+            .collect();
+        for path in &paths {
+            fs::write(path, "one\n").unwrap();
+            cache.insert(&FilePreview::new(path, 10, &listing).unwrap());
+        }
+
+        // The first file was the least recently used one past the limit
+        assert!(cache.get(&paths[0]).is_none());
+        assert_eq!(cache.get(&paths[1]).unwrap().path, paths[1]);
+
+        // The loader hands back an unchanged file's entry, and loads one
+        // that changed afresh
+        let lines = |cached: Option<FilePreview>| {
+            let mut loader = PreviewLoader::spawn_reusing(&paths[2], 10, &listing, cached);
+            match loader
+                .wait(Duration::from_secs(10))
+                .flatten()
+                .unwrap()
+                .content
+            {
+                PreviewContent::Text(lines) => lines.len(),
+                _ => 0,
+            }
+        };
+        let mut stale = cache.get(&paths[2]).unwrap();
+        stale.content = PreviewContent::Text(Vec::new());
+        assert_eq!(lines(Some(stale.clone())), 0);
+        fs::write(&paths[2], "one\ntwo\n").unwrap();
+        assert_eq!(lines(Some(stale)), 2);
+
+        cache.clear();
+        assert!(cache.get(&paths[3]).is_none());
+    }
+
+    #[test]
+    fn test_readme_strip() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        assert!(ReadmeStrip::load(temp_dir.path(), 3).is_none());
+
+        fs::write(
+            temp_dir.path().join("README.md"),
+            "# Project\n\nA **fast** tool.\n\n- one\n- two\n",
+        )
+        .unwrap();
+        let strip = ReadmeStrip::load(temp_dir.path(), 3).unwrap();
+        assert_eq!(strip.name, "README.md");
+        let text: Vec<String> = strip
+            .lines
+            .iter()
+            .map(|l| markdown::plain_text(l))
+            .collect();
+        assert_eq!(text, vec!["Project", "A fast tool.", "• one"]);
+        assert_eq!(strip.rows(20), 4);
+        assert_eq!(strip.rows(8), 0);
+        assert!(ReadmeStrip::load(temp_dir.path(), 0).is_none());
+    }
+
+    #[test]
+    fn test_wrap_rows() {
+        let runs = vec![(1, "abcd".to_string()), (2, "efg".to_string())];
+        assert_eq!(
+            wrap_rows(&runs, 3, true),
+            vec![
+                vec![(1, "abc".to_string())],
+                vec![(1, "d".to_string()), (2, "ef".to_string())],
+                vec![(2, "g".to_string())],
+            ]
+        );
+        assert_eq!(
+            wrap_rows(&runs, 5, false),
+            vec![vec![(1, "abcd".to_string()), (2, "e".to_string())]]
+        );
+        assert_eq!(wrap_rows::<u8>(&[], 5, true), vec![Vec::new()]);
+    }
+
+    #[test]
+    fn test_json_preview() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let valid = temp_dir.path().join("valid.json");
+        let invalid = temp_dir.path().join("invalid.json");
+        fs::write(&valid, r#"{"a":[1,2]}"#).unwrap();
+        fs::write(&invalid, "{\n  \"a\": 1\n  \"b\": 2\n}").unwrap();
+
+        let listing = ListingOptions::default();
+        let mut preview = FilePreview::new(&valid, 50, &listing).unwrap();
+        match &preview.content {
+            PreviewContent::Json(document) => assert_eq!(document.lines.len(), 6),
+            other => panic!("unexpected preview: {:?}", other),
+        }
+        preview.scroll_down(1);
+        preview.toggle_fold();
+        match &preview.content {
+            PreviewContent::Json(document) => assert_eq!(document.lines.len(), 3),
+            other => panic!("unexpected preview: {:?}", other),
+        }
+
+        let preview = FilePreview::new(&invalid, 50, &listing).unwrap();
+        assert!(matches!(preview.content, PreviewContent::Text(_)));
+        assert!(preview.summary_lines()[0].starts_with("⚠️  Invalid JSON: expected"));
+        let highlight = preview.highlight.unwrap();
+        assert_eq!(highlight.line, 3);
+        assert_eq!(highlight.ranges[0], 2..3);
+    }
+
+    #[test]
+    fn test_mbox_preview_pages_messages() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("root");
+        fs::write(
+            &path,
+            "From cron@host Mon Oct  5 03:00:00 2026\n\
+             From: cron@host\n\
+             Subject: backup done\n\
+             \n\
+             OK\n\
+             \n\
+             From cron@host Tue Oct  6 03:00:00 2026\n\
+             From: cron@host\n\
+             Subject: backup failed\n\
+             \n\
+             disk full\n",
+        )
+        .unwrap();
+
+        let mut preview = FilePreview::new(&path, 50, &ListingOptions::default()).unwrap();
+        assert_eq!(
+            preview.summary_lines(),
+            vec!["Message 1 of 2", "From: cron@host", "Subject: backup done"]
+        );
+        preview.next_message();
+        preview.next_message();
+        assert_eq!(preview.summary_lines()[0], "Message 2 of 2");
+        assert!(matches!(&preview.content, PreviewContent::Text(lines) if lines == &["disk full"]));
+
+        // Search results point at lines of the whole spool
+        preview.focus_line(5, |_| Vec::new());
+        assert_eq!(preview.summary_lines()[0], "Message 1 of 2");
+    }
+
+    /// A PDF with one page of text per entry in `pages`
+    fn write_pdf(path: &Path, title: &str, pages: &[&str]) {
+        use lopdf::content::{Content, Operation};
+        use lopdf::{dictionary, Document, Object, Stream};
+
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Courier",
+        });
+        let resources_id = doc.add_object(dictionary! {
+            "Font" => dictionary! { "F1" => font_id },
+        });
+        let kids: Vec<Object> = pages
+            .iter()
+            .map(|text| {
+                let content = Content {
+                    operations: vec![
+                        Operation::new("BT", vec![]),
+                        Operation::new("Tf", vec!["F1".into(), 12.into()]),
+                        Operation::new("Td", vec![72.into(), 720.into()]),
+                        Operation::new("Tj", vec![Object::string_literal(*text)]),
+                        Operation::new("ET", vec![]),
+                    ],
+                };
+                let content_id =
+                    doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+                doc.add_object(dictionary! {
+                    "Type" => "Page",
+                    "Parent" => pages_id,
+                    "Contents" => content_id,
+                })
+                .into()
+            })
+            .collect();
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Count" => kids.len() as i64,
+                "Kids" => kids,
+                "Resources" => resources_id,
+                "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        let info_id = doc.add_object(dictionary! {
+            "Title" => Object::string_literal(title),
+        });
+        doc.trailer.set("Root", catalog_id);
+        doc.trailer.set("Info", info_id);
+        doc.save(path).unwrap();
+    }
+
+    #[test]
+    fn test_pdf_preview_extracts_text() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("report.pdf");
+        write_pdf(
+            &path,
+            "Quarterly report",
+            &["First page", "Second page", "Third page", "Fourth page"],
+        );
+        fs::write(temp_dir.path().join("fake.pdf"), b"%PDF-1.5 truncated").unwrap();
+
+        let listing = ListingOptions::default();
+        let preview = FilePreview::new(&path, 50, &listing).unwrap();
+        assert_eq!(
+            preview.pdf,
+            Some(PdfInfo {
+                title: Some("Quarterly report".to_string()),
+                page_count: 4,
+            })
+        );
+        assert_eq!(
+            preview.summary_lines(),
+            vec!["Title: Quarterly report", "Pages: 4 (text of the first 3)"]
+        );
+        match &preview.content {
+            PreviewContent::Text(lines) => {
+                let text = lines.join("\n");
+                assert!(text.contains("First page"));
+                assert!(text.contains("Third page"));
+                assert!(!text.contains("Fourth page"));
+            }
+            other => panic!("unexpected preview: {:?}", other),
+        }
+
+        // Unparseable PDFs fall back to the regular preview
+        let fake = FilePreview::new(&temp_dir.path().join("fake.pdf"), 50, &listing).unwrap();
+        assert!(fake.pdf.is_none());
+        assert!(fake.summary_lines().is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_virtual_file_preview() {
+        let listing = ListingOptions::default();
+        let status = FilePreview::new(Path::new("/proc/self/status"), 5, &listing).unwrap();
+        assert!(status.file_info.virtual_fs);
+        assert_eq!(status.file_info.size_label(), "n/a (virtual file)");
+        assert!(status.file_info.modified.is_none());
+        match &status.content {
+            PreviewContent::Text(lines) => {
+                assert_eq!(lines.len(), 5);
+                assert!(lines[0].starts_with("Name:"));
+            }
+            other => panic!("unexpected preview: {:?}", other),
+        }
+
+        // NUL-separated arguments become one line each
+        let cmdline = FilePreview::new(Path::new("/proc/self/cmdline"), 100, &listing).unwrap();
+        match &cmdline.content {
+            PreviewContent::Text(lines) => {
+                let args: Vec<String> = std::env::args().collect();
+                assert_eq!(lines, &args);
+            }
+            other => panic!("unexpected preview: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_hex_preview_pages_through_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("blob.bin");
+        let bytes: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+        fs::write(&path, &bytes).unwrap();
+
+        let listing = ListingOptions::default();
+        let mut preview = FilePreview::new(&path, 100, &listing).unwrap();
+        let chunk = |preview: &FilePreview| match &preview.content {
+            PreviewContent::Binary(chunk) => chunk.clone(),
+            other => panic!("unexpected preview: {:?}", other),
+        };
+        assert_eq!(chunk(&preview).bytes.len() as u64, HEX_CHUNK);
+        assert_eq!(preview.hex_len(), 10_000);
+
+        // Paging past the loaded chunk reads the next one
+        preview.scroll_down(200);
+        assert_eq!(preview.hex_offset(), 3200);
+        let shown = chunk(&preview);
+        assert_eq!(shown.from(3200)[0], bytes[3200]);
+        assert!(shown.end() >= 3200 + HEX_CHUNK / 2);
+
+        assert!(preview.seek_hex(0x2000 + 5));
+        assert_eq!(preview.hex_offset(), 0x2000);
+        assert_eq!(chunk(&preview).from(0x2000)[..16], bytes[0x2000..0x2010]);
+        assert!(!preview.seek_hex(10_000));
+
+        // The last row stops the scrolling
+        preview.scroll_down(10_000);
+        assert_eq!(preview.hex_offset(), 9984);
+        assert_eq!(chunk(&preview).from(9984), &bytes[9984..]);
+        preview.scroll_up(10_000);
+        assert_eq!(chunk(&preview).from(0)[..16], bytes[..16]);
+    }
+
+    #[test]
+    fn test_prefers_system_handler() {
+        assert!(FilePreview::prefers_system_handler(Path::new("photo.jpg")));
+        assert!(FilePreview::prefers_system_handler(Path::new("doc.pdf")));
+        assert!(FilePreview::prefers_system_handler(Path::new("song.mp3")));
+        assert!(!FilePreview::prefers_system_handler(Path::new("main.rs")));
+        assert!(!FilePreview::prefers_system_handler(Path::new("data.json")));
+        assert!(!FilePreview::prefers_system_handler(Path::new(
+            "unknown.bin"
+        )));
+    }
+
+    #[test]
+    fn test_selection_summary() {
+        let mut a = FileEntry::parent_link(Path::new("/"));
+        a.name = "a.rs".to_string();
+        a.path = PathBuf::from("/project/src/a.rs");
+        a.is_dir = false;
+        a.size = 100;
+        let mut b = a.clone();
+        b.path = PathBuf::from("/project/src/b.RS");
+        b.size = 50;
+        let mut c = a.clone();
+        c.path = PathBuf::from("/project/Cargo.toml");
+        c.size = 10;
+        let mut d = a.clone();
+        d.path = PathBuf::from("/project/tests");
+        d.is_dir = true;
+        d.size = 4096;
+
+        let summary = SelectionSummary::from_entries(&[&a, &b, &c, &d]);
+        assert_eq!(summary.count, 4);
+        assert_eq!(summary.file_count, 3);
+        assert_eq!(summary.dir_count, 1);
+        assert_eq!(summary.total_size, 160);
+        assert_eq!(summary.type_breakdown[0], (".rs".to_string(), 2));
+        assert_eq!(summary.common_parent, Some(PathBuf::from("/project")));
+    }
+
     #[test]
     fn test_format_size() {
         assert_eq!(FilePreview::format_size(512), "512 B");
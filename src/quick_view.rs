@@ -0,0 +1,286 @@
+use anyhow::Result;
+use crossterm::{
+    cursor::MoveTo,
+    execute,
+    style::{Color, Print, ResetColor},
+    terminal,
+};
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::graphics::ImageCanvas;
+use crate::markdown;
+use crate::models::ListingOptions;
+use crate::preview::{DirectoryPreview, FilePreview, ImageInfo, PreviewContent};
+use crate::ui::SetForegroundColor;
+use crate::utils::{ellipsize, TimeFormat};
+
+/// Transient centered preview of a single entry, dismissed by any key
+pub struct QuickView {
+    preview: FilePreview,
+    title: String,
+    /// Size, type and modification time shown under the title
+    info: String,
+}
+
+impl QuickView {
+    pub fn new(path: &Path, listing: &ListingOptions, time_format: &TimeFormat) -> Result<Self> {
+        let preview = FilePreview::new(path, 200, listing)?;
+        let title = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        let mut info = format!(
+            "{}  {}",
+            preview.file_info.size_label(),
+            preview.file_info.mime_type
+        );
+        if let Some(modified) = preview.file_info.modified {
+            info.push_str("  ");
+            info.push_str(&time_format.format(modified));
+        }
+        Ok(Self {
+            preview,
+            title,
+            info,
+        })
+    }
+
+    /// Body of the popup, already cut to `width` columns and `height` rows
+    fn body(&self, width: usize, height: usize) -> Vec<(Color, String)> {
+        let clip = |s: &str| s.chars().take(width).collect::<String>();
+
+        let mut lines: Vec<(Color, String)> = match &self.preview.content {
+            PreviewContent::Text(text) => {
+                let mut lines: Vec<(Color, String)> = Vec::new();
+                for line in self.preview.summary_lines() {
+                    let color = if line.starts_with('[') {
+                        Color::Cyan
+                    } else {
+                        Color::White
+                    };
+                    lines.push((color, clip(&line)));
+                }
+                if !lines.is_empty() {
+                    lines.push((Color::DarkGrey, "─".repeat(width)));
+                }
+                match self.preview.rendered_markdown() {
+                    // One color per row: that of the line's leading span
+                    Some(rendered) => lines.extend(rendered.iter().map(|spans| {
+                        let color = spans
+                            .first()
+                            .map_or(Color::Reset, |span| span.style.color());
+                        (color, clip(&markdown::plain_text(spans)))
+                    })),
+                    None => lines.extend(text.iter().map(|line| (Color::Reset, clip(line)))),
+                }
+                lines
+            }
+            // One color per row: that of the key, or of the value alone
+            PreviewContent::Json(document) => document
+                .lines
+                .iter()
+                .map(|line| {
+                    let color = line
+                        .segments
+                        .iter()
+                        .find(|(_, text)| !text.trim().is_empty())
+                        .map_or(Color::Reset, |(color, _)| *color);
+                    (color, clip(&line.text()))
+                })
+                .collect(),
+            PreviewContent::Binary(chunk) => {
+                // Each byte takes 3 columns of hex plus 1 of ASCII
+                let per_row = (width.saturating_sub(3) / 4).clamp(1, 16);
+                chunk
+                    .bytes
+                    .chunks(per_row)
+                    .map(|chunk| {
+                        let hex: String = chunk.iter().map(|b| format!("{:02x} ", b)).collect();
+                        let ascii: String = chunk
+                            .iter()
+                            .map(|&b| if b.is_ascii_graphic() { b as char } else { '.' })
+                            .collect();
+                        (
+                            Color::Blue,
+                            clip(&format!("{:<w$}| {}", hex, ascii, w = per_row * 3)),
+                        )
+                    })
+                    .collect()
+            }
+            PreviewContent::Image(info) => {
+                let mut lines = vec![(Color::DarkGrey, clip(&info.description()))];
+                lines.extend(info.details().iter().map(|line| (Color::Cyan, clip(line))));
+                // A decoded image is drawn below this line by `render`
+                if info.image.is_none() {
+                    lines.extend(
+                        info.ascii_art
+                            .as_deref()
+                            .unwrap_or("")
+                            .lines()
+                            .map(|line| (Color::Magenta, clip(line))),
+                    );
+                }
+                lines
+            }
+            PreviewContent::Media(info) => info
+                .lines()
+                .iter()
+                .map(|line| (Color::Cyan, clip(line)))
+                .collect(),
+            PreviewContent::Directory(dir) => {
+                let mut lines = vec![(Color::DarkGrey, clip(&dir.summary()))];
+                lines.extend(dir.entries.iter().map(|entry| {
+                    let color = if entry.is_dir {
+                        Color::Cyan
+                    } else {
+                        Color::White
+                    };
+                    // Annotation flush right, the name cut to make room
+                    let annotation = DirectoryPreview::annotation(entry);
+                    let room = width.saturating_sub(annotation.chars().count() + 1);
+                    let name = ellipsize(&entry.display_name(), room);
+                    let gap =
+                        width.saturating_sub(name.chars().count() + annotation.chars().count());
+                    (
+                        color,
+                        clip(&format!("{}{}{}", name, " ".repeat(gap), annotation)),
+                    )
+                }));
+                if dir.entries.is_empty() {
+                    lines[0] = (Color::DarkGrey, "(empty directory)".to_string());
+                }
+                if dir.hidden_count > 0 {
+                    lines.push((
+                        Color::DarkGrey,
+                        clip(&format!("({} hidden)", dir.hidden_count)),
+                    ));
+                }
+                lines
+            }
+            PreviewContent::Error(msg) => vec![(Color::Red, clip(msg))],
+            PreviewContent::Empty => vec![(Color::DarkGrey, "(empty file)".to_string())],
+        };
+
+        if lines.is_empty() {
+            lines.push((Color::DarkGrey, "(empty file)".to_string()));
+        }
+        lines.truncate(height);
+        lines
+    }
+
+    pub fn render(&self, canvas: &mut ImageCanvas) -> Result<()> {
+        let mut stdout = io::stdout();
+        let (terminal_width, terminal_height) = terminal::size()?;
+
+        let width = (terminal_width * 3 / 4).max(20).min(terminal_width);
+        let height = (terminal_height * 3 / 4).max(8).min(terminal_height);
+        let x = (terminal_width - width) / 2;
+        let y = (terminal_height - height) / 2;
+
+        // Clear the popup area before drawing on top of the listing
+        for row in y..y + height {
+            execute!(stdout, MoveTo(x, row), Print(" ".repeat(width as usize)))?;
+        }
+
+        crate::ui::draw_box(
+            &mut stdout,
+            x,
+            y,
+            width,
+            height,
+            Some(&self.title),
+            Color::Cyan,
+        )?;
+
+        let inner_width = width.saturating_sub(4) as usize;
+        execute!(
+            stdout,
+            MoveTo(x + 2, y + 1),
+            SetForegroundColor(Color::Yellow),
+            Print(self.info.chars().take(inner_width).collect::<String>()),
+            ResetColor
+        )?;
+
+        let body_height = height.saturating_sub(5) as usize;
+        for (i, (color, line)) in self.body(inner_width, body_height).iter().enumerate() {
+            execute!(
+                stdout,
+                MoveTo(x + 2, y + 3 + i as u16),
+                SetForegroundColor(*color),
+                Print(line),
+                ResetColor
+            )?;
+        }
+        if let PreviewContent::Image(
+            ref info @ ImageInfo {
+                image: Some(ref image),
+                ..
+            },
+        ) = self.preview.content
+        {
+            // Below the description and EXIF lines
+            let details = info.details().len() as u16;
+            canvas.draw(
+                &mut stdout,
+                &self.preview.path,
+                image,
+                (x + 2, y + 5 + details),
+                (
+                    inner_width as u16,
+                    (body_height as u16).saturating_sub(2 + details),
+                ),
+            )?;
+        }
+
+        execute!(
+            stdout,
+            MoveTo(x + 2, y + height - 2),
+            SetForegroundColor(Color::DarkGrey),
+            Print(
+                "Press any key to close"
+                    .chars()
+                    .take(inner_width)
+                    .collect::<String>()
+            ),
+            ResetColor
+        )?;
+
+        stdout.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_body_is_clipped_to_popup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        fs::write(&path, "a long first line\nsecond\nthird\nfourth\n").unwrap();
+
+        let view =
+            QuickView::new(&path, &ListingOptions::default(), &TimeFormat::default()).unwrap();
+        assert_eq!(view.title, "notes.txt");
+
+        let body = view.body(6, 3);
+        let lines: Vec<&str> = body.iter().map(|(_, l)| l.as_str()).collect();
+        assert_eq!(lines, vec!["a long", "second", "third"]);
+    }
+
+    #[test]
+    fn test_binary_rows_fit_width() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("blob.bin");
+        fs::write(&path, [0u8, 1, 2, 3, 4, 5, 6, 7, 8, 9]).unwrap();
+
+        let view =
+            QuickView::new(&path, &ListingOptions::default(), &TimeFormat::default()).unwrap();
+        for (_, line) in view.body(30, 10) {
+            assert!(line.chars().count() <= 30);
+        }
+    }
+}
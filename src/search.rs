@@ -1,207 +1,1085 @@
 use anyhow::Result;
 use regex::Regex;
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime};
 
-use crate::models::FileEntry;
+use crate::archive::{self, ENTRY_SEPARATOR};
+use crate::ignore::ExcludeRules;
+use crate::models::{FileEntry, NameOrder};
+use crate::utils::{config_dir, find_program, is_virtual_fs};
 
-#[derive(Debug, Clone)]
+/// Past queries kept in the search history
+const HISTORY_LIMIT: usize = 100;
+
+/// Most paths requested from the locate database per search
+const LOCATE_LIMIT: usize = 1000;
+
+/// Locate implementations tried in order
+const LOCATE_PROGRAMS: &[&str] = &["plocate", "locate"];
+
+/// Matching lines reported per file by content search
+const MAX_MATCHES_PER_FILE: usize = 5;
+
+/// Longest matching line kept as result context, in characters
+const MAX_CONTEXT_CHARS: usize = 100;
+
+/// Files larger than this are not searched for content
+const MAX_CONTENT_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Recursive scopes stop after visiting this many entries
+const MAX_WALK_ENTRIES: usize = 200_000;
+
+/// Where a search looks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchScope {
+    /// The entries currently listed
+    #[default]
+    Listing,
+    /// The current directory and everything below it
+    Recursive,
+    /// Every bookmarked directory, recursively
+    Bookmarks,
+    /// The configured search root (the home directory by default)
+    Root,
+}
+
+impl SearchScope {
+    const ALL: [SearchScope; 4] = [
+        SearchScope::Listing,
+        SearchScope::Recursive,
+        SearchScope::Bookmarks,
+        SearchScope::Root,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SearchScope::Listing => "listing",
+            SearchScope::Recursive => "recursive",
+            SearchScope::Bookmarks => "bookmarks",
+            SearchScope::Root => "root",
+        }
+    }
+}
+
+/// Directories the recursive scopes walk
+#[derive(Debug, Clone, Default)]
+pub struct ScopeRoots {
+    pub current_dir: PathBuf,
+    pub bookmarks: Vec<PathBuf>,
+    pub root: Option<PathBuf>,
+    pub show_hidden: bool,
+    /// How the listing orders names, followed within each walked directory
+    pub name_order: NameOrder,
+}
+
+impl ScopeRoots {
+    /// Top-level directories to walk for `scope`, none for the listing;
+    /// directories inside another one are dropped so nothing is found twice
+    fn for_scope(&self, scope: SearchScope) -> Vec<PathBuf> {
+        let mut dirs = match scope {
+            SearchScope::Listing => Vec::new(),
+            SearchScope::Recursive => vec![self.current_dir.clone()],
+            SearchScope::Bookmarks => self.bookmarks.clone(),
+            SearchScope::Root => self.root.iter().cloned().collect(),
+        };
+        dirs.sort();
+        dirs.dedup();
+        let all = dirs.clone();
+        dirs.retain(|dir| {
+            !all.iter()
+                .any(|other| other != dir && dir.starts_with(other))
+        });
+        dirs
+    }
+}
+
+#[derive(Debug)]
 pub struct SearchMode {
     pub query: String,
     pub use_regex: bool,
     pub case_sensitive: bool,
     pub search_in_contents: bool,
+    /// Content search also reads the files inside tarballs
+    pub search_archives: bool,
+    /// Search the whole machine through the locate database instead of the
+    /// current directory
+    pub use_locate: bool,
+    pub scope: SearchScope,
+    roots: ScopeRoots,
+    /// Leave out what the exclusion rules match
+    pub use_excludes: bool,
+    excludes: ExcludeRules,
+    /// `rg` binary content searches are delegated to
+    ripgrep: Option<PathBuf>,
     pub results: Vec<SearchResult>,
     pub current_result_index: usize,
+    worker: Option<SearchWorker>,
+    /// Query and flags of the search that produced `results`
+    last_run: Option<HistoryEntry>,
+    /// Position in the history while recalling, 0 being the newest entry
+    history_cursor: Option<usize>,
+    /// What was typed before recalling started
+    draft: Option<HistoryEntry>,
 }
 
 #[derive(Debug, Clone)]
 pub struct SearchResult {
     pub entry: FileEntry,
-    #[allow(dead_code)]
     pub match_context: Option<String>,
-    #[allow(dead_code)]
     pub line_number: Option<usize>,
+    /// Path inside the archive `entry` of a match found in a tarball
+    pub archive_entry: Option<String>,
 }
 
-impl SearchMode {
-    pub fn new() -> Self {
-        Self {
-            query: String::new(),
-            use_regex: false,
-            case_sensitive: false,
-            search_in_contents: false,
-            results: Vec::new(),
-            current_result_index: 0,
+impl SearchResult {
+    /// Where a content match is, e.g. `logs.tar.gz!/app/error.log:12`
+    pub fn location(&self) -> String {
+        let mut location = self.entry.name.clone();
+        if let Some(ref inner) = self.archive_entry {
+            location.push_str(ENTRY_SEPARATOR);
+            location.push_str(inner);
         }
+        if let Some(line) = self.line_number {
+            location.push_str(&format!(":{}", line));
+        }
+        location
     }
+}
 
-    pub fn search(&mut self, entries: &[FileEntry], _current_dir: &Path) -> Result<()> {
-        self.results.clear();
-        self.current_result_index = 0;
+/// A search running on a background thread, streaming results back
+#[derive(Debug)]
+struct SearchWorker {
+    receiver: Receiver<SearchResult>,
+    cancel: Arc<AtomicBool>,
+}
 
-        if self.query.is_empty() {
-            return Ok(());
+impl Drop for SearchWorker {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// A `size:` or `mtime:` condition typed into the query
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SearchFilter {
+    /// File size in bytes; directories never match
+    Size(Comparison, u64),
+    /// Time since the last modification
+    Age(Comparison, Duration),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    /// Split a leading operator off `text`; no operator means `Equal`
+    fn parse(text: &str) -> (Self, &str) {
+        for (prefix, comparison) in [
+            (">=", Comparison::GreaterOrEqual),
+            ("<=", Comparison::LessOrEqual),
+            (">", Comparison::Greater),
+            ("<", Comparison::Less),
+            ("=", Comparison::Equal),
+        ] {
+            if let Some(rest) = text.strip_prefix(prefix) {
+                return (comparison, rest);
+            }
         }
+        (Comparison::Equal, text)
+    }
 
-        let pattern = if self.use_regex {
-            match Regex::new(&self.query) {
-                Ok(regex) => Some(regex),
-                Err(_) => return Ok(()), // Invalid regex, no results
+    fn holds<T: PartialOrd>(self, value: T, bound: T) -> bool {
+        match self {
+            Comparison::Less => value < bound,
+            Comparison::LessOrEqual => value <= bound,
+            Comparison::Equal => value == bound,
+            Comparison::GreaterOrEqual => value >= bound,
+            Comparison::Greater => value > bound,
+        }
+    }
+}
+
+impl SearchFilter {
+    /// Parse `size:>10M` (B, K, M, G, T; powers of 1024) or `mtime:<7d`
+    /// (s, m, h, d, w, y); `None` for anything else
+    fn parse(token: &str) -> Option<Self> {
+        let (key, condition) = token.split_once(':')?;
+        let (comparison, amount) = Comparison::parse(condition);
+        let split = amount
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(amount.len());
+        let (number, unit) = amount.split_at(split);
+        let number: f64 = number.parse().ok()?;
+
+        match key.to_lowercase().as_str() {
+            "size" => {
+                let multiplier: u64 = match unit.to_lowercase().as_str() {
+                    "" | "b" => 1,
+                    "k" | "kb" => 1 << 10,
+                    "m" | "mb" => 1 << 20,
+                    "g" | "gb" => 1 << 30,
+                    "t" | "tb" => 1 << 40,
+                    _ => return None,
+                };
+                Some(SearchFilter::Size(
+                    comparison,
+                    (number * multiplier as f64) as u64,
+                ))
+            }
+            "mtime" => {
+                let seconds: u64 = match unit {
+                    "s" => 1,
+                    "m" | "min" => 60,
+                    "h" => 60 * 60,
+                    "" | "d" => 24 * 60 * 60,
+                    "w" => 7 * 24 * 60 * 60,
+                    "y" => 365 * 24 * 60 * 60,
+                    _ => return None,
+                };
+                Some(SearchFilter::Age(
+                    comparison,
+                    Duration::from_secs_f64(number * seconds as f64),
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    fn accepts(&self, entry: &FileEntry, now: SystemTime) -> bool {
+        match *self {
+            SearchFilter::Size(comparison, bound) => {
+                !entry.is_dir && comparison.holds(entry.size, bound)
             }
+            SearchFilter::Age(comparison, bound) => entry
+                .modified
+                .map(|modified| now.duration_since(modified).unwrap_or_default())
+                .is_some_and(|age| comparison.holds(age, bound)),
+        }
+    }
+}
+
+/// Separate `size:`/`mtime:` filters from the text to match
+///
+/// Without filters the query is returned untouched, spacing included.
+fn split_filters(query: &str) -> (String, Vec<SearchFilter>) {
+    let filters: Vec<SearchFilter> = query
+        .split_whitespace()
+        .filter_map(SearchFilter::parse)
+        .collect();
+    if filters.is_empty() {
+        return (query.to_string(), filters);
+    }
+    let text = query
+        .split_whitespace()
+        .filter(|token| SearchFilter::parse(token).is_none())
+        .collect::<Vec<_>>()
+        .join(" ");
+    (text, filters)
+}
+
+/// Snapshot of the search settings, owned by the worker thread
+struct SearchQuery {
+    text: String,
+    regex: Option<Regex>,
+    case_sensitive: bool,
+    search_in_contents: bool,
+    search_archives: bool,
+    excludes: Option<ExcludeRules>,
+    ripgrep: Option<PathBuf>,
+    filters: Vec<SearchFilter>,
+}
+
+impl SearchQuery {
+    fn matches(&self, haystack: &str) -> bool {
+        if let Some(ref regex) = self.regex {
+            regex.is_match(haystack)
+        } else if self.case_sensitive {
+            haystack.contains(&self.text)
         } else {
-            None
-        };
+            haystack.to_lowercase().contains(&self.text)
+        }
+    }
+
+    /// Search `entries`, handing each result to `emit` as soon as it is
+    /// found; stops early when cancelled or when `emit` returns false
+    fn run(
+        &self,
+        entries: &[FileEntry],
+        cancel: &AtomicBool,
+        emit: &mut dyn FnMut(SearchResult) -> bool,
+    ) {
+        // Files whose content ripgrep searches in one go after the names
+        let mut deferred = Vec::new();
+        let now = SystemTime::now();
+        // A filter-only query lists what passes the filters, not every line
+        let search_in_contents = self.search_in_contents && !self.text.is_empty();
 
         for entry in entries {
+            if cancel.load(Ordering::Relaxed) {
+                return;
+            }
             if entry.name == ".." {
                 continue;
             }
+            if self
+                .excludes
+                .as_ref()
+                .is_some_and(|rules| rules.is_excluded(&entry.path, entry.is_dir))
+            {
+                continue;
+            }
+            if !self.filters.iter().all(|filter| filter.accepts(entry, now)) {
+                continue;
+            }
 
             // Search in filename
-            let matches = if let Some(ref regex) = pattern {
-                regex.is_match(&entry.name)
-            } else if self.case_sensitive {
-                entry.name.contains(&self.query)
-            } else {
-                entry
-                    .name
-                    .to_lowercase()
-                    .contains(&self.query.to_lowercase())
-            };
-
-            if matches {
-                self.results.push(SearchResult {
+            if self.matches(&entry.name)
+                && !emit(SearchResult {
                     entry: entry.clone(),
                     match_context: None,
                     line_number: None,
-                });
+                    archive_entry: None,
+                })
+            {
+                return;
             }
 
             // Search in file contents if enabled and it's a text file
-            if self.search_in_contents && !entry.is_dir && entry.is_accessible {
-                if let Some(results) = self.search_in_file(&entry.path, &pattern)? {
-                    for (line_num, context) in results {
-                        self.results.push(SearchResult {
-                            entry: entry.clone(),
-                            match_context: Some(context),
-                            line_number: Some(line_num),
-                        });
+            // procfs/sysfs files can block or stream without end when read
+            if search_in_contents
+                && !entry.is_dir
+                && entry.is_accessible
+                && !is_virtual_fs(&entry.path)
+            {
+                if self.search_archives && archive::is_tar_archive(&entry.name) {
+                    if !self.emit_archive_matches(entry, cancel, emit) {
+                        return;
                     }
+                    continue;
+                }
+                if self.ripgrep.is_some() {
+                    deferred.push(entry);
+                    continue;
+                }
+                if !self.emit_content_matches(entry, cancel, emit) {
+                    return;
                 }
             }
         }
 
-        Ok(())
+        if let Some(ref program) = self.ripgrep {
+            if !deferred.is_empty() && !self.run_ripgrep(program, &deferred, cancel, emit) {
+                // Not runnable after all: fall back to the internal engine
+                for entry in deferred {
+                    if !self.emit_content_matches(entry, cancel, emit) {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Search one file with the internal engine; false once `emit` refuses more
+    fn emit_content_matches(
+        &self,
+        entry: &FileEntry,
+        cancel: &AtomicBool,
+        emit: &mut dyn FnMut(SearchResult) -> bool,
+    ) -> bool {
+        for (line_num, context) in self.search_in_file(&entry.path, cancel) {
+            let result = SearchResult {
+                entry: entry.clone(),
+                match_context: Some(context),
+                line_number: Some(line_num),
+                archive_entry: None,
+            };
+            if !emit(result) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Search the text files inside a tarball; false once `emit` refuses more
+    fn emit_archive_matches(
+        &self,
+        entry: &FileEntry,
+        cancel: &AtomicBool,
+        emit: &mut dyn FnMut(SearchResult) -> bool,
+    ) -> bool {
+        let mut open = true;
+        // Unreadable or corrupt archives simply have no matches
+        let _ = archive::for_each_text_entry(&entry.path, cancel, &mut |inner, text| {
+            let matches = text
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| self.matches(line))
+                .take(MAX_MATCHES_PER_FILE);
+            for (index, line) in matches {
+                open = emit(SearchResult {
+                    entry: entry.clone(),
+                    match_context: Some(truncate_context(line.to_string())),
+                    line_number: Some(index + 1),
+                    archive_entry: Some(inner.to_string()),
+                });
+                if !open {
+                    break;
+                }
+            }
+            open
+        });
+        open
+    }
+
+    /// Arguments for an `rg --json` run matching lines like [`SearchQuery::matches`]
+    fn ripgrep_args(&self) -> Vec<String> {
+        let mut args = vec![
+            // A user's ripgreprc could change matching (e.g. --smart-case)
+            "--no-config".to_string(),
+            "--json".to_string(),
+            "--max-count".to_string(),
+            MAX_MATCHES_PER_FILE.to_string(),
+            "--max-filesize".to_string(),
+            MAX_CONTENT_BYTES.to_string(),
+        ];
+        if self.regex.is_none() {
+            args.push("--fixed-strings".to_string());
+        }
+        // Regex queries are always case-sensitive, as in `matches`
+        args.push(if self.regex.is_some() || self.case_sensitive {
+            "--case-sensitive".to_string()
+        } else {
+            "--ignore-case".to_string()
+        });
+        args.push("--regexp".to_string());
+        args.push(self.text.clone());
+        args.push("--".to_string());
+        args
+    }
+
+    /// Walk `roots` breadth-first, searching each directory's entries as a
+    /// batch; excluded directories are not entered, symlinked ones are not
+    /// followed
+    fn run_walk(
+        &self,
+        roots: &[PathBuf],
+        show_hidden: bool,
+        name_order: NameOrder,
+        cancel: &AtomicBool,
+        emit: &mut dyn FnMut(SearchResult) -> bool,
+    ) {
+        let mut excludes = self.excludes.clone();
+        let mut queue: std::collections::VecDeque<PathBuf> = roots.iter().cloned().collect();
+        let mut visited = 0;
+        let mut open = true;
+
+        while let Some(dir) = queue.pop_front() {
+            if cancel.load(Ordering::Relaxed) || !open || visited >= MAX_WALK_ENTRIES {
+                return;
+            }
+            let Ok(read_dir) = fs::read_dir(&dir) else {
+                continue;
+            };
+            if let Some(ref mut excludes) = excludes {
+                excludes.enter_dir(&dir);
+            }
+
+            let mut entries: Vec<FileEntry> = read_dir
+                .flatten()
+                .map(|child| child.file_name().to_string_lossy().to_string())
+                .filter(|name| show_hidden || !name.starts_with('.'))
+                .map(|name| FileEntry::from_path(dir.join(&name), name))
+                .filter(|entry| {
+                    !excludes
+                        .as_ref()
+                        .is_some_and(|rules| rules.is_excluded(&entry.path, entry.is_dir))
+                })
+                .collect();
+            entries.sort_by(|a, b| name_order.compare(&a.name, &b.name));
+            visited += entries.len();
+
+            queue.extend(
+                entries
+                    .iter()
+                    .filter(|entry| entry.is_dir && !entry.is_symlink)
+                    .map(|entry| entry.path.clone()),
+            );
+            self.run(&entries, cancel, &mut |result| {
+                open = emit(result);
+                open
+            });
+        }
     }
 
-    fn search_in_file(
+    /// Content search of `entries` by ripgrep, streaming its matches;
+    /// returns false when `program` could not be started
+    fn run_ripgrep(
         &self,
-        path: &Path,
-        regex: &Option<Regex>,
-    ) -> Result<Option<Vec<(usize, String)>>> {
-        use std::fs::File;
-        use std::io::{BufRead, BufReader};
+        program: &Path,
+        entries: &[&FileEntry],
+        cancel: &AtomicBool,
+        emit: &mut dyn FnMut(SearchResult) -> bool,
+    ) -> bool {
+        let Ok(mut child) = Command::new(program)
+            .args(self.ripgrep_args())
+            .args(entries.iter().map(|entry| &entry.path))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            return false;
+        };
+
+        if let Some(stdout) = child.stdout.take() {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if cancel.load(Ordering::Relaxed) {
+                    break;
+                }
+                let Some((path, line_number, text)) = parse_ripgrep_match(&line) else {
+                    continue;
+                };
+                let Some(entry) = entries.iter().find(|entry| entry.path == path) else {
+                    continue;
+                };
+                let result = SearchResult {
+                    entry: (*entry).clone(),
+                    match_context: Some(truncate_context(text)),
+                    line_number: Some(line_number),
+                    archive_entry: None,
+                };
+                if !emit(result) {
+                    break;
+                }
+            }
+        }
+
+        let _ = child.kill();
+        let _ = child.wait();
+        true
+    }
+
+    fn search_in_file(&self, path: &Path, cancel: &AtomicBool) -> Vec<(usize, String)> {
+        let mut results = Vec::new();
 
         // Only search in files smaller than 10MB
         if let Ok(metadata) = path.metadata() {
-            if metadata.len() > 10 * 1024 * 1024 {
-                return Ok(None);
+            if metadata.len() > MAX_CONTENT_BYTES {
+                return results;
             }
         }
 
         // Check if file is likely text
-        if !self.is_text_file(path) {
-            return Ok(None);
+        if !is_text_file(path) {
+            return results;
         }
 
-        let file = File::open(path)?;
+        let Ok(file) = File::open(path) else {
+            return results;
+        };
         let reader = BufReader::new(file);
-        let mut results = Vec::new();
 
         for (line_num, line) in reader.lines().enumerate() {
+            if cancel.load(Ordering::Relaxed) {
+                break;
+            }
             if let Ok(line_content) = line {
-                let matches = if let Some(ref regex) = regex {
-                    regex.is_match(&line_content)
-                } else if self.case_sensitive {
-                    line_content.contains(&self.query)
-                } else {
-                    line_content
-                        .to_lowercase()
-                        .contains(&self.query.to_lowercase())
-                };
-
-                if matches {
-                    // Truncate long lines for display
-                    let context = if line_content.len() > 100 {
-                        format!("{}...", &line_content[..100])
-                    } else {
-                        line_content
-                    };
-                    results.push((line_num + 1, context));
+                if self.matches(&line_content) {
+                    results.push((line_num + 1, truncate_context(line_content)));
 
                     // Limit results per file
-                    if results.len() >= 5 {
+                    if results.len() >= MAX_MATCHES_PER_FILE {
                         break;
                     }
                 }
             }
         }
 
-        Ok(if results.is_empty() {
-            None
+        results
+    }
+
+    /// Arguments for a `locate` run matching base names like [`SearchQuery::matches`]
+    fn locate_args(&self) -> Vec<String> {
+        let mut args = vec![
+            "--basename".to_string(),
+            "--limit".to_string(),
+            LOCATE_LIMIT.to_string(),
+        ];
+        if !self.case_sensitive {
+            args.push("--ignore-case".to_string());
+        }
+        if self.regex.is_some() {
+            args.push("--regex".to_string());
+        }
+        args.push("--".to_string());
+        args.push(self.text.clone());
+        args
+    }
+
+    /// Query the locate database, searching every listed path that still
+    /// exists as if it were an entry of the current directory
+    fn run_locate(
+        &self,
+        program: &Path,
+        cancel: &AtomicBool,
+        emit: &mut dyn FnMut(SearchResult) -> bool,
+    ) {
+        let Ok(mut child) = Command::new(program)
+            .args(self.locate_args())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            return;
+        };
+
+        if let Some(stdout) = child.stdout.take() {
+            let mut open = true;
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                let path = PathBuf::from(line);
+                // The database can be older than the filesystem
+                let Ok(metadata) = path.symlink_metadata() else {
+                    continue;
+                };
+                if self
+                    .excludes
+                    .as_ref()
+                    .is_some_and(|rules| rules.is_excluded_path(&path, metadata.is_dir()))
+                {
+                    continue;
+                }
+                let name = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| path.display().to_string());
+                self.run(&[FileEntry::from_path(path, name)], cancel, &mut |result| {
+                    open = emit(result);
+                    open
+                });
+                if !open || cancel.load(Ordering::Relaxed) {
+                    break;
+                }
+            }
+        }
+
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+/// Shorten a matching line for display
+fn truncate_context(line: String) -> String {
+    if line.chars().count() > MAX_CONTEXT_CHARS {
+        format!(
+            "{}...",
+            line.chars().take(MAX_CONTEXT_CHARS).collect::<String>()
+        )
+    } else {
+        line
+    }
+}
+
+/// Path, line number and text of a `match` message from `rg --json`
+///
+/// Other messages (`begin`, `end`, `summary`) and matches in file names or
+/// lines that are not valid UTF-8 yield `None`.
+fn parse_ripgrep_match(line: &str) -> Option<(PathBuf, usize, String)> {
+    let message: serde_json::Value = serde_json::from_str(line).ok()?;
+    if message["type"] != "match" {
+        return None;
+    }
+    let data = &message["data"];
+    let path = data["path"]["text"].as_str()?;
+    let line_number = data["line_number"].as_u64()?;
+    let text = data["lines"]["text"].as_str()?;
+    Some((
+        PathBuf::from(path),
+        line_number as usize,
+        text.trim_end_matches(['\n', '\r']).to_string(),
+    ))
+}
+
+/// The ripgrep binary, if installed
+pub fn ripgrep_program() -> Option<PathBuf> {
+    find_program("rg")
+}
+
+/// The locate implementation installed on this machine, if any
+pub fn locate_program() -> Option<PathBuf> {
+    LOCATE_PROGRAMS
+        .iter()
+        .find_map(|program| find_program(program))
+}
+
+fn is_text_file(path: &Path) -> bool {
+    // Check by extension
+    if let Some(ext) = path.extension() {
+        let ext = ext.to_string_lossy().to_lowercase();
+        matches!(
+            ext.as_str(),
+            "txt"
+                | "md"
+                | "rs"
+                | "toml"
+                | "yaml"
+                | "yml"
+                | "json"
+                | "js"
+                | "ts"
+                | "py"
+                | "sh"
+                | "bash"
+                | "c"
+                | "cpp"
+                | "h"
+                | "hpp"
+                | "java"
+                | "go"
+                | "rb"
+                | "php"
+                | "html"
+                | "css"
+                | "xml"
+                | "conf"
+                | "cfg"
+                | "ini"
+                | "log"
+        )
+    } else {
+        // Check files without extension (like README, LICENSE)
+        let filename = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        matches!(
+            filename.as_str(),
+            "readme" | "license" | "makefile" | "dockerfile" | "changelog"
+        )
+    }
+}
+
+impl SearchMode {
+    pub fn new() -> Self {
+        Self {
+            query: String::new(),
+            use_regex: false,
+            case_sensitive: false,
+            search_in_contents: false,
+            search_archives: false,
+            use_locate: false,
+            scope: SearchScope::Listing,
+            roots: ScopeRoots::default(),
+            use_excludes: true,
+            excludes: ExcludeRules::default(),
+            ripgrep: None,
+            results: Vec::new(),
+            current_result_index: 0,
+            worker: None,
+            last_run: None,
+            history_cursor: None,
+            draft: None,
+        }
+    }
+
+    /// Exclusion rules applied while [`SearchMode::use_excludes`] is on
+    pub fn with_excludes(mut self, excludes: ExcludeRules) -> Self {
+        self.excludes = excludes;
+        self
+    }
+
+    /// Directories searched by the recursive scopes
+    pub fn with_roots(mut self, roots: ScopeRoots) -> Self {
+        self.roots = roots;
+        self
+    }
+
+    /// The scope for the mode line, naming the configured root
+    pub fn scope_label(&self) -> String {
+        match (self.scope, &self.roots.root) {
+            (SearchScope::Root, Some(root)) => format!("root {}", root.display()),
+            (scope, _) => scope.label().to_string(),
+        }
+    }
+
+    /// Move to the next scope that has something to search
+    pub fn cycle_scope(&mut self) {
+        let position = SearchScope::ALL
+            .iter()
+            .position(|s| *s == self.scope)
+            .unwrap_or(0);
+        self.scope = (1..=SearchScope::ALL.len())
+            .map(|offset| SearchScope::ALL[(position + offset) % SearchScope::ALL.len()])
+            .find(|scope| {
+                *scope == SearchScope::Listing || !self.roots.for_scope(*scope).is_empty()
+            })
+            .unwrap_or(SearchScope::Listing);
+        // Clear results as search mode changed
+        self.cancel();
+        self.results.clear();
+    }
+
+    /// Whether content search starts out reading inside tarballs
+    pub fn with_archives(mut self, search_archives: bool) -> Self {
+        self.search_archives = search_archives;
+        self
+    }
+
+    /// Delegate content search to the `rg` binary at `program`
+    pub fn with_ripgrep(mut self, program: Option<PathBuf>) -> Self {
+        self.ripgrep = program;
+        self
+    }
+
+    /// Whether content search runs through ripgrep
+    pub fn uses_ripgrep(&self) -> bool {
+        self.ripgrep.is_some()
+    }
+
+    /// Whether there are exclusion rules to toggle
+    pub fn has_excludes(&self) -> bool {
+        !self.excludes.is_empty()
+    }
+
+    /// Settings for a new search; `None` when nothing can match
+    fn snapshot(&self) -> Option<SearchQuery> {
+        let (text, filters) = split_filters(&self.query);
+        // The locate database needs a name to look up
+        if text.is_empty() && (filters.is_empty() || self.use_locate) {
+            return None;
+        }
+
+        let regex = if self.use_regex {
+            // Invalid regex, no results
+            Some(Regex::new(&text).ok()?)
         } else {
-            Some(results)
+            None
+        };
+
+        Some(SearchQuery {
+            text: if self.case_sensitive {
+                text
+            } else {
+                text.to_lowercase()
+            },
+            regex,
+            case_sensitive: self.case_sensitive,
+            search_in_contents: self.search_in_contents,
+            search_archives: self.search_archives,
+            excludes: self.use_excludes.then(|| self.excludes.clone()),
+            ripgrep: self.ripgrep.clone(),
+            filters,
         })
     }
 
-    fn is_text_file(&self, path: &Path) -> bool {
-        // Check by extension
-        if let Some(ext) = path.extension() {
-            let ext = ext.to_string_lossy().to_lowercase();
-            matches!(
-                ext.as_str(),
-                "txt"
-                    | "md"
-                    | "rs"
-                    | "toml"
-                    | "yaml"
-                    | "yml"
-                    | "json"
-                    | "js"
-                    | "ts"
-                    | "py"
-                    | "sh"
-                    | "bash"
-                    | "c"
-                    | "cpp"
-                    | "h"
-                    | "hpp"
-                    | "java"
-                    | "go"
-                    | "rb"
-                    | "php"
-                    | "html"
-                    | "css"
-                    | "xml"
-                    | "conf"
-                    | "cfg"
-                    | "ini"
-                    | "log"
-            )
+    /// Search on the calling thread, blocking until done
+    #[allow(dead_code)]
+    pub fn search(&mut self, entries: &[FileEntry], _current_dir: &Path) -> Result<()> {
+        self.cancel();
+        self.results.clear();
+        self.current_result_index = 0;
+
+        if let Some(query) = self.snapshot() {
+            let results = &mut self.results;
+            query.run(entries, &AtomicBool::new(false), &mut |result| {
+                results.push(result);
+                true
+            });
+        }
+        Ok(())
+    }
+
+    /// Start searching `entries`, the directories of the scope, or the
+    /// locate database on a worker thread, replacing any search in progress;
+    /// results arrive through [`SearchMode::poll`]
+    pub fn start(&mut self, entries: &[FileEntry]) {
+        self.cancel();
+        self.results.clear();
+        self.current_result_index = 0;
+        self.last_run = Some(self.history_entry());
+
+        let Some(query) = self.snapshot() else {
+            return;
+        };
+        let locate = if self.use_locate {
+            match locate_program() {
+                Some(program) => Some(program),
+                None => return,
+            }
+        } else {
+            None
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let worker_cancel = Arc::clone(&cancel);
+        let entries = entries.to_vec();
+        let walk_roots = self.roots.for_scope(self.scope);
+        let show_hidden = self.roots.show_hidden;
+        let name_order = self.roots.name_order;
+        let scope = self.scope;
+
+        thread::spawn(move || {
+            let mut emit = |result: SearchResult| sender.send(result).is_ok();
+            match locate {
+                Some(program) => query.run_locate(&program, &worker_cancel, &mut emit),
+                None if scope == SearchScope::Listing => {
+                    query.run(&entries, &worker_cancel, &mut emit)
+                }
+                None => query.run_walk(
+                    &walk_roots,
+                    show_hidden,
+                    name_order,
+                    &worker_cancel,
+                    &mut emit,
+                ),
+            }
+        });
+
+        self.worker = Some(SearchWorker { receiver, cancel });
+    }
+
+    /// Collect results streamed since the last call; returns how many arrived
+    pub fn poll(&mut self) -> usize {
+        let Some(ref worker) = self.worker else {
+            return 0;
+        };
+
+        let mut received = 0;
+        loop {
+            match worker.receiver.try_recv() {
+                Ok(result) => {
+                    self.results.push(result);
+                    received += 1;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.worker = None;
+                    break;
+                }
+            }
+        }
+        received
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.worker.is_some()
+    }
+
+    /// Number of `size:`/`mtime:` filters in the query
+    pub fn filter_count(&self) -> usize {
+        split_filters(&self.query).1.len()
+    }
+
+    /// Whether the results still belong to the query as it stands
+    pub fn has_current_results(&self) -> bool {
+        !self.results.is_empty() && self.last_run.as_ref() == Some(&self.history_entry())
+    }
+
+    /// Byte ranges of `text` matching the current query, for highlighting
+    pub fn match_ranges(&self, text: &str) -> Vec<Range<usize>> {
+        let (query, _) = split_filters(&self.query);
+        let pattern = if self.use_regex {
+            query.clone()
+        } else if self.case_sensitive {
+            regex::escape(&query)
         } else {
-            // Check files without extension (like README, LICENSE)
-            let filename = path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("")
-                .to_lowercase();
-            matches!(
-                filename.as_str(),
-                "readme" | "license" | "makefile" | "dockerfile" | "changelog"
-            )
+            format!("(?i){}", regex::escape(&query))
+        };
+
+        match Regex::new(&pattern) {
+            Ok(regex) if !query.is_empty() => regex
+                .find_iter(text)
+                .filter(|m| !m.is_empty())
+                .map(|m| m.range())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The query and flags as a history entry
+    pub fn history_entry(&self) -> HistoryEntry {
+        HistoryEntry {
+            query: self.query.clone(),
+            use_regex: self.use_regex,
+            case_sensitive: self.case_sensitive,
+            search_in_contents: self.search_in_contents,
+            search_archives: self.search_archives,
+            use_locate: self.use_locate,
+            scope: self.scope,
         }
     }
 
+    fn apply(&mut self, entry: &HistoryEntry) {
+        self.query = entry.query.clone();
+        self.use_regex = entry.use_regex;
+        self.case_sensitive = entry.case_sensitive;
+        self.search_in_contents = entry.search_in_contents;
+        self.search_archives = entry.search_archives;
+        self.use_locate = entry.use_locate;
+        self.scope = entry.scope;
+        self.cancel();
+        self.results.clear();
+    }
+
+    /// Step through `history`, older with `older`, back towards the typed
+    /// query otherwise
+    pub fn recall(&mut self, history: &SearchHistory, older: bool) {
+        let next = match (self.history_cursor, older) {
+            (None, true) if !history.is_empty() => Some(0),
+            (None, _) => return,
+            (Some(i), true) if i + 1 < history.len() => Some(i + 1),
+            (Some(i), true) => Some(i),
+            (Some(0), false) => None,
+            (Some(i), false) => Some(i - 1),
+        };
+
+        if self.history_cursor.is_none() {
+            self.draft = Some(self.history_entry());
+        }
+        self.history_cursor = next;
+
+        match next.and_then(|i| history.get(i)) {
+            Some(entry) => self.apply(&entry.clone()),
+            None => {
+                if let Some(draft) = self.draft.take() {
+                    self.apply(&draft);
+                }
+            }
+        }
+    }
+
+    /// Editing the query leaves history recall
+    pub fn stop_recall(&mut self) {
+        self.history_cursor = None;
+        self.draft = None;
+    }
+
+    /// Stop the running search, keeping the results found so far
+    pub fn cancel(&mut self) {
+        // Dropping the worker raises its cancel flag
+        self.worker = None;
+    }
+
     pub fn next_result(&mut self) {
         if !self.results.is_empty() {
             self.current_result_index = (self.current_result_index + 1) % self.results.len();
@@ -221,33 +1099,268 @@ impl SearchMode {
     pub fn toggle_regex(&mut self) {
         self.use_regex = !self.use_regex;
         // Clear results as search mode changed
+        self.cancel();
         self.results.clear();
     }
 
     pub fn toggle_case_sensitive(&mut self) {
         self.case_sensitive = !self.case_sensitive;
         // Clear results as search mode changed
+        self.cancel();
         self.results.clear();
     }
 
     pub fn toggle_search_contents(&mut self) {
         self.search_in_contents = !self.search_in_contents;
         // Clear results as search mode changed
+        self.cancel();
         self.results.clear();
     }
 
+    pub fn toggle_archives(&mut self) {
+        self.search_archives = !self.search_archives;
+        self.cancel();
+        self.results.clear();
+    }
+
+    pub fn toggle_excludes(&mut self) {
+        self.use_excludes = !self.use_excludes;
+        // Clear results as search mode changed
+        self.cancel();
+        self.results.clear();
+    }
+
+    /// Switch between the current directory and the locate database;
+    /// returns false when no locate implementation is installed
+    pub fn toggle_locate(&mut self) -> bool {
+        if !self.use_locate && locate_program().is_none() {
+            return false;
+        }
+        self.use_locate = !self.use_locate;
+        // Clear results as search mode changed
+        self.cancel();
+        self.results.clear();
+        true
+    }
+
     pub fn get_current_result(&self) -> Option<&SearchResult> {
         self.results.get(self.current_result_index)
     }
 
     #[allow(dead_code)]
     pub fn clear(&mut self) {
+        self.cancel();
         self.query.clear();
         self.results.clear();
         self.current_result_index = 0;
     }
 }
 
+/// A past search, restored together with its flags
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub query: String,
+    pub use_regex: bool,
+    pub case_sensitive: bool,
+    pub search_in_contents: bool,
+    #[serde(default)]
+    pub search_archives: bool,
+    #[serde(default)]
+    pub use_locate: bool,
+    #[serde(default)]
+    pub scope: SearchScope,
+}
+
+/// Search queries remembered across sessions, oldest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchHistory {
+    entries: Vec<HistoryEntry>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl SearchHistory {
+    pub fn load() -> Self {
+        let Ok(dir) = config_dir() else {
+            return Self::default();
+        };
+        Self::load_from(dir.join("search_history.json"))
+    }
+
+    fn load_from(path: PathBuf) -> Self {
+        let mut history: Self = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        history.path = path;
+        history
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The `index`-th most recent entry
+    pub fn get(&self, index: usize) -> Option<&HistoryEntry> {
+        self.entries.iter().rev().nth(index)
+    }
+
+    /// Remember `entry` as the most recent search, dropping older duplicates
+    pub fn record(&mut self, entry: HistoryEntry) -> Result<()> {
+        if entry.query.is_empty() {
+            return Ok(());
+        }
+        self.entries.retain(|e| *e != entry);
+        self.entries.push(entry);
+        if self.entries.len() > HISTORY_LIMIT {
+            self.entries.remove(0);
+        }
+
+        if self.path.as_os_str().is_empty() {
+            return Ok(());
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&self.path, json)?;
+        Ok(())
+    }
+}
+
+/// Result of matching a fuzzy query against a candidate
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzyMatch {
+    pub score: i64,
+    /// Character indices of the candidate that matched, in order
+    pub positions: Vec<usize>,
+}
+
+const SCORE_MATCH: i64 = 16;
+const BONUS_CONSECUTIVE: i64 = 6;
+const BONUS_BOUNDARY: i64 = 8;
+const BONUS_PATH_SEGMENT: i64 = 10;
+const BONUS_CAMEL_CASE: i64 = 7;
+const PENALTY_GAP_START: i64 = 3;
+const PENALTY_GAP_EXTENSION: i64 = 1;
+
+fn position_bonus(chars: &[char], j: usize) -> i64 {
+    if j == 0 {
+        return BONUS_BOUNDARY;
+    }
+    let (prev, cur) = (chars[j - 1], chars[j]);
+    if prev == '/' {
+        BONUS_PATH_SEGMENT
+    } else if !prev.is_alphanumeric() {
+        BONUS_BOUNDARY
+    } else if prev.is_lowercase() && cur.is_uppercase() {
+        BONUS_CAMEL_CASE
+    } else {
+        0
+    }
+}
+
+/// Match `query` against `candidate` fzf-style
+///
+/// Every query character must appear in order (case-insensitively); `None`
+/// means no match. Among all alignments the best-scoring one is chosen:
+/// consecutive runs and matches at word, path-segment and camelCase
+/// boundaries score higher, gaps between matches cost points.
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<FuzzyMatch> {
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    let chars: Vec<char> = candidate.chars().collect();
+    let lower: Vec<char> = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+
+    if query.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            positions: Vec::new(),
+        });
+    }
+
+    // Cheap subsequence check rejects most candidates before the DP
+    let mut remaining = lower.iter();
+    if !query.iter().all(|q| remaining.any(|c| c == q)) {
+        return None;
+    }
+
+    const NONE: i64 = i64::MIN / 4;
+    let (m, n) = (query.len(), chars.len());
+    // best[i][j]: best score with query[i] matched at candidate[j]
+    let mut best = vec![vec![NONE; n]; m];
+    let mut from = vec![vec![usize::MAX; n]; m];
+
+    for (j, &c) in lower.iter().enumerate() {
+        if c == query[0] {
+            best[0][j] = SCORE_MATCH + position_bonus(&chars, j);
+        }
+    }
+
+    for i in 1..m {
+        // Best of best[i - 1][k] + k over k < j - 1, to price gaps in O(1)
+        let mut gap_best = NONE;
+        let mut gap_from = usize::MAX;
+
+        for j in 1..n {
+            if j >= 2
+                && best[i - 1][j - 2] != NONE
+                && best[i - 1][j - 2] + (j as i64 - 2) > gap_best
+            {
+                gap_best = best[i - 1][j - 2] + (j as i64 - 2);
+                gap_from = j - 2;
+            }
+            if lower[j] != query[i] {
+                continue;
+            }
+
+            let base = SCORE_MATCH + position_bonus(&chars, j);
+            if best[i - 1][j - 1] != NONE {
+                best[i][j] = best[i - 1][j - 1] + base + BONUS_CONSECUTIVE;
+                from[i][j] = j - 1;
+            }
+            if gap_best != NONE {
+                // A gap of g characters costs start + (g - 1) * extension
+                let gap_score = gap_best
+                    - (j as i64 - 1) * PENALTY_GAP_EXTENSION
+                    - (PENALTY_GAP_START - PENALTY_GAP_EXTENSION)
+                    + base;
+                if gap_score > best[i][j] {
+                    best[i][j] = gap_score;
+                    from[i][j] = gap_from;
+                }
+            }
+        }
+    }
+
+    let (mut j, score) = best[m - 1]
+        .iter()
+        .enumerate()
+        .filter(|(_, &s)| s != NONE)
+        .max_by_key(|(j, &s)| (s, std::cmp::Reverse(*j)))
+        .map(|(j, &s)| (j, s))?;
+
+    let mut positions = vec![0; m];
+    for i in (0..m).rev() {
+        positions[i] = j;
+        j = from[i][j];
+    }
+
+    // Prefer shorter candidates when everything else is equal
+    Some(FuzzyMatch {
+        score: score * 1000 - n as i64,
+        positions,
+    })
+}
+
+/// Score `candidate` against a fuzzy `query`; see [`fuzzy_match`]
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    fuzzy_match(query, candidate).map(|m| m.score)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -279,6 +1392,8 @@ mod tests {
                 group: None,
                 uid: None,
                 gid: None,
+                size: 0,
+                modified: None,
             },
             FileEntry {
                 name: "other.rs".to_string(),
@@ -291,6 +1406,8 @@ mod tests {
                 group: None,
                 uid: None,
                 gid: None,
+                size: 0,
+                modified: None,
             },
         ];
 
@@ -316,6 +1433,8 @@ mod tests {
             group: None,
             uid: None,
             gid: None,
+            size: 0,
+            modified: None,
         }];
 
         let _ = search.search(&entries, Path::new("/"));
@@ -340,6 +1459,8 @@ mod tests {
                 group: None,
                 uid: None,
                 gid: None,
+                size: 0,
+                modified: None,
             },
             FileEntry {
                 name: "test.rs".to_string(),
@@ -352,6 +1473,8 @@ mod tests {
                 group: None,
                 uid: None,
                 gid: None,
+                size: 0,
+                modified: None,
             },
         ];
 
@@ -378,9 +1501,12 @@ mod tests {
                     group: None,
                     uid: None,
                     gid: None,
+                    size: 0,
+                    modified: None,
                 },
                 match_context: None,
                 line_number: None,
+                archive_entry: None,
             });
         }
 
@@ -398,4 +1524,499 @@ mod tests {
         search.previous_result();
         assert_eq!(search.current_result_index, 2); // Wraps backward
     }
+
+    fn entry(query: &str, use_regex: bool) -> HistoryEntry {
+        HistoryEntry {
+            query: query.to_string(),
+            use_regex,
+            case_sensitive: false,
+            search_in_contents: false,
+            search_archives: false,
+            use_locate: false,
+            scope: SearchScope::Listing,
+        }
+    }
+
+    #[test]
+    fn test_history_persists_and_dedupes() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("search_history.json");
+
+        let mut history = SearchHistory::load_from(path.clone());
+        history.record(entry(r"^\d+\.log$", true)).unwrap();
+        history.record(entry("todo", false)).unwrap();
+        history.record(entry(r"^\d+\.log$", true)).unwrap();
+        history.record(entry("", false)).unwrap();
+
+        let history = SearchHistory::load_from(path);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.get(0), Some(&entry(r"^\d+\.log$", true)));
+        assert_eq!(history.get(1), Some(&entry("todo", false)));
+    }
+
+    #[test]
+    fn test_recall_restores_flags_and_draft() {
+        let mut history = SearchHistory::default();
+        history.record(entry("old", false)).unwrap();
+        history.record(entry("new.*", true)).unwrap();
+
+        let mut search = SearchMode::new();
+        search.query = "typed".to_string();
+
+        search.recall(&history, true);
+        assert_eq!(search.history_entry(), entry("new.*", true));
+        search.recall(&history, true);
+        search.recall(&history, true);
+        assert_eq!(search.history_entry(), entry("old", false));
+
+        search.recall(&history, false);
+        search.recall(&history, false);
+        assert_eq!(search.history_entry(), entry("typed", false));
+        search.recall(&history, false);
+        assert_eq!(search.query, "typed");
+    }
+
+    #[test]
+    fn test_match_ranges() {
+        let mut search = SearchMode::new();
+        search.query = "TODO".to_string();
+        assert_eq!(search.match_ranges("// todo: fix TODO"), vec![3..7, 13..17]);
+
+        search.case_sensitive = true;
+        assert_eq!(search.match_ranges("// todo: fix TODO"), vec![13..17]);
+
+        search.use_regex = true;
+        search.query = r"\d+".to_string();
+        assert_eq!(search.match_ranges("a1 b22"), vec![1..2, 4..6]);
+        search.query = "(".to_string();
+        assert!(search.match_ranges("(").is_empty());
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("srtsz", "Sort by size").is_some());
+        assert!(fuzzy_score("xyz", "Sort by size").is_none());
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+
+        // Word starts and consecutive runs beat scattered matches
+        let word_start = fuzzy_score("sbs", "Sort by size").unwrap();
+        let scattered = fuzzy_score("sbs", "sabsent").unwrap();
+        assert!(word_start > scattered);
+        let prefix = fuzzy_score("chm", "chmod").unwrap();
+        let spread = fuzzy_score("chm", "cache home").unwrap();
+        assert!(prefix > spread);
+    }
+
+    #[test]
+    fn test_fuzzy_match_prefers_boundaries() {
+        // The greedy first "m" would be inside "some"; the segment start wins
+        let m = fuzzy_match("main", "some/domain/main.rs").unwrap();
+        assert_eq!(m.positions, vec![12, 13, 14, 15]);
+
+        let m = fuzzy_match("fb", "FooBar").unwrap();
+        assert_eq!(m.positions, vec![0, 3]);
+
+        let path_hit = fuzzy_score("nav", "src/navigator.rs").unwrap();
+        let scattered = fuzzy_score("nav", "src/managers/driver.rs").unwrap();
+        assert!(path_hit > scattered);
+    }
+
+    fn text_file(dir: &Path, name: &str, content: &str) -> FileEntry {
+        let path = dir.join(name);
+        std::fs::write(&path, content).unwrap();
+        FileEntry::from_path(path, name.to_string())
+    }
+
+    #[test]
+    fn test_background_search_streams_results() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let entries: Vec<FileEntry> = (0..20)
+            .map(|i| text_file(temp_dir.path(), &format!("f{}.txt", i), "needle\nhay\n"))
+            .collect();
+
+        let mut search = SearchMode::new();
+        search.query = "needle".to_string();
+        search.search_in_contents = true;
+        search.start(&entries);
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while search.is_searching() && std::time::Instant::now() < deadline {
+            search.poll();
+            thread::yield_now();
+        }
+        assert!(!search.is_searching());
+        assert_eq!(search.results.len(), 20);
+        assert_eq!(search.results[0].line_number, Some(1));
+    }
+
+    #[test]
+    fn test_cancelled_search_stops() {
+        let cancel = AtomicBool::new(true);
+        let query = SearchQuery {
+            text: "f".to_string(),
+            regex: None,
+            case_sensitive: false,
+            search_in_contents: false,
+            search_archives: false,
+            excludes: None,
+            ripgrep: None,
+            filters: Vec::new(),
+        };
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let entries = vec![text_file(temp_dir.path(), "f.txt", "")];
+        let mut emitted = 0;
+        query.run(&entries, &cancel, &mut |_| {
+            emitted += 1;
+            true
+        });
+        assert_eq!(emitted, 0);
+
+        let mut search = SearchMode::new();
+        search.query = "x".to_string();
+        search.start(&entries);
+        search.cancel();
+        assert!(!search.is_searching());
+    }
+
+    #[test]
+    fn test_archive_content_search() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("logs.tar.gz");
+        std::fs::write(
+            &path,
+            crate::archive::tests::tarball(
+                &[
+                    ("app/error.log", b"ok\nnot found: needle\n"),
+                    ("app/ok.log", b"ok\n"),
+                ],
+                true,
+            ),
+        )
+        .unwrap();
+        let entries = vec![FileEntry::from_path(path, "logs.tar.gz".to_string())];
+
+        let mut search = SearchMode::new();
+        search.query = "needle".to_string();
+        search.search_in_contents = true;
+        search.search(&entries, temp_dir.path()).unwrap();
+        assert!(search.results.is_empty());
+
+        search.toggle_archives();
+        search.search(&entries, temp_dir.path()).unwrap();
+        assert_eq!(search.results.len(), 1);
+        assert_eq!(search.results[0].location(), "logs.tar.gz!/app/error.log:2");
+        assert_eq!(
+            search.results[0].match_context.as_deref(),
+            Some("not found: needle")
+        );
+    }
+
+    #[test]
+    fn test_excluded_entries_are_skipped() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let entries = vec![
+            text_file(temp_dir.path(), "app.js", ""),
+            text_file(temp_dir.path(), "app.min.js", ""),
+        ];
+        let excludes = ExcludeRules::new(temp_dir.path(), &["*.min.js".to_string()], false);
+
+        let mut search = SearchMode::new().with_excludes(excludes);
+        search.query = "app".to_string();
+        let _ = search.search(&entries, temp_dir.path());
+        assert_eq!(search.results.len(), 1);
+        assert_eq!(search.results[0].entry.name, "app.js");
+
+        search.toggle_excludes();
+        let _ = search.search(&entries, temp_dir.path());
+        assert_eq!(search.results.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_ripgrep_match() {
+        let begin = r#"{"type":"begin","data":{"path":{"text":"src/main.rs"}}}"#;
+        assert_eq!(parse_ripgrep_match(begin), None);
+
+        let hit = r#"{"type":"match","data":{"path":{"text":"src/main.rs"},"lines":{"text":"// TODO: tidy\n"},"line_number":12,"absolute_offset":0,"submatches":[]}}"#;
+        assert_eq!(
+            parse_ripgrep_match(hit),
+            Some((
+                PathBuf::from("src/main.rs"),
+                12,
+                "// TODO: tidy".to_string()
+            ))
+        );
+
+        let binary = r#"{"type":"match","data":{"path":{"text":"a"},"lines":{"bytes":"/w=="},"line_number":1}}"#;
+        assert_eq!(parse_ripgrep_match(binary), None);
+    }
+
+    #[test]
+    fn test_ripgrep_results_and_fallback() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let notes = text_file(temp_dir.path(), "notes", "todo: ship\n");
+        let fake_rg = temp_dir.path().join("rg");
+        std::fs::write(
+            &fake_rg,
+            format!(
+                "#!/bin/sh\nprintf '%s\\n' '{{\"type\":\"match\",\"data\":{{\"path\":{{\"text\":\"{}\"}},\"lines\":{{\"text\":\"todo: ship\\n\"}},\"line_number\":1}}}}'\n",
+                notes.path.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&fake_rg, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        // `notes` has no extension the internal engine treats as text
+        let mut search = SearchMode::new().with_ripgrep(Some(fake_rg));
+        search.query = "todo".to_string();
+        search.search_in_contents = true;
+        let _ = search.search(std::slice::from_ref(&notes), temp_dir.path());
+        assert_eq!(search.results.len(), 1);
+        assert_eq!(search.results[0].line_number, Some(1));
+
+        // A missing binary falls back to the internal engine
+        let readme = text_file(temp_dir.path(), "README", "todo\n");
+        let mut search = SearchMode::new().with_ripgrep(Some(temp_dir.path().join("missing")));
+        search.query = "todo".to_string();
+        search.search_in_contents = true;
+        let _ = search.search(&[readme], temp_dir.path());
+        assert_eq!(search.results.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_filters() {
+        assert_eq!(
+            SearchFilter::parse("size:>10M"),
+            Some(SearchFilter::Size(Comparison::Greater, 10 << 20))
+        );
+        assert_eq!(
+            SearchFilter::parse("size:<=1.5k"),
+            Some(SearchFilter::Size(Comparison::LessOrEqual, 1536))
+        );
+        assert_eq!(
+            SearchFilter::parse("mtime:<7d"),
+            Some(SearchFilter::Age(
+                Comparison::Less,
+                Duration::from_secs(7 * 24 * 60 * 60)
+            ))
+        );
+        assert_eq!(SearchFilter::parse("size:>10X"), None);
+        assert_eq!(SearchFilter::parse("owner:root"), None);
+
+        let (text, filters) = split_filters("report  size:>1M mtime:<1w draft");
+        assert_eq!(text, "report draft");
+        assert_eq!(filters.len(), 2);
+        assert_eq!(split_filters("a  b").0, "a  b");
+    }
+
+    #[test]
+    fn test_filters_narrow_results() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let big = text_file(temp_dir.path(), "big.log", &"x".repeat(4096));
+        let small = text_file(temp_dir.path(), "small.log", "x");
+        let old_path = temp_dir.path().join("old.log");
+        std::fs::write(&old_path, "x".repeat(4096)).unwrap();
+        let month_ago = SystemTime::now() - Duration::from_secs(30 * 24 * 60 * 60);
+        File::options()
+            .write(true)
+            .open(&old_path)
+            .unwrap()
+            .set_modified(month_ago)
+            .unwrap();
+        let old = FileEntry::from_path(old_path, "old.log".to_string());
+        let entries = vec![big, small, old];
+
+        let names = |query: &str| {
+            let mut search = SearchMode::new();
+            search.query = query.to_string();
+            let _ = search.search(&entries, temp_dir.path());
+            search
+                .results
+                .iter()
+                .map(|r| r.entry.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names("size:>1k"), ["big.log", "old.log"]);
+        assert_eq!(names("log size:>1k mtime:<7d"), ["big.log"]);
+        assert_eq!(names("mtime:>1w"), ["old.log"]);
+        assert_eq!(names("small mtime:<1d"), ["small.log"]);
+    }
+
+    #[test]
+    fn test_scope_roots_and_cycle() {
+        let roots = ScopeRoots {
+            current_dir: PathBuf::from("/srv/app"),
+            bookmarks: vec![
+                PathBuf::from("/srv/app/src"),
+                PathBuf::from("/srv/app"),
+                PathBuf::from("/etc"),
+            ],
+            root: None,
+            show_hidden: false,
+            name_order: NameOrder::Natural,
+        };
+        assert_eq!(
+            roots.for_scope(SearchScope::Bookmarks),
+            [PathBuf::from("/etc"), PathBuf::from("/srv/app")]
+        );
+        assert!(roots.for_scope(SearchScope::Listing).is_empty());
+
+        // Without a root the cycle skips that scope
+        let mut search = SearchMode::new().with_roots(roots);
+        let mut seen = Vec::new();
+        for _ in 0..4 {
+            search.cycle_scope();
+            seen.push(search.scope);
+        }
+        assert_eq!(
+            seen,
+            [
+                SearchScope::Recursive,
+                SearchScope::Bookmarks,
+                SearchScope::Listing,
+                SearchScope::Recursive
+            ]
+        );
+    }
+
+    #[test]
+    fn test_recursive_scope_walks_tree() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("src/deep")).unwrap();
+        std::fs::create_dir_all(root.join("target")).unwrap();
+        std::fs::create_dir_all(root.join(".cache")).unwrap();
+        for file in [
+            "main.rs",
+            "src/lib.rs",
+            "src/deep/mod.rs",
+            "target/gen.rs",
+            ".cache/x.rs",
+        ] {
+            std::fs::write(root.join(file), "").unwrap();
+        }
+
+        let mut search = SearchMode::new()
+            .with_excludes(ExcludeRules::new(root, &["target/".to_string()], false))
+            .with_roots(ScopeRoots {
+                current_dir: root.to_path_buf(),
+                ..ScopeRoots::default()
+            });
+        search.query = ".rs".to_string();
+        search.scope = SearchScope::Recursive;
+        search.start(&[]);
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while search.is_searching() && std::time::Instant::now() < deadline {
+            search.poll();
+            thread::yield_now();
+        }
+        let names: Vec<_> = search
+            .results
+            .iter()
+            .map(|r| r.entry.name.as_str())
+            .collect();
+        assert_eq!(names, ["main.rs", "lib.rs", "mod.rs"]);
+    }
+
+    #[test]
+    fn test_walk_follows_name_order() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        for file in ["file10.rs", "file9.rs", "File1.rs"] {
+            std::fs::write(temp_dir.path().join(file), "").unwrap();
+        }
+
+        let walk = |name_order| {
+            let mut search = SearchMode::new().with_roots(ScopeRoots {
+                current_dir: temp_dir.path().to_path_buf(),
+                name_order,
+                ..ScopeRoots::default()
+            });
+            search.query = ".rs".to_string();
+            search.scope = SearchScope::Recursive;
+            search.start(&[]);
+            let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+            while search.is_searching() && std::time::Instant::now() < deadline {
+                search.poll();
+                thread::yield_now();
+            }
+            search
+                .results
+                .iter()
+                .map(|r| r.entry.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            walk(NameOrder::Natural),
+            ["File1.rs", "file9.rs", "file10.rs"]
+        );
+        assert_eq!(
+            walk(NameOrder::Lexicographic),
+            ["File1.rs", "file10.rs", "file9.rs"]
+        );
+    }
+
+    #[test]
+    fn test_locate_args() {
+        let mut search = SearchMode::new();
+        search.query = "-Notes".to_string();
+        let args = search.snapshot().unwrap().locate_args();
+        assert_eq!(
+            args,
+            [
+                "--basename",
+                "--limit",
+                "1000",
+                "--ignore-case",
+                "--",
+                "-notes"
+            ]
+        );
+
+        search.use_regex = true;
+        search.case_sensitive = true;
+        search.query = r"\.log$".to_string();
+        let args = search.snapshot().unwrap().locate_args();
+        assert_eq!(
+            args,
+            ["--basename", "--limit", "1000", "--regex", "--", r"\.log$"]
+        );
+    }
+
+    #[test]
+    fn test_locate_skips_stale_paths() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let notes = temp_dir.path().join("notes.txt");
+        std::fs::write(&notes, "todo: locate\n").unwrap();
+        let fake_locate = temp_dir.path().join("locate");
+        std::fs::write(
+            &fake_locate,
+            format!(
+                "#!/bin/sh\necho {}\necho {}/gone.txt\n",
+                notes.display(),
+                temp_dir.path().display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&fake_locate, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut search = SearchMode::new();
+        search.query = "todo".to_string();
+        search.search_in_contents = true;
+        let mut results = Vec::new();
+        search.snapshot().unwrap().run_locate(
+            &fake_locate,
+            &AtomicBool::new(false),
+            &mut |result| {
+                results.push(result);
+                true
+            },
+        );
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].entry.path, notes);
+        assert_eq!(results[0].line_number, Some(1));
+    }
 }
@@ -0,0 +1,379 @@
+use anyhow::Result;
+use crossterm::{
+    cursor::MoveTo,
+    event::{KeyCode, KeyModifiers},
+    execute,
+    style::{Color, Print, ResetColor},
+    terminal,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use crate::models::{Grouping, SortKey};
+use crate::ui::{SetBackgroundColor, SetForegroundColor};
+use crate::utils::{config_dir, truncate_chars};
+
+/// What the screen looked like when a session was saved
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub dir: PathBuf,
+    /// Entry under the cursor
+    #[serde(default)]
+    pub highlighted: Option<PathBuf>,
+    #[serde(default)]
+    pub selected: Vec<PathBuf>,
+    #[serde(default)]
+    pub sort_key: SortKey,
+    #[serde(default)]
+    pub grouping: Grouping,
+    #[serde(default)]
+    pub show_hidden: bool,
+    #[serde(default)]
+    pub show_preview: bool,
+    /// Open split-pane view, restored in place of the listing
+    #[serde(default)]
+    pub split: Option<SplitSession>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SplitSession {
+    pub left: PathBuf,
+    pub right: PathBuf,
+    pub right_focused: bool,
+    pub vertical: bool,
+    pub ratio: f32,
+    /// Panes after the first two
+    #[serde(default)]
+    pub more: Vec<PathBuf>,
+    /// Index of the active pane, which sessions saved with only two panes
+    /// record in `right_focused`
+    #[serde(default)]
+    pub focused: Option<usize>,
+    #[serde(default)]
+    pub grid: bool,
+}
+
+impl Session {
+    /// One line for the picker
+    fn summary(&self) -> String {
+        match &self.split {
+            Some(split) => [&split.left, &split.right]
+                .into_iter()
+                .chain(&split.more)
+                .map(|dir| dir.display().to_string())
+                .collect::<Vec<_>>()
+                .join(" | "),
+            None if self.selected.is_empty() => self.dir.display().to_string(),
+            None => format!("{} ({} selected)", self.dir.display(), self.selected.len()),
+        }
+    }
+}
+
+/// Named sessions saved in `sessions.json`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Sessions {
+    #[serde(default)]
+    sessions: BTreeMap<String, Session>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl Sessions {
+    pub fn load() -> Self {
+        let Ok(dir) = config_dir() else {
+            return Self::default();
+        };
+        Self::load_from(dir.join("sessions.json"))
+    }
+
+    fn load_from(path: PathBuf) -> Self {
+        let mut sessions: Self = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        sessions.path = path;
+        sessions
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Session> {
+        self.sessions.get(name)
+    }
+
+    /// Names in alphabetical order
+    pub fn names(&self) -> Vec<&str> {
+        self.sessions.keys().map(String::as_str).collect()
+    }
+
+    /// Save `session` as `name`, replacing a session of that name
+    pub fn save_as(&mut self, name: &str, session: Session) -> Result<()> {
+        self.sessions.insert(name.to_string(), session);
+        self.save()
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<()> {
+        self.sessions.remove(name);
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        if self.path.as_os_str().is_empty() {
+            return Ok(());
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&self.path, json)?;
+        Ok(())
+    }
+}
+
+pub enum SessionOutcome {
+    Continue,
+    /// Close the picker, with a message for the status bar
+    Close(Option<String>),
+    /// Save the current state under this name
+    Save(String),
+    Restore(Session),
+}
+
+/// Popup listing the saved sessions, with a prompt to name a new one
+pub struct SessionPicker {
+    selected: usize,
+    /// Name being typed for the current state
+    naming: Option<String>,
+}
+
+impl SessionPicker {
+    pub fn new() -> Self {
+        Self {
+            selected: 0,
+            naming: None,
+        }
+    }
+
+    pub fn handle_input(
+        &mut self,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+        sessions: &mut Sessions,
+    ) -> SessionOutcome {
+        if let Some(ref mut name) = self.naming {
+            match code {
+                KeyCode::Enter if !name.trim().is_empty() => {
+                    return SessionOutcome::Save(name.trim().to_string());
+                }
+                KeyCode::Esc => self.naming = None,
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                KeyCode::Char(c) => name.push(c),
+                _ => {}
+            }
+            return SessionOutcome::Continue;
+        }
+
+        let names = sessions.names();
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        match code {
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down if self.selected + 1 < names.len() => self.selected += 1,
+            KeyCode::Enter => {
+                if let Some(session) = names.get(self.selected).and_then(|n| sessions.get(n)) {
+                    return SessionOutcome::Restore(session.clone());
+                }
+            }
+            // Saving over the highlighted session starts from its name
+            KeyCode::Char('s') if ctrl => {
+                self.naming = Some(
+                    names
+                        .get(self.selected)
+                        .map(|name| name.to_string())
+                        .unwrap_or_default(),
+                );
+            }
+            KeyCode::Char('n') if ctrl => self.naming = Some(String::new()),
+            KeyCode::Char('d') if ctrl => {
+                if let Some(name) = names.get(self.selected).map(|name| name.to_string()) {
+                    if let Err(e) = sessions.remove(&name) {
+                        return SessionOutcome::Close(Some(format!(
+                            "Failed to delete session: {}",
+                            e
+                        )));
+                    }
+                    self.selected = self.selected.min(sessions.names().len().saturating_sub(1));
+                }
+            }
+            KeyCode::Esc => return SessionOutcome::Close(None),
+            _ => {}
+        }
+        SessionOutcome::Continue
+    }
+
+    pub fn render(&self, sessions: &Sessions) -> Result<()> {
+        let mut stdout = io::stdout();
+        let (terminal_width, terminal_height) = terminal::size()?;
+
+        let width = 64.min(terminal_width.saturating_sub(4)).max(24);
+        let height = 14.min(terminal_height.saturating_sub(2)).max(8);
+        let x = (terminal_width.saturating_sub(width)) / 2;
+        let y = (terminal_height.saturating_sub(height)) / 3;
+
+        for row in y..y + height {
+            execute!(stdout, MoveTo(x, row), Print(" ".repeat(width as usize)))?;
+        }
+        crate::ui::draw_box(
+            &mut stdout,
+            x,
+            y,
+            width,
+            height,
+            Some("Sessions"),
+            Color::Cyan,
+        )?;
+
+        let inner_width = width.saturating_sub(4) as usize;
+        let names = sessions.names();
+        let header = match &self.naming {
+            Some(name) => format!("Save as: {}_", name),
+            None if names.is_empty() => "No sessions saved".to_string(),
+            None => "Restore a session:".to_string(),
+        };
+        execute!(
+            stdout,
+            MoveTo(x + 2, y + 1),
+            SetForegroundColor(Color::Yellow),
+            Print(truncate_chars(&header, inner_width)),
+            ResetColor
+        )?;
+
+        let list_height = height.saturating_sub(5) as usize;
+        let start = self.selected.saturating_sub(list_height.saturating_sub(1));
+        for (i, name) in names.iter().enumerate().skip(start).take(list_height) {
+            let is_selected = i == self.selected && self.naming.is_none();
+            let summary = sessions.get(name).map(Session::summary).unwrap_or_default();
+            execute!(
+                stdout,
+                MoveTo(x + 2, y + 3 + (i - start) as u16),
+                if is_selected {
+                    SetBackgroundColor(Color::DarkGreen)
+                } else {
+                    SetBackgroundColor(Color::Reset)
+                },
+                SetForegroundColor(if is_selected {
+                    Color::White
+                } else {
+                    Color::Grey
+                }),
+                Print(format!(
+                    "{:<width$}",
+                    truncate_chars(&format!("{:12} {}", name, summary), inner_width),
+                    width = inner_width
+                )),
+                ResetColor
+            )?;
+        }
+
+        let footer = if self.naming.is_some() {
+            " Enter: Save | Esc: Back"
+        } else {
+            " Enter: Restore | Ctrl+N: Save as new | Ctrl+S: Overwrite | Ctrl+D: Delete | Esc"
+        };
+        execute!(
+            stdout,
+            MoveTo(x + 2, y + height - 2),
+            SetForegroundColor(Color::DarkGrey),
+            Print(truncate_chars(footer, inner_width)),
+            ResetColor
+        )?;
+
+        stdout.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn session(dir: &str) -> Session {
+        Session {
+            dir: PathBuf::from(dir),
+            highlighted: Some(PathBuf::from(dir).join("Cargo.toml")),
+            selected: vec![PathBuf::from(dir).join("src")],
+            sort_key: SortKey::Modified,
+            grouping: Grouping::Mixed,
+            show_hidden: true,
+            show_preview: false,
+            split: None,
+        }
+    }
+
+    fn press(picker: &mut SessionPicker, sessions: &mut Sessions, code: KeyCode) -> SessionOutcome {
+        picker.handle_input(code, KeyModifiers::NONE, sessions)
+    }
+
+    #[test]
+    fn test_sessions_persist() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sessions.json");
+        let mut sessions = Sessions::load_from(path.clone());
+        sessions.save_as("work", session("/srv/app")).unwrap();
+        let mut split = session("/srv/api");
+        split.split = Some(SplitSession {
+            left: PathBuf::from("/srv/api"),
+            right: PathBuf::from("/var/log"),
+            right_focused: true,
+            vertical: false,
+            ratio: 0.4,
+            more: vec![PathBuf::from("/etc")],
+            focused: Some(2),
+            grid: true,
+        });
+        sessions.save_as("api", split.clone()).unwrap();
+
+        let reloaded = Sessions::load_from(path.clone());
+        assert_eq!(reloaded.names(), ["api", "work"]);
+        assert_eq!(reloaded.get("api"), Some(&split));
+        assert_eq!(reloaded.get("work"), Some(&session("/srv/app")));
+        assert!(fs::read_to_string(&path)
+            .unwrap()
+            .contains(r#""sort_key": "modified""#));
+
+        // Older files without the optional fields still load
+        fs::write(&path, r#"{"sessions": {"old": {"dir": "/tmp"}}}"#).unwrap();
+        let old = Sessions::load_from(path);
+        assert_eq!(old.get("old").unwrap().sort_key, SortKey::Name);
+    }
+
+    #[test]
+    fn test_picker() {
+        let mut sessions = Sessions::default();
+        sessions.save_as("api", session("/srv/api")).unwrap();
+        sessions.save_as("work", session("/srv/app")).unwrap();
+        let mut picker = SessionPicker::new();
+
+        press(&mut picker, &mut sessions, KeyCode::Down);
+        assert!(matches!(
+            press(&mut picker, &mut sessions, KeyCode::Enter),
+            SessionOutcome::Restore(s) if s.dir == Path::new("/srv/app")
+        ));
+
+        // Overwriting starts from the highlighted name
+        picker.handle_input(KeyCode::Char('s'), KeyModifiers::CONTROL, &mut sessions);
+        press(&mut picker, &mut sessions, KeyCode::Char('2'));
+        assert!(matches!(
+            press(&mut picker, &mut sessions, KeyCode::Enter),
+            SessionOutcome::Save(name) if name == "work2"
+        ));
+
+        picker.handle_input(KeyCode::Esc, KeyModifiers::NONE, &mut sessions);
+        picker.handle_input(KeyCode::Char('d'), KeyModifiers::CONTROL, &mut sessions);
+        assert_eq!(sessions.names(), ["api"]);
+        assert!(matches!(
+            press(&mut picker, &mut sessions, KeyCode::Enter),
+            SessionOutcome::Restore(s) if s.dir == Path::new("/srv/api")
+        ));
+    }
+}
@@ -0,0 +1,421 @@
+use anyhow::{Context, Result};
+use crossterm::{
+    cursor::MoveTo,
+    event::KeyCode,
+    execute,
+    style::{Color, Print, ResetColor},
+    terminal,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::preview::FilePreview;
+use crate::ui::{SetBackgroundColor, SetForegroundColor};
+use crate::utils::{config_dir, TimeFormat};
+
+/// Snapshots stop recording after this many entries
+const MAX_ENTRIES: usize = 100_000;
+
+/// Metadata recorded for one entry of the tree
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    pub is_dir: bool,
+    pub size: u64,
+    /// Nanoseconds since the Unix epoch
+    pub modified: Option<u64>,
+    /// FNV-1a digest of the contents, when hashing was requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+}
+
+/// Recorded metadata of a directory tree, keyed by path relative to `root`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub root: PathBuf,
+    /// Seconds since the Unix epoch
+    pub taken: u64,
+    pub hashed: bool,
+    /// Whether `MAX_ENTRIES` cut the walk short
+    pub truncated: bool,
+    pub entries: BTreeMap<String, SnapshotEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added(String),
+    Removed(String),
+    /// Path and what differs, e.g. "size 4 B → 9 B"
+    Modified(String, Vec<String>),
+}
+
+impl Snapshot {
+    /// Walk `root` (hidden entries included, symlinks not followed), or
+    /// only its direct entries unless `recursive`
+    pub fn capture(root: &Path, hash: bool, recursive: bool) -> io::Result<Self> {
+        let mut entries = BTreeMap::new();
+        let mut truncated = false;
+        let mut queue = VecDeque::from([(root.to_path_buf(), String::new())]);
+
+        // The root itself must be readable; unreadable subdirectories are skipped
+        fs::read_dir(root)?;
+
+        'walk: while let Some((dir, prefix)) = queue.pop_front() {
+            let Ok(read_dir) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for child in read_dir.flatten() {
+                if entries.len() >= MAX_ENTRIES {
+                    truncated = true;
+                    break 'walk;
+                }
+                let Ok(metadata) = child.path().symlink_metadata() else {
+                    continue;
+                };
+                let relative = format!("{}{}", prefix, child.file_name().to_string_lossy());
+
+                if recursive && metadata.is_dir() {
+                    queue.push_back((child.path(), format!("{}/", relative)));
+                }
+                entries.insert(
+                    relative,
+                    SnapshotEntry {
+                        is_dir: metadata.is_dir(),
+                        size: if metadata.is_dir() { 0 } else { metadata.len() },
+                        modified: metadata
+                            .modified()
+                            .ok()
+                            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                            .map(|d| d.as_nanos() as u64),
+                        hash: if hash && metadata.is_file() {
+                            hash_file(&child.path()).ok()
+                        } else {
+                            None
+                        },
+                    },
+                );
+            }
+        }
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            taken: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            hashed: hash,
+            truncated,
+            entries,
+        })
+    }
+
+    /// Where the snapshot of `root` is stored; one snapshot per directory
+    pub fn path_for(root: &Path) -> Result<PathBuf> {
+        let dir = config_dir()?.join("snapshots");
+        fs::create_dir_all(&dir)?;
+        let digest = fnv1a(root.to_string_lossy().as_bytes(), FNV_OFFSET);
+        Ok(dir.join(format!("{:016x}.json", digest)))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// The stored snapshot, or `None` if `path` does not exist
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path)?;
+        let snapshot = serde_json::from_str(&content)
+            .with_context(|| format!("Corrupt snapshot {}", path.display()))?;
+        Ok(Some(snapshot))
+    }
+
+    pub fn taken_at(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.taken)
+    }
+
+    /// What changed from this snapshot to `current`, in path order
+    pub fn compare(&self, current: &Snapshot) -> Vec<Change> {
+        let mut changes = Vec::new();
+
+        for (path, old) in &self.entries {
+            let Some(new) = current.entries.get(path) else {
+                changes.push(Change::Removed(path.clone()));
+                continue;
+            };
+
+            let mut details = Vec::new();
+            if old.is_dir != new.is_dir {
+                details.push(if new.is_dir {
+                    "file → directory".to_string()
+                } else {
+                    "directory → file".to_string()
+                });
+            } else if old.size != new.size {
+                details.push(format!(
+                    "size {} → {}",
+                    FilePreview::format_size(old.size),
+                    FilePreview::format_size(new.size)
+                ));
+            }
+            if old.hash.is_some() && new.hash.is_some() && old.hash != new.hash {
+                details.push("content".to_string());
+            }
+            if old.modified != new.modified && !old.is_dir {
+                details.push("modified time".to_string());
+            }
+
+            if !details.is_empty() {
+                changes.push(Change::Modified(path.clone(), details));
+            }
+        }
+
+        changes.extend(
+            current
+                .entries
+                .keys()
+                .filter(|path| !self.entries.contains_key(*path))
+                .map(|path| Change::Added(path.clone())),
+        );
+        changes.sort_by(|a, b| a.path().cmp(b.path()));
+        changes
+    }
+}
+
+impl Change {
+    pub fn path(&self) -> &str {
+        match self {
+            Change::Added(path) | Change::Removed(path) | Change::Modified(path, _) => path,
+        }
+    }
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+fn fnv1a(bytes: &[u8], mut hash: u64) -> u64 {
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+fn hash_file(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut buffer = [0u8; 64 * 1024];
+    let mut hash = FNV_OFFSET;
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hash = fnv1a(&buffer[..read], hash);
+    }
+    Ok(format!("{:016x}", hash))
+}
+
+/// Scrollable report of the changes since a snapshot
+pub struct SnapshotReport {
+    root: PathBuf,
+    taken: String,
+    changes: Vec<Change>,
+    truncated: bool,
+    scroll_offset: usize,
+}
+
+impl SnapshotReport {
+    pub fn new(
+        snapshot: &Snapshot,
+        current: &Snapshot,
+        changes: Vec<Change>,
+        time_format: &TimeFormat,
+    ) -> Self {
+        Self {
+            root: snapshot.root.clone(),
+            taken: time_format.format(snapshot.taken_at()),
+            changes,
+            truncated: snapshot.truncated || current.truncated,
+            scroll_offset: 0,
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let count = |f: fn(&Change) -> bool| self.changes.iter().filter(|c| f(c)).count();
+        format!(
+            "{} added, {} removed, {} changed since {}",
+            count(|c| matches!(c, Change::Added(_))),
+            count(|c| matches!(c, Change::Removed(_))),
+            count(|c| matches!(c, Change::Modified(..))),
+            self.taken
+        )
+    }
+
+    pub fn render(&self) -> Result<()> {
+        let mut stdout = io::stdout();
+        let (terminal_width, terminal_height) = terminal::size()?;
+        let width = terminal_width as usize;
+
+        execute!(stdout, terminal::Clear(terminal::ClearType::All))?;
+
+        let header: String = format!(" 📸 Changes in {}", self.root.display())
+            .chars()
+            .take(width)
+            .collect();
+        execute!(
+            stdout,
+            MoveTo(0, 0),
+            SetBackgroundColor(Color::DarkBlue),
+            SetForegroundColor(Color::White),
+            Print(format!("{:<width$}", header, width = width)),
+            ResetColor
+        )?;
+
+        let mut summary = self.summary();
+        if self.truncated {
+            summary.push_str(&format!(" (only the first {} entries)", MAX_ENTRIES));
+        }
+        execute!(
+            stdout,
+            MoveTo(1, 1),
+            SetForegroundColor(Color::Yellow),
+            Print(
+                summary
+                    .chars()
+                    .take(width.saturating_sub(2))
+                    .collect::<String>()
+            ),
+            ResetColor
+        )?;
+
+        if self.changes.is_empty() {
+            execute!(
+                stdout,
+                MoveTo(1, 3),
+                SetForegroundColor(Color::Green),
+                Print("No changes"),
+                ResetColor
+            )?;
+        }
+
+        let body_height = (terminal_height as usize).saturating_sub(4);
+        for (i, change) in self
+            .changes
+            .iter()
+            .skip(self.scroll_offset)
+            .take(body_height)
+            .enumerate()
+        {
+            let (color, line) = match change {
+                Change::Added(path) => (Color::Green, format!("+ {}", path)),
+                Change::Removed(path) => (Color::Red, format!("- {}", path)),
+                Change::Modified(path, details) => (
+                    Color::Yellow,
+                    format!("~ {}  ({})", path, details.join(", ")),
+                ),
+            };
+            execute!(
+                stdout,
+                MoveTo(1, 3 + i as u16),
+                SetForegroundColor(color),
+                Print(
+                    line.chars()
+                        .take(width.saturating_sub(2))
+                        .collect::<String>()
+                ),
+                ResetColor
+            )?;
+        }
+
+        let footer = " ↑↓: Scroll | PageUp/Down: Page | Esc/q/Enter: Close";
+        execute!(
+            stdout,
+            MoveTo(0, terminal_height.saturating_sub(1)),
+            SetBackgroundColor(Color::DarkGrey),
+            SetForegroundColor(Color::White),
+            Print(format!(
+                "{:<width$}",
+                footer.chars().take(width).collect::<String>(),
+                width = width
+            )),
+            ResetColor
+        )?;
+
+        stdout.flush()?;
+        Ok(())
+    }
+
+    /// Returns false once the report should close
+    pub fn handle_input(&mut self, key: KeyCode) -> bool {
+        let max_offset = self.changes.len().saturating_sub(1);
+        match key {
+            KeyCode::Up => self.scroll_offset = self.scroll_offset.saturating_sub(1),
+            KeyCode::Down => self.scroll_offset = (self.scroll_offset + 1).min(max_offset),
+            KeyCode::PageUp => self.scroll_offset = self.scroll_offset.saturating_sub(10),
+            KeyCode::PageDown => self.scroll_offset = (self.scroll_offset + 10).min(max_offset),
+            KeyCode::Home => self.scroll_offset = 0,
+            KeyCode::End => self.scroll_offset = max_offset,
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter => return false,
+            _ => {}
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_compare_reports_changes() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("conf.d")).unwrap();
+        fs::write(dir.path().join("conf.d/a.conf"), "a=1").unwrap();
+        fs::write(dir.path().join(".secret"), "x").unwrap();
+        fs::write(dir.path().join("gone"), "").unwrap();
+
+        let before = Snapshot::capture(dir.path(), true, true).unwrap();
+        assert_eq!(before.entries.len(), 4);
+
+        // Same size, different content: only the hash notices
+        fs::write(dir.path().join("conf.d/a.conf"), "a=2").unwrap();
+        fs::write(dir.path().join(".secret"), "longer").unwrap();
+        fs::remove_file(dir.path().join("gone")).unwrap();
+        fs::write(dir.path().join("new"), "").unwrap();
+
+        let after = Snapshot::capture(dir.path(), true, true).unwrap();
+        let changes = before.compare(&after);
+        let paths: Vec<_> = changes.iter().map(|c| c.path()).collect();
+        assert_eq!(paths, vec![".secret", "conf.d/a.conf", "gone", "new"]);
+
+        assert!(matches!(&changes[0], Change::Modified(_, d) if d[0] == "size 1 B → 6 B"));
+        assert!(
+            matches!(&changes[1], Change::Modified(_, d) if d.contains(&"content".to_string()))
+        );
+        assert_eq!(changes[2], Change::Removed("gone".to_string()));
+        assert_eq!(changes[3], Change::Added("new".to_string()));
+
+        assert!(after.compare(&after).is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("file"), "data").unwrap();
+        let path = dir.path().join("snapshot.json");
+
+        assert!(Snapshot::load(&path).unwrap().is_none());
+        let snapshot = Snapshot::capture(dir.path(), false, true).unwrap();
+        snapshot.save(&path).unwrap();
+
+        let loaded = Snapshot::load(&path).unwrap().unwrap();
+        assert_eq!(loaded.entries, snapshot.entries);
+        assert!(loaded.entries["file"].hash.is_none());
+    }
+}
@@ -1,48 +1,216 @@
 use anyhow::Result;
 use crossterm::{
     cursor::MoveTo,
+    event::{KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind},
     execute,
-    style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor},
+    style::{Color, Print, ResetColor},
     terminal,
 };
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     io::{self, Write},
     path::{Path, PathBuf},
+    time::{Duration, SystemTime},
 };
 
-use crate::models::FileEntry;
-use crate::utils::get_owner_group;
+use crate::models::{filter_entries, read_directory, DirectoryListing, FileEntry, ListingOptions};
+use crate::tasks::Task;
+use crate::ui::{SetBackgroundColor, SetForegroundColor};
+use crate::utils::{case_only_conflict, copy_path, ellipsize, free_path, move_path};
+
+/// How the panes share the screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Layout {
+    /// Side by side
+    Columns,
+    /// Stacked
+    Rows,
+    /// Two rows of two; a third pane takes the whole bottom row
+    Grid,
+}
+
+impl Layout {
+    /// The next layout that suits `panes` panes: a grid of two is just
+    /// columns
+    fn next(self, panes: usize) -> Self {
+        match self {
+            Layout::Columns => Layout::Rows,
+            Layout::Rows if panes > 2 => Layout::Grid,
+            Layout::Rows | Layout::Grid => Layout::Columns,
+        }
+    }
+}
+
+/// How an entry differs from the entry of the same name in the pane it
+/// is compared with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Difference {
+    /// No entry of that name there
+    Only,
+    Newer,
+    Older,
+    /// Same modification time, but another size or type
+    Differs,
+}
+
+impl Difference {
+    pub fn marker(self) -> &'static str {
+        match self {
+            Difference::Only => "+",
+            Difference::Newer => "▲",
+            Difference::Older => "▼",
+            Difference::Differs => "≠",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Difference::Only => Color::Green,
+            Difference::Newer => Color::Cyan,
+            Difference::Older => Color::DarkYellow,
+            Difference::Differs => Color::Magenta,
+        }
+    }
+}
+
+/// Modification times this close count as the same; FAT keeps them to
+/// two seconds
+const MTIME_SLACK: Duration = Duration::from_secs(2);
+
+/// How each entry of `ours` differs from its namesake in `theirs`, rsync
+/// style: directories are compared by presence only, and entries with no
+/// difference are left out
+pub fn compare(ours: &[FileEntry], theirs: &[FileEntry]) -> HashMap<String, Difference> {
+    let theirs: HashMap<&str, &FileEntry> = theirs
+        .iter()
+        .map(|entry| (entry.name.as_str(), entry))
+        .collect();
+    let age = |a: SystemTime, b: SystemTime| match a.duration_since(b) {
+        Ok(ahead) if ahead > MTIME_SLACK => Some(Difference::Newer),
+        Err(e) if e.duration() > MTIME_SLACK => Some(Difference::Older),
+        _ => None,
+    };
+    ours.iter()
+        .filter(|entry| entry.name != "..")
+        .filter_map(|entry| {
+            let difference = match theirs.get(entry.name.as_str()) {
+                None => Some(Difference::Only),
+                Some(other) if entry.is_dir != other.is_dir => Some(Difference::Differs),
+                Some(_) if entry.is_dir => None,
+                Some(other) => entry
+                    .modified
+                    .zip(other.modified)
+                    .and_then(|(a, b)| age(a, b))
+                    .or((entry.size != other.size).then_some(Difference::Differs)),
+            };
+            difference.map(|difference| (entry.name.clone(), difference))
+        })
+        .collect()
+}
+
+/// Where a pane on `other` goes when the linked pane went from `before`
+/// to `after`: into the same subdirectory, or up as far when `other` ends
+/// with the same names; `None` when the trees do not line up there
+fn mirrored(before: &Path, after: &Path, other: &Path) -> Option<PathBuf> {
+    if let Ok(down) = after.strip_prefix(before) {
+        let target = other.join(down);
+        return (down.components().next().is_some() && target.is_dir()).then_some(target);
+    }
+    let up = before.strip_prefix(after).ok()?;
+    if !other.ends_with(up) {
+        return None;
+    }
+    other
+        .ancestors()
+        .nth(up.components().count())
+        .map(Path::to_path_buf)
+}
+
+/// Line between the panes that sets `split_ratio` when dragged
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Divider {
+    /// Right of the first column
+    Column,
+    /// Below the first row
+    Row,
+}
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum PaneFocus {
-    Left,
-    Right,
+/// Most panes open at once
+pub const MAX_PANES: usize = 4;
+
+/// Screen area of one pane, header included
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cell {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+/// Split `total` columns or rows into `count` spans with a one-wide
+/// divider between them, as `(start, length)`; with two, the first takes
+/// `ratio` of them and the rest share evenly
+fn spans(total: u16, count: usize, ratio: f32) -> Vec<(u16, u16)> {
+    let count = count.max(1) as u16;
+    let usable = total.saturating_sub(count - 1);
+    let mut lengths = vec![usable / count; count as usize];
+    if count == 2 {
+        lengths[0] = ((total as f32 * ratio) as u16).min(usable);
+        lengths[1] = usable - lengths[0];
+    } else if let Some(last) = lengths.last_mut() {
+        *last += usable % count;
+    }
+    let mut start = 0;
+    lengths
+        .into_iter()
+        .map(|length| {
+            let span = (start, length);
+            start += length + 1;
+            span
+        })
+        .collect()
 }
 
-#[derive(Debug, Clone)]
+/// How long entering a directory waits for its listing before drawing it
+/// as still being read
+const LISTING_GRACE: Duration = Duration::from_millis(50);
+
+#[derive(Debug)]
 pub struct Pane {
     pub current_dir: PathBuf,
     pub entries: Vec<FileEntry>,
     pub selected_index: usize,
     pub selected_items: HashSet<usize>,
     pub scroll_offset: usize,
+    pub listing_options: ListingOptions,
+    /// Listing of `current_dir` still being read in the background
+    pending: Option<Task<io::Result<DirectoryListing>>>,
+    /// Text narrowing the listing, shown in the header
+    pub filter: Option<String>,
+    /// Full listing while a filter narrows `entries`
+    unfiltered_entries: Vec<FileEntry>,
 }
 
 impl Pane {
-    pub fn new(path: PathBuf) -> Result<Self> {
+    pub fn new(path: PathBuf, listing_options: ListingOptions) -> Result<Self> {
         let mut pane = Self {
             current_dir: path.clone(),
             entries: Vec::new(),
             selected_index: 0,
             selected_items: HashSet::new(),
             scroll_offset: 0,
+            listing_options,
+            pending: None,
+            filter: None,
+            unfiltered_entries: Vec::new(),
         };
         pane.load_directory(&path)?;
         Ok(pane)
     }
 
     pub fn load_directory(&mut self, path: &Path) -> Result<()> {
+        self.filter = None;
+        self.unfiltered_entries.clear();
         self.entries.clear();
         self.selected_index = 0;
         self.selected_items.clear();
@@ -51,101 +219,111 @@ impl Pane {
         // Add parent directory entry if not at root
         if let Some(parent) = path.parent() {
             if parent != path {
-                self.entries.push(FileEntry {
-                    name: "..".to_string(),
-                    path: parent.to_path_buf(),
-                    is_dir: true,
-                    is_accessible: true,
-                    is_symlink: false,
-                    permissions: None,
-                    owner: None,
-                    group: None,
-                    uid: None,
-                    gid: None,
-                });
+                self.entries.push(FileEntry::parent_link(parent));
             }
         }
 
-        // Read directory entries
-        match std::fs::read_dir(path) {
-            Ok(read_dir) => {
-                let mut dir_entries = Vec::new();
-                let mut file_entries = Vec::new();
-
-                for entry in read_dir.flatten() {
-                    let path = entry.path();
-                    let metadata = entry.metadata();
-                    let symlink_metadata = entry.path().symlink_metadata();
-
-                    let is_symlink = symlink_metadata
-                        .as_ref()
-                        .map(|m| m.file_type().is_symlink())
-                        .unwrap_or(false);
-
-                    let is_dir = metadata.as_ref().map(|m| m.is_dir()).unwrap_or(false);
-                    let is_accessible = metadata.is_ok();
+        self.current_dir = path.to_path_buf();
+        let worker_path = path.to_path_buf();
+        let options = self.listing_options.clone();
+        let mut task = Task::spawn(move |_| read_directory(&worker_path, &options));
+        // Quick directories show at once instead of flashing the placeholder
+        let listing = task.wait(LISTING_GRACE);
+        self.pending = Some(task);
+        if let Some(listing) = listing {
+            self.apply_listing(listing);
+        }
+        Ok(())
+    }
 
-                    let permissions = metadata.as_ref().ok().map(|m| {
-                        use std::os::unix::fs::PermissionsExt;
-                        m.permissions().mode()
-                    });
+    /// Read the directory again, keeping the cursor at the same position
+    pub fn reload(&mut self) -> Result<()> {
+        let dir = self.current_dir.clone();
+        let selected_index = self.selected_index;
+        self.load_directory(&dir)?;
+        self.selected_index = selected_index.min(self.entries.len().saturating_sub(1));
+        Ok(())
+    }
 
-                    let (owner, group, uid, gid) = get_owner_group(&path);
+    /// Pick up a listing finished on the background thread
+    pub fn poll(&mut self) {
+        if let Some(listing) = self.pending.as_mut().and_then(|task| task.poll()) {
+            self.apply_listing(listing);
+        }
+    }
 
-                    let name = entry.file_name().to_string_lossy().to_string();
+    fn apply_listing(&mut self, listing: io::Result<DirectoryListing>) {
+        self.pending = None;
+        // Typed before the listing came in: it narrows the full listing
+        let filter = self.filter.take();
+        if filter.is_some() {
+            self.entries = std::mem::take(&mut self.unfiltered_entries);
+        }
+        match listing {
+            Ok(listing) => self.entries.extend(listing.entries),
+            Err(e) => self.entries.push(FileEntry::error(&self.current_dir, &e)),
+        }
+        if filter.is_some() {
+            self.set_filter(filter);
+        }
+    }
 
-                    // Skip hidden files on Unix-like systems
-                    #[cfg(unix)]
-                    if name.starts_with('.') && name != ".." {
-                        continue;
-                    }
+    /// Narrow the listing to names containing `filter` (case-insensitive),
+    /// or restore the full listing with `None`, keeping the cursor on the
+    /// same entry when it is still visible
+    pub fn set_filter(&mut self, filter: Option<String>) {
+        if self.filter.is_none() {
+            self.unfiltered_entries = self.entries.clone();
+        }
+        let selected_path = self
+            .entries
+            .get(self.selected_index)
+            .map(|e| e.path.clone());
+
+        self.entries = match filter.as_deref() {
+            Some(text) if !text.is_empty() => filter_entries(&self.unfiltered_entries, text),
+            _ => self.unfiltered_entries.clone(),
+        };
+        if filter.is_none() {
+            self.unfiltered_entries.clear();
+        }
+        self.filter = filter;
 
-                    let file_entry = FileEntry {
-                        name,
-                        path,
-                        is_dir,
-                        is_accessible,
-                        is_symlink,
-                        permissions,
-                        owner,
-                        group,
-                        uid,
-                        gid,
-                    };
-
-                    if is_dir {
-                        dir_entries.push(file_entry);
-                    } else {
-                        file_entries.push(file_entry);
-                    }
-                }
+        // Indices into the old listing no longer apply
+        self.selected_items.clear();
+        self.selected_index = selected_path
+            .and_then(|path| self.entries.iter().position(|e| e.path == path))
+            .or_else(|| self.entries.iter().position(|e| e.name != ".."))
+            .unwrap_or(0);
+        self.scroll_offset = 0;
+    }
 
-                // Sort directories and files separately
-                dir_entries.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-                file_entries.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    /// Typing into an active filter; false leaves the key to the split
+    /// view's bindings
+    pub fn handle_filter_input(&mut self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        let Some(mut filter) = self.filter.clone() else {
+            return false;
+        };
 
-                // Add sorted entries (directories first)
-                self.entries.extend(dir_entries);
-                self.entries.extend(file_entries);
+        match code {
+            KeyCode::Char(c)
+                if !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                filter.push(c);
+                self.set_filter(Some(filter));
             }
-            Err(e) => {
-                self.entries.push(FileEntry {
-                    name: format!("⚠️  Error: {}", e),
-                    path: path.to_path_buf(),
-                    is_dir: false,
-                    is_accessible: false,
-                    is_symlink: false,
-                    permissions: None,
-                    owner: None,
-                    group: None,
-                    uid: None,
-                    gid: None,
-                });
+            KeyCode::Backspace => {
+                // Deleting past the start closes the filter
+                if filter.pop().is_some() {
+                    self.set_filter(Some(filter));
+                } else {
+                    self.set_filter(None);
+                }
             }
+            KeyCode::Esc => self.set_filter(None),
+            _ => return false,
         }
-
-        self.current_dir = path.to_path_buf();
-        Ok(())
+        true
     }
 
     pub fn move_up(&mut self) {
@@ -192,7 +370,6 @@ impl Pane {
         }
     }
 
-    #[allow(dead_code)]
     pub fn get_selected_paths(&self) -> Vec<PathBuf> {
         if self.selected_items.is_empty() {
             if let Some(entry) = self.entries.get(self.selected_index) {
@@ -227,34 +404,285 @@ impl Pane {
     }
 }
 
+/// Copy or move `source` into `dir`, under a new name if its own is taken;
+/// returns where it went
+fn transfer(source: &Path, dir: &Path, remove: bool) -> io::Result<PathBuf> {
+    let name = source
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "nothing to copy"))?;
+    if remove && source.parent() == Some(dir) {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "already in that directory",
+        ));
+    }
+    if dir.starts_with(source) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "cannot go inside itself",
+        ));
+    }
+
+    let target = free_path(dir, Path::new(name), !source.is_dir());
+    if remove {
+        move_path(source, &target)?;
+    } else {
+        copy_path(source, &target)?;
+    }
+    Ok(target)
+}
+
+/// What copying or moving entries to the other pane did
+#[derive(Debug, Default)]
+pub struct TransferReport {
+    /// Where the entries went
+    pub dir: PathBuf,
+    pub done: usize,
+    /// Given a new name, the one they had being taken in the destination
+    pub renamed: usize,
+    /// Renamed ones whose name differed only in case from an entry there,
+    /// with that entry's name, where the filesystem folds case
+    pub case_conflicts: Vec<(String, String)>,
+    pub failed: Vec<(PathBuf, io::Error)>,
+}
+
+impl TransferReport {
+    /// `Copied 3 items to /srv (1 renamed)`, with the first case-only
+    /// conflict and the first failure if any
+    pub fn summary(&self, verb: &str) -> String {
+        let dir = &self.dir;
+        let total = self.done + self.failed.len();
+        let mut summary = if self.failed.is_empty() {
+            format!(
+                "{} {} item{} to {}",
+                verb,
+                total,
+                if total == 1 { "" } else { "s" },
+                dir.display()
+            )
+        } else {
+            format!(
+                "{} {} of {} items to {}",
+                verb,
+                self.done,
+                total,
+                dir.display()
+            )
+        };
+        if self.renamed > 0 {
+            summary.push_str(&format!(" ({} renamed)", self.renamed));
+        }
+        if let Some((name, existing)) = self.case_conflicts.first() {
+            summary.push_str(&format!(
+                "; {} differs only in case from {}",
+                name, existing
+            ));
+        }
+        if let Some((path, error)) = self.failed.first() {
+            let name = path.file_name().unwrap_or(path.as_os_str());
+            summary.push_str(&format!("; {}: {}", name.to_string_lossy(), error));
+        }
+        summary
+    }
+}
+
 pub struct SplitPaneView {
-    pub left_pane: Pane,
-    pub right_pane: Pane,
-    pub focus: PaneFocus,
-    pub vertical_split: bool,
-    pub split_ratio: f32, // 0.0 to 1.0, percentage for left/top pane
+    pub panes: Vec<Pane>,
+    /// Index of the active pane
+    pub focus: usize,
+    pub layout: Layout,
+    pub split_ratio: f32, // 0.0 to 1.0, share of the first column and row
+    /// The next pane gives its place to a preview of the entry under the
+    /// active pane's cursor
+    pub preview: bool,
+    /// The active and next pane mark how their entries differ
+    pub compare: bool,
+    /// Moves in the active pane are repeated in the others where their
+    /// trees line up
+    pub linked: bool,
+    /// Divider being dragged with the mouse
+    dragging: Option<Divider>,
+    /// Message shown in place of the key hints until the next key
+    pub status: Option<String>,
+    /// A move is waiting for y/n
+    pub move_confirm: bool,
 }
 
 impl SplitPaneView {
-    pub fn new(left_path: PathBuf, right_path: PathBuf) -> Result<Self> {
+    pub fn new(
+        left_path: PathBuf,
+        right_path: PathBuf,
+        listing_options: ListingOptions,
+    ) -> Result<Self> {
         Ok(Self {
-            left_pane: Pane::new(left_path)?,
-            right_pane: Pane::new(right_path)?,
-            focus: PaneFocus::Left,
-            vertical_split: true,
+            panes: vec![
+                Pane::new(left_path, listing_options.clone())?,
+                Pane::new(right_path, listing_options)?,
+            ],
+            focus: 0,
+            layout: Layout::Columns,
             split_ratio: 0.5,
+            preview: false,
+            compare: false,
+            linked: false,
+            dragging: None,
+            status: None,
+            move_confirm: false,
         })
     }
 
-    pub fn toggle_focus(&mut self) {
-        self.focus = match self.focus {
-            PaneFocus::Left => PaneFocus::Right,
-            PaneFocus::Right => PaneFocus::Left,
+    pub fn poll(&mut self) {
+        for pane in &mut self.panes {
+            pane.poll();
+        }
+    }
+
+    /// Focus the next pane, or with `back` the previous one
+    pub fn cycle_focus(&mut self, back: bool) {
+        let count = self.panes.len();
+        self.focus = if back {
+            (self.focus + count - 1) % count
+        } else {
+            (self.focus + 1) % count
+        };
+    }
+
+    /// Focus pane `number`, counted from 1 as the headers show it; false
+    /// when there is no such pane
+    pub fn focus_pane(&mut self, number: u32) -> bool {
+        match (number as usize).checked_sub(1) {
+            Some(index) if index < self.panes.len() => {
+                self.focus = index;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Open a pane on the active pane's directory, after it and focused;
+    /// false when [`MAX_PANES`] are open already
+    pub fn add_pane(&mut self) -> Result<bool> {
+        if self.panes.len() >= MAX_PANES {
+            return Ok(false);
+        }
+        let active = self.get_active_pane();
+        let pane = Pane::new(active.current_dir.clone(), active.listing_options.clone())?;
+        self.focus += 1;
+        self.panes.insert(self.focus, pane);
+        Ok(true)
+    }
+
+    /// Close the active pane; false when only two are left
+    pub fn close_pane(&mut self) -> bool {
+        if self.panes.len() <= 2 {
+            return false;
+        }
+        self.panes.remove(self.focus);
+        self.focus = self.focus.min(self.panes.len() - 1);
+        if self.layout == Layout::Grid && self.panes.len() == 2 {
+            self.layout = Layout::Columns;
+        }
+        true
+    }
+
+    pub fn cycle_layout(&mut self) {
+        self.layout = self.layout.next(self.panes.len());
+    }
+
+    pub fn toggle_link(&mut self) {
+        self.linked = !self.linked;
+    }
+
+    /// Repeat in the other panes what the active one did since it was in
+    /// `before`: the directory it went to, and the entry its cursor is on
+    pub fn mirror(&mut self, before: &Path) -> Result<()> {
+        if !self.linked {
+            return Ok(());
+        }
+        let active = self.get_active_pane();
+        let after = active.current_dir.clone();
+        let name = active
+            .entries
+            .get(active.selected_index)
+            .map(|entry| entry.name.clone());
+        for (index, pane) in self.panes.iter_mut().enumerate() {
+            if index == self.focus {
+                continue;
+            }
+            if after != before {
+                match mirrored(before, &after, &pane.current_dir) {
+                    Some(dir) => pane.load_directory(&dir)?,
+                    None => continue,
+                }
+            }
+            if let Some(position) = name
+                .as_ref()
+                .and_then(|name| pane.entries.iter().position(|entry| &entry.name == name))
+            {
+                pane.selected_index = position;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn toggle_compare(&mut self) {
+        self.compare = !self.compare;
+    }
+
+    /// Differences of pane `index` from the one it is compared with, while
+    /// comparing
+    fn differences(&self, index: usize) -> Option<HashMap<String, Difference>> {
+        let other = if index == self.focus {
+            self.other_index()
+        } else if index == self.other_index() {
+            self.focus
+        } else {
+            return None;
         };
+        self.compare
+            .then(|| compare(&self.panes[index].entries, &self.panes[other].entries))
+    }
+
+    /// "3 only here, 1 newer…" for the active pane, against the next one
+    fn compare_summary(&self) -> String {
+        let differences = self.differences(self.focus).unwrap_or_default();
+        let only_there = self
+            .differences(self.other_index())
+            .unwrap_or_default()
+            .values()
+            .filter(|&&difference| difference == Difference::Only)
+            .count();
+        let count = |kind: Difference| differences.values().filter(|&&d| d == kind).count();
+        let mut parts = vec![
+            format!("{} only here", count(Difference::Only)),
+            format!("{} only there", only_there),
+        ];
+        for (kind, label) in [
+            (Difference::Newer, "newer"),
+            (Difference::Older, "older"),
+            (Difference::Differs, "differ"),
+        ] {
+            parts.push(format!("{} {}", count(kind), label));
+        }
+        format!(
+            "Compare [{}] with [{}]: {}",
+            self.focus + 1,
+            self.other_index() + 1,
+            parts.join(", ")
+        )
+    }
+
+    pub fn toggle_preview(&mut self) {
+        self.preview = !self.preview;
     }
 
-    pub fn toggle_layout(&mut self) {
-        self.vertical_split = !self.vertical_split;
+    /// Entry to preview in place of the next pane, while the preview is on
+    pub fn preview_target(&self) -> Option<&Path> {
+        let pane = self.get_active_pane();
+        self.preview
+            .then(|| pane.entries.get(pane.selected_index))
+            .flatten()
+            .map(|entry| entry.path.as_path())
     }
 
     pub fn adjust_split(&mut self, delta: f32) {
@@ -262,147 +690,343 @@ impl SplitPaneView {
     }
 
     pub fn get_active_pane(&self) -> &Pane {
-        match self.focus {
-            PaneFocus::Left => &self.left_pane,
-            PaneFocus::Right => &self.right_pane,
-        }
+        &self.panes[self.focus]
     }
 
     pub fn get_active_pane_mut(&mut self) -> &mut Pane {
-        match self.focus {
-            PaneFocus::Left => &mut self.left_pane,
-            PaneFocus::Right => &mut self.right_pane,
+        &mut self.panes[self.focus]
+    }
+
+    /// The pane after the active one, which copies and moves go to
+    fn other_index(&self) -> usize {
+        (self.focus + 1) % self.panes.len()
+    }
+
+    /// Copy the active pane's selection, or the entry under its cursor,
+    /// into the next pane's directory
+    pub fn copy_to_other_pane(&mut self) -> Result<TransferReport> {
+        self.transfer_to_other_pane(false)
+    }
+
+    /// Move the active pane's selection, or the entry under its cursor,
+    /// into the next pane's directory
+    pub fn move_to_other_pane(&mut self) -> Result<TransferReport> {
+        self.transfer_to_other_pane(true)
+    }
+
+    /// Ask whether to move the active pane's selection; `y` then goes
+    /// through [`Self::move_to_other_pane`]
+    pub fn ask_move(&mut self) {
+        let count = self.get_active_pane().get_selected_paths().len();
+        if count == 0 {
+            self.status = Some("Nothing to move".to_string());
+            return;
+        }
+        self.move_confirm = true;
+        self.status = Some(format!(
+            "Move {} item{} to {}? y: Yes, Move | n/Esc: No, Cancel",
+            count,
+            if count == 1 { "" } else { "s" },
+            self.panes[self.other_index()].current_dir.display()
+        ));
+    }
+
+    fn transfer_to_other_pane(&mut self, remove: bool) -> Result<TransferReport> {
+        let mut sources = self.get_active_pane().get_selected_paths();
+        sources.sort();
+        let target_dir = self.panes[self.other_index()].current_dir.clone();
+
+        let mut report = TransferReport {
+            dir: target_dir.clone(),
+            ..TransferReport::default()
+        };
+        for source in sources {
+            let name = source
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string());
+            let clash = name
+                .as_deref()
+                .and_then(|name| case_only_conflict(&target_dir, name).ok().flatten());
+            match transfer(&source, &target_dir, remove) {
+                Ok(target) => {
+                    report.done += 1;
+                    if target.file_name() != source.file_name() {
+                        report.renamed += 1;
+                        if let (Some(name), Some(existing)) = (name, clash) {
+                            report.case_conflicts.push((name, existing));
+                        }
+                    }
+                }
+                Err(e) => report.failed.push((source, e)),
+            }
+        }
+
+        // Every pane shows what is on disk now: the target, the source
+        // after a move, and any other pane on either directory
+        let source_dir = self.get_active_pane().current_dir.clone();
+        let other = self.other_index();
+        for (index, pane) in self.panes.iter_mut().enumerate() {
+            if index == other
+                || pane.current_dir == target_dir
+                || (remove && pane.current_dir == source_dir)
+            {
+                pane.reload()?;
+            }
+        }
+        self.get_active_pane_mut().selected_items.clear();
+        Ok(report)
+    }
+
+    /// Files to compare: two selected in the active pane, or else the one
+    /// chosen in it and in the next pane, the first pane as the old side
+    pub fn diff_paths(&self) -> Option<(PathBuf, PathBuf)> {
+        let files = |pane: &Pane| -> Vec<PathBuf> {
+            pane.get_selected_paths()
+                .into_iter()
+                .filter(|path| path.is_file())
+                .collect()
+        };
+        let active = self.get_active_pane();
+        if active.selected_items.len() == 2 {
+            if let [old, new] = &files(active)[..] {
+                return Some((old.clone(), new.clone()));
+            }
+            return None;
+        }
+        let first = self.focus.min(self.other_index());
+        let second = self.focus.max(self.other_index());
+        match (
+            &files(&self.panes[first])[..],
+            &files(&self.panes[second])[..],
+        ) {
+            ([old], [new]) => Some((old.clone(), new.clone())),
+            _ => None,
         }
     }
 
+    /// Open the directory under the active pane's cursor in the next pane,
+    /// leaving the active one where it is; false when it is not a
+    /// directory
+    pub fn open_in_other_pane(&mut self) -> Result<bool> {
+        let pane = self.get_active_pane();
+        let Some(dir) = pane
+            .entries
+            .get(pane.selected_index)
+            .filter(|entry| entry.is_dir && entry.is_accessible)
+            .map(|entry| entry.path.clone())
+        else {
+            return Ok(false);
+        };
+        let other = self.other_index();
+        self.panes[other].load_directory(&dir)?;
+        Ok(true)
+    }
+
+    /// Open the active pane's directory in every other pane
     pub fn sync_directories(&mut self) -> Result<()> {
         let target_dir = self.get_active_pane().current_dir.clone();
-        match self.focus {
-            PaneFocus::Left => self.right_pane.load_directory(&target_dir)?,
-            PaneFocus::Right => self.left_pane.load_directory(&target_dir)?,
+        for (index, pane) in self.panes.iter_mut().enumerate() {
+            if index != self.focus {
+                pane.load_directory(&target_dir)?;
+            }
         }
         Ok(())
     }
 
-    pub fn render(&mut self) -> Result<()> {
+    /// Draw the panes; with the preview on, the next pane's area is left
+    /// blank and returned for the caller to draw the preview in
+    pub fn render(&mut self) -> Result<Option<Cell>> {
         let mut stdout = io::stdout();
         let (terminal_width, terminal_height) = terminal::size()?;
 
         // Clear screen
         execute!(stdout, terminal::Clear(terminal::ClearType::All))?;
 
-        if self.vertical_split {
-            self.render_vertical_split(&mut stdout, terminal_width, terminal_height)?;
-        } else {
-            self.render_horizontal_split(&mut stdout, terminal_width, terminal_height)?;
+        let cells = self.cells(terminal_width, terminal_height.saturating_sub(2));
+        let previewed = self.preview.then(|| self.other_index());
+        for (index, cell) in cells.iter().enumerate() {
+            if Some(index) != previewed {
+                self.render_pane(&mut stdout, index, cell)?;
+            }
         }
+        self.render_dividers(&mut stdout, &cells, terminal_width)?;
 
         // Render status bar
         self.render_status_bar(&mut stdout, terminal_width, terminal_height)?;
 
         stdout.flush()?;
-        Ok(())
+        Ok(previewed.map(|index| cells[index]))
     }
 
-    fn render_vertical_split(
-        &mut self,
-        stdout: &mut io::Stdout,
-        width: u16,
-        height: u16,
-    ) -> Result<()> {
-        let split_pos = (width as f32 * self.split_ratio) as u16;
-        let left_width = split_pos.saturating_sub(1);
-        let right_width = width.saturating_sub(split_pos + 1);
-
-        // Render left pane
-        Self::render_pane(
-            stdout,
-            &mut self.left_pane,
-            0,
-            0,
-            left_width,
-            height - 2,
-            self.focus == PaneFocus::Left,
-        )?;
+    /// A click focuses the pane under the pointer and puts its cursor on
+    /// the clicked entry; pressing on a divider and dragging resizes the
+    /// panes, on a screen `width` by `height`
+    pub fn handle_mouse(&mut self, mouse: MouseEvent, width: u16, height: u16) {
+        let area = height.saturating_sub(2);
+        let cells = self.cells(width, area);
+        let (column, row) = (mouse.column, mouse.row);
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.dragging = self.divider_at(&cells, column, row);
+                if self.dragging.is_some() {
+                    return;
+                }
+                let previewed = self.preview.then(|| self.other_index());
+                let Some(index) = cells.iter().position(|cell| {
+                    (cell.x..cell.x + cell.width).contains(&column)
+                        && (cell.y..cell.y + cell.height).contains(&row)
+                }) else {
+                    return;
+                };
+                if Some(index) == previewed {
+                    return;
+                }
+                self.focus = index;
+                let pane = &mut self.panes[index];
+                if let Some(offset) = row.checked_sub(cells[index].y + 1) {
+                    let entry = pane.scroll_offset + offset as usize;
+                    if entry < pane.entries.len() {
+                        pane.selected_index = entry;
+                    }
+                }
+            }
+            MouseEventKind::Drag(MouseButton::Left) => {
+                let ratio = match self.dragging {
+                    Some(Divider::Column) => column as f32 / width.max(1) as f32,
+                    Some(Divider::Row) => row as f32 / area.max(1) as f32,
+                    None => return,
+                };
+                self.split_ratio = ratio.clamp(0.2, 0.8);
+            }
+            MouseEventKind::Up(MouseButton::Left) => self.dragging = None,
+            MouseEventKind::ScrollUp => self.get_active_pane_mut().move_up(),
+            MouseEventKind::ScrollDown => self.get_active_pane_mut().move_down(),
+            _ => {}
+        }
+    }
 
-        // Render divider
-        for y in 0..height - 2 {
-            execute!(
-                stdout,
-                MoveTo(split_pos, y),
-                SetForegroundColor(Color::DarkGrey),
-                Print("│"),
-                ResetColor
-            )?;
+    /// The divider under `column`, `row`, among those `split_ratio` moves:
+    /// between two panes, and both lines of the grid
+    fn divider_at(&self, cells: &[Cell], column: u16, row: u16) -> Option<Divider> {
+        let (columns, rows) = match self.layout {
+            Layout::Grid => (true, true),
+            Layout::Columns => (self.panes.len() == 2, false),
+            Layout::Rows => (false, self.panes.len() == 2),
+        };
+        // Only lines with a pane on their other side; the one below the
+        // last row is the blank line above the status bar
+        let right_of = |cell: &Cell| {
+            column == cell.x + cell.width
+                && (cell.y..cell.y + cell.height).contains(&row)
+                && cells.iter().any(|other| other.x == column + 1)
+        };
+        let below = |cell: &Cell| {
+            row == cell.y + cell.height
+                && (cell.x..cell.x + cell.width).contains(&column)
+                && cells.iter().any(|other| other.y == row + 1)
+        };
+        if columns && cells.iter().any(right_of) {
+            Some(Divider::Column)
+        } else if rows && cells.iter().any(below) {
+            Some(Divider::Row)
+        } else {
+            None
         }
+    }
 
-        // Render right pane
-        Self::render_pane(
-            stdout,
-            &mut self.right_pane,
-            split_pos + 1,
-            0,
-            right_width,
-            height - 2,
-            self.focus == PaneFocus::Right,
-        )?;
+    /// Where each pane goes on a `width` by `height` area
+    fn cells(&self, width: u16, height: u16) -> Vec<Cell> {
+        let count = self.panes.len();
+        match self.layout {
+            Layout::Columns => spans(width, count, self.split_ratio)
+                .into_iter()
+                .map(|(x, columns)| Cell {
+                    x,
+                    y: 0,
+                    width: columns,
+                    height,
+                })
+                .collect(),
+            Layout::Rows => spans(height, count, self.split_ratio)
+                .into_iter()
+                .map(|(y, rows)| Cell {
+                    x: 0,
+                    y,
+                    width,
+                    height: rows,
+                })
+                .collect(),
+            Layout::Grid => {
+                let rows = spans(height, 2, self.split_ratio);
+                let columns = spans(width, 2, self.split_ratio);
+                (0..count)
+                    .map(|index| {
+                        let (y, rows) = rows[index / 2];
+                        // A third pane alone on the bottom row spans it
+                        let (x, columns) = if index == 2 && count == 3 {
+                            (0, width)
+                        } else {
+                            columns[index % 2]
+                        };
+                        Cell {
+                            x,
+                            y,
+                            width: columns,
+                            height: rows,
+                        }
+                    })
+                    .collect()
+            }
+        }
+    }
 
+    /// Lines right of and below every cell that does not reach the edge
+    fn render_dividers(&self, stdout: &mut io::Stdout, cells: &[Cell], width: u16) -> Result<()> {
+        let bottom = cells
+            .iter()
+            .map(|cell| cell.y + cell.height)
+            .max()
+            .unwrap_or(0);
+        for cell in cells {
+            if cell.x + cell.width < width {
+                for y in cell.y..cell.y + cell.height {
+                    execute!(
+                        stdout,
+                        MoveTo(cell.x + cell.width, y),
+                        SetForegroundColor(Color::DarkGrey),
+                        Print("│"),
+                        ResetColor
+                    )?;
+                }
+            }
+            if cell.y + cell.height < bottom {
+                execute!(
+                    stdout,
+                    MoveTo(cell.x, cell.y + cell.height),
+                    SetForegroundColor(Color::DarkGrey),
+                    Print("─".repeat(cell.width as usize)),
+                    ResetColor
+                )?;
+            }
+        }
         Ok(())
     }
 
-    fn render_horizontal_split(
-        &mut self,
-        stdout: &mut io::Stdout,
-        width: u16,
-        height: u16,
-    ) -> Result<()> {
-        let split_pos = ((height - 2) as f32 * self.split_ratio) as u16;
-        let top_height = split_pos;
-        let bottom_height = (height - 2).saturating_sub(split_pos + 1);
-
-        // Render top pane
-        Self::render_pane(
-            stdout,
-            &mut self.left_pane,
-            0,
-            0,
-            width,
-            top_height,
-            self.focus == PaneFocus::Left,
-        )?;
-
-        // Render divider
-        execute!(
-            stdout,
-            MoveTo(0, split_pos),
-            SetForegroundColor(Color::DarkGrey),
-            Print("─".repeat(width as usize)),
-            ResetColor
-        )?;
-
-        // Render bottom pane
-        Self::render_pane(
-            stdout,
-            &mut self.right_pane,
-            0,
-            split_pos + 1,
+    fn render_pane(&mut self, stdout: &mut io::Stdout, index: usize, cell: &Cell) -> Result<()> {
+        let is_active = self.focus == index;
+        let number = index + 1;
+        let differences = self.differences(index);
+        let pane = &mut self.panes[index];
+        let Cell {
+            x,
+            y,
             width,
-            bottom_height,
-            self.focus == PaneFocus::Right,
-        )?;
-
-        Ok(())
-    }
+            height,
+        } = *cell;
+        if width < 3 || height < 2 {
+            return Ok(());
+        }
 
-    fn render_pane(
-        stdout: &mut io::Stdout,
-        pane: &mut Pane,
-        x: u16,
-        y: u16,
-        width: u16,
-        height: u16,
-        is_active: bool,
-    ) -> Result<()> {
         // Header
         let header_color = if is_active {
             Color::Blue
@@ -410,6 +1034,10 @@ impl SplitPaneView {
             Color::DarkGrey
         };
 
+        let mut title = format!("[{}] {}", number, pane.current_dir.to_string_lossy());
+        if let Some(ref filter) = pane.filter {
+            title.push_str(&format!("  / {}_", filter));
+        }
         execute!(
             stdout,
             MoveTo(x, y),
@@ -417,15 +1045,9 @@ impl SplitPaneView {
             SetForegroundColor(Color::White),
             Print(format!(
                 " {} ",
-                pane.current_dir
-                    .to_string_lossy()
-                    .chars()
-                    .take((width - 2) as usize)
-                    .collect::<String>()
-            )),
-            Print(" ".repeat(
-                (width as usize).saturating_sub(pane.current_dir.to_string_lossy().len() + 2)
+                title.chars().take((width - 2) as usize).collect::<String>()
             )),
+            Print(" ".repeat((width as usize).saturating_sub(title.chars().count() + 2))),
             ResetColor
         )?;
 
@@ -463,12 +1085,20 @@ impl SplitPaneView {
             let marker = if is_selected { "[✓]" } else { "   " };
             let prefix = if is_highlighted { ">" } else { " " };
 
-            let display_name = entry.display_name();
-            let truncated_name = if display_name.len() > (width - 5) as usize {
-                format!("{}...", &display_name[..(width - 8) as usize])
-            } else {
-                display_name
-            };
+            let difference = differences
+                .as_ref()
+                .and_then(|differences| differences.get(&entry.name));
+            let mut truncated_name = ellipsize(
+                &entry.display_name(),
+                width.saturating_sub(if differences.is_some() { 7 } else { 5 }) as usize,
+            );
+            if let Some(difference) = difference {
+                if !is_highlighted {
+                    execute!(stdout, SetForegroundColor(difference.color()))?;
+                }
+                truncated_name.push(' ');
+                truncated_name.push_str(difference.marker());
+            }
 
             execute!(
                 stdout,
@@ -476,20 +1106,36 @@ impl SplitPaneView {
             )?;
 
             if is_highlighted {
-                let padding = (width as usize)
-                    .saturating_sub(prefix.len() + marker.len() + truncated_name.len() + 1);
+                let padding = (width as usize).saturating_sub(
+                    prefix.len() + marker.len() + truncated_name.chars().count() + 1,
+                );
                 execute!(stdout, Print(" ".repeat(padding)))?;
             }
 
             execute!(stdout, ResetColor)?;
         }
 
+        let loading_row = pane.entries.len().saturating_sub(pane.scroll_offset);
+        if pane.pending.is_some() && loading_row < list_height {
+            execute!(
+                stdout,
+                MoveTo(x, y + 1 + loading_row as u16),
+                SetForegroundColor(Color::DarkGrey),
+                Print("     ⏳ Reading directory…"),
+                ResetColor
+            )?;
+        }
+
         Ok(())
     }
 
     fn render_status_bar(&self, stdout: &mut io::Stdout, width: u16, height: u16) -> Result<()> {
-        let status =
-            " Tab: Switch Pane | F5: Sync Dirs | F6: Toggle Layout | +/-: Adjust Split | q: Quit";
+        let status = match self.status {
+            Some(ref message) => format!(" {}", message),
+            None if self.compare => format!(" {} | =: Stop comparing", self.compare_summary()),
+            None if self.linked => " 🔗 Linked: moves repeat in the other panes | l: Unlink".to_string(),
+            None => " Tab/Alt+1-4: Switch Pane | Ctrl+F: Filter | Space: Select | c/m: Copy/Move to next | o: Open in next | C/O: Chmod/Chown | d: Diff | =: Compare | l: Link | p: Preview | F5: Sync Dirs | F6: Layout | F7/F8: Add/Close Pane | +/-: Adjust Split | q: Quit".to_string(),
+        };
 
         execute!(
             stdout,
@@ -497,10 +1143,450 @@ impl SplitPaneView {
             SetBackgroundColor(Color::DarkGrey),
             SetForegroundColor(Color::White),
             Print(&status),
-            Print(" ".repeat((width as usize).saturating_sub(status.len()))),
+            Print(" ".repeat((width as usize).saturating_sub(status.chars().count()))),
             ResetColor
         )?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::Instant;
+
+    /// Wait for every pane to finish reading their directories
+    fn loaded(view: &mut SplitPaneView) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while view.panes.iter().any(|pane| pane.pending.is_some()) && Instant::now() < deadline {
+            view.poll();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn select(pane: &mut Pane, names: &[&str]) {
+        pane.selected_items = pane
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| names.contains(&entry.name.as_str()))
+            .map(|(i, _)| i)
+            .collect();
+    }
+
+    #[test]
+    fn test_transfer_to_other_pane() {
+        let left = tempfile::tempdir().unwrap();
+        let right = tempfile::tempdir().unwrap();
+        fs::write(left.path().join("notes.txt"), "left").unwrap();
+        fs::write(right.path().join("notes.txt"), "right").unwrap();
+        fs::create_dir_all(left.path().join("src/nested")).unwrap();
+        fs::write(left.path().join("src/nested/lib.rs"), "code").unwrap();
+
+        let mut view = SplitPaneView::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ListingOptions::default(),
+        )
+        .unwrap();
+        loaded(&mut view);
+
+        // The taken name gets a number; the tree is copied whole
+        select(&mut view.panes[0], &["notes.txt", "src"]);
+        let report = view.copy_to_other_pane().unwrap();
+        assert_eq!((report.done, report.renamed), (2, 1));
+        assert_eq!(
+            report.summary("Copied"),
+            format!("Copied 2 items to {} (1 renamed)", right.path().display())
+        );
+        let on_usb = TransferReport {
+            dir: PathBuf::from("/mnt/usb"),
+            done: 1,
+            renamed: 1,
+            case_conflicts: vec![("notes.txt".to_string(), "Notes.txt".to_string())],
+            ..TransferReport::default()
+        };
+        assert_eq!(
+            on_usb.summary("Moved"),
+            "Moved 1 item to /mnt/usb (1 renamed); notes.txt differs only in case from Notes.txt"
+        );
+        assert_eq!(
+            fs::read_to_string(right.path().join("notes (2).txt")).unwrap(),
+            "left"
+        );
+        assert_eq!(
+            fs::read_to_string(right.path().join("notes.txt")).unwrap(),
+            "right"
+        );
+        assert!(right.path().join("src/nested/lib.rs").is_file());
+        assert!(view.panes[0].selected_items.is_empty());
+
+        loaded(&mut view);
+        assert!(view.panes[1]
+            .entries
+            .iter()
+            .any(|entry| entry.name == "notes (2).txt"));
+
+        // Moving back from the right pane empties it of the moved entry,
+        // once asked
+        view.cycle_focus(false);
+        select(&mut view.panes[1], &["src"]);
+        view.ask_move();
+        assert!(view.move_confirm);
+        assert!(view
+            .status
+            .as_deref()
+            .unwrap()
+            .starts_with("Move 1 item to"));
+        let report = view.move_to_other_pane().unwrap();
+        assert_eq!((report.done, report.renamed), (1, 1));
+        assert!(!right.path().join("src").exists());
+        assert!(left.path().join("src (2)/nested/lib.rs").is_file());
+
+        // Into itself, or where it already is: refused
+        fs::create_dir(right.path().join("inner")).unwrap();
+        view.panes[0]
+            .load_directory(&right.path().join("inner"))
+            .unwrap();
+        view.panes[1].load_directory(right.path()).unwrap();
+        loaded(&mut view);
+        select(&mut view.panes[1], &["inner"]);
+        let report = view.move_to_other_pane().unwrap();
+        assert_eq!(report.done, 0);
+        assert_eq!(report.failed.len(), 1);
+    }
+
+    #[test]
+    fn test_panes_and_layouts() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("a")).unwrap();
+        let mut view = SplitPaneView::new(
+            dir.path().to_path_buf(),
+            dir.path().join("a"),
+            ListingOptions::default(),
+        )
+        .unwrap();
+
+        // New panes open next to the active one, on its directory
+        assert!(view.add_pane().unwrap());
+        assert_eq!(view.focus, 1);
+        assert_eq!(view.panes[1].current_dir, dir.path());
+        assert!(view.add_pane().unwrap());
+        assert!(!view.add_pane().unwrap());
+        assert_eq!(view.panes.len(), MAX_PANES);
+        view.cycle_focus(false);
+        view.cycle_focus(false);
+        assert_eq!(view.focus, 0);
+        view.cycle_focus(true);
+        assert_eq!(view.focus, 3);
+        assert_eq!(view.panes[3].current_dir, dir.path().join("a"));
+
+        let cell = |x, y, width, height| Cell {
+            x,
+            y,
+            width,
+            height,
+        };
+        assert_eq!(
+            view.cells(83, 20),
+            [
+                cell(0, 0, 20, 20),
+                cell(21, 0, 20, 20),
+                cell(42, 0, 20, 20),
+                cell(63, 0, 20, 20)
+            ]
+        );
+        view.cycle_layout();
+        view.cycle_layout();
+        assert_eq!(view.layout, Layout::Grid);
+        view.split_ratio = 0.6;
+        assert_eq!(
+            view.cells(81, 21),
+            [
+                cell(0, 0, 48, 12),
+                cell(49, 0, 32, 12),
+                cell(0, 13, 48, 8),
+                cell(49, 13, 32, 8)
+            ]
+        );
+
+        // A third pane alone spans the bottom row; two go back to columns
+        assert!(view.close_pane());
+        assert_eq!(view.focus, 2);
+        assert_eq!(view.cells(81, 21)[2], cell(0, 13, 81, 8));
+        assert!(view.close_pane());
+        assert!(!view.close_pane());
+        assert_eq!(view.layout, Layout::Columns);
+        assert_eq!(view.focus, 1);
+
+        // The preview follows the active pane's cursor
+        assert_eq!(view.preview_target(), None);
+        view.toggle_preview();
+        loaded(&mut view);
+        view.get_active_pane_mut().move_down();
+        let pane = view.get_active_pane();
+        assert_eq!(
+            view.preview_target(),
+            Some(pane.entries[pane.selected_index].path.as_path())
+        );
+    }
+
+    #[test]
+    fn test_compare() {
+        let now = SystemTime::now();
+        let entry = |name: &str, is_dir: bool, size: u64, age: u64| {
+            let mut entry = FileEntry::parent_link(Path::new("/"));
+            entry.name = name.to_string();
+            entry.is_dir = is_dir;
+            entry.size = size;
+            entry.modified = Some(now - Duration::from_secs(age));
+            entry
+        };
+        let ours = [
+            entry("..", true, 0, 0),
+            entry("src", true, 0, 0),
+            entry("same.txt", false, 5, 100),
+            entry("fresh.txt", false, 5, 10),
+            entry("stale.txt", false, 5, 100),
+            entry("resized.txt", false, 9, 100),
+            entry("build", false, 0, 100),
+            entry("mine.txt", false, 1, 0),
+        ];
+        let theirs = [
+            entry("..", true, 0, 0),
+            entry("src", true, 0, 500),
+            entry("same.txt", false, 5, 101),
+            entry("fresh.txt", false, 5, 100),
+            entry("stale.txt", false, 5, 10),
+            entry("resized.txt", false, 4, 100),
+            entry("build", true, 0, 100),
+            entry("theirs.txt", false, 1, 0),
+        ];
+
+        let differences = compare(&ours, &theirs);
+        let mut found: Vec<(&str, Difference)> = differences
+            .iter()
+            .map(|(name, &difference)| (name.as_str(), difference))
+            .collect();
+        found.sort_by_key(|&(name, _)| name);
+        assert_eq!(
+            found,
+            [
+                ("build", Difference::Differs),
+                ("fresh.txt", Difference::Newer),
+                ("mine.txt", Difference::Only),
+                ("resized.txt", Difference::Differs),
+                ("stale.txt", Difference::Older),
+            ]
+        );
+        assert_eq!(
+            compare(&theirs, &ours).get("theirs.txt"),
+            Some(&Difference::Only)
+        );
+    }
+
+    #[test]
+    fn test_open_in_other_pane() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("logs")).unwrap();
+        fs::write(dir.path().join("notes.txt"), "").unwrap();
+        let mut view = SplitPaneView::new(
+            dir.path().to_path_buf(),
+            dir.path().to_path_buf(),
+            ListingOptions::default(),
+        )
+        .unwrap();
+        loaded(&mut view);
+
+        let position = |view: &SplitPaneView, name: &str| {
+            view.panes[0]
+                .entries
+                .iter()
+                .position(|entry| entry.name == name)
+                .unwrap()
+        };
+        view.panes[0].selected_index = position(&view, "notes.txt");
+        assert!(!view.open_in_other_pane().unwrap());
+        view.panes[0].selected_index = position(&view, "logs");
+        assert!(view.open_in_other_pane().unwrap());
+        assert_eq!(view.panes[1].current_dir, dir.path().join("logs"));
+        assert_eq!(view.panes[0].current_dir, dir.path());
+        assert_eq!(view.focus, 0);
+    }
+
+    #[test]
+    fn test_pane_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["Cargo.toml", "cargo.lock", "README.md"] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+        let mut view = SplitPaneView::new(
+            dir.path().to_path_buf(),
+            dir.path().to_path_buf(),
+            ListingOptions::default(),
+        )
+        .unwrap();
+        loaded(&mut view);
+        let names = |pane: &Pane| -> Vec<String> {
+            pane.entries
+                .iter()
+                .map(|entry| entry.name.clone())
+                .collect()
+        };
+
+        let pane = &mut view.panes[0];
+        assert!(!pane.handle_filter_input(KeyCode::Char('c'), KeyModifiers::NONE));
+        pane.set_filter(Some(String::new()));
+        for c in "CARGO".chars() {
+            assert!(pane.handle_filter_input(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+        assert_eq!(names(pane), ["cargo.lock", "Cargo.toml"]);
+        assert_eq!(pane.selected_index, 0);
+
+        // Bindings pass through; Esc brings the listing back
+        assert!(!pane.handle_filter_input(KeyCode::Down, KeyModifiers::NONE));
+        pane.move_down();
+        assert!(pane.handle_filter_input(KeyCode::Esc, KeyModifiers::NONE));
+        assert_eq!(pane.filter, None);
+        assert_eq!(names(pane).len(), 4);
+        assert_eq!(pane.entries[pane.selected_index].name, "Cargo.toml");
+        assert_eq!(names(&view.panes[1]).len(), 4);
+    }
+
+    #[test]
+    fn test_mouse() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["a", "b", "c"] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+        let mut view = SplitPaneView::new(
+            dir.path().to_path_buf(),
+            dir.path().to_path_buf(),
+            ListingOptions::default(),
+        )
+        .unwrap();
+        loaded(&mut view);
+        let mouse = |view: &mut SplitPaneView, kind, column, row| {
+            let event = MouseEvent {
+                kind,
+                column,
+                row,
+                modifiers: KeyModifiers::NONE,
+            };
+            view.handle_mouse(event, 81, 22);
+        };
+        let press = MouseEventKind::Down(MouseButton::Left);
+        let drag = MouseEventKind::Drag(MouseButton::Left);
+        let release = MouseEventKind::Up(MouseButton::Left);
+
+        // Row 3 is the third entry below the header
+        mouse(&mut view, press, 60, 3);
+        assert_eq!(view.focus, 1);
+        assert_eq!(
+            view.panes[1].entries[view.panes[1].selected_index].name,
+            "b"
+        );
+        mouse(&mut view, press, 60, 15);
+        assert_eq!(view.panes[1].selected_index, 2);
+
+        // The divider follows the pointer, within bounds
+        mouse(&mut view, press, 40, 8);
+        mouse(&mut view, drag, 32, 8);
+        assert_eq!(view.split_ratio, 32.0 / 81.0);
+        mouse(&mut view, drag, 2, 8);
+        assert_eq!(view.split_ratio, 0.2);
+        mouse(&mut view, release, 2, 8);
+        mouse(&mut view, drag, 60, 8);
+        assert_eq!(view.split_ratio, 0.2);
+        assert_eq!(view.focus, 1);
+
+        // In rows, the line between them
+        view.cycle_layout();
+        view.split_ratio = 0.5;
+        mouse(&mut view, press, 30, 10);
+        mouse(&mut view, drag, 30, 14);
+        assert_eq!(view.split_ratio, 0.7);
+        mouse(&mut view, press, 30, 20);
+        mouse(&mut view, drag, 30, 5);
+        assert_eq!(view.split_ratio, 0.7);
+    }
+
+    #[test]
+    fn test_linked_panes() {
+        let old = tempfile::tempdir().unwrap();
+        let new = tempfile::tempdir().unwrap();
+        for root in [old.path(), new.path()] {
+            fs::create_dir_all(root.join("src/ui")).unwrap();
+            fs::write(root.join("src/main.rs"), "").unwrap();
+            fs::write(root.join("src/lib.rs"), "").unwrap();
+        }
+        fs::create_dir(old.path().join("docs")).unwrap();
+
+        assert_eq!(
+            mirrored(old.path(), &old.path().join("src/ui"), new.path()),
+            Some(new.path().join("src/ui"))
+        );
+        assert_eq!(
+            mirrored(
+                &old.path().join("docs"),
+                old.path(),
+                &new.path().join("src")
+            ),
+            None
+        );
+        assert_eq!(
+            mirrored(
+                &old.path().join("src/ui"),
+                old.path(),
+                &new.path().join("src/ui")
+            ),
+            Some(new.path().to_path_buf())
+        );
+
+        let mut view = SplitPaneView::new(
+            old.path().to_path_buf(),
+            new.path().to_path_buf(),
+            ListingOptions::default(),
+        )
+        .unwrap();
+        loaded(&mut view);
+        view.toggle_link();
+        let point = |view: &mut SplitPaneView, name: &str| {
+            let pane = view.get_active_pane_mut();
+            pane.selected_index = pane
+                .entries
+                .iter()
+                .position(|entry| entry.name == name)
+                .unwrap();
+        };
+
+        // Into src, with the cursor following by name
+        point(&mut view, "src");
+        view.mirror(old.path()).unwrap();
+        assert_eq!(
+            view.panes[1].entries[view.panes[1].selected_index].name,
+            "src"
+        );
+        view.get_active_pane_mut().navigate_to_selected().unwrap();
+        view.mirror(old.path()).unwrap();
+        loaded(&mut view);
+        assert_eq!(view.panes[1].current_dir, new.path().join("src"));
+        point(&mut view, "main.rs");
+        view.mirror(&old.path().join("src")).unwrap();
+        assert_eq!(
+            view.panes[1].entries[view.panes[1].selected_index].name,
+            "main.rs"
+        );
+
+        // Where the other tree has no such directory it stays put
+        view.get_active_pane_mut().navigate_up().unwrap();
+        view.mirror(&old.path().join("src")).unwrap();
+        loaded(&mut view);
+        point(&mut view, "docs");
+        view.get_active_pane_mut().navigate_to_selected().unwrap();
+        view.mirror(old.path()).unwrap();
+        assert_eq!(view.panes[1].current_dir, new.path());
+    }
+}
@@ -0,0 +1,210 @@
+use std::path::Path;
+
+use crate::custom_commands::{shell_quote, CommandSpec, CustomCommand};
+
+/// Suffixes of systemd unit files
+const UNIT_EXTENSIONS: &[&str] = &[
+    "service",
+    "timer",
+    "socket",
+    "mount",
+    "automount",
+    "path",
+    "target",
+    "slice",
+];
+
+/// Keys worth summarizing, per section; everything else stays in the
+/// file body below the summary
+const KEY_FIELDS: &[(&str, &[&str])] = &[
+    ("Unit", &["Description", "After", "Requires", "Wants"]),
+    (
+        "Service",
+        &[
+            "Type",
+            "ExecStart",
+            "User",
+            "Group",
+            "WorkingDirectory",
+            "Restart",
+        ],
+    ),
+    (
+        "Timer",
+        &[
+            "OnCalendar",
+            "OnBootSec",
+            "OnActiveSec",
+            "OnUnitActiveSec",
+            "Persistent",
+            "Unit",
+        ],
+    ),
+    ("Socket", &["ListenStream", "ListenDatagram", "Accept"]),
+    ("Install", &["WantedBy", "RequiredBy", "Alias"]),
+];
+
+pub fn is_unit_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| UNIT_EXTENSIONS.contains(&e))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitSection {
+    pub name: String,
+    pub entries: Vec<(String, String)>,
+}
+
+/// The sections and settings of a unit file
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitFile {
+    pub sections: Vec<UnitSection>,
+}
+
+impl UnitFile {
+    pub fn parse(content: &str) -> Self {
+        let mut sections: Vec<UnitSection> = Vec::new();
+        let mut pending = String::new();
+
+        for line in content.lines() {
+            let line = line.trim();
+            if pending.is_empty() && (line.starts_with('#') || line.starts_with(';')) {
+                continue;
+            }
+            // A trailing backslash continues the setting on the next line
+            if let Some(part) = line.strip_suffix('\\') {
+                pending.push_str(part.trim_end());
+                pending.push(' ');
+                continue;
+            }
+            pending.push_str(line);
+            let line = std::mem::take(&mut pending);
+
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                sections.push(UnitSection {
+                    name: name.to_string(),
+                    entries: Vec::new(),
+                });
+            } else if let (Some((key, value)), Some(section)) =
+                (line.split_once('='), sections.last_mut())
+            {
+                section
+                    .entries
+                    .push((key.trim().to_string(), value.trim().to_string()));
+            }
+        }
+
+        Self { sections }
+    }
+
+    /// Section headers followed by their key settings, e.g. `[Timer]` then
+    /// `  OnCalendar=daily`
+    pub fn summary_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for section in &self.sections {
+            let Some((_, keys)) = KEY_FIELDS.iter().find(|(name, _)| *name == section.name) else {
+                continue;
+            };
+            let entries: Vec<&(String, String)> = section
+                .entries
+                .iter()
+                .filter(|(key, value)| keys.contains(&key.as_str()) && !value.is_empty())
+                .collect();
+            if entries.is_empty() {
+                continue;
+            }
+            lines.push(format!("[{}]", section.name));
+            lines.extend(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| format!("  {}={}", key, value)),
+            );
+        }
+        lines
+    }
+}
+
+/// `systemctl status` for the unit defined by the file at `path`, as a
+/// command run like the configured custom commands
+pub fn status_command(path: &Path) -> Option<CustomCommand> {
+    let unit = path.file_name()?.to_string_lossy().to_string();
+    // Units under a `systemd/user` directory belong to the user's manager
+    let user = path
+        .to_string_lossy()
+        .split('/')
+        .collect::<Vec<_>>()
+        .windows(2)
+        .any(|pair| pair == ["systemd", "user"]);
+
+    let command = format!(
+        "systemctl{} status --no-pager --full -- {}",
+        if user { " --user" } else { "" },
+        // `%` would be taken for a placeholder
+        shell_quote(Path::new(&unit)).replace('%', "%%")
+    );
+    Some(CustomCommand::new(
+        &format!("{} status", unit),
+        &CommandSpec::Line(command),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse_unit_summary() {
+        let unit = UnitFile::parse(
+            "# Backup job\n\
+             [Unit]\n\
+             Description=Nightly backup\n\
+             Documentation=man:backup(8)\n\
+             \n\
+             [Service]\n\
+             Type=oneshot\n\
+             ExecStart=/usr/bin/backup \\\n\
+             \x20   --verbose\n\
+             \n\
+             [X-Custom]\n\
+             Foo=bar\n\
+             \n\
+             [Install]\n\
+             WantedBy=multi-user.target\n",
+        );
+        assert_eq!(unit.sections.len(), 4);
+        assert_eq!(
+            unit.summary_lines(),
+            vec![
+                "[Unit]",
+                "  Description=Nightly backup",
+                "[Service]",
+                "  Type=oneshot",
+                "  ExecStart=/usr/bin/backup --verbose",
+                "[Install]",
+                "  WantedBy=multi-user.target",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_status_command() {
+        assert!(is_unit_file(Path::new("/etc/systemd/system/backup.timer")));
+        assert!(!is_unit_file(Path::new("notes.txt")));
+
+        let system = status_command(Path::new("/etc/systemd/system/backup.service")).unwrap();
+        assert_eq!(system.name, "backup.service status");
+        assert_eq!(
+            system.expand(None, &[], Path::new("/")),
+            "systemctl status --no-pager --full -- 'backup.service'"
+        );
+
+        let home = PathBuf::from("/home/u/.config/systemd/user/sync@%i.service");
+        let user = status_command(&home).unwrap();
+        assert_eq!(
+            user.expand(None, &[], Path::new("/")),
+            "systemctl --user status --no-pager --full -- 'sync@%i.service'"
+        );
+    }
+}
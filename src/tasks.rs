@@ -0,0 +1,109 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// One piece of blocking work run on its own thread, with its result picked
+/// up by the UI thread.
+///
+/// The UI thread renders and handles keys; reads whose cost grows with the
+/// disk or the directory (listings with their stat and owner lookups,
+/// READMEs, previews) go through a `Task` instead, so a slow or hung mount
+/// never freezes the screen. The UI thread asks for the result with
+/// [`Task::wait`] for a short grace period, so fast disks never show a
+/// placeholder, then keeps drawing and calls [`Task::poll`] once per
+/// event-loop pass until the result arrives. Rendering itself never touches
+/// the filesystem: it draws what the last task delivered.
+///
+/// Dropping a task cancels it: a worker that has not started yet returns at
+/// once, and one already under way finishes with its result thrown away.
+/// Long-running work checks the flag it is handed to stop early.
+#[derive(Debug)]
+pub struct Task<T> {
+    receiver: Receiver<T>,
+    cancel: Arc<AtomicBool>,
+    done: bool,
+}
+
+impl<T: Send + 'static> Task<T> {
+    pub fn spawn<F>(work: F) -> Self
+    where
+        F: FnOnce(&AtomicBool) -> T + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let worker_cancel = Arc::clone(&cancel);
+
+        thread::spawn(move || {
+            if worker_cancel.load(Ordering::Relaxed) {
+                return;
+            }
+            let result = work(&worker_cancel);
+            if !worker_cancel.load(Ordering::Relaxed) {
+                let _ = sender.send(result);
+            }
+        });
+
+        Self {
+            receiver,
+            cancel,
+            done: false,
+        }
+    }
+}
+
+impl<T> Task<T> {
+    /// The result if it gets ready within `timeout`; returns it only once
+    pub fn wait(&mut self, timeout: Duration) -> Option<T> {
+        if self.done {
+            return None;
+        }
+        let result = self.receiver.recv_timeout(timeout).ok()?;
+        self.done = true;
+        Some(result)
+    }
+
+    pub fn poll(&mut self) -> Option<T> {
+        self.wait(Duration::ZERO)
+    }
+
+    pub fn is_pending(&self) -> bool {
+        !self.done
+    }
+}
+
+impl<T> Drop for Task<T> {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn test_task_result_and_cancel() {
+        let mut task = Task::spawn(|_| 6 * 7);
+        assert_eq!(task.wait(Duration::from_secs(5)), Some(42));
+        assert!(!task.is_pending());
+        // A result is handed over once
+        assert_eq!(task.poll(), None);
+
+        // Dropping a task tells a worker that is still running to stop
+        let (started, on_start) = channel();
+        let (stopped, on_stop) = channel();
+        let task = Task::spawn(move |cancel: &AtomicBool| {
+            started.send(()).unwrap();
+            while !cancel.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(1));
+            }
+            stopped.send(()).unwrap();
+        });
+        on_start.recv_timeout(Duration::from_secs(5)).unwrap();
+        drop(task);
+        assert!(on_stop.recv_timeout(Duration::from_secs(5)).is_ok());
+    }
+}
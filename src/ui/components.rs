@@ -1,12 +1,12 @@
+use super::theme::{SetBackgroundColor, SetForegroundColor};
 use anyhow::Result;
 use crossterm::{
     cursor::MoveTo,
     execute,
-    style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor},
+    style::{Color, Print, ResetColor},
 };
 use std::io;
 
-#[allow(dead_code)]
 pub fn draw_box(
     stdout: &mut io::Stdout,
     x: u16,
@@ -28,12 +28,12 @@ pub fn draw_box(
 
     // Title if provided
     if let Some(title) = title {
-        let title_len = title.len().min((width - 4) as usize);
+        let title: String = title.chars().take((width - 4) as usize).collect();
         execute!(
             stdout,
             MoveTo(x + 2, y),
             Print(" "),
-            Print(&title[..title_len]),
+            Print(title),
             Print(" ")
         )?;
     }
@@ -1,4 +1,13 @@
 mod components;
+mod mouse;
 mod renderer;
+#[cfg(test)]
+mod screen;
+mod theme;
 
-pub use renderer::{RenderContext, Renderer};
+pub use components::draw_box;
+pub use mouse::DragSelect;
+pub use renderer::{list_entry_at, list_rows, RenderContext, Renderer, HINT_PERIOD};
+#[cfg(test)]
+pub use screen::Screen;
+pub use theme::{color_level, set_color_level, ColorLevel, SetBackgroundColor, SetForegroundColor};
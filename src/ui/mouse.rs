@@ -0,0 +1,79 @@
+use std::collections::HashSet;
+
+/// A press, and the drag that may follow it, over the file list in
+/// selection mode
+///
+/// A plain press starts a new selection: the rows between the pressed one
+/// and the one under the pointer. With Ctrl held the selection is kept and
+/// the pressed row is toggled, together with every row dragged over.
+pub struct DragSelect {
+    anchor: usize,
+    /// Whether the rows dragged over end up selected or deselected
+    select: bool,
+    /// The selection before the press
+    base: HashSet<usize>,
+}
+
+impl DragSelect {
+    pub fn start(anchor: usize, toggle: bool, selection: &HashSet<usize>) -> Self {
+        if toggle {
+            Self {
+                anchor,
+                select: !selection.contains(&anchor),
+                base: selection.clone(),
+            }
+        } else {
+            Self {
+                anchor,
+                select: true,
+                base: HashSet::new(),
+            }
+        }
+    }
+
+    /// The selection with the pointer over row `current`; rows for which
+    /// `selectable` is false, such as `..`, are left out
+    pub fn selection(&self, current: usize, selectable: impl Fn(usize) -> bool) -> HashSet<usize> {
+        let mut selection = self.base.clone();
+        let range = self.anchor.min(current)..=self.anchor.max(current);
+        for index in range.filter(|&index| selectable(index)) {
+            if self.select {
+                selection.insert(index);
+            } else {
+                selection.remove(&index);
+            }
+        }
+        selection
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(selection: HashSet<usize>) -> Vec<usize> {
+        let mut selection: Vec<usize> = selection.into_iter().collect();
+        selection.sort_unstable();
+        selection
+    }
+
+    #[test]
+    fn test_drag_select() {
+        let selection = HashSet::from([1, 2, 8]);
+        // Row 0 is the parent link
+        let selectable = |index: usize| index != 0;
+
+        // A plain press and drag replaces the selection, in either direction
+        let drag = DragSelect::start(5, false, &selection);
+        assert_eq!(sorted(drag.selection(5, selectable)), [5]);
+        assert_eq!(sorted(drag.selection(7, selectable)), [5, 6, 7]);
+        assert_eq!(sorted(drag.selection(0, selectable)), [1, 2, 3, 4, 5]);
+
+        // Ctrl toggles on top of it: a selected anchor deselects the range
+        let drag = DragSelect::start(2, true, &selection);
+        assert_eq!(sorted(drag.selection(2, selectable)), [1, 8]);
+        assert_eq!(sorted(drag.selection(0, selectable)), [8]);
+        let drag = DragSelect::start(3, true, &selection);
+        assert_eq!(sorted(drag.selection(4, selectable)), [1, 2, 3, 4, 8]);
+    }
+}
@@ -2,7 +2,7 @@ use anyhow::Result;
 use crossterm::{
     cursor::MoveTo,
     execute,
-    style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor},
+    style::{Attribute, Color, Print, ResetColor, SetAttribute},
     terminal::{self, Clear, ClearType},
 };
 use std::{
@@ -11,9 +11,13 @@ use std::{
     path::Path,
 };
 
+use super::theme::{SetBackgroundColor, SetForegroundColor};
+use crate::checksums::ChecksumRun;
 use crate::models::FileEntry;
 use crate::navigator::NavigatorMode;
+use crate::preview::ReadmeStrip;
 use crate::search::SearchMode;
+use crate::utils::{ellipsize, truncate_chars, PatternMode};
 
 pub struct RenderContext<'a> {
     pub current_dir: &'a Path,
@@ -25,9 +29,64 @@ pub struct RenderContext<'a> {
     pub mode: &'a NavigatorMode,
     pub is_root: bool,
     pub pattern_input: &'a str,
+    pub pattern_mode: PatternMode,
+    /// Type-to-filter text while a browse filter is active
+    pub filter: Option<&'a str>,
+    /// Names pinned to the top of the current directory
+    pub pinned: &'a [String],
     pub status_message: &'a Option<String>,
     pub search_mode: Option<&'a SearchMode>,
     pub preview_focused: bool,
+    /// README of the current directory, drawn below the list
+    pub readme: Option<&'a ReadmeStrip>,
+    /// Canonical path of the current directory, when symbolic links led
+    /// there
+    pub real_dir: Option<&'a Path>,
+    /// Files that appeared in hot directories since they were opened
+    pub hot_new: usize,
+    /// Verification of the current directory's checksum files, marking
+    /// the entries they list
+    pub checksums: Option<&'a ChecksumRun>,
+    /// Pattern typed at the focused preview's `/` prompt, and its matches
+    pub preview_search: Option<(&'a str, usize)>,
+    /// The directory is still being read in the background
+    pub loading: bool,
+    /// Count and combined size of the selected items, right of the status
+    pub selection_total: Option<&'a str>,
+    /// Steps recorded so far while a workflow is being recorded
+    pub recording: Option<usize>,
+    /// Counts up every [`HINT_PERIOD`] to rotate the footer hints that do
+    /// not fit
+    pub hint_turn: usize,
+}
+
+/// Screen row of the first entry of the file list
+const LIST_START: u16 = 3;
+
+/// Index of the entry drawn on screen row `row`, if the list takes that
+/// row; with `clamp`, rows above and below it count as its first and last,
+/// so a drag past its edges stays in the list
+pub fn list_entry_at(
+    row: u16,
+    clamp: bool,
+    scroll_offset: usize,
+    terminal_height: u16,
+    readme: Option<&ReadmeStrip>,
+) -> Option<usize> {
+    let rows = list_rows(terminal_height, readme);
+    let offset = row.checked_sub(LIST_START).map(usize::from);
+    match offset {
+        Some(offset) if offset < rows => Some(scroll_offset + offset),
+        _ if !clamp || rows == 0 => None,
+        None => Some(scroll_offset),
+        Some(_) => Some(scroll_offset + rows - 1),
+    }
+}
+
+/// Rows of the file list, less those a README strip takes
+pub fn list_rows(terminal_height: u16, readme: Option<&ReadmeStrip>) -> usize {
+    let rows = (terminal_height as usize).saturating_sub(5);
+    rows - readme.map_or(0, |readme| readme.rows(rows))
 }
 
 pub struct Renderer {
@@ -42,88 +101,130 @@ impl Renderer {
     pub fn render(&self, ctx: RenderContext) -> Result<()> {
         let mut stdout = io::stdout();
         let (terminal_width, _) = terminal::size()?;
+        self.draw(&mut stdout, &ctx, terminal_width)?;
+        stdout.flush()?;
+        Ok(())
+    }
 
+    /// Draw the browse view to `out`, a terminal `terminal_width` columns wide
+    fn draw(&self, out: &mut impl Write, ctx: &RenderContext, terminal_width: u16) -> Result<()> {
         // Clear screen
-        execute!(stdout, Clear(ClearType::All), MoveTo(0, 0))?;
+        execute!(out, Clear(ClearType::All), MoveTo(0, 0))?;
 
         // Draw header with breadcrumb
-        self.render_header(&mut stdout, ctx.current_dir, ctx.is_root, terminal_width)?;
+        self.render_header(out, ctx, terminal_width)?;
 
         // Mode indicator - now includes search mode properly
-        self.render_mode(&mut stdout, ctx.mode, ctx.pattern_input, ctx.search_mode)?;
+        self.render_mode(out, ctx, terminal_width)?;
 
         // Draw file list
-        self.render_file_list(&mut stdout, &ctx)?;
+        self.render_file_list(out, ctx, terminal_width)?;
 
         // Status message
         if let Some(ref msg) = ctx.status_message {
-            self.render_status(&mut stdout, msg, ctx.terminal_height)?;
+            self.render_status(out, msg, ctx.terminal_height, terminal_width)?;
+        }
+        if let Some(total) = ctx.selection_total {
+            self.render_selection_total(out, ctx, total, terminal_width)?;
         }
 
         // Draw footer with controls
-        self.render_footer(
-            &mut stdout,
-            ctx.mode,
-            ctx.is_root,
-            ctx.preview_focused,
-            ctx.terminal_height,
-            terminal_width,
-        )?;
-
-        stdout.flush()?;
+        self.render_footer(out, ctx, terminal_width)?;
         Ok(())
     }
 
     fn render_header(
         &self,
-        stdout: &mut io::Stdout,
-        current_dir: &Path,
-        is_root: bool,
+        out: &mut impl Write,
+        ctx: &RenderContext,
         terminal_width: u16,
     ) -> Result<()> {
-        let header_text = if is_root {
-            format!(" 📂 {} [ROOT MODE]", current_dir.display())
-        } else {
-            format!(" 📂 {}", current_dir.display())
-        };
+        // The path as navigated, then where symbolic links lead
+        let mut header_text = format!(" 📂 {}", ctx.current_dir.display());
+        if let Some(real_dir) = ctx.real_dir {
+            header_text.push_str(&format!(" → {}", real_dir.display()));
+        }
+        if ctx.is_root {
+            header_text.push_str(" [ROOT MODE]");
+        }
+        if ctx.hot_new > 0 {
+            header_text.push_str(&format!("  🔥 {} new (N)", ctx.hot_new));
+        }
 
         execute!(
-            stdout,
+            out,
             SetBackgroundColor(Color::DarkBlue),
             SetForegroundColor(Color::White),
             Print(" ".repeat(terminal_width as usize)),
             MoveTo(0, 0),
-            Print(&header_text),
+            Print(truncate_chars(&header_text, terminal_width as usize)),
             ResetColor
         )?;
 
         Ok(())
     }
 
-    // In ui/renderer.rs, update the render_mode function to handle Search mode properly:
     fn render_mode(
         &self,
-        stdout: &mut io::Stdout,
-        mode: &NavigatorMode,
-        pattern_input: &str,
-        search_mode: Option<&SearchMode>,
+        out: &mut impl Write,
+        ctx: &RenderContext,
+        terminal_width: u16,
     ) -> Result<()> {
-        let mode_text = match mode {
-            NavigatorMode::Browse => "BROWSE".to_string(),
+        let pattern_input = ctx.pattern_input;
+        let mode_text = match ctx.mode {
+            NavigatorMode::Browse => match (ctx.preview_search, ctx.filter) {
+                (Some((pattern, found)), _) => format!(
+                    "PREVIEW SEARCH: {}_  ({} matches, Enter: done, Esc: cancel)",
+                    pattern, found
+                ),
+                (None, Some(filter)) => format!(
+                    "FILTER: {}_  ({} matches, Esc: clear)",
+                    filter,
+                    ctx.entries.iter().filter(|e| e.name != "..").count()
+                ),
+                (None, None) => "BROWSE".to_string(),
+            },
             NavigatorMode::Select => "SELECT (Space: toggle, Enter: confirm)".to_string(),
-            NavigatorMode::PatternSelect => format!("PATTERN: {}_", pattern_input),
+            NavigatorMode::PatternSelect => {
+                format!("PATTERN ({}): {}_", ctx.pattern_mode.label(), pattern_input)
+            }
             NavigatorMode::Search => {
-                if let Some(search) = search_mode {
+                if let Some(search) = ctx.search_mode {
+                    let progress = if search.is_searching() {
+                        format!("  searching… {} found (Esc: stop)", search.results.len())
+                    } else if !search.results.is_empty() {
+                        format!("  {} results", search.results.len())
+                    } else {
+                        String::new()
+                    };
                     format!(
-                        "SEARCH: {}_  [Regex: {}] [Case: {}] [Content: {}]",
+                        "SEARCH: {}_  [Regex: {}] [Case: {}] [Content: {}{}] [Scope: {}]{}{}{}{}",
                         search.query,
                         if search.use_regex { "ON" } else { "OFF" },
                         if search.case_sensitive { "ON" } else { "OFF" },
-                        if search.search_in_contents {
-                            "ON"
+                        match (search.search_in_contents, search.uses_ripgrep()) {
+                            (true, true) => "ON (rg)",
+                            (true, false) => "ON",
+                            _ => "OFF",
+                        },
+                        if search.search_in_contents && search.search_archives {
+                            " + archives"
                         } else {
-                            "OFF"
-                        }
+                            ""
+                        },
+                        search.scope_label(),
+                        match search.filter_count() {
+                            0 => String::new(),
+                            1 => " [1 filter]".to_string(),
+                            n => format!(" [{} filters]", n),
+                        },
+                        if search.use_locate { " [Locate]" } else { "" },
+                        match (search.has_excludes(), search.use_excludes) {
+                            (true, true) => " [Exclude: ON]",
+                            (true, false) => " [Exclude: OFF]",
+                            _ => "",
+                        },
+                        progress
                     )
                 } else {
                     "SEARCH: _".to_string()
@@ -131,13 +232,25 @@ impl Renderer {
             }
             _ => String::new(),
         };
+        let mode_text = match ctx.recording {
+            Some(steps) if !mode_text.is_empty() => format!(
+                "{}  ● REC {} step{} (w: stop)",
+                mode_text,
+                steps,
+                if steps == 1 { "" } else { "s" }
+            ),
+            _ => mode_text,
+        };
 
         if !mode_text.is_empty() {
             execute!(
-                stdout,
+                out,
                 MoveTo(0, 1),
                 SetForegroundColor(Color::Yellow),
-                Print(format!(" Mode: {} ", mode_text)),
+                Print(truncate_chars(
+                    &format!(" Mode: {} ", mode_text),
+                    terminal_width as usize
+                )),
                 ResetColor
             )?;
         }
@@ -145,15 +258,19 @@ impl Renderer {
         Ok(())
     }
 
-    fn render_file_list(&self, stdout: &mut io::Stdout, ctx: &RenderContext) -> Result<()> {
-        let (terminal_width, _) = terminal::size()?;
-        let list_start = 3;
-        let visible_area = (ctx.terminal_height as usize).saturating_sub(5);
+    fn render_file_list(
+        &self,
+        out: &mut impl Write,
+        ctx: &RenderContext,
+        terminal_width: u16,
+    ) -> Result<()> {
+        let list_start = LIST_START as usize;
+        let visible_area = list_rows(ctx.terminal_height, ctx.readme);
         let end_index = (ctx.scroll_offset + visible_area).min(ctx.entries.len());
 
         for (i, entry) in ctx.entries[ctx.scroll_offset..end_index].iter().enumerate() {
             let row = (list_start + i) as u16;
-            execute!(stdout, MoveTo(0, row))?;
+            execute!(out, MoveTo(0, row))?;
 
             let display_index = ctx.scroll_offset + i;
             let is_selected = ctx.selected_items.contains(&display_index);
@@ -164,22 +281,31 @@ impl Renderer {
 
             if is_highlighted {
                 execute!(
-                    stdout,
+                    out,
                     SetBackgroundColor(Color::DarkGrey),
                     SetForegroundColor(Color::White)
                 )?;
             }
 
+            // Columns left on the row; each part is cut to fit
+            let mut remaining = terminal_width as usize;
+
             // Show selection checkbox in select mode
             if *ctx.mode == NavigatorMode::Select {
-                execute!(stdout, Print(format!(" {} ", selection_marker)))?;
+                let marker = format!(" {} ", selection_marker);
+                print_clipped(out, &marker, &mut remaining)?;
             }
 
             // Entry name
+            let pin_marker = if ctx.pinned.contains(&entry.name) {
+                " 📌"
+            } else {
+                ""
+            };
             let display_str = if is_highlighted {
-                format!(" > {}", entry.display_name())
+                format!(" > {}{}", entry.display_name(), pin_marker)
             } else {
-                format!("   {}", entry.display_name())
+                format!("   {}{}", entry.display_name(), pin_marker)
             };
 
             let color = if !entry.is_accessible {
@@ -192,55 +318,138 @@ impl Renderer {
                 Color::White
             };
 
-            execute!(stdout, SetForegroundColor(color), Print(&display_str))?;
+            execute!(out, SetForegroundColor(color))?;
+            print_clipped(out, &display_str, &mut remaining)?;
+
+            if let Some(verdict) = ctx.checksums.and_then(|run| run.verdict(&entry.path)) {
+                execute!(out, SetForegroundColor(verdict.color()))?;
+                print_clipped(out, verdict.marker(), &mut remaining)?;
+            }
 
             // Show permissions and ownership if in select mode and root
             if *ctx.mode == NavigatorMode::Select && ctx.is_root {
                 let perms = entry.permissions_string();
                 let ownership = entry.ownership_string();
                 let info = format!(" {} {}", perms, ownership);
-                execute!(stdout, SetForegroundColor(Color::DarkGrey), Print(&info))?;
+                execute!(out, SetForegroundColor(Color::DarkGrey))?;
+                print_clipped(out, &info, &mut remaining)?;
             }
 
+            // The highlight bar runs to the edge, and no further
             if is_highlighted {
-                // Calculate actual content length more accurately
-                let content_len = display_str.len()
-                    + if *ctx.mode == NavigatorMode::Select {
-                        4
-                    } else {
-                        0
-                    }
-                    + if *ctx.mode == NavigatorMode::Select && ctx.is_root {
-                        entry.permissions_string().len() + 1 + entry.ownership_string().len() + 1
-                    } else {
-                        0
-                    };
-
-                // Only fill up to terminal width to prevent wrapping
-                let padding = (terminal_width as usize)
-                    .saturating_sub(content_len)
-                    .min(terminal_width as usize);
-                execute!(stdout, Print(" ".repeat(padding)))?;
+                execute!(out, Print(" ".repeat(remaining)))?;
             }
 
-            execute!(stdout, ResetColor)?;
+            execute!(out, ResetColor)?;
+        }
+
+        let loading_row = ctx.entries.len().saturating_sub(ctx.scroll_offset);
+        if ctx.loading && loading_row < visible_area {
+            execute!(
+                out,
+                MoveTo(0, (list_start + loading_row) as u16),
+                SetForegroundColor(Color::DarkGrey),
+                Print("   ⏳ Reading directory…"),
+                ResetColor
+            )?;
+        }
+
+        if let Some(readme) = ctx.readme {
+            let rows = readme.rows((ctx.terminal_height as usize).saturating_sub(5));
+            if rows > 0 {
+                self.render_readme(out, readme, list_start + visible_area, terminal_width)?;
+            }
         }
 
         Ok(())
     }
 
+    /// Title rule and the README's opening lines, starting at row `top`
+    fn render_readme(
+        &self,
+        out: &mut impl Write,
+        readme: &ReadmeStrip,
+        top: usize,
+        terminal_width: u16,
+    ) -> Result<()> {
+        let width = terminal_width as usize;
+        let title = format!("── {} ", readme.name);
+        let rule = width.saturating_sub(title.chars().count());
+        execute!(
+            out,
+            MoveTo(0, top as u16),
+            SetForegroundColor(Color::DarkGrey),
+            Print(
+                format!("{}{}", title, "─".repeat(rule))
+                    .chars()
+                    .take(width)
+                    .collect::<String>()
+            ),
+            ResetColor
+        )?;
+
+        for (i, spans) in readme.lines.iter().enumerate() {
+            execute!(out, MoveTo(0, (top + 1 + i) as u16), Print(" "))?;
+            let mut remaining = width.saturating_sub(2);
+            for span in spans {
+                let text: String = span.text.chars().take(remaining).collect();
+                remaining -= text.chars().count();
+                execute!(out, SetForegroundColor(span.style.color()))?;
+                if let Some(attribute) = span.style.attribute() {
+                    execute!(out, SetAttribute(attribute))?;
+                }
+                execute!(out, Print(text), SetAttribute(Attribute::Reset))?;
+            }
+            execute!(out, ResetColor)?;
+        }
+        Ok(())
+    }
+
     fn render_status(
         &self,
-        stdout: &mut io::Stdout,
+        out: &mut impl Write,
         msg: &str,
         terminal_height: u16,
+        terminal_width: u16,
     ) -> Result<()> {
-        let status_row = terminal_height - 2;
+        let status_row = terminal_height.saturating_sub(2);
         execute!(
-            stdout,
+            out,
             MoveTo(0, status_row),
             SetForegroundColor(Color::Yellow),
-            Print(format!(" {} ", msg)),
+            Print(truncate_chars(
+                &format!(" {} ", msg),
+                terminal_width as usize
+            )),
+            ResetColor
+        )?;
+        Ok(())
+    }
+
+    /// Right-aligned on the status row, where it fits beside the message
+    fn render_selection_total(
+        &self,
+        out: &mut impl Write,
+        ctx: &RenderContext,
+        total: &str,
+        terminal_width: u16,
+    ) -> Result<()> {
+        let message_width = ctx
+            .status_message
+            .as_ref()
+            .map_or(0, |msg| msg.chars().count() + 2);
+        let width = total.chars().count() + 1;
+        if message_width + width > terminal_width as usize {
+            return Ok(());
+        }
+        execute!(
+            out,
+            MoveTo(
+                terminal_width - width as u16,
+                ctx.terminal_height.saturating_sub(2)
+            ),
+            SetForegroundColor(Color::Cyan),
+            Print(total),
             ResetColor
         )?;
         Ok(())
@@ -248,55 +457,395 @@ impl Renderer {
 
     fn render_footer(
         &self,
-        stdout: &mut io::Stdout,
-        mode: &NavigatorMode,
-        is_root: bool,
-        preview_focused: bool,
-        terminal_height: u16,
+        out: &mut impl Write,
+        ctx: &RenderContext,
         terminal_width: u16,
     ) -> Result<()> {
-        let footer_row = terminal_height - 1;
-
-        let controls = if preview_focused {
-            " ↑↓: Scroll | PageUp/Down: Page | Tab: Back to Files | Esc: Close Preview"
-        } else if is_root {
-            match mode {
-                NavigatorMode::Browse => {
-                    " ↑↓: Nav | Enter: Open | Ctrl+F: Search | Ctrl+B: Bookmarks | Ctrl+P: Preview | F2: Split | S: Shell | q: Quit"
-                }
-                NavigatorMode::Select => {
-                    " ↑↓: Navigate | Space: Toggle | Enter: Confirm | c: Chmod | o: Chown | Esc: Cancel"
-                }
-                NavigatorMode::PatternSelect => {
-                    " Type pattern | Enter: Apply | Esc: Cancel"
-                }
-                NavigatorMode::Search => {
-                    " Type to search | Enter: Execute | Ctrl+R: Regex | Ctrl+C: Case | Ctrl+N/P: Next/Prev | Esc: Cancel"
-                }
-                _ => "",
-            }
+        let footer_row = ctx.terminal_height.saturating_sub(1);
+        let width = terminal_width as usize;
+
+        let pointer = if *ctx.mode == NavigatorMode::Browse && !ctx.preview_focused {
+            "F1: Help | :: Palette "
         } else {
-            match mode {
-                NavigatorMode::Browse => {
-                    " ↑↓: Nav | Enter: Open | Ctrl+F: Search | Ctrl+B: Bookmarks | Ctrl+P: Preview | F2: Split | S: Shell | q: Quit"
-                }
-                NavigatorMode::Search => {
-                    " Type to search | Enter: Execute | Ctrl+R: Regex | Ctrl+C: Case | Ctrl+N/P: Next/Prev | Esc: Cancel"
-                }
-                _ => " ↑↓: Navigate | Enter: Open | Esc: Back",
-            }
+            "F1: Help "
         };
+        // The pointer to the full keymap wins over every hint
+        let hints = footer_hints(ctx);
+        let room = width.saturating_sub(pointer.chars().count() + 1);
+        let line = fit_hints(&hints, room, ctx.hint_turn);
+        let gap = width.saturating_sub(line.chars().count() + pointer.chars().count());
 
         execute!(
-            stdout,
+            out,
             MoveTo(0, footer_row),
             SetBackgroundColor(Color::DarkGrey),
             SetForegroundColor(Color::White),
-            Print(controls),
-            Print(" ".repeat(terminal_width as usize - controls.len())),
+            Print(&line),
+            Print(" ".repeat(gap)),
+            SetForegroundColor(Color::Cyan),
+            Print(ellipsize(pointer, width - line.chars().count() - gap)),
             ResetColor
         )?;
 
         Ok(())
     }
 }
+
+/// How long each of the hints that do not fit is shown in the last slot
+pub const HINT_PERIOD: std::time::Duration = std::time::Duration::from_secs(4);
+
+/// Key hints of the active mode, most useful first
+fn footer_hints(ctx: &RenderContext) -> Vec<(&'static str, &'static str)> {
+    if ctx.preview_focused {
+        return vec![
+            ("↑↓", "Scroll"),
+            ("Tab", "Back to Files"),
+            ("PageUp/Down", "Page"),
+            ("/", "Search"),
+            ("w", "Wrap"),
+            ("l", "Line numbers"),
+            ("Esc", "Close Preview"),
+        ];
+    }
+    match ctx.mode {
+        NavigatorMode::Browse if ctx.filter.is_some() => vec![
+            ("Type", "Filter"),
+            ("Enter", "Open"),
+            ("Esc", "Clear"),
+            ("Backspace", "Delete"),
+        ],
+        NavigatorMode::Browse => {
+            let mut hints = vec![
+                ("↑↓", "Nav"),
+                ("Enter", "Open"),
+                ("Ctrl+F", "Search"),
+                ("Ctrl+B", "Bookmarks"),
+                ("q", "Quit"),
+                ("Ctrl+P", "Preview"),
+                ("F2", "Split"),
+                ("/", "Filter"),
+                ("Ctrl+T", "Fuzzy find"),
+                ("Space", "Quick view"),
+                (".", "Hidden"),
+                ("Ctrl+S", "Sort"),
+                ("P", "Pin"),
+                ("S", "Shell"),
+            ];
+            if ctx.is_root {
+                hints.extend([("s", "Select"), ("p", "Pattern")]);
+            }
+            hints
+        }
+        NavigatorMode::Select => vec![
+            ("↑↓", "Navigate"),
+            ("Space", "Toggle"),
+            ("Esc", "Cancel"),
+            ("Enter", "Confirm"),
+            ("c", "Chmod"),
+            ("o", "Chown"),
+            ("d", "Diff"),
+            ("Ctrl+P", "Summary"),
+        ],
+        NavigatorMode::PatternSelect => vec![
+            ("Type", "Pattern"),
+            ("Enter", "Apply"),
+            ("Esc", "Cancel"),
+            ("Tab", "Glob/Regex/Literal"),
+        ],
+        NavigatorMode::Search => vec![
+            ("Type", "Search"),
+            ("Enter", "Execute/Go to"),
+            ("Esc", "Cancel"),
+            ("Ctrl+N/P", "Next/Prev"),
+            ("Ctrl+R", "Regex"),
+            ("Ctrl+C", "Case"),
+            ("Ctrl+S", "Scope"),
+            ("Ctrl+E", "Exclude"),
+            ("Ctrl+A", "Archives"),
+            ("Ctrl+L", "Locate"),
+        ],
+        _ => vec![("↑↓", "Navigate"), ("Enter", "Open"), ("Esc", "Back")],
+    }
+}
+
+/// As many of `hints` as fit in `width` columns, in order; when some are
+/// left over, the last slot cycles through them, one per `turn`
+fn fit_hints(hints: &[(&str, &str)], width: usize, turn: usize) -> String {
+    let labels: Vec<String> = hints
+        .iter()
+        .map(|(key, action)| format!("{}: {}", key, action))
+        .collect();
+    let join = |labels: &[&String]| -> String {
+        let mut line = String::new();
+        for label in labels {
+            line.push_str(if line.is_empty() { " " } else { " | " });
+            line.push_str(label);
+        }
+        line
+    };
+
+    let all: Vec<&String> = labels.iter().collect();
+    if join(&all).chars().count() <= width {
+        return join(&all);
+    }
+    // One slot less than fits, the last of them taken by the rotation
+    let mut shown = 0;
+    while shown < labels.len() && join(&all[..=shown]).chars().count() <= width {
+        shown += 1;
+    }
+    let fixed = &all[..shown.saturating_sub(1)];
+    let rest = &all[fixed.len()..];
+    let rotating = (0..rest.len())
+        .map(|offset| rest[(turn + offset) % rest.len()])
+        .find(|label| {
+            let mut line: Vec<&String> = fixed.to_vec();
+            line.push(label);
+            join(&line).chars().count() <= width
+        });
+    let mut line: Vec<&String> = fixed.to_vec();
+    line.extend(rotating);
+    join(&line)
+}
+
+/// Print as much of `text` as fits in `remaining` columns, and count it off
+fn print_clipped(out: &mut impl Write, text: &str, remaining: &mut usize) -> Result<()> {
+    let text = truncate_chars(text, *remaining);
+    *remaining -= text.chars().count();
+    execute!(out, Print(text))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::{set_color_level, ColorLevel, Screen};
+    use std::path::PathBuf;
+
+    fn entry(name: &str, is_dir: bool) -> FileEntry {
+        FileEntry {
+            name: name.to_string(),
+            path: PathBuf::from("/srv/app").join(name),
+            is_dir,
+            is_accessible: true,
+            is_symlink: false,
+            permissions: Some(if is_dir { 0o755 } else { 0o644 }),
+            owner: Some("root".to_string()),
+            group: Some("www-data".to_string()),
+            uid: Some(0),
+            gid: Some(33),
+            size: 0,
+            modified: None,
+        }
+    }
+
+    fn entries() -> Vec<FileEntry> {
+        let mut entries = vec![FileEntry::parent_link(Path::new("/srv"))];
+        entries.push(entry("config", true));
+        entries.push(entry("src", true));
+        entries.push(entry("Cargo.toml", false));
+        entries.push(entry(
+            "a-very-long-file-name-that-does-not-fit-narrow-terminals.tar.gz",
+            false,
+        ));
+        entries.push(entry("résumé-été.md", false));
+        entries
+    }
+
+    /// Listing shared by the tests, with entries 2 and 4 selected and
+    /// Cargo.toml pinned
+    struct Fixture {
+        entries: Vec<FileEntry>,
+        selected_items: HashSet<usize>,
+        pinned: Vec<String>,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            Self {
+                entries: entries(),
+                selected_items: HashSet::from([2, 4]),
+                pinned: vec!["Cargo.toml".to_string()],
+            }
+        }
+
+        fn ctx<'a>(
+            &'a self,
+            mode: &'a NavigatorMode,
+            is_root: bool,
+            height: u16,
+        ) -> RenderContext<'a> {
+            RenderContext {
+                current_dir: Path::new("/srv/app"),
+                entries: &self.entries,
+                selected_index: 3,
+                selected_items: &self.selected_items,
+                scroll_offset: 0,
+                terminal_height: height,
+                mode,
+                is_root,
+                pattern_input: "",
+                pattern_mode: PatternMode::Glob,
+                filter: None,
+                pinned: &self.pinned,
+                status_message: &None,
+                search_mode: None,
+                preview_focused: false,
+                readme: None,
+                real_dir: None,
+                hot_new: 0,
+                checksums: None,
+                preview_search: None,
+                loading: false,
+                selection_total: None,
+                recording: None,
+                hint_turn: 0,
+            }
+        }
+    }
+
+    /// `ctx` drawn on a screen `width` columns wide
+    fn draw(ctx: &RenderContext, width: u16) -> String {
+        let mut screen = Screen::new(width, ctx.terminal_height);
+        Renderer::new().draw(&mut screen, ctx, width).unwrap();
+        screen.text()
+    }
+
+    #[test]
+    fn test_fit_hints() {
+        let hints = [("a", "One"), ("b", "Two"), ("c", "Three"), ("d", "Four")];
+        assert_eq!(
+            fit_hints(&hints, 80, 0),
+            " a: One | b: Two | c: Three | d: Four"
+        );
+
+        // The last slot takes turns, skipping hints too long for it
+        assert_eq!(fit_hints(&hints, 30, 0), " a: One | b: Two | c: Three");
+        assert_eq!(fit_hints(&hints, 30, 1), " a: One | b: Two | d: Four");
+        assert_eq!(fit_hints(&hints, 30, 2), " a: One | b: Two | c: Three");
+        assert_eq!(fit_hints(&hints, 17, 1), " a: One | d: Four");
+        assert_eq!(fit_hints(&hints, 0, 0), "");
+    }
+
+    #[test]
+    fn test_browse_layouts() {
+        let fixture = Fixture::new();
+        let browse = NavigatorMode::Browse;
+        let ctx = fixture.ctx(&browse, false, 11);
+        insta::assert_snapshot!("browse_wide", draw(&ctx, 100));
+        insta::assert_snapshot!("browse_narrow", draw(&ctx, 32));
+
+        let mut ctx = fixture.ctx(&browse, false, 8);
+        ctx.entries = &fixture.entries[3..4];
+        ctx.selected_index = 0;
+        ctx.filter = Some("toml");
+        insta::assert_snapshot!("browse_filter", draw(&ctx, 60));
+
+        let status = Some("⚠️  Selection modes require root privileges".to_string());
+        let mut ctx = fixture.ctx(&browse, false, 11);
+        ctx.entries = &fixture.entries[..2];
+        ctx.loading = true;
+        ctx.status_message = &status;
+        insta::assert_snapshot!("browse_loading_status", draw(&ctx, 40));
+
+        // Reached through a symbolic link: both paths
+        let mut ctx = fixture.ctx(&browse, true, 8);
+        ctx.real_dir = Some(Path::new("/data/app"));
+        assert!(draw(&ctx, 60)
+            .lines()
+            .next()
+            .unwrap()
+            .starts_with(" 📂 /srv/app → /data/app [ROOT MODE]"));
+    }
+
+    #[test]
+    fn test_styles_per_color_level() {
+        let fixture = Fixture::new();
+        let browse = NavigatorMode::Browse;
+        let mut ctx = fixture.ctx(&browse, false, 11);
+        ctx.entries = &fixture.entries[..5];
+        // The level is global: the levels are drawn one after the other
+        // here, and full color, the default, is put back after each
+        for (name, level) in [
+            ("styled_full", ColorLevel::Full),
+            ("styled_ansi16", ColorLevel::Ansi16),
+            ("styled_ansi8", ColorLevel::Ansi8),
+            ("styled_monochrome", ColorLevel::Monochrome),
+        ] {
+            set_color_level(level);
+            let mut screen = Screen::new(40, ctx.terminal_height);
+            let drawn = Renderer::new().draw(&mut screen, &ctx, 40);
+            set_color_level(ColorLevel::Full);
+            drawn.unwrap();
+            insta::assert_snapshot!(name, screen.styled());
+        }
+    }
+
+    #[test]
+    fn test_list_entry_at() {
+        // Height 11: the list takes rows 3 to 8
+        assert_eq!(list_entry_at(3, false, 10, 11, None), Some(10));
+        assert_eq!(list_entry_at(8, false, 10, 11, None), Some(15));
+        assert_eq!(list_entry_at(9, false, 10, 11, None), None);
+        assert_eq!(list_entry_at(1, false, 10, 11, None), None);
+        // A drag past the edges stays on the first and last rows
+        assert_eq!(list_entry_at(1, true, 10, 11, None), Some(10));
+        assert_eq!(list_entry_at(10, true, 10, 11, None), Some(15));
+    }
+
+    #[test]
+    fn test_selection_layouts() {
+        let fixture = Fixture::new();
+        let select = NavigatorMode::Select;
+        let ctx = fixture.ctx(&select, true, 11);
+        insta::assert_snapshot!("select_root", draw(&ctx, 80));
+        insta::assert_snapshot!("select_narrow", draw(&ctx, 40));
+
+        // The total gives way to a status message it would run into
+        let mut ctx = fixture.ctx(&select, true, 8);
+        ctx.selection_total = Some("2 selected · 1.5 KB (sizing 1 dir…)");
+        insta::assert_snapshot!("select_total", draw(&ctx, 80));
+        let status = Some("Permissions changed on 2 items".to_string());
+        ctx.status_message = &status;
+        assert!(!draw(&ctx, 60).contains("selected"));
+
+        let pattern_select = NavigatorMode::PatternSelect;
+        let mut ctx = fixture.ctx(&pattern_select, true, 6);
+        ctx.entries = &[];
+        ctx.pattern_input = "*.t[a-z]*";
+        insta::assert_snapshot!("pattern_select", draw(&ctx, 70));
+        ctx.recording = Some(1);
+        assert!(draw(&ctx, 70).contains("● REC 1 step (w: stop)"));
+    }
+
+    #[test]
+    fn test_search_layout() {
+        let fixture = Fixture::new();
+        let mut search = SearchMode::new();
+        search.query = "todo size:>1M".to_string();
+        search.search_in_contents = true;
+        let mode = NavigatorMode::Search;
+        let mut ctx = fixture.ctx(&mode, false, 6);
+        ctx.entries = &[];
+        ctx.search_mode = Some(&search);
+        insta::assert_snapshot!("search", draw(&ctx, 90));
+    }
+
+    #[test]
+    fn test_rows_never_overflow() {
+        let fixture = Fixture::new();
+        // Overflowing text would wrap and scroll the header off the top
+        for mode in &[
+            NavigatorMode::Browse,
+            NavigatorMode::Select,
+            NavigatorMode::PatternSelect,
+        ] {
+            let ctx = fixture.ctx(mode, true, 11);
+            for width in 8..=120 {
+                let text = draw(&ctx, width);
+                let rows: Vec<&str> = text.lines().collect();
+                assert!(rows[0].starts_with(" 📂 /srv"), "{:?} at {}", mode, width);
+                assert!(!rows[10].trim().is_empty(), "{:?} at {}", mode, width);
+            }
+        }
+    }
+}
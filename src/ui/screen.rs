@@ -0,0 +1,237 @@
+use std::io::{self, Write};
+
+/// In-memory terminal for tests: takes the escape sequences the renderers
+/// write and keeps the text they leave on a `width` × `height` grid
+///
+/// Cursor moves, clears and SGR styles are followed. Like a real terminal,
+/// text running past the right edge wraps onto the next row and a write
+/// past the bottom scrolls the grid, so overflows show up in the captured
+/// text.
+pub struct Screen {
+    width: usize,
+    height: usize,
+    output: Vec<u8>,
+}
+
+/// Colors and attributes a cell was written with; colors are ANSI indexes
+/// (`14` for bright cyan whether written as `96` or `38;5;14`) or `#rrggbb`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Style {
+    foreground: Option<String>,
+    background: Option<String>,
+    bold: bool,
+    dim: bool,
+    italic: bool,
+    underline: bool,
+    reverse: bool,
+}
+
+impl Style {
+    /// Follow the parameters of an SGR (`\x1b[…m`) sequence
+    fn apply(&mut self, params: &str) {
+        let mut codes = params.split(';').map(|n| n.parse::<u16>().unwrap_or(0));
+        while let Some(code) = codes.next() {
+            match code {
+                0 => *self = Style::default(),
+                1 => self.bold = true,
+                2 => self.dim = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                7 => self.reverse = true,
+                22 => {
+                    self.bold = false;
+                    self.dim = false;
+                }
+                23 => self.italic = false,
+                24 => self.underline = false,
+                27 => self.reverse = false,
+                30..=37 => self.foreground = Some((code - 30).to_string()),
+                90..=97 => self.foreground = Some((code - 82).to_string()),
+                39 => self.foreground = None,
+                40..=47 => self.background = Some((code - 40).to_string()),
+                100..=107 => self.background = Some((code - 92).to_string()),
+                49 => self.background = None,
+                38 | 48 => {
+                    let color = match codes.next() {
+                        Some(5) => codes.next().map(|n| n.to_string()),
+                        Some(2) => {
+                            let mut channel = || codes.next().unwrap_or(0);
+                            let (r, g, b) = (channel(), channel(), channel());
+                            Some(format!("#{:02x}{:02x}{:02x}", r, g, b))
+                        }
+                        _ => None,
+                    };
+                    if code == 38 {
+                        self.foreground = color;
+                    } else {
+                        self.background = color;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// `fg:14 bg:0 bold reverse`
+    fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(ref color) = self.foreground {
+            parts.push(format!("fg:{}", color));
+        }
+        if let Some(ref color) = self.background {
+            parts.push(format!("bg:{}", color));
+        }
+        for (set, name) in [
+            (self.bold, "bold"),
+            (self.dim, "dim"),
+            (self.italic, "italic"),
+            (self.underline, "underline"),
+            (self.reverse, "reverse"),
+        ] {
+            if set {
+                parts.push(name.to_string());
+            }
+        }
+        parts.join(" ")
+    }
+}
+
+type Cell = (char, Style);
+
+impl Screen {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width: width as usize,
+            height: height as usize,
+            output: Vec::new(),
+        }
+    }
+
+    /// The grid after everything written so far, one line per row with
+    /// trailing blanks trimmed
+    pub fn text(&self) -> String {
+        self.grid()
+            .iter()
+            .map(|cells| row_text(cells))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Like [`Self::text`], with each row followed by a line marking the
+    /// style of its cells: blank for default cells, otherwise a letter
+    /// looked up in the legend that ends the output
+    pub fn styled(&self) -> String {
+        let mut styles: Vec<Style> = Vec::new();
+        let mut lines = Vec::new();
+        for cells in self.grid() {
+            lines.push(row_text(&cells));
+            let marks: String = cells
+                .iter()
+                .map(|(_, style)| {
+                    if *style == Style::default() {
+                        return ' ';
+                    }
+                    let index = styles
+                        .iter()
+                        .position(|known| known == style)
+                        .unwrap_or_else(|| {
+                            styles.push(style.clone());
+                            styles.len() - 1
+                        });
+                    mark(index)
+                })
+                .collect();
+            lines.push(marks.trim_end().to_string());
+        }
+        lines.push(String::new());
+        for (index, style) in styles.iter().enumerate() {
+            lines.push(format!("{} = {}", mark(index), style.describe()));
+        }
+        lines.join("\n")
+    }
+
+    fn grid(&self) -> Vec<Vec<Cell>> {
+        let blank = || vec![vec![(' ', Style::default()); self.width]; self.height];
+        let mut grid = blank();
+        let mut style = Style::default();
+        let (mut row, mut col) = (0, 0);
+        let output = String::from_utf8_lossy(&self.output);
+        let mut chars = output.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\x1b' => {
+                    if chars.next() != Some('[') {
+                        continue;
+                    }
+                    let mut params = String::new();
+                    for c in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&c) {
+                            match c {
+                                'H' => {
+                                    let mut numbers = params
+                                        .split(';')
+                                        .map(|n| n.parse::<usize>().unwrap_or(1).max(1));
+                                    row = numbers.next().unwrap_or(1) - 1;
+                                    col = numbers.next().unwrap_or(1) - 1;
+                                }
+                                'J' if params == "2" => grid = blank(),
+                                'm' => style.apply(&params),
+                                _ => {}
+                            }
+                            break;
+                        }
+                        params.push(c);
+                    }
+                }
+                '\n' => {
+                    row += 1;
+                    col = 0;
+                }
+                '\r' => col = 0,
+                c => {
+                    if col >= self.width {
+                        row += 1;
+                        col = 0;
+                    }
+                    if row >= self.height {
+                        grid.remove(0);
+                        grid.push(vec![(' ', Style::default()); self.width]);
+                        row = self.height - 1;
+                    }
+                    grid[row][col] = (c, style.clone());
+                    col += 1;
+                }
+            }
+        }
+        grid
+    }
+}
+
+fn row_text(cells: &[Cell]) -> String {
+    cells
+        .iter()
+        .map(|(c, _)| c)
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+/// `a`…`z`, then `A`…`Z`, for the legend of [`Screen::styled`]
+fn mark(index: usize) -> char {
+    (b'a'..=b'z')
+        .chain(b'A'..=b'Z')
+        .nth(index)
+        .map_or('?', char::from)
+}
+
+impl Write for Screen {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,274 @@
+use crossterm::{style, style::Color, Command};
+use serde::Deserialize;
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Colors the terminal can show, chosen with `colors` in `config.toml` or
+/// detected from the environment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorLevel {
+    /// 256 colors or more: colors are written as crossterm writes them
+    Full,
+    /// The 16 ANSI colors, as on most terminals without a 256-color `TERM`
+    Ansi16,
+    /// The 8 ANSI colors: bright colors become bold, and a dark grey
+    /// highlight becomes reverse video; the Linux console
+    Ansi8,
+    /// No color at all (`NO_COLOR`, serial terminals): backgrounds become
+    /// reverse video, and bright ones bold as well
+    Monochrome,
+}
+
+/// Level used by [`SetForegroundColor`] and [`SetBackgroundColor`]; full
+/// color until [`set_color_level`] says otherwise
+static LEVEL: AtomicU8 = AtomicU8::new(0);
+
+const LEVELS: [ColorLevel; 4] = [
+    ColorLevel::Full,
+    ColorLevel::Ansi16,
+    ColorLevel::Ansi8,
+    ColorLevel::Monochrome,
+];
+
+impl ColorLevel {
+    pub fn detect() -> Self {
+        Self::detect_from(|name| std::env::var(name).ok())
+    }
+
+    fn detect_from(var: impl Fn(&str) -> Option<String>) -> Self {
+        // https://no-color.org: set and not empty
+        if var("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+            return ColorLevel::Monochrome;
+        }
+        let colorterm = var("COLORTERM").unwrap_or_default();
+        if colorterm.contains("truecolor") || colorterm.contains("24bit") {
+            return ColorLevel::Full;
+        }
+
+        let term = var("TERM").unwrap_or_default();
+        if ["256color", "direct", "truecolor"]
+            .iter()
+            .any(|suffix| term.contains(suffix))
+            || [
+                "kitty",
+                "alacritty",
+                "foot",
+                "wezterm",
+                "ghostty",
+                "contour",
+            ]
+            .iter()
+            .any(|name| term.contains(name))
+        {
+            ColorLevel::Full
+        } else if term.is_empty() || term == "dumb" || term.starts_with("vt") {
+            // vt100, vt220 and the like: serial consoles without color
+            ColorLevel::Monochrome
+        } else if matches!(term.as_str(), "linux" | "ansi" | "cons25" | "pcansi") {
+            ColorLevel::Ansi8
+        } else {
+            ColorLevel::Ansi16
+        }
+    }
+
+    fn index(self) -> u8 {
+        LEVELS.iter().position(|&level| level == self).unwrap_or(0) as u8
+    }
+}
+
+pub fn set_color_level(level: ColorLevel) {
+    LEVEL.store(level.index(), Ordering::Relaxed);
+}
+
+pub fn color_level() -> ColorLevel {
+    LEVELS[LEVEL.load(Ordering::Relaxed) as usize]
+}
+
+/// Drop-in replacement for crossterm's `SetForegroundColor` that writes
+/// only what the terminal's [`ColorLevel`] can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetForegroundColor(pub Color);
+
+/// Drop-in replacement for crossterm's `SetBackgroundColor`; on limited
+/// terminals highlights fall back to reverse video so they stay visible
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetBackgroundColor(pub Color);
+
+impl Command for SetForegroundColor {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        match color_level() {
+            ColorLevel::Full => style::SetForegroundColor(self.0).write_ansi(f),
+            level => f.write_str(&sgr(level, self.0, false)),
+        }
+    }
+}
+
+impl Command for SetBackgroundColor {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        match color_level() {
+            ColorLevel::Full => style::SetBackgroundColor(self.0).write_ansi(f),
+            level => f.write_str(&sgr(level, self.0, true)),
+        }
+    }
+}
+
+/// One of the eight ANSI colors, and whether it is the bright variant
+fn ansi(color: Color) -> Option<(u8, bool)> {
+    let ansi = match color {
+        Color::Black => (0, false),
+        Color::DarkRed => (1, false),
+        Color::DarkGreen => (2, false),
+        Color::DarkYellow => (3, false),
+        Color::DarkBlue => (4, false),
+        Color::DarkMagenta => (5, false),
+        Color::DarkCyan => (6, false),
+        Color::Grey => (7, false),
+        Color::DarkGrey => (0, true),
+        Color::Red => (1, true),
+        Color::Green => (2, true),
+        Color::Yellow => (3, true),
+        Color::Blue => (4, true),
+        Color::Magenta => (5, true),
+        Color::Cyan => (6, true),
+        Color::White => (7, true),
+        Color::AnsiValue(value) if value < 16 => (value % 8, value >= 8),
+        Color::AnsiValue(value) if value >= 232 => {
+            // Grey ramp
+            let level = (value - 232) * 10 + 8;
+            return ansi(Color::Rgb {
+                r: level,
+                g: level,
+                b: level,
+            });
+        }
+        Color::AnsiValue(value) => {
+            // 6×6×6 cube
+            let step = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+            let cube = value - 16;
+            return ansi(Color::Rgb {
+                r: step(cube / 36),
+                g: step(cube / 6 % 6),
+                b: step(cube % 6),
+            });
+        }
+        Color::Rgb { r, g, b } => {
+            let index = u8::from(r >= 128) | u8::from(g >= 128) << 1 | u8::from(b >= 128) << 2;
+            let brightest = r.max(g).max(b);
+            match index {
+                0 => (0, brightest >= 64),
+                _ => (index, brightest >= 192),
+            }
+        }
+        Color::Reset => return None,
+    };
+    Some(ansi)
+}
+
+/// SGR sequence showing `color` on a terminal limited to `level`
+fn sgr(level: ColorLevel, color: Color, background: bool) -> String {
+    let codes = match (ansi(color), level, background) {
+        // Default colors; a background also ends reverse video
+        (None, ColorLevel::Monochrome, false) => return String::new(),
+        (None, ColorLevel::Monochrome, true) => "27".to_string(),
+        (None, _, false) => "39".to_string(),
+        (None, _, true) => "49;27".to_string(),
+        (Some(_), ColorLevel::Monochrome, false) => return String::new(),
+        // Black is the plain background of unselected items
+        (Some((0, false)), ColorLevel::Monochrome, true) => "27".to_string(),
+        (Some((_, bright)), ColorLevel::Monochrome, true) => {
+            if bright {
+                "7;1".to_string()
+            } else {
+                "7".to_string()
+            }
+        }
+        (Some((index, true)), ColorLevel::Ansi16, false) => format!("{}", 90 + index),
+        (Some((index, true)), ColorLevel::Ansi16, true) => format!("{}", 100 + index),
+        // Eight colors: bright is bold, so a dark color turns bold off
+        (Some((index, false)), ColorLevel::Ansi8, false) => format!("22;{}", 30 + index),
+        (Some((index, false)), _, false) => format!("{}", 30 + index),
+        (Some((index, false)), _, true) => format!("{}", 40 + index),
+        // A dark grey background would vanish into black
+        (Some((index, true)), _, false) => format!("1;{}", 30 + index),
+        (Some((0, true)), _, true) => "7".to_string(),
+        (Some((index, true)), _, true) => format!("1;{}", 40 + index),
+    };
+    format!("\x1b[{}m", codes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    #[test]
+    fn test_detect_color_level() {
+        use ColorLevel::*;
+        let detect = |vars| ColorLevel::detect_from(env(vars));
+        assert_eq!(detect(&[("TERM", "xterm-256color")]), Full);
+        assert_eq!(
+            detect(&[("TERM", "xterm"), ("COLORTERM", "truecolor")]),
+            Full
+        );
+        assert_eq!(detect(&[("TERM", "xterm")]), Ansi16);
+        assert_eq!(detect(&[("TERM", "screen")]), Ansi16);
+        assert_eq!(detect(&[("TERM", "linux")]), Ansi8);
+        assert_eq!(detect(&[("TERM", "vt220")]), Monochrome);
+        assert_eq!(detect(&[]), Monochrome);
+        assert_eq!(
+            detect(&[("TERM", "xterm-256color"), ("NO_COLOR", "1")]),
+            Monochrome
+        );
+        // An empty NO_COLOR does not count
+        assert_eq!(
+            detect(&[("TERM", "xterm-256color"), ("NO_COLOR", "")]),
+            Full
+        );
+    }
+
+    #[test]
+    fn test_limited_palettes() {
+        use ColorLevel::*;
+        // Selection bars stay visible everywhere
+        assert_eq!(sgr(Ansi16, Color::DarkGrey, true), "\x1b[100m");
+        assert_eq!(sgr(Ansi8, Color::DarkGrey, true), "\x1b[7m");
+        assert_eq!(sgr(Monochrome, Color::DarkGrey, true), "\x1b[7;1m");
+        // Focused and unfocused headers differ in every level
+        assert_ne!(
+            sgr(Ansi8, Color::Blue, true),
+            sgr(Ansi8, Color::DarkBlue, true)
+        );
+        assert_ne!(
+            sgr(Monochrome, Color::Blue, true),
+            sgr(Monochrome, Color::DarkBlue, true)
+        );
+
+        assert_eq!(sgr(Ansi16, Color::Cyan, false), "\x1b[96m");
+        assert_eq!(sgr(Ansi8, Color::Cyan, false), "\x1b[1;36m");
+        assert_eq!(sgr(Ansi8, Color::DarkCyan, false), "\x1b[22;36m");
+        assert_eq!(sgr(Monochrome, Color::Cyan, false), "");
+        assert_eq!(sgr(Monochrome, Color::Reset, true), "\x1b[27m");
+        assert_eq!(sgr(Monochrome, Color::Black, true), "\x1b[27m");
+        assert_eq!(
+            sgr(
+                Ansi16,
+                Color::Rgb {
+                    r: 250,
+                    g: 40,
+                    b: 30
+                },
+                false
+            ),
+            "\x1b[91m"
+        );
+        assert_eq!(sgr(Ansi16, Color::AnsiValue(10), false), "\x1b[92m");
+    }
+}
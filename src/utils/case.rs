@@ -0,0 +1,99 @@
+use std::fs;
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+/// Whether names in `dir` are matched case-insensitively (exFAT, NTFS, FAT
+/// and case-folding ext4 directories)
+///
+/// Looks up an existing entry under its swapped-case name first, and only
+/// creates a short-lived probe file when the directory has none to test.
+pub fn is_case_insensitive(dir: &Path) -> io::Result<bool> {
+    for entry in fs::read_dir(dir)?.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let swapped = swap_case(&name);
+        if swapped != name {
+            return same_entry(&entry.path(), &dir.join(swapped));
+        }
+    }
+
+    let probe_name = format!(".fsnav-case-probe-{}", std::process::id());
+    let probe = dir.join(&probe_name);
+    fs::File::create(&probe)?;
+    let result = same_entry(&probe, &dir.join(swap_case(&probe_name)));
+    let _ = fs::remove_file(&probe);
+    result
+}
+
+/// An existing entry of `dir` that `name` would silently replace because
+/// they differ only in case on a case-insensitive filesystem
+///
+/// Exact matches are not reported: they are ordinary conflicts.
+pub fn case_only_conflict(dir: &Path, name: &str) -> io::Result<Option<String>> {
+    let lowered = name.to_lowercase();
+    let existing = fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .find(|existing| existing != name && existing.to_lowercase() == lowered);
+
+    match existing {
+        Some(existing) if is_case_insensitive(dir)? => Ok(Some(existing)),
+        _ => Ok(None),
+    }
+}
+
+fn swap_case(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_lowercase() {
+                c.to_uppercase().next().unwrap_or(c)
+            } else {
+                c.to_lowercase().next().unwrap_or(c)
+            }
+        })
+        .collect()
+}
+
+fn same_entry(a: &Path, b: &Path) -> io::Result<bool> {
+    let a = a.symlink_metadata()?;
+    match b.symlink_metadata() {
+        Ok(b) => Ok(a.dev() == b.dev() && a.ino() == b.ino()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_swap_case() {
+        assert_eq!(swap_case("File.TXT"), "fILE.txt");
+        assert_eq!(swap_case("123"), "123");
+    }
+
+    #[test]
+    fn test_detection_matches_filesystem() {
+        let temp_dir = TempDir::new().unwrap();
+        let insensitive = is_case_insensitive(temp_dir.path()).unwrap();
+        // The probe file is cleaned up
+        assert!(fs::read_dir(temp_dir.path()).unwrap().next().is_none());
+
+        fs::write(temp_dir.path().join("File.txt"), "").unwrap();
+        assert_eq!(is_case_insensitive(temp_dir.path()).unwrap(), insensitive);
+        assert_eq!(
+            insensitive,
+            temp_dir.path().join("FILE.TXT").exists(),
+            "detection disagrees with the filesystem"
+        );
+
+        let conflict = case_only_conflict(temp_dir.path(), "file.txt").unwrap();
+        assert_eq!(conflict, insensitive.then(|| "File.txt".to_string()));
+        assert_eq!(
+            case_only_conflict(temp_dir.path(), "File.txt").unwrap(),
+            None
+        );
+    }
+}
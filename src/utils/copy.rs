@@ -0,0 +1,154 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::case_only_conflict;
+
+/// `dir/name`, or when that is taken the first free of `name (2)`,
+/// `name (3)`…, with the extension kept last when `keep_extension` is set
+/// (files; a directory like `v1.2` becomes `v1.2 (2)`). On a
+/// case-insensitive filesystem a name differing only in case is taken too.
+pub fn free_path(dir: &Path, name: &Path, keep_extension: bool) -> PathBuf {
+    let is_free = |path: &Path| {
+        fs::symlink_metadata(path).is_err()
+            && !path.file_name().is_some_and(|name| {
+                case_only_conflict(dir, &name.to_string_lossy()).is_ok_and(|clash| clash.is_some())
+            })
+    };
+    let candidate = dir.join(name);
+    if is_free(&candidate) {
+        return candidate;
+    }
+    let (stem, extension) = match (name.file_stem(), name.extension()) {
+        (Some(stem), Some(extension)) if keep_extension => (stem, Some(extension)),
+        _ => (name.as_os_str(), None),
+    };
+    (2..)
+        .map(|n| {
+            let mut numbered = OsString::from(stem);
+            numbered.push(format!(" ({})", n));
+            if let Some(extension) = extension {
+                numbered.push(".");
+                numbered.push(extension);
+            }
+            dir.join(numbered)
+        })
+        .find(|path| is_free(path))
+        .unwrap_or(candidate)
+}
+
+/// Copy a file, symbolic link or whole directory tree to `to`, which must
+/// not exist yet
+fn copy_recursive(from: &Path, to: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(from)?;
+    if metadata.file_type().is_symlink() {
+        #[cfg(unix)]
+        return std::os::unix::fs::symlink(fs::read_link(from)?, to);
+        #[cfg(not(unix))]
+        return fs::copy(from, to).map(|_| ());
+    }
+    if metadata.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        return fs::set_permissions(to, metadata.permissions());
+    }
+    fs::copy(from, to).map(|_| ())
+}
+
+/// Like [`copy_recursive`], but removes whatever was copied when it fails
+/// partway, so a failed copy leaves no half-filled `to` behind
+pub fn copy_path(from: &Path, to: &Path) -> io::Result<()> {
+    copy_recursive(from, to).inspect_err(|_| {
+        let _ = remove_path(to);
+    })
+}
+
+/// Rename `from` to `to`; across filesystems, copy it and remove the
+/// original, removing the partial copy instead when copying fails
+pub fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {}
+        result => return result,
+    }
+    copy_path(from, to)?;
+    remove_path(from)
+}
+
+fn remove_path(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::{symlink, MetadataExt, PermissionsExt};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_free_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        assert_eq!(free_path(dir, Path::new("a.txt"), true), dir.join("a.txt"));
+
+        fs::write(dir.join("a.txt"), "").unwrap();
+        fs::write(dir.join("a (2).txt"), "").unwrap();
+        assert_eq!(
+            free_path(dir, Path::new("a.txt"), true),
+            dir.join("a (3).txt")
+        );
+
+        fs::create_dir(dir.join("v1.2")).unwrap();
+        assert_eq!(
+            free_path(dir, Path::new("v1.2"), false),
+            dir.join("v1.2 (2)")
+        );
+
+        // Only where the filesystem folds case is `A.txt` taken by `a.txt`
+        let insensitive = super::super::case::is_case_insensitive(dir).unwrap();
+        assert_eq!(
+            free_path(dir, Path::new("A.txt"), true) != dir.join("A.txt"),
+            insensitive
+        );
+    }
+
+    #[test]
+    fn test_copy_recursive_keeps_links_and_modes() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        fs::create_dir_all(source.join("bin")).unwrap();
+        fs::write(source.join("bin/tool"), "#!/bin/sh\n").unwrap();
+        fs::set_permissions(source.join("bin/tool"), fs::Permissions::from_mode(0o755)).unwrap();
+        symlink("bin/tool", source.join("tool")).unwrap();
+
+        let target = temp_dir.path().join("target");
+        copy_recursive(&source, &target).unwrap();
+        let mode = fs::metadata(target.join("bin/tool")).unwrap().mode();
+        assert_eq!(mode & 0o777, 0o755);
+        assert_eq!(
+            fs::read_link(target.join("tool")).unwrap(),
+            PathBuf::from("bin/tool")
+        );
+    }
+
+    #[test]
+    fn test_failed_copy_leaves_no_partial_target() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        fs::create_dir(&source).unwrap();
+        fs::write(source.join("a"), "a").unwrap();
+        // A socket cannot be copied, so the copy fails partway
+        let _socket = std::os::unix::net::UnixListener::bind(source.join("z.sock")).unwrap();
+
+        let target = temp_dir.path().join("target");
+        assert!(copy_path(&source, &target).is_err());
+        assert!(fs::symlink_metadata(&target).is_err());
+    }
+}
@@ -0,0 +1,187 @@
+use std::fmt;
+
+/// Longest name, in bytes, accepted by common filesystems (ext4, NTFS, exFAT)
+const MAX_NAME_BYTES: usize = 255;
+
+/// Characters rejected by Windows filesystems (FAT, exFAT, NTFS)
+const RESERVED_CHARS: &[char] = &['\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// Device names Windows refuses as file names, with or without extension
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Something about a file name that breaks here or on other systems
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilenameIssue {
+    /// Empty, `.`, `..`, or containing `/` or NUL: not a valid name at all
+    Invalid,
+    ReservedCharacter(char),
+    ControlCharacter,
+    TrailingSpaceOrDot,
+    ReservedName,
+    TooLong(usize),
+}
+
+impl FilenameIssue {
+    /// Whether the name cannot be created even on this system
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, FilenameIssue::Invalid | FilenameIssue::TooLong(_))
+    }
+}
+
+impl fmt::Display for FilenameIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilenameIssue::Invalid => write!(f, "not a valid file name"),
+            FilenameIssue::ReservedCharacter(c) => {
+                write!(f, "'{}' is not allowed on FAT/NTFS drives", c)
+            }
+            FilenameIssue::ControlCharacter => write!(f, "contains control characters"),
+            FilenameIssue::TrailingSpaceOrDot => {
+                write!(f, "trailing spaces or dots are dropped on Windows")
+            }
+            FilenameIssue::ReservedName => write!(f, "reserved device name on Windows"),
+            FilenameIssue::TooLong(bytes) => {
+                write!(f, "{} bytes long (limit {})", bytes, MAX_NAME_BYTES)
+            }
+        }
+    }
+}
+
+/// Problems with `name` as a single path component, most severe first
+pub fn validate_filename(name: &str) -> Vec<FilenameIssue> {
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\0']) {
+        return vec![FilenameIssue::Invalid];
+    }
+
+    let mut issues = Vec::new();
+    if name.len() > MAX_NAME_BYTES {
+        issues.push(FilenameIssue::TooLong(name.len()));
+    }
+    if name.chars().any(char::is_control) {
+        issues.push(FilenameIssue::ControlCharacter);
+    }
+    let mut reserved: Vec<char> = name
+        .chars()
+        .filter(|c| RESERVED_CHARS.contains(c))
+        .collect();
+    reserved.dedup();
+    issues.extend(reserved.into_iter().map(FilenameIssue::ReservedCharacter));
+    if name.ends_with([' ', '.']) {
+        issues.push(FilenameIssue::TrailingSpaceOrDot);
+    }
+    if is_reserved_name(name) {
+        issues.push(FilenameIssue::ReservedName);
+    }
+    issues
+}
+
+/// A portable variant of `name` that [`validate_filename`] accepts
+///
+/// Problem characters become `_`, trailing spaces and dots are dropped,
+/// reserved device names get a `_` prefix and overlong names are cut
+/// while keeping their extension.
+pub fn sanitize_filename(name: &str) -> String {
+    let mut sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c == '/' || c.is_control() || RESERVED_CHARS.contains(&c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+
+    let trimmed_len = sanitized.trim_end_matches([' ', '.']).len();
+    sanitized.truncate(trimmed_len);
+
+    if sanitized.is_empty() {
+        return "_".to_string();
+    }
+    if is_reserved_name(&sanitized) {
+        sanitized.insert(0, '_');
+    }
+    if sanitized.len() > MAX_NAME_BYTES {
+        sanitized = shorten(&sanitized, MAX_NAME_BYTES);
+    }
+    sanitized
+}
+
+fn is_reserved_name(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(stem))
+}
+
+/// Cut `name` to at most `max` bytes on a character boundary, keeping a
+/// short extension
+fn shorten(name: &str, max: usize) -> String {
+    let extension = name
+        .rfind('.')
+        .filter(|&i| i > 0 && name.len() - i <= 16)
+        .map(|i| &name[i..])
+        .unwrap_or("");
+
+    let mut stem_end = max - extension.len();
+    while !name.is_char_boundary(stem_end) {
+        stem_end -= 1;
+    }
+    let stem = name[..stem_end].trim_end_matches([' ', '.']);
+    format!("{}{}", stem, extension)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_filename() {
+        assert!(validate_filename("notes.txt").is_empty());
+        assert_eq!(validate_filename(".."), vec![FilenameIssue::Invalid]);
+        assert_eq!(validate_filename("a/b"), vec![FilenameIssue::Invalid]);
+        assert_eq!(
+            validate_filename("what? now?. "),
+            vec![
+                FilenameIssue::ReservedCharacter('?'),
+                FilenameIssue::TrailingSpaceOrDot
+            ]
+        );
+        assert_eq!(
+            validate_filename("tab\there"),
+            vec![FilenameIssue::ControlCharacter]
+        );
+        assert_eq!(
+            validate_filename("con.txt"),
+            vec![FilenameIssue::ReservedName]
+        );
+        assert!(validate_filename("console.txt").is_empty());
+
+        let long = "x".repeat(300);
+        assert_eq!(validate_filename(&long), vec![FilenameIssue::TooLong(300)]);
+        assert!(FilenameIssue::TooLong(300).is_fatal());
+    }
+
+    #[test]
+    fn test_sanitize_filename() {
+        assert_eq!(sanitize_filename("notes.txt"), "notes.txt");
+        assert_eq!(
+            sanitize_filename("report: v1/v2 <draft>?.doc. "),
+            "report_ v1_v2 _draft__.doc"
+        );
+        assert_eq!(sanitize_filename("AUX.log"), "_AUX.log");
+        assert_eq!(sanitize_filename("..."), "_");
+
+        let long = format!("{}.tar.gz", "é".repeat(200));
+        let sanitized = sanitize_filename(&long);
+        assert!(sanitized.len() <= MAX_NAME_BYTES);
+        assert!(sanitized.ends_with(".gz"));
+
+        for name in ["a\u{7}b", "nul", "x|y.", &long] {
+            assert!(validate_filename(&sanitize_filename(name)).is_empty());
+        }
+    }
+}
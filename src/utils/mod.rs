@@ -1,5 +1,20 @@
+mod case;
+mod copy;
+mod filename;
 mod patterns;
 mod system;
+mod terminal;
+mod text;
+mod time;
 
-pub use patterns::match_pattern;
-pub use system::{get_owner_group, is_root_user};
+pub use case::case_only_conflict;
+pub use copy::{copy_path, free_path, move_path};
+pub use filename::{sanitize_filename, validate_filename, FilenameIssue};
+pub use patterns::{path_glob_to_regex, Pattern, PatternMode};
+pub use system::{
+    config_dir, editor_command, expand_home, find_program, get_owner_group, home_dir, is_root_user,
+    is_virtual_fs, lock_file, logical_current_dir, open_with_system_handler, spawn_detached,
+};
+pub use terminal::with_suspended_tui;
+pub use text::{ellipsize, truncate_chars};
+pub use time::{init_locale, TimeFormat, DEFAULT_DATE_FORMAT};
@@ -1,58 +1,245 @@
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 
-/// Match a pattern against a string
-/// Supports:
-/// - Glob patterns with * (e.g., "*.txt", "file*")
-/// - Regex patterns (automatically detected)
-/// - Simple substring matching
-pub fn match_pattern(pattern: &str, text: &str) -> bool {
-    if pattern.is_empty() {
-        return false;
-    }
-
-    // Check if it's a glob pattern
-    if pattern.contains('*') {
-        let regex_pattern = pattern
-            .replace('.', r"\.")
-            .replace('*', ".*")
-            .replace('?', ".");
-
-        if let Ok(regex) = Regex::new(&format!("^{}$", regex_pattern)) {
-            return regex.is_match(text);
+/// How a pattern typed by the user is read
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PatternMode {
+    /// `*` any run of characters, `?` any one character and `[abc]` /
+    /// `[!abc]` a character class; everything else is literal and the
+    /// whole name must match
+    #[default]
+    Glob,
+    /// A regular expression found anywhere in the name
+    Regex,
+    /// Plain text found anywhere in the name
+    Literal,
+}
+
+impl PatternMode {
+    pub fn next(self) -> Self {
+        match self {
+            PatternMode::Glob => PatternMode::Regex,
+            PatternMode::Regex => PatternMode::Literal,
+            PatternMode::Literal => PatternMode::Glob,
         }
     }
 
-    // Try as regex
-    if let Ok(regex) = Regex::new(pattern) {
-        return regex.is_match(text);
+    pub fn label(self) -> &'static str {
+        match self {
+            PatternMode::Glob => "glob",
+            PatternMode::Regex => "regex",
+            PatternMode::Literal => "literal",
+        }
     }
+}
 
-    // Fall back to substring matching
-    text.contains(pattern)
+/// A pattern compiled once for matching many names
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    regex: Regex,
+}
+
+impl Pattern {
+    /// Fails only for an invalid regular expression in [`PatternMode::Regex`]
+    pub fn new(pattern: &str, mode: PatternMode) -> Result<Self, regex::Error> {
+        let source = match mode {
+            PatternMode::Glob => glob_to_regex(pattern),
+            PatternMode::Regex => pattern.to_string(),
+            PatternMode::Literal => regex::escape(pattern),
+        };
+        Ok(Self {
+            regex: Regex::new(&source)?,
+        })
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.regex.is_match(text)
+    }
+}
+
+/// Translate a file-name glob to an anchored regex
+fn glob_to_regex(glob: &str) -> String {
+    translate_glob(glob, false)
+}
+
+/// Translate a `.gitignore`-style glob, matched against `/`-separated
+/// paths, to an anchored regex: `*` and `?` stay within one segment, `**`
+/// spans several and `\` makes the next character literal
+pub fn path_glob_to_regex(glob: &str) -> String {
+    translate_glob(glob, true)
+}
+
+fn translate_glob(glob: &str, paths: bool) -> String {
+    let chars: Vec<char> = glob.chars().collect();
+    // Any one character, or within a path any but the separator
+    let any = if paths { "[^/]" } else { "." };
+    // Names may hold newlines too
+    let mut regex = String::from("(?s)^");
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '*' if paths && chars.get(i + 1) == Some(&'*') => {
+                if chars.get(i + 2) == Some(&'/') {
+                    regex.push_str("(?:.*/)?");
+                    i += 3;
+                } else {
+                    regex.push_str(".*");
+                    i += 2;
+                }
+                continue;
+            }
+            '*' => {
+                regex.push_str(any);
+                regex.push('*');
+            }
+            '?' => regex.push_str(any),
+            '\\' if paths && i + 1 < chars.len() => {
+                regex.push_str(&regex::escape(&chars[i + 1].to_string()));
+                i += 2;
+                continue;
+            }
+            // A class needs a closing bracket and something inside
+            '[' => match chars[i + 1..].iter().position(|&c| c == ']') {
+                Some(len) if len > 0 && !(len == 1 && chars[i + 1] == '!') => {
+                    let class = &chars[i + 1..i + 1 + len];
+                    let (negated, class) = match class.split_first() {
+                        Some(('!', rest)) => (true, rest),
+                        _ => (false, class),
+                    };
+                    regex.push('[');
+                    if negated {
+                        regex.push('^');
+                    }
+                    let mut j = 0;
+                    while j < class.len() {
+                        let escaped = |c: char| regex::escape(&c.to_string());
+                        // Ranges in order keep their dash; anything else,
+                        // a stray dash included, is literal
+                        match class.get(j..j + 3) {
+                            Some(&[low, '-', high]) if low <= high => {
+                                regex.push_str(&format!("{}-{}", escaped(low), escaped(high)));
+                                j += 3;
+                            }
+                            _ => {
+                                regex.push_str(&escaped(class[j]));
+                                j += 1;
+                            }
+                        }
+                    }
+                    regex.push(']');
+                    i += len + 2;
+                    continue;
+                }
+                _ => regex.push_str(r"\["),
+            },
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+
+    regex.push('$');
+    regex
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+
+    /// An empty pattern or an invalid regex matches nothing
+    fn match_pattern(pattern: &str, text: &str, mode: PatternMode) -> bool {
+        !pattern.is_empty() && Pattern::new(pattern, mode).is_ok_and(|p| p.is_match(text))
+    }
 
     #[test]
     fn test_glob_patterns() {
-        assert!(match_pattern("*.txt", "file.txt"));
-        assert!(!match_pattern("*.txt", "file.md"));
-        assert!(match_pattern("file*", "file123"));
-        assert!(match_pattern("*test*", "mytestfile"));
+        let glob = |p, t| match_pattern(p, t, PatternMode::Glob);
+        assert!(glob("*.txt", "file.txt"));
+        assert!(!glob("*.txt", "file.md"));
+        assert!(!glob("*.txt", "file.txt.bak"));
+        assert!(glob("file*", "file123"));
+        assert!(glob("*test*", "mytestfile"));
+        assert!(glob("data?.csv", "data1.csv"));
+        assert!(glob("img[0-9].png", "img7.png"));
+        assert!(!glob("img[!0-9].png", "img7.png"));
+        // Regex syntax is literal in a glob
+        assert!(glob("c++ (v2)*", "c++ (v2) notes"));
+        assert!(!glob("a+", "aaa"));
+        assert!(glob("[", "["));
+        assert!(glob("[]", "[]"));
     }
 
     #[test]
     fn test_regex_patterns() {
-        assert!(match_pattern(r"^\d+$", "123"));
-        assert!(!match_pattern(r"^\d+$", "abc"));
-        assert!(match_pattern(r"test\d+", "test123"));
+        let regex = |p, t| match_pattern(p, t, PatternMode::Regex);
+        assert!(regex(r"^\d+$", "123"));
+        assert!(!regex(r"^\d+$", "abc"));
+        assert!(regex(r"test\d+", "test123"));
+        // An invalid regex matches nothing rather than falling back
+        assert!(!regex("(", "("));
+        assert!(Pattern::new("(", PatternMode::Regex).is_err());
     }
 
     #[test]
-    fn test_substring_matching() {
-        assert!(match_pattern("test", "mytestfile"));
-        assert!(!match_pattern("test", "myfile"));
+    fn test_literal_matching() {
+        let literal = |p, t| match_pattern(p, t, PatternMode::Literal);
+        assert!(literal("test", "mytestfile"));
+        assert!(!literal("test", "myfile"));
+        assert!(literal("*.txt", "a*.txt"));
+        assert!(!literal("*.txt", "a.txt"));
+        assert!(!literal("", "anything"));
+    }
+
+    #[test]
+    fn test_path_globs() {
+        let matches = |glob: &str, text: &str| {
+            Regex::new(&path_glob_to_regex(glob))
+                .unwrap()
+                .is_match(text)
+        };
+        assert!(matches("*.min.js", "app.min.js"));
+        assert!(!matches("src/*.rs", "src/models/sort.rs"));
+        assert!(matches("src/**/*.rs", "src/models/sort.rs"));
+        assert!(matches("**/build", "build"));
+        assert!(matches("file[0-9].txt", "file7.txt"));
+        assert!(!matches("file[!0-9].txt", "file7.txt"));
+        assert!(matches(r"\*.txt", "*.txt"));
+        assert!(!matches(r"\*.txt", "a.txt"));
+        // Class bodies are literal, whatever they mean to the regex crate
+        assert!(matches("[a&&b]", "&"));
+        assert!(matches("[x--y]", "-"));
+        assert!(matches("[~~]", "~"));
+        assert!(matches("[[]x", "[x"));
+    }
+
+    proptest! {
+        #[test]
+        fn prop_literal_matches_substrings(prefix in ".*", needle in ".+", suffix in ".*") {
+            let text = format!("{}{}{}", prefix, needle, suffix);
+            prop_assert!(match_pattern(&needle, &text, PatternMode::Literal));
+        }
+
+        #[test]
+        fn prop_glob_without_wildcards_is_exact(name in "[^*?\\[]+", other in ".+") {
+            prop_assert!(match_pattern(&name, &name, PatternMode::Glob));
+            prop_assert_eq!(match_pattern(&name, &other, PatternMode::Glob), name == other);
+        }
+
+        #[test]
+        fn prop_glob_star_matches_any_affix(stem in "[^*?\\[]*", before in ".*", after in ".*") {
+            let text = format!("{}{}{}", before, stem, after);
+            let glob = format!("*{}*", stem);
+            prop_assert!(match_pattern(&glob, &text, PatternMode::Glob));
+        }
+
+        #[test]
+        fn prop_any_input_compiles_as_glob_or_literal(pattern in ".*", text in ".*") {
+            prop_assert!(Pattern::new(&pattern, PatternMode::Glob).is_ok());
+            prop_assert!(Pattern::new(&pattern, PatternMode::Literal).is_ok());
+            // Never panics, whatever the regex
+            let _ = match_pattern(&pattern, &text, PatternMode::Regex);
+        }
     }
 }
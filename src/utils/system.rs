@@ -1,4 +1,7 @@
-use std::path::Path;
+use anyhow::{Context, Result};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 
 /// Check if the current user is root
 pub fn is_root_user() -> bool {
@@ -12,6 +15,62 @@ pub fn is_root_user() -> bool {
     }
 }
 
+/// The current directory as the shell reached it: `$PWD` when it names the
+/// same directory, keeping the symbolic links on the way
+pub fn logical_current_dir() -> io::Result<PathBuf> {
+    let current = std::env::current_dir()?;
+    let canonical = std::fs::canonicalize(&current).ok();
+    let logical = std::env::var_os("PWD")
+        .map(PathBuf::from)
+        .filter(|pwd| pwd.is_absolute() && std::fs::canonicalize(pwd).ok() == canonical);
+    Ok(logical.unwrap_or(current))
+}
+
+/// Open `path`, creating it, and hold an exclusive lock on it until the
+/// returned file is dropped; other processes taking the lock wait
+pub fn lock_file(path: &Path) -> io::Result<std::fs::File> {
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)?;
+    file.lock()?;
+    Ok(file)
+}
+
+/// Home directory of the current user
+pub fn home_dir() -> Option<PathBuf> {
+    std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .ok()
+        .map(PathBuf::from)
+}
+
+/// `path` with a leading `~` standing for the home directory
+pub fn expand_home(path: &str) -> PathBuf {
+    let rest = match path {
+        "~" => Some(""),
+        _ => path.strip_prefix("~/"),
+    };
+    match (rest, home_dir()) {
+        (Some(""), Some(home)) => home,
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// fsnav's configuration directory (`~/.config/fsnav`), created if missing
+pub fn config_dir() -> Result<PathBuf> {
+    let home = home_dir().context("Failed to get home directory")?;
+    let config_dir = home.join(".config").join("fsnav");
+
+    if !config_dir.exists() {
+        std::fs::create_dir_all(&config_dir)?;
+    }
+
+    Ok(config_dir)
+}
+
 /// Get owner and group information for a file
 pub fn get_owner_group(path: &Path) -> (Option<String>, Option<String>, Option<u32>, Option<u32>) {
     #[cfg(unix)]
@@ -50,3 +109,75 @@ pub fn get_owner_group(path: &Path) -> (Option<String>, Option<String>, Option<u
 
     (None, None, None, None)
 }
+
+/// Whether `path` lies in procfs or sysfs, whose files report sizes that
+/// do not match their contents and may block when read
+pub fn is_virtual_fs(path: &Path) -> bool {
+    path.starts_with("/proc") || path.starts_with("/sys")
+}
+
+/// Resolve the user's preferred editor as a program plus its arguments
+/// Honors `$VISUAL`, then `$EDITOR`, falling back to `vi`
+pub fn editor_command() -> (String, Vec<String>) {
+    let raw = std::env::var("VISUAL")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .or_else(|| std::env::var("EDITOR").ok())
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+
+    let mut parts = raw.split_whitespace().map(str::to_string);
+    let program = parts.next().unwrap_or_else(|| "vi".to_string());
+    (program, parts.collect())
+}
+
+/// Spawn a command fully detached from the TUI (no inherited stdio)
+/// The child is reaped on a background thread so it never lingers as a zombie
+pub fn spawn_detached(mut command: Command) -> io::Result<()> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+
+    Ok(())
+}
+
+/// Open a file with the desktop's default application (`open` on macOS, `xdg-open` elsewhere)
+pub fn open_with_system_handler(path: &Path) -> io::Result<()> {
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+
+    let mut command = Command::new(opener);
+    command.arg(path);
+    spawn_detached(command)
+}
+
+/// Locate an executable named `program` in `$PATH`
+pub fn find_program(program: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(program))
+        .find(|candidate| is_executable(candidate))
+}
+
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata()
+            .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
@@ -0,0 +1,34 @@
+use anyhow::Result;
+use crossterm::{
+    cursor::{Hide, Show},
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use std::io;
+
+/// Temporarily hand the terminal back to the shell.
+///
+/// Leaves the alternate screen and raw mode, runs `f` (typically a child
+/// process that needs a regular terminal, such as an editor), then restores
+/// the TUI state even if `f` fails. Mouse capture, when `mouse_captured`
+/// says it is on, is turned off meanwhile so the child does not receive
+/// mouse escape sequences.
+pub fn with_suspended_tui<T>(mouse_captured: bool, f: impl FnOnce() -> T) -> Result<T> {
+    let mut stdout = io::stdout();
+    if mouse_captured {
+        execute!(stdout, DisableMouseCapture)?;
+    }
+    execute!(stdout, LeaveAlternateScreen, Show)?;
+    terminal::disable_raw_mode()?;
+
+    let result = f();
+
+    terminal::enable_raw_mode()?;
+    execute!(stdout, EnterAlternateScreen, Hide)?;
+    if mouse_captured {
+        execute!(stdout, EnableMouseCapture)?;
+    }
+
+    Ok(result)
+}
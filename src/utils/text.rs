@@ -0,0 +1,52 @@
+/// The first `max_chars` characters of `text`, cut on a character boundary
+pub fn truncate_chars(text: &str, max_chars: usize) -> &str {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+/// `text` in at most `max_chars` characters, ending in `...` when it had
+/// to be cut
+pub fn ellipsize(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    if max_chars < 3 {
+        return ".".repeat(max_chars);
+    }
+    format!("{}...", truncate_chars(text, max_chars - 3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_multibyte_truncation() {
+        assert_eq!(truncate_chars("José Müller", 4), "José");
+        assert_eq!(truncate_chars("日本語", 10), "日本語");
+        assert_eq!(ellipsize("rapport-été-2024.pdf", 10), "rapport...");
+        assert_eq!(ellipsize("été", 3), "été");
+        assert_eq!(ellipsize("éééé", 2), "..");
+    }
+
+    proptest! {
+        #[test]
+        fn prop_truncate_is_a_prefix(text in ".*", max in 0usize..40) {
+            let cut = truncate_chars(&text, max);
+            prop_assert!(text.starts_with(cut));
+            prop_assert_eq!(cut.chars().count(), text.chars().count().min(max));
+        }
+
+        #[test]
+        fn prop_ellipsize_fits(text in ".*", max in 0usize..40) {
+            let shown = ellipsize(&text, max);
+            prop_assert!(shown.chars().count() <= max);
+            if text.chars().count() <= max {
+                prop_assert_eq!(shown, text);
+            }
+        }
+    }
+}
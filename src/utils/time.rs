@@ -0,0 +1,135 @@
+use std::ffi::CString;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// How modification times are displayed
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeFormat {
+    /// `strftime` pattern used for absolute dates
+    pub pattern: String,
+    /// Show "3h ago" style ages instead of absolute dates
+    pub relative: bool,
+}
+
+impl Default for TimeFormat {
+    fn default() -> Self {
+        Self {
+            pattern: DEFAULT_DATE_FORMAT.to_string(),
+            relative: false,
+        }
+    }
+}
+
+impl TimeFormat {
+    pub fn format(&self, time: SystemTime) -> String {
+        if self.relative {
+            format_relative(time, SystemTime::now())
+        } else {
+            format_with_pattern(time, &self.pattern)
+        }
+    }
+}
+
+/// Use the user's locale for date names in `strftime` patterns and for
+/// locale-aware name collation
+pub fn init_locale() {
+    // Safety: called once at startup before any other thread formats dates
+    unsafe {
+        libc::setlocale(libc::LC_TIME, c"".as_ptr());
+        libc::setlocale(libc::LC_COLLATE, c"".as_ptr());
+    }
+}
+
+/// Format a timestamp in local time with a `strftime` pattern
+pub fn format_with_pattern(time: SystemTime, pattern: &str) -> String {
+    let secs = match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs() as libc::time_t,
+        Err(_) => return "-".to_string(),
+    };
+    let Ok(pattern) = CString::new(pattern) else {
+        return "-".to_string();
+    };
+
+    let mut buffer = [0u8; 128];
+    // Safety: localtime_r only writes into the provided tm struct and strftime
+    // never writes more than the buffer length
+    let written = unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        if libc::localtime_r(&secs, &mut tm).is_null() {
+            return "-".to_string();
+        }
+        libc::strftime(
+            buffer.as_mut_ptr() as *mut libc::c_char,
+            buffer.len(),
+            pattern.as_ptr(),
+            &tm,
+        )
+    };
+
+    String::from_utf8_lossy(&buffer[..written]).into_owned()
+}
+
+/// Age of `time` relative to `now`, e.g. "3h ago" or "in 2d"
+pub fn format_relative(time: SystemTime, now: SystemTime) -> String {
+    let (age, future) = match now.duration_since(time) {
+        Ok(age) => (age, false),
+        Err(e) => (e.duration(), true),
+    };
+
+    if age < Duration::from_secs(10) {
+        return "just now".to_string();
+    }
+
+    let secs = age.as_secs();
+    let amount = match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3_599 => format!("{}m", secs / 60),
+        3_600..=86_399 => format!("{}h", secs / 3_600),
+        86_400..=2_591_999 => format!("{}d", secs / 86_400),
+        2_592_000..=31_535_999 => format!("{}mo", secs / 2_592_000),
+        _ => format!("{}y", secs / 31_536_000),
+    };
+
+    if future {
+        format!("in {}", amount)
+    } else {
+        format!("{} ago", amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_relative() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        let ago = |secs| format_relative(now - Duration::from_secs(secs), now);
+
+        assert_eq!(ago(3), "just now");
+        assert_eq!(ago(42), "42s ago");
+        assert_eq!(ago(5 * 60), "5m ago");
+        assert_eq!(ago(3 * 3_600 + 59), "3h ago");
+        assert_eq!(ago(2 * 86_400), "2d ago");
+        assert_eq!(ago(90 * 86_400), "3mo ago");
+        assert_eq!(ago(800 * 86_400), "2y ago");
+        assert_eq!(
+            format_relative(now + Duration::from_secs(7_200), now),
+            "in 2h"
+        );
+    }
+
+    #[test]
+    fn test_format_with_pattern() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        // Only fields that do not depend on the local timezone
+        assert_eq!(format_with_pattern(time, "%%literal"), "%literal");
+        assert_eq!(format_with_pattern(time, "%Y").len(), 4);
+        assert_eq!(format_with_pattern(time, ""), "");
+        assert_eq!(
+            format_with_pattern(time, DEFAULT_DATE_FORMAT).len(),
+            "2001-09-09 01:46".len()
+        );
+    }
+}
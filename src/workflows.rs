@@ -0,0 +1,500 @@
+use anyhow::Result;
+use crossterm::{
+    cursor::MoveTo,
+    event::{KeyCode, KeyModifiers},
+    execute,
+    style::{Color, Print, ResetColor},
+    terminal,
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use crate::ui::{SetBackgroundColor, SetForegroundColor};
+use crate::utils::{config_dir, truncate_chars, PatternMode};
+
+/// One recorded operation, replayed on whatever directory is open
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "step", rename_all = "snake_case")]
+pub enum Step {
+    /// Select the entries matching a pattern, as the `p` prompt does
+    Select { pattern: String, mode: PatternMode },
+    /// Mode such as "644" or "go-w" for the selection, or for its
+    /// directories when files get their own mode
+    Chmod {
+        mode: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        files: Option<String>,
+        #[serde(default)]
+        recursive: bool,
+    },
+    /// chown owner argument: "user:group", "user" or ":group"
+    Chown { owner: String, recursive: bool },
+    /// A custom command, kept as its template so later edits to
+    /// `config.toml` do not change the workflow
+    Command { name: String, command: String },
+}
+
+impl Step {
+    pub fn describe(&self) -> String {
+        match self {
+            Step::Select { pattern, mode } => {
+                format!("Select {} '{}'", mode.label(), pattern)
+            }
+            Step::Chmod {
+                mode,
+                files,
+                recursive,
+            } => format!(
+                "chmod {}{}{}",
+                if *recursive { "-R " } else { "" },
+                mode,
+                files
+                    .as_ref()
+                    .map(|files| format!(" (files {})", files))
+                    .unwrap_or_default()
+            ),
+            Step::Chown { owner, recursive } => {
+                format!("chown {}{}", if *recursive { "-R " } else { "" }, owner)
+            }
+            Step::Command { name, command } => format!("Run {}: {}", name, command),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Workflow {
+    pub name: String,
+    pub steps: Vec<Step>,
+}
+
+/// Workflows saved in `workflows.json`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Workflows {
+    #[serde(default)]
+    workflows: Vec<Workflow>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl Workflows {
+    pub fn load() -> Self {
+        let Ok(dir) = config_dir() else {
+            return Self::default();
+        };
+        Self::load_from(dir.join("workflows.json"))
+    }
+
+    fn load_from(path: PathBuf) -> Self {
+        let mut workflows: Self = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        workflows.path = path;
+        workflows
+    }
+
+    pub fn list(&self) -> &[Workflow] {
+        &self.workflows
+    }
+
+    /// Save `workflow`, replacing one with the same name
+    pub fn add(&mut self, workflow: Workflow) -> Result<()> {
+        match self.workflows.iter_mut().find(|w| w.name == workflow.name) {
+            Some(existing) => *existing = workflow,
+            None => self.workflows.push(workflow),
+        }
+        self.save()
+    }
+
+    pub fn remove(&mut self, index: usize) -> Result<()> {
+        if index < self.workflows.len() {
+            self.workflows.remove(index);
+        }
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        if self.path.as_os_str().is_empty() {
+            return Ok(());
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&self.path, json)?;
+        Ok(())
+    }
+}
+
+pub enum WorkflowOutcome {
+    Continue,
+    /// Close the view, with a message for the status bar
+    Close(Option<String>),
+    /// Run this step; the view has moved on to the next one
+    Run(Step),
+}
+
+enum Stage {
+    /// Naming the steps just recorded
+    Naming { name: String, steps: Vec<Step> },
+    /// Choosing a saved workflow
+    Picking { selected: usize },
+    /// Replaying `workflow`, waiting to confirm step `step`
+    Confirming { workflow: Workflow, step: usize },
+}
+
+/// Popup over the listing that saves, lists and replays workflows
+pub struct WorkflowView {
+    stage: Stage,
+}
+
+impl WorkflowView {
+    pub fn naming(steps: Vec<Step>) -> Self {
+        Self {
+            stage: Stage::Naming {
+                name: String::new(),
+                steps,
+            },
+        }
+    }
+
+    pub fn picking() -> Self {
+        Self {
+            stage: Stage::Picking { selected: 0 },
+        }
+    }
+
+    /// Every step has been confirmed or skipped
+    pub fn is_finished(&self) -> bool {
+        matches!(&self.stage, Stage::Confirming { workflow, step } if *step >= workflow.steps.len())
+    }
+
+    /// Name of the workflow being replayed
+    pub fn running(&self) -> Option<&str> {
+        match &self.stage {
+            Stage::Confirming { workflow, .. } => Some(&workflow.name),
+            _ => None,
+        }
+    }
+
+    pub fn handle_input(
+        &mut self,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+        workflows: &mut Workflows,
+    ) -> WorkflowOutcome {
+        match &mut self.stage {
+            Stage::Naming { name, steps } => match code {
+                KeyCode::Enter if !name.trim().is_empty() => {
+                    let workflow = Workflow {
+                        name: name.trim().to_string(),
+                        steps: std::mem::take(steps),
+                    };
+                    let message = format!(
+                        "Saved workflow '{}' ({} steps)",
+                        workflow.name,
+                        workflow.steps.len()
+                    );
+                    return WorkflowOutcome::Close(Some(match workflows.add(workflow) {
+                        Ok(()) => message,
+                        Err(e) => format!("Failed to save workflow: {}", e),
+                    }));
+                }
+                KeyCode::Esc => {
+                    return WorkflowOutcome::Close(Some("Recording discarded".to_string()));
+                }
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                KeyCode::Char(c) => name.push(c),
+                _ => {}
+            },
+            Stage::Picking { selected } => match code {
+                KeyCode::Up => *selected = selected.saturating_sub(1),
+                KeyCode::Down if *selected + 1 < workflows.list().len() => *selected += 1,
+                KeyCode::Enter => {
+                    if let Some(workflow) = workflows.list().get(*selected) {
+                        self.stage = Stage::Confirming {
+                            workflow: workflow.clone(),
+                            step: 0,
+                        };
+                    }
+                }
+                KeyCode::Char('d') if modifiers.contains(KeyModifiers::CONTROL) => {
+                    let name = workflows.list().get(*selected).map(|w| w.name.clone());
+                    if let Some(name) = name {
+                        if let Err(e) = workflows.remove(*selected) {
+                            return WorkflowOutcome::Close(Some(format!(
+                                "Failed to delete workflow: {}",
+                                e
+                            )));
+                        }
+                        *selected = (*selected).min(workflows.list().len().saturating_sub(1));
+                        if workflows.list().is_empty() {
+                            return WorkflowOutcome::Close(Some(format!(
+                                "Deleted workflow '{}'",
+                                name
+                            )));
+                        }
+                    }
+                }
+                KeyCode::Esc => return WorkflowOutcome::Close(None),
+                _ => {}
+            },
+            Stage::Confirming { workflow, step } => match code {
+                KeyCode::Enter | KeyCode::Char('y') => {
+                    if let Some(current) = workflow.steps.get(*step).cloned() {
+                        *step += 1;
+                        return WorkflowOutcome::Run(current);
+                    }
+                }
+                KeyCode::Char('s') | KeyCode::Char('n') => {
+                    *step += 1;
+                    if *step >= workflow.steps.len() {
+                        return WorkflowOutcome::Close(Some(format!(
+                            "Workflow '{}' finished",
+                            workflow.name
+                        )));
+                    }
+                }
+                KeyCode::Esc | KeyCode::Char('q') => {
+                    return WorkflowOutcome::Close(Some(format!(
+                        "Workflow '{}' stopped before step {}",
+                        workflow.name,
+                        *step + 1
+                    )));
+                }
+                _ => {}
+            },
+        }
+        WorkflowOutcome::Continue
+    }
+
+    pub fn render(&self, workflows: &Workflows) -> Result<()> {
+        let mut stdout = io::stdout();
+        let (terminal_width, terminal_height) = terminal::size()?;
+
+        let width = 64.min(terminal_width.saturating_sub(4)).max(24);
+        let height = 16.min(terminal_height.saturating_sub(2)).max(8);
+        let x = (terminal_width.saturating_sub(width)) / 2;
+        let y = (terminal_height.saturating_sub(height)) / 3;
+
+        for row in y..y + height {
+            execute!(stdout, MoveTo(x, row), Print(" ".repeat(width as usize)))?;
+        }
+
+        let title = match &self.stage {
+            Stage::Naming { .. } => "Save recorded workflow".to_string(),
+            Stage::Picking { .. } => "Workflows".to_string(),
+            Stage::Confirming { workflow, .. } => format!("Workflow: {}", workflow.name),
+        };
+        crate::ui::draw_box(&mut stdout, x, y, width, height, Some(&title), Color::Cyan)?;
+
+        let inner_width = width.saturating_sub(4) as usize;
+        let list_height = height.saturating_sub(5) as usize;
+        let (header, rows, highlighted, footer): (String, Vec<String>, Option<usize>, &str) =
+            match &self.stage {
+                Stage::Naming { name, steps } => (
+                    format!("Name: {}_", name),
+                    numbered(steps),
+                    None,
+                    " Enter: Save | Esc: Discard",
+                ),
+                Stage::Picking { selected } => (
+                    if workflows.list().is_empty() {
+                        "No workflows saved".to_string()
+                    } else {
+                        "Replay on the current directory:".to_string()
+                    },
+                    workflows
+                        .list()
+                        .iter()
+                        .map(|w| {
+                            let steps: Vec<String> = w.steps.iter().map(Step::describe).collect();
+                            format!("{}: {}", w.name, steps.join(" → "))
+                        })
+                        .collect(),
+                    Some(*selected),
+                    " ↑↓: Select | Enter: Replay | Ctrl+D: Delete | Esc: Close",
+                ),
+                Stage::Confirming { workflow, step } => (
+                    format!("Step {} of {}: run it?", step + 1, workflow.steps.len()),
+                    numbered(&workflow.steps),
+                    Some(*step),
+                    " Enter/y: Run step | s: Skip | Esc: Stop",
+                ),
+            };
+
+        execute!(
+            stdout,
+            MoveTo(x + 2, y + 1),
+            SetForegroundColor(Color::Yellow),
+            Print(truncate_chars(&header, inner_width)),
+            ResetColor
+        )?;
+
+        let start = highlighted
+            .unwrap_or(0)
+            .saturating_sub(list_height.saturating_sub(1));
+        for (i, row) in rows.iter().enumerate().skip(start).take(list_height) {
+            let is_highlighted = highlighted == Some(i);
+            execute!(
+                stdout,
+                MoveTo(x + 2, y + 3 + (i - start) as u16),
+                if is_highlighted {
+                    SetBackgroundColor(Color::DarkGreen)
+                } else {
+                    SetBackgroundColor(Color::Reset)
+                },
+                SetForegroundColor(if is_highlighted {
+                    Color::White
+                } else {
+                    Color::Grey
+                }),
+                Print(format!(
+                    "{:<width$}",
+                    truncate_chars(row, inner_width),
+                    width = inner_width
+                )),
+                ResetColor
+            )?;
+        }
+
+        execute!(
+            stdout,
+            MoveTo(x + 2, y + height - 2),
+            SetForegroundColor(Color::DarkGrey),
+            Print(truncate_chars(footer, inner_width)),
+            ResetColor
+        )?;
+
+        stdout.flush()?;
+        Ok(())
+    }
+}
+
+fn numbered(steps: &[Step]) -> Vec<String> {
+    steps
+        .iter()
+        .enumerate()
+        .map(|(i, step)| format!("{}. {}", i + 1, step.describe()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn steps() -> Vec<Step> {
+        vec![
+            Step::Select {
+                pattern: "*.html".to_string(),
+                mode: PatternMode::Glob,
+            },
+            Step::Chmod {
+                mode: "644".to_string(),
+                files: None,
+                recursive: false,
+            },
+            Step::Chown {
+                owner: "www-data:www-data".to_string(),
+                recursive: false,
+            },
+            Step::Command {
+                name: "archive".to_string(),
+                command: "tar czf site.tar.gz %s".to_string(),
+            },
+        ]
+    }
+
+    fn press(view: &mut WorkflowView, workflows: &mut Workflows, code: KeyCode) -> WorkflowOutcome {
+        view.handle_input(code, KeyModifiers::NONE, workflows)
+    }
+
+    #[test]
+    fn test_record_and_persist() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("workflows.json");
+        let mut workflows = Workflows::load_from(path.clone());
+
+        let mut view = WorkflowView::naming(steps());
+        // A name is required
+        assert!(matches!(
+            press(&mut view, &mut workflows, KeyCode::Enter),
+            WorkflowOutcome::Continue
+        ));
+        for c in "deploy".chars() {
+            press(&mut view, &mut workflows, KeyCode::Char(c));
+        }
+        assert!(matches!(
+            press(&mut view, &mut workflows, KeyCode::Enter),
+            WorkflowOutcome::Close(Some(_))
+        ));
+
+        let reloaded = Workflows::load_from(path.clone());
+        assert_eq!(
+            reloaded.list(),
+            [Workflow {
+                name: "deploy".to_string(),
+                steps: steps(),
+            }]
+        );
+        let json = fs::read_to_string(&path).unwrap();
+        assert!(json.contains(r#""step": "chmod""#));
+        assert!(json.contains(r#""mode": "glob""#));
+
+        // The same name replaces the workflow
+        workflows
+            .add(Workflow {
+                name: "deploy".to_string(),
+                steps: vec![],
+            })
+            .unwrap();
+        assert_eq!(workflows.list().len(), 1);
+        assert!(workflows.list()[0].steps.is_empty());
+    }
+
+    #[test]
+    fn test_replay_confirms_each_step() {
+        let mut workflows = Workflows::default();
+        workflows
+            .add(Workflow {
+                name: "deploy".to_string(),
+                steps: steps(),
+            })
+            .unwrap();
+
+        let mut view = WorkflowView::picking();
+        press(&mut view, &mut workflows, KeyCode::Enter);
+        assert_eq!(view.running(), Some("deploy"));
+
+        // Run, skip, run, then stop before the archive
+        assert!(matches!(
+            press(&mut view, &mut workflows, KeyCode::Enter),
+            WorkflowOutcome::Run(Step::Select { .. })
+        ));
+        assert!(matches!(
+            press(&mut view, &mut workflows, KeyCode::Char('s')),
+            WorkflowOutcome::Continue
+        ));
+        assert!(matches!(
+            press(&mut view, &mut workflows, KeyCode::Char('y')),
+            WorkflowOutcome::Run(Step::Chown { .. })
+        ));
+        assert!(!view.is_finished());
+        assert!(matches!(
+            press(&mut view, &mut workflows, KeyCode::Esc),
+            WorkflowOutcome::Close(Some(message)) if message.contains("before step 4")
+        ));
+
+        // Confirming the last step finishes the replay
+        let mut view = WorkflowView::picking();
+        press(&mut view, &mut workflows, KeyCode::Enter);
+        for _ in 0..4 {
+            assert!(matches!(
+                press(&mut view, &mut workflows, KeyCode::Enter),
+                WorkflowOutcome::Run(_)
+            ));
+        }
+        assert!(view.is_finished());
+    }
+}